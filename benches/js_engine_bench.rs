@@ -0,0 +1,45 @@
+//! Benchmarks for `JsEngine` setup.
+//!
+//! Compares a fresh `JsEngine::new()` + `inject_minimal_dom()` per page
+//! against reusing one engine with `reset()` between pages, as a batch of
+//! SPA extractions would.
+//!
+//! Run with: `cargo bench --bench js_engine_bench`
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use nab::JsEngine;
+
+/// Stand-in for a page's inline bootstrap script.
+const PAGE_SCRIPT: &str = r"
+    window.location.href = 'https://example.com/products';
+    window.__INITIAL_STATE__ = { items: [1, 2, 3], user: { name: 'ada' } };
+    var app = document.createElement('div');
+    document._elements['app'] = app;
+    JSON.stringify(window.__INITIAL_STATE__);
+";
+
+fn bench_engine_setup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("js_engine_setup");
+
+    group.bench_function("fresh_engine_per_page", |b| {
+        b.iter(|| {
+            let engine = JsEngine::new().unwrap();
+            engine.inject_minimal_dom().unwrap();
+            black_box(engine.eval(PAGE_SCRIPT).unwrap())
+        });
+    });
+
+    let engine = JsEngine::new().unwrap();
+    engine.inject_minimal_dom().unwrap();
+    group.bench_function("reset_pooled_engine", |b| {
+        b.iter(|| {
+            engine.reset().unwrap();
+            black_box(engine.eval(PAGE_SCRIPT).unwrap())
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_engine_setup);
+criterion_main!(benches);
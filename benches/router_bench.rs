@@ -251,6 +251,56 @@ fn bench_all_providers_miss(c: &mut Criterion) {
     group.finish();
 }
 
+// ---------------------------------------------------------------------------
+// Host prefilter: SiteRouter lookup vs scanning every provider's `matches`
+// ---------------------------------------------------------------------------
+
+fn bench_router_prefilter(c: &mut Criterion) {
+    let mut group = c.benchmark_group("site_router_lookup");
+    let router = SiteRouter::new();
+
+    let mixed_urls: Vec<&str> = REDDIT_URLS
+        .iter()
+        .chain(GITHUB_URLS.iter())
+        .chain(YOUTUBE_URLS.iter())
+        .chain(NON_MATCHING_URLS.iter())
+        .chain(EDGE_CASE_URLS.iter())
+        .copied()
+        .collect();
+
+    // Baseline: every provider's full `matches` until the first hit
+    let providers: Vec<Box<dyn SiteProvider>> = vec![
+        Box::new(RedditProvider),
+        Box::new(HackerNewsProvider),
+        Box::new(GitHubProvider),
+        Box::new(InstagramProvider),
+        Box::new(YouTubeProvider),
+    ];
+    group.bench_function("full_scan", |b| {
+        b.iter(|| {
+            for url in &mixed_urls {
+                black_box(
+                    providers
+                        .iter()
+                        .find(|p| p.matches(black_box(url)))
+                        .is_some(),
+                );
+            }
+        });
+    });
+
+    // Router: host extracted once, `matches` only for plausible providers
+    group.bench_function("host_prefilter", |b| {
+        b.iter(|| {
+            for url in &mixed_urls {
+                black_box(router.provider_for(black_box(url)).is_some());
+            }
+        });
+    });
+
+    group.finish();
+}
+
 // ---------------------------------------------------------------------------
 // Router construction benchmarks
 // ---------------------------------------------------------------------------
@@ -337,6 +387,7 @@ criterion_group!(
     bench_instagram_match,
     bench_youtube_match,
     bench_all_providers_miss,
+    bench_router_prefilter,
     bench_content_router_creation,
     bench_site_router_creation,
     bench_content_dispatch_overhead,
@@ -0,0 +1,21 @@
+#!/bin/sh
+# Refresh the Public Suffix List that src/cookie_jar.rs bundles to refuse
+# cookies scoped to a public suffix (`co.uk`, `github.io`).
+#
+# Run before each release and commit data/public_suffix_list.dat on its own;
+# the first line of the file records when it was fetched.
+set -eu
+
+url=https://publicsuffix.org/list/public_suffix_list.dat
+list="$(dirname "$0")/../data/public_suffix_list.dat"
+tmp=$(mktemp)
+trap 'rm -f "$tmp"' EXIT
+
+curl -fsSL "$url" -o "$tmp"
+grep -q '===BEGIN ICANN DOMAINS===' "$tmp"
+{
+    printf '// nab: fetched %s from %s\n' "$(date -u +%Y-%m-%d)" "$url"
+    printf '// Refresh with scripts/update-public-suffix-list.sh\n\n'
+    cat "$tmp"
+} >"$list"
+echo "Updated $list"
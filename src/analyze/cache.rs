@@ -0,0 +1,281 @@
+//! Per-stage result cache
+//!
+//! Re-running `analyze` on the same video reuses stages that already
+//! completed. Results are keyed by the video (its size, modification time and
+//! a SHA-256 of its first and last MiB) and a hash of the settings each stage
+//! depends on, so changing e.g. the Whisper model only recomputes the
+//! transcript:
+//!
+//! ```text
+//! <root>/<video key>/<stage>-<settings hash>/result.json
+//! ```
+//!
+//! `result.json` is written last, so a stage interrupted mid-way is simply
+//! recomputed on the next run. Each video's entries are evicted as a whole,
+//! least recently used first, once the root outgrows its size cap.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::future::Future;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::Result;
+
+const RESULT_FILE: &str = "result.json";
+/// Touched whenever a video's entries are opened; its mtime orders eviction
+const USED_FILE: &str = "last-used";
+/// Bytes hashed from each end of the video
+const SAMPLE_LEN: u64 = 1 << 20;
+
+/// Default cache size cap (10 GiB)
+pub const DEFAULT_MAX_CACHE_BYTES: u64 = 10 << 30;
+
+/// Default cache root (`<cache dir>/nab/analyze`)
+#[must_use]
+pub fn default_cache_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("nab")
+        .join("analyze")
+}
+
+/// Cached stage results for one video
+#[derive(Debug, Clone)]
+pub struct StageCache {
+    dir: PathBuf,
+}
+
+impl StageCache {
+    /// Open the cache for `video` under `root`, marking it as recently used
+    pub async fn open(root: &Path, video: &Path) -> Result<Self> {
+        let video = video.to_path_buf();
+        let key = tokio::task::spawn_blocking(move || video_key(&video))
+            .await
+            .map_err(std::io::Error::other)??;
+
+        let dir = root.join(key);
+        tokio::fs::create_dir_all(&dir).await?;
+        tokio::fs::write(dir.join(USED_FILE), b"").await?;
+        Ok(Self { dir })
+    }
+
+    /// Evict other videos' entries, least recently used first, until the
+    /// cache root holds at most `max_bytes`
+    pub async fn prune(&self, max_bytes: u64) -> Result<()> {
+        let keep = self.dir.clone();
+        tokio::task::spawn_blocking(move || prune_root(&keep, max_bytes))
+            .await
+            .map_err(std::io::Error::other)??;
+        Ok(())
+    }
+
+    /// Directory holding a stage's result and any files it produced
+    pub fn stage_dir(&self, stage: &str, settings: &impl Serialize) -> Result<PathBuf> {
+        let digest = Sha256::digest(serde_json::to_vec(settings)?);
+        let key: String = format!("{digest:x}").chars().take(16).collect();
+        Ok(self.dir.join(format!("{stage}-{key}")))
+    }
+
+    /// Previously stored result, if any
+    pub async fn load<T: DeserializeOwned>(
+        &self,
+        stage: &str,
+        settings: &impl Serialize,
+    ) -> Result<Option<T>> {
+        let path = self.stage_dir(stage, settings)?.join(RESULT_FILE);
+        match tokio::fs::read(&path).await {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)
+                .inspect_err(|e| tracing::warn!("Ignoring corrupt cache entry {path:?}: {e}"))
+                .ok()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Store a stage result, replacing any previous one
+    pub async fn store<T: Serialize>(
+        &self,
+        stage: &str,
+        settings: &impl Serialize,
+        value: &T,
+    ) -> Result<()> {
+        let dir = self.stage_dir(stage, settings)?;
+        tokio::fs::create_dir_all(&dir).await?;
+        let partial = dir.join(format!("{RESULT_FILE}.{}.part", std::process::id()));
+        tokio::fs::write(&partial, serde_json::to_vec(value)?).await?;
+        tokio::fs::rename(&partial, dir.join(RESULT_FILE)).await?;
+        Ok(())
+    }
+}
+
+/// Identify a video without reading all of it: its size, modification time
+/// and the first and last [`SAMPLE_LEN`] bytes
+fn video_key(path: &Path) -> std::io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let metadata = file.metadata()?;
+    let len = metadata.len();
+    let modified = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |since| since.as_nanos());
+
+    let mut hasher = Sha256::new();
+    hasher.update(len.to_le_bytes());
+    hasher.update(modified.to_le_bytes());
+    std::io::copy(&mut (&mut file).take(SAMPLE_LEN), &mut hasher)?;
+    if len > SAMPLE_LEN {
+        file.seek(SeekFrom::Start(
+            len.saturating_sub(SAMPLE_LEN).max(SAMPLE_LEN),
+        ))?;
+        std::io::copy(&mut file.take(SAMPLE_LEN), &mut hasher)?;
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Remove video directories next to `keep`, oldest use first, while the
+/// total exceeds `max_bytes`
+fn prune_root(keep: &Path, max_bytes: u64) -> std::io::Result<()> {
+    let Some(root) = keep.parent() else {
+        return Ok(());
+    };
+    let mut videos = Vec::new();
+    for entry in fs::read_dir(root)? {
+        let path = entry?.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let used = fs::metadata(path.join(USED_FILE))
+            .and_then(|metadata| metadata.modified())
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        videos.push((used, dir_size(&path), path));
+    }
+    videos.sort_by_key(|(used, _, _)| *used);
+
+    let mut total: u64 = videos.iter().map(|(_, size, _)| size).sum();
+    for (_, size, path) in videos {
+        if total <= max_bytes {
+            break;
+        }
+        if path == keep {
+            continue;
+        }
+        tracing::info!("Evicting cached analysis {}", path.display());
+        fs::remove_dir_all(&path)?;
+        total -= size;
+    }
+    Ok(())
+}
+
+/// Total size of the files under `path`; unreadable entries count as empty
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => dir_size(&entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// Reuse a cached stage result, or compute and store it
+pub(crate) async fn cached<T, Fut>(
+    cache: Option<&StageCache>,
+    stage: &str,
+    settings: &impl Serialize,
+    compute: impl FnOnce() -> Fut,
+) -> Result<T>
+where
+    T: Serialize + DeserializeOwned,
+    Fut: Future<Output = Result<T>>,
+{
+    let Some(cache) = cache else {
+        return compute().await;
+    };
+    if let Some(value) = cache.load(stage, settings).await? {
+        tracing::info!("Reusing cached {stage}");
+        return Ok(value);
+    }
+    let value = compute().await?;
+    cache.store(stage, settings, &value).await?;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn evicts_least_recently_used_videos_over_the_cap() {
+        let root = std::env::temp_dir().join(format!("nab_stage_cache_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&root).unwrap();
+        let video = |name: &str| {
+            let path = root.join(name);
+            fs::write(&path, name).unwrap();
+            path
+        };
+        let (a, b, c) = (video("a.mp4"), video("b.mp4"), video("c.mp4"));
+
+        let mut caches = Vec::new();
+        for (i, path) in [&a, &b, &c].into_iter().enumerate() {
+            let cache = StageCache::open(&root.join("cache"), path).await.unwrap();
+            cache.store("stage", &(), &"x".repeat(1000)).await.unwrap();
+            // Oldest first, without relying on the clock's resolution
+            let used = UNIX_EPOCH + std::time::Duration::from_secs(1_000 * (i as u64 + 1));
+            fs::File::options()
+                .write(true)
+                .open(cache.dir.join(USED_FILE))
+                .unwrap()
+                .set_modified(used)
+                .unwrap();
+            caches.push(cache);
+        }
+        // Touch `a` again: `b` is now the least recently used
+        let a_cache = StageCache::open(&root.join("cache"), &a).await.unwrap();
+        assert_eq!(a_cache.dir, caches[0].dir);
+
+        a_cache.prune(2_500).await.unwrap();
+        assert!(caches[0].dir.is_dir());
+        assert!(!caches[1].dir.exists());
+        assert!(caches[2].dir.is_dir());
+
+        // The open video is kept even when it alone is over the cap
+        a_cache.prune(0).await.unwrap();
+        assert!(a_cache.dir.is_dir());
+        assert!(!caches[2].dir.exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn video_key_changes_with_the_contents() {
+        let path = std::env::temp_dir().join(format!("nab_video_key_{}", uuid::Uuid::new_v4()));
+        let big = vec![7u8; usize::try_from(SAMPLE_LEN * 3).unwrap()];
+        fs::write(&path, &big).unwrap();
+        let key = video_key(&path).unwrap();
+        assert_eq!(video_key(&path).unwrap(), key);
+
+        // Same size, last byte changed
+        let mut changed = big;
+        *changed.last_mut().unwrap() = 8;
+        fs::write(&path, &changed).unwrap();
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        assert_ne!(video_key(&path).unwrap(), key);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -8,6 +8,7 @@ use std::path::Path;
 use std::process::Stdio;
 use tokio::process::Command;
 
+use super::remote::{RemoteStage, RemoteWorker};
 use super::{AnalysisError, Result};
 
 /// Speaker segment identifying who speaks when
@@ -22,21 +23,30 @@ pub struct SpeakerSegment {
 
 /// Speaker diarization engine using pyannote
 pub struct Diarizer {
-    dgx_host: Option<String>,
+    remote: Option<RemoteWorker>,
 }
 
 impl Diarizer {
-    pub fn new(dgx_host: Option<String>) -> Result<Self> {
-        Ok(Self { dgx_host })
+    pub fn new(remote: Option<RemoteWorker>) -> Result<Self> {
+        Ok(Self { remote })
     }
 
     /// Perform speaker diarization on audio file
     pub async fn diarize(&self, audio_path: &Path) -> Result<Vec<SpeakerSegment>> {
-        if let Some(host) = &self.dgx_host {
-            self.diarize_remote(audio_path, host).await
-        } else {
-            self.diarize_local(audio_path).await
+        if let Some(remote) = self
+            .remote
+            .as_ref()
+            .filter(|r| r.handles(RemoteStage::Diarize))
+        {
+            match remote.diarize(audio_path).await {
+                Ok(segments) => return Ok(segments),
+                Err(e) if remote.falls_back() => {
+                    tracing::warn!("Remote diarization failed, falling back to local: {e}");
+                }
+                Err(e) => return Err(e),
+            }
         }
+        self.diarize_local(audio_path).await
     }
 
     /// Local diarization using pyannote
@@ -96,79 +106,6 @@ print(json.dumps(segments))
         Ok(segments)
     }
 
-    /// Remote diarization on DGX Spark
-    async fn diarize_remote(&self, audio_path: &Path, host: &str) -> Result<Vec<SpeakerSegment>> {
-        let remote_path = format!("/tmp/nab_diarize_{}.wav", std::process::id());
-
-        // Copy audio to DGX
-        let scp_status = Command::new("scp")
-            .args([
-                audio_path.to_str().unwrap(),
-                &format!("{host}:{remote_path}"),
-            ])
-            .status()
-            .await?;
-
-        if !scp_status.success() {
-            return Err(AnalysisError::Diarization(
-                "Failed to copy audio to DGX".to_string(),
-            ));
-        }
-
-        // Run pyannote on DGX with GPU
-        let script = format!(
-            r#"
-import json
-import os
-import torch
-from pyannote.audio import Pipeline
-
-pipeline = Pipeline.from_pretrained(
-    "pyannote/speaker-diarization-3.1",
-    use_auth_token=os.environ.get("HF_TOKEN")
-)
-
-# Move to GPU
-pipeline.to(torch.device("cuda"))
-
-diarization = pipeline("{remote_path}")
-
-segments = []
-for turn, _, speaker in diarization.itertracks(yield_label=True):
-    segments.append({{
-        "speaker": speaker,
-        "start": turn.start,
-        "end": turn.end,
-    }})
-
-print(json.dumps(segments))
-"#
-        );
-
-        let output = Command::new("ssh")
-            .args([host, "python3", "-c", &format!("'{script}'")])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .await?;
-
-        // Clean up
-        let _ = Command::new("ssh")
-            .args([host, "rm", "-f", &remote_path])
-            .status()
-            .await;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(AnalysisError::Diarization(format!(
-                "Remote diarization failed: {stderr}"
-            )));
-        }
-
-        let segments: Vec<SpeakerSegment> = serde_json::from_slice(&output.stdout)?;
-        Ok(segments)
-    }
-
     /// Diarize with known number of speakers
     pub async fn diarize_with_speakers(
         &self,
@@ -27,6 +27,8 @@ pub struct ExtractedFrame {
 pub struct FrameExtractor {
     scene_threshold: f32,
     max_frames: usize,
+    ffmpeg_path: String,
+    ffprobe_path: String,
 }
 
 impl FrameExtractor {
@@ -35,9 +37,19 @@ impl FrameExtractor {
         Self {
             scene_threshold,
             max_frames,
+            ffmpeg_path: "ffmpeg".to_string(),
+            ffprobe_path: "ffprobe".to_string(),
         }
     }
 
+    /// Use specific ffmpeg/ffprobe executables instead of PATH lookup
+    #[must_use]
+    pub fn with_tools(mut self, ffmpeg_path: &str, ffprobe_path: &str) -> Self {
+        self.ffmpeg_path = ffmpeg_path.to_string();
+        self.ffprobe_path = ffprobe_path.to_string();
+        self
+    }
+
     /// Extract keyframes from video using scene detection
     pub async fn extract(
         &self,
@@ -51,7 +63,7 @@ impl FrameExtractor {
         // select='gt(scene,threshold)' filters for scene changes
         let output_pattern = output_dir.join("frame_%04d.jpg");
 
-        let status = Command::new("ffmpeg")
+        let status = Command::new(&self.ffmpeg_path)
             .args([
                 "-i",
                 video_path.to_str().ok_or_else(|| {
@@ -103,7 +115,7 @@ impl FrameExtractor {
 
     /// Get video metadata using ffprobe
     async fn get_metadata(&self, video_path: &Path) -> Result<VideoMetadata> {
-        let output = Command::new("ffprobe")
+        let output = Command::new(&self.ffprobe_path)
             .args([
                 "-v",
                 "quiet",
@@ -212,7 +224,7 @@ impl FrameExtractor {
         timestamp: f64,
         output_path: &Path,
     ) -> Result<ExtractedFrame> {
-        let status = Command::new("ffmpeg")
+        let status = Command::new(&self.ffmpeg_path)
             .args([
                 "-ss",
                 &format!("{timestamp:.3}"),
@@ -254,17 +266,28 @@ impl FrameExtractor {
 }
 
 /// Audio extractor
-pub struct AudioExtractor;
+pub struct AudioExtractor {
+    ffmpeg_path: String,
+}
 
 impl AudioExtractor {
     #[must_use]
     pub fn new() -> Self {
-        Self
+        Self {
+            ffmpeg_path: "ffmpeg".to_string(),
+        }
+    }
+
+    /// Use a specific ffmpeg executable instead of PATH lookup
+    #[must_use]
+    pub fn with_ffmpeg(mut self, ffmpeg_path: &str) -> Self {
+        self.ffmpeg_path = ffmpeg_path.to_string();
+        self
     }
 
     /// Extract audio track as WAV (16kHz mono for Whisper)
     pub async fn extract(&self, video_path: &Path, output_path: &Path) -> Result<()> {
-        let status = Command::new("ffmpeg")
+        let status = Command::new(&self.ffmpeg_path)
             .args([
                 "-i",
                 video_path.to_str().ok_or_else(|| {
@@ -308,7 +331,7 @@ impl AudioExtractor {
     ) -> Result<()> {
         let duration = end - start;
 
-        let status = Command::new("ffmpeg")
+        let status = Command::new(&self.ffmpeg_path)
             .args([
                 "-ss",
                 &format!("{start:.3}"),
@@ -309,6 +309,7 @@ mod tests {
             text: "Hello world".to_string(),
             words: None,
             language: None,
+            language_probability: None,
             confidence: None,
         }];
 
@@ -7,9 +7,12 @@
 //! - Visual analysis (local models or Claude Vision API)
 //! - Multimodal fusion with timestamp alignment
 
+pub mod cache;
 pub mod diarize;
 pub mod extract;
 pub mod fusion;
+pub mod models;
+pub mod remote;
 pub mod report;
 pub mod transcribe;
 pub mod vision;
@@ -18,11 +21,17 @@ use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
+use cache::{cached, StageCache};
+
 pub use diarize::{Diarizer, SpeakerSegment};
 pub use extract::{AudioExtractor, ExtractedFrame, FrameExtractor};
 pub use fusion::{FusedSegment, FusionEngine};
+pub use models::{DownloadProgress, ModelStore};
+pub use remote::{RemoteStage, RemoteWorker};
 pub use report::{AnalysisReport, ReportFormat};
-pub use transcribe::{Transcriber, TranscriptSegment, WordTiming};
+pub use transcribe::{
+    is_auto_language, Transcriber, TranscriptSegment, WordTiming, LOW_LANGUAGE_CONFIDENCE,
+};
 pub use vision::{VisionAnalyzer, VisionBackend, VisualAnalysis};
 
 /// Analysis pipeline errors
@@ -40,6 +49,9 @@ pub enum AnalysisError {
     #[error("Vision analysis error: {0}")]
     Vision(String),
 
+    #[error("Remote worker error: {0}")]
+    Remote(String),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -102,6 +114,21 @@ pub struct AnalysisOutput {
     pub segments: Vec<AnalysisSegment>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<VideoMetadata>,
+    /// Transcript language, detected or forced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Probability of the detected language (0.0-1.0); `None` when forced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language_confidence: Option<f64>,
+}
+
+impl AnalysisOutput {
+    /// Whether the language was auto-detected with low confidence
+    #[must_use]
+    pub fn language_uncertain(&self) -> bool {
+        self.language_confidence
+            .is_some_and(|confidence| confidence < LOW_LANGUAGE_CONFIDENCE)
+    }
 }
 
 /// Video metadata
@@ -124,16 +151,28 @@ pub struct PipelineConfig {
     pub scene_threshold: f32,
     /// Maximum frames to extract
     pub max_frames: usize,
-    /// Whisper model size (tiny, base, small, medium, large)
+    /// Whisper model size (tiny, base, small, medium, large-v3) or checkpoint path
     pub whisper_model: String,
+    /// Where local Whisper checkpoints are cached and downloaded
+    pub models: ModelStore,
+    /// Transcription language code (`None` or `auto` auto-detects)
+    pub language: Option<String>,
     /// Enable speaker diarization
     pub enable_diarization: bool,
     /// Vision backend preference
     pub vision_backend: VisionBackend,
     /// Output directory for intermediate files
     pub work_dir: PathBuf,
-    /// DGX Spark host for GPU offload
-    pub dgx_host: Option<String>,
+    /// Stage result cache root (`None` recomputes every stage)
+    pub cache_dir: Option<PathBuf>,
+    /// Size cap for the cache root; least recently analyzed videos go first
+    pub cache_max_bytes: u64,
+    /// Remote GPU worker for offloaded stages
+    pub remote: Option<RemoteWorker>,
+    /// Path to ffmpeg executable
+    pub ffmpeg_path: String,
+    /// Path to ffprobe executable
+    pub ffprobe_path: String,
 }
 
 impl Default for PipelineConfig {
@@ -142,10 +181,16 @@ impl Default for PipelineConfig {
             scene_threshold: 0.3,
             max_frames: 100,
             whisper_model: "base".to_string(),
+            models: ModelStore::default(),
+            language: None,
             enable_diarization: true,
             vision_backend: VisionBackend::Local,
             work_dir: std::env::temp_dir().join("nab_analyze"),
-            dgx_host: None,
+            cache_dir: Some(cache::default_cache_dir()),
+            cache_max_bytes: cache::DEFAULT_MAX_CACHE_BYTES,
+            remote: None,
+            ffmpeg_path: "ffmpeg".to_string(),
+            ffprobe_path: "ffprobe".to_string(),
         }
     }
 }
@@ -168,57 +213,70 @@ impl AnalysisPipeline {
     }
 
     /// Create pipeline with custom configuration
-    pub fn with_config(config: PipelineConfig) -> Result<Self> {
+    pub fn with_config(mut config: PipelineConfig) -> Result<Self> {
+        // `auto` and no language are the same run, and share cache entries
+        config.language = config
+            .language
+            .filter(|language| !is_auto_language(language));
+
         // Ensure work directory exists
         std::fs::create_dir_all(&config.work_dir)?;
 
+        let mut transcriber = Transcriber::new(&config.whisper_model, config.remote.clone())?
+            .with_model_store(config.models.clone());
+        if let Some(language) = &config.language {
+            transcriber = transcriber.with_language(language);
+        }
+
         Ok(Self {
-            frame_extractor: FrameExtractor::new(config.scene_threshold, config.max_frames),
-            audio_extractor: AudioExtractor::new(),
-            transcriber: Transcriber::new(&config.whisper_model, config.dgx_host.clone())?,
-            diarizer: Diarizer::new(config.dgx_host.clone())?,
-            vision: VisionAnalyzer::new(config.vision_backend.clone(), config.dgx_host.clone())?,
+            frame_extractor: FrameExtractor::new(config.scene_threshold, config.max_frames)
+                .with_tools(&config.ffmpeg_path, &config.ffprobe_path),
+            audio_extractor: AudioExtractor::new().with_ffmpeg(&config.ffmpeg_path),
+            transcriber,
+            diarizer: Diarizer::new(config.remote.clone())?,
+            vision: VisionAnalyzer::new(config.vision_backend.clone(), config.remote.clone())?,
             fusion: FusionEngine::new(),
             config,
         })
     }
 
     /// Run full analysis pipeline on a video file
+    ///
+    /// Stages whose inputs and settings are unchanged since an earlier run
+    /// are reused from the cache.
     pub async fn analyze(&self, video_path: impl AsRef<Path>) -> Result<AnalysisOutput> {
         let video_path = video_path.as_ref();
         tracing::info!("Starting analysis of: {}", video_path.display());
 
-        // 1. Extract frames and audio in parallel
-        let work_dir = &self.config.work_dir;
-        let frames_dir = work_dir.join("frames");
-        let audio_path = work_dir.join("audio.wav");
-
-        std::fs::create_dir_all(&frames_dir)?;
-
-        // Run extraction
-        let (frames, metadata) = self
-            .frame_extractor
-            .extract(video_path, &frames_dir)
-            .await?;
-        self.audio_extractor
-            .extract(video_path, &audio_path)
-            .await?;
+        let cache = self.open_cache(video_path).await?;
+        let cache = cache.as_ref();
+        let mut audio = None;
 
+        // 1. Extract keyframes
+        let (frames, metadata) = self.extract_frames(video_path, cache).await?;
         tracing::info!("Extracted {} keyframes", frames.len());
 
         // 2. Transcribe audio
-        let transcript = self.transcriber.transcribe(&audio_path).await?;
+        let transcript = self.transcribe(video_path, cache, &mut audio).await?;
         tracing::info!("Transcribed {} segments", transcript.len());
 
         // 3. Speaker diarization (if enabled)
         let speakers = if self.config.enable_diarization {
-            Some(self.diarizer.diarize(&audio_path).await?)
+            Some(self.diarize(video_path, cache, &mut audio).await?)
         } else {
             None
         };
 
         // 4. Visual analysis of keyframes
-        let visual_analyses = self.vision.analyze_frames(&frames).await?;
+        let vision_settings = serde_json::json!({
+            "frames": self.frame_settings(),
+            "vision": self.vision.cache_settings(),
+        });
+        let visual_analyses: Vec<VisualAnalysis> =
+            cached(cache, "vision", &vision_settings, || {
+                self.vision.analyze_frames(&frames)
+            })
+            .await?;
         tracing::info!("Analyzed {} frames visually", visual_analyses.len());
 
         // 5. Fuse all modalities
@@ -226,28 +284,29 @@ impl AnalysisPipeline {
             self.fusion
                 .fuse(&transcript, speakers.as_deref(), &frames, &visual_analyses)?;
 
+        self.prune_cache(cache).await;
+        let (language, language_confidence) = transcript_language(&transcript);
         Ok(AnalysisOutput {
             segments,
             metadata: Some(metadata),
+            language,
+            language_confidence,
         })
     }
 
     /// Run analysis with only audio (faster, no vision)
     pub async fn analyze_audio_only(&self, video_path: impl AsRef<Path>) -> Result<AnalysisOutput> {
         let video_path = video_path.as_ref();
-        let audio_path = self.config.work_dir.join("audio.wav");
-
-        // Extract audio
-        self.audio_extractor
-            .extract(video_path, &audio_path)
-            .await?;
+        let cache = self.open_cache(video_path).await?;
+        let cache = cache.as_ref();
+        let mut audio = None;
 
         // Transcribe
-        let transcript = self.transcriber.transcribe(&audio_path).await?;
+        let transcript = self.transcribe(video_path, cache, &mut audio).await?;
 
         // Diarize
         let speakers = if self.config.enable_diarization {
-            Some(self.diarizer.diarize(&audio_path).await?)
+            Some(self.diarize(video_path, cache, &mut audio).await?)
         } else {
             None
         };
@@ -274,13 +333,159 @@ impl AnalysisPipeline {
             })
             .collect();
 
+        self.prune_cache(cache).await;
+        let (language, language_confidence) = transcript_language(&transcript);
         Ok(AnalysisOutput {
             segments,
             metadata: None,
+            language,
+            language_confidence,
         })
     }
 }
 
+/// Language and detection confidence of the first segment that has a language
+fn transcript_language(transcript: &[TranscriptSegment]) -> (Option<String>, Option<f64>) {
+    transcript
+        .iter()
+        .find(|segment| segment.language.is_some())
+        .map_or((None, None), |segment| {
+            (segment.language.clone(), segment.language_probability)
+        })
+}
+
+impl AnalysisPipeline {
+    async fn open_cache(&self, video_path: &Path) -> Result<Option<StageCache>> {
+        let Some(root) = &self.config.cache_dir else {
+            return Ok(None);
+        };
+        Ok(Some(StageCache::open(root, video_path).await?))
+    }
+
+    /// Keep the cache root under its size cap; failing only costs disk space
+    async fn prune_cache(&self, cache: Option<&StageCache>) {
+        if let Some(cache) = cache {
+            if let Err(e) = cache.prune(self.config.cache_max_bytes).await {
+                tracing::warn!("Failed to prune the analysis cache: {e}");
+            }
+        }
+    }
+
+    /// Worker a stage is offloaded to, part of the stage's cache key
+    fn remote_endpoint(&self, stage: RemoteStage) -> Option<&str> {
+        self.config
+            .remote
+            .as_ref()
+            .filter(|remote| remote.handles(stage))
+            .map(RemoteWorker::endpoint)
+    }
+
+    fn frame_settings(&self) -> serde_json::Value {
+        serde_json::json!({
+            "scene_threshold": self.config.scene_threshold,
+            "max_frames": self.config.max_frames,
+        })
+    }
+
+    /// Keyframes and metadata, reused while the cached frame files still exist
+    async fn extract_frames(
+        &self,
+        video_path: &Path,
+        cache: Option<&StageCache>,
+    ) -> Result<(Vec<ExtractedFrame>, VideoMetadata)> {
+        let settings = self.frame_settings();
+        let frames_dir = match cache {
+            Some(cache) => {
+                let reusable = cache
+                    .load::<(Vec<ExtractedFrame>, VideoMetadata)>("frames", &settings)
+                    .await?
+                    .filter(|(frames, _)| frames.iter().all(|f| f.path.is_file()));
+                if let Some(extracted) = reusable {
+                    tracing::info!("Reusing cached frames");
+                    return Ok(extracted);
+                }
+                cache.stage_dir("frames", &settings)?.join("frames")
+            }
+            None => self.config.work_dir.join("frames"),
+        };
+
+        // Start empty so frames left over from an earlier run aren't picked up
+        let _ = std::fs::remove_dir_all(&frames_dir);
+        std::fs::create_dir_all(&frames_dir)?;
+        let extracted = self
+            .frame_extractor
+            .extract(video_path, &frames_dir)
+            .await?;
+        if let Some(cache) = cache {
+            cache.store("frames", &settings, &extracted).await?;
+        }
+        Ok(extracted)
+    }
+
+    /// Extracted audio track, produced at most once per run
+    async fn audio(
+        &self,
+        video_path: &Path,
+        cache: Option<&StageCache>,
+        audio: &mut Option<PathBuf>,
+    ) -> Result<PathBuf> {
+        if let Some(path) = audio {
+            return Ok(path.clone());
+        }
+
+        let path = if let Some(cache) = cache {
+            let dir = cache.stage_dir("audio", &())?;
+            let path = dir.join("audio.wav");
+            if cache.load::<()>("audio", &()).await?.is_none() || !path.is_file() {
+                std::fs::create_dir_all(&dir)?;
+                self.audio_extractor.extract(video_path, &path).await?;
+                cache.store("audio", &(), &()).await?;
+            }
+            path
+        } else {
+            let path = self.config.work_dir.join("audio.wav");
+            self.audio_extractor.extract(video_path, &path).await?;
+            path
+        };
+        *audio = Some(path.clone());
+        Ok(path)
+    }
+
+    async fn transcribe(
+        &self,
+        video_path: &Path,
+        cache: Option<&StageCache>,
+        audio: &mut Option<PathBuf>,
+    ) -> Result<Vec<TranscriptSegment>> {
+        let settings = serde_json::json!({
+            "model": self.config.whisper_model,
+            "language": self.config.language,
+            "remote": self.remote_endpoint(RemoteStage::Transcribe),
+        });
+        cached(cache, "transcript", &settings, || async move {
+            let audio_path = self.audio(video_path, cache, audio).await?;
+            self.transcriber.transcribe(&audio_path).await
+        })
+        .await
+    }
+
+    async fn diarize(
+        &self,
+        video_path: &Path,
+        cache: Option<&StageCache>,
+        audio: &mut Option<PathBuf>,
+    ) -> Result<Vec<SpeakerSegment>> {
+        let settings = serde_json::json!({
+            "remote": self.remote_endpoint(RemoteStage::Diarize),
+        });
+        cached(cache, "diarization", &settings, || async move {
+            let audio_path = self.audio(video_path, cache, audio).await?;
+            self.diarizer.diarize(&audio_path).await
+        })
+        .await
+    }
+}
+
 impl Default for AnalysisPipeline {
     fn default() -> Self {
         Self::new().expect("Failed to create default pipeline")
@@ -290,6 +495,8 @@ impl Default for AnalysisPipeline {
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(unix)]
+    use crate::test_support::write_stub;
 
     #[test]
     fn test_config_default() {
@@ -324,4 +531,116 @@ mod tests {
         assert!(json.contains("Speaker_1"));
         assert!(json.contains("waving"));
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_rerun_reuses_cached_stages() {
+        let dir = std::env::temp_dir().join(format!("nab_analyze_cache_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // ffmpeg stub: log the call, then write two frames or an audio file
+        // to the output path (the argument before `-y`)
+        let ffmpeg = dir.join("ffmpeg");
+        write_stub(
+            &ffmpeg,
+            &format!(
+                r#"echo "$@" >> '{}'
+for arg; do [ "$arg" = "-y" ] && out="$prev"; prev="$arg"; done
+case "$out" in
+  *%04d.jpg) d=$(dirname "$out"); printf 1 > "$d/frame_0001.jpg"; printf 2 > "$d/frame_0002.jpg" ;;
+  *) printf RIFF > "$out" ;;
+esac
+"#,
+                dir.join("ffmpeg.log").display()
+            ),
+        );
+        let ffprobe = dir.join("ffprobe");
+        write_stub(
+            &ffprobe,
+            r#"echo '{"streams": [{"codec_type": "video", "width": 640, "height": 360, "r_frame_rate": "2/1"}], "format": {"duration": "1.0"}}'"#,
+        );
+        let video = dir.join("input.mp4");
+        std::fs::write(&video, b"not really a video").unwrap();
+
+        // One body that parses as transcript, diarization and vision results
+        let (addr, seen) = remote::tests::mock_worker(
+            200,
+            r#"{"segments": [{"start": 0.0, "end": 1.0, "text": "Hi", "speaker": "SPEAKER_00",
+                              "language": "fi", "language_probability": 0.42}],
+                "action": "talking"}"#,
+        );
+        let config = |model: &str| PipelineConfig {
+            whisper_model: model.to_string(),
+            work_dir: dir.join("work"),
+            cache_dir: Some(dir.join("cache")),
+            remote: Some(RemoteWorker::new(&addr).unwrap()),
+            ffmpeg_path: ffmpeg.to_string_lossy().to_string(),
+            ffprobe_path: ffprobe.to_string_lossy().to_string(),
+            ..Default::default()
+        };
+        let ffmpeg_runs = || {
+            std::fs::read_to_string(dir.join("ffmpeg.log"))
+                .unwrap()
+                .lines()
+                .count()
+        };
+        let worker_calls = || seen.lock().unwrap().len();
+
+        let pipeline = AnalysisPipeline::with_config(config("base")).unwrap();
+        let first = pipeline.analyze(&video).await.unwrap();
+        // Frames + audio; transcript + diarization + one call per frame
+        assert_eq!(ffmpeg_runs(), 2);
+        assert_eq!(worker_calls(), 4);
+
+        let second = pipeline.analyze(&video).await.unwrap();
+        assert_eq!(ffmpeg_runs(), 2);
+        assert_eq!(worker_calls(), 4);
+        assert_eq!(
+            serde_json::to_value(&first).unwrap(),
+            serde_json::to_value(&second).unwrap()
+        );
+        assert_eq!(first.language.as_deref(), Some("fi"));
+        assert_eq!(first.language_confidence, Some(0.42));
+        assert!(first.language_uncertain());
+
+        // A different model only invalidates the transcript
+        AnalysisPipeline::with_config(config("small"))
+            .unwrap()
+            .analyze(&video)
+            .await
+            .unwrap();
+        assert_eq!(ffmpeg_runs(), 2);
+        assert_eq!(worker_calls(), 5);
+        assert_eq!(seen.lock().unwrap()[4].target, "/v1/transcribe?model=small");
+
+        // `auto` is the same run as no language
+        let auto = PipelineConfig {
+            language: Some("Auto".to_string()),
+            ..config("base")
+        };
+        AnalysisPipeline::with_config(auto)
+            .unwrap()
+            .analyze(&video)
+            .await
+            .unwrap();
+        assert_eq!(worker_calls(), 5);
+
+        // Another worker recomputes the offloaded stages, but not the frames
+        // or the audio
+        let (other, other_seen) =
+            remote::tests::mock_worker(200, r#"{"segments": [], "action": "x"}"#);
+        let elsewhere = PipelineConfig {
+            remote: Some(RemoteWorker::new(&other).unwrap()),
+            ..config("base")
+        };
+        AnalysisPipeline::with_config(elsewhere)
+            .unwrap()
+            .analyze(&video)
+            .await
+            .unwrap();
+        assert_eq!(ffmpeg_runs(), 2);
+        assert_eq!(other_seen.lock().unwrap().len(), 4);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
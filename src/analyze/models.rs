@@ -0,0 +1,341 @@
+//! Whisper model cache
+//!
+//! Resolves a model name (`base`, `large-v3`, ...) to a checkpoint file,
+//! downloading it into a cache directory on first use and verifying its
+//! SHA-256 before it is trusted. The directory defaults to the one Whisper
+//! itself uses (`$XDG_CACHE_HOME/whisper`, else `~/.cache/whisper`), with
+//! the same `<name>.pt` file names, so checkpoints are shared rather than
+//! stored twice. `NAB_MODEL_DIR` or [`ModelStore::new`] override it.
+
+use futures::StreamExt;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+
+use super::{AnalysisError, Result};
+
+/// Where the official Whisper checkpoints are published
+pub const DEFAULT_MODEL_BASE_URL: &str = "https://openaipublic.azureedge.net/main/whisper/models";
+
+/// Environment variable overriding the cache directory
+pub const MODEL_DIR_ENV: &str = "NAB_MODEL_DIR";
+
+/// Official checkpoints and their SHA-256 (also part of the download URL)
+const WHISPER_MODELS: &[(&str, &str)] = &[
+    (
+        "tiny.en",
+        "d3dd57d32accea0b295c96e26691aa14d8822fac7d9d27d5dc00b4ca2826dd03",
+    ),
+    (
+        "tiny",
+        "65147644a518d12f04e32d6f3b26facc3f8dd46e5390956a9424a650c0ce22b9",
+    ),
+    (
+        "base.en",
+        "25a8566e1d0c1e2231d1c762132cd20e0f96a85d16145c3a00adf5d1ac670ead",
+    ),
+    (
+        "base",
+        "ed3a0b6b1c0edf879ad9b11b1af5a0e6ab5db9205f891f668f8b0e6c6326e34e",
+    ),
+    (
+        "small.en",
+        "f953ad0fd29cacd07d5a9eda5624af0f6bcf2258be67c92b79389873d91e0872",
+    ),
+    (
+        "small",
+        "9ecf779972d90ba49c06d968637d720dd632c55bbf19d441fb42bf17a411e794",
+    ),
+    (
+        "medium.en",
+        "d7440d1dc186f76616474e0ff0b3b6b879abc9d1a4926b7adfa41db2d497ab4f",
+    ),
+    (
+        "medium",
+        "345ae4da62f9b3d59415adc60127b97c714f32e89e936602e85993674d08dcb1",
+    ),
+    (
+        "large-v1",
+        "e4b87e7e0bf463eb8e6956e646f1e277e901512310def2c24bf0e11bd3c28e9a",
+    ),
+    (
+        "large-v2",
+        "81f7c96c852ee8fc832187b0132e569d6c3065a3252ed18e56effd0b6a73e524",
+    ),
+    (
+        "large-v3",
+        "e5b1a55b89c1367dacf97e3e19bfd829a01529dbfdeefa8caeb59b3f1b81dadb",
+    ),
+    (
+        "large-v3-turbo",
+        "aff26ae408abcba5fbf8813c21e62b0941638c5f6eebfb145be0c9839262a19a",
+    ),
+];
+
+/// Download progress for one model
+#[derive(Debug, Clone, Copy)]
+pub struct DownloadProgress<'a> {
+    pub model: &'a str,
+    pub bytes_downloaded: u64,
+    pub bytes_total: Option<u64>,
+}
+
+/// Progress callback invoked after each received chunk
+pub type DownloadProgressFn = Arc<dyn Fn(DownloadProgress<'_>) + Send + Sync>;
+
+/// Cache of Whisper checkpoints
+#[derive(Clone)]
+pub struct ModelStore {
+    dir: PathBuf,
+    base_url: String,
+    extra_models: Vec<(String, String)>,
+    progress: Option<DownloadProgressFn>,
+}
+
+impl fmt::Debug for ModelStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ModelStore")
+            .field("dir", &self.dir)
+            .field("base_url", &self.base_url)
+            .field("extra_models", &self.extra_models)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for ModelStore {
+    /// `NAB_MODEL_DIR`, or Whisper's own cache directory
+    fn default() -> Self {
+        let dir = std::env::var_os(MODEL_DIR_ENV).map_or_else(
+            || {
+                // Same lookup as `whisper.load_model`'s default download root
+                std::env::var_os("XDG_CACHE_HOME")
+                    .map(PathBuf::from)
+                    .or_else(|| dirs::home_dir().map(|home| home.join(".cache")))
+                    .unwrap_or_else(std::env::temp_dir)
+                    .join("whisper")
+            },
+            PathBuf::from,
+        );
+        Self::new(dir)
+    }
+}
+
+impl ModelStore {
+    /// Cache models in `dir`
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            base_url: DEFAULT_MODEL_BASE_URL.to_string(),
+            extra_models: Vec::new(),
+            progress: None,
+        }
+    }
+
+    /// Download from a mirror laid out like the official bucket
+    /// (`<base>/<sha256>/<name>.pt`)
+    #[must_use]
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Register an additional checkpoint, e.g. a fine-tuned model on a mirror
+    #[must_use]
+    pub fn with_model(mut self, name: impl Into<String>, sha256: impl Into<String>) -> Self {
+        self.extra_models
+            .push((name.into(), sha256.into().to_ascii_lowercase()));
+        self
+    }
+
+    /// Report download progress
+    #[must_use]
+    pub fn with_progress(mut self, progress: DownloadProgressFn) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Cache directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Path of a model checkpoint, downloading it if it isn't cached yet
+    ///
+    /// An existing file path is returned unchanged, so users can point at
+    /// their own checkpoints, and so is a name without a known checksum,
+    /// for Whisper to resolve (or reject) itself.
+    pub async fn resolve(&self, model: &str) -> Result<PathBuf> {
+        let as_path = Path::new(model);
+        if as_path.is_file() {
+            return Ok(as_path.to_path_buf());
+        }
+
+        let Some(sha256) = self.checksum(model) else {
+            tracing::debug!("No checksum for Whisper model '{model}', passing the name through");
+            return Ok(as_path.to_path_buf());
+        };
+        let path = self.dir.join(format!("{model}.pt"));
+        if path.is_file() {
+            return Ok(path);
+        }
+
+        tokio::fs::create_dir_all(&self.dir).await?;
+        self.download(model, sha256, &path).await?;
+        Ok(path)
+    }
+
+    fn checksum(&self, model: &str) -> Option<&str> {
+        self.extra_models
+            .iter()
+            .map(|(name, sha)| (name.as_str(), sha.as_str()))
+            .chain(WHISPER_MODELS.iter().copied())
+            .find(|(name, _)| *name == model)
+            .map(|(_, sha)| sha)
+    }
+
+    /// Stream into a temporary file, hashing as we go, and only move it into
+    /// place once the checksum matches
+    async fn download(&self, model: &str, sha256: &str, path: &Path) -> Result<()> {
+        let url = format!("{}/{sha256}/{model}.pt", self.base_url);
+        tracing::info!("Downloading Whisper model {model} from {url}");
+
+        let response = reqwest::get(&url)
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| AnalysisError::Whisper(format!("Failed to download {model}: {e}")))?;
+        let bytes_total = response.content_length();
+
+        let partial = path.with_extension(format!("pt.{}.part", std::process::id()));
+        let mut file = tokio::fs::File::create(&partial).await?;
+        let mut hasher = Sha256::new();
+        let mut bytes_downloaded = 0;
+        let mut chunks = response.bytes_stream();
+
+        let written: Result<()> = async {
+            while let Some(chunk) = chunks.next().await {
+                let chunk = chunk.map_err(|e| {
+                    AnalysisError::Whisper(format!("Download of {model} interrupted: {e}"))
+                })?;
+                hasher.update(&chunk);
+                file.write_all(&chunk).await?;
+                bytes_downloaded += chunk.len() as u64;
+                if let Some(progress) = &self.progress {
+                    progress(DownloadProgress {
+                        model,
+                        bytes_downloaded,
+                        bytes_total,
+                    });
+                }
+            }
+            file.flush().await?;
+
+            let actual = format!("{:x}", hasher.finalize());
+            if actual != sha256 {
+                return Err(AnalysisError::Whisper(format!(
+                    "Checksum mismatch for {model}: expected {sha256}, got {actual}"
+                )));
+            }
+            Ok(())
+        }
+        .await;
+
+        drop(file);
+        match written {
+            Ok(()) => Ok(tokio::fs::rename(&partial, path).await?),
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&partial).await;
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{serve, Response};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    const MODEL: &[u8] = b"not really a whisper checkpoint";
+
+    /// Serve `MODEL` for every request, counting requested paths
+    fn model_server() -> (String, Arc<Mutex<Vec<String>>>) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&requests);
+        let addr = serve(move |request| {
+            log.lock().unwrap().push(request.target.clone());
+            Response::ok(MODEL)
+        });
+        (format!("http://{addr}/models"), requests)
+    }
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("nab-models-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn downloads_once_then_reuses_cache() {
+        let (base_url, requests) = model_server();
+        let dir = temp_dir();
+        let sha = format!("{:x}", Sha256::digest(MODEL));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&calls);
+        let store = ModelStore::new(&dir)
+            .with_base_url(base_url)
+            .with_model("tiny-test", &sha)
+            .with_progress(Arc::new(move |p: DownloadProgress<'_>| {
+                assert_eq!(p.model, "tiny-test");
+                assert_eq!(p.bytes_total, Some(MODEL.len() as u64));
+                counter.fetch_add(1, Ordering::SeqCst);
+            }));
+
+        let path = store.resolve("tiny-test").await.unwrap();
+        assert_eq!(path, dir.join("tiny-test.pt"));
+        assert_eq!(std::fs::read(&path).unwrap(), MODEL);
+        assert!(calls.load(Ordering::SeqCst) > 0);
+
+        let again = store.resolve("tiny-test").await.unwrap();
+        assert_eq!(again, path);
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [format!("/models/{sha}/tiny-test.pt")]
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn checksum_mismatch_is_not_cached() {
+        let (base_url, _) = model_server();
+        let dir = temp_dir();
+        let store = ModelStore::new(&dir)
+            .with_base_url(base_url)
+            .with_model("bad", "00".repeat(32));
+
+        let err = store.resolve("bad").await.unwrap_err();
+        assert!(err.to_string().contains("Checksum mismatch"));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn unknown_names_and_explicit_paths_pass_through() {
+        let dir = temp_dir();
+        let store = ModelStore::new(&dir).with_base_url("http://127.0.0.1:9");
+        assert_eq!(
+            store.resolve("turbo").await.unwrap(),
+            PathBuf::from("turbo")
+        );
+        assert!(!dir.exists());
+
+        let file = std::env::temp_dir().join(format!("nab-ckpt-{}.pt", uuid::Uuid::new_v4()));
+        std::fs::write(&file, MODEL).unwrap();
+        let resolved = store.resolve(file.to_str().unwrap()).await.unwrap();
+        assert_eq!(resolved, file);
+        let _ = std::fs::remove_file(&file);
+    }
+}
@@ -0,0 +1,317 @@
+//! Remote GPU worker offload
+//!
+//! Ships audio and frames to a worker on another machine (any GPU box) and
+//! reads back the same structures the local Python models produce. Each stage
+//! falls back to local processing when the worker fails, unless the worker
+//! was set up [`without_fallback`](RemoteWorker::without_fallback).
+//!
+//! # Protocol
+//!
+//! Plain HTTP; the request body is the raw file, the response is JSON.
+//! With a token configured, requests carry `Authorization: Bearer <token>`.
+//!
+//! | Stage | Request | Response |
+//! |-------|---------|----------|
+//! | transcribe | `POST /v1/transcribe?model=<whisper model>[&language=<code>]`, `audio/wav` | `{"segments": [TranscriptSegment]}` |
+//! | diarize | `POST /v1/diarize`, `audio/wav` | `{"segments": [SpeakerSegment]}` |
+//! | vision | `POST /v1/vision?timestamp=<seconds>`, `image/jpeg` | `VisualAnalysis` (`timestamp` optional) |
+//!
+//! Any non-2xx status is a failure; the response body is included in the error.
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use super::{
+    AnalysisError, ExtractedFrame, Result, SpeakerSegment, TranscriptSegment, VisualAnalysis,
+};
+
+/// Port used when the endpoint doesn't name one
+pub const DEFAULT_REMOTE_PORT: u16 = 8765;
+
+/// Transcription of long recordings can take a while even on a GPU
+const REQUEST_TIMEOUT: Duration = Duration::from_mins(30);
+
+/// Fail fast on an unreachable worker so the local fallback starts promptly
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Pipeline stage that can run on a remote worker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RemoteStage {
+    Transcribe,
+    Diarize,
+    Vision,
+}
+
+impl RemoteStage {
+    pub const ALL: [RemoteStage; 3] = [Self::Transcribe, Self::Diarize, Self::Vision];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Transcribe => "transcribe",
+            Self::Diarize => "diarize",
+            Self::Vision => "vision",
+        }
+    }
+}
+
+impl fmt::Display for RemoteStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for RemoteStage {
+    type Err = AnalysisError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|stage| stage.as_str().eq_ignore_ascii_case(s.trim()))
+            .ok_or_else(|| {
+                AnalysisError::Remote(format!(
+                    "Unknown remote stage '{s}' (expected transcribe, diarize or vision)"
+                ))
+            })
+    }
+}
+
+/// HTTP client for a remote analysis worker
+#[derive(Debug, Clone)]
+pub struct RemoteWorker {
+    base_url: url::Url,
+    token: Option<String>,
+    stages: HashSet<RemoteStage>,
+    fallback: bool,
+    client: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct SegmentsResponse<T> {
+    segments: Vec<T>,
+}
+
+impl RemoteWorker {
+    /// Worker at `endpoint` (`host`, `host:port` or an `http(s)://` URL),
+    /// offloading every stage
+    pub fn new(endpoint: &str) -> Result<Self> {
+        let with_scheme = if endpoint.contains("://") {
+            endpoint.to_string()
+        } else {
+            format!("http://{endpoint}")
+        };
+        let mut base_url = url::Url::parse(&with_scheme).map_err(|e| {
+            AnalysisError::Remote(format!("Invalid remote endpoint '{endpoint}': {e}"))
+        })?;
+        if base_url.port().is_none() && !endpoint.contains("://") {
+            let _ = base_url.set_port(Some(DEFAULT_REMOTE_PORT));
+        }
+
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .connect_timeout(CONNECT_TIMEOUT)
+            .build()
+            .map_err(|e| AnalysisError::Remote(format!("Failed to build HTTP client: {e}")))?;
+
+        Ok(Self {
+            base_url,
+            token: None,
+            stages: RemoteStage::ALL.into_iter().collect(),
+            fallback: true,
+            client,
+        })
+    }
+
+    /// Send `Authorization: Bearer <token>` with every request
+    #[must_use]
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    /// Only offload these stages; the rest run locally
+    #[must_use]
+    pub fn with_stages(mut self, stages: impl IntoIterator<Item = RemoteStage>) -> Self {
+        self.stages = stages.into_iter().collect();
+        self
+    }
+
+    /// Fail a stage when the worker fails instead of running it locally
+    #[must_use]
+    pub fn without_fallback(mut self) -> Self {
+        self.fallback = false;
+        self
+    }
+
+    /// Whether a failed stage falls back to local processing
+    pub fn falls_back(&self) -> bool {
+        self.fallback
+    }
+
+    /// Whether `stage` is offloaded to this worker
+    pub fn handles(&self, stage: RemoteStage) -> bool {
+        self.stages.contains(&stage)
+    }
+
+    /// Worker base URL
+    pub fn endpoint(&self) -> &str {
+        self.base_url.as_str()
+    }
+
+    /// Transcribe an audio file with the given Whisper model, auto-detecting
+    /// the language unless one is given
+    pub async fn transcribe(
+        &self,
+        audio: &Path,
+        model: &str,
+        language: Option<&str>,
+    ) -> Result<Vec<TranscriptSegment>> {
+        let mut query = vec![("model", model)];
+        query.extend(language.map(|language| ("language", language)));
+        let response: SegmentsResponse<TranscriptSegment> = self
+            .post(RemoteStage::Transcribe, audio, "audio/wav", &query)
+            .await?;
+        Ok(response.segments)
+    }
+
+    /// Diarize speakers in an audio file
+    pub async fn diarize(&self, audio: &Path) -> Result<Vec<SpeakerSegment>> {
+        let response: SegmentsResponse<SpeakerSegment> = self
+            .post(RemoteStage::Diarize, audio, "audio/wav", &[])
+            .await?;
+        Ok(response.segments)
+    }
+
+    /// Analyze one keyframe
+    pub async fn analyze_frame(&self, frame: &ExtractedFrame) -> Result<VisualAnalysis> {
+        let timestamp = frame.timestamp.to_string();
+        let mut value: serde_json::Value = self
+            .post(
+                RemoteStage::Vision,
+                &frame.path,
+                "image/jpeg",
+                &[("timestamp", &timestamp)],
+            )
+            .await?;
+        // The frame's own timestamp wins over whatever the worker echoes
+        if let Some(object) = value.as_object_mut() {
+            object.insert("timestamp".to_string(), frame.timestamp.into());
+        }
+        Ok(serde_json::from_value(value)?)
+    }
+
+    async fn post<T: DeserializeOwned>(
+        &self,
+        stage: RemoteStage,
+        file: &Path,
+        content_type: &str,
+        query: &[(&str, &str)],
+    ) -> Result<T> {
+        let body = tokio::fs::read(file).await?;
+        let mut url = self
+            .base_url
+            .join(&format!("v1/{stage}"))
+            .map_err(|e| AnalysisError::Remote(e.to_string()))?;
+        if !query.is_empty() {
+            url.query_pairs_mut().extend_pairs(query);
+        }
+
+        let mut request = self
+            .client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, content_type)
+            .body(body);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| AnalysisError::Remote(format!("{stage} request failed: {e}")))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(AnalysisError::Remote(format!(
+                "{stage} worker returned {status}: {}",
+                body.trim()
+            )));
+        }
+        response
+            .json()
+            .await
+            .map_err(|e| AnalysisError::Remote(format!("Invalid {stage} response: {e}")))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::test_support::{serve, Request, Response};
+    use std::sync::{Arc, Mutex};
+
+    /// Worker answering every request with `status` and `body`, logging
+    /// the requests it saw
+    pub(crate) fn mock_worker(
+        status: u16,
+        body: &'static str,
+    ) -> (String, Arc<Mutex<Vec<Request>>>) {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        let addr = serve(move |request| {
+            log.lock().unwrap().push(request.clone());
+            Response::status(status)
+                .header("Content-Type", "application/json")
+                .body(body)
+        });
+        (addr.to_string(), seen)
+    }
+
+    #[test]
+    fn parses_endpoints_and_stages() {
+        let worker = RemoteWorker::new("gpu-box").unwrap();
+        assert_eq!(worker.endpoint(), "http://gpu-box:8765/");
+        assert!(RemoteStage::ALL.iter().all(|s| worker.handles(*s)));
+
+        let worker = RemoteWorker::new("https://gpu.example.com/nab/")
+            .unwrap()
+            .with_stages([RemoteStage::Vision]);
+        assert_eq!(worker.endpoint(), "https://gpu.example.com/nab/");
+        assert!(worker.handles(RemoteStage::Vision));
+        assert!(!worker.handles(RemoteStage::Transcribe));
+
+        assert_eq!(
+            "Diarize".parse::<RemoteStage>().unwrap(),
+            RemoteStage::Diarize
+        );
+        assert!("ocr".parse::<RemoteStage>().is_err());
+    }
+
+    #[tokio::test]
+    async fn vision_response_gets_frame_timestamp() {
+        let (addr, seen) = mock_worker(200, r#"{"timestamp": 0, "action": "talking"}"#);
+        let path = std::env::temp_dir().join(format!("nab-frame-{}.jpg", uuid::Uuid::new_v4()));
+        std::fs::write(&path, b"\xFF\xD8\xFF").unwrap();
+        let frame = ExtractedFrame {
+            path: path.clone(),
+            timestamp: 12.5,
+            frame_number: 300,
+            scene_score: 0.4,
+        };
+
+        let analysis = RemoteWorker::new(&addr)
+            .unwrap()
+            .analyze_frame(&frame)
+            .await
+            .unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(analysis.action, "talking");
+        assert!((analysis.timestamp - 12.5).abs() < f64::EPSILON);
+        assert_eq!(seen.lock().unwrap()[0].target, "/v1/vision?timestamp=12.5");
+    }
+}
@@ -81,6 +81,16 @@ impl AnalysisReport {
 
         writeln!(md, "- **Total Segments**: {total_segments}")?;
         writeln!(md, "- **Unique Speakers**: {}", speakers.len())?;
+        if let Some(ref language) = output.language {
+            match output.language_confidence {
+                Some(confidence) => writeln!(
+                    md,
+                    "- **Language**: {language} ({:.0}% confidence)",
+                    confidence * 100.0
+                )?,
+                None => writeln!(md, "- **Language**: {language}")?,
+            }
+        }
 
         // Emotion distribution
         let mut emotion_counts: std::collections::HashMap<&str, usize> =
@@ -323,6 +333,8 @@ mod tests {
                 audio_channels: Some(2),
                 audio_sample_rate: Some(48000),
             }),
+            language: None,
+            language_confidence: None,
         }
     }
 
@@ -1,13 +1,15 @@
 //! Audio transcription via Whisper
 //!
 //! Supports both local Whisper (via Python subprocess) and
-//! remote execution on DGX Spark for GPU acceleration.
+//! offload to a remote GPU worker, falling back to local on failure.
 
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 use std::process::Stdio;
 use tokio::process::Command;
 
+use super::models::ModelStore;
+use super::remote::{RemoteStage, RemoteWorker};
 use super::{AnalysisError, Result};
 
 /// Transcript segment with timestamps
@@ -20,6 +22,9 @@ pub struct TranscriptSegment {
     pub words: Option<Vec<WordTiming>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub language: Option<String>,
+    /// Probability of the detected `language` (0.0-1.0); `None` when forced
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language_probability: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub confidence: Option<f32>,
 }
@@ -37,28 +42,64 @@ pub struct WordTiming {
 /// Whisper transcription engine
 pub struct Transcriber {
     model: String,
-    dgx_host: Option<String>,
+    language: Option<String>,
+    models: ModelStore,
+    remote: Option<RemoteWorker>,
 }
 
 impl Transcriber {
-    pub fn new(model: &str, dgx_host: Option<String>) -> Result<Self> {
+    pub fn new(model: &str, remote: Option<RemoteWorker>) -> Result<Self> {
         Ok(Self {
             model: model.to_string(),
-            dgx_host,
+            language: None,
+            models: ModelStore::default(),
+            remote,
         })
     }
 
+    /// Force the transcription language instead of auto-detecting it;
+    /// `auto` keeps detection on
+    #[must_use]
+    pub fn with_language(mut self, language: &str) -> Self {
+        self.language = (!is_auto_language(language)).then(|| language.to_string());
+        self
+    }
+
+    /// Resolve local checkpoints through `models` instead of the default cache
+    #[must_use]
+    pub fn with_model_store(mut self, models: ModelStore) -> Self {
+        self.models = models;
+        self
+    }
+
     /// Transcribe audio file with word-level timestamps
     pub async fn transcribe(&self, audio_path: &Path) -> Result<Vec<TranscriptSegment>> {
-        if let Some(host) = &self.dgx_host {
-            self.transcribe_remote(audio_path, host).await
-        } else {
-            self.transcribe_local(audio_path).await
+        if let Some(remote) = self
+            .remote
+            .as_ref()
+            .filter(|r| r.handles(RemoteStage::Transcribe))
+        {
+            match remote
+                .transcribe(audio_path, &self.model, self.language.as_deref())
+                .await
+            {
+                Ok(segments) => return Ok(segments),
+                Err(e) if remote.falls_back() => {
+                    tracing::warn!("Remote transcription failed, falling back to local: {e}");
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        match &self.language {
+            Some(language) => self.transcribe_with_language(audio_path, language).await,
+            None => self.transcribe_local(audio_path).await,
         }
     }
 
     /// Local transcription using Python whisper
     async fn transcribe_local(&self, audio_path: &Path) -> Result<Vec<TranscriptSegment>> {
+        let model_path = self.models.resolve(&self.model).await?;
+
         // Create Python script for Whisper transcription
         let script = format!(
             r#"
@@ -73,6 +114,12 @@ result = model.transcribe(
     verbose=False
 )
 
+# transcribe() doesn't say how sure it was; detection uses the first 30s
+audio = whisper.pad_or_trim(whisper.load_audio("{audio_path}"))
+mel = whisper.log_mel_spectrogram(audio, n_mels=model.dims.n_mels).to(model.device)
+_, probs = model.detect_language(mel)
+language_probability = probs.get(result.get("language"))
+
 segments = []
 for seg in result["segments"]:
     segment = {{
@@ -80,6 +127,7 @@ for seg in result["segments"]:
         "end": seg["end"],
         "text": seg["text"].strip(),
         "language": result.get("language"),
+        "language_probability": language_probability,
     }}
 
     if "words" in seg:
@@ -97,7 +145,7 @@ for seg in result["segments"]:
 
 print(json.dumps(segments))
 "#,
-            model = self.model,
+            model = model_path.display(),
             audio_path = audio_path.display()
         );
 
@@ -117,106 +165,14 @@ print(json.dumps(segments))
         Ok(segments)
     }
 
-    /// Remote transcription on DGX Spark
-    async fn transcribe_remote(
-        &self,
-        audio_path: &Path,
-        host: &str,
-    ) -> Result<Vec<TranscriptSegment>> {
-        // Copy audio to DGX
-        let remote_path = format!("/tmp/nab_audio_{}.wav", std::process::id());
-
-        let scp_status = Command::new("scp")
-            .args([
-                audio_path.to_str().unwrap(),
-                &format!("{host}:{remote_path}"),
-            ])
-            .status()
-            .await?;
-
-        if !scp_status.success() {
-            return Err(AnalysisError::Whisper(
-                "Failed to copy audio to DGX".to_string(),
-            ));
-        }
-
-        // Run Whisper on DGX with GPU acceleration
-        let script = format!(
-            r#"
-import json
-import whisper
-
-# Use large-v3 on DGX for best quality
-model = whisper.load_model("{model}", device="cuda")
-result = model.transcribe(
-    "{remote_path}",
-    word_timestamps=True,
-    fp16=True,  # Use FP16 for speed on Blackwell
-    verbose=False
-)
-
-segments = []
-for seg in result["segments"]:
-    segment = {{
-        "start": seg["start"],
-        "end": seg["end"],
-        "text": seg["text"].strip(),
-        "language": result.get("language"),
-    }}
-
-    if "words" in seg:
-        segment["words"] = [
-            {{
-                "word": w["word"].strip(),
-                "start": w["start"],
-                "end": w["end"],
-                "confidence": w.get("probability")
-            }}
-            for w in seg["words"]
-        ]
-
-    segments.append(segment)
-
-print(json.dumps(segments))
-"#,
-            model = if self.model == "base" {
-                "large-v3"
-            } else {
-                &self.model
-            },
-            remote_path = remote_path
-        );
-
-        let output = Command::new("ssh")
-            .args([host, "python3", "-c", &format!("'{script}'")])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .await?;
-
-        // Clean up remote file
-        let _ = Command::new("ssh")
-            .args([host, "rm", "-f", &remote_path])
-            .status()
-            .await;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(AnalysisError::Whisper(format!(
-                "Remote Whisper failed: {stderr}"
-            )));
-        }
-
-        let segments: Vec<TranscriptSegment> = serde_json::from_slice(&output.stdout)?;
-        Ok(segments)
-    }
-
     /// Transcribe with language hint
     pub async fn transcribe_with_language(
         &self,
         audio_path: &Path,
         language: &str,
     ) -> Result<Vec<TranscriptSegment>> {
+        let model_path = self.models.resolve(&self.model).await?;
+
         let script = format!(
             r#"
 import json
@@ -254,7 +210,7 @@ for seg in result["segments"]:
 
 print(json.dumps(segments))
 "#,
-            model = self.model,
+            model = model_path.display(),
             audio_path = audio_path.display(),
             language = language
         );
@@ -276,8 +232,17 @@ print(json.dumps(segments))
     }
 }
 
+/// Auto-detected languages below this probability are flagged as uncertain
+pub const LOW_LANGUAGE_CONFIDENCE: f64 = 0.5;
+
+/// Whether a language code asks for detection (`auto`, any case)
+pub fn is_auto_language(language: &str) -> bool {
+    language.trim().eq_ignore_ascii_case("auto")
+}
+
 #[cfg(test)]
 mod tests {
+    use super::super::remote::tests::mock_worker;
     use super::*;
 
     #[test]
@@ -315,6 +280,7 @@ mod tests {
                 },
             ]),
             language: Some("en".to_string()),
+            language_probability: Some(0.97),
             confidence: None,
         };
 
@@ -322,4 +288,121 @@ mod tests {
         assert!(json.contains("Hello world"));
         assert!(json.contains("\"en\""));
     }
+
+    /// Store that can't download, so local fallbacks fail fast
+    fn offline_models() -> ModelStore {
+        ModelStore::new(std::env::temp_dir().join(format!("nab-models-{}", uuid::Uuid::new_v4())))
+            .with_base_url("http://127.0.0.1:9")
+    }
+
+    fn temp_audio() -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("nab-audio-{}.wav", uuid::Uuid::new_v4()));
+        std::fs::write(&path, b"RIFF\x24\x00\x00\x00WAVEfmt ").unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn remote_worker_receives_audio_and_returns_segments() {
+        let (addr, seen) = mock_worker(
+            200,
+            r#"{"segments": [
+                {"start": 0.0, "end": 1.5, "text": "Hello", "language": "en"},
+                {"start": 1.5, "end": 3.0, "text": "world"}
+            ]}"#,
+        );
+        let worker = RemoteWorker::new(&addr).unwrap().with_token("s3cret");
+        let audio = temp_audio();
+
+        let segments = Transcriber::new("small", Some(worker))
+            .unwrap()
+            .transcribe(&audio)
+            .await
+            .unwrap();
+
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].text, "Hello");
+        assert_eq!(segments[0].language.as_deref(), Some("en"));
+        assert!((segments[1].end - 3.0).abs() < f64::EPSILON);
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen[0].target, "/v1/transcribe?model=small");
+        assert_eq!(seen[0].header("authorization"), Some("Bearer s3cret"));
+        assert_eq!(seen[0].body, std::fs::read(&audio).unwrap());
+        let _ = std::fs::remove_file(&audio);
+    }
+
+    #[tokio::test]
+    async fn language_override_is_sent_to_remote_worker() {
+        let (addr, seen) = mock_worker(200, r#"{"segments": []}"#);
+        let audio = temp_audio();
+
+        Transcriber::new("base", Some(RemoteWorker::new(&addr).unwrap()))
+            .unwrap()
+            .with_language("fi")
+            .transcribe(&audio)
+            .await
+            .unwrap();
+        let _ = std::fs::remove_file(&audio);
+
+        assert_eq!(
+            seen.lock().unwrap()[0].target,
+            "/v1/transcribe?model=base&language=fi"
+        );
+    }
+
+    #[tokio::test]
+    async fn remote_failure_falls_back_to_local() {
+        let (addr, seen) = mock_worker(503, r#"{"error": "busy"}"#);
+        let worker = RemoteWorker::new(&addr).unwrap();
+        let audio = temp_audio();
+
+        // The local path can't fetch the model, so it fails too, but with a
+        // local error rather than the remote one.
+        let result = Transcriber::new("tiny", Some(worker))
+            .unwrap()
+            .with_model_store(offline_models())
+            .transcribe(&audio)
+            .await;
+        let _ = std::fs::remove_file(&audio);
+
+        assert_eq!(seen.lock().unwrap().len(), 1);
+        assert!(!matches!(result, Err(AnalysisError::Remote(_))));
+    }
+
+    #[tokio::test]
+    async fn remote_failure_without_fallback_is_an_error() {
+        let (addr, seen) = mock_worker(503, r#"{"error": "busy"}"#);
+        let worker = RemoteWorker::new(&addr).unwrap().without_fallback();
+        let audio = temp_audio();
+
+        let result = Transcriber::new("tiny", Some(worker))
+            .unwrap()
+            .with_model_store(offline_models())
+            .transcribe(&audio)
+            .await;
+        let _ = std::fs::remove_file(&audio);
+
+        assert_eq!(seen.lock().unwrap().len(), 1);
+        let err = result.unwrap_err();
+        assert!(matches!(err, AnalysisError::Remote(_)), "{err}");
+        assert!(err.to_string().contains("busy"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn stages_not_offloaded_skip_the_worker() {
+        let (addr, seen) = mock_worker(200, r#"{"segments": []}"#);
+        let worker = RemoteWorker::new(&addr)
+            .unwrap()
+            .with_stages([RemoteStage::Vision]);
+        let audio = temp_audio();
+
+        let _ = Transcriber::new("tiny", Some(worker))
+            .unwrap()
+            .with_model_store(offline_models())
+            .transcribe(&audio)
+            .await;
+        let _ = std::fs::remove_file(&audio);
+
+        assert!(seen.lock().unwrap().is_empty());
+    }
 }
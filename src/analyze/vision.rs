@@ -7,8 +7,12 @@ use serde::{Deserialize, Serialize};
 use std::process::Stdio;
 use tokio::process::Command;
 
+use super::remote::{RemoteStage, RemoteWorker};
 use super::{AnalysisError, ExtractedFrame, Result};
 
+/// Claude model used by the `ClaudeApi` and `Hybrid` backends
+const CLAUDE_VISION_MODEL: &str = "claude-sonnet-4-20250514";
+
 /// Vision backend selection
 #[derive(Debug, Clone, Default)]
 pub enum VisionBackend {
@@ -59,12 +63,12 @@ pub struct FaceAnalysis {
 /// Vision analyzer
 pub struct VisionAnalyzer {
     backend: VisionBackend,
-    dgx_host: Option<String>,
+    remote: Option<RemoteWorker>,
 }
 
 impl VisionAnalyzer {
-    pub fn new(backend: VisionBackend, dgx_host: Option<String>) -> Result<Self> {
-        Ok(Self { backend, dgx_host })
+    pub fn new(backend: VisionBackend, remote: Option<RemoteWorker>) -> Result<Self> {
+        Ok(Self { backend, remote })
     }
 
     /// Analyze multiple frames
@@ -79,8 +83,41 @@ impl VisionAnalyzer {
         Ok(results)
     }
 
+    /// Everything that determines the results, for the stage cache key
+    pub fn cache_settings(&self) -> serde_json::Value {
+        let (backend, model) = match &self.backend {
+            VisionBackend::Local => ("local", None),
+            VisionBackend::ClaudeApi { .. } => ("claude", Some(CLAUDE_VISION_MODEL)),
+            VisionBackend::Hybrid { .. } => ("hybrid", Some(CLAUDE_VISION_MODEL)),
+        };
+        serde_json::json!({
+            "backend": backend,
+            "model": model,
+            "remote": self.remote().map(RemoteWorker::endpoint),
+        })
+    }
+
+    /// Worker the frames go to; an explicitly chosen Claude API backend wins
+    /// over it
+    fn remote(&self) -> Option<&RemoteWorker> {
+        let claude = matches!(self.backend, VisionBackend::ClaudeApi { .. });
+        self.remote
+            .as_ref()
+            .filter(|r| r.handles(RemoteStage::Vision) && !claude)
+    }
+
     /// Analyze a single frame
     pub async fn analyze_frame(&self, frame: &ExtractedFrame) -> Result<VisualAnalysis> {
+        if let Some(remote) = self.remote() {
+            match remote.analyze_frame(frame).await {
+                Ok(analysis) => return Ok(analysis),
+                Err(e) if remote.falls_back() => {
+                    tracing::warn!("Remote vision failed, falling back to local: {e}");
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
         match &self.backend {
             VisionBackend::Local => self.analyze_local(frame).await,
             VisionBackend::ClaudeApi { api_key } => self.analyze_claude(frame, api_key).await,
@@ -93,11 +130,6 @@ impl VisionAnalyzer {
 
     /// Local analysis using Python models
     async fn analyze_local(&self, frame: &ExtractedFrame) -> Result<VisualAnalysis> {
-        // Use DGX if available
-        if let Some(host) = &self.dgx_host {
-            return self.analyze_remote(frame, host).await;
-        }
-
         let script = format!(
             r#"
 import json
@@ -194,123 +226,6 @@ print(json.dumps(result))
         Ok(analysis)
     }
 
-    /// Remote analysis on DGX Spark
-    async fn analyze_remote(&self, frame: &ExtractedFrame, host: &str) -> Result<VisualAnalysis> {
-        let remote_path = format!("/tmp/nab_frame_{}.jpg", std::process::id());
-
-        // Copy frame to DGX
-        let scp_status = Command::new("scp")
-            .args([
-                frame.path.to_str().unwrap(),
-                &format!("{host}:{remote_path}"),
-            ])
-            .status()
-            .await?;
-
-        if !scp_status.success() {
-            return Err(AnalysisError::Vision(
-                "Failed to copy frame to DGX".to_string(),
-            ));
-        }
-
-        let script = format!(
-            r#"
-import json
-import cv2
-from deepface import DeepFace
-
-image_path = "{remote_path}"
-
-try:
-    faces = DeepFace.analyze(
-        image_path,
-        actions=['emotion'],
-        enforce_detection=False,
-        silent=True
-    )
-
-    if not isinstance(faces, list):
-        faces = [faces]
-
-    face_results = []
-    primary_emotion = None
-    primary_confidence = 0.0
-
-    for face in faces:
-        region = face.get("region", {{}})
-        emotions = face.get("emotion", {{}})
-
-        top_emotion = max(emotions, key=emotions.get)
-        confidence = emotions[top_emotion] / 100.0
-
-        if confidence > primary_confidence:
-            primary_emotion = top_emotion
-            primary_confidence = confidence
-
-        img = cv2.imread(image_path)
-        h, w = img.shape[:2]
-
-        bbox = [
-            region.get("x", 0) / w,
-            region.get("y", 0) / h,
-            region.get("w", 0) / w,
-            region.get("h", 0) / h,
-        ]
-
-        face_results.append({{
-            "bbox": bbox,
-            "emotion": {{
-                "primary": top_emotion,
-                "confidence": confidence,
-            }}
-        }})
-
-    result = {{
-        "timestamp": {timestamp},
-        "action": "present" if face_results else "none",
-        "emotion": {{
-            "primary": primary_emotion or "neutral",
-            "confidence": primary_confidence,
-        }} if primary_emotion else None,
-        "faces": face_results if face_results else None,
-    }}
-
-except Exception as e:
-    result = {{
-        "timestamp": {timestamp},
-        "action": "unknown",
-    }}
-
-print(json.dumps(result))
-"#,
-            remote_path = remote_path,
-            timestamp = frame.timestamp
-        );
-
-        let output = Command::new("ssh")
-            .args([host, "python3", "-c", &format!("'{script}'")])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .await?;
-
-        // Clean up
-        let _ = Command::new("ssh")
-            .args([host, "rm", "-f", &remote_path])
-            .status()
-            .await;
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(AnalysisError::Vision(format!(
-                "Remote vision analysis failed: {stderr}"
-            )));
-        }
-
-        let analysis: VisualAnalysis = serde_json::from_slice(&output.stdout)?;
-        Ok(analysis)
-    }
-
     /// Analyze using Claude Vision API
     async fn analyze_claude(
         &self,
@@ -331,7 +246,7 @@ print(json.dumps(result))
 Return ONLY valid JSON, no markdown."#;
 
         let request_body = serde_json::json!({
-            "model": "claude-sonnet-4-20250514",
+            "model": CLAUDE_VISION_MODEL,
             "max_tokens": 500,
             "messages": [{
                 "role": "user",
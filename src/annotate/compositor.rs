@@ -13,6 +13,7 @@ use tokio::io::{AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
 use tokio::process::Command;
 use tracing::{debug, info, warn};
 
+use super::fonts::{drawtext_font_param, escape_filter_path, ResolvedFont};
 use super::overlay::OverlayTrack;
 use super::subtitle::{AssGenerator, SubtitleEntry, SubtitleGenerator, SubtitleStyle};
 
@@ -80,6 +81,8 @@ pub struct CompositorConfig {
     pub output_args: Vec<String>,
     /// Buffer size for streaming (bytes)
     pub buffer_size: usize,
+    /// Font for burned-in text (`None` = look fonts up by style family name)
+    pub font: Option<ResolvedFont>,
 }
 
 impl Default for CompositorConfig {
@@ -98,6 +101,7 @@ impl Default for CompositorConfig {
             input_args: Vec::new(),
             output_args: Vec::new(),
             buffer_size: 64 * 1024, // 64KB
+            font: None,
         }
     }
 }
@@ -138,6 +142,13 @@ impl CompositorConfig {
         }
     }
 
+    /// Burn in text with this font (see [`super::fonts::resolve_font`])
+    #[must_use]
+    pub fn with_font(mut self, font: ResolvedFont) -> Self {
+        self.font = Some(font);
+        self
+    }
+
     /// Enable hardware acceleration
     #[must_use]
     pub fn with_hwaccel(mut self, accel: &str) -> Self {
@@ -197,12 +208,14 @@ impl Compositor {
 
         // ASS subtitle filter (primary subtitles)
         if let Some(ass_path) = subtitle_file {
-            let path_escaped = ass_path
-                .to_string_lossy()
-                .replace('\\', "\\\\")
-                .replace(':', "\\:")
-                .replace('\'', "\\'");
-            filters.push(format!("ass='{path_escaped}'"));
+            let path_escaped = escape_filter_path(ass_path);
+            match self.config.font.as_ref().and_then(ResolvedFont::dir) {
+                Some(dir) => filters.push(format!(
+                    "ass='{path_escaped}':fontsdir='{}'",
+                    escape_filter_path(dir)
+                )),
+                None => filters.push(format!("ass='{path_escaped}'")),
+            }
         }
 
         // Drawtext filters for additional overlay tracks
@@ -232,14 +245,14 @@ impl Compositor {
 
                 let drawtext = format!(
                     "drawtext=text='{text}':\
-                     fontfile=/System/Library/Fonts/Supplemental/{}.ttf:\
+                     {font}:\
                      fontsize={fontsize}:\
                      fontcolor=0x{color}:\
                      x={x}:y={y}:\
                      borderw={borderw}:\
                      bordercolor=0x{bordercolor}:\
                      enable='between(t,{start_sec},{end_sec})'",
-                    style.font_name,
+                    font = drawtext_font_param(&style.font_name, self.config.font.as_ref()),
                     fontsize = style.font_size,
                     color = style.color,
                     borderw = style.outline_width as u32,
@@ -451,6 +464,13 @@ impl Compositor {
             styles.push(track.to_ass_style());
         }
 
+        // Use the resolved font so libass finds glyphs for the subtitle language
+        if let Some(ref font) = self.config.font {
+            for style in &mut styles {
+                style.font_name.clone_from(&font.family);
+            }
+        }
+
         // Create generator with all styles
         let mut generator = AssGenerator::new();
         for style in styles {
@@ -508,6 +528,24 @@ mod tests {
         assert!(filter.contains("/tmp/test.ass"));
     }
 
+    #[test]
+    fn test_build_filter_complex_uses_resolved_font() {
+        let font = ResolvedFont {
+            family: "Noto Sans CJK JP".to_string(),
+            path: PathBuf::from("/usr/share/fonts/noto/NotoSansCJK-Regular.ttc"),
+        };
+        let compositor = Compositor::with_config(CompositorConfig::default().with_font(font));
+
+        let mut track = OverlayTrack::new("speakers");
+        track.add_entry(crate::annotate::OverlayEntry::new(0, 1000, "話者 1"));
+        let path = PathBuf::from("/tmp/test.ass");
+        let filter = compositor.build_filter_complex(Some(&path), &[track]);
+
+        assert!(filter.contains("ass='/tmp/test.ass':fontsdir='/usr/share/fonts/noto'"));
+        assert!(filter.contains("fontfile='/usr/share/fonts/noto/NotoSansCJK-Regular.ttc'"));
+        assert!(!filter.contains("/System/Library"));
+    }
+
     #[test]
     fn test_build_args_file_output() {
         let compositor = Compositor::default();
@@ -0,0 +1,283 @@
+//! Font resolution for burned-in subtitles
+//!
+//! Picks a font family with glyph coverage for the subtitle language
+//! (e.g. Noto Sans CJK JP for Japanese) and resolves its file path via
+//! cross-platform system font discovery.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Families for Latin, Cyrillic and Greek scripts (widely available)
+const DEFAULT_FAMILIES: &[&str] = &[
+    "Arial",
+    "Helvetica",
+    "Liberation Sans",
+    "DejaVu Sans",
+    "Noto Sans",
+];
+
+const JAPANESE_FAMILIES: &[&str] = &[
+    "Noto Sans CJK JP",
+    "Noto Sans JP",
+    "Source Han Sans JP",
+    "Hiragino Sans",
+    "Hiragino Kaku Gothic ProN",
+    "Yu Gothic",
+    "MS Gothic",
+    "Arial Unicode MS",
+];
+
+const SIMPLIFIED_CHINESE_FAMILIES: &[&str] = &[
+    "Noto Sans CJK SC",
+    "Noto Sans SC",
+    "Source Han Sans SC",
+    "PingFang SC",
+    "Microsoft YaHei",
+    "SimHei",
+    "Arial Unicode MS",
+];
+
+const TRADITIONAL_CHINESE_FAMILIES: &[&str] = &[
+    "Noto Sans CJK TC",
+    "Noto Sans TC",
+    "Source Han Sans TC",
+    "PingFang TC",
+    "Microsoft JhengHei",
+    "Arial Unicode MS",
+];
+
+const KOREAN_FAMILIES: &[&str] = &[
+    "Noto Sans CJK KR",
+    "Noto Sans KR",
+    "Source Han Sans KR",
+    "Apple SD Gothic Neo",
+    "Malgun Gothic",
+    "Arial Unicode MS",
+];
+
+const ARABIC_FAMILIES: &[&str] = &[
+    "Noto Sans Arabic",
+    "Noto Naskh Arabic",
+    "Geeza Pro",
+    "Segoe UI",
+    "Arial",
+];
+
+const HEBREW_FAMILIES: &[&str] = &["Noto Sans Hebrew", "Arial Hebrew", "Arial"];
+
+const DEVANAGARI_FAMILIES: &[&str] = &[
+    "Noto Sans Devanagari",
+    "Kohinoor Devanagari",
+    "Nirmala UI",
+    "Mangal",
+];
+
+const THAI_FAMILIES: &[&str] = &["Noto Sans Thai", "Thonburi", "Leelawadee UI", "Tahoma"];
+
+/// A font family resolved to a file on disk
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedFont {
+    /// Family name (as used in ASS styles)
+    pub family: String,
+    /// Font file (`.ttf`, `.otf` or `.ttc`)
+    pub path: PathBuf,
+}
+
+impl ResolvedFont {
+    /// Directory containing the font file (for libass `fontsdir`)
+    #[must_use]
+    pub fn dir(&self) -> Option<&Path> {
+        self.path.parent()
+    }
+}
+
+/// Looks up the file for a font family
+pub trait FontLookup {
+    /// Path of a regular face of `family`, if installed
+    fn find_family(&self, family: &str) -> Option<PathBuf>;
+}
+
+impl FontLookup for fontdb::Database {
+    fn find_family(&self, family: &str) -> Option<PathBuf> {
+        let families = [fontdb::Family::Name(family)];
+        let id = self.query(&fontdb::Query {
+            families: &families,
+            ..fontdb::Query::default()
+        })?;
+        match self.face(id)?.source {
+            fontdb::Source::File(ref path) | fontdb::Source::SharedFile(ref path, _) => {
+                Some(path.clone())
+            }
+            fontdb::Source::Binary(_) => None,
+        }
+    }
+}
+
+/// Load the installed system fonts (fontconfig on Linux, system font
+/// directories on macOS and Windows)
+#[must_use]
+pub fn system_fonts() -> fontdb::Database {
+    let mut db = fontdb::Database::new();
+    db.load_system_fonts();
+    db
+}
+
+/// Normalize a language code or Whisper language name ("ja", "ja-JP", "Japanese")
+fn normalize_language(lang: &str) -> String {
+    let lang = lang.trim().to_ascii_lowercase();
+    match lang.as_str() {
+        "japanese" => "ja".to_string(),
+        "chinese" | "mandarin" => "zh".to_string(),
+        "cantonese" => "zh-hk".to_string(),
+        "korean" => "ko".to_string(),
+        "arabic" => "ar".to_string(),
+        "persian" => "fa".to_string(),
+        "urdu" => "ur".to_string(),
+        "hebrew" => "he".to_string(),
+        "hindi" => "hi".to_string(),
+        "marathi" => "mr".to_string(),
+        "nepali" => "ne".to_string(),
+        "thai" => "th".to_string(),
+        _ => lang.replace('_', "-"),
+    }
+}
+
+/// Candidate font families for a language, most preferred first
+#[must_use]
+pub fn families_for_language(lang: &str) -> &'static [&'static str] {
+    let lang = normalize_language(lang);
+    let primary = lang.split('-').next().unwrap_or_default();
+    match primary {
+        "ja" => JAPANESE_FAMILIES,
+        "zh" if ["tw", "hk", "mo", "hant"]
+            .iter()
+            .any(|region| lang.split('-').skip(1).any(|part| part == *region)) =>
+        {
+            TRADITIONAL_CHINESE_FAMILIES
+        }
+        "zh" | "yue" => SIMPLIFIED_CHINESE_FAMILIES,
+        "ko" => KOREAN_FAMILIES,
+        "ar" | "fa" | "ur" | "ps" => ARABIC_FAMILIES,
+        "he" | "iw" | "yi" => HEBREW_FAMILIES,
+        "hi" | "mr" | "ne" | "sa" => DEVANAGARI_FAMILIES,
+        "th" => THAI_FAMILIES,
+        _ => DEFAULT_FAMILIES,
+    }
+}
+
+/// Whether the language needs a script-specific font (CJK, Arabic, ...)
+/// rather than a common Latin font
+#[must_use]
+pub fn requires_script_font(lang: &str) -> bool {
+    !std::ptr::eq(families_for_language(lang), DEFAULT_FAMILIES)
+}
+
+/// Resolve the first installed font family with coverage for `lang`
+pub fn resolve_font(lookup: &impl FontLookup, lang: &str) -> Result<ResolvedFont> {
+    let families = families_for_language(lang);
+    families
+        .iter()
+        .find_map(|family| {
+            lookup.find_family(family).map(|path| ResolvedFont {
+                family: (*family).to_string(),
+                path,
+            })
+        })
+        .ok_or_else(|| {
+            anyhow!(
+                "No installed font covers language '{lang}' (tried: {}). Install e.g. {}",
+                families.join(", "),
+                families[0]
+            )
+        })
+}
+
+/// ffmpeg `drawtext` font parameter: the resolved font file, or a
+/// fontconfig family lookup when no file was resolved
+#[must_use]
+pub fn drawtext_font_param(font_name: &str, font: Option<&ResolvedFont>) -> String {
+    match font {
+        Some(font) => format!("fontfile='{}'", escape_filter_path(&font.path)),
+        None => format!("font='{}'", font_name.replace('\'', "\\'")),
+    }
+}
+
+/// Escape a path for use inside an ffmpeg filter option value
+pub(crate) fn escape_filter_path(path: &Path) -> String {
+    path.to_string_lossy()
+        .replace('\\', "\\\\")
+        .replace(':', "\\:")
+        .replace('\'', "\\'")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    struct StubFonts(HashMap<&'static str, &'static str>);
+
+    impl FontLookup for StubFonts {
+        fn find_family(&self, family: &str) -> Option<PathBuf> {
+            self.0.get(family).map(PathBuf::from)
+        }
+    }
+
+    #[test]
+    fn test_japanese_resolves_cjk_font() {
+        let fonts = StubFonts(HashMap::from([
+            ("Arial", "/fonts/Arial.ttf"),
+            ("DejaVu Sans", "/fonts/DejaVuSans.ttf"),
+            (
+                "Noto Sans CJK JP",
+                "/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+            ),
+        ]));
+
+        let font = resolve_font(&fonts, "ja").unwrap();
+        assert_eq!(font.family, "Noto Sans CJK JP");
+        assert_eq!(
+            font.path,
+            PathBuf::from("/fonts/opentype/noto/NotoSansCJK-Regular.ttc")
+        );
+        assert_eq!(font.dir(), Some(Path::new("/fonts/opentype/noto")));
+
+        // Whisper may report the language by name
+        assert_eq!(resolve_font(&fonts, "Japanese").unwrap(), font);
+        assert_eq!(resolve_font(&fonts, "en").unwrap().family, "Arial");
+    }
+
+    #[test]
+    fn test_missing_font_is_a_clear_error() {
+        let fonts = StubFonts(HashMap::from([("Arial", "/fonts/Arial.ttf")]));
+
+        let err = resolve_font(&fonts, "ja").unwrap_err().to_string();
+        assert!(err.contains("'ja'"));
+        assert!(err.contains("Noto Sans CJK JP"));
+    }
+
+    #[test]
+    fn test_families_for_language() {
+        assert_eq!(families_for_language("zh-TW")[0], "Noto Sans CJK TC");
+        assert_eq!(families_for_language("zh")[0], "Noto Sans CJK SC");
+        assert_eq!(families_for_language("ko")[0], "Noto Sans CJK KR");
+        assert_eq!(families_for_language("ar")[0], "Noto Sans Arabic");
+        assert_eq!(families_for_language("fi")[0], "Arial");
+        assert!(requires_script_font("ja"));
+        assert!(!requires_script_font("fi"));
+    }
+
+    #[test]
+    fn test_drawtext_font_param() {
+        let font = ResolvedFont {
+            family: "Noto Sans CJK JP".to_string(),
+            path: PathBuf::from("C:/Fonts/NotoSansCJK.ttc"),
+        };
+        assert_eq!(
+            drawtext_font_param("Arial", Some(&font)),
+            "fontfile='C\\:/Fonts/NotoSansCJK.ttc'"
+        );
+        assert_eq!(drawtext_font_param("Arial", None), "font='Arial'");
+    }
+}
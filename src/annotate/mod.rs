@@ -4,10 +4,12 @@
 //!
 //! # Features
 //!
-//! - **Subtitle generation** - Whisper transcription to SRT/ASS format
+//! - **Subtitle generation** - Whisper transcription to SRT/ASS/WebVTT format
 //! - **Analysis overlay** - Behavioral/emotional analysis as on-screen text
+//! - **Karaoke highlighting** - Word-level `\kf` timing from Whisper word timestamps
 //! - **Speaker labels** - Diarization-based speaker identification
 //! - **ffmpeg compositing** - Burn overlays into video streams
+//! - **Font resolution** - Language-aware font discovery (CJK, Arabic, ...) for burn-in
 //!
 //! # Example
 //!
@@ -23,17 +25,21 @@
 //! ```
 
 pub mod compositor;
+pub mod fonts;
 pub mod overlay;
 pub mod pipeline;
 pub mod subtitle;
 
 pub use compositor::{Compositor, CompositorConfig, CompositorOutput};
+pub use fonts::ResolvedFont;
 pub use overlay::{
     AnalysisOverlay, OverlayEntry, OverlayPosition, OverlayStyle, OverlayTrack, SpeakerLabelOverlay,
 };
 pub use pipeline::{
-    AnalysisConfig, AnnotationPipeline, PipelineConfig, PipelineResult, TranscriptionConfig,
+    AnalysisConfig, AnnotationPipeline, PipelineConfig, PipelineResult, TranscriptResult,
+    TranscriptionConfig,
 };
 pub use subtitle::{
     AssGenerator, SrtGenerator, SubtitleEntry, SubtitleFormat, SubtitleGenerator, SubtitleStyle,
+    SubtitleWord, VttGenerator,
 };
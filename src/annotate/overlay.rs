@@ -8,6 +8,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use super::fonts::{drawtext_font_param, ResolvedFont};
 use super::subtitle::{SubtitleEntry, SubtitleStyle};
 
 /// Position for overlay text
@@ -203,13 +204,23 @@ impl OverlayStyle {
         }
     }
 
-    /// Generate ffmpeg drawtext filter parameters
+    /// Generate ffmpeg drawtext filter parameters (font looked up by family name)
     #[must_use]
     pub fn to_drawtext_params(&self, position: OverlayPosition) -> String {
+        self.to_drawtext_params_with_font(position, None)
+    }
+
+    /// Generate ffmpeg drawtext filter parameters using a resolved font file
+    #[must_use]
+    pub fn to_drawtext_params_with_font(
+        &self,
+        position: OverlayPosition,
+        font: Option<&ResolvedFont>,
+    ) -> String {
         let (x, y) = position.to_drawtext_position(20);
 
         let mut params = vec![
-            format!("fontfile=/System/Library/Fonts/{}.ttf", self.font_name),
+            drawtext_font_param(&self.font_name, font),
             format!("fontsize={}", self.font_size),
             format!("fontcolor=0x{}", self.color),
             format!("x={x}"),
@@ -299,6 +310,7 @@ impl OverlayEntry {
             text: self.text.clone(),
             speaker: None,
             style: Some(style_name.to_string()),
+            words: Vec::new(),
         }
     }
 }
@@ -12,11 +12,18 @@ use std::process::Stdio;
 use tokio::fs;
 use tokio::io::AsyncWrite;
 use tokio::process::Command;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 use super::compositor::{Compositor, CompositorConfig};
+use super::fonts::{requires_script_font, resolve_font, system_fonts};
 use super::overlay::{AnalysisOverlay, OverlayPosition, SpeakerLabelOverlay};
-use super::subtitle::{AssGenerator, SubtitleEntry, SubtitleGenerator};
+use super::subtitle::{
+    AssGenerator, SrtGenerator, SubtitleEntry, SubtitleFormat, SubtitleGenerator, SubtitleWord,
+    VttGenerator,
+};
+
+pub use crate::analyze::transcribe::LOW_LANGUAGE_CONFIDENCE;
+use crate::analyze::transcribe::is_auto_language;
 
 /// Configuration for Whisper transcription
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -122,6 +129,7 @@ impl AnalysisConfig {
 
 /// Full pipeline configuration
 #[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)] // Independent output toggles
 pub struct PipelineConfig {
     /// Transcription settings
     pub transcription: TranscriptionConfig,
@@ -141,6 +149,8 @@ pub struct PipelineConfig {
     pub speaker_position: OverlayPosition,
     /// Analysis overlay position
     pub analysis_position: OverlayPosition,
+    /// Highlight each word as it is spoken (ASS `\kf` tags from word timestamps)
+    pub karaoke: bool,
 }
 
 impl Default for PipelineConfig {
@@ -155,6 +165,7 @@ impl Default for PipelineConfig {
             subtitles: true,
             speaker_position: OverlayPosition::TopLeft,
             analysis_position: OverlayPosition::TopRight,
+            karaoke: false,
         }
     }
 }
@@ -188,6 +199,16 @@ impl PipelineConfig {
         self
     }
 
+    /// Enable karaoke-style word highlighting (turns on word timestamps)
+    #[must_use]
+    pub fn with_karaoke(mut self, enabled: bool) -> Self {
+        self.karaoke = enabled;
+        if enabled {
+            self.transcription.word_timestamps = true;
+        }
+        self
+    }
+
     /// Enable analysis overlay
     #[must_use]
     pub fn with_analysis(mut self, enabled: bool) -> Self {
@@ -208,6 +229,10 @@ pub struct PipelineResult {
     pub subtitle_count: usize,
     /// Detected language
     pub detected_language: Option<String>,
+    /// Language detection probability (0-1), when auto-detected and
+    /// reported by the backend
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language_confidence: Option<f64>,
     /// Speaker segments (if diarization enabled)
     pub speakers: Vec<String>,
     /// Analysis results (if analysis enabled)
@@ -218,12 +243,41 @@ pub struct PipelineResult {
     pub processing_time_secs: f64,
 }
 
+impl PipelineResult {
+    /// Whether the language was auto-detected with low confidence
+    #[must_use]
+    pub fn language_uncertain(&self) -> bool {
+        self.language_confidence
+            .is_some_and(|confidence| confidence < LOW_LANGUAGE_CONFIDENCE)
+    }
+}
+
+/// Structured transcription without any rendering
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptResult {
+    /// Full transcript text
+    pub text: String,
+    /// Detected (or configured) language code
+    pub language: String,
+    /// Language detection probability (0-1), when auto-detected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language_confidence: Option<f64>,
+    /// Timed segments, with speakers when diarization is enabled and
+    /// word timings when karaoke is enabled
+    pub entries: Vec<SubtitleEntry>,
+    /// Distinct speaker labels, sorted
+    pub speakers: Vec<String>,
+}
+
 /// Whisper transcription output format (JSON)
 #[derive(Debug, Clone, Deserialize)]
 struct WhisperOutput {
     text: String,
     segments: Vec<WhisperSegment>,
     language: String,
+    /// Reported by faster-whisper based backends; openai-whisper omits it
+    #[serde(default, alias = "language_confidence")]
+    language_probability: Option<f64>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -291,6 +345,33 @@ impl AnnotationPipeline {
         Ok(results)
     }
 
+    /// Compositor whose font covers the transcript language.
+    ///
+    /// Scripts like CJK or Arabic need a font with matching glyphs, so a
+    /// missing font is an error rather than a run that renders tofu boxes;
+    /// Latin text falls back to family-name lookup.
+    fn compositor_for_language(&self, language: &str) -> Result<Compositor> {
+        if self.config.compositor.font.is_some() {
+            return Ok(Compositor::with_config(self.config.compositor.clone()));
+        }
+
+        match resolve_font(&system_fonts(), language) {
+            Ok(font) => {
+                info!("Using font {} ({})", font.family, font.path.display());
+                Ok(Compositor::with_config(
+                    self.config.compositor.clone().with_font(font),
+                ))
+            }
+            Err(e) if requires_script_font(language) => {
+                Err(e.context("Cannot burn in subtitles without a suitable font"))
+            }
+            Err(e) => {
+                debug!("{e}; falling back to font lookup by name");
+                Ok(Compositor::with_config(self.config.compositor.clone()))
+            }
+        }
+    }
+
     /// Extract audio from video for transcription
     async fn extract_audio(&self, input: &Path) -> Result<PathBuf> {
         let audio_path = self
@@ -338,7 +419,7 @@ impl AnnotationPipeline {
             output_dir.to_string_lossy().to_string(),
         ];
 
-        if self.config.transcription.language != "auto" {
+        if !is_auto_language(&self.config.transcription.language) {
             args.push("--language".to_string());
             args.push(self.config.transcription.language.clone());
         }
@@ -371,11 +452,27 @@ impl AnnotationPipeline {
         }
 
         let json_content = fs::read_to_string(&json_path).await?;
-        let whisper_output: WhisperOutput = serde_json::from_str(&json_content)?;
+        let mut whisper_output: WhisperOutput = serde_json::from_str(&json_content)?;
 
         // Cleanup
         let _ = fs::remove_file(&json_path).await;
 
+        if is_auto_language(&self.config.transcription.language) {
+            if let Some(p) = whisper_output
+                .language_probability
+                .filter(|&p| p < LOW_LANGUAGE_CONFIDENCE)
+            {
+                warn!(
+                    "Detected language '{}' with low confidence ({:.0}%); set the language explicitly if it's wrong",
+                    whisper_output.language,
+                    p * 100.0
+                );
+            }
+        } else {
+            // Forced language: nothing was detected
+            whisper_output.language_probability = None;
+        }
+
         Ok(whisper_output)
     }
 
@@ -391,6 +488,19 @@ impl AnnotationPipeline {
 
                 let mut entry = SubtitleEntry::new(start_ms, end_ms, text);
 
+                if self.config.karaoke && !seg.words.is_empty() {
+                    entry = entry.with_words(
+                        seg.words
+                            .iter()
+                            .map(|w| SubtitleWord {
+                                start_ms: (w.start * 1000.0) as u64,
+                                end_ms: (w.end * 1000.0) as u64,
+                                text: w.word.clone(),
+                            })
+                            .collect(),
+                    );
+                }
+
                 if let Some(ref speaker) = seg.speaker {
                     entry = entry.with_speaker(speaker.clone());
                 }
@@ -468,14 +578,15 @@ impl AnnotationPipeline {
             info!("Analysis overlay enabled (placeholder)");
         }
 
-        // Step 5: Generate combined ASS file
+        // Step 5: Generate combined ASS file with a font for the detected language
+        let compositor = self.compositor_for_language(&whisper_output.language)?;
         let ass_path = self
             .config
             .temp_dir
             .join(format!("{}.ass", uuid::Uuid::new_v4()));
 
         if self.config.subtitles {
-            self.compositor
+            compositor
                 .generate_combined_ass(&subtitles, &overlay_tracks, &ass_path)
                 .await?;
             info!("Generated ASS subtitle file");
@@ -483,7 +594,7 @@ impl AnnotationPipeline {
 
         // Step 6: Composite video
         info!("Compositing video with overlays...");
-        self.compositor
+        compositor
             .composite_to_file(
                 input,
                 output_path,
@@ -514,6 +625,7 @@ impl AnnotationPipeline {
             transcript: whisper_output.text,
             subtitle_count: subtitles.len(),
             detected_language: Some(whisper_output.language),
+            language_confidence: whisper_output.language_probability,
             speakers,
             analysis_results: HashMap::new(),
             output_path: Some(output_path.to_path_buf()),
@@ -552,19 +664,20 @@ impl AnnotationPipeline {
             }
         }
 
+        let compositor = self.compositor_for_language(&whisper_output.language)?;
         let ass_path = self
             .config
             .temp_dir
             .join(format!("{}.ass", uuid::Uuid::new_v4()));
 
         if self.config.subtitles {
-            self.compositor
+            compositor
                 .generate_combined_ass(&subtitles, &overlay_tracks, &ass_path)
                 .await?;
         }
 
         // Stream output
-        self.compositor
+        compositor
             .composite_to_stream(
                 input,
                 if self.config.subtitles {
@@ -593,6 +706,7 @@ impl AnnotationPipeline {
             transcript: whisper_output.text,
             subtitle_count: subtitles.len(),
             detected_language: Some(whisper_output.language),
+            language_confidence: whisper_output.language_probability,
             speakers,
             analysis_results: HashMap::new(),
             output_path: None,
@@ -600,45 +714,80 @@ impl AnnotationPipeline {
         })
     }
 
-    /// Generate subtitles only (no video compositing)
+    /// Generate subtitles only (no video compositing).
+    ///
+    /// The format follows the output extension (`.srt`, `.vtt`, `.ass`);
+    /// unknown extensions get ASS.
     pub async fn generate_subtitles_only(
         &self,
         input: &str,
         output_path: &Path,
     ) -> Result<PipelineResult> {
         let start_time = std::time::Instant::now();
-        let input_path = Path::new(input);
 
-        info!("Generating subtitles for {:?}", input_path);
-
-        let audio_path = self.extract_audio(input_path).await?;
-        let whisper_output = self.transcribe_audio(&audio_path).await?;
-        let _ = fs::remove_file(&audio_path).await;
+        info!("Generating subtitles for {:?}", Path::new(input));
+
+        let transcript = self.transcribe_only(input).await?;
+        let subtitles = &transcript.entries;
+
+        let format = SubtitleFormat::from_path(output_path).unwrap_or(SubtitleFormat::Ass);
+        let content = match format {
+            SubtitleFormat::Srt if self.config.speaker_labels => SrtGenerator::new()
+                .with_speaker_labels()
+                .generate(subtitles)?,
+            SubtitleFormat::Srt => SrtGenerator::new().generate(subtitles)?,
+            SubtitleFormat::Vtt if self.config.speaker_labels => VttGenerator::new()
+                .with_speaker_labels()
+                .generate(subtitles)?,
+            SubtitleFormat::Vtt => VttGenerator::new().generate(subtitles)?,
+            SubtitleFormat::Ass => AssGenerator::new().generate(subtitles)?,
+        };
+        fs::write(output_path, content).await?;
+        info!(
+            "Wrote {} {} entries to {:?}",
+            subtitles.len(),
+            format.extension(),
+            output_path
+        );
 
-        let subtitles = self.whisper_to_subtitles(&whisper_output);
+        let elapsed = start_time.elapsed().as_secs_f64();
 
-        // Generate ASS file
-        let generator = AssGenerator::new();
-        generator.write_to_file(&subtitles, output_path).await?;
+        Ok(PipelineResult {
+            subtitle_count: subtitles.len(),
+            transcript: transcript.text,
+            detected_language: Some(transcript.language),
+            language_confidence: transcript.language_confidence,
+            speakers: transcript.speakers,
+            analysis_results: HashMap::new(),
+            output_path: Some(output_path.to_path_buf()),
+            processing_time_secs: elapsed,
+        })
+    }
 
-        let elapsed = start_time.elapsed().as_secs_f64();
+    /// Extract audio and transcribe it, returning timed segments.
+    ///
+    /// Runs ffmpeg only for audio extraction; no overlays or compositing.
+    pub async fn transcribe_only(&self, input: &str) -> Result<TranscriptResult> {
+        let audio_path = self.extract_audio(Path::new(input)).await?;
+        let whisper_output = self.transcribe_audio(&audio_path).await;
+        let _ = fs::remove_file(&audio_path).await;
+        let whisper_output = whisper_output?;
 
-        let speakers: Vec<String> = whisper_output
-            .segments
+        let entries = self.whisper_to_subtitles(&whisper_output);
+        let mut speakers: Vec<String> = entries
             .iter()
-            .filter_map(|s| s.speaker.clone())
+            .filter_map(|e| e.speaker.clone())
             .collect::<std::collections::HashSet<_>>()
             .into_iter()
             .collect();
+        speakers.sort();
 
-        Ok(PipelineResult {
-            transcript: whisper_output.text,
-            subtitle_count: subtitles.len(),
-            detected_language: Some(whisper_output.language),
+        Ok(TranscriptResult {
+            text: whisper_output.text.trim().to_string(),
+            language: whisper_output.language,
+            language_confidence: whisper_output.language_probability,
+            entries,
             speakers,
-            analysis_results: HashMap::new(),
-            output_path: Some(output_path.to_path_buf()),
-            processing_time_secs: elapsed,
         })
     }
 }
@@ -646,6 +795,8 @@ impl AnnotationPipeline {
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(unix)]
+    use crate::test_support::write_stub;
 
     #[test]
     fn test_transcription_config_fast() {
@@ -669,6 +820,207 @@ mod tests {
         assert_eq!(config.compositor.output_format, CompositorOutput::MpegTs);
     }
 
+    /// Pipeline wired to stub ffmpeg/whisper scripts in a fresh temp dir.
+    ///
+    /// The ffmpeg stub logs each invocation to `ffmpeg.log` and creates its
+    /// output file; the whisper stub logs its arguments to `whisper.log` and
+    /// writes a canned two-segment transcript.
+    #[cfg(unix)]
+    fn stub_pipeline(name: &str) -> (AnnotationPipeline, PathBuf) {
+        stub_pipeline_with(name, |_| {})
+    }
+
+    #[cfg(unix)]
+    fn stub_pipeline_with(
+        name: &str,
+        configure: impl FnOnce(&mut PipelineConfig),
+    ) -> (AnnotationPipeline, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("nab_{name}_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let ffmpeg = dir.join("ffmpeg");
+        write_stub(
+            &ffmpeg,
+            &format!(
+                "echo \"$@\" >> '{}'\nfor last; do :; done\n: > \"$last\"\n",
+                dir.join("ffmpeg.log").display()
+            ),
+        );
+
+        let whisper = dir.join("whisper");
+        write_stub(
+            &whisper,
+            &format!(
+                "echo \"$@\" >> '{}'\n{}",
+                dir.join("whisper.log").display(),
+                r#"audio="$1"
+while [ $# -gt 0 ]; do
+  if [ "$1" = "--output_dir" ]; then dir="$2"; fi
+  shift
+done
+stem=$(basename "$audio" .wav)
+cat > "$dir/$stem.json" <<'JSON'
+{"text": " Hello there. General Kenobi.", "language": "en", "language_probability": 0.42, "segments": [
+  {"id": 0, "start": 0.0, "end": 1.5, "text": " Hello there.", "speaker": "SPEAKER_00"},
+  {"id": 1, "start": 1.5, "end": 3.25, "text": " General Kenobi."}
+]}
+JSON
+"#
+            ),
+        );
+
+        let mut config = PipelineConfig {
+            temp_dir: dir.join("tmp"),
+            ..Default::default()
+        };
+        config.transcription.whisper_path = whisper.to_string_lossy().to_string();
+        config.compositor.ffmpeg_path = ffmpeg.to_string_lossy().to_string();
+        configure(&mut config);
+        (AnnotationPipeline::new(config).unwrap(), dir)
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_transcribe_only_returns_timed_entries() {
+        let (pipeline, dir) = stub_pipeline("transcribe_only");
+
+        let transcript = pipeline.transcribe_only("input.mp4").await.unwrap();
+
+        assert_eq!(transcript.text, "Hello there. General Kenobi.");
+        assert_eq!(transcript.language, "en");
+        assert_eq!(transcript.speakers, vec!["SPEAKER_00".to_string()]);
+        assert_eq!(transcript.entries.len(), 2);
+        assert_eq!(transcript.entries[0].start_ms, 0);
+        assert_eq!(transcript.entries[0].end_ms, 1500);
+        assert_eq!(transcript.entries[0].text, "Hello there.");
+        assert_eq!(transcript.entries[0].speaker.as_deref(), Some("SPEAKER_00"));
+        assert_eq!(transcript.entries[1].start_ms, 1500);
+        assert_eq!(transcript.entries[1].end_ms, 3250);
+        assert_eq!(transcript.entries[1].text, "General Kenobi.");
+
+        // The extracted audio is cleaned up
+        assert_eq!(std::fs::read_dir(dir.join("tmp")).unwrap().count(), 0);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_detected_language_confidence_is_reported() {
+        let (pipeline, dir) = stub_pipeline("lang_detect");
+
+        let transcript = pipeline.transcribe_only("input.mp4").await.unwrap();
+        assert_eq!(transcript.language, "en");
+        assert_eq!(transcript.language_confidence, Some(0.42));
+
+        let result = pipeline
+            .generate_subtitles_only("input.mp4", &dir.join("out.srt"))
+            .await
+            .unwrap();
+        assert_eq!(result.detected_language.as_deref(), Some("en"));
+        assert!(result.language_uncertain());
+
+        let args = std::fs::read_to_string(dir.join("whisper.log")).unwrap();
+        assert!(!args.contains("--language"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_language_override_is_passed_to_whisper() {
+        let (pipeline, dir) = stub_pipeline_with("lang_override", |config| {
+            config.transcription.language = "fi".to_string();
+        });
+
+        let transcript = pipeline.transcribe_only("input.mp4").await.unwrap();
+        assert_eq!(transcript.language_confidence, None);
+
+        let args = std::fs::read_to_string(dir.join("whisper.log")).unwrap();
+        assert!(args.contains("--language fi"), "{args}");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_subtitles_only_writes_vtt_without_compositing() {
+        let (pipeline, dir) = stub_pipeline("subs_only");
+        let ffmpeg_log = dir.join("ffmpeg.log");
+
+        let out = dir.join("out.vtt");
+        let result = pipeline
+            .generate_subtitles_only("input.mp4", &out)
+            .await
+            .unwrap();
+
+        assert_eq!(result.subtitle_count, 2);
+        assert_eq!(result.detected_language.as_deref(), Some("en"));
+        let vtt = std::fs::read_to_string(&out).unwrap();
+        assert!(vtt.starts_with("WEBVTT"));
+        assert!(vtt.contains("00:00:00.000 --> 00:00:01.500\n<v SPEAKER_00>Hello there."));
+        assert!(vtt.contains("00:00:01.500 --> 00:00:03.250\nGeneral Kenobi."));
+
+        // ffmpeg ran once, for audio extraction only: no compositing pass
+        let log = std::fs::read_to_string(&ffmpeg_log).unwrap();
+        assert_eq!(log.lines().count(), 1);
+        assert!(log.contains("-vn"));
+        assert!(!log.contains(".ass"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_whisper_words_attached_only_with_karaoke() {
+        let whisper: WhisperOutput = serde_json::from_str(
+            r#"{"text": " Hi there", "language": "en", "segments": [
+                {"id": 0, "start": 0.5, "end": 1.5, "text": " Hi there", "words": [
+                    {"word": " Hi", "start": 0.5, "end": 0.8, "probability": 0.9},
+                    {"word": " there", "start": 0.9, "end": 1.4, "probability": 0.95}
+                ]}
+            ]}"#,
+        )
+        .unwrap();
+
+        let temp_dir = std::env::temp_dir().join("nab_annotate_test");
+        let plain = AnnotationPipeline::new(PipelineConfig {
+            temp_dir: temp_dir.clone(),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(plain.whisper_to_subtitles(&whisper)[0].words.is_empty());
+
+        let karaoke = AnnotationPipeline::new(
+            PipelineConfig {
+                temp_dir,
+                ..Default::default()
+            }
+            .with_karaoke(true),
+        )
+        .unwrap();
+        let entries = karaoke.whisper_to_subtitles(&whisper);
+        assert_eq!(entries[0].words.len(), 2);
+        assert_eq!(entries[0].words[1].start_ms, 900);
+        assert_eq!(entries[0].words[1].text, " there");
+    }
+
+    #[test]
+    fn test_compositor_keeps_configured_font() {
+        let font = crate::annotate::ResolvedFont {
+            family: "Noto Sans CJK JP".to_string(),
+            path: PathBuf::from("/fonts/NotoSansCJK-Regular.ttc"),
+        };
+        let mut config = PipelineConfig {
+            temp_dir: std::env::temp_dir().join("nab_annotate_test"),
+            ..Default::default()
+        };
+        config.compositor = config.compositor.with_font(font);
+        let pipeline = AnnotationPipeline::new(config).unwrap();
+
+        // A preconfigured font skips system discovery, whatever the language
+        assert!(pipeline.compositor_for_language("ja").is_ok());
+    }
+
     #[test]
     fn test_pipeline_config_high_quality() {
         let config = PipelineConfig::high_quality();
@@ -1,8 +1,6 @@
 //! Subtitle generation from transcripts
 //!
-//! Supports SRT and ASS formats with styling options.
-
-#![allow(dead_code)] // VTT format support reserved for future
+//! Supports SRT, ASS and `WebVTT` formats with styling options.
 
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
@@ -33,6 +31,18 @@ impl SubtitleFormat {
             Self::Vtt => "vtt",
         }
     }
+
+    /// Detect the format from a file extension (`.srt`, `.ass`/`.ssa`, `.vtt`)
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "srt" => Some(Self::Srt),
+            "ass" | "ssa" => Some(Self::Ass),
+            "vtt" => Some(Self::Vtt),
+            _ => None,
+        }
+    }
 }
 
 /// A single subtitle entry with timing and text
@@ -48,6 +58,20 @@ pub struct SubtitleEntry {
     pub speaker: Option<String>,
     /// Optional style name (for ASS format)
     pub style: Option<String>,
+    /// Word-level timings; when present, ASS output highlights each word karaoke-style
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<SubtitleWord>,
+}
+
+/// A single word with its spoken time range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubtitleWord {
+    /// Start time in milliseconds
+    pub start_ms: u64,
+    /// End time in milliseconds
+    pub end_ms: u64,
+    /// Word text
+    pub text: String,
 }
 
 impl SubtitleEntry {
@@ -60,6 +84,7 @@ impl SubtitleEntry {
             text: text.into(),
             speaker: None,
             style: None,
+            words: Vec::new(),
         }
     }
 
@@ -77,6 +102,13 @@ impl SubtitleEntry {
         self
     }
 
+    /// Attach word-level timings (enables karaoke highlighting in ASS)
+    #[must_use]
+    pub fn with_words(mut self, words: Vec<SubtitleWord>) -> Self {
+        self.words = words;
+        self
+    }
+
     /// Format time as SRT timestamp (HH:MM:SS,mmm)
     fn format_srt_time(ms: u64) -> String {
         let hours = ms / 3_600_000;
@@ -333,6 +365,89 @@ impl SubtitleGenerator for SrtGenerator {
     }
 }
 
+/// `WebVTT` subtitle generator
+#[derive(Debug, Clone, Default)]
+pub struct VttGenerator {
+    /// Tag cues with `<v Speaker>` voice spans
+    pub include_speaker: bool,
+}
+
+impl VttGenerator {
+    /// Create a new `WebVTT` generator
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable speaker voice tags
+    #[must_use]
+    pub fn with_speaker_labels(mut self) -> Self {
+        self.include_speaker = true;
+        self
+    }
+}
+
+impl SubtitleGenerator for VttGenerator {
+    fn format(&self) -> SubtitleFormat {
+        SubtitleFormat::Vtt
+    }
+
+    fn generate(&self, entries: &[SubtitleEntry]) -> Result<String> {
+        let mut output = String::from("WEBVTT\n\n");
+
+        for entry in entries {
+            writeln!(
+                output,
+                "{} --> {}",
+                SubtitleEntry::format_vtt_time(entry.start_ms),
+                SubtitleEntry::format_vtt_time(entry.end_ms)
+            )?;
+
+            // Cue text may not contain "-->" or raw markup characters
+            let text = entry
+                .text
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;");
+
+            match entry.speaker {
+                Some(ref speaker) if self.include_speaker => {
+                    writeln!(output, "<v {speaker}>{text}")?;
+                }
+                _ => writeln!(output, "{text}")?,
+            }
+
+            writeln!(output)?;
+        }
+
+        Ok(output)
+    }
+
+    fn write_to_file<'a>(
+        &'a self,
+        entries: &'a [SubtitleEntry],
+        path: &'a Path,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(async move {
+            let content = self.generate(entries)?;
+            fs::write(path, content).await?;
+            Ok(())
+        })
+    }
+
+    fn write_to<'a, W: AsyncWrite + Unpin + Send + 'a>(
+        &'a self,
+        entries: &'a [SubtitleEntry],
+        writer: &'a mut W,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(async move {
+            let content = self.generate(entries)?;
+            writer.write_all(content.as_bytes()).await?;
+            Ok(())
+        })
+    }
+}
+
 /// ASS subtitle generator with rich styling support
 #[derive(Debug, Clone)]
 pub struct AssGenerator {
@@ -434,6 +549,44 @@ impl AssGenerator {
 
         header
     }
+
+    /// Escape special characters for ASS
+    fn escape_text(text: &str) -> String {
+        text.replace('\\', "\\\\")
+            .replace('{', "\\{")
+            .replace('}', "\\}")
+            .replace('\n', "\\N")
+    }
+
+    /// Dialogue text with a `\kf` sweep per word.
+    ///
+    /// Each word's duration runs from its start to the next word's start (the
+    /// first word from the cue start, the last to the cue end), measured in
+    /// centiseconds on the same grid as the cue timestamps, so the durations
+    /// sum exactly to the cue length.
+    fn karaoke_text(entry: &SubtitleEntry) -> String {
+        let mut boundaries: Vec<u64> = Vec::with_capacity(entry.words.len() + 1);
+        boundaries.push(entry.start_ms / 10);
+        for word in entry.words.iter().skip(1) {
+            let prev = *boundaries.last().unwrap_or(&0);
+            boundaries.push((word.start_ms / 10).clamp(prev, entry.end_ms / 10));
+        }
+        boundaries.push((entry.end_ms / 10).max(*boundaries.last().unwrap_or(&0)));
+
+        let mut text = String::new();
+        for (i, word) in entry.words.iter().enumerate() {
+            if i > 0 {
+                text.push(' ');
+            }
+            let centis = boundaries[i + 1] - boundaries[i];
+            let _ = write!(
+                text,
+                "{{\\kf{centis}}}{}",
+                Self::escape_text(word.text.trim())
+            );
+        }
+        text
+    }
 }
 
 impl SubtitleGenerator for AssGenerator {
@@ -448,13 +601,11 @@ impl SubtitleGenerator for AssGenerator {
             let style = entry.style.as_deref().unwrap_or("Default");
             let speaker = entry.speaker.as_deref().unwrap_or("");
 
-            // Escape special characters for ASS
-            let text = entry
-                .text
-                .replace('\\', "\\\\")
-                .replace('{', "\\{")
-                .replace('}', "\\}")
-                .replace('\n', "\\N");
+            let text = if entry.words.is_empty() {
+                Self::escape_text(&entry.text)
+            } else {
+                Self::karaoke_text(entry)
+            };
 
             writeln!(
                 output,
@@ -619,6 +770,33 @@ mod tests {
         assert!(output.contains("Dialogue: 0,0:00:00.00,0:00:02.00,Default,,0,0,0,,Hello, world!"));
     }
 
+    #[test]
+    fn test_ass_karaoke_durations() {
+        let word = |start_ms, end_ms, text: &str| SubtitleWord {
+            start_ms,
+            end_ms,
+            text: text.to_string(),
+        };
+        let entry = SubtitleEntry::new(1000, 3500, "Hello big world").with_words(vec![
+            word(1200, 1600, " Hello"),
+            word(1650, 2100, " big"),
+            word(2400, 3300, " world"),
+        ]);
+
+        let output = AssGenerator::new().generate(&[entry]).unwrap();
+        let line = output.lines().find(|l| l.starts_with("Dialogue:")).unwrap();
+
+        assert!(line.starts_with("Dialogue: 0,0:00:01.00,0:00:03.50,"));
+        assert!(line.ends_with(r"{\kf65}Hello {\kf75}big {\kf110}world"));
+
+        let total: u64 = line
+            .split(r"{\kf")
+            .skip(1)
+            .map(|part| part.split('}').next().unwrap().parse::<u64>().unwrap())
+            .sum();
+        assert_eq!(total, 250); // 2.5 s cue in centiseconds
+    }
+
     #[test]
     fn test_parse_srt() {
         let content = r"1
@@ -640,6 +818,39 @@ With multiple lines.
         assert_eq!(entries[1].text, "This is a test.\nWith multiple lines.");
     }
 
+    #[test]
+    fn test_vtt_generation() {
+        let gen = VttGenerator::new().with_speaker_labels();
+        let entries = vec![
+            SubtitleEntry::new(0, 2000, "Hello <world>").with_speaker("John"),
+            SubtitleEntry::new(3_661_500, 3_663_000, "Bye"),
+        ];
+
+        let output = gen.generate(&entries).unwrap();
+
+        assert!(output.starts_with("WEBVTT\n\n"));
+        assert!(output.contains("00:00:00.000 --> 00:00:02.000\n<v John>Hello &lt;world&gt;\n"));
+        assert!(output.contains("01:01:01.500 --> 01:01:03.000\nBye\n"));
+    }
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(
+            SubtitleFormat::from_path(Path::new("out.VTT")),
+            Some(SubtitleFormat::Vtt)
+        );
+        assert_eq!(
+            SubtitleFormat::from_path(Path::new("dir/out.srt")),
+            Some(SubtitleFormat::Srt)
+        );
+        assert_eq!(
+            SubtitleFormat::from_path(Path::new("out.ass")),
+            Some(SubtitleFormat::Ass)
+        );
+        assert_eq!(SubtitleFormat::from_path(Path::new("out.txt")), None);
+        assert_eq!(SubtitleFormat::from_path(Path::new("out")), None);
+    }
+
     #[test]
     fn test_srt_with_speaker() {
         let gen = SrtGenerator::new().with_speaker_labels();
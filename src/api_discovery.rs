@@ -19,7 +19,11 @@
 
 use anyhow::Result;
 use regex::Regex;
-use std::collections::HashSet;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeSet, HashSet};
+
+/// HTTP methods that map to OpenAPI operations
+const OPENAPI_METHODS: &[&str] = &["get", "put", "post", "delete", "options", "head", "patch"];
 
 /// Discovered API endpoint
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -274,6 +278,108 @@ impl ApiDiscovery {
     }
 }
 
+impl ApiDiscovery {
+    /// Build a minimal OpenAPI 3.1 document from discovered endpoints.
+    ///
+    /// Methods default to GET (POST for GraphQL), query strings become
+    /// optional string parameters, absolute URLs and `baseURL` configs
+    /// become `servers`. Each operation carries its `x-nab-score` rank.
+    #[must_use]
+    pub fn to_openapi(endpoints: &[ApiEndpoint]) -> Value {
+        let placeholder = url::Url::parse("http://relative.invalid").expect("valid base URL");
+        let mut servers = BTreeSet::new();
+        let mut paths: Map<String, Value> = Map::new();
+
+        for endpoint in endpoints {
+            let Ok(parsed) = placeholder.join(&endpoint.url) else {
+                continue;
+            };
+            let absolute = parsed.host_str() != placeholder.host_str();
+            if absolute {
+                servers.insert(parsed.origin().unicode_serialization());
+            }
+            if endpoint.source == "base_url" {
+                continue;
+            }
+
+            let method = match &endpoint.method {
+                Some(method) => method.to_lowercase(),
+                None if endpoint.url.contains("graphql") => "post".to_string(),
+                None => "get".to_string(),
+            };
+            if !OPENAPI_METHODS.contains(&method.as_str()) {
+                continue;
+            }
+
+            let path_item = paths
+                .entry(parsed.path().to_string())
+                .or_insert_with(|| json!({}));
+            let operation = path_item
+                .as_object_mut()
+                .expect("path items are objects")
+                .entry(method.clone())
+                .or_insert_with(|| {
+                    let mut op = json!({
+                        "x-nab-score": Self::score_endpoint(endpoint),
+                        "x-nab-source": endpoint.source,
+                        "parameters": [],
+                        "responses": { "200": { "description": "Discovered endpoint response" } },
+                    });
+                    if matches!(method.as_str(), "post" | "put" | "patch") {
+                        op["requestBody"] = json!({
+                            "content": { "application/json": { "schema": { "type": "object" } } }
+                        });
+                    }
+                    op
+                });
+
+            let score = Self::score_endpoint(endpoint);
+            if operation["x-nab-score"]
+                .as_i64()
+                .is_some_and(|s| i64::from(score) > s)
+            {
+                operation["x-nab-score"] = json!(score);
+            }
+
+            let parameters = operation["parameters"]
+                .as_array_mut()
+                .expect("parameters is an array");
+            for (name, value) in parsed.query_pairs() {
+                if parameters.iter().any(|p| p["name"] == name.as_ref()) {
+                    continue;
+                }
+                let mut parameter = json!({
+                    "name": name,
+                    "in": "query",
+                    "required": false,
+                    "schema": { "type": "string" },
+                });
+                if !value.is_empty() {
+                    parameter["example"] = json!(value);
+                }
+                parameters.push(parameter);
+            }
+        }
+
+        let mut doc = json!({
+            "openapi": "3.1.0",
+            "info": {
+                "title": "Discovered API",
+                "version": "0.0.0",
+                "description": "Endpoints discovered statically by nab; methods and parameters are inferred.",
+            },
+            "paths": paths,
+        });
+        if !servers.is_empty() {
+            doc["servers"] = servers
+                .into_iter()
+                .map(|url| json!({ "url": url }))
+                .collect();
+        }
+        doc
+    }
+}
+
 impl Default for ApiDiscovery {
     fn default() -> Self {
         Self::new().expect("Failed to create API discovery engine")
@@ -350,4 +456,48 @@ mod tests {
 
         assert!(ApiDiscovery::score_endpoint(&ep1) > ApiDiscovery::score_endpoint(&ep2));
     }
+
+    #[test]
+    fn test_openapi_export() {
+        let endpoint = |url: &str, method: Option<&str>| ApiEndpoint {
+            url: url.to_string(),
+            method: method.map(str::to_string),
+            source: "fetch".to_string(),
+        };
+        let endpoints = vec![
+            endpoint("/api/search?q=shoes&page=2", Some("GET")),
+            endpoint("/api/search?sort=price", None),
+            endpoint("/api/orders", Some("POST")),
+            endpoint("https://api.example.com/v1/items?limit=10", Some("GET")),
+        ];
+
+        let doc = ApiDiscovery::to_openapi(&endpoints);
+
+        assert_eq!(doc["openapi"], "3.1.0");
+        assert_eq!(doc["servers"][0]["url"], "https://api.example.com");
+
+        let paths = doc["paths"].as_object().unwrap();
+        assert_eq!(paths.len(), 3);
+
+        let search = &doc["paths"]["/api/search"];
+        assert!(search.get("post").is_none());
+        let names: Vec<_> = search["get"]["parameters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["q", "page", "sort"]);
+        assert_eq!(search["get"]["parameters"][0]["in"], "query");
+        assert_eq!(search["get"]["parameters"][0]["example"], "shoes");
+
+        let orders = &doc["paths"]["/api/orders"];
+        assert!(orders.get("get").is_none());
+        assert!(orders["post"]["parameters"].as_array().unwrap().is_empty());
+        assert!(orders["post"]["requestBody"].is_object());
+        assert!(search["get"]["x-nab-score"].as_i64() > orders["post"]["x-nab-score"].as_i64());
+
+        let items = &doc["paths"]["/v1/items"]["get"];
+        assert_eq!(items["parameters"][0]["name"], "limit");
+    }
 }
@@ -7,19 +7,41 @@
 //! - Browser cookie extraction (Brave, Chrome, Firefox, Safari)
 //! - WebAuthn/Passkey authentication
 
-use std::collections::HashMap;
-use std::process::Command;
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
 
+use crate::cookie_jar::domain_matches;
+
 /// OTP (One-Time Password) with source information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct OtpCode {
     pub code: String,
     pub source: OtpSource,
     pub expires_in_seconds: Option<u32>,
+    /// When the SMS or email carrying the code arrived, if the message says
+    #[serde(default)]
+    pub received_at: Option<DateTime<Utc>>,
+}
+
+// The code is a live credential; keep it out of debug logs
+impl std::fmt::Debug for OtpCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OtpCode")
+            .field("code", &"[redacted]")
+            .field("source", &self.source)
+            .field("expires_in_seconds", &self.expires_in_seconds)
+            .field("received_at", &self.received_at)
+            .finish()
+    }
 }
 
 /// Source of the OTP code
@@ -59,6 +81,83 @@ pub struct Credential {
     pub passkey_credential_id: Option<String>,
 }
 
+/// Passkey stored in 1Password, with what's needed to use or audit it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Passkey {
+    /// 1Password item title
+    pub title: String,
+    /// Relying party ID (the domain the passkey is registered for)
+    pub rp_id: String,
+    pub credential_id: String,
+    /// `WebAuthn` user handle, if 1Password exposes it
+    pub user_handle: Option<String>,
+    pub username: Option<String>,
+    pub public_key: Option<String>,
+    /// Creation time as reported by 1Password (RFC 3339)
+    pub created: Option<String>,
+}
+
+impl Passkey {
+    /// Whether the passkey can sign in to `domain` (a host or URL)
+    ///
+    /// As in `WebAuthn`, a passkey for `example.com` also serves its
+    /// subdomains, such as `login.example.com`.
+    pub fn matches_domain(&self, domain: &str) -> bool {
+        let host = url::Url::parse(domain)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+            .unwrap_or_else(|| domain.to_string())
+            .trim_end_matches('.')
+            .to_lowercase();
+        domain_matches(&host, &self.rp_id)
+    }
+
+    /// Passkey details from an `op item get --format=json` item, if it holds one
+    fn from_op_item(item: &OpItem) -> Option<Self> {
+        // Field labels vary ("Credential ID", "credentialId"), so compare them
+        // without case, spaces or punctuation
+        let normalize = |label: &str| {
+            label
+                .chars()
+                .filter(char::is_ascii_alphanumeric)
+                .collect::<String>()
+                .to_ascii_lowercase()
+        };
+        let fields = item.fields.as_deref().unwrap_or_default();
+        let field = |names: &[&str]| {
+            fields
+                .iter()
+                .filter(|field| field.value.as_deref().is_some_and(|v| !v.is_empty()))
+                .find(|field| {
+                    let label = normalize(field.label.as_deref().unwrap_or(&field.id));
+                    names.contains(&label.as_str())
+                })
+                .and_then(|field| field.value.clone())
+        };
+
+        let credential_id = field(&["credentialid", "passkeycredentialid", "passkey"])?;
+        let rp_id = field(&["rpid", "relyingparty", "relyingpartyid", "site"]).or_else(|| {
+            let urls = item.urls.as_deref().unwrap_or_default();
+            urls.iter()
+                .find(|u| u.primary.unwrap_or(false))
+                .or_else(|| urls.first())
+                .and_then(|u| url::Url::parse(&u.href).ok())
+                .and_then(|u| u.host_str().map(str::to_string))
+        })?;
+
+        Some(Self {
+            title: item.title.clone(),
+            rp_id: rp_id.trim_end_matches('.').to_lowercase(),
+            credential_id,
+            user_handle: field(&["userhandle", "userid"]),
+            username: field(&["username", "email"]),
+            public_key: field(&["publickey"]),
+            created: field(&["created", "createdat", "creationdate"])
+                .or_else(|| item.created_at.clone()),
+        })
+    }
+}
+
 /// 1Password item structure (from `op item get --format=json`)
 #[derive(Debug, Deserialize)]
 struct OpItem {
@@ -69,6 +168,8 @@ struct OpItem {
     category: String,
     urls: Option<Vec<OpUrl>>,
     fields: Option<Vec<OpField>>,
+    #[serde(default)]
+    created_at: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -158,8 +259,8 @@ impl OnePasswordAuth {
         Ok(None)
     }
 
-    /// Get full item details by ID
-    fn get_item_details(&self, item_id: &str) -> Result<Option<Credential>> {
+    /// Fetch an item by ID with `op item get`
+    fn get_item(item_id: &str) -> Result<Option<OpItem>> {
         debug!("Getting 1Password item details: {}", item_id);
 
         let output = Command::new("op")
@@ -173,8 +274,16 @@ impl OnePasswordAuth {
             return Ok(None);
         }
 
-        let item: OpItem =
-            serde_json::from_slice(&output.stdout).context("Failed to parse 1Password item")?;
+        serde_json::from_slice(&output.stdout)
+            .map(Some)
+            .context("Failed to parse 1Password item")
+    }
+
+    /// Get full item details by ID
+    fn get_item_details(&self, item_id: &str) -> Result<Option<Credential>> {
+        let Some(item) = Self::get_item(item_id)? else {
+            return Ok(None);
+        };
 
         let mut username = None;
         let mut password = None;
@@ -258,6 +367,7 @@ impl OnePasswordAuth {
                     code,
                     source: OtpSource::OnePasswordTotp,
                     expires_in_seconds: Some(30), // TOTP typically 30 seconds
+                    received_at: None,
                 }));
             }
         }
@@ -278,6 +388,7 @@ impl OnePasswordAuth {
                     code,
                     source: OtpSource::OnePasswordTotp,
                     expires_in_seconds: Some(30),
+                    received_at: None,
                 }));
             }
         }
@@ -286,7 +397,7 @@ impl OnePasswordAuth {
     }
 
     /// List all available passkeys
-    pub fn list_passkeys(&self) -> Result<Vec<Credential>> {
+    pub fn list_passkeys(&self) -> Result<Vec<Passkey>> {
         let mut cmd = Command::new("op");
         cmd.args(["item", "list", "--categories=Passkey", "--format=json"]);
 
@@ -304,13 +415,21 @@ impl OnePasswordAuth {
 
         let mut passkeys = Vec::new();
         for item in items {
-            if let Ok(Some(cred)) = self.get_item_details(&item.id) {
-                passkeys.push(cred);
+            if let Ok(Some(item)) = Self::get_item(&item.id) {
+                passkeys.extend(Passkey::from_op_item(&item));
             }
         }
 
         Ok(passkeys)
     }
+
+    /// Passkeys usable on `domain` (a host or URL), including ones registered
+    /// for a parent domain
+    pub fn list_passkeys_for_domain(&self, domain: &str) -> Result<Vec<Passkey>> {
+        let mut passkeys = self.list_passkeys()?;
+        passkeys.retain(|passkey| passkey.matches_domain(domain));
+        Ok(passkeys)
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -320,13 +439,476 @@ struct OpListItem {
     urls: Option<Vec<OpUrl>>,
 }
 
+/// Bitwarden item structure (from `bw list items`)
+#[derive(Debug, Deserialize)]
+struct BwItem {
+    id: String,
+    name: String,
+    login: Option<BwLogin>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BwLogin {
+    username: Option<String>,
+    password: Option<String>,
+    /// TOTP seed (`otpauth://` URI or base32 secret), not a code
+    totp: Option<String>,
+    uris: Option<Vec<BwUri>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BwUri {
+    uri: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BwStatus {
+    status: String,
+}
+
+/// Bitwarden CLI wrapper
+pub struct BitwardenAuth {
+    /// Session token from `bw unlock --raw`
+    session: Option<String>,
+}
+
+impl BitwardenAuth {
+    /// Create new Bitwarden auth with an optional session token
+    #[must_use]
+    pub fn new(session: Option<String>) -> Self {
+        Self { session }
+    }
+
+    /// Use the session token in `BW_SESSION`, if set
+    #[must_use]
+    pub fn from_env() -> Self {
+        Self::new(std::env::var("BW_SESSION").ok().filter(|s| !s.is_empty()))
+    }
+
+    /// Check if the Bitwarden CLI is installed and the vault is unlocked
+    #[must_use]
+    pub fn is_available() -> bool {
+        Command::new("bw")
+            .arg("status")
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .and_then(|o| serde_json::from_slice::<BwStatus>(&o.stdout).ok())
+            .is_some_and(|status| status.status == "unlocked")
+    }
+
+    /// `bw` command with the session token appended to `args`
+    fn command(&self, args: &[&str]) -> Command {
+        let mut cmd = Command::new("bw");
+        cmd.args(args);
+        if let Some(ref session) = self.session {
+            cmd.args(["--session", session]);
+        }
+        cmd
+    }
+
+    /// Get credential for a URL/domain
+    pub fn get_credential_for_url(&self, url: &str) -> Result<Option<Credential>> {
+        let domain = url::Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(std::string::ToString::to_string))
+            .unwrap_or_else(|| url.to_string());
+
+        debug!("Searching Bitwarden for domain: {}", domain);
+
+        let output = self
+            .command(&["list", "items", "--search", &domain])
+            .output()
+            .context("Failed to run 'bw list items'")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            warn!("Bitwarden search failed: {}", stderr);
+            return Ok(None);
+        }
+
+        let items: Vec<BwItem> =
+            serde_json::from_slice(&output.stdout).context("Failed to parse Bitwarden items")?;
+
+        let Some((id, mut credential)) = Self::credential_from_items(items, &domain) else {
+            return Ok(None);
+        };
+        if credential.has_totp {
+            credential.totp = self.get_totp_code(&id).ok().flatten();
+        }
+        Ok(Some(credential))
+    }
+
+    /// Login item for `domain` and its ID: the first with a URI on the domain
+    /// (or a parent of it)
+    ///
+    /// `bw list items --search` also matches names and notes, so a login whose
+    /// URIs are all elsewhere is never used. The TOTP code is left empty;
+    /// `has_totp` says whether the item has a seed.
+    fn credential_from_items(items: Vec<BwItem>, domain: &str) -> Option<(String, Credential)> {
+        let domain = domain.to_lowercase();
+        let uri_matches = |item: &BwItem| {
+            item.login
+                .as_ref()
+                .and_then(|login| login.uris.as_deref())
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|u| u.uri.as_deref())
+                .filter_map(|u| url::Url::parse(u).ok())
+                .filter_map(|u| u.host_str().map(str::to_lowercase))
+                .any(|host| domain_matches(&domain, &host))
+        };
+
+        let item = items.into_iter().find(uri_matches)?;
+        let login = item.login?;
+
+        Some((
+            item.id,
+            Credential {
+                title: item.name,
+                username: login.username,
+                password: login.password,
+                url: login
+                    .uris
+                    .and_then(|uris| uris.into_iter().find_map(|u| u.uri)),
+                totp: None,
+                has_totp: login.totp.is_some_and(|seed| !seed.is_empty()),
+                passkey_credential_id: None,
+            },
+        ))
+    }
+
+    /// Get current TOTP code for an item (internal)
+    fn get_totp_code(&self, item_id: &str) -> Result<Option<String>> {
+        let output = self
+            .command(&["get", "totp", item_id])
+            .output()
+            .context("Failed to get TOTP")?;
+
+        if output.status.success() {
+            let code = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if !code.is_empty() {
+                return Ok(Some(code));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Built-in OTP keywords: language code → lowercase words. Matched as
+/// substrings, so compounds like "Bestätigungscode" or "vahvistuskoodi" count.
+const DEFAULT_OTP_KEYWORDS: &[(&str, &[&str])] = &[
+    (
+        "en",
+        &["code", "otp", "verification", "passcode", "one-time"],
+    ),
+    (
+        "de",
+        &[
+            "code",
+            "bestätigung",
+            "sicherheitscode",
+            "einmalpasswort",
+            "kennwort",
+        ],
+    ),
+    (
+        "fi",
+        &[
+            "koodi",
+            "vahvistus",
+            "varmennus",
+            "tunnusluku",
+            "kertakäyttö",
+        ],
+    ),
+    ("sv", &["kod", "verifiering", "bekräftelse", "engångs"]),
+    (
+        "fr",
+        &["code", "vérification", "confirmation", "usage unique"],
+    ),
+    ("es", &["código", "verificación", "confirmación", "clave"]),
+];
+
+/// How far before a code a keyword may appear ("Ihr Code lautet 483920")
+const KEYWORD_WINDOW_CHARS: usize = 40;
+
+/// Words that mark a one-time code, per language
+///
+/// The default covers English, German, Finnish, Swedish, French and Spanish.
+/// `~/.config/nab/otp.toml` can add or replace languages:
+///
+/// ```toml
+/// [keywords]
+/// nl = ["code", "verificatie"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OtpKeywords {
+    languages: BTreeMap<String, Vec<String>>,
+}
+
+/// Top-level OTP configuration file
+#[derive(Debug, Default, Deserialize)]
+struct OtpFile {
+    #[serde(default)]
+    keywords: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for OtpKeywords {
+    fn default() -> Self {
+        DEFAULT_OTP_KEYWORDS
+            .iter()
+            .fold(Self::empty(), |keywords, (language, words)| {
+                keywords.with_language(*language, words.iter().copied())
+            })
+    }
+}
+
+impl OtpKeywords {
+    /// No keywords; extraction then relies on the bare 6-digit pattern
+    #[must_use]
+    pub fn empty() -> Self {
+        Self {
+            languages: BTreeMap::new(),
+        }
+    }
+
+    /// Defaults overlaid with `~/.config/nab/otp.toml`, if present
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn load() -> Result<Self> {
+        let path = dirs::config_dir()
+            .unwrap_or_else(|| std::path::PathBuf::from("."))
+            .join("nab")
+            .join("otp.toml");
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Self::default()
+            .merge_toml(&content)
+            .with_context(|| format!("invalid {}", path.display()))
+    }
+
+    /// Add or replace languages from an OTP config's `[keywords]` table
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for invalid TOML.
+    pub fn merge_toml(self, content: &str) -> Result<Self> {
+        let file: OtpFile = toml::from_str(content)?;
+        Ok(file
+            .keywords
+            .into_iter()
+            .fold(self, |keywords, (language, words)| {
+                keywords.with_language(language, words)
+            }))
+    }
+
+    /// Use `words` for `language`, replacing any previous list
+    #[must_use]
+    pub fn with_language<S: AsRef<str>>(
+        mut self,
+        language: impl Into<String>,
+        words: impl IntoIterator<Item = S>,
+    ) -> Self {
+        let words = words
+            .into_iter()
+            .map(|word| word.as_ref().trim().to_lowercase())
+            .filter(|word| !word.is_empty())
+            .collect();
+        self.languages.insert(language.into().to_lowercase(), words);
+        self
+    }
+
+    /// Every keyword across languages, without duplicates
+    pub fn words(&self) -> Vec<&str> {
+        let mut words: Vec<&str> = Vec::new();
+        for word in self.languages.values().flatten() {
+            if !words.contains(&word.as_str()) {
+                words.push(word);
+            }
+        }
+        words
+    }
+
+    /// `OR`-joined search terms, quoting multi-word keywords
+    fn search_terms(&self, prefix: &str) -> String {
+        self.words()
+            .iter()
+            .map(|word| {
+                let word = if word.contains(' ') {
+                    format!("\"{word}\"")
+                } else {
+                    (*word).to_string()
+                };
+                format!("{prefix}{word}")
+            })
+            .collect::<Vec<_>>()
+            .join(" OR ")
+    }
+
+    fn precedes(&self, text_before: &str) -> bool {
+        let window: String = text_before
+            .chars()
+            .rev()
+            .take(KEYWORD_WINDOW_CHARS)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect::<String>()
+            .to_lowercase();
+        self.words().iter().any(|word| window.contains(word))
+    }
+}
+
+/// How long a login waits for an SMS or email code
+pub(crate) const OTP_WAIT_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// How often a login re-queries SMS and email while waiting for a code
+pub(crate) const OTP_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
 /// Multi-source OTP retrieval
 pub struct OtpRetriever;
 
 impl OtpRetriever {
     /// Get OTP from all available sources for a domain
     /// Checks: 1Password TOTP → SMS (Beeper) → Email (Gmail)
+    ///
+    /// Messages are searched and scored with [`OtpKeywords::load`].
     pub fn get_otp_for_domain(domain: &str) -> Result<Option<OtpCode>> {
+        Self::get_otp_for_domain_with(domain, &Self::keywords())
+    }
+
+    /// Wait for an SMS or email code sent at or after `requested_at`
+    ///
+    /// A 1Password TOTP is returned straight away. Otherwise SMS (Beeper) and
+    /// email (Gmail) are re-queried every `poll_interval` until a fresh code
+    /// shows up (see [`poll_fresh_otp`](Self::poll_fresh_otp)). Returns
+    /// `None` once `timeout` elapses.
+    pub fn get_otp_for_domain_wait(
+        domain: &str,
+        requested_at: DateTime<Utc>,
+        timeout: Duration,
+        poll_interval: Duration,
+    ) -> Result<Option<OtpCode>> {
+        let op_auth = OnePasswordAuth::new(None);
+        if let Ok(Some(otp)) = op_auth.get_totp(&format!("https://{domain}")) {
+            info!("   ✅ Found TOTP in 1Password");
+            return Ok(Some(otp));
+        }
+
+        let keywords = Self::keywords();
+        info!("⏳ Waiting up to {timeout:?} for a new OTP for: {domain}");
+        Self::poll_fresh_otp(
+            requested_at,
+            timeout,
+            poll_interval,
+            &mut [&mut || Self::get_sms_otp(domain, &keywords), &mut || {
+                Self::get_email_otp(domain, &keywords)
+            }],
+        )
+    }
+
+    /// [`get_otp_for_domain_wait`](Self::get_otp_for_domain_wait) for one
+    /// message source, [`OtpSource::SmsBeeper`] or [`OtpSource::EmailGmail`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for a source that doesn't deliver codes by message.
+    pub fn wait_for_message_otp(
+        domain: &str,
+        source: &OtpSource,
+        requested_at: DateTime<Utc>,
+        timeout: Duration,
+        poll_interval: Duration,
+    ) -> Result<Option<OtpCode>> {
+        let keywords = Self::keywords();
+        info!("⏳ Waiting up to {timeout:?} for a new {source} OTP for: {domain}");
+        match source {
+            OtpSource::SmsBeeper => Self::poll_fresh_otp(
+                requested_at,
+                timeout,
+                poll_interval,
+                &mut [&mut || Self::get_sms_otp(domain, &keywords)],
+            ),
+            OtpSource::EmailGmail => Self::poll_fresh_otp(
+                requested_at,
+                timeout,
+                poll_interval,
+                &mut [&mut || Self::get_email_otp(domain, &keywords)],
+            ),
+            _ => anyhow::bail!("{source} codes are not sent by message"),
+        }
+    }
+
+    /// Query every source each `poll_interval` until one returns a code sent
+    /// at or after `requested_at`
+    ///
+    /// A code whose message carries no timestamp counts as fresh once it
+    /// differs from what its source returned on the first query. A failing
+    /// source is logged and polled again, without holding up the others.
+    /// Returns `None` when `timeout` elapses without a fresh code.
+    pub fn poll_fresh_otp(
+        requested_at: DateTime<Utc>,
+        timeout: Duration,
+        poll_interval: Duration,
+        sources: &mut [&mut dyn FnMut() -> Result<Option<OtpCode>>],
+    ) -> Result<Option<OtpCode>> {
+        let deadline = Instant::now() + timeout;
+        // Per source, the untimestamped code present before polling started
+        let mut stale: Vec<Option<String>> = vec![None; sources.len()];
+        let mut first = true;
+
+        loop {
+            for (source, stale) in sources.iter_mut().zip(stale.iter_mut()) {
+                let otp = match source() {
+                    Ok(otp) => otp,
+                    Err(e) => {
+                        warn!("OTP source failed, still waiting: {e:#}");
+                        continue;
+                    }
+                };
+                let Some(otp) = otp else { continue };
+                let fresh = match otp.received_at {
+                    Some(received_at) => received_at >= requested_at,
+                    None if first => {
+                        *stale = Some(otp.code.clone());
+                        false
+                    }
+                    None => stale.as_ref() != Some(&otp.code),
+                };
+                if fresh {
+                    return Ok(Some(otp));
+                }
+            }
+            first = false;
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Ok(None);
+            }
+            std::thread::sleep(poll_interval.min(remaining));
+        }
+    }
+
+    /// Keywords from [`OtpKeywords::load`], or the defaults
+    fn keywords() -> OtpKeywords {
+        OtpKeywords::load().unwrap_or_else(|e| {
+            warn!("Using default OTP keywords: {e:#}");
+            OtpKeywords::default()
+        })
+    }
+
+    /// [`get_otp_for_domain`](Self::get_otp_for_domain) with explicit keywords
+    pub fn get_otp_for_domain_with(
+        domain: &str,
+        keywords: &OtpKeywords,
+    ) -> Result<Option<OtpCode>> {
         info!("🔐 Searching for OTP codes for: {}", domain);
 
         // 1. Try 1Password TOTP first (fastest, most reliable)
@@ -337,13 +919,13 @@ impl OtpRetriever {
         }
 
         // 2. Try SMS via Beeper MCP
-        if let Ok(Some(otp)) = Self::get_sms_otp(domain) {
+        if let Ok(Some(otp)) = Self::get_sms_otp(domain, keywords) {
             info!("   ✅ Found SMS OTP via Beeper");
             return Ok(Some(otp));
         }
 
         // 3. Try Email via Gmail
-        if let Ok(Some(otp)) = Self::get_email_otp(domain) {
+        if let Ok(Some(otp)) = Self::get_email_otp(domain, keywords) {
             info!("   ✅ Found Email OTP via Gmail");
             return Ok(Some(otp));
         }
@@ -353,12 +935,13 @@ impl OtpRetriever {
     }
 
     /// Extract OTP from recent SMS messages via Beeper MCP
-    fn get_sms_otp(domain: &str) -> Result<Option<OtpCode>> {
+    fn get_sms_otp(domain: &str, keywords: &OtpKeywords) -> Result<Option<OtpCode>> {
         // Call mcp-cli to query Beeper for recent SMS
+        let query = keywords.search_terms(&format!("{domain} "));
         let output = Command::new("mcp-cli")
             .args([
                 "beeper/search_messages",
-                &format!(r#"{{"query": "{domain} code OR {domain} verification", "limit": 5}}"#),
+                &serde_json::json!({"query": query, "limit": 5}).to_string(),
             ])
             .output();
 
@@ -366,11 +949,14 @@ impl OtpRetriever {
             if output.status.success() {
                 let response = String::from_utf8_lossy(&output.stdout);
                 // Extract OTP code from message (6-digit pattern)
-                if let Some(code) = Self::extract_otp_from_text(&response) {
+                if let Some((code, received_at)) =
+                    Self::extract_otp_from_response(&response, keywords)
+                {
                     return Ok(Some(OtpCode {
                         code,
                         source: OtpSource::SmsBeeper,
                         expires_in_seconds: Some(300), // SMS codes typically 5 minutes
+                        received_at,
                     }));
                 }
             }
@@ -380,14 +966,16 @@ impl OtpRetriever {
     }
 
     /// Extract OTP from recent emails via Gmail API
-    fn get_email_otp(domain: &str) -> Result<Option<OtpCode>> {
+    fn get_email_otp(domain: &str, keywords: &OtpKeywords) -> Result<Option<OtpCode>> {
         // Call mcp-cli to query Gmail for recent verification emails
+        let query = format!(
+            "from:{domain} subject:({}) newer_than:10m",
+            keywords.search_terms("")
+        );
         let output = Command::new("mcp-cli")
             .args([
                 "gmail/search_emails",
-                &format!(
-                    r#"{{"query": "from:{domain} subject:(code OR verification OR OTP) newer_than:10m", "max_results": 5}}"#
-                ),
+                &serde_json::json!({"query": query, "max_results": 5}).to_string(),
             ])
             .output();
 
@@ -395,11 +983,14 @@ impl OtpRetriever {
             if output.status.success() {
                 let response = String::from_utf8_lossy(&output.stdout);
                 // Extract OTP code from email body
-                if let Some(code) = Self::extract_otp_from_text(&response) {
+                if let Some((code, received_at)) =
+                    Self::extract_otp_from_response(&response, keywords)
+                {
                     return Ok(Some(OtpCode {
                         code,
                         source: OtpSource::EmailGmail,
                         expires_in_seconds: Some(600), // Email codes typically 10 minutes
+                        received_at,
                     }));
                 }
             }
@@ -408,54 +999,331 @@ impl OtpRetriever {
         Ok(None)
     }
 
-    /// Extract 6-digit OTP code from text
-    #[cfg_attr(test, allow(dead_code))]
-    pub(crate) fn extract_otp_from_text(text: &str) -> Option<String> {
-        // Common OTP patterns:
-        // - 6 digits: 123456
-        // - 6 digits with spaces: 123 456
-        // - 6 digits with dash: 123-456
-        // - 8 digits for some services
+    /// Extract an OTP code from an SMS/email search response, with the
+    /// arrival time of the message it came from
+    ///
+    /// JSON responses are searched message by message, newest first; any
+    /// other response is searched as text and the time is unknown.
+    fn extract_otp_from_response(
+        response: &str,
+        keywords: &OtpKeywords,
+    ) -> Option<(String, Option<DateTime<Utc>>)> {
+        if let Ok(json) = serde_json::from_str::<serde_json::Value>(response) {
+            let mut messages = Vec::new();
+            collect_messages(&json, &mut messages);
+            messages.sort_by_key(|(received_at, _)| std::cmp::Reverse(*received_at));
+            let found = messages.iter().find_map(|(received_at, text)| {
+                Self::extract_otp_from_text(text, keywords).map(|code| (code, Some(*received_at)))
+            });
+            if found.is_some() {
+                return found;
+            }
+        }
+        Self::extract_otp_from_text(response, keywords).map(|code| (code, None))
+    }
+
+    /// Extract an OTP code from text
+    ///
+    /// Prefers a 6-8 digit code (also `123 456`/`123-456`) shortly after one
+    /// of `keywords`, then the first bare 6-digit code.
+    pub(crate) fn extract_otp_from_text(text: &str, keywords: &OtpKeywords) -> Option<String> {
         use std::sync::LazyLock;
-        static OTP_REGEX: LazyLock<regex::Regex> = LazyLock::new(|| {
-            regex::Regex::new(r"(?:code|otp|verification)[:\s]*(\d{6,8})|\b(\d{3}[-\s]?\d{3})\b")
+        static CANDIDATE_REGEX: LazyLock<regex::Regex> = LazyLock::new(|| {
+            regex::Regex::new(r"\b(\d{6,8}|\d{3}[-\s]\d{3})\b")
                 .expect("Static regex pattern should compile")
         });
 
-        if let Some(caps) = OTP_REGEX.captures(text) {
-            if let Some(code) = caps.get(1).or_else(|| caps.get(2)) {
-                let code_str = code.as_str().replace(['-', ' '], "");
-                if code_str.len() >= 6 {
-                    return Some(code_str);
+        let candidates: Vec<_> = CANDIDATE_REGEX
+            .find_iter(text)
+            .map(|m| (m.start(), m.as_str().replace(['-', ' '], "")))
+            .collect();
+
+        candidates
+            .iter()
+            .find(|(start, _)| keywords.precedes(&text[..*start]))
+            .or_else(|| candidates.iter().find(|(_, code)| code.len() == 6))
+            .map(|(_, code)| code.clone())
+    }
+}
+
+/// Fields a message search result may carry its arrival time in
+const MESSAGE_TIME_FIELDS: [&str; 8] = [
+    "timestamp",
+    "date",
+    "internalDate",
+    "received_at",
+    "receivedAt",
+    "sent_at",
+    "sentAt",
+    "time",
+];
+
+/// Objects in a search response that carry a timestamp, as the time and
+/// their string fields joined; objects without one are searched inside
+fn collect_messages(value: &serde_json::Value, messages: &mut Vec<(DateTime<Utc>, String)>) {
+    match value {
+        serde_json::Value::Object(fields) => {
+            let received_at = MESSAGE_TIME_FIELDS
+                .iter()
+                .find_map(|name| fields.get(*name).and_then(parse_message_time));
+            match received_at {
+                Some(received_at) => {
+                    let text: Vec<&str> = fields
+                        .iter()
+                        .filter(|(name, _)| !MESSAGE_TIME_FIELDS.contains(&name.as_str()))
+                        .filter_map(|(_, value)| value.as_str())
+                        .collect();
+                    messages.push((received_at, text.join("\n")));
+                }
+                None => {
+                    for value in fields.values() {
+                        collect_messages(value, messages);
+                    }
                 }
             }
         }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                collect_messages(item, messages);
+            }
+        }
+        _ => {}
+    }
+}
 
-        // Fallback: find any 6-digit sequence
-        static DIGIT_REGEX: LazyLock<regex::Regex> = LazyLock::new(|| {
-            regex::Regex::new(r"\b(\d{6})\b").expect("Static regex pattern should compile")
-        });
+/// RFC 3339 or RFC 2822 date, or Unix time in seconds or milliseconds
+/// (Gmail's `internalDate`)
+fn parse_message_time(value: &serde_json::Value) -> Option<DateTime<Utc>> {
+    let epoch = match value {
+        serde_json::Value::Number(n) => n.as_i64(),
+        serde_json::Value::String(s) => s.parse().ok(),
+        _ => None,
+    };
+    if let Some(epoch) = epoch {
+        // Seconds won't reach 10^11 until the year 5138
+        return if epoch >= 100_000_000_000 {
+            DateTime::from_timestamp_millis(epoch)
+        } else {
+            DateTime::from_timestamp(epoch, 0)
+        };
+    }
+    let text = value.as_str()?;
+    DateTime::parse_from_rfc3339(text)
+        .or_else(|_| DateTime::parse_from_rfc2822(text))
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+/// Per-browser limit in [`get_cookies_merged`]; a locked database must not
+/// hold up the other browsers
+const COOKIE_EXTRACTION_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+thread_local! {
+    /// When the extraction running on this thread is given up on
+    static EXTRACTION_DEADLINE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// Sets this thread's extraction deadline until dropped; blocking threads
+/// are pooled, so it must not outlive the extraction
+struct ExtractionDeadline;
+
+impl ExtractionDeadline {
+    fn set(deadline: Instant) -> Self {
+        EXTRACTION_DEADLINE.set(Some(deadline));
+        Self
+    }
+}
 
-        DIGIT_REGEX
-            .captures(text)
-            .and_then(|c| c.get(1))
-            .map(|m| m.as_str().to_string())
+impl Drop for ExtractionDeadline {
+    fn drop(&mut self) {
+        EXTRACTION_DEADLINE.set(None);
     }
 }
 
+/// `command.output()`, killing the child once the current extraction's
+/// deadline passes
+///
+/// [`get_cookies_merged`] stops waiting for a browser at its deadline, but a
+/// blocking thread can't be cancelled: killing the child is what stops a
+/// `sqlite3` stuck on a locked database. Outside a merged extraction this
+/// waits like `output()`.
+fn output_bounded(command: &mut Command) -> std::io::Result<Output> {
+    let Some(deadline) = EXTRACTION_DEADLINE.get() else {
+        return command.output();
+    };
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // Drained on their own threads so a full pipe can't block the child
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                "cookie extraction timed out; command killed",
+            ));
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    };
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    })
+}
+
+fn drain(pipe: Option<impl Read + Send + 'static>) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut bytes);
+        }
+        bytes
+    })
+}
+
 /// Cookie source for browser cookie extraction
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CookieSource {
     Brave,
     Chrome,
+    Edge,
     Firefox,
     Safari,
 }
 
+/// How one browser fared in [`get_cookies_merged`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CookieReport {
+    pub source: CookieSource,
+    /// Number of cookies found, or why extraction failed
+    pub outcome: std::result::Result<usize, String>,
+}
+
+/// Cookies gathered from several browsers
+#[derive(Debug, Clone, Default)]
+pub struct MergedCookies {
+    /// On name clashes, the browser listed first wins
+    pub cookies: HashMap<String, String>,
+    /// One entry per browser, in the order given
+    pub reports: Vec<CookieReport>,
+}
+
+impl MergedCookies {
+    /// Reports of browsers whose extraction failed
+    pub fn failures(&self) -> impl Iterator<Item = &CookieReport> {
+        self.reports.iter().filter(|report| report.outcome.is_err())
+    }
+}
+
+/// Extract cookies for `domain` from several browsers concurrently
+///
+/// Each browser runs on its own blocking thread; one that fails or times out
+/// is reported in [`MergedCookies::reports`] without affecting the others.
+pub async fn get_cookies_merged(sources: &[CookieSource], domain: &str) -> MergedCookies {
+    get_cookies_merged_with(sources, domain, |source, domain| source.get_cookies(domain)).await
+}
+
+/// [`get_cookies_merged`] with a custom per-browser extractor
+///
+/// Commands the extractor runs (`sqlite3`, `python3`, keychain lookups) are
+/// killed when its browser times out.
+pub async fn get_cookies_merged_with<F>(
+    sources: &[CookieSource],
+    domain: &str,
+    extract: F,
+) -> MergedCookies
+where
+    F: Fn(CookieSource, &str) -> Result<HashMap<String, String>> + Clone + Send + 'static,
+{
+    merge_cookies_within(sources, domain, COOKIE_EXTRACTION_TIMEOUT, extract).await
+}
+
+async fn merge_cookies_within<F>(
+    sources: &[CookieSource],
+    domain: &str,
+    timeout: Duration,
+    extract: F,
+) -> MergedCookies
+where
+    F: Fn(CookieSource, &str) -> Result<HashMap<String, String>> + Clone + Send + 'static,
+{
+    let tasks = sources.iter().map(|&source| {
+        let extract = extract.clone();
+        let domain = domain.to_string();
+        let deadline = Instant::now() + timeout;
+        let task = tokio::task::spawn_blocking(move || {
+            let _deadline = ExtractionDeadline::set(deadline);
+            extract(source, &domain)
+        });
+        async move {
+            let result = match tokio::time::timeout_at(deadline.into(), task).await {
+                Ok(Ok(result)) => result.map_err(|e| format!("{e:#}")),
+                Ok(Err(e)) => Err(format!("extraction panicked: {e}")),
+                Err(_) => Err(format!("timed out after {}s", timeout.as_secs_f32())),
+            };
+            (source, result)
+        }
+    });
+
+    let mut merged = MergedCookies::default();
+    for (source, result) in futures::future::join_all(tasks).await {
+        let outcome = match result {
+            Ok(cookies) => {
+                let count = cookies.len();
+                for (name, value) in cookies {
+                    merged.cookies.entry(name).or_insert(value);
+                }
+                Ok(count)
+            }
+            Err(e) => {
+                warn!("Cookie extraction from {source:?} failed: {e}");
+                Err(e)
+            }
+        };
+        merged.reports.push(CookieReport { source, outcome });
+    }
+    merged
+}
+
 impl CookieSource {
+    /// Every supported browser
+    pub const ALL: [CookieSource; 5] = [
+        Self::Brave,
+        Self::Chrome,
+        Self::Edge,
+        Self::Firefox,
+        Self::Safari,
+    ];
+
+    /// Cookie source for a browser name such as `--cookies edge`, ignoring
+    /// case; unknown names (other Chromium browsers, typically) read Chrome's
+    pub fn from_browser_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "brave" => Self::Brave,
+            "edge" => Self::Edge,
+            "firefox" => Self::Firefox,
+            "safari" => Self::Safari,
+            _ => Self::Chrome,
+        }
+    }
+
     /// Get the cookie database path for this browser
-    fn cookie_path(&self) -> Option<std::path::PathBuf> {
-        let home = dirs::home_dir()?;
+    fn cookie_path(&self) -> Option<PathBuf> {
+        self.cookie_path_in(&dirs::home_dir()?)
+    }
+
+    /// Cookie database path for this browser under `home` (macOS layout)
+    #[cfg(not(target_os = "linux"))]
+    fn cookie_path_in(&self, home: &Path) -> Option<PathBuf> {
         let path = match self {
             CookieSource::Brave => {
                 home.join("Library/Application Support/BraveSoftware/Brave-Browser/Default/Cookies")
@@ -463,8 +1331,51 @@ impl CookieSource {
             CookieSource::Chrome => {
                 home.join("Library/Application Support/Google/Chrome/Default/Cookies")
             }
-            CookieSource::Firefox => home.join("Library/Application Support/Firefox/Profiles"), // Needs profile detection
-            CookieSource::Safari => home.join("Library/Cookies/Cookies.binarycookies"),
+            CookieSource::Edge => {
+                home.join("Library/Application Support/Microsoft Edge/Default/Cookies")
+            }
+            CookieSource::Firefox => {
+                firefox_profile(&home.join("Library/Application Support/Firefox"))?
+                    .join("cookies.sqlite")
+            }
+            CookieSource::Safari => {
+                // Sandboxed Safari (macOS 10.14+) keeps cookies in its container
+                let container =
+                    home.join("Library/Containers/com.apple.Safari/Data/Library/Cookies");
+                let legacy = home.join("Library/Cookies");
+                [container, legacy]
+                    .into_iter()
+                    .map(|dir| dir.join("Cookies.binarycookies"))
+                    .find(|path| path.exists())
+                    .unwrap_or_else(|| home.join("Library/Cookies/Cookies.binarycookies"))
+            }
+        };
+        Some(path)
+    }
+
+    /// Cookie database path for this browser under `home` (Linux layout)
+    ///
+    /// Chromium moved the database into `Network/` in version 96; the old
+    /// location is used when the new one doesn't exist. Safari has none.
+    #[cfg(target_os = "linux")]
+    fn cookie_path_in(&self, home: &Path) -> Option<PathBuf> {
+        let chromium = |profile: PathBuf| {
+            let network = profile.join("Network/Cookies");
+            if network.exists() {
+                network
+            } else {
+                profile.join("Cookies")
+            }
+        };
+        let config = home.join(".config");
+        let path = match self {
+            CookieSource::Brave => chromium(config.join("BraveSoftware/Brave-Browser/Default")),
+            CookieSource::Chrome => chromium(config.join("google-chrome/Default")),
+            CookieSource::Edge => chromium(config.join("microsoft-edge/Default")),
+            CookieSource::Firefox => {
+                firefox_profile(&home.join(".mozilla/firefox"))?.join("cookies.sqlite")
+            }
+            CookieSource::Safari => return None,
         };
         Some(path)
     }
@@ -474,28 +1385,64 @@ impl CookieSource {
         match self {
             CookieSource::Brave => "Brave Safe Storage",
             CookieSource::Chrome => "Chrome Safe Storage",
+            CookieSource::Edge => "Microsoft Edge Safe Storage",
             CookieSource::Firefox => "",
             CookieSource::Safari => "",
         }
     }
 
-    /// Get encryption key from macOS Keychain
-    fn get_keychain_key(&self) -> Result<Vec<u8>> {
+    /// Get the cookie encryption key: the browser's Safe Storage password
+    /// from the macOS Keychain, stretched with PBKDF2 as Chromium does
+    #[cfg(not(target_os = "linux"))]
+    fn get_keychain_key(&self) -> Result<[u8; 16]> {
         let service = self.keychain_service();
         if service.is_empty() {
             anyhow::bail!("Browser does not use Keychain encryption");
         }
 
-        let output = Command::new("security")
-            .args(["find-generic-password", "-s", service, "-w"])
-            .output()
-            .context("Failed to access Keychain")?;
+        let output = output_bounded(Command::new("security").args([
+            "find-generic-password",
+            "-s",
+            service,
+            "-w",
+        ]))
+        .context("Failed to access Keychain")?;
 
         if !output.status.success() {
             anyhow::bail!("Keychain access denied for {service}");
         }
 
-        Ok(output.stdout.trim_ascii().to_vec())
+        Ok(chromium_cookie_key(
+            output.stdout.trim_ascii(),
+            CHROMIUM_ITERATIONS,
+        ))
+    }
+
+    /// Get the key for `v11` cookies: the browser's Safe Storage password
+    /// from the Secret Service (libsecret), else `KWallet`
+    ///
+    /// `v10` cookies don't need it, see [`key_for_blob`].
+    #[cfg(target_os = "linux")]
+    fn get_keychain_key(&self) -> Result<[u8; 16]> {
+        let (application, folder) = match self {
+            CookieSource::Brave => ("brave", "Brave Keys"),
+            CookieSource::Chrome => ("chrome", "Chrome Keys"),
+            CookieSource::Edge => ("microsoft-edge", "Microsoft Edge Keys"),
+            CookieSource::Firefox | CookieSource::Safari => {
+                anyhow::bail!("Browser does not use keyring encryption")
+            }
+        };
+        let service = self.keychain_service();
+
+        let password = command_stdout("secret-tool", &["lookup", "application", application])
+            .or_else(|| {
+                command_stdout(
+                    "kwallet-query",
+                    &["--read-password", service, "--folder", folder, "kdewallet"],
+                )
+            })
+            .with_context(|| format!("No {service} password in Secret Service or KWallet"))?;
+        Ok(chromium_cookie_key(&password, CHROMIUM_ITERATIONS))
     }
 
     /// Get cookies for a domain from the specified browser
@@ -521,7 +1468,7 @@ impl CookieSource {
             }
         }
 
-        // Fallback to Python browser_cookie3 (handles all encryption edge cases)
+        // Last resort: Python browser_cookie3 (e.g. for Safari)
         self.get_cookies_via_python(domain)
     }
 
@@ -534,19 +1481,29 @@ impl CookieSource {
             warn!("Cookie database not found: {:?}", cookie_path);
             return Ok(HashMap::new());
         }
+        if matches!(self, CookieSource::Safari) {
+            return safari_cookies(&cookie_path, domain);
+        }
 
         // Copy database to temp file (browser may have it locked)
-        let temp_dir = std::env::temp_dir().join(format!("nab_cookies_{}", std::process::id()));
+        // Per browser, since several may be read at once
+        let temp_dir =
+            std::env::temp_dir().join(format!("nab_cookies_{}_{self:?}", std::process::id()));
         std::fs::create_dir_all(&temp_dir)?;
         let temp_db = temp_dir.join("Cookies");
 
         std::fs::copy(&cookie_path, &temp_db)?;
 
-        // Also copy WAL/SHM if present
+        // Also copy WAL/SHM if present (`Cookies-wal`, `cookies.sqlite-wal`)
+        let with_suffix = |path: &Path, suffix: &str| {
+            let mut name = path.as_os_str().to_owned();
+            name.push(suffix);
+            PathBuf::from(name)
+        };
         for suffix in ["-wal", "-shm"] {
-            let wal = cookie_path.with_extension(format!("Cookies{suffix}"));
+            let wal = with_suffix(&cookie_path, suffix);
             if wal.exists() {
-                let _ = std::fs::copy(&wal, temp_db.with_extension(format!("Cookies{suffix}")));
+                let _ = std::fs::copy(&wal, with_suffix(&temp_db, suffix));
             }
         }
 
@@ -556,32 +1513,37 @@ impl CookieSource {
         // - Cookie on example.com matches only example.com exactly
         // - We need to match: exact domain, .domain (parent), and any .parent where domain is subdomain
 
+        // Firefox keeps cookies unencrypted in `moz_cookies`
+        let (table, host, encrypted) = match self {
+            CookieSource::Firefox => ("moz_cookies", "host", "''"),
+            _ => ("cookies", "host_key", "hex(encrypted_value)"),
+        };
+
         // Extract base domain parts for subdomain matching
         let domain_parts: Vec<&str> = domain.split('.').collect();
         let mut conditions = vec![
-            format!("host_key = '{domain}'"),  // Exact match
-            format!("host_key = '.{domain}'"), // Parent domain with dot
+            format!("{host} = '{domain}'"),  // Exact match
+            format!("{host} = '.{domain}'"), // Parent domain with dot
         ];
 
         // Add parent domain matches (e.g., for areena.yle.fi, also match .yle.fi, .fi)
         for i in 1..domain_parts.len() {
             let parent = domain_parts[i..].join(".");
-            conditions.push(format!("host_key = '.{parent}'"));
+            conditions.push(format!("{host} = '.{parent}'"));
         }
 
         let where_clause = conditions.join(" OR ");
         let query =
-            format!("SELECT name, value, encrypted_value FROM cookies WHERE {where_clause}");
+            format!("SELECT name, value, {encrypted}, {host} FROM {table} WHERE {where_clause}");
 
         debug!("Cookie SQL query for '{}': WHERE {}", domain, where_clause);
 
         let temp_db_str = temp_db
             .to_str()
             .ok_or_else(|| anyhow::anyhow!("Invalid temp database path"))?;
-        let output = Command::new("sqlite3")
-            .args(["-separator", "\t", temp_db_str, &query])
-            .output()
-            .context("Failed to query cookie database")?;
+        let output =
+            output_bounded(Command::new("sqlite3").args(["-separator", "\t", temp_db_str, &query]))
+                .context("Failed to query cookie database")?;
 
         // Cleanup
         let _ = std::fs::remove_dir_all(&temp_dir);
@@ -597,6 +1559,7 @@ impl CookieSource {
 
         // Get decryption key if needed
         let key = self.get_keychain_key().ok();
+        let mut undecrypted = 0;
 
         for line in stdout.lines() {
             let parts: Vec<&str> = line.split('\t').collect();
@@ -605,16 +1568,24 @@ impl CookieSource {
                 let value = parts[1].to_string();
 
                 // If value is empty and we have encrypted_value, try to decrypt
-                if value.is_empty() && parts.len() >= 3 {
-                    if let Some(k) = key.as_ref() {
-                        // Try native decryption - if it fails, we'll fall back to Python at the caller
-                        if let Ok(decrypted) = self.decrypt_cookie_value(parts[2], k) {
+                if value.is_empty() && parts.len() >= 4 && !parts[2].is_empty() {
+                    let decrypted = decode_hex(parts[2])
+                        .context("Invalid encrypted_value")
+                        .and_then(|blob| {
+                            let k = key_for_blob(&blob, key.as_ref())
+                                .context("No keyring key for encrypted cookie")?;
+                            decrypt_chromium_cookie(&blob, &k, parts[3])
+                        });
+                    match decrypted {
+                        Ok(decrypted) => {
                             cookies.insert(name, decrypted);
-                            continue;
                         }
-                        // If decryption fails, return error to trigger Python fallback
-                        anyhow::bail!("Cookie decryption failed for encrypted values");
+                        Err(e) => {
+                            debug!("Failed to decrypt cookie {name}: {e:#}");
+                            undecrypted += 1;
+                        }
                     }
+                    continue;
                 }
 
                 if !value.is_empty() {
@@ -623,6 +1594,14 @@ impl CookieSource {
             }
         }
 
+        // Nothing readable natively: let the caller try Python
+        if cookies.is_empty() && undecrypted > 0 {
+            anyhow::bail!("Could not decrypt {undecrypted} encrypted cookies");
+        }
+        if undecrypted > 0 {
+            warn!("Skipped {undecrypted} cookies that could not be decrypted");
+        }
+
         if cookies.is_empty() {
             debug!("Native extraction: 0 cookies for {}", domain);
         } else {
@@ -635,18 +1614,12 @@ impl CookieSource {
         Ok(cookies)
     }
 
-    /// Decrypt a Chrome/Brave encrypted cookie value
-    fn decrypt_cookie_value(&self, _encrypted_hex: &str, _key: &[u8]) -> Result<String> {
-        // Chrome uses AES-128-CBC with PBKDF2-derived key
-        // For simplicity, fall back to Python for decryption
-        anyhow::bail!("Encrypted cookie - use Python fallback")
-    }
-
     /// Fallback: Get cookies via Python `browser_cookie3`
     fn get_cookies_via_python(&self, domain: &str) -> Result<HashMap<String, String>> {
         let browser_fn = match self {
             CookieSource::Brave => "brave",
             CookieSource::Chrome => "chrome",
+            CookieSource::Edge => "edge",
             CookieSource::Firefox => "firefox",
             CookieSource::Safari => "safari",
         };
@@ -679,9 +1652,7 @@ except Exception as e:
 "#
         );
 
-        let output = Command::new("python3")
-            .args(["-c", &script])
-            .output()
+        let output = output_bounded(Command::new("python3").args(["-c", &script]))
             .context("Failed to run Python cookie extraction")?;
 
         if !output.status.success() {
@@ -710,31 +1681,373 @@ except Exception as e:
     }
 }
 
+/// PBKDF2 rounds Chromium uses on macOS
+const CHROMIUM_MACOS_ITERATIONS: u32 = 1003;
+
+/// PBKDF2 rounds Chromium uses on Linux
+const CHROMIUM_LINUX_ITERATIONS: u32 = 1;
+
+const CHROMIUM_ITERATIONS: u32 = if cfg!(target_os = "linux") {
+    CHROMIUM_LINUX_ITERATIONS
+} else {
+    CHROMIUM_MACOS_ITERATIONS
+};
+
+/// Password of Linux `v10` cookies, written when no keyring is available
+const LINUX_FALLBACK_PASSWORD: &[u8] = b"peanuts";
+
+/// Salt of Chromium's cookie key derivation on every platform
+const CHROMIUM_SALT: &[u8] = b"saltysalt";
+
+/// AES-128 key for `v10`/`v11` cookies from a Safe Storage password
+fn chromium_cookie_key(password: &[u8], iterations: u32) -> [u8; 16] {
+    let mut key = [0u8; 16];
+    pbkdf2::pbkdf2_hmac::<sha1::Sha1>(password, CHROMIUM_SALT, iterations, &mut key);
+    key
+}
+
+/// Key for an encrypted cookie, by its version prefix
+///
+/// On Linux `v10` cookies always use the built-in `peanuts` password and
+/// only `v11` ones need the keyring; elsewhere both use the keyring key.
+fn key_for_blob(blob: &[u8], keyring: Option<&[u8; 16]>) -> Option<[u8; 16]> {
+    if cfg!(target_os = "linux") && blob.starts_with(b"v10") {
+        return Some(chromium_cookie_key(
+            LINUX_FALLBACK_PASSWORD,
+            CHROMIUM_LINUX_ITERATIONS,
+        ));
+    }
+    keyring.copied()
+}
+
+/// Decrypt a Chromium `encrypted_value` blob
+///
+/// The `v10`/`v11` prefix is stripped and the rest decrypted with AES-128-CBC
+/// and a fixed IV of spaces, as Chromium does on macOS and Linux. Windows
+/// cookies (AES-256-GCM with a DPAPI-protected key) are not supported. Since
+/// cookie database version 24 the plaintext starts with the SHA-256 of the
+/// cookie's `host_key`, which is removed.
+pub(crate) fn decrypt_chromium_cookie(
+    blob: &[u8],
+    key: &[u8; 16],
+    host_key: &str,
+) -> Result<String> {
+    use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit};
+    use sha2::{Digest, Sha256};
+
+    let ciphertext = blob
+        .strip_prefix(b"v10")
+        .or_else(|| blob.strip_prefix(b"v11"))
+        .context("Unknown cookie encryption version")?;
+
+    let mut buffer = ciphertext.to_vec();
+    let plaintext = cbc::Decryptor::<aes::Aes128>::new(key.into(), &[b' '; 16].into())
+        .decrypt_padded_mut::<Pkcs7>(&mut buffer)
+        .map_err(|_| anyhow::anyhow!("AES-CBC decryption failed (wrong key?)"))?;
+
+    let value = plaintext
+        .strip_prefix(&Sha256::digest(host_key.as_bytes())[..])
+        .unwrap_or(plaintext);
+    String::from_utf8(value.to_vec()).context("Decrypted cookie is not UTF-8")
+}
+
+/// Unexpired cookies for `domain` from Safari's `Cookies.binarycookies`
+///
+/// Matches hosts like the cookie database queries: `domain`, `.domain` and
+/// `.parent` for every parent domain. Reading the file needs Full Disk Access
+/// on recent macOS; a permission error sends the caller to the Python
+/// fallback.
+fn safari_cookies(path: &Path, domain: &str) -> Result<HashMap<String, String>> {
+    let data = std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let now = chrono::Utc::now();
+    let cookies: HashMap<String, String> = crate::binarycookies::parse(&data)?
+        .into_iter()
+        .filter(|cookie| cookie_host_matches(&cookie.domain, domain) && !cookie.is_expired(now))
+        .map(|cookie| (cookie.name, cookie.value))
+        .collect();
+    debug!(
+        "Native extraction: {} Safari cookies for {domain}",
+        cookies.len()
+    );
+    Ok(cookies)
+}
+
+/// Whether a cookie stored for `host` is sent to `domain`: the exact host,
+/// `.domain`, or `.parent` for any parent of `domain`
+fn cookie_host_matches(host: &str, domain: &str) -> bool {
+    match host.strip_prefix('.') {
+        Some(parent) => domain_matches(domain, parent),
+        None => host.eq_ignore_ascii_case(domain),
+    }
+}
+
+/// A Firefox profile directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FirefoxProfile {
+    path: PathBuf,
+    /// Marked `Default=1`, or the default of an install
+    default: bool,
+}
+
+/// Firefox profile to read cookies from, under the Firefox data directory
+/// `root` (the one holding `profiles.ini`)
+///
+/// Profiles are listed by `profiles.ini`, else found by scanning `root` and
+/// `root/Profiles`. The `*.default-release` profile wins over the default
+/// one, so a Developer Edition or Nightly install marked default doesn't
+/// shadow regular Firefox; then `*.default`, then the first profile.
+fn firefox_profile(root: &Path) -> Option<PathBuf> {
+    let mut profiles = std::fs::read_to_string(root.join("profiles.ini"))
+        .map(|ini| parse_profiles_ini(&ini, root))
+        .unwrap_or_default();
+    if profiles.is_empty() {
+        profiles = [root.to_path_buf(), root.join("Profiles")]
+            .iter()
+            .filter_map(|dir| std::fs::read_dir(dir).ok())
+            .flatten()
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_dir())
+            .map(|path| FirefoxProfile {
+                path,
+                default: false,
+            })
+            .collect();
+        profiles.sort_by(|a, b| a.path.cmp(&b.path));
+    }
+
+    let has_suffix = |profile: &&FirefoxProfile, suffix: &str| {
+        profile
+            .path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(suffix))
+    };
+    profiles
+        .iter()
+        .find(|p| has_suffix(p, ".default-release"))
+        .or_else(|| profiles.iter().find(|p| p.default))
+        .or_else(|| profiles.iter().find(|p| has_suffix(p, ".default")))
+        .or_else(|| profiles.first())
+        .map(|profile| profile.path.clone())
+}
+
+/// Profiles listed in `profiles.ini`, with relative paths resolved
+/// against `root`
+fn parse_profiles_ini(ini: &str, root: &Path) -> Vec<FirefoxProfile> {
+    #[derive(Default)]
+    struct Section {
+        profile: bool,
+        path: Option<String>,
+        relative: bool,
+        default: bool,
+        install_default: Option<String>,
+    }
+
+    let mut sections: Vec<Section> = Vec::new();
+    for line in ini.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            sections.push(Section {
+                profile: name.starts_with("Profile"),
+                relative: true,
+                ..Section::default()
+            });
+            continue;
+        }
+        let (Some(section), Some((key, value))) = (sections.last_mut(), line.split_once('='))
+        else {
+            continue;
+        };
+        match (section.profile, key.trim(), value.trim()) {
+            (true, "Path", path) => section.path = Some(path.to_string()),
+            (true, "IsRelative", relative) => section.relative = relative != "0",
+            (true, "Default", default) => section.default = default == "1",
+            (false, "Default", path) => section.install_default = Some(path.to_string()),
+            _ => {}
+        }
+    }
+
+    let install_defaults: Vec<&str> = sections
+        .iter()
+        .filter_map(|s| s.install_default.as_deref())
+        .collect();
+    sections
+        .iter()
+        .filter(|s| s.profile)
+        .filter_map(|s| {
+            let path = s.path.as_deref()?;
+            Some(FirefoxProfile {
+                path: if s.relative {
+                    root.join(path)
+                } else {
+                    PathBuf::from(path)
+                },
+                default: s.default || install_defaults.contains(&path),
+            })
+        })
+        .collect()
+}
+
+/// Trimmed stdout of a successful command, `None` if it failed or printed
+/// nothing
+#[cfg(target_os = "linux")]
+fn command_stdout(program: &str, args: &[&str]) -> Option<Vec<u8>> {
+    let output = output_bounded(Command::new(program).args(args)).ok()?;
+    let stdout = output.stdout.trim_ascii();
+    (output.status.success() && !stdout.is_empty()).then(|| stdout.to_vec())
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Keychain & Browser Password Support
 // ═══════════════════════════════════════════════════════════════════════════════
 
 /// Source for retrieving credentials
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CredentialSource {
     /// macOS Keychain (Internet passwords)
+    #[serde(rename = "keychain")]
     Keychain,
     /// 1Password CLI
+    #[serde(rename = "1password", alias = "onepassword")]
     OnePassword,
+    /// Bitwarden CLI (`bw`), unlocked via `BW_SESSION`
+    #[serde(rename = "bitwarden")]
+    Bitwarden,
     /// Browser password manager (Brave)
+    #[serde(rename = "brave")]
     BravePasswords,
     /// Browser password manager (Chrome)
+    #[serde(rename = "chrome")]
     ChromePasswords,
 }
 
-/// Unified credential retriever - tries multiple sources
-pub struct CredentialRetriever;
+impl CredentialSource {
+    /// Lookup order when none is configured
+    pub const DEFAULT_ORDER: [CredentialSource; 5] = [
+        Self::OnePassword,
+        Self::Bitwarden,
+        Self::Keychain,
+        Self::BravePasswords,
+        Self::ChromePasswords,
+    ];
+}
+
+/// Top-level credentials configuration file
+#[derive(Debug, Deserialize)]
+struct CredentialsFile {
+    sources: Option<Vec<CredentialSource>>,
+}
+
+/// Unified credential retriever - tries multiple sources in priority order
+///
+/// The order defaults to 1Password > Bitwarden > Keychain > Brave > Chrome
+/// passwords.
+/// `~/.config/nab/credentials.toml` can reorder it or leave sources out:
+///
+/// ```toml
+/// sources = ["keychain", "1password"]  # never read browser passwords
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CredentialRetriever {
+    sources: Vec<CredentialSource>,
+}
+
+impl Default for CredentialRetriever {
+    fn default() -> Self {
+        Self {
+            sources: CredentialSource::DEFAULT_ORDER.to_vec(),
+        }
+    }
+}
 
 impl CredentialRetriever {
-    /// Get credentials for a URL from all available sources
+    /// Get credentials for a URL using the configured source order
     ///
-    /// Priority: 1Password > Keychain > Browser passwords
+    /// See [`load`](Self::load); an unreadable config falls back to the
+    /// default order.
     pub fn get_credential_for_url(url: &str) -> Result<Option<Credential>> {
+        let retriever = Self::load().unwrap_or_else(|e| {
+            warn!("Using default credential sources: {e:#}");
+            Self::default()
+        });
+        retriever.find(url)
+    }
+
+    /// Source order from `~/.config/nab/credentials.toml`, or the default
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn load() -> Result<Self> {
+        let path = dirs::config_dir()
+            .unwrap_or_else(|| std::path::PathBuf::from("."))
+            .join("nab")
+            .join("credentials.toml");
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Self::from_toml(&content).with_context(|| format!("invalid {}", path.display()))
+    }
+
+    /// Parse a credentials config; without `sources` the default order is kept
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for invalid TOML or an unknown source name.
+    pub fn from_toml(content: &str) -> Result<Self> {
+        let file: CredentialsFile = toml::from_str(content)?;
+        Ok(file.sources.map_or_else(Self::default, |sources| {
+            Self::default().with_sources(sources)
+        }))
+    }
+
+    /// Query exactly these sources, in this order
+    #[must_use]
+    pub fn with_sources(mut self, sources: impl IntoIterator<Item = CredentialSource>) -> Self {
+        self.sources.clear();
+        for source in sources {
+            if !self.sources.contains(&source) {
+                self.sources.push(source);
+            }
+        }
+        self
+    }
+
+    /// Never query `source`
+    #[must_use]
+    pub fn without(mut self, source: CredentialSource) -> Self {
+        self.sources.retain(|s| *s != source);
+        self
+    }
+
+    /// Sources in the order they are queried
+    pub fn sources(&self) -> &[CredentialSource] {
+        &self.sources
+    }
+
+    /// First credential for `url` from the configured sources
+    pub fn find(&self, url: &str) -> Result<Option<Credential>> {
+        self.find_with(url, |source, domain| Self::query(source, url, domain))
+    }
+
+    /// [`find`](Self::find) with a custom per-source lookup, called with the
+    /// source and the URL's domain
+    pub fn find_with(
+        &self,
+        url: &str,
+        mut lookup: impl FnMut(CredentialSource, &str) -> Result<Option<Credential>>,
+    ) -> Result<Option<Credential>> {
         // Extract domain
         let domain = url::Url::parse(url)
             .ok()
@@ -745,28 +2058,31 @@ impl CredentialRetriever {
             return Ok(None);
         }
 
-        // Try 1Password first (most secure, has TOTP)
-        if OnePasswordAuth::is_available() {
-            let auth = OnePasswordAuth::new(None);
-            if let Ok(Some(cred)) = auth.get_credential_for_url(url) {
-                info!("Found credential in 1Password: {}", cred.title);
+        for &source in &self.sources {
+            if let Some(cred) = lookup(source, &domain)? {
+                info!("Found credential in {source:?}: {}", cred.title);
                 return Ok(Some(cred));
             }
         }
 
-        // Try macOS Keychain
-        if let Some(cred) = Self::get_keychain_credential(&domain)? {
-            info!("Found credential in Keychain");
-            return Ok(Some(cred));
-        }
+        Ok(None)
+    }
 
-        // Try browser passwords
-        if let Some(cred) = Self::get_browser_credential(&domain)? {
-            info!("Found credential in browser");
-            return Ok(Some(cred));
+    fn query(source: CredentialSource, url: &str, domain: &str) -> Result<Option<Credential>> {
+        match source {
+            // 1Password failures (locked, not signed in) fall through to the next source
+            CredentialSource::OnePassword => Ok(OnePasswordAuth::is_available()
+                .then(|| OnePasswordAuth::new(None).get_credential_for_url(url).ok())
+                .flatten()
+                .flatten()),
+            CredentialSource::Bitwarden => Ok(BitwardenAuth::is_available()
+                .then(|| BitwardenAuth::from_env().get_credential_for_url(url).ok())
+                .flatten()
+                .flatten()),
+            CredentialSource::Keychain => Self::get_keychain_credential(domain),
+            CredentialSource::BravePasswords => Self::get_chromium_password("brave", domain),
+            CredentialSource::ChromePasswords => Self::get_chromium_password("chrome", domain),
         }
-
-        Ok(None)
     }
 
     /// Get credential from macOS Keychain
@@ -823,17 +2139,6 @@ impl CredentialRetriever {
         Ok(None)
     }
 
-    /// Get credential from browser password manager
-    fn get_browser_credential(domain: &str) -> Result<Option<Credential>> {
-        // Try Brave first, then Chrome
-        for browser in ["brave", "chrome"] {
-            if let Some(cred) = Self::get_chromium_password(browser, domain)? {
-                return Ok(Some(cred));
-            }
-        }
-        Ok(None)
-    }
-
     /// Get password from Chromium-based browser (Brave/Chrome)
     fn get_chromium_password(browser: &str, domain: &str) -> Result<Option<Credential>> {
         let home = dirs::home_dir().context("No home directory")?;
@@ -907,15 +2212,429 @@ mod tests {
         println!("1Password CLI available: {}", available);
     }
 
+    #[test]
+    fn test_otp_code_debug_is_redacted() {
+        let otp = OtpCode {
+            code: "493817".to_string(),
+            source: OtpSource::OnePasswordTotp,
+            expires_in_seconds: Some(30),
+            received_at: None,
+        };
+        let debug = format!("{otp:?}");
+        assert!(!debug.contains("493817"));
+        assert!(debug.contains("[redacted]"));
+        assert!(debug.contains("OnePasswordTotp"));
+    }
+
+    fn sms_code(code: &str, received_at: Option<DateTime<Utc>>) -> OtpCode {
+        OtpCode {
+            code: code.to_string(),
+            source: OtpSource::SmsBeeper,
+            expires_in_seconds: Some(300),
+            received_at,
+        }
+    }
+
+    fn poll(
+        requested_at: DateTime<Utc>,
+        timeout: Duration,
+        sources: &mut [&mut dyn FnMut() -> Result<Option<OtpCode>>],
+    ) -> Option<OtpCode> {
+        OtpRetriever::poll_fresh_otp(requested_at, timeout, Duration::from_millis(10), sources)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_poll_waits_for_delayed_code() {
+        let mut polls = 0;
+        let otp = poll(
+            Utc::now(),
+            Duration::from_secs(5),
+            &mut [&mut || {
+                polls += 1;
+                Ok((polls > 2).then(|| sms_code("482913", None)))
+            }],
+        )
+        .unwrap();
+        assert_eq!(otp.code, "482913");
+        assert_eq!(polls, 3);
+    }
+
+    #[test]
+    fn test_poll_skips_stale_untimestamped_code() {
+        let mut polls = 0;
+        let otp = poll(
+            Utc::now(),
+            Duration::from_secs(5),
+            &mut [&mut || {
+                polls += 1;
+                Ok(Some(sms_code(
+                    if polls < 4 { "111111" } else { "222222" },
+                    None,
+                )))
+            }],
+        )
+        .unwrap();
+        assert_eq!(otp.code, "222222");
+    }
+
+    #[test]
+    fn test_poll_compares_message_time_with_request_time() {
+        let requested_at = Utc::now();
+        let before = requested_at - chrono::TimeDelta::seconds(30);
+        let after = requested_at + chrono::TimeDelta::seconds(2);
+
+        // Already there on the first query, but sent after the request
+        let otp = poll(
+            requested_at,
+            Duration::from_secs(5),
+            &mut [&mut || Ok(Some(sms_code("482913", Some(after))))],
+        )
+        .unwrap();
+        assert_eq!(otp.code, "482913");
+
+        // Sent before the request: never fresh, even once it changes
+        let mut polls = 0;
+        let otp = poll(
+            requested_at,
+            Duration::from_millis(60),
+            &mut [&mut || {
+                polls += 1;
+                Ok(Some(sms_code(&format!("{polls:06}"), Some(before))))
+            }],
+        );
+        assert!(otp.is_none());
+    }
+
+    #[test]
+    fn test_poll_keeps_going_when_a_source_fails() {
+        let mut polls = 0;
+        let otp = poll(
+            Utc::now(),
+            Duration::from_secs(5),
+            &mut [
+                &mut || {
+                    polls += 1;
+                    Ok((polls > 2).then(|| sms_code("731904", None)))
+                },
+                &mut || anyhow::bail!("gmail unavailable"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(otp.code, "731904");
+    }
+
+    #[test]
+    fn test_poll_times_out() {
+        let start = Instant::now();
+        let otp = poll(
+            Utc::now(),
+            Duration::from_millis(60),
+            &mut [&mut || Ok(None)],
+        );
+        assert!(otp.is_none());
+        assert!(start.elapsed() >= Duration::from_millis(60));
+    }
+
+    #[test]
+    fn test_response_code_carries_message_time() {
+        let keywords = OtpKeywords::default();
+        let response = r#"{"messages": [
+            {"text": "Your code is 111111", "timestamp": "2026-03-01T10:00:00Z"},
+            {"text": "Your code is 222222", "timestamp": "2026-03-01T10:05:00Z"}
+        ]}"#;
+        let (code, received_at) =
+            OtpRetriever::extract_otp_from_response(response, &keywords).unwrap();
+        assert_eq!(code, "222222");
+        assert_eq!(
+            received_at.unwrap().to_rfc3339(),
+            "2026-03-01T10:05:00+00:00"
+        );
+
+        // Gmail's internalDate is milliseconds since the epoch
+        let gmail = r#"[{"snippet": "Verification code 482913", "internalDate": "1772359500000"}]"#;
+        let (_, received_at) = OtpRetriever::extract_otp_from_response(gmail, &keywords).unwrap();
+        assert_eq!(received_at.unwrap().timestamp(), 1_772_359_500);
+
+        let (code, received_at) =
+            OtpRetriever::extract_otp_from_response("Your code is 654321", &keywords).unwrap();
+        assert_eq!(code, "654321");
+        assert!(received_at.is_none());
+    }
+
     #[test]
     fn test_otp_extraction_patterns() {
-        // Test via public method that calls extract_otp_from_text
-        // These tests verify the OTP regex patterns work correctly
+        let keywords = OtpKeywords::default();
+        let extract = |text| OtpRetriever::extract_otp_from_text(text, &keywords);
+
+        assert_eq!(
+            extract("Your verification code: 12345678").as_deref(),
+            Some("12345678")
+        );
+        assert_eq!(extract("Use 123-456 to sign in").as_deref(), Some("123456"));
+        assert_eq!(extract("Order 2024 shipped"), None);
+    }
+
+    #[test]
+    fn test_otp_extraction_uses_localized_keywords() {
+        let keywords = OtpKeywords::default();
+
+        let german = "Bestellung 771204 ist unterwegs. Ihr Code lautet 483920.";
+        assert_eq!(
+            OtpRetriever::extract_otp_from_text(german, &keywords).as_deref(),
+            Some("483920")
+        );
+
+        let finnish = "Tilaus 550011 vastaanotettu. Vahvistuskoodisi on 728146.";
+        assert_eq!(
+            OtpRetriever::extract_otp_from_text(finnish, &keywords).as_deref(),
+            Some("728146")
+        );
+        // Without Finnish keywords the first number wins
+        let english = OtpKeywords::empty().with_language("en", ["code"]);
+        assert_eq!(
+            OtpRetriever::extract_otp_from_text(finnish, &english).as_deref(),
+            Some("550011")
+        );
+    }
+
+    #[test]
+    fn test_otp_keywords_config() {
+        let keywords = OtpKeywords::empty()
+            .with_language("fi", ["Koodi"])
+            .merge_toml("[keywords]\nnl = [\"verificatie\", \"code\"]\nfi = [\"tunnusluku\"]")
+            .unwrap();
+        assert_eq!(keywords.words(), ["tunnusluku", "verificatie", "code"]);
+        assert_eq!(
+            keywords.search_terms("example.com "),
+            "example.com tunnusluku OR example.com verificatie OR example.com code"
+        );
+        assert!(OtpKeywords::default().words().contains(&"koodi"));
+    }
+
+    #[test]
+    fn test_passkey_from_op_item() {
+        let item: OpItem = serde_json::from_str(
+            r#"{
+                "id": "k4x2",
+                "title": "GitHub",
+                "category": "LOGIN",
+                "created_at": "2025-03-02T10:15:00Z",
+                "urls": [{"href": "https://github.com/login", "primary": true}],
+                "fields": [
+                    {"id": "username", "type": "STRING", "label": "username", "value": "octocat"},
+                    {"id": "pk1", "type": "STRING", "label": "Credential ID",
+                     "value": "AbC123-credential", "section": {"label": "Passkey"}},
+                    {"id": "pk2", "type": "STRING", "label": "Relying Party", "value": "github.com"},
+                    {"id": "pk3", "type": "STRING", "label": "User Handle", "value": "dXNlcjEyMw"}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let passkey = Passkey::from_op_item(&item).unwrap();
+        assert_eq!(passkey.rp_id, "github.com");
+        assert_eq!(passkey.credential_id, "AbC123-credential");
+        assert_eq!(passkey.user_handle.as_deref(), Some("dXNlcjEyMw"));
+        assert_eq!(passkey.username.as_deref(), Some("octocat"));
+        assert_eq!(passkey.created.as_deref(), Some("2025-03-02T10:15:00Z"));
+
+        assert!(passkey.matches_domain("github.com"));
+        assert!(passkey.matches_domain("https://gist.github.com/new"));
+        assert!(!passkey.matches_domain("notgithub.com"));
+        assert!(!passkey.matches_domain("gitlab.com"));
+
+        let plain: OpItem = serde_json::from_str(
+            r#"{"id": "x", "title": "Bank", "category": "LOGIN",
+                "fields": [{"id": "password", "label": "password", "value": "hunter2"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(Passkey::from_op_item(&plain), None);
+    }
+
+    #[test]
+    fn test_credential_sources_follow_config() {
+        let found = |title: &str| Credential {
+            title: title.to_string(),
+            username: Some("ada".to_string()),
+            password: Some("secret".to_string()),
+            url: None,
+            totp: None,
+            has_totp: false,
+            passkey_credential_id: None,
+        };
+
+        let keychain_first =
+            CredentialRetriever::from_toml(r#"sources = ["keychain", "1password", "brave"]"#)
+                .unwrap();
+        let mut queried = Vec::new();
+        let cred = keychain_first
+            .find_with("https://example.com/login", |source, domain| {
+                assert_eq!(domain, "example.com");
+                queried.push(source);
+                Ok((source == CredentialSource::OnePassword).then(|| found("1Password")))
+            })
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            queried,
+            [CredentialSource::Keychain, CredentialSource::OnePassword]
+        );
+        assert_eq!(cred.title, "1Password");
+
+        let no_browser = CredentialRetriever::default()
+            .without(CredentialSource::BravePasswords)
+            .without(CredentialSource::ChromePasswords);
+        let mut queried = Vec::new();
+        let cred = no_browser
+            .find_with("https://example.com", |source, _| {
+                queried.push(source);
+                Ok(None)
+            })
+            .unwrap();
+        assert!(cred.is_none());
+        assert_eq!(
+            queried,
+            [
+                CredentialSource::OnePassword,
+                CredentialSource::Bitwarden,
+                CredentialSource::Keychain
+            ]
+        );
+
+        assert_eq!(
+            CredentialRetriever::from_toml("").unwrap(),
+            CredentialRetriever::default()
+        );
+        assert!(CredentialRetriever::from_toml(r#"sources = ["lastpass"]"#).is_err());
+    }
+
+    #[test]
+    fn test_cookie_source_from_browser_name() {
+        assert_eq!(CookieSource::from_browser_name("Edge"), CookieSource::Edge);
+        assert_eq!(
+            CookieSource::from_browser_name("brave"),
+            CookieSource::Brave
+        );
+        assert_eq!(
+            CookieSource::from_browser_name("FIREFOX"),
+            CookieSource::Firefox
+        );
+        assert_eq!(CookieSource::from_browser_name("dia"), CookieSource::Chrome);
+    }
+
+    #[test]
+    fn test_bitwarden_item_for_domain() {
+        let items: Vec<BwItem> = serde_json::from_str(
+            r#"[
+                {"id": "note", "name": "example.com recovery codes", "type": 2, "login": null},
+                {"id": "other", "name": "Example staging", "type": 1, "login": {
+                    "username": "stage", "password": "s", "totp": null,
+                    "uris": [{"match": null, "uri": "https://staging.example.org"}]}},
+                {"id": "main", "name": "Example", "type": 1, "login": {
+                    "username": "ada@example.com", "password": "hunter2",
+                    "totp": "otpauth://totp/Example?secret=JBSWY3DPEHPK3PXP",
+                    "uris": [{"match": null, "uri": "https://example.com/login"}]}}
+            ]"#,
+        )
+        .unwrap();
+
+        let (id, cred) =
+            BitwardenAuth::credential_from_items(items, "accounts.example.com").unwrap();
+        assert_eq!(id, "main");
+        assert_eq!(cred.title, "Example");
+        assert_eq!(cred.username.as_deref(), Some("ada@example.com"));
+        assert_eq!(cred.password.as_deref(), Some("hunter2"));
+        assert_eq!(cred.url.as_deref(), Some("https://example.com/login"));
+        assert!(cred.has_totp);
+        // The seed is never exposed as a code
+        assert_eq!(cred.totp, None);
+
+        assert!(BitwardenAuth::credential_from_items(Vec::new(), "example.com").is_none());
+
+        // A search hit whose URIs are on another site is not a match
+        let unrelated: Vec<BwItem> = serde_json::from_str(
+            r#"[{"id": "mention", "name": "example.com forwarding", "type": 1, "login": {
+                "username": "u", "password": "p", "totp": null,
+                "uris": [{"match": null, "uri": "https://mail.example.net"}]}}]"#,
+        )
+        .unwrap();
+        assert!(BitwardenAuth::credential_from_items(unrelated, "example.com").is_none());
+        assert_eq!(
+            CredentialRetriever::from_toml(r#"sources = ["bitwarden"]"#)
+                .unwrap()
+                .sources(),
+            [CredentialSource::Bitwarden]
+        );
+    }
 
-        // The extract_otp_from_text function is used internally by the auth system
-        // We can't test it directly from the test module, but we verify the regex patterns
-        // compile correctly by checking the OnePasswordAuth is_available method works
-        let _ = OnePasswordAuth::is_available();
+    #[tokio::test]
+    async fn test_merged_cookies_isolate_failures() {
+        let merged = get_cookies_merged_with(
+            &[
+                CookieSource::Brave,
+                CookieSource::Firefox,
+                CookieSource::Chrome,
+            ],
+            "example.com",
+            |source, domain| {
+                assert_eq!(domain, "example.com");
+                match source {
+                    CookieSource::Brave => anyhow::bail!("database is locked"),
+                    CookieSource::Firefox => Ok(HashMap::from([
+                        ("sid".to_string(), "ff".to_string()),
+                        ("theme".to_string(), "dark".to_string()),
+                    ])),
+                    _ => Ok(HashMap::from([("sid".to_string(), "chrome".to_string())])),
+                }
+            },
+        )
+        .await;
+
+        assert_eq!(merged.cookies.len(), 2);
+        assert_eq!(merged.cookies["sid"], "ff");
+        assert_eq!(
+            merged.reports,
+            [
+                CookieReport {
+                    source: CookieSource::Brave,
+                    outcome: Err("database is locked".to_string()),
+                },
+                CookieReport {
+                    source: CookieSource::Firefox,
+                    outcome: Ok(2),
+                },
+                CookieReport {
+                    source: CookieSource::Chrome,
+                    outcome: Ok(1),
+                },
+            ]
+        );
+        assert_eq!(merged.failures().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_timed_out_extraction_kills_its_command() {
+        let (finished_tx, finished_rx) = std::sync::mpsc::channel();
+        let started = Instant::now();
+        let merged = merge_cookies_within(
+            &[CookieSource::Chrome],
+            "example.com",
+            Duration::from_millis(200),
+            move |_, _| {
+                let output = output_bounded(Command::new("sleep").arg("30"));
+                let _ = finished_tx.send(());
+                output?;
+                Ok(HashMap::new())
+            },
+        )
+        .await;
+
+        assert!(merged.reports[0].outcome.is_err());
+        // The blocking thread returns as soon as `sleep` is killed
+        finished_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(started.elapsed() < Duration::from_secs(10));
     }
 
     #[test]
@@ -930,4 +2649,200 @@ mod tests {
         assert_ne!(format!("{firefox:?}"), format!("{brave:?}"));
         assert_ne!(format!("{brave:?}"), format!("{safari:?}"));
     }
+
+    #[test]
+    fn test_chromium_cookie_key() {
+        // Chromium's Linux default password, a well-known key
+        assert_eq!(
+            chromium_cookie_key(b"peanuts", 1),
+            [
+                0xfd, 0x62, 0x1f, 0xe5, 0xa2, 0xb4, 0x02, 0x53, 0x9d, 0xfa, 0x14, 0x7c, 0xa9, 0x27,
+                0x27, 0x78
+            ]
+        );
+        assert_ne!(
+            chromium_cookie_key(b"peanuts", CHROMIUM_MACOS_ITERATIONS),
+            chromium_cookie_key(b"peanuts", 1)
+        );
+    }
+
+    #[test]
+    fn test_decrypt_cbc_cookie() {
+        use aes::cipher::{block_padding::Pkcs7, BlockEncryptMut, KeyIvInit};
+        use sha2::{Digest, Sha256};
+
+        let key = chromium_cookie_key(b"keychain-password", CHROMIUM_MACOS_ITERATIONS);
+        let encrypt = |plaintext: &[u8]| {
+            let mut buffer = plaintext.to_vec();
+            buffer.resize(plaintext.len() + 16, 0);
+            let sealed = cbc::Encryptor::<aes::Aes128>::new(&key.into(), &[b' '; 16].into())
+                .encrypt_padded_mut::<Pkcs7>(&mut buffer, plaintext.len())
+                .unwrap();
+            [b"v10".as_slice(), sealed].concat()
+        };
+
+        let blob = encrypt(b"session-value");
+        assert_eq!(
+            decrypt_chromium_cookie(&blob, &key, ".example.com").unwrap(),
+            "session-value"
+        );
+
+        // Newer databases prefix the host key's hash
+        let hashed = [
+            &Sha256::digest(b".example.com")[..],
+            b"session-value".as_slice(),
+        ]
+        .concat();
+        assert_eq!(
+            decrypt_chromium_cookie(&encrypt(&hashed), &key, ".example.com").unwrap(),
+            "session-value"
+        );
+
+        let wrong = chromium_cookie_key(b"other", CHROMIUM_MACOS_ITERATIONS);
+        assert!(decrypt_chromium_cookie(&blob, &wrong, ".example.com").is_err());
+        assert!(decrypt_chromium_cookie(b"v99abc", &key, ".example.com").is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_linux_cookie_paths() {
+        let home = std::env::temp_dir().join(format!("nab-home-{}", uuid::Uuid::new_v4()));
+        let firefox = home.join(".mozilla/firefox");
+        for profile in ["abc.default", "xyz.default-release", "Crash Reports"] {
+            std::fs::create_dir_all(firefox.join(profile)).unwrap();
+        }
+
+        assert_eq!(
+            CookieSource::Brave.cookie_path_in(&home),
+            Some(home.join(".config/BraveSoftware/Brave-Browser/Default/Cookies"))
+        );
+        assert_eq!(
+            CookieSource::Firefox.cookie_path_in(&home),
+            Some(firefox.join("xyz.default-release/cookies.sqlite"))
+        );
+        assert_eq!(
+            CookieSource::Edge.cookie_path_in(&home),
+            Some(home.join(".config/microsoft-edge/Default/Cookies"))
+        );
+        assert_eq!(CookieSource::Safari.cookie_path_in(&home), None);
+
+        // Newer Chromium keeps the database under Network/
+        let network = home.join(".config/google-chrome/Default/Network");
+        assert_eq!(
+            CookieSource::Chrome.cookie_path_in(&home),
+            Some(home.join(".config/google-chrome/Default/Cookies"))
+        );
+        std::fs::create_dir_all(&network).unwrap();
+        std::fs::write(network.join("Cookies"), b"").unwrap();
+        assert_eq!(
+            CookieSource::Chrome.cookie_path_in(&home),
+            Some(network.join("Cookies"))
+        );
+
+        std::fs::remove_dir_all(firefox.join("xyz.default-release")).unwrap();
+        assert_eq!(
+            CookieSource::Firefox.cookie_path_in(&home),
+            Some(firefox.join("abc.default/cookies.sqlite"))
+        );
+        let _ = std::fs::remove_dir_all(&home);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_linux_v10_cookies_need_no_keyring() {
+        let peanuts = chromium_cookie_key(b"peanuts", 1);
+        assert_eq!(key_for_blob(b"v10...", None), Some(peanuts));
+        assert_eq!(key_for_blob(b"v11...", None), None);
+        assert_eq!(key_for_blob(b"v11...", Some(&[3; 16])), Some([3; 16]));
+    }
+
+    #[test]
+    fn test_firefox_profile_prefers_release_over_dev_edition() {
+        let root = std::env::temp_dir().join(format!("nab-firefox-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(
+            root.join("profiles.ini"),
+            "[Install46F492E0ACFF84D4]\n\
+             Default=Profiles/k2lm.dev-edition-default\n\
+             Locked=1\n\n\
+             [Profile1]\n\
+             Name=dev-edition-default\n\
+             IsRelative=1\n\
+             Path=Profiles/k2lm.dev-edition-default\n\
+             Default=1\n\n\
+             [Profile0]\n\
+             Name=default-release\n\
+             IsRelative=1\n\
+             Path=Profiles/x9q3.default-release\n\n\
+             [General]\n\
+             StartWithLastProfile=1\n\
+             Version=2\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            firefox_profile(&root),
+            Some(root.join("Profiles/x9q3.default-release"))
+        );
+
+        // Without a release profile the default one is used
+        let profiles = parse_profiles_ini(
+            "[Profile0]\nPath=/opt/ff/nightly\nIsRelative=0\n\n\
+             [Profile1]\nPath=Profiles/k2lm.dev-edition-default\nDefault=1\n",
+            &root,
+        );
+        assert_eq!(
+            profiles,
+            vec![
+                FirefoxProfile {
+                    path: PathBuf::from("/opt/ff/nightly"),
+                    default: false,
+                },
+                FirefoxProfile {
+                    path: root.join("Profiles/k2lm.dev-edition-default"),
+                    default: true,
+                },
+            ]
+        );
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn test_macos_cookie_paths() {
+        let home = Path::new("/Users/me");
+        assert_eq!(
+            CookieSource::Chrome.cookie_path_in(home),
+            Some(home.join("Library/Application Support/Google/Chrome/Default/Cookies"))
+        );
+        assert_eq!(
+            CookieSource::Edge.cookie_path_in(home),
+            Some(home.join("Library/Application Support/Microsoft Edge/Default/Cookies"))
+        );
+        assert_eq!(
+            CookieSource::Safari.cookie_path_in(home),
+            Some(home.join("Library/Cookies/Cookies.binarycookies"))
+        );
+        assert_eq!(key_for_blob(b"v10...", Some(&[3; 16])), Some([3; 16]));
+        assert_eq!(key_for_blob(b"v10...", None), None);
+    }
+
+    #[test]
+    fn test_cookie_host_matches() {
+        assert!(cookie_host_matches("areena.yle.fi", "areena.yle.fi"));
+        assert!(cookie_host_matches(".areena.yle.fi", "areena.yle.fi"));
+        assert!(cookie_host_matches(".yle.fi", "areena.yle.fi"));
+        assert!(cookie_host_matches(".YLE.fi", "Areena.yle.fi"));
+        assert!(!cookie_host_matches("yle.fi", "areena.yle.fi"));
+        assert!(!cookie_host_matches(".notyle.fi", "yle.fi"));
+        assert!(!cookie_host_matches(".areena.yle.fi", "yle.fi"));
+    }
+
+    #[test]
+    fn test_decode_hex() {
+        assert_eq!(decode_hex("76313000ff"), Some(b"v10\x00\xff".to_vec()));
+        assert_eq!(decode_hex(""), Some(Vec::new()));
+        assert_eq!(decode_hex("abc"), None);
+        assert_eq!(decode_hex("zz"), None);
+    }
 }
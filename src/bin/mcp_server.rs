@@ -10,6 +10,7 @@
 //! nab-mcp
 //! ```
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Instant;
 
@@ -25,10 +26,13 @@ use rust_mcp_sdk::{tool_box, McpServer, StdioTransport, TransportOptions};
 use serde::{Deserialize, Serialize};
 use tokio::sync::OnceCell;
 
+use nab::content::links::fetch_links;
 use nab::content::ContentRouter;
+use nab::crawl::discover_site_map;
 use nab::{
-    chrome_profile, firefox_profile, random_profile, safari_profile, AcceleratedClient,
-    CookieSource, CredentialRetriever, OnePasswordAuth,
+    android_chrome_profile, chrome_profile, firefox_profile, ios_safari_profile, mobile_profile,
+    random_profile, safari_profile, AcceleratedClient, CookieSource, CredentialRetriever,
+    OnePasswordAuth, OtpCode, OtpRetriever,
 };
 
 // Global shared client (initialized once)
@@ -40,6 +44,42 @@ async fn get_client() -> &'static AcceleratedClient {
         .await
 }
 
+/// Human-readable text plus the same result as MCP structured content
+fn text_with_structured(text: String, structured: serde_json::Value) -> CallToolResult {
+    let mut result = CallToolResult::text_content(vec![TextContent::from(text)]);
+    if let serde_json::Value::Object(map) = structured {
+        result.structured_content = Some(map);
+    }
+    result
+}
+
+fn default_max_chars() -> u32 {
+    4000
+}
+
+/// Keep the first `max_chars` characters of `content` (0 keeps everything),
+/// noting the original length when anything was cut
+fn truncate_content(content: &str, max_chars: u32) -> String {
+    let max_chars = usize::try_from(max_chars).unwrap_or(usize::MAX);
+    let total = content.chars().count();
+    if max_chars == 0 || total <= max_chars {
+        return content.to_string();
+    }
+    let kept: String = content.chars().take(max_chars).collect();
+    format!(
+        "{kept}\n\n... [truncated: showing {max_chars} of {total} chars; raise max_chars for more]"
+    )
+}
+
+/// First 500 characters of a batch response body, with `...` when cut
+fn body_preview(body: &str) -> String {
+    let mut preview: String = body.chars().take(500).collect();
+    if preview.len() < body.len() {
+        preview.push_str("...");
+    }
+    preview
+}
+
 // ============================================================================
 // TOOLS
 // ============================================================================
@@ -54,13 +94,17 @@ Content conversion (automatic by Content-Type):
 - JSON/plain text → passthrough
 - SPA data auto-extracted (__NEXT_DATA__, __NUXT__, __APOLLO_STATE__, etc.)
 
+Requests default to GET; set method, request_headers and data to POST
+JSON or call authenticated APIs (e.g. an Authorization header).
+
 Network features:
 - HTTP/2 multiplexing, HTTP/3 (QUIC) with 0-RTT
 - TLS 1.3, Brotli/Zstd/Gzip decompression
 - Realistic browser fingerprints (Chrome/Firefox/Safari)
 - Browser cookie injection (Brave/Chrome/Firefox/Safari)
 
-Returns: Markdown-converted body with timing info.",
+Returns: Markdown-converted body with timing info, plus structured content
+(status, content_type, markdown, elapsed_ms).",
     read_only_hint = true,
     open_world_hint = true
 )]
@@ -74,15 +118,33 @@ pub struct FetchTool {
     /// Include full body (not just summary)
     #[serde(default)]
     body: bool,
-    /// Browser cookies to use (brave, chrome, firefox, safari)
+    /// Browser cookies to use (brave, chrome, edge, firefox, safari)
     #[serde(default)]
     cookies: Option<String>,
+    /// HTTP method (GET, POST, PUT, PATCH, DELETE, HEAD); default GET
+    #[serde(default)]
+    method: Option<String>,
+    /// Extra request headers, e.g. {"Authorization": "Bearer ..."}
+    #[serde(default)]
+    request_headers: Option<HashMap<String, String>>,
+    /// Request body (sent as application/json unless Content-Type is set)
+    #[serde(default)]
+    data: Option<String>,
+    /// Maximum characters of content to return (0 = no limit)
+    #[serde(default = "default_max_chars")]
+    max_chars: u32,
 }
 
 impl FetchTool {
     pub async fn run(&self) -> Result<CallToolResult, CallToolError> {
+        self.run_with_client(get_client().await).await
+    }
+
+    async fn run_with_client(
+        &self,
+        client: &AcceleratedClient,
+    ) -> Result<CallToolResult, CallToolError> {
         let start = Instant::now();
-        let client = get_client().await;
         let profile = client.profile().await;
 
         let mut output = format!("🌐 Fetching: {}\n", self.url);
@@ -91,26 +153,34 @@ impl FetchTool {
             profile.user_agent.split('/').next().unwrap_or("Unknown")
         ));
 
+        let method = self.method.as_deref().unwrap_or("GET");
+        let mut request_headers = self.request_headers.clone().unwrap_or_default();
+        let plain_get =
+            method.eq_ignore_ascii_case("GET") && request_headers.is_empty() && self.data.is_none();
+
         // Try site-specific providers first (e.g., Twitter via FxTwitter API)
         let site_router = nab::site::SiteRouter::new();
-        if let Some(site_content) = site_router.try_extract(&self.url, client).await {
+        let site_content = if plain_get {
+            site_router.try_extract(&self.url, client).await
+        } else {
+            None
+        };
+        if let Some(site_content) = site_content {
             output.push_str("\n📄 Content (from specialized provider):\n\n");
             output.push_str(&site_content.markdown);
 
-            return Ok(CallToolResult::text_content(vec![TextContent::from(
-                output,
-            )]));
+            let structured = serde_json::json!({
+                "url": self.url,
+                "provider": true,
+                "markdown": site_content.markdown,
+                "elapsed_ms": start.elapsed().as_secs_f64() * 1000.0,
+            });
+            return Ok(text_with_structured(output, structured));
         }
 
         // Get cookies if requested
         let cookie_header = if let Some(browser) = &self.cookies {
-            let source = match browser.to_lowercase().as_str() {
-                "brave" => CookieSource::Brave,
-                "chrome" => CookieSource::Chrome,
-                "firefox" => CookieSource::Firefox,
-                "safari" => CookieSource::Safari,
-                _ => CookieSource::Brave,
-            };
+            let source = CookieSource::from_browser_name(browser);
             let domain = url::Url::parse(&self.url)
                 .ok()
                 .and_then(|u| u.host_str().map(std::string::ToString::to_string))
@@ -120,21 +190,19 @@ impl FetchTool {
             String::new()
         };
 
-        // Fetch with or without cookies
-        let response = if cookie_header.is_empty() {
-            client.fetch(&self.url).await
-        } else {
-            client
-                .inner()
-                .get(&self.url)
-                .header("Cookie", &cookie_header)
-                .headers(profile.to_headers())
-                .send()
-                .await
-                .map_err(anyhow::Error::from)
-        };
+        // Explicit Cookie header wins over browser cookies
+        if !cookie_header.is_empty()
+            && !request_headers
+                .keys()
+                .any(|name| name.eq_ignore_ascii_case("cookie"))
+        {
+            request_headers.insert("Cookie".to_string(), cookie_header);
+        }
 
-        let response = response.map_err(|e| CallToolError::from_message(e.to_string()))?;
+        let response = client
+            .request(method, &self.url, &request_headers, self.data.as_deref())
+            .await
+            .map_err(|e| CallToolError::from_message(e.to_string()))?;
 
         let elapsed = start.elapsed();
         let status = response.status();
@@ -148,14 +216,13 @@ impl FetchTool {
             elapsed.as_secs_f64() * 1000.0
         ));
 
+        let mut headers = serde_json::Map::new();
         if self.headers {
             output.push_str("\n📋 Headers:\n");
             for (name, value) in response.headers() {
-                output.push_str(&format!(
-                    "   {}: {}\n",
-                    name,
-                    value.to_str().unwrap_or("<binary>")
-                ));
+                let value = value.to_str().unwrap_or("<binary>");
+                output.push_str(&format!("   {name}: {value}\n"));
+                headers.insert(name.to_string(), value.into());
             }
         }
 
@@ -191,19 +258,24 @@ impl FetchTool {
             ));
         }
 
-        if self.body {
-            let body_text = &conversion.markdown;
-            let truncated = if body_text.len() > 4000 {
-                format!("{}\n\n... [truncated]", &body_text[..4000])
-            } else {
-                body_text.to_string()
-            };
+        let markdown = self.body.then(|| {
+            let truncated = truncate_content(&conversion.markdown, self.max_chars);
             output.push_str(&format!("\n{truncated}"));
-        }
-
-        Ok(CallToolResult::text_content(vec![TextContent::from(
-            output,
-        )]))
+            truncated
+        });
+
+        let structured = serde_json::json!({
+            "url": self.url,
+            "status": status.as_u16(),
+            "version": version,
+            "content_type": content_type,
+            "bytes": body_bytes.len(),
+            "page_count": conversion.page_count,
+            "headers": self.headers.then_some(headers),
+            "markdown": markdown,
+            "elapsed_ms": elapsed.as_secs_f64() * 1000.0,
+        });
+        Ok(text_with_structured(output, structured))
     }
 }
 
@@ -214,7 +286,8 @@ impl FetchTool {
 Uses connection pooling and multiplexing for maximum efficiency.
 All URLs are fetched concurrently.
 
-Returns: Results for each URL with timing.",
+Returns: Results for each URL with timing, plus structured content
+(results[] with url, status, bytes, elapsed_ms or error).",
     read_only_hint = true,
     open_world_hint = true
 )]
@@ -247,26 +320,35 @@ impl FetchBatchTool {
         let total_elapsed = start.elapsed();
 
         let mut output = format!("🚀 Batch fetch: {} URLs\n\n", self.urls.len());
+        let mut items = Vec::with_capacity(results.len());
 
         for (url, result, elapsed) in results {
             output.push_str(&format!("=== {url} ===\n"));
+            let elapsed_ms = elapsed.as_secs_f64() * 1000.0;
             match result {
                 Ok(response) => {
                     let status = response.status();
                     let body = response.text().await.unwrap_or_default();
-                    let preview = if body.len() > 500 {
-                        format!("{}...", &body[..500])
-                    } else {
-                        body.clone()
-                    };
+                    let preview = body_preview(&body);
                     output.push_str(&format!(
-                        "Status: {status} | {:.0}ms | {} bytes\n{preview}\n\n",
-                        elapsed.as_secs_f64() * 1000.0,
+                        "Status: {status} | {elapsed_ms:.0}ms | {} bytes\n{preview}\n\n",
                         body.len()
                     ));
+                    items.push(serde_json::json!({
+                        "url": url,
+                        "status": status.as_u16(),
+                        "bytes": body.len(),
+                        "elapsed_ms": elapsed_ms,
+                        "preview": preview,
+                    }));
                 }
                 Err(e) => {
                     output.push_str(&format!("Error: {e}\n\n"));
+                    items.push(serde_json::json!({
+                        "url": url,
+                        "error": e.to_string(),
+                        "elapsed_ms": elapsed_ms,
+                    }));
                 }
             }
         }
@@ -277,9 +359,11 @@ impl FetchBatchTool {
             self.urls.len()
         ));
 
-        Ok(CallToolResult::text_content(vec![TextContent::from(
-            output,
-        )]))
+        let structured = serde_json::json!({
+            "results": items,
+            "total_elapsed_ms": total_elapsed.as_secs_f64() * 1000.0,
+        });
+        Ok(text_with_structured(output, structured))
     }
 }
 
@@ -290,7 +374,8 @@ impl FetchBatchTool {
 Searches 1Password for credentials matching the URL/domain.
 Returns credential info (username, TOTP availability) without exposing password.
 
-Returns: Credential info if found.",
+Returns: Credential info if found, plus structured content
+(found, title, username, has_password, has_totp).",
     read_only_hint = true
 )]
 #[derive(Debug, Deserialize, Serialize, JsonSchema)]
@@ -306,13 +391,32 @@ impl AuthLookupTool {
         if !OnePasswordAuth::is_available() {
             output.push_str("❌ 1Password CLI not available or not authenticated\n");
             output.push_str("   Run: op signin\n");
-            return Ok(CallToolResult::text_content(vec![TextContent::from(
-                output,
-            )]));
+            let structured = serde_json::json!({
+                "url": self.url,
+                "available": false,
+                "found": false,
+            });
+            return Ok(text_with_structured(output, structured));
         }
 
+        let mut structured = serde_json::json!({
+            "url": self.url,
+            "available": true,
+            "found": false,
+        });
         match CredentialRetriever::get_credential_for_url(&self.url) {
             Ok(Some(cred)) => {
+                // Never expose the password itself, only whether one exists
+                structured = serde_json::json!({
+                    "url": self.url,
+                    "available": true,
+                    "found": true,
+                    "title": cred.title,
+                    "username": cred.username,
+                    "has_password": cred.password.is_some(),
+                    "has_totp": cred.has_totp,
+                    "passkey_credential_id": cred.passkey_credential_id,
+                });
                 output.push_str("✅ Found credential:\n");
                 output.push_str(&format!("   Title: {}\n", cred.title));
                 if let Some(ref username) = cred.username {
@@ -333,9 +437,66 @@ impl AuthLookupTool {
             }
             Err(e) => {
                 output.push_str(&format!("⚠️ Error: {e}\n"));
+                structured["error"] = e.to_string().into();
             }
         }
 
+        Ok(text_with_structured(output, structured))
+    }
+}
+
+#[mcp_tool(
+    name = "otp",
+    description = "Retrieve a one-time password (OTP) for a domain.
+
+Checks 1Password TOTP, then SMS (via Beeper), then email (via Gmail).
+Use during automated logins that ask for a verification code.
+
+Returns: The code, its source and seconds until it expires.",
+    read_only_hint = false
+)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct OtpTool {
+    /// Domain or URL to get a code for (e.g. github.com)
+    domain: String,
+}
+
+impl OtpTool {
+    pub async fn run(&self) -> Result<CallToolResult, CallToolError> {
+        self.run_with(OtpRetriever::get_otp_for_domain).await
+    }
+
+    async fn run_with(
+        &self,
+        lookup: impl FnOnce(&str) -> anyhow::Result<Option<OtpCode>> + Send + 'static,
+    ) -> Result<CallToolResult, CallToolError> {
+        // Extract domain from URL if needed
+        let domain = url::Url::parse(&self.domain)
+            .ok()
+            .and_then(|u| u.host_str().map(std::string::ToString::to_string))
+            .unwrap_or_else(|| self.domain.clone());
+
+        // The lookup shells out to `op` and reads local databases
+        let lookup_domain = domain.clone();
+        let otp = tokio::task::spawn_blocking(move || lookup(&lookup_domain))
+            .await
+            .map_err(|e| CallToolError::from_message(e.to_string()))?
+            .map_err(|e| CallToolError::from_message(e.to_string()))?;
+        // OtpCode's Debug output redacts the code
+        tracing::debug!(%domain, ?otp, "OTP lookup finished");
+
+        let mut output = format!("🔐 OTP for: {domain}\n\n");
+        if let Some(otp) = otp {
+            output.push_str(&format!("   Code: {}\n", otp.code));
+            output.push_str(&format!("   Source: {}\n", otp.source));
+            if let Some(expires) = otp.expires_in_seconds {
+                output.push_str(&format!("   Expires in: {expires}s\n"));
+            }
+        } else {
+            output.push_str("❌ No OTP code found\n");
+            output.push_str("   Searched: 1Password TOTP, SMS via Beeper, Email via Gmail\n");
+        }
+
         Ok(CallToolResult::text_content(vec![TextContent::from(
             output,
         )]))
@@ -346,7 +507,9 @@ impl AuthLookupTool {
     name = "fingerprint",
     description = "Generate realistic browser fingerprints.
 
-Creates browser profiles for Chrome, Firefox, or Safari.
+Creates browser profiles for Chrome, Firefox, or Safari, or mobile
+profiles (Chrome on Android, Safari on iPhone), which many sites answer
+with lighter HTML.
 Includes User-Agent, Sec-CH-UA headers, Accept-Language, platform info.
 
 Returns: Generated fingerprint profiles.",
@@ -357,7 +520,7 @@ pub struct FingerprintTool {
     /// Number of profiles to generate (1-10)
     #[serde(default = "default_count")]
     count: u32,
-    /// Browser type (chrome, firefox, safari, random)
+    /// Browser type (chrome, firefox, safari, android, ios, mobile, random)
     #[serde(default)]
     browser: Option<String>,
 }
@@ -378,6 +541,9 @@ impl FingerprintTool {
                 "chrome" => chrome_profile(),
                 "firefox" => firefox_profile(),
                 "safari" => safari_profile(),
+                "android" => android_chrome_profile(),
+                "ios" => ios_safari_profile(),
+                "mobile" => mobile_profile(),
                 _ => random_profile(),
             };
 
@@ -560,7 +726,8 @@ impl BenchmarkTool {
     description = "Submit a web form with smart field extraction.
 
 Fetches a page, parses all forms, extracts hidden fields and CSRF tokens,
-merges user-provided fields, and submits via POST.
+merges user-provided fields, and submits with the form's method (or `method`).
+With an `idempotency_key`, failed submissions are retried with the same key.
 
 Use for: login forms, search forms, API interactions behind HTML pages.
 
@@ -574,22 +741,40 @@ pub struct SubmitTool {
     url: String,
     /// Fields to submit as key=value pairs (e.g. ["username=admin", "q=search term"])
     fields: Vec<String>,
-    /// CSS selector to extract CSRF token from (e.g. "input[name=csrf_token]")
+    /// CSS selector to extract CSRF token from (e.g. "input[name=csrf_token]");
+    /// without one, token inputs, `csrf-token` meta tags and CSRF cookies are detected
     #[serde(default)]
     csrf_selector: Option<String>,
-    /// Browser cookies to use (brave, chrome, firefox, safari)
+    /// Browser cookies to use (brave, chrome, edge, firefox, safari)
     #[serde(default)]
     cookies: Option<String>,
+    /// HTTP method overriding the form's (GET, POST, PUT, PATCH, DELETE)
+    #[serde(default)]
+    method: Option<String>,
+    /// Idempotency-Key header value, reused when the submission is retried
+    #[serde(default)]
+    idempotency_key: Option<String>,
+    /// Maximum characters of content to return (0 = no limit)
+    #[serde(default = "default_max_chars")]
+    max_chars: u32,
 }
 
+/// Retries for a submission that is safe to repeat
+const SUBMIT_RETRIES: u32 = 2;
+
 impl SubmitTool {
     pub async fn run(&self) -> Result<CallToolResult, CallToolError> {
         let client = get_client().await;
         let mut output = format!("📝 Submitting form on: {}\n", self.url);
 
         // Fetch the form page
-        let page_html = client
-            .fetch_text(&self.url)
+        let page = client
+            .fetch(&self.url)
+            .await
+            .map_err(|e| CallToolError::from_message(e.to_string()))?;
+        let page_headers = page.headers().clone();
+        let page_html = page
+            .text()
             .await
             .map_err(|e| CallToolError::from_message(e.to_string()))?;
 
@@ -604,7 +789,8 @@ impl SubmitTool {
         let mut form = forms.remove(0);
         output.push_str(&format!("   Form: {} {}\n", form.method, form.action));
 
-        // Extract CSRF if requested
+        // Extract CSRF with the given selector, or detect it
+        let mut options = nab::SubmitOptions::default().with_retries(SUBMIT_RETRIES);
         if let Some(ref selector) = self.csrf_selector {
             if let Ok(Some(token)) = nab::Form::extract_csrf_token(&page_html, selector) {
                 let field_name = if selector.contains("name=") {
@@ -619,6 +805,11 @@ impl SubmitTool {
                 form.fields.insert(field_name.to_string(), token);
                 output.push_str("   CSRF: extracted\n");
             }
+        } else if let Some(token) = nab::Form::detect_csrf_token(&page_html)
+            .or_else(|| nab::Form::csrf_cookie(&page_headers))
+        {
+            options = options.with_csrf_token(token);
+            output.push_str("   CSRF: detected\n");
         }
 
         // Merge user fields
@@ -627,17 +818,14 @@ impl SubmitTool {
         form.merge_fields(&user_fields);
 
         // Submit
-        let action_url = form
-            .resolve_action(&self.url)
-            .map_err(|e| CallToolError::from_message(e.to_string()))?;
-        let form_data = form.encode_urlencoded();
-
-        let response = client
-            .inner()
-            .post(&action_url)
-            .header("Content-Type", form.content_type())
-            .body(form_data)
-            .send()
+        if let Some(method) = &self.method {
+            options = options.with_method(method);
+        }
+        if let Some(key) = &self.idempotency_key {
+            options = options.with_idempotency_key(key);
+        }
+        let response = form
+            .submit(client, &self.url, &options)
             .await
             .map_err(|e| CallToolError::from_message(e.to_string()))?;
 
@@ -655,12 +843,7 @@ impl SubmitTool {
             .convert(body.as_bytes(), "text/html")
             .map_err(|e| CallToolError::from_message(e.to_string()))?;
 
-        let truncated = if conversion.markdown.len() > 4000 {
-            format!("{}\n\n... [truncated]", &conversion.markdown[..4000])
-        } else {
-            conversion.markdown
-        };
-        output.push_str(&truncated);
+        output.push_str(&truncate_content(&conversion.markdown, self.max_chars));
 
         Ok(CallToolResult::text_content(vec![TextContent::from(
             output,
@@ -685,14 +868,17 @@ Returns: Final page content after login (markdown-converted).",
 pub struct LoginTool {
     /// URL of the login page
     url: String,
-    /// Browser cookies to use (brave, chrome, firefox, safari)
+    /// Browser cookies to use (brave, chrome, edge, firefox, safari)
     #[serde(default)]
     cookies: Option<String>,
+    /// Maximum characters of content to return (0 = no limit)
+    #[serde(default = "default_max_chars")]
+    max_chars: u32,
 }
 
 impl LoginTool {
     pub async fn run(&self) -> Result<CallToolResult, CallToolError> {
-        use nab::LoginFlow;
+        use nab::{LoginFlow, LoginOutcome};
 
         let mut output = format!("🔐 Auto-login: {}\n", self.url);
 
@@ -712,7 +898,12 @@ impl LoginTool {
             .map_err(|e| CallToolError::from_message(e.to_string()))?;
 
         output.push_str(&format!("   Final URL: {}\n", result.final_url));
-        output.push_str("   Status: ✅ Login successful\n\n");
+        let icon = match result.outcome {
+            LoginOutcome::Success => "✅",
+            LoginOutcome::MfaRequired => "🔑",
+            LoginOutcome::Failed(_) => "❌",
+        };
+        output.push_str(&format!("   Status: {icon} {}\n\n", result.message));
 
         // Convert to markdown
         let router = ContentRouter::new();
@@ -725,12 +916,109 @@ impl LoginTool {
             .convert(result.body.as_bytes(), content_type)
             .map_err(|e| CallToolError::from_message(e.to_string()))?;
 
-        let truncated = if conversion.markdown.len() > 4000 {
-            format!("{}\n\n... [truncated]", &conversion.markdown[..4000])
-        } else {
-            conversion.markdown
-        };
-        output.push_str(&truncated);
+        output.push_str(&truncate_content(&conversion.markdown, self.max_chars));
+
+        Ok(CallToolResult::text_content(vec![TextContent::from(
+            output,
+        )]))
+    }
+}
+
+#[mcp_tool(
+    name = "metrics",
+    description = "Report request metrics in Prometheus text format.
+
+Counters and histograms for requests by host and status, latency,
+response bytes and cache hits (requires the metrics feature).
+
+Returns: Prometheus text exposition.",
+    read_only_hint = true
+)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, Default)]
+pub struct MetricsTool {}
+
+impl MetricsTool {
+    pub fn run(&self) -> Result<CallToolResult, CallToolError> {
+        #[cfg(feature = "metrics")]
+        let output = nab::telemetry::render_prometheus()
+            .unwrap_or_else(|| "# metrics recorder not installed\n".to_string());
+        #[cfg(not(feature = "metrics"))]
+        let output = "# nab-mcp was built without the `metrics` feature\n".to_string();
+
+        Ok(CallToolResult::text_content(vec![TextContent::from(
+            output,
+        )]))
+    }
+}
+
+#[mcp_tool(
+    name = "links",
+    description = "Extract the links of a page as structured data.
+
+Fetches the URL and collects every <a href> with its anchor text. Relative
+links are resolved against the page URL (or its <base href>), fragments are
+dropped and duplicates removed. Only http(s) links are returned.
+
+Returns: JSON array of {text, href} objects with absolute hrefs.",
+    read_only_hint = true,
+    open_world_hint = true
+)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct LinksTool {
+    /// URL of the page to extract links from
+    url: String,
+    /// Only return links to the page's own host
+    #[serde(default)]
+    same_host: bool,
+}
+
+impl LinksTool {
+    pub async fn run(&self) -> Result<CallToolResult, CallToolError> {
+        let client = get_client().await;
+
+        let links = fetch_links(client, &self.url, self.same_host)
+            .await
+            .map_err(|e| CallToolError::from_message(e.to_string()))?;
+        let output = serde_json::to_string_pretty(&links)
+            .map_err(|e| CallToolError::from_message(e.to_string()))?;
+
+        Ok(CallToolResult::text_content(vec![TextContent::from(
+            output,
+        )]))
+    }
+}
+
+#[mcp_tool(
+    name = "site_map",
+    description = "Discover a site's structure before crawling it.
+
+Fetches robots.txt and the sitemaps it lists (or /sitemap.xml), follows
+sitemap indexes, and applies the robots rules for the given user agent.
+
+Returns: JSON with the allowed same-host URLs (with lastmod), the number of
+disallowed URLs, the sitemaps read, and the crawl-delay in seconds.",
+    read_only_hint = true,
+    open_world_hint = true
+)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct SiteMapTool {
+    /// Any URL on the site (only scheme, host and port are used)
+    url: String,
+    /// User agent token to evaluate robots rules for (default: nab)
+    #[serde(default)]
+    user_agent: Option<String>,
+}
+
+impl SiteMapTool {
+    pub async fn run(&self) -> Result<CallToolResult, CallToolError> {
+        let client = get_client().await;
+        let user_agent = self.user_agent.as_deref().unwrap_or("nab");
+
+        let map = discover_site_map(client, &self.url, user_agent)
+            .await
+            .map_err(|e| CallToolError::from_message(e.to_string()))?;
+        let output = serde_json::to_string_pretty(&map)
+            .map_err(|e| CallToolError::from_message(e.to_string()))?;
 
         Ok(CallToolResult::text_content(vec![TextContent::from(
             output,
@@ -747,9 +1035,13 @@ tool_box!(
         SubmitTool,
         LoginTool,
         AuthLookupTool,
+        OtpTool,
         FingerprintTool,
         ValidateTool,
-        BenchmarkTool
+        BenchmarkTool,
+        MetricsTool,
+        LinksTool,
+        SiteMapTool
     ]
 );
 
@@ -787,9 +1079,13 @@ impl ServerHandler for MicroFetchHandler {
             MicroFetchTools::SubmitTool(t) => t.run().await,
             MicroFetchTools::LoginTool(t) => t.run().await,
             MicroFetchTools::AuthLookupTool(t) => t.run(),
+            MicroFetchTools::OtpTool(t) => t.run().await,
             MicroFetchTools::FingerprintTool(t) => t.run(),
             MicroFetchTools::ValidateTool(t) => t.run().await,
             MicroFetchTools::BenchmarkTool(t) => t.run().await,
+            MicroFetchTools::MetricsTool(t) => t.run(),
+            MicroFetchTools::LinksTool(t) => t.run().await,
+            MicroFetchTools::SiteMapTool(t) => t.run().await,
         }
     }
 }
@@ -806,6 +1102,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with_writer(std::io::stderr)
         .init();
 
+    // Record request metrics for the `metrics` tool
+    #[cfg(feature = "metrics")]
+    nab::telemetry::install_prometheus()?;
+
     // Pre-initialize the HTTP client
     let _ = get_client().await;
 
@@ -841,3 +1141,125 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Start server
     Ok(server.start().await?)
 }
+
+#[cfg(test)]
+#[path = "../test_support.rs"]
+mod test_support;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{http1_client, serve, Response};
+    use nab::OtpSource;
+
+    /// Serve a fixed page on a local port.
+    fn mock_endpoint(content_type: &'static str, page: String) -> String {
+        let addr = serve(move |_| Response::typed(content_type, page.clone()));
+        format!("http://{addr}/page")
+    }
+
+    fn fetch_tool(url: &str) -> FetchTool {
+        FetchTool {
+            url: url.to_string(),
+            headers: false,
+            body: true,
+            cookies: None,
+            method: None,
+            request_headers: None,
+            data: None,
+            max_chars: default_max_chars(),
+        }
+    }
+
+    #[tokio::test]
+    async fn fetch_tool_returns_structured_content() {
+        let url = mock_endpoint(
+            "text/html",
+            "<html><body><h1>Mock page</h1><p>Hello from nab</p></body></html>".to_string(),
+        );
+        let client = http1_client().await;
+        let tool = fetch_tool(&url);
+
+        let result = tool.run_with_client(&client).await.unwrap();
+
+        let structured = serde_json::Value::Object(result.structured_content.unwrap());
+        assert_eq!(structured["status"], 200);
+        assert_eq!(structured["content_type"], "text/html");
+        assert_eq!(structured["url"], url);
+        assert!(structured["markdown"]
+            .as_str()
+            .unwrap()
+            .contains("Hello from nab"));
+        assert!(structured["elapsed_ms"].is_number());
+    }
+
+    #[tokio::test]
+    async fn fetch_tool_truncates_to_max_chars() {
+        let url = mock_endpoint("text/plain", "x".repeat(5000));
+        let client = http1_client().await;
+        let tool = FetchTool {
+            max_chars: 100,
+            ..fetch_tool(&url)
+        };
+
+        let result = tool.run_with_client(&client).await.unwrap();
+
+        let structured = serde_json::Value::Object(result.structured_content.unwrap());
+        let markdown = structured["markdown"].as_str().unwrap();
+        let (kept, note) = markdown.split_once("\n\n").unwrap();
+        assert_eq!(kept, "x".repeat(100));
+        assert!(note.contains("showing 100 of 5000 chars"), "{note}");
+    }
+
+    #[test]
+    fn truncate_content_counts_characters() {
+        assert_eq!(truncate_content("häïü", 10), "häïü");
+        assert!(truncate_content("häïü", 2).starts_with("hä\n\n"));
+        assert_eq!(truncate_content("häïü", 0), "häïü");
+    }
+
+    #[test]
+    fn body_preview_counts_characters() {
+        assert_eq!(body_preview("short"), "short");
+        let long = "ä".repeat(600);
+        let preview = body_preview(&long);
+        assert_eq!(preview.chars().count(), 503);
+        assert!(preview.ends_with("ä..."));
+    }
+
+    #[tokio::test]
+    async fn otp_tool_returns_code_and_source() {
+        let tool = OtpTool {
+            domain: "https://github.com/login".to_string(),
+        };
+
+        let result = tool
+            .run_with(|domain| {
+                assert_eq!(domain, "github.com");
+                Ok(Some(OtpCode {
+                    code: "482913".to_string(),
+                    source: OtpSource::SmsBeeper,
+                    expires_in_seconds: Some(300),
+                    received_at: None,
+                }))
+            })
+            .await
+            .unwrap();
+
+        let content = serde_json::to_string(&result).unwrap();
+        assert!(content.contains("482913"), "{content}");
+        assert!(content.contains("SMS (Beeper)"), "{content}");
+        assert!(content.contains("300s"), "{content}");
+    }
+
+    #[tokio::test]
+    async fn otp_tool_reports_missing_code() {
+        let tool = OtpTool {
+            domain: "example.com".to_string(),
+        };
+
+        let result = tool.run_with(|_| Ok(None)).await.unwrap();
+        let content = serde_json::to_string(&result).unwrap();
+        assert!(content.contains("No OTP code found"), "{content}");
+    }
+}
@@ -0,0 +1,275 @@
+//! Safari `Cookies.binarycookies` parser
+//!
+//! The file is a big-endian header (magic `cook`, page count, page sizes)
+//! followed by pages. Each page starts with `00 00 01 00`, then a
+//! little-endian cookie count and one offset per cookie, relative to the
+//! page. A cookie record is little-endian throughout:
+//!
+//! | Offset | Field |
+//! |--------|-------|
+//! | 0 | Record size |
+//! | 8 | Flags: `0x1` secure, `0x4` `HttpOnly` |
+//! | 16, 20, 24, 28 | Offsets of the domain, name, path and value strings |
+//! | 40 | Expiry, `f64` seconds since 2001-01-01 (Mac epoch) |
+//!
+//! Strings are NUL-terminated and located relative to the record start.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+
+/// Seconds between the Unix epoch and the Mac epoch (2001-01-01 UTC)
+pub const MAC_EPOCH_OFFSET: i64 = 978_307_200;
+
+const MAGIC: &[u8; 4] = b"cook";
+const PAGE_HEADER: [u8; 4] = [0, 0, 1, 0];
+const FLAG_SECURE: u32 = 0x1;
+const FLAG_HTTP_ONLY: u32 = 0x4;
+/// Fixed part of a cookie record, up to and including the creation date
+const RECORD_HEADER_LEN: usize = 56;
+
+/// One cookie from a `binarycookies` file
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryCookie {
+    pub name: String,
+    pub value: String,
+    /// As stored, e.g. `.example.com` for a domain cookie
+    pub domain: String,
+    pub path: String,
+    /// `None` for session cookies and unrepresentable dates
+    pub expires: Option<DateTime<Utc>>,
+    pub secure: bool,
+    pub http_only: bool,
+}
+
+impl BinaryCookie {
+    /// Whether the cookie has expired at `now`
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+}
+
+/// Parse a whole `Cookies.binarycookies` file
+pub fn parse(data: &[u8]) -> Result<Vec<BinaryCookie>> {
+    if data.get(..4) != Some(MAGIC.as_slice()) {
+        bail!("Not a binarycookies file (missing 'cook' magic)");
+    }
+    let pages = read_u32_be(data, 4).context("Truncated binarycookies header")? as usize;
+    let sizes_end = 8 + pages.checked_mul(4).context("Too many pages")?;
+    if data.len() < sizes_end {
+        bail!("Truncated binarycookies page table ({pages} pages)");
+    }
+
+    let mut cookies = Vec::new();
+    let mut start = sizes_end;
+    for page in 0..pages {
+        let size = read_u32_be(data, 8 + page * 4).unwrap_or_default() as usize;
+        let bytes = start
+            .checked_add(size)
+            .and_then(|end| data.get(start..end))
+            .with_context(|| format!("Truncated binarycookies page {page}"))?;
+        cookies.extend(parse_page(bytes).with_context(|| format!("Invalid page {page}"))?);
+        start += size;
+    }
+    Ok(cookies)
+}
+
+/// Cookies of one page; a malformed record is skipped rather than costing
+/// the rest of the jar
+fn parse_page(page: &[u8]) -> Result<Vec<BinaryCookie>> {
+    if page.get(..4) != Some(PAGE_HEADER.as_slice()) {
+        bail!("Bad page header");
+    }
+    let count = read_u32_le(page, 4).context("Truncated page")? as usize;
+    let mut cookies = Vec::new();
+    for i in 0..count {
+        let Some(offset) = read_u32_le(page, 8 + i * 4) else {
+            tracing::debug!(count, read = i, "Truncated binarycookies offsets");
+            break;
+        };
+        let cookie = page
+            .get(offset as usize..)
+            .context("Cookie offset outside page")
+            .and_then(parse_record);
+        match cookie {
+            Ok(cookie) => cookies.push(cookie),
+            Err(e) => tracing::debug!(cookie = i, error = %e, "Skipping malformed cookie"),
+        }
+    }
+    Ok(cookies)
+}
+
+fn parse_record(record: &[u8]) -> Result<BinaryCookie> {
+    let size = read_u32_le(record, 0).context("Truncated cookie")? as usize;
+    if size < RECORD_HEADER_LEN || size > record.len() {
+        bail!("Cookie size {size} out of range");
+    }
+    let record = &record[..size];
+    let flags = read_u32_le(record, 8).unwrap_or_default();
+    let string = |at: usize| -> Result<String> {
+        let offset = read_u32_le(record, at).unwrap_or_default() as usize;
+        let bytes = record
+            .get(offset..)
+            .context("String offset outside cookie")?;
+        let end = bytes
+            .iter()
+            .position(|&b| b == 0)
+            .context("Unterminated string")?;
+        Ok(String::from_utf8_lossy(&bytes[..end]).into_owned())
+    };
+    let expiry = f64::from_le_bytes(record[40..48].try_into()?);
+
+    Ok(BinaryCookie {
+        domain: string(16)?,
+        name: string(20)?,
+        path: string(24)?,
+        value: string(28)?,
+        expires: mac_time(expiry),
+        secure: flags & FLAG_SECURE != 0,
+        http_only: flags & FLAG_HTTP_ONLY != 0,
+    })
+}
+
+/// Convert Mac-epoch seconds to a UTC time; `0` means no expiry
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // Range checked first; the fraction is in 0..1
+pub fn mac_time(secs: f64) -> Option<DateTime<Utc>> {
+    if !secs.is_finite() || secs == 0.0 || secs.abs() > 1e15 {
+        return None;
+    }
+    let whole = secs.floor();
+    let nanos = ((secs - whole) * 1e9) as u32;
+    DateTime::from_timestamp(whole as i64 + MAC_EPOCH_OFFSET, nanos)
+}
+
+fn read_u32_be(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn read_u32_le(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Cookie record as Safari writes it
+    fn record(
+        domain: &str,
+        name: &str,
+        path: &str,
+        value: &str,
+        flags: u32,
+        expiry: f64,
+    ) -> Vec<u8> {
+        let mut strings = Vec::new();
+        let mut offsets = Vec::new();
+        for s in [domain, name, path, value] {
+            offsets.push(u32::try_from(RECORD_HEADER_LEN + strings.len()).unwrap());
+            strings.extend_from_slice(s.as_bytes());
+            strings.push(0);
+        }
+        let size = u32::try_from(RECORD_HEADER_LEN + strings.len()).unwrap();
+
+        let mut out = Vec::new();
+        out.extend(size.to_le_bytes());
+        out.extend(1u32.to_le_bytes());
+        out.extend(flags.to_le_bytes());
+        out.extend(0u32.to_le_bytes());
+        for offset in offsets {
+            out.extend(offset.to_le_bytes());
+        }
+        out.extend([0u8; 8]);
+        out.extend(expiry.to_le_bytes());
+        out.extend(0f64.to_le_bytes());
+        out.extend(strings);
+        out
+    }
+
+    fn page(records: &[Vec<u8>]) -> Vec<u8> {
+        let mut out = PAGE_HEADER.to_vec();
+        out.extend(u32::try_from(records.len()).unwrap().to_le_bytes());
+        let mut offset = 8 + records.len() * 4 + 4;
+        for record in records {
+            out.extend(u32::try_from(offset).unwrap().to_le_bytes());
+            offset += record.len();
+        }
+        out.extend([0u8; 4]);
+        for record in records {
+            out.extend(record);
+        }
+        out
+    }
+
+    fn file(pages: &[Vec<u8>]) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        out.extend(u32::try_from(pages.len()).unwrap().to_be_bytes());
+        for page in pages {
+            out.extend(u32::try_from(page.len()).unwrap().to_be_bytes());
+        }
+        for page in pages {
+            out.extend(page);
+        }
+        // Checksum and plist trailer, ignored
+        out.extend([0u8; 8]);
+        out
+    }
+
+    #[test]
+    fn parses_cookies_across_pages() {
+        let data = file(&[
+            page(&[
+                record(".example.com", "sid", "/", "abc", 0x5, 0.0),
+                record("www.example.com", "pref", "/app", "dark", 0x4, 0.0),
+            ]),
+            page(&[record(".other.org", "t", "/", "1", 0x1, 0.0)]),
+        ]);
+        let cookies = parse(&data).unwrap();
+
+        assert_eq!(cookies.len(), 3);
+        assert_eq!(cookies[0].name, "sid");
+        assert_eq!(cookies[0].value, "abc");
+        assert_eq!(cookies[0].domain, ".example.com");
+        assert!(cookies[0].secure && cookies[0].http_only);
+        assert_eq!(cookies[1].path, "/app");
+        assert!(!cookies[1].secure && cookies[1].http_only);
+        assert!(cookies[2].secure && !cookies[2].http_only);
+        assert_eq!(cookies[2].expires, None);
+    }
+
+    #[test]
+    fn converts_mac_epoch_expiry() {
+        // 2026-01-01T00:00:00Z is 788_918_400 seconds after 2001-01-01
+        let data = file(&[page(&[record(".a.com", "n", "/", "v", 0, 788_918_400.5)])]);
+        let cookie = &parse(&data).unwrap()[0];
+        let expires = cookie.expires.unwrap();
+
+        assert_eq!(expires.timestamp(), 1_767_225_600);
+        assert_eq!(expires.timestamp_subsec_millis(), 500);
+        assert!(cookie.is_expired(expires));
+        assert!(!cookie.is_expired(DateTime::from_timestamp(1_700_000_000, 0).unwrap()));
+        assert_eq!(mac_time(0.0), None);
+        assert_eq!(mac_time(f64::NAN), None);
+    }
+
+    #[test]
+    fn rejects_malformed_files() {
+        assert!(parse(b"SQLite format 3\0").is_err());
+        assert!(parse(b"cook\0\0\0\x02\0\0\0\x10").is_err());
+    }
+
+    #[test]
+    fn skips_malformed_records() {
+        let mut data = file(&[page(&[
+            record(".a.com", "bad", "/", "v", 0, 0.0),
+            record(".b.com", "good", "/", "v", 0, 0.0),
+        ])]);
+        // Point the first name outside its record: file header, page header
+        // with two offsets, field
+        let name_offset = 12 + 20 + 20;
+        data[name_offset..name_offset + 4].copy_from_slice(&10_000u32.to_le_bytes());
+
+        let cookies = parse(&data).unwrap();
+        assert_eq!(cookies.len(), 1);
+        assert_eq!(cookies[0].name, "good");
+    }
+}
@@ -1,13 +1,22 @@
 //! Browser Detection
 //!
 //! Automatically detects the default web browser on the system.
-//! Supports macOS, Linux, and Windows.
+//! Supports macOS, Linux, and Windows. [`BrowserCache`] keeps the result in a
+//! state file so repeated runs skip the lookup.
 
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long a cached detection is trusted before detecting again
+pub const DETECTION_TTL: Duration = Duration::from_hours(24);
 
 /// Detected browser type
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum BrowserType {
     Brave,
     Chrome,
@@ -32,6 +41,24 @@ impl BrowserType {
     }
 }
 
+impl std::str::FromStr for BrowserType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "brave" => Ok(BrowserType::Brave),
+            "chrome" => Ok(BrowserType::Chrome),
+            "firefox" => Ok(BrowserType::Firefox),
+            "safari" => Ok(BrowserType::Safari),
+            "edge" => Ok(BrowserType::Edge),
+            "dia" => Ok(BrowserType::Dia),
+            other => anyhow::bail!(
+                "Unknown browser '{other}' (expected brave, chrome, firefox, safari, edge or dia)"
+            ),
+        }
+    }
+}
+
 /// Detect the default web browser on the current system
 pub fn detect_default_browser() -> Result<BrowserType> {
     #[cfg(target_os = "macos")]
@@ -55,6 +82,146 @@ pub fn detect_default_browser() -> Result<BrowserType> {
     }
 }
 
+/// Contents of the browser state file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct BrowserState {
+    detected: Option<BrowserType>,
+    /// Unix seconds of the detection
+    detected_at: Option<u64>,
+    last_explicit: Option<String>,
+}
+
+/// Default-browser detection cached in `<cache dir>/nab/browser.json`, along
+/// with the browser last chosen explicitly (e.g. `--cookies firefox`)
+#[derive(Debug, Clone)]
+pub struct BrowserCache {
+    path: PathBuf,
+    ttl: Duration,
+}
+
+impl Default for BrowserCache {
+    fn default() -> Self {
+        Self::new(
+            dirs::cache_dir()
+                .unwrap_or_else(std::env::temp_dir)
+                .join("nab")
+                .join("browser.json"),
+        )
+    }
+}
+
+impl BrowserCache {
+    /// Cache stored at `path`, trusted for [`DETECTION_TTL`]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            ttl: DETECTION_TTL,
+        }
+    }
+
+    /// Detect again once a result is older than `ttl`
+    #[must_use]
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Default browser, detected only when the cached result is missing or stale
+    pub fn default_browser(&self) -> Result<BrowserType> {
+        self.default_browser_with(detect_default_browser)
+    }
+
+    /// [`default_browser`](Self::default_browser) with a custom detector
+    pub fn default_browser_with(
+        &self,
+        detect: impl FnOnce() -> Result<BrowserType>,
+    ) -> Result<BrowserType> {
+        let state = self.load();
+        if let (Some(browser), Some(at)) = (state.detected, state.detected_at) {
+            if unix_now().saturating_sub(at) < self.ttl.as_secs() {
+                return Ok(browser);
+            }
+        }
+        Ok(self.store_detection(state, detect()?))
+    }
+
+    /// Detect now, replacing the cached result
+    pub fn refresh(&self) -> Result<BrowserType> {
+        self.refresh_with(detect_default_browser)
+    }
+
+    /// [`refresh`](Self::refresh) with a custom detector
+    pub fn refresh_with(
+        &self,
+        detect: impl FnOnce() -> Result<BrowserType>,
+    ) -> Result<BrowserType> {
+        Ok(self.store_detection(self.load(), detect()?))
+    }
+
+    /// Browser last chosen explicitly; `--cookies auto` falls back to it
+    /// when detection fails
+    pub fn last_explicit(&self) -> Option<String> {
+        self.load().last_explicit
+    }
+
+    /// Record an explicitly chosen browser
+    pub fn remember_explicit(&self, browser: &str) -> Result<()> {
+        let mut state = self.load();
+        let browser = browser.trim().to_lowercase();
+        if state.last_explicit.as_deref() == Some(browser.as_str()) {
+            return Ok(());
+        }
+        state.last_explicit = Some(browser);
+        self.save(&state)
+    }
+
+    fn store_detection(&self, mut state: BrowserState, browser: BrowserType) -> BrowserType {
+        state.detected = Some(browser);
+        state.detected_at = Some(unix_now());
+        // The cache only saves time; a read-only cache dir must not fail the lookup
+        if let Err(e) = self.save(&state) {
+            tracing::debug!("Failed to cache browser detection in {:?}: {e}", self.path);
+        }
+        browser
+    }
+
+    /// Stored state; missing or unreadable files count as empty
+    fn load(&self) -> BrowserState {
+        std::fs::read(&self.path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    /// Replace the state file atomically, so concurrent runs (or batch
+    /// tasks) never read a half-written file
+    fn save(&self, state: &BrowserState) -> Result<()> {
+        static NEXT_PART: AtomicUsize = AtomicUsize::new(0);
+
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut partial = self.path.clone().into_os_string();
+        partial.push(format!(
+            ".{}.{}.part",
+            std::process::id(),
+            NEXT_PART.fetch_add(1, Ordering::Relaxed)
+        ));
+        let written = std::fs::write(&partial, serde_json::to_vec_pretty(state)?)
+            .and_then(|()| std::fs::rename(&partial, &self.path));
+        if written.is_err() {
+            let _ = std::fs::remove_file(&partial);
+        }
+        Ok(written?)
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
 #[cfg(target_os = "macos")]
 fn detect_macos_default_browser() -> Result<BrowserType> {
     // Try to get default browser from LaunchServices
@@ -262,4 +429,96 @@ mod tests {
         let result = detect_default_browser();
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn cache_detects_once_within_ttl() {
+        let path = std::env::temp_dir().join(format!("nab-browser-{}.json", uuid::Uuid::new_v4()));
+        let cache = BrowserCache::new(&path);
+        let calls = std::cell::Cell::new(0);
+        let detect = |browser| {
+            calls.set(calls.get() + 1);
+            Ok(browser)
+        };
+
+        assert_eq!(
+            cache
+                .default_browser_with(|| detect(BrowserType::Firefox))
+                .unwrap(),
+            BrowserType::Firefox
+        );
+        assert_eq!(
+            cache
+                .default_browser_with(|| detect(BrowserType::Brave))
+                .unwrap(),
+            BrowserType::Firefox
+        );
+        assert_eq!(calls.get(), 1);
+
+        assert_eq!(
+            cache.refresh_with(|| detect(BrowserType::Brave)).unwrap(),
+            BrowserType::Brave
+        );
+        assert_eq!(
+            cache
+                .default_browser_with(|| detect(BrowserType::Safari))
+                .unwrap(),
+            BrowserType::Brave
+        );
+        assert_eq!(calls.get(), 2);
+
+        let expired = BrowserCache::new(&path).with_ttl(Duration::ZERO);
+        assert_eq!(
+            expired
+                .default_browser_with(|| detect(BrowserType::Safari))
+                .unwrap(),
+            BrowserType::Safari
+        );
+        assert_eq!(calls.get(), 3);
+
+        cache.remember_explicit("Edge").unwrap();
+        assert_eq!(cache.last_explicit().as_deref(), Some("edge"));
+        assert_eq!(
+            cache
+                .default_browser_with(|| detect(BrowserType::Chrome))
+                .unwrap(),
+            BrowserType::Safari
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn explicit_choice_is_written_atomically_and_only_on_change() {
+        let dir = std::env::temp_dir().join(format!("nab-browser-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("browser.json");
+        let cache = BrowserCache::new(&path);
+
+        cache.remember_explicit("firefox").unwrap();
+        let long_ago = UNIX_EPOCH + Duration::from_secs(1_000_000);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(long_ago)
+            .unwrap();
+        cache.remember_explicit("Firefox").unwrap();
+        assert_eq!(
+            std::fs::metadata(&path).unwrap().modified().unwrap(),
+            long_ago
+        );
+
+        cache.remember_explicit("brave").unwrap();
+        assert_ne!(
+            std::fs::metadata(&path).unwrap().modified().unwrap(),
+            long_ago
+        );
+        assert_eq!(cache.last_explicit().as_deref(), Some("brave"));
+        // Only the state file is left behind, no partial writes
+        let files: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(files, ["browser.json"]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,414 @@
+//! Record/replay cassettes for deterministic tests (VCR-style)
+//!
+//! In [`CassetteMode::Record`] every request made through an
+//! [`AcceleratedClient`](crate::AcceleratedClient) goes to the network and
+//! the request/response pair is appended to a JSON cassette file. In
+//! [`CassetteMode::Replay`] responses are served from the cassette without
+//! touching the network; requests are matched on method, URL and body.
+//!
+//! Large response bodies can be stored compressed (see [`BodyCodec`]); the
+//! codec is kept with each entry, so replay decodes them transparently.
+//!
+//! ```rust,no_run
+//! use nab::{AcceleratedClient, CassetteMode};
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let client = AcceleratedClient::new()?
+//!     .with_cassette("tests/cassettes/example.json", CassetteMode::Replay)?;
+//! let html = client.fetch_text("https://example.com").await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use reqwest::{Client, Request, Response};
+use serde::{Deserialize, Serialize};
+
+/// Whether a cassette captures live traffic or serves recorded responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Hit the network and append each interaction to the cassette file
+    Record,
+    /// Serve responses from the cassette; unmatched requests are errors
+    Replay,
+}
+
+/// Bodies smaller than this are stored as-is even with a codec configured
+const MIN_COMPRESSED_BODY: usize = 1024;
+
+/// Compression applied to recorded response bodies
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BodyCodec {
+    /// Store bodies uncompressed, as text where possible
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+impl BodyCodec {
+    fn encode(self, bytes: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::None => Ok(bytes.to_vec()),
+            Self::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(bytes)?;
+                Ok(encoder.finish()?)
+            }
+            Self::Zstd => Ok(zstd::stream::encode_all(bytes, 0)?),
+        }
+    }
+
+    fn decode(self, bytes: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::None => Ok(bytes.to_vec()),
+            Self::Gzip => {
+                let mut out = Vec::new();
+                flate2::read::GzDecoder::new(bytes).read_to_end(&mut out)?;
+                Ok(out)
+            }
+            Self::Zstd => Ok(zstd::stream::decode_all(bytes)?),
+        }
+    }
+}
+
+impl FromStr for BodyCodec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "gzip" | "gz" => Ok(Self::Gzip),
+            "zstd" => Ok(Self::Zstd),
+            other => bail!("Unknown body codec '{other}' (expected none, gzip or zstd)"),
+        }
+    }
+}
+
+/// Body bytes, stored as text when valid UTF-8 to keep cassettes readable,
+/// or compressed and base64-encoded when a [`BodyCodec`] applies
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum RecordedBody {
+    Text(String),
+    Binary(Vec<u8>),
+    Compressed { codec: BodyCodec, base64: String },
+}
+
+impl RecordedBody {
+    fn from_bytes(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => Self::Text(text.to_string()),
+            Err(_) => Self::Binary(bytes.to_vec()),
+        }
+    }
+
+    /// Compress with `codec` when the body is large enough to benefit
+    fn encode(bytes: &[u8], codec: BodyCodec) -> Result<Self> {
+        if codec == BodyCodec::None || bytes.len() < MIN_COMPRESSED_BODY {
+            return Ok(Self::from_bytes(bytes));
+        }
+        Ok(Self::Compressed {
+            codec,
+            base64: base64::engine::general_purpose::STANDARD.encode(codec.encode(bytes)?),
+        })
+    }
+
+    fn into_bytes(self) -> Result<Vec<u8>> {
+        match self {
+            Self::Text(text) => Ok(text.into_bytes()),
+            Self::Binary(bytes) => Ok(bytes),
+            Self::Compressed { codec, base64 } => {
+                let compressed = base64::engine::general_purpose::STANDARD
+                    .decode(base64)
+                    .context("Invalid base64 in compressed cassette body")?;
+                codec
+                    .decode(&compressed)
+                    .with_context(|| format!("Failed to decode {codec:?} cassette body"))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<RecordedBody>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: RecordedBody,
+}
+
+/// A single recorded request/response pair
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+/// A cassette file bound to a record or replay mode
+#[derive(Debug)]
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    codec: BodyCodec,
+    interactions: Mutex<Vec<Interaction>>,
+    /// Replay cursor: interactions already served, so repeated identical
+    /// requests replay in recorded order
+    served: Mutex<Vec<bool>>,
+}
+
+impl Cassette {
+    /// Open a cassette. Replay mode requires the file to exist; record mode
+    /// starts empty and overwrites the file on the first recorded request.
+    pub fn open(path: impl AsRef<Path>, mode: CassetteMode) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let interactions = match mode {
+            CassetteMode::Record => Vec::new(),
+            CassetteMode::Replay => {
+                let raw = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read cassette {}", path.display()))?;
+                serde_json::from_str::<CassetteFile>(&raw)
+                    .with_context(|| format!("Invalid cassette {}", path.display()))?
+                    .interactions
+            }
+        };
+        let served = vec![false; interactions.len()];
+
+        Ok(Self {
+            path,
+            mode,
+            codec: BodyCodec::None,
+            interactions: Mutex::new(interactions),
+            served: Mutex::new(served),
+        })
+    }
+
+    /// Compress response bodies recorded from now on with `codec`
+    ///
+    /// Replay always honours the codec stored with each entry.
+    #[must_use]
+    pub fn with_codec(mut self, codec: BodyCodec) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    /// Cassette mode
+    #[must_use]
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// Cassette file path
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Execute `request` through the cassette: record it from the network or
+    /// replay the matching recorded response.
+    ///
+    /// Replayed responses carry the recorded status, headers and body, but
+    /// `Response::url()` is not preserved.
+    pub async fn execute(&self, client: &Client, request: Request) -> Result<Response> {
+        let recorded_request = RecordedRequest {
+            method: request.method().to_string(),
+            url: request.url().to_string(),
+            body: request
+                .body()
+                .and_then(reqwest::Body::as_bytes)
+                .map(RecordedBody::from_bytes),
+        };
+
+        match self.mode {
+            CassetteMode::Replay => self.replay(&recorded_request),
+            CassetteMode::Record => {
+                let response = client.execute(request).await?;
+                let status = response.status().as_u16();
+                let headers: Vec<(String, String)> = response
+                    .headers()
+                    .iter()
+                    .filter_map(|(name, value)| {
+                        value
+                            .to_str()
+                            .ok()
+                            .map(|v| (name.to_string(), v.to_string()))
+                    })
+                    .collect();
+                let body = response.bytes().await?;
+
+                let recorded = RecordedResponse {
+                    status,
+                    headers: headers.clone(),
+                    body: RecordedBody::encode(&body, self.codec)?,
+                };
+                self.record(Interaction {
+                    request: recorded_request,
+                    response: recorded,
+                })?;
+                into_response(RecordedResponse {
+                    status,
+                    headers,
+                    body: RecordedBody::Binary(body.to_vec()),
+                })
+            }
+        }
+    }
+
+    fn replay(&self, request: &RecordedRequest) -> Result<Response> {
+        let interactions = self.interactions.lock().expect("cassette lock poisoned");
+        let mut served = self.served.lock().expect("cassette lock poisoned");
+
+        let matching: Vec<usize> = interactions
+            .iter()
+            .enumerate()
+            .filter(|(_, i)| {
+                i.request.method == request.method
+                    && i.request.url == request.url
+                    && i.request.body == request.body
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+
+        // Serve the next unplayed match, then keep repeating the last one
+        let Some(&idx) = matching
+            .iter()
+            .find(|&&idx| !served[idx])
+            .or_else(|| matching.last())
+        else {
+            bail!(
+                "No recorded interaction for {} {} in cassette {}",
+                request.method,
+                request.url,
+                self.path.display()
+            );
+        };
+        served[idx] = true;
+
+        into_response(interactions[idx].response.clone())
+    }
+
+    fn record(&self, interaction: Interaction) -> Result<()> {
+        let mut interactions = self.interactions.lock().expect("cassette lock poisoned");
+        interactions.push(interaction);
+
+        let file = CassetteFile {
+            interactions: interactions.clone(),
+        };
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(&file)?)
+            .with_context(|| format!("Failed to write cassette {}", self.path.display()))
+    }
+}
+
+fn into_response(recorded: RecordedResponse) -> Result<Response> {
+    let mut builder = http::Response::builder().status(recorded.status);
+    for (name, value) in &recorded.headers {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+    Ok(Response::from(builder.body(recorded.body.into_bytes()?)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{serve, Response};
+
+    #[test]
+    fn test_recorded_body_roundtrip() {
+        let text = RecordedBody::from_bytes(b"hello");
+        assert_eq!(text, RecordedBody::Text("hello".into()));
+
+        let binary = RecordedBody::from_bytes(&[0xff, 0x00, 0xfe]);
+        assert_eq!(binary.clone().into_bytes().unwrap(), vec![0xff, 0x00, 0xfe]);
+
+        let json = serde_json::to_string(&binary).unwrap();
+        assert_eq!(serde_json::from_str::<RecordedBody>(&json).unwrap(), binary);
+    }
+
+    #[tokio::test]
+    async fn test_zstd_body_roundtrip_shrinks_cassette() {
+        let path = std::env::temp_dir().join(format!("nab-cassette-{}.json", uuid::Uuid::new_v4()));
+        let body: Vec<u8> = (0..20_000)
+            .flat_map(|i| format!("<p>row {i} of a large page</p>\n").into_bytes())
+            .collect();
+        let served = body.clone();
+        let addr = serve(move |_| Response::typed("text/html", served.clone()));
+        let url = format!("http://{addr}/large");
+        let client = Client::new();
+
+        let recorder = Cassette::open(&path, CassetteMode::Record)
+            .unwrap()
+            .with_codec(BodyCodec::Zstd);
+        let recorded = recorder
+            .execute(&client, client.get(&url).build().unwrap())
+            .await
+            .unwrap();
+        // The caller gets the body as served, not the stored encoding
+        assert_eq!(recorded.bytes().await.unwrap(), body);
+
+        let on_disk = std::fs::metadata(&path).unwrap().len();
+        assert!(on_disk < body.len() as u64 / 10, "{on_disk} bytes on disk");
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .contains(r#""codec": "zstd""#));
+
+        let player = Cassette::open(&path, CassetteMode::Replay).unwrap();
+        let replayed = player
+            .execute(&client, client.get(&url).build().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(replayed.headers()["content-type"], "text/html");
+        assert_eq!(replayed.bytes().await.unwrap(), body);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_gzip_and_small_bodies() {
+        let large = vec![b'x'; 4096];
+        let gzipped = RecordedBody::encode(&large, BodyCodec::Gzip).unwrap();
+        assert!(matches!(
+            gzipped,
+            RecordedBody::Compressed {
+                codec: BodyCodec::Gzip,
+                ..
+            }
+        ));
+        assert_eq!(gzipped.into_bytes().unwrap(), large);
+
+        // Small bodies stay readable
+        assert_eq!(
+            RecordedBody::encode(b"hi", BodyCodec::Zstd).unwrap(),
+            RecordedBody::Text("hi".into())
+        );
+        assert_eq!("ZSTD".parse::<BodyCodec>().unwrap(), BodyCodec::Zstd);
+        assert!("brotli".parse::<BodyCodec>().is_err());
+    }
+
+    #[test]
+    fn test_replay_requires_existing_file() {
+        let missing = std::env::temp_dir().join("nab-cassette-does-not-exist.json");
+        assert!(Cassette::open(missing, CassetteMode::Replay).is_err());
+    }
+}
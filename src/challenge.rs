@@ -0,0 +1,200 @@
+//! Anti-bot challenge detection
+//!
+//! Recognises interstitials served instead of content (Cloudflare "Just a
+//! moment...", managed challenges, firewall blocks, DataDome, PerimeterX) so
+//! callers can report them rather than treating the challenge HTML as the page.
+//!
+//! ```rust
+//! use nab::challenge::{detect_challenge, ChallengeType};
+//! use reqwest::header::HeaderMap;
+//!
+//! let html = "<html><head><title>Just a moment...</title></head></html>";
+//! let challenge = detect_challenge(503, &HeaderMap::new(), html).unwrap();
+//! assert_eq!(challenge.kind, ChallengeType::CloudflareJs);
+//! ```
+
+use std::fmt;
+
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+use thiserror::Error;
+
+/// Only the head of the body is scanned; challenge markers sit near the top
+const SCAN_LIMIT: usize = 64 * 1024;
+
+/// Kind of anti-bot interstitial
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChallengeType {
+    /// Cloudflare JavaScript challenge ("Just a moment...")
+    CloudflareJs,
+    /// Cloudflare managed challenge / Turnstile (`cf-mitigated: challenge`)
+    CloudflareManaged,
+    /// Cloudflare firewall block ("Attention Required!", error 1020)
+    CloudflareBlock,
+    /// DataDome captcha
+    DataDome,
+    /// PerimeterX / HUMAN "press & hold" challenge
+    PerimeterX,
+}
+
+impl fmt::Display for ChallengeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::CloudflareJs => "Cloudflare JS challenge",
+            Self::CloudflareManaged => "Cloudflare managed challenge",
+            Self::CloudflareBlock => "Cloudflare block",
+            Self::DataDome => "DataDome captcha",
+            Self::PerimeterX => "PerimeterX challenge",
+        })
+    }
+}
+
+/// A challenge page was returned instead of the requested content
+#[derive(Debug, Clone, Error, Serialize)]
+#[error("{kind} detected (HTTP {status}); response is a challenge page, not content")]
+pub struct ChallengeDetected {
+    pub kind: ChallengeType,
+    pub status: u16,
+}
+
+impl ChallengeDetected {
+    /// What to try next
+    #[must_use]
+    pub fn suggestion(&self) -> &'static str {
+        match self.kind {
+            ChallengeType::CloudflareBlock => {
+                "The site blocks this client outright; browser cookies from a session that passed (--cookies brave|chrome|...) may help"
+            }
+            _ => {
+                "Reuse cookies from a browser that passed the challenge (--cookies brave|chrome|...) or render the page with --render"
+            }
+        }
+    }
+}
+
+/// Detect an anti-bot challenge from the response status, headers and body.
+///
+/// Body markers only count on blocking statuses (403/429/503), since sites
+/// behind Cloudflare bot management embed challenge scripts in normal pages.
+#[must_use]
+pub fn detect_challenge(status: u16, headers: &HeaderMap, body: &str) -> Option<ChallengeDetected> {
+    let found = |kind| Some(ChallengeDetected { kind, status });
+
+    if headers
+        .get("cf-mitigated")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.eq_ignore_ascii_case("challenge"))
+    {
+        return found(ChallengeType::CloudflareManaged);
+    }
+
+    let mut end = SCAN_LIMIT.min(body.len());
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    let head = &body[..end];
+    let title = page_title(head).unwrap_or_default().to_lowercase();
+
+    if title == "just a moment..." || title == "just a moment…" {
+        return found(ChallengeType::CloudflareJs);
+    }
+
+    if !matches!(status, 403 | 429 | 503) {
+        return None;
+    }
+
+    let lower = head.to_lowercase();
+    let cloudflare = headers
+        .get("server")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.eq_ignore_ascii_case("cloudflare"))
+        || lower.contains("cloudflare");
+
+    if lower.contains("__cf_chl") || lower.contains("cf-chl") || lower.contains("_cf_chl_opt") {
+        return if lower.contains("turnstile") {
+            found(ChallengeType::CloudflareManaged)
+        } else {
+            found(ChallengeType::CloudflareJs)
+        };
+    }
+    if cloudflare
+        && (title.starts_with("attention required!")
+            || lower.contains("error code: 1020")
+            || lower.contains("cf-error-details"))
+    {
+        return found(ChallengeType::CloudflareBlock);
+    }
+    if lower.contains("captcha-delivery.com") || lower.contains("datadome") {
+        return found(ChallengeType::DataDome);
+    }
+    if lower.contains("px-captcha") || lower.contains("_pxappid") {
+        return found(ChallengeType::PerimeterX);
+    }
+
+    None
+}
+
+fn page_title(html: &str) -> Option<&str> {
+    let lower = html.to_ascii_lowercase();
+    let open = lower.find("<title")?;
+    let start = open + lower[open..].find('>')? + 1;
+    let end = start + lower[start..].find("</title")?;
+    html.get(start..end).map(str::trim)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    const CF_JS_CHALLENGE: &str = r#"<!DOCTYPE html><html lang="en-US"><head><title>Just a moment...</title>
+<meta http-equiv="refresh" content="390"></head><body><div class="main-wrapper" role="main">
+<noscript>Enable JavaScript and cookies to continue</noscript></div>
+<script>(function(){window._cf_chl_opt={cvId: '3',cZone: "example.com",cType: 'managed'};
+var cpo=document.createElement('script');cpo.src='/cdn-cgi/challenge-platform/h/g/orchestrate/chl_page/v1?ray=8a';
+})();</script></body></html>"#;
+
+    const CF_BLOCK: &str = r#"<!DOCTYPE html><html><head><title>Attention Required! | Cloudflare</title></head>
+<body><div id="cf-wrapper"><div id="cf-error-details"><h1>Sorry, you have been blocked</h1>
+<p>This website is using a security service to protect itself from online attacks.</p>
+<span>Cloudflare Ray ID: 8a1b2c3d4e</span></div></div></body></html>"#;
+
+    const NORMAL_PAGE: &str = r#"<!DOCTYPE html><html><head><title>Release notes</title>
+<script src="/cdn-cgi/challenge-platform/scripts/jsd/main.js"></script></head>
+<body><article><h1>Version 2.0</h1><p>We fixed the moment handling bug.</p></article></body></html>"#;
+
+    #[test]
+    fn detects_cloudflare_js_challenge() {
+        let challenge = detect_challenge(403, &HeaderMap::new(), CF_JS_CHALLENGE).unwrap();
+        assert_eq!(challenge.kind, ChallengeType::CloudflareJs);
+        assert_eq!(challenge.status, 403);
+        assert!(challenge.to_string().contains("Cloudflare JS challenge"));
+        assert!(challenge.suggestion().contains("--render"));
+    }
+
+    #[test]
+    fn detects_cf_mitigated_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert("cf-mitigated", HeaderValue::from_static("challenge"));
+        let challenge = detect_challenge(403, &headers, "").unwrap();
+        assert_eq!(challenge.kind, ChallengeType::CloudflareManaged);
+    }
+
+    #[test]
+    fn detects_cloudflare_block() {
+        let mut headers = HeaderMap::new();
+        headers.insert("server", HeaderValue::from_static("cloudflare"));
+        let challenge = detect_challenge(403, &headers, CF_BLOCK).unwrap();
+        assert_eq!(challenge.kind, ChallengeType::CloudflareBlock);
+    }
+
+    #[test]
+    fn normal_pages_are_not_flagged() {
+        let mut headers = HeaderMap::new();
+        headers.insert("server", HeaderValue::from_static("cloudflare"));
+        assert!(detect_challenge(200, &headers, NORMAL_PAGE).is_none());
+        assert!(detect_challenge(404, &headers, NORMAL_PAGE).is_none());
+        assert!(detect_challenge(200, &HeaderMap::new(), "plain text body").is_none());
+    }
+}
@@ -1,23 +1,51 @@
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use anyhow::Result;
+use nab::analyze::DownloadProgress;
 
 use crate::AnalyzeOutputFormat;
 
-pub async fn cmd_analyze(
-    video: &str,
-    audio_only: bool,
-    diarize: bool,
-    format: AnalyzeOutputFormat,
-    output: Option<PathBuf>,
-    dgx: bool,
-    api_key: Option<&str>,
-) -> Result<()> {
+/// `nab analyze` flags
+#[allow(clippy::struct_excessive_bools)] // Independent flags
+pub struct AnalyzeOptions<'a> {
+    pub audio_only: bool,
+    pub diarize: bool,
+    pub format: AnalyzeOutputFormat,
+    pub output: Option<PathBuf>,
+    pub lang: Option<&'a str>,
+    pub no_cache: bool,
+    pub model_dir: Option<PathBuf>,
+    pub remote: Option<&'a str>,
+    pub remote_token: Option<&'a str>,
+    pub remote_stages: &'a [String],
+    /// Fail instead of running a stage locally when the worker fails
+    pub remote_required: bool,
+    pub api_key: Option<&'a str>,
+}
+
+pub async fn cmd_analyze(video: &str, options: AnalyzeOptions<'_>) -> Result<()> {
     use nab::analyze::{
         report::{AnalysisReport, ReportFormat},
-        AnalysisPipeline, PipelineConfig as AnalysisConfig, VisionBackend,
+        AnalysisPipeline, ModelStore, PipelineConfig as AnalysisConfig, RemoteStage, RemoteWorker,
+        VisionBackend,
     };
 
+    let AnalyzeOptions {
+        audio_only,
+        diarize,
+        format,
+        output,
+        lang,
+        no_cache,
+        model_dir,
+        remote,
+        remote_token,
+        remote_stages,
+        remote_required,
+        api_key,
+    } = options;
+
     eprintln!("🎬 Analyzing: {video}");
 
     // Auto-detect audio-only files by extension
@@ -37,9 +65,43 @@ pub async fn cmd_analyze(
     // Build configuration
     let mut config = AnalysisConfig::default();
 
-    if dgx {
-        config.dgx_host = Some("spark".to_string());
-        eprintln!("   GPU: DGX Spark (nvfp4 quantization)");
+    let models = model_dir.map_or_else(ModelStore::default, ModelStore::new);
+    config.models = models.with_progress(Arc::new(print_download_progress));
+
+    if let Some(endpoint) = remote {
+        let mut worker = RemoteWorker::new(endpoint)?;
+        let token = remote_token
+            .map(str::to_string)
+            .or_else(|| std::env::var("NAB_REMOTE_TOKEN").ok());
+        if let Some(token) = token {
+            worker = worker.with_token(token);
+        }
+        if !remote_stages.is_empty() {
+            let stages = remote_stages
+                .iter()
+                .map(|s| s.parse::<RemoteStage>())
+                .collect::<Result<Vec<_>, _>>()?;
+            worker = worker.with_stages(stages);
+        }
+        if remote_required {
+            worker = worker.without_fallback();
+        }
+        let stages: Vec<String> = RemoteStage::ALL
+            .into_iter()
+            .filter(|s| worker.handles(*s))
+            .map(|s| s.to_string())
+            .collect();
+        eprintln!("   Remote: {} ({})", worker.endpoint(), stages.join(", "));
+        config.remote = Some(worker);
+    }
+
+    if let Some(lang) = lang {
+        config.language = Some(lang.to_string());
+        eprintln!("   Language: {lang}");
+    }
+
+    if no_cache {
+        config.cache_dir = None;
     }
 
     config.enable_diarization = diarize;
@@ -55,6 +117,12 @@ pub async fn cmd_analyze(
             api_key: key.to_string(),
         };
         eprintln!("   Vision: Claude API");
+    } else if config
+        .remote
+        .as_ref()
+        .is_some_and(|worker| worker.handles(RemoteStage::Vision))
+    {
+        eprintln!("   Vision: remote worker");
     } else if let Ok(key) = std::env::var("ANTHROPIC_API_KEY") {
         config.vision_backend = VisionBackend::ClaudeApi { api_key: key };
         eprintln!("   Vision: Claude API (from ANTHROPIC_API_KEY)");
@@ -94,6 +162,14 @@ pub async fn cmd_analyze(
         println!("{report}");
     }
 
+    if let Some(ref lang) = analysis.language {
+        super::annotate::print_language(
+            lang,
+            analysis.language_confidence,
+            analysis.language_uncertain(),
+        );
+    }
+
     if let Some(ref meta) = analysis.metadata {
         eprintln!(
             "\n📊 Video: {}x{} @ {:.1}fps, {:.1}s",
@@ -113,3 +189,27 @@ pub async fn cmd_analyze(
 
     Ok(())
 }
+
+/// Render a one-line progress bar for a model download
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+fn print_download_progress(p: DownloadProgress<'_>) {
+    const WIDTH: u64 = 30;
+    let mb = p.bytes_downloaded as f64 / 1_000_000.0;
+    match p.bytes_total.filter(|&total| total > 0) {
+        Some(total) => {
+            let filled = (p.bytes_downloaded * WIDTH / total).min(WIDTH);
+            eprint!(
+                "\r   📥 Whisper {}: [{}{}] {:>3}% ({mb:.1}/{:.1} MB)",
+                p.model,
+                "#".repeat(filled as usize),
+                "-".repeat((WIDTH - filled) as usize),
+                p.bytes_downloaded * 100 / total,
+                total as f64 / 1_000_000.0
+            );
+            if p.bytes_downloaded >= total {
+                eprintln!();
+            }
+        }
+        None => eprint!("\r   📥 Whisper {}: {mb:.1} MB", p.model),
+    }
+}
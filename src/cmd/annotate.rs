@@ -2,19 +2,22 @@ use anyhow::Result;
 
 use crate::OverlayStyleArg;
 
+#[allow(clippy::too_many_arguments)]
 pub async fn cmd_annotate(
     video: &str,
-    output: &str,
+    output: Option<&str>,
+    subs_only: Option<&str>,
     subtitles: bool,
     speaker_labels: bool,
     analysis: bool,
+    karaoke: bool,
     style: OverlayStyleArg,
     hwaccel: bool,
+    lang: Option<&str>,
 ) -> Result<()> {
     use nab::annotate::{AnalysisConfig, AnnotationPipeline, PipelineConfig};
 
     eprintln!("🎬 Annotating: {video}");
-    eprintln!("   Output: {output}");
 
     let mut config = match style {
         OverlayStyleArg::Minimal => PipelineConfig::default(),
@@ -29,6 +32,11 @@ pub async fn cmd_annotate(
         eprintln!("   Subtitles: enabled");
     }
 
+    if let Some(lang) = lang {
+        config.transcription = config.transcription.with_language(lang);
+        eprintln!("   Language: {lang}");
+    }
+
     if speaker_labels {
         config.speaker_labels = true;
         config.transcription = config.transcription.with_diarization();
@@ -41,6 +49,11 @@ pub async fn cmd_annotate(
         eprintln!("   Analysis overlay: enabled");
     }
 
+    if karaoke {
+        config = config.with_karaoke(true);
+        eprintln!("   Karaoke word highlighting: enabled");
+    }
+
     if hwaccel {
         #[cfg(target_os = "macos")]
         {
@@ -59,7 +72,16 @@ pub async fn cmd_annotate(
     let pipeline = AnnotationPipeline::new(config)?;
 
     let start = std::time::Instant::now();
-    let result = pipeline.process_file(video, output).await?;
+    let result = if let Some(subs_path) = subs_only {
+        eprintln!("   Subtitles only: {subs_path}");
+        pipeline
+            .generate_subtitles_only(video, std::path::Path::new(subs_path))
+            .await?
+    } else {
+        let output = output.ok_or_else(|| anyhow::anyhow!("Output video path required"))?;
+        eprintln!("   Output: {output}");
+        pipeline.process_file(video, output).await?
+    };
     let elapsed = start.elapsed();
 
     eprintln!("\n✅ Annotation complete in {:.1}s", elapsed.as_secs_f64());
@@ -72,8 +94,29 @@ pub async fn cmd_annotate(
     eprintln!("   Speakers detected: {}", result.speakers.len());
 
     if let Some(ref lang) = result.detected_language {
-        eprintln!("   Language: {lang}");
+        print_language(
+            lang,
+            result.language_confidence,
+            result.language_uncertain(),
+        );
     }
 
     Ok(())
 }
+
+/// Print the transcript language and, when it was detected, how sure the
+/// detection was
+pub(super) fn print_language(language: &str, confidence: Option<f64>, uncertain: bool) {
+    match confidence {
+        Some(confidence) => {
+            eprintln!(
+                "   Language: {language} ({:.0}% confidence)",
+                confidence * 100.0
+            );
+        }
+        None => eprintln!("   Language: {language}"),
+    }
+    if uncertain {
+        eprintln!("   ⚠️  Low language detection confidence; pass --lang <code> if wrong");
+    }
+}
@@ -1,18 +1,37 @@
 use anyhow::Result;
 
-use nab::OnePasswordAuth;
+use nab::{BitwardenAuth, OnePasswordAuth};
 
 pub fn cmd_auth(url: &str) -> Result<()> {
-    if !OnePasswordAuth::is_available() {
-        println!("❌ 1Password CLI not available or not authenticated");
-        println!("   Run: op signin");
+    let onepassword = OnePasswordAuth::is_available();
+    let bitwarden = BitwardenAuth::is_available();
+
+    println!("🔑 Password managers:");
+    if onepassword {
+        println!("   1Password: ✅ available");
+    } else {
+        println!("   1Password: ❌ not available (run: op signin)");
+    }
+    if bitwarden {
+        println!("   Bitwarden: ✅ unlocked");
+    } else {
+        println!("   Bitwarden: ❌ not available (run: export BW_SESSION=$(bw unlock --raw))");
+    }
+    if !onepassword && !bitwarden {
         return Ok(());
     }
 
-    println!("🔐 Searching 1Password for: {url}");
+    println!("\n🔐 Searching for: {url}");
+
+    let mut found = None;
+    if onepassword {
+        found = OnePasswordAuth::new(None).get_credential_for_url(url)?;
+    }
+    if found.is_none() && bitwarden {
+        found = BitwardenAuth::from_env().get_credential_for_url(url)?;
+    }
 
-    let auth = OnePasswordAuth::new(None);
-    match auth.get_credential_for_url(url)? {
+    match found {
         Some(cred) => {
             println!("\n✅ Found credential:");
             println!("   Title: {}", cred.title);
@@ -1,42 +1,1413 @@
-use std::time::Instant;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard, Once, PoisonError};
+use std::task::{Context as TaskContext, Poll};
+use std::time::{Duration, Instant};
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+use hickory_resolver::config::LookupIpStrategy;
+use hickory_resolver::TokioResolver;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::ClientBuilder;
+use rustls::crypto::{ActiveKeyExchange, SupportedKxGroup};
+use rustls::ffdhe_groups::FfdheGroup;
+use rustls::{NamedGroup, ProtocolVersion};
+use serde::Serialize;
+use tower_layer::Layer;
+use tower_service::Service;
+use url::Url;
 
-use nab::AcceleratedClient;
+use nab::http_client::DEFAULT_MAX_REDIRECTS;
+use nab::{random_profile, AcceleratedClient, BrowserProfile, RetryPolicy, TlsOptions};
 
-pub async fn cmd_bench(urls: &str, iterations: usize) -> Result<()> {
-    let client = AcceleratedClient::new()?;
+use crate::BenchOutputFormat;
+
+/// Phase timings of one benchmark iteration, in milliseconds.
+///
+/// DNS, connect and TLS are timed on the connection the request itself opens,
+/// through the client's resolver and connector, so they are zero when the
+/// request reuses a pooled connection. The phases add up to the request time.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct PhaseTimings {
+    /// Looking up the host, or the proxy; zero for IP addresses
+    pub dns_ms: f64,
+    /// TCP connect, including any proxy tunnel
+    pub connect_ms: f64,
+    /// `None` for plain `http://` URLs
+    pub tls_ms: Option<f64>,
+    /// Connection ready until response headers received
+    pub ttfb_ms: f64,
+    /// Response headers received until the body is read
+    pub download_ms: f64,
+    /// HTTP version the response arrived over: `h1`, `h2`, `h3` or `h1.0`
+    pub protocol: &'static str,
+    /// Served on a connection an earlier request already opened
+    pub reused: bool,
+    /// Response body size in bytes
+    pub bytes: usize,
+    /// Sent with `If-None-Match`/`If-Modified-Since` (`bench --conditional`)
+    pub conditional: bool,
+    /// Answered `304 Not Modified`
+    pub not_modified: bool,
+}
+
+impl PhaseTimings {
+    /// Request time as the client sees it, connection setup included
+    #[must_use]
+    pub fn total_ms(&self) -> f64 {
+        self.dns_ms + self.connect_ms + self.tls_ms.unwrap_or(0.0) + self.ttfb_ms + self.download_ms
+    }
+}
+
+/// Total request latency over all iterations, in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct LatencyStats {
+    pub min: f64,
+    pub avg: f64,
+    pub max: f64,
+    pub p50: f64,
+    pub p95: f64,
+    pub p99: f64,
+}
+
+impl LatencyStats {
+    /// Summarize request times; `None` when there are none
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn from_totals(totals: &[f64]) -> Option<Self> {
+        if totals.is_empty() {
+            return None;
+        }
+        let mut sorted = totals.to_vec();
+        sorted.sort_by(f64::total_cmp);
+
+        Some(Self {
+            min: sorted[0],
+            avg: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            max: sorted[sorted.len() - 1],
+            p50: percentile(&sorted, 50.0),
+            p95: percentile(&sorted, 95.0),
+            p99: percentile(&sorted, 99.0),
+        })
+    }
+}
+
+/// Per-phase averages and total-time percentiles over all iterations.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct BenchSummary {
+    pub dns_avg: f64,
+    pub connect_avg: f64,
+    pub tls_avg: Option<f64>,
+    pub ttfb_avg: f64,
+    pub download_avg: f64,
+    pub latency: LatencyStats,
+}
+
+impl BenchSummary {
+    /// Summarize samples; `None` when there are none
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn from_samples(samples: &[PhaseTimings]) -> Option<Self> {
+        let totals: Vec<f64> = samples.iter().map(PhaseTimings::total_ms).collect();
+        let latency = LatencyStats::from_totals(&totals)?;
+        let avg = |phase: fn(&PhaseTimings) -> f64| {
+            samples.iter().map(phase).sum::<f64>() / samples.len() as f64
+        };
+        let tls: Vec<f64> = samples.iter().filter_map(|s| s.tls_ms).collect();
+
+        Some(Self {
+            dns_avg: avg(|s| s.dns_ms),
+            connect_avg: avg(|s| s.connect_ms),
+            tls_avg: (!tls.is_empty()).then(|| tls.iter().sum::<f64>() / tls.len() as f64),
+            ttfb_avg: avg(|s| s.ttfb_ms),
+            download_avg: avg(|s| s.download_ms),
+            latency,
+        })
+    }
+}
+
+/// Revalidation results of a `bench --conditional` run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct CacheStats {
+    /// Measured requests that carried validators
+    pub conditional: usize,
+    /// Of those, answered `304 Not Modified`
+    pub not_modified: usize,
+    /// Body bytes not downloaded thanks to 304s, relative to the full response
+    pub bytes_saved: usize,
+}
+
+impl CacheStats {
+    /// Share of conditional requests answered 304, from 0.0 to 1.0
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn not_modified_rate(&self) -> f64 {
+        if self.conditional == 0 {
+            0.0
+        } else {
+            self.not_modified as f64 / self.conditional as f64
+        }
+    }
+}
+
+/// Cache validators from the last full response, replayed on later requests
+#[derive(Debug, Clone, Default)]
+struct Validators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    /// Body size of the full response they belong to
+    full_bytes: usize,
+}
+
+impl Validators {
+    fn headers(&self) -> HashMap<String, String> {
+        let mut headers = HashMap::new();
+        if let Some(etag) = &self.etag {
+            headers.insert("If-None-Match".to_string(), etag.clone());
+        }
+        if let Some(last_modified) = &self.last_modified {
+            headers.insert("If-Modified-Since".to_string(), last_modified.clone());
+        }
+        headers
+    }
+}
+
+/// Measured iterations for one URL, after the warm-up.
+#[derive(Debug, Clone, Default)]
+pub struct BenchRun {
+    pub samples: Vec<PhaseTimings>,
+    /// Errors of the measured iterations that failed, which have no sample
+    pub errors: Vec<String>,
+    /// Set for `bench --conditional`
+    pub cache: Option<CacheStats>,
+}
+
+impl BenchRun {
+    /// Measured requests served on an already-open connection
+    #[must_use]
+    pub fn reused(&self) -> usize {
+        self.samples.iter().filter(|s| s.reused).count()
+    }
+}
+
+/// Run `warmup` unrecorded iterations to prime DNS and the connection pool,
+/// then `iterations` measured ones. A failed iteration is recorded and the
+/// run goes on.
+///
+/// With `conditional`, every request after the first full response revalidates
+/// it with `If-None-Match`/`If-Modified-Since`, as a returning browser would.
+async fn bench_url(
+    client: &AcceleratedClient,
+    timer: &ConnectTimer,
+    url: &str,
+    iterations: usize,
+    warmup: usize,
+    conditional: bool,
+    progress: bool,
+) -> BenchRun {
+    let mut run = BenchRun {
+        samples: Vec::with_capacity(iterations),
+        errors: Vec::new(),
+        cache: conditional.then(CacheStats::default),
+    };
+    let mut validators = conditional.then(Validators::default);
+
+    for i in 0..warmup + iterations {
+        let full_bytes = validators.as_ref().map_or(0, |v| v.full_bytes);
+        let result = timed_fetch(client, timer, url, validators.as_mut()).await;
+        if i < warmup {
+            continue;
+        }
+        if progress {
+            print!("{}", if result.is_ok() { "." } else { "x" });
+            if i == warmup + iterations - 1 {
+                println!();
+            }
+        }
+        let sample = match result {
+            Ok(sample) => sample,
+            Err(e) => {
+                run.errors.push(format!("{e:#}"));
+                continue;
+            }
+        };
+        if let Some(cache) = run.cache.as_mut().filter(|_| sample.conditional) {
+            cache.conditional += 1;
+            if sample.not_modified {
+                cache.not_modified += 1;
+                cache.bytes_saved += full_bytes.saturating_sub(sample.bytes);
+            }
+        }
+        run.samples.push(sample);
+    }
+
+    run
+}
+
+/// Benchmark results in export form (`bench --format json|csv`)
+#[derive(Debug, Serialize)]
+pub struct BenchReport {
+    /// RFC 3339 time the run started
+    pub timestamp: String,
+    pub client: ClientConfig,
+    pub iterations: usize,
+    pub warmup: usize,
+    pub results: Vec<UrlResult>,
+}
+
+/// The client setup the numbers were measured with
+#[derive(Debug, Serialize)]
+pub struct ClientConfig {
+    pub nab_version: &'static str,
+    /// HTTP versions the responses arrived over, e.g. `h2`, or `h1,h2` when
+    /// they differ
+    pub protocol: String,
+    pub user_agent: String,
+}
+
+/// Timings for one URL
+#[derive(Debug, Serialize)]
+pub struct UrlResult {
+    pub url: String,
+    pub reused: usize,
+    /// Measured iterations that failed
+    pub failed: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    pub summary: BenchSummary,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache: Option<CacheStats>,
+    pub iterations: Vec<IterationRecord>,
+}
+
+/// One measured iteration
+#[derive(Debug, Serialize)]
+pub struct IterationRecord {
+    pub iteration: usize,
+    #[serde(flatten)]
+    pub timings: PhaseTimings,
+    pub total_ms: f64,
+}
+
+impl UrlResult {
+    /// `None` when the run has no measured iterations
+    #[must_use]
+    pub fn new(url: &str, run: &BenchRun) -> Option<Self> {
+        Some(Self {
+            url: url.to_string(),
+            reused: run.reused(),
+            failed: run.errors.len(),
+            last_error: run.errors.last().cloned(),
+            summary: BenchSummary::from_samples(&run.samples)?,
+            cache: run.cache,
+            iterations: run
+                .samples
+                .iter()
+                .enumerate()
+                .map(|(i, timings)| IterationRecord {
+                    iteration: i + 1,
+                    timings: *timings,
+                    total_ms: timings.total_ms(),
+                })
+                .collect(),
+        })
+    }
+}
+
+impl BenchReport {
+    /// One row per iteration plus a `summary` row per URL carrying the
+    /// phase averages and percentiles
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "timestamp,url,row,dns_ms,connect_ms,tls_ms,ttfb_ms,download_ms,total_ms,reused,p50_ms,p95_ms,p99_ms\n",
+        );
+        let opt = |ms: Option<f64>| ms.map_or_else(String::new, |ms| format!("{ms:.3}"));
+        for result in &self.results {
+            let url = csv_field(&result.url);
+            for record in &result.iterations {
+                let t = &record.timings;
+                let _ = writeln!(
+                    csv,
+                    "{},{url},{},{:.3},{:.3},{},{:.3},{:.3},{:.3},{},,,",
+                    self.timestamp,
+                    record.iteration,
+                    t.dns_ms,
+                    t.connect_ms,
+                    opt(t.tls_ms),
+                    t.ttfb_ms,
+                    t.download_ms,
+                    record.total_ms,
+                    t.reused
+                );
+            }
+            let s = &result.summary;
+            let _ = writeln!(
+                csv,
+                "{},{url},summary,{:.3},{:.3},{},{:.3},{:.3},{:.3},{},{:.3},{:.3},{:.3}",
+                self.timestamp,
+                s.dns_avg,
+                s.connect_avg,
+                opt(s.tls_avg),
+                s.ttfb_avg,
+                s.download_avg,
+                s.latency.avg,
+                result.reused,
+                s.latency.p50,
+                s.latency.p95,
+                s.latency.p99
+            );
+        }
+        csv
+    }
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or newline
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// A client configuration compared by `bench --compare`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BenchProtocol {
+    Http1,
+    Http2,
+    Http3,
+}
+
+impl BenchProtocol {
+    /// Parse a comma-separated list such as `h1,h2,h3`
+    pub fn parse_list(list: &str) -> Result<Vec<Self>> {
+        let mut protocols = Vec::new();
+        for name in list.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            let protocol = match name.to_ascii_lowercase().as_str() {
+                "h1" | "http1" | "http/1.1" => Self::Http1,
+                "h2" | "http2" => Self::Http2,
+                "h3" | "http3" => Self::Http3,
+                other => bail!("Unknown protocol '{other}' (expected h1, h2 or h3)"),
+            };
+            if !protocols.contains(&protocol) {
+                protocols.push(protocol);
+            }
+        }
+        if protocols.is_empty() {
+            bail!("No protocols to compare");
+        }
+        Ok(protocols)
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Http1 => "h1",
+            Self::Http2 => "h2",
+            Self::Http3 => "h3",
+        }
+    }
+}
+
+/// The client used to benchmark one protocol
+enum Transport {
+    Reqwest(AcceleratedClient),
+    #[cfg(feature = "http3")]
+    Http3(Box<nab::Http3Client>),
+}
+
+impl Transport {
+    /// Every protocol presents the same browser `profile`, so only the
+    /// transport differs
+    fn for_protocol(protocol: BenchProtocol, profile: &BrowserProfile) -> Result<Self> {
+        let reqwest = |customize: fn(ClientBuilder) -> ClientBuilder| {
+            AcceleratedClient::with_builder(
+                profile.clone(),
+                TlsOptions::default(),
+                DEFAULT_MAX_REDIRECTS,
+                customize,
+            )
+            .map(Self::Reqwest)
+        };
+        match protocol {
+            BenchProtocol::Http1 => reqwest(ClientBuilder::http1_only),
+            BenchProtocol::Http2 => reqwest(ClientBuilder::http2_prior_knowledge),
+            #[cfg(feature = "http3")]
+            BenchProtocol::Http3 => Ok(Self::Http3(Box::new(nab::Http3Client::new(
+                profile.clone(),
+            )?))),
+            #[cfg(not(feature = "http3"))]
+            BenchProtocol::Http3 => bail!("HTTP/3 disabled in this build"),
+        }
+    }
+
+    /// Total time of one request, body included
+    async fn request_ms(&self, url: &str) -> Result<f64> {
+        let start = Instant::now();
+        match self {
+            Self::Reqwest(client) => {
+                client.fetch(url).await?.bytes().await?;
+            }
+            #[cfg(feature = "http3")]
+            Self::Http3(client) => {
+                client.fetch(url).await?;
+            }
+        }
+        Ok(ms_since(start))
+    }
+}
+
+/// Latency of one protocol, or why it couldn't be measured
+#[derive(Debug, Clone)]
+pub struct ComparisonRow {
+    pub protocol: BenchProtocol,
+    pub result: std::result::Result<LatencyStats, String>,
+}
+
+/// Benchmark `url` with each transport, recording failures per row
+async fn compare_url(
+    transports: &[(BenchProtocol, std::result::Result<Transport, String>)],
+    url: &str,
+    iterations: usize,
+    warmup: usize,
+) -> Vec<ComparisonRow> {
+    let mut rows = Vec::with_capacity(transports.len());
+    for (protocol, transport) in transports {
+        let result = match transport {
+            Ok(transport) => measure(transport, url, iterations, warmup)
+                .await
+                .map_err(|e| format!("{e:#}")),
+            Err(e) => Err(e.clone()),
+        };
+        rows.push(ComparisonRow {
+            protocol: *protocol,
+            result,
+        });
+    }
+    rows
+}
+
+async fn measure(
+    transport: &Transport,
+    url: &str,
+    iterations: usize,
+    warmup: usize,
+) -> Result<LatencyStats> {
+    let mut totals = Vec::with_capacity(iterations);
+    for i in 0..warmup + iterations {
+        let ms = transport.request_ms(url).await?;
+        if i >= warmup {
+            totals.push(ms);
+        }
+    }
+    LatencyStats::from_totals(&totals).context("No measured iterations")
+}
+
+/// Protocol with the lowest median latency
+fn fastest(rows: &[ComparisonRow]) -> Option<BenchProtocol> {
+    rows.iter()
+        .filter_map(|row| Some((row.protocol, row.result.as_ref().ok()?)))
+        .min_by(|a, b| a.1.p50.total_cmp(&b.1.p50))
+        .map(|(protocol, _)| protocol)
+}
+
+/// Side-by-side latency table, one row per protocol, marking the fastest
+fn render_comparison(rows: &[ComparisonRow]) -> String {
+    let winner = fastest(rows);
+    let mut table = format!(
+        "   {:<8} {:>10} {:>10} {:>10} {:>10} {:>10}\n",
+        "Protocol", "p50", "p95", "avg", "min", "max"
+    );
+    for row in rows {
+        let _ = match &row.result {
+            Ok(stats) => writeln!(
+                table,
+                "   {:<8} {:>8.2}ms {:>8.2}ms {:>8.2}ms {:>8.2}ms {:>8.2}ms{}",
+                row.protocol.label(),
+                stats.p50,
+                stats.p95,
+                stats.avg,
+                stats.min,
+                stats.max,
+                if winner == Some(row.protocol) {
+                    "  🏆 fastest"
+                } else {
+                    ""
+                }
+            ),
+            Err(e) => writeln!(table, "   {:<8} failed: {e}", row.protocol.label()),
+        };
+    }
+    table
+}
+
+/// Nearest-rank percentile of sorted, non-empty values
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+pub async fn cmd_bench(
+    urls: &str,
+    iterations: usize,
+    warmup: usize,
+    compare: Option<&str>,
+    conditional: bool,
+    format: BenchOutputFormat,
+    output: Option<PathBuf>,
+) -> Result<()> {
     let urls: Vec<&str> = urls.split(',').map(str::trim).collect();
+    let text = matches!(format, BenchOutputFormat::Text);
+    if text && output.is_some() {
+        bail!("--output needs --format json or csv");
+    }
+    if let Some(compare) = compare {
+        if !text {
+            bail!("--compare only supports text output");
+        }
+        return cmd_bench_compare(&urls, iterations, warmup, compare).await;
+    }
+
+    let timestamp = chrono::Utc::now().to_rfc3339();
+    install_handshake_marker();
+    let timer = ConnectTimer::new()?;
+    let client = AcceleratedClient::with_builder(
+        random_profile(),
+        TlsOptions::default(),
+        DEFAULT_MAX_REDIRECTS,
+        |builder| timer.attach(builder).http2_prior_knowledge(),
+    )?
+    // One attempt per iteration, so failures count as failures
+    .with_retry(RetryPolicy::none());
+
+    if text {
+        println!(
+            "🚀 Benchmarking {} URLs, {} iterations each ({} warm-up)\n",
+            urls.len(),
+            iterations,
+            warmup
+        );
+    }
+
+    let mut results = Vec::with_capacity(urls.len());
+    for url in urls {
+        let run = bench_url(&client, &timer, url, iterations, warmup, conditional, text).await;
+        let Some(result) = UrlResult::new(url, &run) else {
+            let error = run.errors.last().map_or("no iterations", String::as_str);
+            eprintln!("❌ {url}: every request failed: {error}\n");
+            continue;
+        };
+        if text {
+            print_result(&result);
+        }
+        results.push(result);
+    }
+
+    let report = BenchReport {
+        timestamp,
+        client: ClientConfig {
+            nab_version: env!("CARGO_PKG_VERSION"),
+            protocol: protocols(&results),
+            user_agent: client.profile().await.user_agent,
+        },
+        iterations,
+        warmup,
+        results,
+    };
+    let rendered = match format {
+        BenchOutputFormat::Text => return Ok(()),
+        BenchOutputFormat::Json => serde_json::to_string_pretty(&report)? + "\n",
+        BenchOutputFormat::Csv => report.to_csv(),
+    };
+    match output {
+        Some(path) => {
+            std::fs::write(&path, rendered)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            eprintln!("📁 Saved benchmark results to {}", path.display());
+        }
+        None => print!("{rendered}"),
+    }
+
+    Ok(())
+}
+
+fn print_result(result: &UrlResult) {
+    let summary = &result.summary;
+    let latency = summary.latency;
+    println!("📊 {}", result.url);
+    println!(
+        "   Avg: {:.2}ms | Min: {:.2}ms | Max: {:.2}ms",
+        latency.avg, latency.min, latency.max
+    );
+    println!(
+        "   p50: {:.2}ms | p95: {:.2}ms | p99: {:.2}ms",
+        latency.p50, latency.p95, latency.p99
+    );
+    let tls_avg = summary
+        .tls_avg
+        .map_or_else(|| "-".to_string(), |ms| format!("{ms:.2}ms"));
+    println!(
+        "   Phases (avg): DNS {:.2}ms | Connect {:.2}ms | TLS {tls_avg} | TTFB {:.2}ms | Download {:.2}ms",
+        summary.dns_avg, summary.connect_avg, summary.ttfb_avg, summary.download_avg
+    );
+    println!(
+        "   Connections reused: {}/{}",
+        result.reused,
+        result.iterations.len()
+    );
+    if let Some(error) = &result.last_error {
+        println!(
+            "   Failed: {}/{} (last: {error})",
+            result.failed,
+            result.failed + result.iterations.len()
+        );
+    }
+    if let Some(cache) = &result.cache {
+        println!(
+            "   Not modified (304): {}/{} ({:.0}%) | Saved: {:.1} KB",
+            cache.not_modified,
+            cache.conditional,
+            cache.not_modified_rate() * 100.0,
+            kilobytes(cache.bytes_saved)
+        );
+    }
+    println!();
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn kilobytes(bytes: usize) -> f64 {
+    bytes as f64 / 1024.0
+}
+
+async fn cmd_bench_compare(
+    urls: &[&str],
+    iterations: usize,
+    warmup: usize,
+    compare: &str,
+) -> Result<()> {
+    let profile = random_profile();
+    let transports: Vec<_> = BenchProtocol::parse_list(compare)?
+        .into_iter()
+        .map(|protocol| {
+            let transport =
+                Transport::for_protocol(protocol, &profile).map_err(|e| format!("{e:#}"));
+            (protocol, transport)
+        })
+        .collect();
+    let labels: Vec<&str> = transports.iter().map(|(p, _)| p.label()).collect();
 
     println!(
-        "🚀 Benchmarking {} URLs, {} iterations each\n",
+        "🚀 Comparing {} across {} URLs, {} iterations each ({} warm-up)\n",
+        labels.join(" vs "),
         urls.len(),
-        iterations
+        iterations,
+        warmup
     );
 
     for url in urls {
-        let mut times = Vec::with_capacity(iterations);
+        let rows = compare_url(&transports, url, iterations, warmup).await;
+        println!("📊 {url}");
+        println!("{}", render_comparison(&rows));
+    }
+
+    Ok(())
+}
+
+/// When the TLS handshake in progress started, set by [`MarkHandshake`]
+static HANDSHAKE_STARTED: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Key exchange group that marks the start of a TLS handshake.
+///
+/// rustls starts the key exchange while building the `ClientHello`, right
+/// after TCP connects, and reqwest has no hook between the two. Benchmarks
+/// run one request at a time, so a single mark is enough.
+#[derive(Debug)]
+struct MarkHandshake(&'static dyn SupportedKxGroup);
+
+impl SupportedKxGroup for MarkHandshake {
+    fn start(&self) -> Result<Box<dyn ActiveKeyExchange>, rustls::Error> {
+        // A HelloRetryRequest starts a second exchange in the same handshake
+        lock(&HANDSHAKE_STARTED).get_or_insert_with(Instant::now);
+        self.0.start()
+    }
+
+    fn ffdhe_group(&self) -> Option<FfdheGroup<'static>> {
+        self.0.ffdhe_group()
+    }
+
+    fn name(&self) -> NamedGroup {
+        self.0.name()
+    }
+
+    fn fips(&self) -> bool {
+        self.0.fips()
+    }
+
+    fn usable_for_version(&self, version: ProtocolVersion) -> bool {
+        self.0.usable_for_version(version)
+    }
+}
+
+/// Install ring, which reqwest would pick anyway, as the process crypto
+/// provider with [`MarkHandshake`] around its key exchange groups. Without
+/// it the TLS handshake counts towards the connect phase.
+fn install_handshake_marker() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let ring = rustls::crypto::ring::default_provider();
+        let kx_groups = ring
+            .kx_groups
+            .iter()
+            .map(|&group| -> &'static dyn SupportedKxGroup {
+                Box::leak(Box::new(MarkHandshake(group)))
+            })
+            .collect();
+        let _ = rustls::crypto::CryptoProvider { kx_groups, ..ring }.install_default();
+    });
+}
+
+/// Connection setup recorded by a [`ConnectTimer`]
+#[derive(Debug, Clone, Copy, Default)]
+struct ConnectionSetup {
+    dns: Duration,
+    connect: Duration,
+    tls: Duration,
+    /// New connections opened
+    connections: usize,
+}
+
+/// Times the DNS lookups and new connections of the client it is attached
+/// to, as its resolver and as a layer around its connector
+#[derive(Clone)]
+struct ConnectTimer {
+    resolver: TokioResolver,
+    setup: Arc<Mutex<ConnectionSetup>>,
+}
 
-        for i in 0..iterations {
+impl ConnectTimer {
+    /// Resolve with hickory-dns configured as reqwest configures it
+    fn new() -> Result<Self> {
+        let mut builder = TokioResolver::builder_tokio()
+            .context("Failed to read the system DNS configuration")?;
+        builder.options_mut().ip_strategy = LookupIpStrategy::Ipv4AndIpv6;
+        Ok(Self {
+            resolver: builder.build(),
+            setup: Arc::default(),
+        })
+    }
+
+    fn attach(&self, builder: ClientBuilder) -> ClientBuilder {
+        builder
+            .dns_resolver(Arc::new(self.clone()))
+            .connector_layer(self.clone())
+    }
+
+    /// What was recorded since the last call
+    fn take(&self) -> ConnectionSetup {
+        std::mem::take(&mut *lock(&self.setup))
+    }
+}
+
+impl Resolve for ConnectTimer {
+    fn resolve(&self, name: Name) -> Resolving {
+        let timer = self.clone();
+        Box::pin(async move {
             let start = Instant::now();
-            let response = client.fetch(url).await?;
-            let _ = response.text().await?;
-            let elapsed = start.elapsed();
-            times.push(elapsed.as_secs_f64() * 1000.0);
+            let lookup = timer.resolver.lookup_ip(name.as_str()).await?;
+            lock(&timer.setup).dns += start.elapsed();
+            let addrs: Addrs = Box::new(lookup.into_iter().map(|ip| SocketAddr::new(ip, 0)));
+            Ok(addrs)
+        })
+    }
+}
 
-            print!(".");
-            if i == iterations - 1 {
-                println!();
+impl<S> Layer<S> for ConnectTimer {
+    type Service = TimedConnect<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TimedConnect {
+            inner,
+            setup: Arc::clone(&self.setup),
+        }
+    }
+}
+
+/// Connector service timing each connection it establishes
+#[derive(Clone)]
+struct TimedConnect<S> {
+    inner: S,
+    setup: Arc<Mutex<ConnectionSetup>>,
+}
+
+impl<S, R> Service<R> for TimedConnect<S>
+where
+    S: Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut TaskContext<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        let setup = Arc::clone(&self.setup);
+        let dns_before = lock(&setup).dns;
+        *lock(&HANDSHAKE_STARTED) = None;
+        let start = Instant::now();
+        let connecting = self.inner.call(request);
+
+        Box::pin(async move {
+            let conn = connecting.await?;
+            let handshake = lock(&HANDSHAKE_STARTED).take().filter(|&at| at >= start);
+            let mut setup = lock(&setup);
+            let dns = setup.dns.saturating_sub(dns_before);
+            let tcp_done = handshake.unwrap_or_else(Instant::now);
+            setup.connect += tcp_done.duration_since(start).saturating_sub(dns);
+            setup.tls += handshake.map_or(Duration::ZERO, |at| at.elapsed());
+            setup.connections += 1;
+            Ok(conn)
+        })
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Fetch `url` once with a client `timer` is attached to, timing each phase.
+///
+/// With `validators`, the request revalidates the last full response and a
+/// new full response replaces the validators.
+async fn timed_fetch(
+    client: &AcceleratedClient,
+    timer: &ConnectTimer,
+    url: &str,
+    validators: Option<&mut Validators>,
+) -> Result<PhaseTimings> {
+    let https = Url::parse(url)
+        .with_context(|| format!("Invalid URL '{url}'"))?
+        .scheme()
+        == "https";
+    let headers = validators.as_deref().map(Validators::headers);
+    let conditional = headers.as_ref().is_some_and(|h| !h.is_empty());
+
+    // Drop whatever an earlier, failed request left behind
+    timer.take();
+    let start = Instant::now();
+    let response = match &headers {
+        Some(headers) if conditional => client.request("GET", url, headers, None).await?,
+        _ => client.fetch(url).await?,
+    };
+    let headers_ms = ms_since(start);
+    let setup = timer.take();
+    let status = response.status();
+    let protocol = protocol_label(response.version());
+    let header = |name: reqwest::header::HeaderName| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from)
+    };
+    let etag = header(reqwest::header::ETAG);
+    let last_modified = header(reqwest::header::LAST_MODIFIED);
+    let body_start = Instant::now();
+    let bytes = response.bytes().await?.len();
+    let download_ms = ms_since(body_start);
+
+    if let Some(validators) = validators.filter(|_| status.is_success()) {
+        *validators = Validators {
+            etag,
+            last_modified,
+            full_bytes: bytes,
+        };
+    }
+
+    let (dns_ms, connect_ms, tls_ms) = (ms(setup.dns), ms(setup.connect), ms(setup.tls));
+    Ok(PhaseTimings {
+        dns_ms,
+        connect_ms,
+        tls_ms: https.then_some(tls_ms),
+        ttfb_ms: (headers_ms - dns_ms - connect_ms - tls_ms).max(0.0),
+        download_ms,
+        protocol,
+        reused: setup.connections == 0,
+        bytes,
+        conditional,
+        not_modified: status == reqwest::StatusCode::NOT_MODIFIED,
+    })
+}
+
+fn protocol_label(version: reqwest::Version) -> &'static str {
+    match version {
+        reqwest::Version::HTTP_2 => "h2",
+        reqwest::Version::HTTP_3 => "h3",
+        reqwest::Version::HTTP_10 | reqwest::Version::HTTP_09 => "h1.0",
+        _ => "h1",
+    }
+}
+
+/// The distinct HTTP versions of all iterations, in order of appearance
+fn protocols(results: &[UrlResult]) -> String {
+    let mut seen: Vec<&str> = Vec::new();
+    for record in results.iter().flat_map(|r| &r.iterations) {
+        if !seen.contains(&record.timings.protocol) {
+            seen.push(record.timings.protocol);
+        }
+    }
+    seen.join(",")
+}
+
+fn ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn ms_since(start: Instant) -> f64 {
+    ms(start.elapsed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// Timed bench client speaking HTTP/1.1 to the mock servers, with the
+    /// timer it reports to
+    async fn timed_client() -> (AcceleratedClient, ConnectTimer) {
+        tokio::task::spawn_blocking(|| {
+            let timer = ConnectTimer::new().unwrap();
+            let client = AcceleratedClient::with_builder(
+                nab::chrome_profile(),
+                TlsOptions::default(),
+                DEFAULT_MAX_REDIRECTS,
+                |builder| timer.attach(builder).http1_only(),
+            )
+            .unwrap()
+            .with_retry(RetryPolicy::none());
+            (client, timer)
+        })
+        .await
+        .unwrap()
+    }
+
+    /// Serve a page whose headers arrive after `header_delay` and whose body
+    /// trickles in after a further `body_delay`
+    fn slow_server(header_delay: Duration, body_delay: Duration) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                std::thread::spawn(move || {
+                    let mut buf = [0u8; 4096];
+                    if stream.read(&mut buf).unwrap_or(0) == 0 {
+                        return;
+                    }
+                    let body = "x".repeat(2048);
+                    std::thread::sleep(header_delay);
+                    let head = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\n\
+                         Connection: close\r\n\r\n",
+                        body.len()
+                    );
+                    let _ = stream.write_all(head.as_bytes());
+                    let _ = stream.flush();
+                    std::thread::sleep(body_delay);
+                    let _ = stream.write_all(body.as_bytes());
+                });
+            }
+        });
+        format!("http://{addr}/page")
+    }
+
+    /// Keep-alive server whose very first request takes `cold_delay` and is
+    /// answered `cold` instead of `ok`
+    fn cold_start_server(cold_delay: Duration) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let served = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let served = Arc::clone(&served);
+                std::thread::spawn(move || {
+                    let mut buf = [0u8; 4096];
+                    while stream.read(&mut buf).unwrap_or(0) > 0 {
+                        let body = if served.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 0
+                        {
+                            std::thread::sleep(cold_delay);
+                            "cold"
+                        } else {
+                            "ok"
+                        };
+                        let _ = write!(
+                            stream,
+                            "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\n\
+                             Content-Length: {}\r\n\r\n{body}",
+                            body.len()
+                        );
+                    }
+                });
+            }
+        });
+        format!("http://{addr}/")
+    }
+
+    /// Keep-alive server that tags a 2 KB page with an `ETag` and answers
+    /// `304 Not Modified` when the request carries it
+    fn etag_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                std::thread::spawn(move || {
+                    let mut buf = [0u8; 4096];
+                    loop {
+                        let n = stream.read(&mut buf).unwrap_or(0);
+                        if n == 0 {
+                            return;
+                        }
+                        let request = String::from_utf8_lossy(&buf[..n]).to_ascii_lowercase();
+                        let response = if request.contains("if-none-match: \"v1\"") {
+                            "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\n\r\n".to_string()
+                        } else {
+                            let body = "x".repeat(2048);
+                            format!(
+                                "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\n\
+                                 Last-Modified: Sun, 25 Jan 2026 12:00:00 GMT\r\n\
+                                 Content-Length: {}\r\n\r\n{body}",
+                                body.len()
+                            )
+                        };
+                        let _ = stream.write_all(response.as_bytes());
+                    }
+                });
+            }
+        });
+        format!("http://{addr}/")
+    }
+
+    #[tokio::test]
+    #[allow(clippy::float_cmp)]
+    async fn warmup_excludes_cold_first_request() {
+        let (client, timer) = timed_client().await;
+        let url = cold_start_server(Duration::from_millis(150));
+
+        let run = bench_url(&client, &timer, &url, 5, 1, false, false).await;
+        assert_eq!(run.samples.len(), 5);
+        assert_eq!(run.reused(), 5);
+        assert!(run.samples.iter().all(|s| s.bytes == 2));
+        assert!(run
+            .samples
+            .iter()
+            .all(|s| s.dns_ms == 0.0 && s.connect_ms == 0.0));
+
+        // Without warm-up the cold request, and its connection, land in the stats
+        let (client, timer) = timed_client().await;
+        let url = cold_start_server(Duration::from_millis(150));
+        let run = bench_url(&client, &timer, &url, 5, 0, false, false).await;
+        assert_eq!(run.samples.len(), 5);
+        assert_eq!(run.reused(), 4);
+        let cold = &run.samples[0];
+        assert!(!cold.reused && cold.bytes == 4);
+        // An IP address needs no lookup
+        assert_eq!(cold.dns_ms, 0.0);
+        assert!(cold.connect_ms > 0.0);
+        assert!(cold.total_ms() >= 150.0, "total {}", cold.total_ms());
+    }
+
+    #[tokio::test]
+    async fn conditional_bench_reports_304_rate_and_bytes_saved() {
+        let (client, timer) = timed_client().await;
+        let url = etag_server();
+
+        let run = bench_url(&client, &timer, &url, 5, 0, true, false).await;
+        assert_eq!(run.samples.len(), 5);
+        assert!(!run.samples[0].conditional && !run.samples[0].not_modified);
+        assert_eq!(run.samples[0].bytes, 2048);
+        assert!(run.samples[1..]
+            .iter()
+            .all(|s| s.conditional && s.not_modified));
+
+        let cache = run.cache.unwrap();
+        assert_eq!(
+            cache,
+            CacheStats {
+                conditional: 4,
+                not_modified: 4,
+                bytes_saved: 4 * 2048,
             }
+        );
+        assert!((cache.not_modified_rate() - 1.0).abs() < f64::EPSILON);
+
+        // After a warm-up every measured request is a revalidation
+        let run = bench_url(&client, &timer, &url, 3, 1, true, false).await;
+        assert_eq!(run.cache.unwrap().not_modified, 3);
+
+        let run = bench_url(&client, &timer, &url, 3, 0, false, false).await;
+        assert!(run.cache.is_none());
+        assert!(run.samples.iter().all(|s| !s.not_modified));
+    }
+
+    fn stats(p50: f64) -> LatencyStats {
+        LatencyStats::from_totals(&[p50 - 1.0, p50, p50 + 1.0]).unwrap()
+    }
+
+    #[test]
+    fn comparison_table_has_a_row_per_protocol_and_a_winner() {
+        let rows = [
+            ComparisonRow {
+                protocol: BenchProtocol::Http1,
+                result: Ok(stats(42.0)),
+            },
+            ComparisonRow {
+                protocol: BenchProtocol::Http2,
+                result: Ok(stats(18.5)),
+            },
+            ComparisonRow {
+                protocol: BenchProtocol::Http3,
+                result: Err("QUIC handshake failed".to_string()),
+            },
+        ];
+        assert_eq!(fastest(&rows), Some(BenchProtocol::Http2));
+
+        let table = render_comparison(&rows);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].contains("Protocol") && lines[0].contains("p95"));
+        assert!(lines[1].trim_start().starts_with("h1") && !lines[1].contains("fastest"));
+        assert!(lines[2].trim_start().starts_with("h2") && lines[2].contains("🏆 fastest"));
+        assert!(lines[2].contains("18.50ms"));
+        assert!(lines[3].contains("h3") && lines[3].contains("failed: QUIC handshake failed"));
+    }
+
+    #[test]
+    fn parses_protocol_lists() {
+        assert_eq!(
+            BenchProtocol::parse_list("h1, H2,h3,h2").unwrap(),
+            [
+                BenchProtocol::Http1,
+                BenchProtocol::Http2,
+                BenchProtocol::Http3
+            ]
+        );
+        assert!(BenchProtocol::parse_list("h4").is_err());
+        assert!(BenchProtocol::parse_list(" , ").is_err());
+    }
+
+    #[tokio::test]
+    async fn compares_protocols_against_http1_mock() {
+        let url = cold_start_server(Duration::ZERO);
+        let transports = tokio::task::spawn_blocking(|| {
+            [BenchProtocol::Http1, BenchProtocol::Http2]
+                .into_iter()
+                .map(|p| {
+                    let transport = Transport::for_protocol(p, &nab::chrome_profile());
+                    (p, transport.map_err(|e| e.to_string()))
+                })
+                .collect::<Vec<_>>()
+        })
+        .await
+        .unwrap();
+
+        let rows = compare_url(&transports, &url, 3, 1).await;
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].result.is_ok());
+        // The mock only speaks HTTP/1.1, so the prior-knowledge HTTP/2 client fails
+        assert!(rows[1].result.is_err());
+        assert_eq!(fastest(&rows), Some(BenchProtocol::Http1));
+    }
+
+    #[tokio::test]
+    async fn http1_leg_presents_the_browser_profile() {
+        let (addr, server) =
+            crate::cmd::test_support::serve_once(|_| crate::cmd::test_support::Response::ok("ok"));
+        let profile = nab::chrome_profile();
+        let transport = {
+            let profile = profile.clone();
+            tokio::task::spawn_blocking(move || {
+                Transport::for_protocol(BenchProtocol::Http1, &profile)
+            })
+            .await
+            .unwrap()
+            .unwrap()
+        };
+
+        transport
+            .request_ms(&format!("http://{addr}/"))
+            .await
+            .unwrap();
+        let request = server.join().unwrap();
+        assert_eq!(
+            request.header("user-agent"),
+            Some(profile.user_agent.as_str())
+        );
+        assert_eq!(
+            request.header("accept-language"),
+            Some(profile.accept_language.as_str())
+        );
+    }
+
+    #[tokio::test]
+    async fn json_export_has_iterations_and_percentiles_per_url() {
+        let (client, timer) = timed_client().await;
+        let urls = [
+            cold_start_server(Duration::ZERO),
+            cold_start_server(Duration::ZERO) + "a,b",
+        ];
+
+        let mut results = Vec::new();
+        for url in &urls {
+            let run = bench_url(&client, &timer, url, 4, 1, false, false).await;
+            results.extend(UrlResult::new(url, &run));
+        }
+        let report = BenchReport {
+            timestamp: "2026-01-25T12:00:00+00:00".to_string(),
+            client: ClientConfig {
+                nab_version: env!("CARGO_PKG_VERSION"),
+                protocol: protocols(&results),
+                user_agent: "test".to_string(),
+            },
+            iterations: 4,
+            warmup: 1,
+            results,
+        };
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["timestamp"], "2026-01-25T12:00:00+00:00");
+        // The mock speaks HTTP/1.1
+        assert_eq!(json["client"]["protocol"], "h1");
+        let results = json["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        for (result, url) in results.iter().zip(&urls) {
+            assert_eq!(result["url"], url.as_str());
+            assert_eq!(result["failed"], 0);
+            assert!(result.get("last_error").is_none());
+            let iterations = result["iterations"].as_array().unwrap();
+            assert_eq!(iterations.len(), 4);
+            assert_eq!(iterations[0]["iteration"], 1);
+            assert_eq!(iterations[0]["protocol"], "h1");
+            assert!(iterations[0]["total_ms"].as_f64().unwrap() > 0.0);
+            assert!(iterations[0]["tls_ms"].is_null());
+            assert!(iterations[0].get("local_addr").is_none());
+            let latency = &result["summary"]["latency"];
+            assert!(latency["p50"].as_f64().unwrap() <= latency["p99"].as_f64().unwrap());
         }
 
-        let avg = times.iter().sum::<f64>() / times.len() as f64;
-        let min = times.iter().copied().fold(f64::INFINITY, f64::min);
-        let max = times.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let csv = report.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 1 + 2 * (4 + 1));
+        assert!(lines[0].starts_with("timestamp,url,row,"));
+        assert_eq!(lines[1].split(',').count(), 13);
+        assert!(lines[5].contains(",summary,"));
+        // The URL with a comma is quoted
+        assert!(lines[6].contains(&format!("\"{}\"", urls[1])));
+    }
 
-        println!("📊 {url}");
-        println!("   Avg: {avg:.2}ms | Min: {min:.2}ms | Max: {max:.2}ms\n");
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn percentiles_use_nearest_rank() {
+        let samples: Vec<PhaseTimings> = (1..=100)
+            .map(|ms| PhaseTimings {
+                ttfb_ms: f64::from(ms),
+                ..PhaseTimings::default()
+            })
+            .rev()
+            .collect();
+        let summary = BenchSummary::from_samples(&samples).unwrap();
+        assert_eq!(summary.latency.min, 1.0);
+        assert_eq!(summary.latency.max, 100.0);
+        assert_eq!(summary.latency.p50, 50.0);
+        assert_eq!(summary.latency.p95, 95.0);
+        assert_eq!(summary.latency.p99, 99.0);
+        assert!((summary.latency.avg - 50.5).abs() < 1e-9);
+        assert!(summary.tls_avg.is_none());
+        assert!(BenchSummary::from_samples(&[]).is_none());
     }
 
-    Ok(())
+    #[tokio::test]
+    async fn reports_ttfb_below_total_with_injected_latency() {
+        let url = slow_server(Duration::from_millis(40), Duration::from_millis(30));
+        let (client, timer) = timed_client().await;
+
+        let mut samples = Vec::new();
+        for _ in 0..5 {
+            samples.push(timed_fetch(&client, &timer, &url, None).await.unwrap());
+        }
+        let summary = BenchSummary::from_samples(&samples).unwrap();
+
+        // Only lower bounds: the server's sleeps guarantee them, a busy
+        // machine can only add to them
+        for sample in &samples {
+            assert!(sample.tls_ms.is_none());
+            // `Connection: close`, so every request connects
+            assert!(!sample.reused && sample.connect_ms > 0.0);
+            assert!(sample.ttfb_ms >= 40.0, "ttfb {}", sample.ttfb_ms);
+            assert!(sample.download_ms > 0.0);
+            assert!(sample.ttfb_ms < sample.total_ms());
+            assert!(sample.total_ms() >= 70.0, "total {}", sample.total_ms());
+        }
+        assert!(summary.ttfb_avg >= 40.0 && summary.ttfb_avg < summary.latency.avg);
+        assert!(summary.connect_avg > 0.0);
+        assert!(summary.latency.min >= 70.0);
+        assert!(
+            summary.latency.min <= summary.latency.p50
+                && summary.latency.p50 <= summary.latency.p95
+        );
+        assert!(
+            summary.latency.p95 <= summary.latency.p99
+                && summary.latency.p99 <= summary.latency.max
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_iterations_are_recorded_without_aborting() {
+        // Every other connection is closed without an answer
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for (i, mut stream) in listener.incoming().flatten().enumerate() {
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf);
+                if i % 2 == 0 {
+                    let _ = stream.write_all(
+                        b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+                    );
+                }
+            }
+        });
+        let url = format!("http://{addr}/");
+        let (client, timer) = timed_client().await;
+
+        let run = bench_url(&client, &timer, &url, 4, 0, false, false).await;
+        assert_eq!(run.samples.len(), 2);
+        assert_eq!(run.errors.len(), 2);
+        let result = UrlResult::new(&url, &run).unwrap();
+        assert_eq!((result.failed, result.iterations.len()), (2, 2));
+        assert!(result.last_error.is_some());
+
+        // Nothing listening: no samples, but every failure is kept
+        let url = format!("http://{}/", closed_port());
+        let run = bench_url(&client, &timer, &url, 3, 1, false, false).await;
+        assert!(run.samples.is_empty());
+        assert_eq!(run.errors.len(), 3);
+        assert!(UrlResult::new(&url, &run).is_none());
+    }
+
+    /// An address nothing listens on
+    fn closed_port() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap()
+    }
 }
@@ -0,0 +1,227 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+
+use nab::content::{ContentRouter, ConversionResult};
+
+use crate::OutputFormat;
+
+pub async fn cmd_convert(path: &Path, format: OutputFormat, output: Option<PathBuf>) -> Result<()> {
+    let result = convert_file(path).await?;
+
+    if let Some(out) = &output {
+        std::fs::write(out, &result.markdown)
+            .with_context(|| format!("Failed to write {}", out.display()))?;
+    }
+
+    match format {
+        OutputFormat::Full => {
+            println!("📄 Convert: {}", path.display());
+            println!("   Type: {}", result.content_type);
+            if let Some(pages) = result.page_count {
+                println!("   Pages: {pages}");
+            }
+            println!("   Conversion: {:.1}ms", result.elapsed_ms);
+            match &output {
+                Some(out) => println!(
+                    "💾 Saved {} bytes to {}",
+                    result.markdown.len(),
+                    out.display()
+                ),
+                None => println!("\n{}", result.markdown),
+            }
+        }
+        OutputFormat::Compact => {
+            if output.is_none() {
+                println!("{}", result.markdown);
+            }
+        }
+        OutputFormat::Json => {
+            let json = serde_json::json!({
+                "path": path.display().to_string(),
+                "content_type": result.content_type,
+                "page_count": result.page_count,
+                "elapsed_ms": result.elapsed_ms,
+                "output": output.as_ref().map(|p| p.display().to_string()),
+                "markdown": if output.is_none() { Some(&result.markdown) } else { None },
+            });
+            println!("{}", serde_json::to_string(&json)?);
+        }
+    }
+    Ok(())
+}
+
+/// Run a local file through the same [`ContentRouter`] as fetched bodies
+///
+/// The extension picks the content type; unknown extensions are sniffed.
+/// PDF, DOCX and EPUB files fail when nab was built without their handler
+/// rather than being dumped as plain text.
+pub async fn convert_file(path: &Path) -> Result<ConversionResult> {
+    let content_type = content_type_for(path);
+    let router = ContentRouter::new();
+    if let Some(feature) = feature_for(content_type).filter(|_| !router.supports(content_type)) {
+        bail!(
+            "Unsupported file type for {} ({content_type}): nab was built without the `{feature}` feature",
+            path.display()
+        );
+    }
+
+    let bytes = tokio::fs::read(path)
+        .await
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    // PDF extraction is blocking FFI
+    tokio::task::spawn_blocking(move || router.convert(&bytes, content_type))
+        .await?
+        .with_context(|| format!("Failed to convert {}", path.display()))
+}
+
+/// Content type for a file extension, empty (sniff the body) when unknown
+fn content_type_for(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match ext.as_str() {
+        "html" | "htm" | "xhtml" => "text/html",
+        "pdf" => "application/pdf",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "epub" => "application/epub+zip",
+        "json" => "application/json",
+        "jsonl" | "ndjson" => "application/x-ndjson",
+        "xml" | "rss" | "atom" => "application/xml",
+        "md" | "markdown" | "txt" => "text/plain",
+        _ => "",
+    }
+}
+
+/// Cargo feature providing the handler for a binary document type
+fn feature_for(content_type: &str) -> Option<&'static str> {
+    match content_type {
+        "application/pdf" => Some("pdf"),
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => Some("docx"),
+        "application/epub+zip" => Some("epub"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(ext: &str, contents: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("nab-convert-{}.{ext}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn content_type_follows_extension() {
+        assert_eq!(content_type_for(Path::new("page.HTML")), "text/html");
+        assert_eq!(content_type_for(Path::new("a/b.pdf")), "application/pdf");
+        assert_eq!(content_type_for(Path::new("notes")), "");
+    }
+
+    #[cfg(not(feature = "epub"))]
+    #[tokio::test]
+    async fn epub_without_its_feature_is_unsupported() {
+        let path = temp_file("epub", b"PK\x03\x04");
+        let err = convert_file(&path).await.unwrap_err();
+        let _ = std::fs::remove_file(&path);
+
+        let err = format!("{err:#}");
+        assert!(err.contains("Unsupported file type"), "{err}");
+        assert!(err.contains("`epub` feature"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn converts_local_html() {
+        let path = temp_file(
+            "html",
+            b"<html><body><h1>Release notes</h1><p>Now with <a href=\"/x\">links</a>.</p></body></html>",
+        );
+        let result = convert_file(&path).await.unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(result.content_type, "text/html");
+        assert!(result.markdown.contains("Release notes"));
+        assert!(result.markdown.contains("Now with"));
+        assert!(result.page_count.is_none());
+    }
+
+    #[tokio::test]
+    async fn unknown_extension_is_sniffed() {
+        let path = temp_file(
+            "bin",
+            b"<!DOCTYPE html><html><body><p>Sniffed</p></body></html>",
+        );
+        let result = convert_file(&path).await.unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(result.content_type, "text/html");
+        assert!(result.markdown.contains("Sniffed"));
+    }
+
+    #[cfg(feature = "pdf")]
+    #[tokio::test]
+    async fn converts_local_pdf_with_page_count() {
+        let path = temp_file("pdf", &two_page_pdf());
+        let result = convert_file(&path).await;
+        let _ = std::fs::remove_file(&path);
+
+        let result = match result {
+            Ok(result) => result,
+            Err(e) if format!("{e:#}").contains("pdfium library not found") => {
+                println!("pdfium unavailable, skipping test");
+                return;
+            }
+            Err(e) => panic!("{e:#}"),
+        };
+        assert_eq!(result.content_type, "application/pdf");
+        assert_eq!(result.page_count, Some(2));
+        assert!(result.markdown.contains("Second page"));
+    }
+
+    /// Minimal two-page PDF with one line of text per page
+    #[cfg(feature = "pdf")]
+    fn two_page_pdf() -> Vec<u8> {
+        let text = |s: &str| format!("BT /F1 24 Tf 72 700 Td ({s}) Tj ET");
+        let (first, second) = (text("First page"), text("Second page"));
+        let objects = [
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 >>".to_string(),
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents 5 0 R \
+             /Resources << /Font << /F1 7 0 R >> >> >>"
+                .to_string(),
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents 6 0 R \
+             /Resources << /Font << /F1 7 0 R >> >> >>"
+                .to_string(),
+            format!("<< /Length {} >>\nstream\n{first}\nendstream", first.len()),
+            format!(
+                "<< /Length {} >>\nstream\n{second}\nendstream",
+                second.len()
+            ),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+        ];
+
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf.extend(format!("{} 0 obj\n{object}\nendobj\n", i + 1).into_bytes());
+        }
+        let xref = pdf.len();
+        pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).into_bytes());
+        for offset in offsets {
+            pdf.extend(format!("{offset:010} 00000 n \n").into_bytes());
+        }
+        pdf.extend(
+            format!(
+                "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+                objects.len() + 1
+            )
+            .into_bytes(),
+        );
+        pdf
+    }
+}
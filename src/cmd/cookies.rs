@@ -1,11 +1,14 @@
+use std::collections::HashMap;
+
 use anyhow::Result;
 
 use nab::CookieSource;
 
-use super::fetch::resolve_browser_name;
+use super::fetch::{resolve_browser_name, resolve_cookie_source};
 
 pub async fn cmd_cookies(subcommand: &str, domain: &str, browser: &str) -> Result<()> {
     match subcommand {
+        "export" if browser.eq_ignore_ascii_case("all") => cmd_cookies_export_all(domain).await,
         "export" => cmd_cookies_export(domain, browser),
         _ => anyhow::bail!("Unknown cookies subcommand: {subcommand}. Use 'export'."),
     }
@@ -16,14 +19,7 @@ fn cmd_cookies_export(domain: &str, browser: &str) -> Result<()> {
     let browser_name = resolve_browser_name(browser)
         .ok_or_else(|| anyhow::anyhow!("No browser specified. Use --cookies to select one."))?;
 
-    let source = match browser_name.to_lowercase().as_str() {
-        "brave" => CookieSource::Brave,
-        "chrome" => CookieSource::Chrome,
-        "firefox" => CookieSource::Firefox,
-        "safari" => CookieSource::Safari,
-        "edge" => CookieSource::Chrome,
-        _ => CookieSource::Chrome,
-    };
+    let source = resolve_cookie_source(&browser_name);
 
     eprintln!(
         "🍪 Exporting cookies for '{}' from {}",
@@ -31,10 +27,31 @@ fn cmd_cookies_export(domain: &str, browser: &str) -> Result<()> {
     );
 
     let cookies = source.get_cookies(domain)?;
+    print_netscape(domain, &browser_name, &cookies);
+    Ok(())
+}
+
+/// Export cookies for a domain from every browser, read concurrently
+async fn cmd_cookies_export_all(domain: &str) -> Result<()> {
+    eprintln!("🍪 Exporting cookies for '{domain}' from all browsers");
+
+    let merged = nab::get_cookies_merged(&CookieSource::ALL, domain).await;
+    for report in &merged.reports {
+        match &report.outcome {
+            Ok(count) => eprintln!("   {:?}: {count} cookies", report.source),
+            Err(e) => eprintln!("   {:?}: ⚠️  {e}", report.source),
+        }
+    }
 
+    print_netscape(domain, "all browsers", &merged.cookies);
+    Ok(())
+}
+
+/// Print cookies in Netscape format
+fn print_netscape(domain: &str, browser_name: &str, cookies: &HashMap<String, String>) {
     if cookies.is_empty() {
         eprintln!("No cookies found for domain: {domain}");
-        return Ok(());
+        return;
     }
 
     // Output in Netscape cookie format
@@ -44,7 +61,7 @@ fn cmd_cookies_export(domain: &str, browser: &str) -> Result<()> {
     println!("# Domain: {domain}");
     println!();
 
-    for (name, value) in &cookies {
+    for (name, value) in cookies {
         let include_subdomains = if domain.starts_with('.') {
             "TRUE"
         } else {
@@ -69,6 +86,4 @@ fn cmd_cookies_export(domain: &str, browser: &str) -> Result<()> {
     }
 
     eprintln!("\n✅ Exported {} cookies", cookies.len());
-
-    Ok(())
 }
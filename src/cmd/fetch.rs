@@ -1,71 +1,193 @@
 use std::fs::File;
+use std::future::Future;
 use std::io::Write;
 use std::path::PathBuf;
-use std::time::Instant;
-
-use anyhow::Result;
-
-use nab::{AcceleratedClient, CookieSource, OnePasswordAuth};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use reqwest::ClientBuilder;
+use tracing::{field, instrument, Span};
+
+use nab::content::hash::ContentHasher;
+use nab::crawl::{extension_for, OutputDir};
+use nab::fingerprint::domains::DomainProfiles;
+use nab::fingerprint::profile_for;
+use nab::http_client::{
+    content_disposition_filename, sanitize_filename, without_decompression, Preconnected,
+    RedirectLoop, SetCookie, WireBody,
+};
+use nab::render::PageRenderer;
+use nab::{
+    AcceleratedClient, BrowserProfile, BrowserType, CookieSource, OnePasswordAuth, TlsOptions,
+};
 
 use super::output::output_body;
+use super::paginate;
 use crate::OutputFormat;
 
-#[allow(clippy::too_many_arguments)]
-pub async fn cmd_fetch(
-    url: &str,
-    show_headers: bool,
-    show_body: bool,
-    format: OutputFormat,
-    output_file: Option<PathBuf>,
-    cookies: &str,
-    use_1password: bool,
-    raw_html: bool,
-    links: bool,
-    max_body: usize,
-    custom_headers: &[String],
-    auto_referer: bool,
-    warmup_url: Option<&str>,
-    method: &str,
-    data: Option<&str>,
-    capture_cookies: bool,
-    no_redirect: bool,
-    no_spa: bool,
-    batch_file: Option<&str>,
-    parallel: usize,
-    proxy: Option<&str>,
-) -> Result<()> {
+/// `nab fetch` flags
+#[allow(clippy::struct_excessive_bools)] // Independent flags
+pub struct FetchOptions<'a> {
+    pub show_headers: bool,
+    pub show_body: bool,
+    pub format: OutputFormat,
+    pub output_file: Option<PathBuf>,
+    /// Browser to read cookies from, `auto` or `none`
+    pub cookies: &'a str,
+    pub use_1password: bool,
+    pub raw_html: bool,
+    pub links: bool,
+    pub max_body: usize,
+    /// `--add-header "Name: Value"` lines
+    pub custom_headers: &'a [String],
+    pub auto_referer: bool,
+    pub warmup_url: Option<&'a str>,
+    pub preconnect: &'a [String],
+    pub method: &'a str,
+    pub data: Option<&'a str>,
+    pub capture_cookies: bool,
+    /// 0 returns redirect responses instead of following them
+    pub max_redirects: usize,
+    pub no_spa: bool,
+    /// Fetch every URL listed in this file instead of the one given
+    pub batch_file: Option<&'a str>,
+    pub parallel: usize,
+    pub jsonl: bool,
+    pub output_dir: Option<&'a OutputDir>,
+    pub proxy: Option<&'a str>,
+    pub user_agent: Option<&'a str>,
+    pub paginate: Option<usize>,
+    pub json_path: Option<&'a str>,
+    pub range: Option<&'a str>,
+    pub tls: TlsOptions,
+    pub render: bool,
+    pub hasher: Option<&'a ContentHasher>,
+    pub dry_run: bool,
+}
+
+impl Default for FetchOptions<'_> {
+    /// The CLI defaults
+    fn default() -> Self {
+        Self {
+            show_headers: false,
+            show_body: false,
+            format: OutputFormat::default(),
+            output_file: None,
+            cookies: "auto",
+            use_1password: false,
+            raw_html: false,
+            links: false,
+            max_body: 0,
+            custom_headers: &[],
+            auto_referer: false,
+            warmup_url: None,
+            preconnect: &[],
+            method: "GET",
+            data: None,
+            capture_cookies: false,
+            max_redirects: nab::http_client::DEFAULT_MAX_REDIRECTS,
+            no_spa: false,
+            batch_file: None,
+            parallel: 5,
+            jsonl: false,
+            output_dir: None,
+            proxy: None,
+            user_agent: None,
+            paginate: None,
+            json_path: None,
+            range: None,
+            tls: TlsOptions::default(),
+            render: false,
+            hasher: None,
+            dry_run: false,
+        }
+    }
+}
+
+#[instrument(
+    name = "fetch",
+    skip_all,
+    fields(
+        request_id = %uuid::Uuid::new_v4(),
+        url = %url,
+        status = field::Empty,
+        bytes = field::Empty,
+        elapsed_ms = field::Empty,
+    )
+)]
+pub async fn cmd_fetch(url: &str, options: FetchOptions<'_>) -> Result<()> {
+    let byte_range = options.range.map(parse_byte_range).transpose()?;
+
     // Handle batch mode
-    if let Some(file_path) = batch_file {
-        return cmd_fetch_batch(
-            file_path,
-            parallel,
-            show_headers,
-            show_body,
-            format,
-            cookies,
-            use_1password,
-            raw_html,
-            links,
-            max_body,
-            custom_headers,
-            auto_referer,
-            method,
-            data,
-            capture_cookies,
-            no_redirect,
-            no_spa,
-            proxy,
-        )
-        .await;
+    if let Some(file_path) = options.batch_file {
+        return cmd_fetch_batch(file_path, &options).await;
     }
 
+    let FetchOptions {
+        show_headers,
+        show_body,
+        format,
+        output_file,
+        cookies,
+        use_1password,
+        raw_html,
+        links,
+        max_body,
+        custom_headers,
+        auto_referer,
+        warmup_url,
+        preconnect,
+        method,
+        data,
+        capture_cookies,
+        max_redirects,
+        no_spa,
+        batch_file: _,
+        parallel: _,
+        jsonl: _,
+        output_dir: _,
+        proxy,
+        user_agent,
+        paginate,
+        json_path,
+        range,
+        tls,
+        render,
+        hasher,
+        dry_run,
+    } = options;
+
     // Create client - with or without redirect following
-    let client = build_client(no_redirect, proxy)?;
-    let profile = client.profile().await;
+    let (profile, fingerprints) = fetch_profile(
+        url,
+        user_agent,
+        fingerprint_overrides(DomainProfiles::load()?, cookies, url),
+    )?;
+    let client = client_with(
+        profile.clone(),
+        fingerprints.clone(),
+        max_redirects,
+        proxy,
+        &tls,
+        |builder| builder,
+    )?;
 
     // Try site-specific providers first (e.g., Twitter via FxTwitter API)
     let site_router = nab::site::SiteRouter::new();
-    if let Some(site_content) = site_router.try_extract(url, &client).await {
+    let site_content = if dry_run || json_path.is_some() || byte_range.is_some() {
+        None
+    } else {
+        site_router.try_extract(url, &client).await
+    };
+    if let Some(site_content) = site_content {
+        if site_content.metadata.paywalled {
+            eprintln!("⚠️  Paywalled content: only a teaser is available");
+        }
+        if let Some(hasher) = hasher {
+            print_content_hash(hasher, url, None, &site_content.markdown, format);
+            return Ok(());
+        }
         // Convert raw_html flag to markdown (default is markdown unless --raw-html)
         let markdown = !raw_html;
         output_body(
@@ -110,29 +232,135 @@ pub async fn cmd_fetch(
         }
     }
 
+    // The main request reads the body undecoded to measure compression;
+    // pagination keeps the decoding client
+    let wire = if paginate.is_none() {
+        Some(client_with(
+            profile.clone(),
+            fingerprints,
+            max_redirects,
+            proxy,
+            &tls,
+            without_decompression,
+        )?)
+    } else {
+        None
+    };
+    let fetcher = wire.as_ref().unwrap_or(&client);
+    let transport = fetcher.inner();
+    // Collects what the warmup and every redirect hop set
+    let jar = fetcher.cookie_jar();
+
     // Session warmup (for APIs that require prior page load)
-    if let Some(warmup) = warmup_url {
+    if let Some(warmup) = warmup_url.filter(|_| !dry_run) {
         if matches!(format, OutputFormat::Full) {
             println!("🔥 Warming up session: {warmup}");
         }
-        let mut warmup_req = client.inner().get(warmup);
-        warmup_req = warmup_req.headers(profile.to_headers());
+        let mut warmup_req = transport.get(warmup);
+        warmup_req = warmup_req.headers(client.profile_for_url(warmup).await.to_headers());
         if !cookie_header.is_empty() {
             warmup_req = warmup_req.header("Cookie", &cookie_header);
         }
-        let _ = warmup_req.send().await; // Ignore result, just establish session
+        // Ignore result, just establish session
+        if let Ok(warmup_req) = warmup_req.build() {
+            let _ = fetcher.execute(warmup_req).await;
+        }
+    }
+
+    // Open connections to hosts the page will need, sharing the main request's pool
+    if !preconnect.is_empty() && !dry_run {
+        let hosts: Vec<&str> = preconnect.iter().map(String::as_str).collect();
+        let warmed = fetcher.preconnect(&hosts).await;
+        if matches!(format, OutputFormat::Full) {
+            for host in &warmed {
+                print_preconnected(host);
+            }
+        }
+    }
+
+    // Follow rel=next pagination and concatenate the pages
+    if let Some(max_pages) = paginate {
+        let mut page_headers = profile.to_headers();
+        if !cookie_header.is_empty() {
+            if let Ok(value) = reqwest::header::HeaderValue::from_str(&cookie_header) {
+                page_headers.insert(reqwest::header::COOKIE, value);
+            }
+        }
+        for header_str in custom_headers {
+            if let Some((name, value)) = header_str.split_once(':') {
+                if let (Ok(name), Ok(value)) = (
+                    reqwest::header::HeaderName::from_bytes(name.trim().as_bytes()),
+                    reqwest::header::HeaderValue::from_str(value.trim()),
+                ) {
+                    page_headers.insert(name, value);
+                }
+            }
+        }
+
+        let pages = paginate::fetch_pages(&client, url, max_pages, &page_headers, markdown).await?;
+
+        match format {
+            OutputFormat::Json => {
+                let output: Vec<_> = pages
+                    .iter()
+                    .map(|page| {
+                        serde_json::json!({
+                            "url": page.url,
+                            "status": page.status,
+                            "markdown": page.body,
+                            "elapsed_ms": page.elapsed_ms,
+                        })
+                    })
+                    .collect();
+                println!("{}", serde_json::to_string(&output)?);
+            }
+            OutputFormat::Compact => {
+                for page in &pages {
+                    println!(
+                        "{} {}B {:.0}ms {}",
+                        page.status,
+                        page.body.len(),
+                        page.elapsed_ms,
+                        page.url
+                    );
+                }
+                output_body(
+                    &paginate::join_pages(&pages),
+                    output_file,
+                    markdown,
+                    links,
+                    max_body,
+                    !no_spa,
+                )?;
+            }
+            OutputFormat::Full => {
+                println!("📑 Fetched {} page(s) from {url}", pages.len());
+                for (i, page) in pages.iter().enumerate() {
+                    println!("   {}. {} {}", i + 1, page.status, page.url);
+                }
+                output_body(
+                    &paginate::join_pages(&pages),
+                    output_file,
+                    markdown,
+                    links,
+                    max_body,
+                    !no_spa,
+                )?;
+            }
+        }
+        return Ok(());
     }
 
     let start = Instant::now();
 
     // Build request based on HTTP method
     let mut request = match method.to_uppercase().as_str() {
-        "POST" => client.inner().post(url),
-        "PUT" => client.inner().put(url),
-        "PATCH" => client.inner().patch(url),
-        "DELETE" => client.inner().delete(url),
-        "HEAD" => client.inner().head(url),
-        _ => client.inner().get(url),
+        "POST" => transport.post(url),
+        "PUT" => transport.put(url),
+        "PATCH" => transport.patch(url),
+        "DELETE" => transport.delete(url),
+        "HEAD" => transport.head(url),
+        _ => transport.get(url),
     };
 
     // Add request body for methods that support it
@@ -150,17 +378,37 @@ pub async fn cmd_fetch(
     // Add fingerprint headers
     request = request.headers(profile.to_headers());
 
-    // Add cookies if present
+    // Ask for JSON when querying an API response
+    if json_path.is_some()
+        && !custom_headers
+            .iter()
+            .any(|h| h.to_lowercase().starts_with("accept:"))
+    {
+        request = request.headers(single_header(reqwest::header::ACCEPT, "application/json"));
+    }
+
+    // Byte ranges apply to the raw representation, so skip compression
+    if let Some((start_byte, end_byte)) = byte_range {
+        request = request
+            .header(
+                "Range",
+                nab::http_client::range_header(start_byte, end_byte),
+            )
+            .headers(single_header(reqwest::header::ACCEPT_ENCODING, "identity"));
+    }
+
+    // Add cookies if present; server-set ones replace stale browser values
+    let cookie_header = match (jar, url::Url::parse(url)) {
+        (Some(jar), Ok(parsed)) => jar.merge_header(&parsed, &cookie_header),
+        _ => cookie_header,
+    };
     if !cookie_header.is_empty() {
         request = request.header("Cookie", &cookie_header);
     }
 
     // Add auto-referer if requested (domain origin)
     if auto_referer {
-        if let Ok(parsed) = url::Url::parse(url) {
-            let referer = format!("{}://{}/", parsed.scheme(), parsed.host_str().unwrap_or(""));
-            request = request.header("Referer", referer);
-        }
+        request = request.headers(site_root_referrer(url, method));
     }
 
     // Add custom headers (--add-header "Name: Value")
@@ -171,11 +419,26 @@ pub async fn cmd_fetch(
         }
     }
 
-    let response = request.send().await?;
+    if dry_run {
+        let request = request.build().context("Failed to build request")?;
+        return print_request(&mut std::io::stdout().lock(), &request);
+    }
+
+    let response = fetcher
+        .execute(request.build().context("Failed to build request")?)
+        .await
+        .map_err(|e| match e.downcast::<reqwest::Error>() {
+            Ok(e) => match RedirectLoop::from_error(&e) {
+                Some(redirect_loop) => redirect_loop.into(),
+                None => tls.explain_error(e),
+            },
+            Err(e) => e,
+        })?;
 
     let elapsed = start.elapsed();
     let status = response.status();
     let version = response.version();
+    Span::current().record("status", status.as_u16());
 
     // Extract headers before consuming response body
     let set_cookies: Vec<String> = response
@@ -192,16 +455,37 @@ pub async fn cmd_fetch(
         .unwrap_or("text/html")
         .to_string();
 
-    // Output Set-Cookie headers if requested (for auth flows)
-    if capture_cookies && !set_cookies.is_empty() {
-        println!("🍪 Set-Cookie:");
-        for cookie in &set_cookies {
-            if let Some(name_value) = cookie.split(';').next() {
-                println!("   {name_value}");
+    // Output Set-Cookie headers if requested (for auth flows); JSON gets a
+    // `cookies` array instead
+    let captured: Vec<SetCookie> = if capture_cookies {
+        set_cookies
+            .iter()
+            .filter_map(|c| SetCookie::parse(c))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    match format {
+        _ if captured.is_empty() => {}
+        OutputFormat::Full => print!("🍪 Set-Cookie:\n{}", cookie_table(&captured)),
+        OutputFormat::Compact => {
+            println!("🍪 Set-Cookie:");
+            for cookie in &captured {
+                println!("   {}={}", cookie.name, cookie.value);
             }
         }
+        OutputFormat::Json => {}
     }
 
+    let header_map = response.headers().clone();
+
+    // A directory target downloads the response as-is under the server's
+    // suggested file name; a file target gets the converted output
+    let (output_file, download) = match output_file {
+        Some(dir) if dir.is_dir() => (None, Some(output_path(dir, &header_map, url))),
+        file => (file, None),
+    };
+
     // Extract headers for Full format before consuming response
     let response_headers: Vec<(String, String)> = if show_headers {
         response
@@ -219,20 +503,90 @@ pub async fn cmd_fetch(
     };
 
     // Get body as bytes (handles both text and binary content like PDF)
-    let body_bytes = response.bytes().await?;
+    let (wire, range_honored) = if let Some((start_byte, end_byte)) = byte_range {
+        // Ranges are requested with `Accept-Encoding: identity`
+        let ranged =
+            nab::http_client::RangeResponse::from_response(response, start_byte, end_byte).await?;
+        (WireBody::decode(None, &ranged.body)?, Some(ranged.honored))
+    } else {
+        (WireBody::read(response).await?, None)
+    };
+    let compression = compression_summary(&wire);
+    let body_bytes = wire.body;
     let body_len = body_bytes.len();
+    Span::current()
+        .record("bytes", body_len)
+        .record("elapsed_ms", elapsed.as_millis());
+
+    if let Some(path) = &download {
+        std::fs::write(path, &body_bytes)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        if !matches!(format, OutputFormat::Json) {
+            println!("💾 Saved {body_len} bytes to {}", path.display());
+        }
+    }
+
+    // Query JSON API responses instead of converting them
+    if let Some(path) = json_path {
+        let value: serde_json::Value =
+            nab::http_client::parse_json_body(&body_bytes, &content_type)
+                .with_context(|| format!("HTTP {} from {url}", status.as_u16()))?;
+        let selected = nab::json_path::select(&value, path)
+            .ok_or_else(|| anyhow::anyhow!("JSON path '{path}' not found in response"))?;
+        let rendered = serde_json::to_string_pretty(selected)?;
+        if let Some(path) = output_file {
+            std::fs::write(&path, &rendered)?;
+        } else {
+            println!("{rendered}");
+        }
+        return Ok(());
+    }
 
     // Keep raw text for link extraction (extract_links needs HTML, not markdown)
     let raw_text = String::from_utf8_lossy(&body_bytes).to_string();
 
-    // Convert body to text using content-type-aware routing
-    let body_text = if markdown && !links {
+    // Flag anti-bot interstitials instead of presenting them as content
+    let challenge = nab::challenge::detect_challenge(status.as_u16(), &header_map, &raw_text);
+    if let Some(challenge) = &challenge {
+        if !matches!(format, OutputFormat::Json) {
+            eprintln!("⚠️  {challenge}");
+            eprintln!("   💡 {}", challenge.suggestion());
+        }
+    }
+
+    // Flag paywall teasers so the excerpt isn't mistaken for the full article;
+    // the page is parsed once for this and the JSON title
+    let (title, paywall) = if content_type.contains("html") {
+        let document = scraper::Html::parse_document(&raw_text);
+        (
+            document_title(&document),
+            nab::paywall::detect_paywall_in(&document),
+        )
+    } else {
+        (None, None)
+    };
+    if let Some(signal) = paywall {
+        if !matches!(format, OutputFormat::Json) {
+            eprintln!("⚠️  Paywalled content ({signal}): the body is likely only a teaser");
+        }
+    }
+
+    // Convert body to text using content-type-aware routing; `--hash` always
+    // hashes the converted text, whatever the output options
+    let body_text = if (markdown && !links) || hasher.is_some() {
         let router = nab::content::ContentRouter::new();
         let ct = content_type.clone();
-        let bytes = body_bytes.to_vec();
+        let bytes = body_bytes.clone();
+        // Keep the conversion span nested under this request's span
+        let span = Span::current();
+        let dispatch = tracing::dispatcher::get_default(Clone::clone);
         let result = tokio::time::timeout(
             std::time::Duration::from_secs(60),
-            tokio::task::spawn_blocking(move || router.convert(&bytes, &ct)),
+            tokio::task::spawn_blocking(move || {
+                tracing::dispatcher::with_default(&dispatch, || {
+                    span.in_scope(|| router.convert(&bytes, &ct))
+                })
+            }),
         )
         .await
         .map_err(|_| anyhow::anyhow!("Content conversion timed out after 60s"))???;
@@ -248,6 +602,21 @@ pub async fn cmd_fetch(
         raw_text.clone()
     };
 
+    // Client-side rendered pages convert to almost nothing; render them instead
+    let (raw_text, body_text, rendered_with) = if render && markdown && !links {
+        match render_fallback(url, &raw_text, &body_text, default_renderer).await? {
+            Some((html, rendered_markdown, name)) => (html, rendered_markdown, Some(name)),
+            None => (raw_text, body_text, None),
+        }
+    } else {
+        (raw_text, body_text, None)
+    };
+
+    if let Some(hasher) = hasher {
+        print_content_hash(hasher, url, Some(status.as_u16()), &body_text, format);
+        return Ok(());
+    }
+
     // Output based on format
     match format {
         OutputFormat::Compact => {
@@ -257,25 +626,48 @@ pub async fn cmd_fetch(
                 body_len,
                 elapsed.as_secs_f64() * 1000.0
             );
+            if range_honored == Some(false) {
+                println!("range ignored by server, truncated client-side");
+            }
+            if let Some(name) = rendered_with {
+                println!("rendered via {name}");
+            }
 
-            if show_body || output_file.is_some() || markdown || links {
+            if download.is_none() && (show_body || output_file.is_some() || markdown || links) {
                 output_body(&body_text, output_file, markdown, links, max_body, !no_spa)?;
             }
         }
         OutputFormat::Json => {
             let metadata = serde_json::json!({
-                "title": extract_title(&raw_text),
+                "title": title.or_else(|| extract_title(&raw_text)),
                 "content_length": body_len,
                 "content_type": content_type,
+                "paywalled": paywall.is_some(),
+                "paywall_signal": paywall,
             });
-            let output = serde_json::json!({
+            let mut output = serde_json::json!({
                 "url": url,
                 "status": status.as_u16(),
                 "content_type": content_type,
                 "markdown": body_text,
                 "metadata": metadata,
+                "range_honored": range_honored,
+                "rendered": rendered_with.is_some(),
+                "challenge": challenge.as_ref().map(|c| serde_json::json!({
+                    "type": c.kind,
+                    "status": c.status,
+                    "suggestion": c.suggestion(),
+                })),
                 "elapsed_ms": (elapsed.as_secs_f64() * 1000.0 * 10.0).round() / 10.0,
             });
+            if let (Some(output), serde_json::Value::Object(compression)) =
+                (output.as_object_mut(), compression)
+            {
+                output.extend(compression);
+            }
+            if capture_cookies {
+                output["cookies"] = serde_json::to_value(&captured)?;
+            }
             println!("{}", serde_json::to_string(&output)?);
 
             if let Some(path) = output_file {
@@ -303,6 +695,17 @@ pub async fn cmd_fetch(
             println!("   Status: {status}");
             println!("   Version: {version:?}");
             println!("   Time: {:.2}ms", elapsed.as_secs_f64() * 1000.0);
+            if let Some(name) = rendered_with {
+                println!("   Rendered: {name} (page looked client-side rendered)");
+            }
+            match range_honored {
+                Some(true) => println!("   Range: {} (honored)", range.unwrap_or_default()),
+                Some(false) => println!(
+                    "   Range: {} (ignored by server, truncated client-side)",
+                    range.unwrap_or_default()
+                ),
+                None => {}
+            }
 
             if show_headers {
                 println!("\n📋 Headers:");
@@ -312,8 +715,15 @@ pub async fn cmd_fetch(
             }
 
             println!("\n📄 Body: {} bytes", body_len);
+            if let Some(encoding) = compression["content_encoding"].as_str() {
+                println!(
+                    "   Compression: {encoding}, {} bytes on the wire ({:.1}x smaller)",
+                    compression["compressed_bytes"],
+                    compression["ratio"].as_f64().unwrap_or(1.0)
+                );
+            }
 
-            if show_body || output_file.is_some() || markdown || links {
+            if download.is_none() && (show_body || output_file.is_some() || markdown || links) {
                 output_body(&body_text, output_file, markdown, links, max_body, !no_spa)?;
             }
         }
@@ -322,9 +732,355 @@ pub async fn cmd_fetch(
     Ok(())
 }
 
+/// One `--preconnect` host: protocol and time, or why warming failed
+fn print_preconnected(host: &Preconnected) {
+    match (&host.connection, &host.error) {
+        (Some(connection), _) => {
+            let h3 = host
+                .h3_endpoint
+                .as_ref()
+                .map(|endpoint| {
+                    let warmed = if host.h3_warmed { ", warmed" } else { "" };
+                    format!(", h3 at {endpoint}{warmed}")
+                })
+                .unwrap_or_default();
+            println!(
+                "🔌 Preconnected {} in {:.0}ms ({}{h3})",
+                host.host,
+                host.elapsed_ms,
+                connection.protocol.unwrap_or(connection.http_version)
+            );
+        }
+        (None, error) => println!(
+            "⚠️  Preconnect to {} failed: {}",
+            host.host,
+            error.as_deref().unwrap_or("unknown error")
+        ),
+    }
+}
+
+/// Captured cookies as an aligned table: name, value, domain, path, expiry
+/// and flags
+fn cookie_table(cookies: &[SetCookie]) -> String {
+    use std::fmt::Write as _;
+
+    const MAX_VALUE: usize = 40;
+    let rows: Vec<[String; 6]> = cookies
+        .iter()
+        .map(|c| {
+            let value = if c.value.chars().count() > MAX_VALUE {
+                format!(
+                    "{}…",
+                    c.value.chars().take(MAX_VALUE - 1).collect::<String>()
+                )
+            } else {
+                c.value.clone()
+            };
+            let mut flags = Vec::new();
+            if c.secure {
+                flags.push("Secure".to_string());
+            }
+            if c.http_only {
+                flags.push("HttpOnly".to_string());
+            }
+            if let Some(same_site) = &c.same_site {
+                flags.push(format!("SameSite={same_site}"));
+            }
+            [
+                c.name.clone(),
+                value,
+                c.domain.clone().unwrap_or_else(|| "-".to_string()),
+                c.path.clone().unwrap_or_else(|| "-".to_string()),
+                c.expires.map_or_else(
+                    || "session".to_string(),
+                    |at| at.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+                ),
+                flags.join(" "),
+            ]
+        })
+        .collect();
+
+    let header = ["NAME", "VALUE", "DOMAIN", "PATH", "EXPIRES", "FLAGS"].map(String::from);
+    let mut widths = [0; 6];
+    for row in std::iter::once(&header).chain(&rows) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut out = String::new();
+    for row in std::iter::once(&header).chain(&rows) {
+        let line: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect();
+        let _ = writeln!(out, "   {}", line.join("  ").trim_end());
+    }
+    out
+}
+
+/// `--output` path for a response: a directory is joined with the
+/// `Content-Disposition` file name, else the last URL path segment; the raw
+/// body is saved there, since that name describes the server's bytes
+fn output_path(path: PathBuf, headers: &reqwest::header::HeaderMap, url: &str) -> PathBuf {
+    if !path.is_dir() {
+        return path;
+    }
+    let suggested = headers
+        .get(reqwest::header::CONTENT_DISPOSITION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(content_disposition_filename);
+    let from_url = || {
+        url::Url::parse(url)
+            .ok()?
+            .path_segments()?
+            .rfind(|segment| !segment.is_empty())
+            .and_then(|segment| sanitize_filename(&urlencoding::decode(segment).ok()?))
+    };
+    path.join(
+        suggested
+            .or_else(from_url)
+            .unwrap_or_else(|| "index".to_string()),
+    )
+}
+
+/// `content_encoding`, `compressed_bytes`, `decompressed_bytes` and `ratio`
+/// (decoded over on-wire size) for the JSON output
+fn compression_summary(wire: &WireBody) -> serde_json::Value {
+    serde_json::json!({
+        "content_encoding": wire.content_encoding,
+        "compressed_bytes": wire.compressed_bytes,
+        "decompressed_bytes": wire.decompressed_bytes(),
+        "ratio": wire.ratio().map(|ratio| (ratio * 100.0).round() / 100.0),
+    })
+}
+
+/// Hasher for `--hash`, or `None` when hashing wasn't requested
+pub fn content_hasher(hash: bool, ignore: &[String]) -> Result<Option<ContentHasher>> {
+    if !hash && ignore.is_empty() {
+        return Ok(None);
+    }
+    ignore
+        .iter()
+        .try_fold(ContentHasher::new(), |hasher, pattern| {
+            hasher.with_pattern(pattern)
+        })
+        .map(Some)
+}
+
+/// Print the normalized-content hash in place of the body
+fn print_content_hash(
+    hasher: &ContentHasher,
+    url: &str,
+    status: Option<u16>,
+    content: &str,
+    format: OutputFormat,
+) {
+    let hash = hasher.hash(content);
+    match format {
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::json!({
+                "url": url,
+                "status": status,
+                "sha256": hash,
+                "normalized_bytes": hasher.normalize(content).len(),
+            })
+        ),
+        OutputFormat::Compact | OutputFormat::Full => println!("{hash}  {url}"),
+    }
+}
+
+/// Renderer used by `--render`
+#[cfg(feature = "headless")]
+fn default_renderer() -> Result<Box<dyn PageRenderer>> {
+    Ok(Box::new(nab::render::ChromeRenderer::discover()?))
+}
+
+#[cfg(not(feature = "headless"))]
+fn default_renderer() -> Result<Box<dyn PageRenderer>> {
+    anyhow::bail!("--render needs nab built with the `headless` feature")
+}
+
+/// When `markdown` looks empty for the size of `html`, render `url` headlessly
+/// and re-convert. Returns the rendered HTML, its markdown and the renderer name.
+async fn render_fallback(
+    url: &str,
+    html: &str,
+    markdown: &str,
+    make_renderer: impl FnOnce() -> Result<Box<dyn PageRenderer>>,
+) -> Result<Option<(String, String, &'static str)>> {
+    if !nab::render::looks_client_rendered(html, markdown) {
+        return Ok(None);
+    }
+
+    let renderer = make_renderer()?;
+    let dom = renderer.render(url).await?;
+    let converted = nab::content::ContentRouter::new().convert(dom.as_bytes(), "text/html")?;
+    Ok(Some((dom, converted.markdown, renderer.name())))
+}
+
+/// The whole command ran past its `--deadline`
+#[derive(Debug, thiserror::Error)]
+#[error("Deadline exceeded: gave up after {}ms", .0.as_millis())]
+pub struct DeadlineExceeded(pub Duration);
+
+/// Run `operation` within `deadline`, if one is set
+///
+/// Fetching, conversion and rendering share the one budget: when it runs out
+/// the whole operation is dropped and [`DeadlineExceeded`] is returned.
+pub async fn with_deadline<T>(
+    deadline: Option<Duration>,
+    operation: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    match deadline {
+        Some(deadline) => tokio::time::timeout(deadline, operation)
+            .await
+            .map_err(|_| DeadlineExceeded(deadline))?,
+        None => operation.await,
+    }
+}
+
+/// Exit the process if `error` is [`DeadlineExceeded`], otherwise hand it back
+///
+/// Content conversion and cookie extraction run on blocking threads, which
+/// outlive the dropped operation and which runtime shutdown would wait for;
+/// exiting is the only way to stop them on time.
+pub fn exit_on_deadline(error: anyhow::Error) -> anyhow::Error {
+    if error.is::<DeadlineExceeded>() {
+        let _ = std::io::stdout().flush();
+        eprintln!("Error: {error}");
+        std::process::exit(1);
+    }
+    error
+}
+
+/// Parse a `--deadline` like `500ms`, `30s`, `2m`, `1m30s` or plain seconds.
+pub fn parse_deadline(spec: &str) -> Result<Duration> {
+    let spec = spec.trim().to_ascii_lowercase();
+    let mut total = Duration::ZERO;
+    let mut rest = spec.as_str();
+    // Plain seconds
+    if let Ok(secs) = spec.parse::<u64>() {
+        total = Duration::from_secs(secs);
+        rest = "";
+    }
+
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let (number, tail) = rest.split_at(digits);
+        let unit_len = tail
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(tail.len());
+        let (unit, tail) = tail.split_at(unit_len);
+        let value: u64 = number
+            .parse()
+            .with_context(|| format!("Invalid deadline '{spec}'"))?;
+        let millis = match unit {
+            "ms" => Some(value),
+            "s" => value.checked_mul(1000),
+            "m" => value.checked_mul(60_000),
+            "h" => value.checked_mul(3_600_000),
+            _ => anyhow::bail!("Invalid deadline '{spec}': use units ms, s, m or h"),
+        };
+        total = millis
+            .and_then(|millis| total.checked_add(Duration::from_millis(millis)))
+            .with_context(|| format!("Invalid deadline '{spec}': too long"))?;
+        rest = tail;
+    }
+
+    if total.is_zero() {
+        anyhow::bail!("Invalid deadline '{spec}': must be greater than zero");
+    }
+    Ok(total)
+}
+
+/// Write a built request the way it would go out: request line, every
+/// header (cookies and fingerprint included) and the body. `Host` is only
+/// added by the connection at send time, so it is filled in from the URL.
+fn print_request(out: &mut impl Write, request: &reqwest::Request) -> Result<()> {
+    writeln!(
+        out,
+        "{} {} {:?}",
+        request.method(),
+        request.url(),
+        request.version()
+    )?;
+    if !request.headers().contains_key(reqwest::header::HOST) {
+        if let Some(host) = request.url().host_str() {
+            match request.url().port() {
+                Some(port) => writeln!(out, "host: {host}:{port}")?,
+                None => writeln!(out, "host: {host}")?,
+            }
+        }
+    }
+    for (name, value) in request.headers() {
+        writeln!(out, "{name}: {}", String::from_utf8_lossy(value.as_bytes()))?;
+    }
+    if let Some(body) = request.body() {
+        writeln!(out)?;
+        match body.as_bytes() {
+            Some(bytes) => writeln!(out, "{}", String::from_utf8_lossy(bytes))?,
+            None => writeln!(out, "<streaming body>")?,
+        }
+    }
+    Ok(())
+}
+
+/// `--auto-referer` headers: the request appears to come from the site's root page
+fn site_root_referrer(url: &str, method: &str) -> reqwest::header::HeaderMap {
+    url::Url::parse(url).map_or_else(
+        |_| reqwest::header::HeaderMap::new(),
+        |parsed| {
+            let root = format!("{}/", parsed.origin().ascii_serialization());
+            nab::fingerprint::referrer_headers(&root, url, method)
+        },
+    )
+}
+
+/// A one-entry header map; passed to `RequestBuilder::headers` it replaces the
+/// fingerprint's value where `header` would send both
+fn single_header(
+    name: reqwest::header::HeaderName,
+    value: &'static str,
+) -> reqwest::header::HeaderMap {
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert(name, reqwest::header::HeaderValue::from_static(value));
+    headers
+}
+
+/// Parse a `--range` spec: `START-END` (inclusive) or `START-` (to the end).
+fn parse_byte_range(spec: &str) -> Result<(u64, Option<u64>)> {
+    let (start, end) = spec
+        .trim()
+        .split_once('-')
+        .ok_or_else(|| anyhow::anyhow!("Invalid range '{spec}': expected START-END or START-"))?;
+    let start: u64 = start
+        .trim()
+        .parse()
+        .with_context(|| format!("Invalid range start in '{spec}'"))?;
+    let end = match end.trim() {
+        "" => None,
+        end => Some(
+            end.parse::<u64>()
+                .with_context(|| format!("Invalid range end in '{spec}'"))?,
+        ),
+    };
+    if end.is_some_and(|end| end < start) {
+        anyhow::bail!("Invalid range '{spec}': end is before start");
+    }
+    Ok((start, end))
+}
+
 /// Extract <title> from HTML for metadata
 fn extract_title(html: &str) -> Option<String> {
-    let doc = scraper::Html::parse_document(html);
+    document_title(&scraper::Html::parse_document(html))
+}
+
+/// `<title>` of a parsed page
+fn document_title(doc: &scraper::Html) -> Option<String> {
     let sel = scraper::Selector::parse("title").ok()?;
     doc.select(&sel)
         .next()
@@ -332,30 +1088,29 @@ fn extract_title(html: &str) -> Option<String> {
 }
 
 /// Batch fetch: read URLs from file, fetch with concurrency control
-#[allow(clippy::too_many_arguments)]
-async fn cmd_fetch_batch(
-    file_path: &str,
-    parallel: usize,
-    _show_headers: bool,
-    show_body: bool,
-    format: OutputFormat,
-    cookies: &str,
-    _use_1password: bool,
-    raw_html: bool,
-    _links: bool,
-    max_body: usize,
-    custom_headers: &[String],
-    auto_referer: bool,
-    method: &str,
-    data: Option<&str>,
-    _capture_cookies: bool,
-    no_redirect: bool,
-    _no_spa: bool,
-    proxy: Option<&str>,
-) -> Result<()> {
-    use std::sync::Arc;
+async fn cmd_fetch_batch(file_path: &str, options: &FetchOptions<'_>) -> Result<()> {
     use tokio::sync::Semaphore;
 
+    let &FetchOptions {
+        show_body,
+        format,
+        cookies,
+        raw_html,
+        max_body,
+        custom_headers,
+        auto_referer,
+        method,
+        data,
+        max_redirects,
+        parallel,
+        jsonl,
+        output_dir,
+        proxy,
+        user_agent,
+        ref tls,
+        ..
+    } = options;
+
     let contents = std::fs::read_to_string(file_path)
         .map_err(|e| anyhow::anyhow!("Failed to read batch file '{}': {}", file_path, e))?;
 
@@ -385,25 +1140,43 @@ async fn cmd_fetch_batch(
     let method = method.to_string();
     let data = data.map(String::from);
     let proxy_owned = proxy.map(String::from);
+    let user_agent = user_agent.map(String::from);
+    let fingerprints = DomainProfiles::load()?;
+    let output_dir = output_dir.cloned();
 
-    for url in urls {
+    // Tag each task with its input index so output order matches the batch file
+    for (index, input) in urls.into_iter().enumerate() {
+        let url = input.clone();
         let sem = semaphore.clone();
         let custom_headers = custom_headers.clone();
         let cookies = cookies.clone();
         let method = method.clone();
         let data = data.clone();
         let proxy_owned = proxy_owned.clone();
+        let user_agent = user_agent.clone();
+        let tls = tls.clone();
+        let fingerprints = fingerprints.clone();
+        let output_dir = output_dir.clone();
 
         let handle = tokio::spawn(async move {
             let _permit = sem.acquire().await.unwrap();
             let start = Instant::now();
 
-            let client = match build_client(no_redirect, proxy_owned.as_deref()) {
+            let client = match build_client(
+                &url,
+                max_redirects,
+                proxy_owned.as_deref(),
+                user_agent.as_deref(),
+                &tls,
+                fingerprint_overrides(fingerprints, &cookies, &url),
+            ) {
                 Ok(c) => c,
+                // Bad proxy URL, unreadable CA file: nothing was sent
                 Err(e) => {
                     return serde_json::json!({
                         "url": url,
                         "error": e.to_string(),
+                        "error_kind": "client",
                     });
                 }
             };
@@ -446,11 +1219,7 @@ async fn cmd_fetch_batch(
             }
 
             if auto_referer {
-                if let Ok(parsed) = url::Url::parse(&url) {
-                    let referer =
-                        format!("{}://{}/", parsed.scheme(), parsed.host_str().unwrap_or(""));
-                    request = request.header("Referer", referer);
-                }
+                request = request.headers(site_root_referrer(&url, &method));
             }
 
             for header_str in &custom_headers {
@@ -460,65 +1229,31 @@ async fn cmd_fetch_batch(
                 }
             }
 
-            match request.send().await {
-                Ok(response) => {
-                    let elapsed = start.elapsed();
-                    let status = response.status().as_u16();
-                    let content_type = response
-                        .headers()
-                        .get("content-type")
-                        .and_then(|v| v.to_str().ok())
-                        .unwrap_or("text/html")
-                        .to_string();
-
-                    let body_bytes = response.bytes().await.unwrap_or_default();
-                    let body_len = body_bytes.len();
-                    let raw_text = String::from_utf8_lossy(&body_bytes).to_string();
-
-                    let markdown = if !raw_html {
-                        let router = nab::content::ContentRouter::new();
-                        router
-                            .convert(&body_bytes, &content_type)
-                            .map(|r| r.markdown)
-                            .unwrap_or_else(|_| raw_text.clone())
-                    } else {
-                        raw_text
-                    };
-
-                    let metadata = serde_json::json!({
-                        "title": extract_title(&String::from_utf8_lossy(&body_bytes)),
-                        "content_length": body_len,
-                        "content_type": content_type,
-                    });
-
-                    serde_json::json!({
-                        "url": url,
-                        "status": status,
-                        "content_type": content_type,
-                        "markdown": markdown,
-                        "metadata": metadata,
-                        "elapsed_ms": (elapsed.as_secs_f64() * 1000.0 * 10.0).round() / 10.0,
-                    })
-                }
-                Err(e) => {
-                    serde_json::json!({
-                        "url": url,
-                        "error": e.to_string(),
-                    })
-                }
-            }
+            let output = output_dir.as_ref().map(|dir| (dir, index + 1));
+            batch_result(&client, &url, request, start, raw_html, output).await
         });
 
-        handles.push(handle);
+        handles.push((index, input, handle));
     }
 
-    // Collect results
-    let mut results = Vec::new();
-    for handle in handles {
-        match handle.await {
-            Ok(result) => results.push(result),
-            Err(e) => results.push(serde_json::json!({"error": e.to_string()})),
+    // Collect results as they complete; JSONL mode streams them immediately
+    let results = collect_batch_results(handles, |result| {
+        if jsonl {
+            if let Ok(line) = serde_json::to_string(result) {
+                println!("{line}");
+            }
         }
+    })
+    .await;
+
+    if jsonl {
+        let success_count = results.iter().filter(|r| r.get("error").is_none()).count();
+        eprintln!(
+            "\n📦 Batch complete: {}/{} succeeded",
+            success_count,
+            results.len()
+        );
+        return Ok(());
     }
 
     // In batch mode, always output as JSON array regardless of format
@@ -565,6 +1300,9 @@ async fn cmd_fetch_batch(
                         r.get("status").and_then(|s| s.as_u64()).unwrap_or(0),
                         r.get("elapsed_ms").and_then(|t| t.as_f64()).unwrap_or(0.0),
                     );
+                    if let Some(file) = r.get("file").and_then(|f| f.as_str()) {
+                        println!("💾 {file}");
+                    }
                     if show_body {
                         if let Some(md) = r.get("markdown").and_then(|m| m.as_str()) {
                             let display = if max_body > 0 && md.len() > max_body {
@@ -590,61 +1328,1149 @@ async fn cmd_fetch_batch(
     Ok(())
 }
 
-/// Build HTTP client with optional proxy and redirect settings
-fn build_client(no_redirect: bool, proxy: Option<&str>) -> Result<AcceleratedClient> {
-    // Check for proxy from argument or environment
-    let proxy_url = proxy
+/// Send one batch request and describe the outcome as a JSON result object.
+///
+/// The request goes through [`AcceleratedClient::execute`], so batch URLs are
+/// retried by the same policy as a single fetch.
+///
+/// With an output directory, a successful body is also saved there as the
+/// `index`-th file and its path is reported as `file`.
+///
+/// Failures carry a machine-readable `error_kind` (`dns`, `timeout`, `tls`,
+/// `connect`, `status`, `redirect`, `decode`, `write`) alongside the
+/// human-readable `error`.
+async fn batch_result(
+    client: &AcceleratedClient,
+    url: &str,
+    request: reqwest::RequestBuilder,
+    start: Instant,
+    raw_html: bool,
+    output: Option<(&OutputDir, usize)>,
+) -> serde_json::Value {
+    let request = match request.build() {
+        Ok(request) => request,
+        Err(e) => {
+            return serde_json::json!({
+                "url": url,
+                "error": e.to_string(),
+                "error_kind": "client",
+            });
+        }
+    };
+    let response = match client.execute(request).await {
+        Ok(response) => response,
+        Err(e) => {
+            let Some(cause) = e.downcast_ref::<reqwest::Error>() else {
+                return serde_json::json!({
+                    "url": url,
+                    "error": format!("{e:#}"),
+                    "error_kind": "client",
+                });
+            };
+            let error =
+                RedirectLoop::from_error(cause).map_or_else(|| format!("{e:#}"), |l| l.to_string());
+            return serde_json::json!({
+                "url": url,
+                "error": error,
+                "error_kind": classify_error(cause),
+            });
+        }
+    };
+
+    let elapsed = start.elapsed();
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("text/html")
+        .to_string();
+
+    let body_bytes = match response.bytes().await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            return serde_json::json!({
+                "url": url,
+                "status": status.as_u16(),
+                "error": e.to_string(),
+                "error_kind": classify_error(&e),
+            });
+        }
+    };
+    let body_len = body_bytes.len();
+    let raw_text = String::from_utf8_lossy(&body_bytes).to_string();
+
+    let markdown = if raw_html {
+        raw_text
+    } else {
+        let router = nab::content::ContentRouter::new();
+        router
+            .convert(&body_bytes, &content_type)
+            .map_or_else(|_| raw_text.clone(), |r| r.markdown)
+    };
+
+    let metadata = serde_json::json!({
+        "title": extract_title(&String::from_utf8_lossy(&body_bytes)),
+        "content_length": body_len,
+        "content_type": content_type,
+    });
+
+    let mut result = serde_json::json!({
+        "url": url,
+        "status": status.as_u16(),
+        "content_type": content_type,
+        "markdown": markdown,
+        "metadata": metadata,
+        "elapsed_ms": (elapsed.as_secs_f64() * 1000.0 * 10.0).round() / 10.0,
+    });
+
+    if status.is_client_error() || status.is_server_error() {
+        result["error"] = serde_json::Value::String(format!("HTTP {status}"));
+        result["error_kind"] = serde_json::Value::String("status".to_string());
+    } else if let Some((dir, index)) = output {
+        // JSON and PDF are saved untouched; everything else as converted text
+        let ext = match extension_for(&content_type) {
+            "md" if raw_html => "html",
+            ext => ext,
+        };
+        let contents = match ext {
+            "json" | "pdf" => &body_bytes[..],
+            _ => markdown.as_bytes(),
+        };
+        match dir.write(url, index, ext, contents) {
+            Ok(path) => result["file"] = serde_json::Value::String(path.display().to_string()),
+            Err(e) => {
+                result["error"] = serde_json::Value::String(format!("{e:#}"));
+                result["error_kind"] = serde_json::Value::String("write".to_string());
+            }
+        }
+    }
+
+    result
+}
+
+/// Classify a reqwest error into a coarse, stable category for batch output.
+fn classify_error(err: &reqwest::Error) -> &'static str {
+    if err.is_timeout() {
+        return "timeout";
+    }
+    if err.is_status() {
+        return "status";
+    }
+    if err.is_redirect() {
+        return "redirect";
+    }
+    if err.is_decode() || err.is_body() {
+        return "decode";
+    }
+
+    // DNS and TLS failures surface as connect errors; inspect the source chain
+    let mut chain = String::new();
+    let mut source = std::error::Error::source(err);
+    while let Some(cause) = source {
+        chain.push_str(&cause.to_string().to_lowercase());
+        chain.push('\n');
+        source = cause.source();
+    }
+
+    if chain.contains("dns")
+        || chain.contains("failed to lookup")
+        || chain.contains("no record found")
+        || chain.contains("name or service not known")
+    {
+        "dns"
+    } else if chain.contains("certificate") || chain.contains("tls") || chain.contains("handshake")
+    {
+        "tls"
+    } else {
+        "connect"
+    }
+}
+
+/// Await batch tasks concurrently, calling `on_complete` as each one finishes,
+/// and return the results sorted back into input order.
+///
+/// A task that panicked or was cancelled still yields a result for its URL,
+/// with `error_kind` `internal`.
+async fn collect_batch_results<F>(
+    handles: Vec<(usize, String, tokio::task::JoinHandle<serde_json::Value>)>,
+    mut on_complete: F,
+) -> Vec<serde_json::Value>
+where
+    F: FnMut(&serde_json::Value),
+{
+    use futures::stream::{FuturesUnordered, StreamExt};
+
+    let mut pending: FuturesUnordered<_> = handles
+        .into_iter()
+        .map(|(index, url, handle)| async move {
+            let result = match handle.await {
+                Ok(result) => result,
+                Err(e) => serde_json::json!({
+                    "url": url,
+                    "error": e.to_string(),
+                    "error_kind": "internal",
+                }),
+            };
+            (index, result)
+        })
+        .collect();
+
+    let mut indexed = Vec::new();
+    while let Some((index, result)) = pending.next().await {
+        on_complete(&result);
+        indexed.push((index, result));
+    }
+
+    indexed.sort_by_key(|(index, _)| *index);
+    indexed.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Fingerprints to pin: the per-domain overrides from `fingerprints.toml`,
+/// plus an explicit `--cookies` browser for `url`'s host, the one its
+/// cookies are sent to
+fn fingerprint_overrides(profiles: DomainProfiles, cookies: &str, url: &str) -> DomainProfiles {
+    let host = url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string));
+    match (cookies.parse::<BrowserType>(), host) {
+        (Ok(browser), Some(host)) => profiles.with_rule(host, browser),
+        _ => profiles,
+    }
+}
+
+/// Build HTTP client with optional proxy, redirect, User-Agent and TLS settings
+///
+/// `max_redirects` of 0 returns redirect responses instead of following them.
+fn build_client(
+    url: &str,
+    max_redirects: usize,
+    proxy: Option<&str>,
+    user_agent: Option<&str>,
+    tls: &TlsOptions,
+    fingerprints: DomainProfiles,
+) -> Result<AcceleratedClient> {
+    let (profile, fingerprints) = fetch_profile(url, user_agent, fingerprints)?;
+    client_with(
+        profile,
+        fingerprints,
+        max_redirects,
+        proxy,
+        tls,
+        |builder| builder,
+    )
+}
+
+/// Browser profile for requests to `url`, and the per-domain fingerprints
+/// that still apply
+///
+/// A custom `user_agent` replaces the profile's and disables per-domain
+/// fingerprints, whose headers would contradict it.
+fn fetch_profile(
+    url: &str,
+    user_agent: Option<&str>,
+    fingerprints: DomainProfiles,
+) -> Result<(BrowserProfile, DomainProfiles)> {
+    // Start from the target host's pinned browser so its requests carry
+    // only that browser's headers
+    let profile = url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().and_then(|host| fingerprints.browser_for(host)))
+        .map_or_else(nab::random_profile, profile_for);
+    match user_agent {
+        Some(ua) => Ok((with_user_agent(profile, ua)?, DomainProfiles::new())),
+        None => Ok((profile, fingerprints)),
+    }
+}
+
+/// Client presenting `profile`, with the proxy, redirect and TLS settings,
+/// letting `customize` adjust the reqwest builder last
+///
+/// Every `fetch` client is built here, so all of them get the shared cookie
+/// jar, timeouts and connection pool of [`AcceleratedClient::with_builder`].
+fn client_with(
+    profile: BrowserProfile,
+    fingerprints: DomainProfiles,
+    max_redirects: usize,
+    proxy: Option<&str>,
+    tls: &TlsOptions,
+    customize: impl FnOnce(ClientBuilder) -> ClientBuilder,
+) -> Result<AcceleratedClient> {
+    let proxy = proxy_url(proxy)
+        .map(|purl| {
+            reqwest::Proxy::all(&purl)
+                .map_err(|e| anyhow::anyhow!("Invalid proxy URL '{purl}': {e}"))
+        })
+        .transpose()?;
+    let client = AcceleratedClient::with_builder(profile, tls.clone(), max_redirects, |builder| {
+        let builder = match proxy {
+            Some(proxy) => builder.proxy(proxy),
+            None => builder,
+        };
+        customize(builder)
+    })?;
+    Ok(client.with_domain_profiles(fingerprints))
+}
+
+/// Replace `profile`'s User-Agent with `--user-agent`
+pub fn with_user_agent(profile: BrowserProfile, user_agent: &str) -> Result<BrowserProfile> {
+    reqwest::header::HeaderValue::from_str(user_agent)
+        .map_err(|_| anyhow::anyhow!("Invalid --user-agent '{user_agent}'"))?;
+    Ok(profile.with_user_agent(user_agent))
+}
+
+/// Proxy from `--proxy`, else from the usual environment variables
+fn proxy_url(proxy: Option<&str>) -> Option<String> {
+    proxy
         .map(String::from)
         .or_else(|| std::env::var("HTTPS_PROXY").ok())
         .or_else(|| std::env::var("HTTP_PROXY").ok())
         .or_else(|| std::env::var("ALL_PROXY").ok())
         .or_else(|| std::env::var("https_proxy").ok())
         .or_else(|| std::env::var("http_proxy").ok())
-        .or_else(|| std::env::var("all_proxy").ok());
-
-    if let Some(ref purl) = proxy_url {
-        // Build client with proxy
-        let proxy = reqwest::Proxy::all(purl)
-            .map_err(|e| anyhow::anyhow!("Invalid proxy URL '{}': {}", purl, e))?;
-
-        let mut builder = reqwest::Client::builder().proxy(proxy);
-
-        if no_redirect {
-            builder = builder.redirect(reqwest::redirect::Policy::none());
-        }
+        .or_else(|| std::env::var("all_proxy").ok())
+}
 
-        let inner_client = builder.build()?;
-        AcceleratedClient::from_client(inner_client)
-    } else if no_redirect {
-        AcceleratedClient::new_no_redirect()
+/// Combine `--tls13-only`, `--min-tls`, `--max-tls`, `--cacert` and
+/// `--insecure` into TLS options
+pub fn tls_options(
+    tls13_only: bool,
+    min_tls: Option<&str>,
+    max_tls: Option<&str>,
+    cacert: Option<&str>,
+    insecure: bool,
+) -> Result<TlsOptions> {
+    let mut tls = if tls13_only {
+        TlsOptions::tls13_only()
     } else {
-        AcceleratedClient::new()
+        TlsOptions::default()
+    };
+    if let Some(min) = min_tls {
+        tls = tls.with_min_tls_version(TlsOptions::parse_version(min)?);
+    }
+    if let Some(max) = max_tls {
+        tls = tls.with_max_tls_version(TlsOptions::parse_version(max)?);
+    }
+    if let (Some(min), Some(max)) = (tls.min_tls_version, tls.max_tls_version) {
+        if min > max {
+            anyhow::bail!("--min-tls is newer than --max-tls");
+        }
+    }
+    if let Some(path) = cacert {
+        tls = tls.with_root_certificate_file(path)?;
+    }
+    if insecure {
+        eprintln!(
+            "⚠️  WARNING: --insecure disables TLS certificate verification. \
+             The connection can be intercepted; do not send credentials."
+        );
+        tls = tls.with_insecure(true);
     }
+    Ok(tls)
 }
 
 /// Resolve browser name from cookie flag
+///
+/// `auto` uses the cached default-browser detection; when detection fails it
+/// falls back to the browser last chosen explicitly, then Chrome. An explicit
+/// browser is remembered as the last one chosen.
 pub fn resolve_browser_name(cookies: &str) -> Option<String> {
+    let cache = nab::BrowserCache::default();
     if cookies.to_lowercase() == "none" {
         None
     } else if cookies.to_lowercase() == "auto" {
-        if let Ok(detected) = nab::detect_default_browser() {
-            Some(detected.as_str().to_string())
-        } else {
-            Some("chrome".to_string()) // fallback
+        match cache.default_browser() {
+            Ok(detected) => Some(detected.as_str().to_string()),
+            Err(e) => {
+                tracing::debug!("Browser detection failed: {e}");
+                Some(
+                    cache
+                        .last_explicit()
+                        .unwrap_or_else(|| "chrome".to_string()),
+                )
+            }
         }
     } else {
+        if let Err(e) = cache.remember_explicit(cookies) {
+            tracing::debug!("Failed to remember browser choice: {e}");
+        }
         Some(cookies.to_string())
     }
 }
 
 /// Resolve CookieSource from browser name string
 pub fn resolve_cookie_source(browser: &str) -> CookieSource {
-    match browser.to_lowercase().as_str() {
-        "brave" => CookieSource::Brave,
-        "chrome" => CookieSource::Chrome,
-        "firefox" => CookieSource::Firefox,
-        "safari" => CookieSource::Safari,
-        "edge" => CookieSource::Chrome,
-        _ => CookieSource::Chrome,
+    CookieSource::from_browser_name(browser)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::test_support::{serve, Response};
+
+    /// Serve HTTP/1.1 on localhost, replying with `response` after `delay`.
+    /// Returns the base URL of the mock endpoint.
+    fn mock_endpoint(response: Response, delay: std::time::Duration) -> String {
+        let addr = serve(move |_| {
+            std::thread::sleep(delay);
+            response.clone()
+        });
+        format!("http://{addr}/")
+    }
+
+    /// Fetch flags for tests: compact output and no browser cookies
+    fn quiet() -> FetchOptions<'static> {
+        FetchOptions {
+            format: OutputFormat::Compact,
+            cookies: "none",
+            ..FetchOptions::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn batch_result_classifies_timeout() {
+        let url = mock_endpoint(Response::status(200), std::time::Duration::from_secs(2));
+        let client = AcceleratedClient::from_client(
+            reqwest::Client::builder()
+                .timeout(std::time::Duration::from_millis(200))
+                .build()
+                .unwrap(),
+        )
+        .unwrap()
+        .with_retry(nab::RetryPolicy::none());
+
+        let request = client.inner().get(&url);
+        let result = batch_result(&client, &url, request, Instant::now(), true, None).await;
+        assert_eq!(result["error_kind"], "timeout");
+        assert!(result.get("status").is_none());
+    }
+
+    #[tokio::test]
+    async fn batch_result_classifies_http_status() {
+        let url = mock_endpoint(
+            Response::status(404)
+                .header("Content-Type", "text/plain")
+                .body("not found"),
+            std::time::Duration::ZERO,
+        );
+        let client = AcceleratedClient::from_client(reqwest::Client::new()).unwrap();
+
+        let request = client.inner().get(&url);
+        let result = batch_result(&client, &url, request, Instant::now(), true, None).await;
+        assert_eq!(result["error_kind"], "status");
+        assert_eq!(result["status"], 404);
+        assert_eq!(result["markdown"], "not found");
+    }
+
+    #[tokio::test]
+    async fn batch_result_retries_transient_status() {
+        let attempts = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let seen = Arc::clone(&attempts);
+        let addr = serve(move |_| {
+            if seen.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 0 {
+                Response::status(503)
+            } else {
+                Response::typed("text/plain", "recovered")
+            }
+        });
+        let url = format!("http://{addr}/");
+        let client = AcceleratedClient::from_client(reqwest::Client::new())
+            .unwrap()
+            .with_retry(nab::RetryPolicy {
+                base_delay: std::time::Duration::from_millis(1),
+                ..nab::RetryPolicy::default()
+            });
+
+        let request = client.inner().get(&url);
+        let result = batch_result(&client, &url, request, Instant::now(), true, None).await;
+        assert_eq!(result["status"], 200);
+        assert_eq!(result["markdown"], "recovered");
+        assert_eq!(attempts.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn batch_results_follow_input_order() {
+        // Earlier URLs are slower, so tasks complete in reverse input order
+        let latencies_ms = [60u64, 40, 20, 0];
+        let handles = latencies_ms
+            .iter()
+            .enumerate()
+            .map(|(index, &delay)| {
+                let url = format!("https://example.com/{index}");
+                let handle = tokio::spawn({
+                    let url = url.clone();
+                    async move {
+                        tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+                        serde_json::json!({"url": url})
+                    }
+                });
+                (index, url, handle)
+            })
+            .collect();
+
+        let mut completed = Vec::new();
+        let results = collect_batch_results(handles, |r| {
+            completed.push(r["url"].as_str().unwrap().to_string());
+        })
+        .await;
+
+        let urls: Vec<&str> = results.iter().map(|r| r["url"].as_str().unwrap()).collect();
+        assert_eq!(
+            urls,
+            [
+                "https://example.com/0",
+                "https://example.com/1",
+                "https://example.com/2",
+                "https://example.com/3",
+            ]
+        );
+        assert_eq!(completed.first().unwrap(), "https://example.com/3");
+    }
+
+    #[tokio::test]
+    async fn batch_task_panic_reports_its_url() {
+        let url = "https://example.com/boom".to_string();
+        let handle = tokio::spawn(async { panic!("boom") });
+
+        let results = collect_batch_results(vec![(0, url, handle)], |_| {}).await;
+
+        assert_eq!(results[0]["url"], "https://example.com/boom");
+        assert_eq!(results[0]["error_kind"], "internal");
+        assert!(results[0]["error"].as_str().unwrap().contains("panicked"));
+    }
+
+    /// Records every new span with its parent and the nearest `request_id`.
+    #[derive(Clone, Default)]
+    struct SpanCapture(std::sync::Arc<std::sync::Mutex<Vec<CapturedSpan>>>);
+
+    struct CapturedSpan {
+        name: &'static str,
+        parent: Option<&'static str>,
+        request_id: Option<String>,
+    }
+
+    struct RequestId(String);
+
+    impl<S> tracing_subscriber::Layer<S> for SpanCapture
+    where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            id: &tracing::span::Id,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            struct Visitor(Option<String>);
+            impl tracing::field::Visit for Visitor {
+                fn record_debug(&mut self, field: &field::Field, value: &dyn std::fmt::Debug) {
+                    if field.name() == "request_id" {
+                        self.0 = Some(format!("{value:?}"));
+                    }
+                }
+            }
+
+            let span = ctx.span(id).unwrap();
+            let mut visitor = Visitor(None);
+            attrs.record(&mut visitor);
+            if let Some(request_id) = visitor.0 {
+                span.extensions_mut().insert(RequestId(request_id));
+            }
+            let request_id = span
+                .scope()
+                .find_map(|s| s.extensions().get::<RequestId>().map(|r| r.0.clone()));
+
+            self.0.lock().unwrap().push(CapturedSpan {
+                name: span.name(),
+                parent: span.parent().map(|p| p.name()),
+                request_id,
+            });
+        }
+    }
+
+    struct StubRenderer(&'static str);
+
+    #[async_trait::async_trait]
+    impl PageRenderer for StubRenderer {
+        fn name(&self) -> &'static str {
+            "stub"
+        }
+
+        async fn render(&self, _url: &str) -> Result<String> {
+            Ok(self.0.to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn render_fallback_replaces_empty_csr_page() {
+        let shell = format!(
+            r#"<html><head><title>Shop</title></head><body><div id="app"></div>
+<script src="/bundle.js"></script><script>window.__CONFIG__="{}"</script></body></html>"#,
+            "x".repeat(2000)
+        );
+        let rendered = "<html><body><h1>Spring catalogue</h1><p>Forty new products are in stock.</p></body></html>";
+
+        let (html, markdown, name) = render_fallback("https://shop.test/", &shell, "Shop", || {
+            Ok(Box::new(StubRenderer(rendered)))
+        })
+        .await
+        .unwrap()
+        .expect("CSR shell should trigger rendering");
+
+        assert_eq!(html, rendered);
+        assert_eq!(name, "stub");
+        assert!(markdown.contains("Spring catalogue"));
+        assert!(markdown.contains("Forty new products"));
+    }
+
+    #[tokio::test]
+    async fn render_fallback_skips_server_rendered_page() {
+        let html = format!(
+            "<html><body><script></script>{}</body></html>",
+            "<p>word</p>".repeat(200)
+        );
+        let markdown = "word ".repeat(200);
+
+        let result = render_fallback("https://example.com/", &html, &markdown, || {
+            panic!("renderer must not be created for server-rendered pages")
+        })
+        .await
+        .unwrap();
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn edge_reads_its_own_cookies() {
+        assert_eq!(resolve_cookie_source("Edge"), CookieSource::Edge);
+        assert_eq!(resolve_cookie_source("dia"), CookieSource::Chrome);
+    }
+
+    #[test]
+    fn cookie_browser_pins_only_the_url_host() {
+        let config = DomainProfiles::new().with_rule("apple.com", BrowserType::Safari);
+        let pins = fingerprint_overrides(config, "Firefox", "https://news.example.com/a");
+
+        assert_eq!(
+            pins.browser_for("news.example.com"),
+            Some(BrowserType::Firefox)
+        );
+        assert_eq!(pins.browser_for("other.org"), None);
+        assert_eq!(pins.browser_for("apple.com"), Some(BrowserType::Safari));
+        assert!(fingerprint_overrides(DomainProfiles::new(), "auto", "https://a.com/").is_empty());
+    }
+
+    #[test]
+    fn parses_byte_ranges() {
+        assert_eq!(parse_byte_range("0-1023").unwrap(), (0, Some(1023)));
+        assert_eq!(parse_byte_range("512-").unwrap(), (512, None));
+        assert!(parse_byte_range("10-5").is_err());
+        assert!(parse_byte_range("abc").is_err());
+    }
+
+    #[test]
+    fn parses_deadlines() {
+        assert_eq!(parse_deadline("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_deadline("500ms").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_deadline("1m30s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_deadline("2H").unwrap(), Duration::from_hours(2));
+        assert!(parse_deadline("0s").is_err());
+        assert!(parse_deadline("0").is_err());
+        assert!(parse_deadline("10x").is_err());
+        assert!(parse_deadline("soon").is_err());
+        assert!(parse_deadline("18446744073709551615h").is_err());
+    }
+
+    #[tokio::test]
+    async fn deadline_aborts_stalled_fetch() {
+        tokio::task::spawn_blocking(nab::random_profile)
+            .await
+            .unwrap();
+        let url = mock_endpoint(Response::status(200), Duration::from_secs(30));
+
+        let start = Instant::now();
+        let fetch = cmd_fetch(&url, quiet());
+        let err = with_deadline(Some(Duration::from_millis(300)), fetch)
+            .await
+            .unwrap_err();
+
+        assert!(start.elapsed() < Duration::from_secs(5));
+        let exceeded = err.downcast_ref::<DeadlineExceeded>().unwrap();
+        assert_eq!(exceeded.0, Duration::from_millis(300));
+        assert_eq!(err.to_string(), "Deadline exceeded: gave up after 300ms");
+    }
+
+    #[test]
+    fn no_redirect_tls_client_shares_the_cookie_jar() {
+        let tls = TlsOptions::tls13_only().with_insecure(true);
+        let client = build_client(
+            "https://example.com/",
+            0,
+            None,
+            None,
+            &tls,
+            DomainProfiles::new(),
+        )
+        .unwrap();
+        assert!(client.cookie_jar().is_some());
+    }
+
+    #[test]
+    fn combines_tls_flags() {
+        let tls = tls_options(true, None, None, None, false).unwrap();
+        assert_eq!(tls, TlsOptions::tls13_only());
+
+        let legacy = tls_options(false, Some("1.2"), Some("1.2"), None, false).unwrap();
+        assert_eq!(legacy.min_tls_version, Some(reqwest::tls::Version::TLS_1_2));
+        assert_eq!(legacy.max_tls_version, Some(reqwest::tls::Version::TLS_1_2));
+
+        assert!(tls_options(true, None, Some("1.2"), None, false).is_err());
+        assert!(tls_options(false, None, None, Some("/nonexistent/ca.pem"), false).is_err());
+
+        let insecure = tls_options(false, None, None, None, true).unwrap();
+        assert!(insecure.insecure);
+        assert!(!TlsOptions::default().insecure);
+    }
+
+    #[tokio::test]
+    async fn fetch_spans_nest_under_request_id() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        // Load browser profiles off the runtime before cmd_fetch needs them
+        tokio::task::spawn_blocking(nab::random_profile)
+            .await
+            .unwrap();
+
+        let url = mock_endpoint(
+            Response::typed("text/html", "<html><p>traced</p></html>\n"),
+            std::time::Duration::ZERO,
+        );
+        let capture = SpanCapture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        cmd_fetch(&url, quiet()).await.unwrap();
+
+        let spans = capture.0.lock().unwrap();
+        let fetch = spans.iter().find(|s| s.name == "fetch").unwrap();
+        let request_id = fetch.request_id.clone().unwrap();
+        assert_eq!(request_id.len(), 36);
+
+        for child in ["site_router", "content_convert"] {
+            let span = spans.iter().find(|s| s.name == child).unwrap();
+            assert_eq!(span.parent, Some("fetch"), "{child} parent");
+            assert_eq!(span.request_id.as_deref(), Some(request_id.as_str()));
+        }
+    }
+
+    #[tokio::test]
+    async fn batch_output_dir_writes_one_file_per_url() {
+        tokio::task::spawn_blocking(nab::random_profile)
+            .await
+            .unwrap();
+
+        let addr = serve(|request| match request.target.as_str() {
+            "/api/items?page=2" => Response::typed("application/json", r#"{"items":[1,2]}"#),
+            "/files/Report.pdf" => Response::typed("application/pdf", "%PDF-1.4 stub"),
+            _ => Response::typed("text/html", "<html><body><h1>Guide</h1></body></html>"),
+        });
+        let base = format!("http://{addr}");
+
+        let dir = std::env::temp_dir().join(format!("nab-batch-{}", uuid::Uuid::new_v4()));
+        let batch = dir.join("urls.txt");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            &batch,
+            format!(
+                "{base}/docs/Getting Started\n{base}/api/items?page=2\n\
+                 {base}/files/Report.pdf\n{base}/docs/Getting%20Started\n"
+            ),
+        )
+        .unwrap();
+        let out_dir = dir.join("out");
+        let output = OutputDir::new(&out_dir)
+            .with_template("{index}-{path_slug}.{ext}")
+            .unwrap();
+
+        cmd_fetch(
+            "",
+            FetchOptions {
+                format: OutputFormat::Json,
+                max_redirects: 0,
+                batch_file: Some(batch.to_str().unwrap()),
+                parallel: 2,
+                output_dir: Some(&output),
+                ..quiet()
+            },
+        )
+        .await
+        .unwrap();
+
+        let mut files: Vec<String> = std::fs::read_dir(&out_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        assert_eq!(
+            files,
+            [
+                "1-docs-getting-started.md",
+                "2-api-items-page-2.json",
+                "3-files-report.pdf",
+                "4-docs-getting-started.md",
+            ]
+        );
+        let json = std::fs::read_to_string(out_dir.join("2-api-items-page-2.json")).unwrap();
+        assert_eq!(json, r#"{"items":[1,2]}"#);
+        let markdown = std::fs::read_to_string(out_dir.join("1-docs-getting-started.md")).unwrap();
+        assert!(markdown.contains("Guide"), "{markdown}");
+
+        // Without {index}, the same page twice gets a numeric suffix
+        let output = OutputDir::new(&out_dir)
+            .with_template("{host}-{path_slug}.{ext}")
+            .unwrap();
+        let host = url::Url::parse(&base)
+            .unwrap()
+            .host_str()
+            .unwrap()
+            .to_string();
+        let first = output.write(&format!("{base}/a"), 1, "md", b"a").unwrap();
+        let second = output.write(&format!("{base}/a"), 2, "md", b"b").unwrap();
+        assert_eq!(first, out_dir.join(format!("{host}-a.md")));
+        assert_eq!(second, out_dir.join(format!("{host}-a-2.md")));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn reports_compression_of_gzip_body() {
+        use flate2::write::GzEncoder;
+
+        let html = format!(
+            "<html><body>{}</body></html>",
+            "<p>repeated words</p>".repeat(500)
+        );
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(html.as_bytes()).unwrap();
+        let gzipped = encoder.finish().unwrap();
+        let on_wire = gzipped.len();
+
+        let addr = serve(move |_| {
+            Response::typed("text/html", gzipped.clone()).header("Content-Encoding", "gzip")
+        });
+        let url = format!("http://{addr}/");
+
+        let client = tokio::task::spawn_blocking(|| {
+            client_with(
+                nab::random_profile(),
+                DomainProfiles::new(),
+                0,
+                None,
+                &TlsOptions::default(),
+                without_decompression,
+            )
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        let response = client
+            .inner()
+            .get(&url)
+            .header("Accept-Encoding", "gzip, br")
+            .send()
+            .await
+            .unwrap();
+        let wire = WireBody::read(response).await.unwrap();
+        assert_eq!(wire.body, html.as_bytes());
+
+        let summary = compression_summary(&wire);
+        assert_eq!(summary["content_encoding"], "gzip");
+        assert_eq!(summary["compressed_bytes"], on_wire);
+        assert_eq!(summary["decompressed_bytes"], html.len());
+        let ratio = summary["ratio"].as_f64().unwrap();
+        #[allow(clippy::cast_precision_loss)]
+        let expected = html.len() as f64 / on_wire as f64;
+        assert!(ratio > 10.0, "ratio {ratio}");
+        assert!(
+            (ratio - expected).abs() < 0.01,
+            "ratio {ratio} vs {expected}"
+        );
+    }
+
+    /// Redirects `/a` → `/b` → `/a` forever and `/start` → `/done`
+    fn redirect_server() -> String {
+        let addr = serve(|request| {
+            let location = match request.target.as_str() {
+                "/a" => "/b",
+                "/b" => "/a",
+                "/start" => "/done",
+                _ => return Response::ok("done"),
+            };
+            Response::status(302).header("Location", location)
+        });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn redirect_loop_is_reported_by_fetch() {
+        tokio::task::spawn_blocking(nab::random_profile)
+            .await
+            .unwrap();
+        let base = redirect_server();
+
+        let err = cmd_fetch(
+            &format!("{base}/a"),
+            FetchOptions {
+                max_redirects: 5,
+                ..quiet()
+            },
+        )
+        .await
+        .unwrap_err();
+
+        let redirect_loop = err.downcast_ref::<RedirectLoop>().unwrap();
+        assert_eq!(redirect_loop.url, format!("{base}/a"));
+        assert_eq!(
+            redirect_loop.chain,
+            [format!("{base}/a"), format!("{base}/b")]
+        );
+    }
+
+    #[tokio::test]
+    async fn max_redirects_zero_behaves_like_no_redirect() {
+        let base = redirect_server();
+        let url = format!("{base}/start");
+
+        let build = |max_redirects| {
+            let url = url.clone();
+            tokio::task::spawn_blocking(move || {
+                build_client(
+                    &url,
+                    max_redirects,
+                    None,
+                    None,
+                    &TlsOptions::default(),
+                    DomainProfiles::new(),
+                )
+            })
+        };
+        let zero = build(0).await.unwrap().unwrap();
+        let following = build(3).await.unwrap().unwrap();
+
+        let response = zero.inner().get(&url).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::FOUND);
+        assert_eq!(response.headers()["location"], "/done");
+
+        let response = following.inner().get(&url).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert_eq!(response.url().path(), "/done");
+    }
+
+    #[tokio::test]
+    async fn warmup_cookies_reach_main_request() {
+        tokio::task::spawn_blocking(nab::random_profile)
+            .await
+            .unwrap();
+
+        let (tx, rx) = std::sync::mpsc::channel::<String>();
+        let addr = serve(move |request| {
+            let _ = tx.send(request.head().to_lowercase());
+            if request.target == "/warm" {
+                Response::ok("ok").header("Set-Cookie", "sid=fresh; Path=/")
+            } else {
+                Response::ok("ok")
+            }
+        });
+        let base = format!("http://{addr}");
+
+        cmd_fetch(
+            &format!("{base}/page"),
+            FetchOptions {
+                warmup_url: Some(&format!("{base}/warm")),
+                ..quiet()
+            },
+        )
+        .await
+        .unwrap();
+
+        assert!(rx.recv().unwrap().starts_with("get /warm"));
+        let main = rx.recv().unwrap();
+        assert!(main.starts_with("get /page"), "{main}");
+        assert!(main.contains("cookie: sid=fresh"), "{main}");
+    }
+
+    #[tokio::test]
+    async fn custom_user_agent_is_sent_exactly_once() {
+        tokio::task::spawn_blocking(nab::random_profile)
+            .await
+            .unwrap();
+
+        let (tx, rx) = std::sync::mpsc::channel::<String>();
+        let addr = serve(move |request| {
+            let _ = tx.send(request.head().to_lowercase());
+            Response::ok("ok")
+        });
+        let url = format!("http://{addr}/");
+        let ua = "nab-test/2.0 (+https://example.com/bot)";
+        let assert_exact = |request: &str| {
+            let agents: Vec<&str> = request
+                .lines()
+                .filter_map(|line| line.strip_prefix("user-agent:"))
+                .map(str::trim)
+                .collect();
+            assert_eq!(agents, [ua.to_lowercase()], "{request}");
+            assert!(!request.contains("sec-ch-ua"), "{request}");
+        };
+
+        cmd_fetch(
+            &url,
+            FetchOptions {
+                user_agent: Some(ua),
+                ..quiet()
+            },
+        )
+        .await
+        .unwrap();
+        assert_exact(&rx.recv().unwrap());
+
+        // Clients with the profile as default headers send it too
+        let client = {
+            let url = url.clone();
+            tokio::task::spawn_blocking(move || {
+                build_client(
+                    &url,
+                    0,
+                    None,
+                    Some(ua),
+                    &TlsOptions::default(),
+                    DomainProfiles::new(),
+                )
+            })
+            .await
+            .unwrap()
+            .unwrap()
+        };
+        client.fetch(&url).await.unwrap();
+        assert_exact(&rx.recv().unwrap());
+
+        assert!(with_user_agent(nab::chrome_profile(), "bad\nagent").is_err());
+    }
+
+    #[test]
+    fn cookie_table_shows_every_attribute() {
+        let now = chrono::DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let cookies = [
+            SetCookie::parse_at(
+                "sid=abc123; Domain=.example.com; Path=/; Max-Age=60; Secure; HttpOnly; SameSite=Strict",
+                now,
+            )
+            .unwrap(),
+            SetCookie::parse_at("theme=dark", now).unwrap(),
+        ];
+        let table = cookie_table(&cookies);
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].trim_start().starts_with("NAME"));
+        assert!(lines[1].contains("sid"));
+        assert!(lines[1].contains(".example.com"));
+        assert!(lines[1].contains("2026-01-01 00:01:00 UTC"));
+        assert!(lines[1].ends_with("Secure HttpOnly SameSite=Strict"));
+        assert!(lines[2].contains("session"));
+        // Columns line up
+        assert_eq!(lines[0].find("VALUE"), lines[1].find("abc123"));
+    }
+
+    #[test]
+    fn output_directory_uses_suggested_file_name() {
+        let dir = std::env::temp_dir().join(format!("nab-output-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            reqwest::header::CONTENT_DISPOSITION,
+            "attachment; filename*=UTF-8''Q3%20report.pdf"
+                .parse()
+                .unwrap(),
+        );
+        let url = "https://example.com/download/Q3%20r%C3%A9port.pdf?id=7";
+
+        assert_eq!(
+            output_path(dir.clone(), &headers, url),
+            dir.join("Q3 report.pdf")
+        );
+        let empty = reqwest::header::HeaderMap::new();
+        assert_eq!(
+            output_path(dir.clone(), &empty, url),
+            dir.join("Q3 réport.pdf")
+        );
+        assert_eq!(
+            output_path(dir.clone(), &empty, "https://example.com/"),
+            dir.join("index")
+        );
+        // A file path is used as given
+        let file = dir.join("mine.md");
+        assert_eq!(output_path(file.clone(), &headers, url), file);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn output_directory_saves_the_raw_response() {
+        tokio::task::spawn_blocking(nab::random_profile)
+            .await
+            .unwrap();
+        let dir = std::env::temp_dir().join(format!("nab-download-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let html = "<html><body><h1>Q3</h1><p>Revenue grew.</p></body></html>";
+        let addr = serve(move |_| {
+            Response::typed("text/html", html)
+                .header("Content-Disposition", "attachment; filename=\"q3.html\"")
+        });
+
+        cmd_fetch(
+            &format!("http://{addr}/report"),
+            FetchOptions {
+                output_file: Some(dir.clone()),
+                ..quiet()
+            },
+        )
+        .await
+        .unwrap();
+
+        // The server's bytes, not the markdown conversion
+        assert_eq!(std::fs::read_to_string(dir.join("q3.html")).unwrap(), html);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn dry_run_prints_request_without_sending() {
+        tokio::task::spawn_blocking(nab::random_profile)
+            .await
+            .unwrap();
+
+        // Counts requests before answering, so any sent one is seen
+        let requests = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = std::sync::Arc::clone(&requests);
+        let addr = serve(move |_| {
+            counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Response::status(200)
+        });
+        let url = format!("http://{addr}/api");
+
+        cmd_fetch(
+            &url,
+            FetchOptions {
+                custom_headers: &["Cookie: sid=abc123".to_string()],
+                warmup_url: Some(&url),
+                method: "POST",
+                data: Some(r#"{"q":1}"#),
+                dry_run: true,
+                ..quiet()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 0);
     }
 }
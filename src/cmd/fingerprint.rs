@@ -1,13 +1,28 @@
-pub fn cmd_fingerprint(count: usize) {
-    println!("🎭 Generating {count} browser fingerprints:\n");
+pub fn cmd_fingerprint(count: usize, mobile: bool) {
+    let kind = if mobile { "mobile" } else { "browser" };
+    println!("🎭 Generating {count} {kind} fingerprints:\n");
 
     for i in 0..count {
-        let profile = nab::random_profile();
+        let profile = if mobile {
+            nab::mobile_profile()
+        } else {
+            nab::random_profile()
+        };
         println!("Profile {}:", i + 1);
         println!("   UA: {}", profile.user_agent);
         println!("   Accept-Language: {}", profile.accept_language);
         if !profile.sec_ch_ua.is_empty() {
             println!("   Sec-CH-UA: {}", profile.sec_ch_ua);
+            println!(
+                "   Sec-CH-UA-Mobile: {}  Sec-CH-UA-Platform: {}",
+                profile.sec_ch_ua_mobile, profile.sec_ch_ua_platform
+            );
+        }
+        if !profile.sec_ch_ua_platform_version.is_empty() {
+            println!(
+                "   Sec-CH-UA-Platform-Version: {}",
+                profile.sec_ch_ua_platform_version
+            );
         }
         println!();
     }
@@ -0,0 +1,227 @@
+use std::time::Instant;
+
+use anyhow::Result;
+use reqwest::header::{self, HeaderMap, HeaderValue};
+use reqwest::StatusCode;
+
+use nab::AcceleratedClient;
+
+use super::fetch::{resolve_browser_name, resolve_cookie_source};
+use crate::OutputFormat;
+
+/// Response metadata gathered without downloading the body.
+#[derive(Debug)]
+pub struct HeadInfo {
+    pub status: StatusCode,
+    pub content_type: Option<String>,
+    pub content_length: Option<u64>,
+    pub last_modified: Option<String>,
+    pub etag: Option<String>,
+    pub final_url: String,
+    /// `true` when the server rejected HEAD and a `Range: bytes=0-0` GET was used
+    pub ranged_get: bool,
+    pub elapsed_ms: f64,
+}
+
+pub async fn cmd_head(url: &str, format: OutputFormat, cookies: &str) -> Result<()> {
+    let client = AcceleratedClient::new()?;
+    let profile = client.profile().await;
+
+    let mut headers = profile.to_headers();
+    let domain = url::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(std::string::ToString::to_string))
+        .unwrap_or_default();
+    if let Some(browser) = resolve_browser_name(cookies) {
+        let cookie_header = resolve_cookie_source(&browser)
+            .get_cookie_header(&domain)
+            .unwrap_or_default();
+        if let Ok(value) = HeaderValue::from_str(&cookie_header) {
+            if !cookie_header.is_empty() {
+                headers.insert(header::COOKIE, value);
+            }
+        }
+    }
+
+    let info = fetch_head_info(client.inner(), url, headers).await?;
+
+    match format {
+        OutputFormat::Compact => {
+            println!(
+                "{} {} {} {:.0}ms {}",
+                info.status.as_u16(),
+                info.content_type.as_deref().unwrap_or("-"),
+                info.content_length
+                    .map_or_else(|| "-".to_string(), |len| format!("{len}B")),
+                info.elapsed_ms,
+                info.final_url
+            );
+        }
+        OutputFormat::Json => {
+            let output = serde_json::json!({
+                "url": url,
+                "final_url": info.final_url,
+                "status": info.status.as_u16(),
+                "content_type": info.content_type,
+                "content_length": info.content_length,
+                "last_modified": info.last_modified,
+                "etag": info.etag,
+                "method": if info.ranged_get { "GET (range)" } else { "HEAD" },
+                "elapsed_ms": info.elapsed_ms,
+            });
+            println!("{}", serde_json::to_string(&output)?);
+        }
+        OutputFormat::Full => {
+            println!("🔎 HEAD: {url}");
+            if info.ranged_get {
+                println!("   (server rejected HEAD, used ranged GET)");
+            }
+            println!("\n📊 Metadata:");
+            println!("   Status: {}", info.status);
+            println!(
+                "   Content-Type: {}",
+                info.content_type.as_deref().unwrap_or("(none)")
+            );
+            match info.content_length {
+                Some(len) => println!("   Content-Length: {len} bytes"),
+                None => println!("   Content-Length: (unknown)"),
+            }
+            println!(
+                "   Last-Modified: {}",
+                info.last_modified.as_deref().unwrap_or("(none)")
+            );
+            println!("   ETag: {}", info.etag.as_deref().unwrap_or("(none)"));
+            println!("   Final URL: {}", info.final_url);
+            println!("   Time: {:.2}ms", info.elapsed_ms);
+        }
+    }
+
+    Ok(())
+}
+
+/// Issue a HEAD request, falling back to a single-byte ranged GET when the
+/// server rejects HEAD (405/501). The body is never read.
+pub async fn fetch_head_info(
+    client: &reqwest::Client,
+    url: &str,
+    mut headers: HeaderMap,
+) -> Result<HeadInfo> {
+    // Ask for the raw representation so Content-Length survives decompression
+    headers.insert(
+        header::ACCEPT_ENCODING,
+        HeaderValue::from_static("identity"),
+    );
+
+    let start = Instant::now();
+    let mut response = client.head(url).headers(headers.clone()).send().await?;
+    let mut ranged_get = false;
+
+    if matches!(
+        response.status(),
+        StatusCode::METHOD_NOT_ALLOWED | StatusCode::NOT_IMPLEMENTED
+    ) {
+        headers.insert(header::RANGE, HeaderValue::from_static("bytes=0-0"));
+        response = client.get(url).headers(headers).send().await?;
+        ranged_get = true;
+    }
+
+    let header_str = |name: header::HeaderName| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from)
+    };
+
+    // A 206 carries the full size in `Content-Range: bytes 0-0/<total>`
+    let content_length = if response.status() == StatusCode::PARTIAL_CONTENT {
+        header_str(header::CONTENT_RANGE)
+            .and_then(|range| range.rsplit('/').next().and_then(|t| t.parse().ok()))
+    } else {
+        header_str(header::CONTENT_LENGTH).and_then(|len| len.parse().ok())
+    };
+
+    let status = if ranged_get && response.status() == StatusCode::PARTIAL_CONTENT {
+        StatusCode::OK
+    } else {
+        response.status()
+    };
+
+    Ok(HeadInfo {
+        status,
+        content_type: header_str(header::CONTENT_TYPE),
+        content_length,
+        last_modified: header_str(header::LAST_MODIFIED),
+        etag: header_str(header::ETAG),
+        final_url: response.url().to_string(),
+        ranged_get,
+        elapsed_ms: (start.elapsed().as_secs_f64() * 1000.0 * 10.0).round() / 10.0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::test_support::{serve, Response};
+
+    /// Serve `/file` (HEAD or GET) and `/old` redirecting to it. With
+    /// `reject_head`, HEAD gets a 405 and GET honours `Range: bytes=0-0`.
+    fn mock_file_server(reject_head: bool) -> String {
+        let addr = serve(move |request| {
+            let ranged = request.header("range") == Some("bytes=0-0");
+            match (request.method.as_str(), request.target.as_str()) {
+                (_, "/old") => Response::status(301).header("Location", "/file"),
+                ("HEAD", _) if reject_head => Response::status(405),
+                ("HEAD", _) => Response::status(200)
+                    .header("Content-Type", "application/pdf")
+                    .header("Content-Length", "5000")
+                    .header("Last-Modified", "Tue, 01 Apr 2025 10:00:00 GMT")
+                    .header("ETag", "\"v1\""),
+                ("GET", _) if ranged => Response::status(206)
+                    .header("Content-Type", "application/pdf")
+                    .header("Content-Range", "bytes 0-0/5000")
+                    .header("ETag", "\"v1\"")
+                    .body("%"),
+                _ => Response::status(500),
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn head_reports_metadata_and_final_url() {
+        let base = mock_file_server(false);
+        let client = reqwest::Client::new();
+
+        let info = fetch_head_info(&client, &format!("{base}/old"), HeaderMap::new())
+            .await
+            .unwrap();
+
+        assert_eq!(info.status, StatusCode::OK);
+        assert!(!info.ranged_get);
+        assert_eq!(info.content_type.as_deref(), Some("application/pdf"));
+        assert_eq!(info.content_length, Some(5000));
+        assert_eq!(
+            info.last_modified.as_deref(),
+            Some("Tue, 01 Apr 2025 10:00:00 GMT")
+        );
+        assert_eq!(info.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(info.final_url, format!("{base}/file"));
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_ranged_get_on_405() {
+        let base = mock_file_server(true);
+        let client = reqwest::Client::new();
+
+        let info = fetch_head_info(&client, &format!("{base}/file"), HeaderMap::new())
+            .await
+            .unwrap();
+
+        assert!(info.ranged_get);
+        assert_eq!(info.status, StatusCode::OK);
+        assert_eq!(info.content_length, Some(5000));
+        assert_eq!(info.content_type.as_deref(), Some("application/pdf"));
+        assert_eq!(info.etag.as_deref(), Some("\"v1\""));
+    }
+}
@@ -14,7 +14,7 @@ pub async fn cmd_login(
     _show_headers: bool,
     format: OutputFormat,
 ) -> Result<()> {
-    use nab::LoginFlow;
+    use nab::{LoginFlow, LoginOutcome};
 
     if !use_1password {
         anyhow::bail!("Login requires 1Password integration. Use --1password flag.");
@@ -35,14 +35,19 @@ pub async fn cmd_login(
 
     let result = login_flow.login(url).await?;
 
-    if save_session {
-        login_flow.save_session(url, save_session)?;
-        println!("✅ Session saved");
-    }
-
-    println!("\n✅ Login successful!");
+    let icon = match result.outcome {
+        LoginOutcome::Success => "✅",
+        LoginOutcome::MfaRequired => "🔑",
+        LoginOutcome::Failed(_) => "❌",
+    };
+    println!("\n{icon} {}", result.message);
     println!("   Final URL: {}", result.final_url);
 
+    if save_session && result.success {
+        let path = login_flow.save_session(url)?;
+        println!("✅ Session saved to {}", path.display());
+    }
+
     if matches!(format, OutputFormat::Full) {
         println!("\n📄 Final page content:");
     }
@@ -57,6 +62,9 @@ pub async fn cmd_login(
 
     output_body(&conversion.markdown, None, true, false, 0, false)?;
 
+    if let LoginOutcome::Failed(_) = result.outcome {
+        anyhow::bail!(result.message);
+    }
     Ok(())
 }
 
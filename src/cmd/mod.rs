@@ -2,27 +2,40 @@ pub mod analyze;
 pub mod annotate;
 pub mod auth;
 pub mod bench;
+pub mod convert;
 pub mod cookies;
 pub mod fetch;
 pub mod fingerprint;
+pub mod head;
 pub mod login;
 pub mod otp;
 pub mod output;
+pub mod paginate;
 pub mod spa;
+pub mod sse;
 pub mod stream;
 pub mod submit;
 pub mod validate;
 
-pub use analyze::cmd_analyze;
+#[cfg(test)]
+#[path = "../test_support.rs"]
+mod test_support;
+#[cfg(test)]
+use nab::AcceleratedClient;
+
+pub use analyze::{cmd_analyze, AnalyzeOptions};
 pub use annotate::cmd_annotate;
 pub use auth::cmd_auth;
 pub use bench::cmd_bench;
+pub use convert::cmd_convert;
 pub use cookies::cmd_cookies;
-pub use fetch::cmd_fetch;
+pub use fetch::{cmd_fetch, FetchOptions};
 pub use fingerprint::cmd_fingerprint;
+pub use head::cmd_head;
 pub use login::cmd_login;
 pub use otp::cmd_otp;
 pub use spa::cmd_spa;
+pub use sse::cmd_sse;
 pub use stream::cmd_stream;
 pub use submit::cmd_submit;
 pub use validate::cmd_validate;
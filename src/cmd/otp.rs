@@ -1,8 +1,12 @@
+use std::time::Duration;
+
 use anyhow::Result;
 
 use nab::OtpRetriever;
 
-pub fn cmd_otp(domain: &str) -> Result<()> {
+/// `wait` is the timeout and poll interval for a code that is yet to arrive
+pub fn cmd_otp(domain: &str, wait: Option<(Duration, Duration)>) -> Result<()> {
+    let started_at = chrono::Utc::now();
     println!("🔐 Searching for OTP codes for: {domain}\n");
 
     // Extract domain from URL if needed
@@ -11,7 +15,20 @@ pub fn cmd_otp(domain: &str) -> Result<()> {
         .and_then(|u| u.host_str().map(std::string::ToString::to_string))
         .unwrap_or_else(|| domain.to_string());
 
-    if let Some(otp) = OtpRetriever::get_otp_for_domain(&clean_domain)? {
+    let otp = match wait {
+        Some((timeout, poll_interval)) => {
+            println!("⏳ Waiting up to {}s for a new code...", timeout.as_secs());
+            OtpRetriever::get_otp_for_domain_wait(
+                &clean_domain,
+                started_at,
+                timeout,
+                poll_interval,
+            )?
+        }
+        None => OtpRetriever::get_otp_for_domain(&clean_domain)?,
+    };
+
+    if let Some(otp) = otp {
         println!("✅ Found OTP code!");
         println!("   Code: {}", otp.code);
         println!("   Source: {}", otp.source);
@@ -1,10 +1,9 @@
-use std::collections::HashSet;
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
 
 use anyhow::Result;
-use scraper::{Html, Selector};
+use nab::content::links::extract_links;
 
 use crate::OutputFormat;
 
@@ -58,35 +57,6 @@ pub fn output_body(
     Ok(())
 }
 
-pub fn extract_links(html: &str) -> Vec<(String, String)> {
-    let document = Html::parse_document(html);
-    let selector = Selector::parse("a[href]").unwrap();
-
-    let mut links = Vec::new();
-    let mut seen = HashSet::new();
-
-    for element in document.select(&selector) {
-        if let Some(href) = element.value().attr("href") {
-            // Skip anchors, javascript, and duplicates
-            if href.starts_with('#') || href.starts_with("javascript:") || seen.contains(href) {
-                continue;
-            }
-            seen.insert(href.to_string());
-
-            let text = element
-                .text()
-                .collect::<Vec<_>>()
-                .join(" ")
-                .trim()
-                .to_string();
-
-            links.push((text, href.to_string()));
-        }
-    }
-
-    links
-}
-
 pub fn truncate_text(text: &str, max: usize) -> String {
     if text.len() <= max {
         text.to_string()
@@ -0,0 +1,206 @@
+use std::collections::HashSet;
+use std::time::Instant;
+
+use anyhow::Result;
+use reqwest::header::HeaderMap;
+use scraper::{Html, Selector};
+
+use nab::prefetch::EarlyHints;
+use nab::AcceleratedClient;
+
+/// A single fetched page of a paginated listing.
+pub struct Page {
+    pub url: String,
+    pub status: u16,
+    pub body: String,
+    pub elapsed_ms: f64,
+}
+
+/// Fetch `start_url` and follow `rel=next` links until `max_pages` pages have
+/// been collected, the chain ends, or a previously visited URL comes up again.
+///
+/// Each page goes through [`AcceleratedClient::execute`] and its retry policy.
+pub async fn fetch_pages(
+    client: &AcceleratedClient,
+    start_url: &str,
+    max_pages: usize,
+    headers: &HeaderMap,
+    markdown: bool,
+) -> Result<Vec<Page>> {
+    let mut pages = Vec::new();
+    let mut visited = HashSet::new();
+    let mut next = Some(start_url.to_string());
+
+    while let Some(url) = next.take() {
+        if pages.len() >= max_pages || !visited.insert(url.clone()) {
+            break;
+        }
+
+        let start = Instant::now();
+        let request = client.inner().get(&url).headers(headers.clone()).build()?;
+        let response = client.execute(request).await?;
+        let status = response.status().as_u16();
+        let final_url = response.url().clone();
+
+        let link_headers: Vec<String> = response
+            .headers()
+            .get_all("link")
+            .iter()
+            .filter_map(|v| v.to_str().ok().map(String::from))
+            .collect();
+        let content_type = response
+            .headers()
+            .get("content-type")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("text/html")
+            .to_string();
+
+        let body_bytes = response.bytes().await?;
+        let raw_text = String::from_utf8_lossy(&body_bytes).to_string();
+
+        next = next_page_url(&link_headers, &raw_text, &final_url);
+
+        let body = if markdown {
+            nab::content::ContentRouter::new()
+                .convert(&body_bytes, &content_type)
+                .map_or_else(|_| raw_text.clone(), |r| r.markdown)
+        } else {
+            raw_text
+        };
+
+        pages.push(Page {
+            url,
+            status,
+            body,
+            elapsed_ms: (start.elapsed().as_secs_f64() * 1000.0 * 10.0).round() / 10.0,
+        });
+    }
+
+    Ok(pages)
+}
+
+/// Join page bodies in order, separated by a marker naming each page's URL.
+pub fn join_pages(pages: &[Page]) -> String {
+    pages
+        .iter()
+        .enumerate()
+        .map(|(i, page)| format!("<!-- page {}: {} -->\n\n{}", i + 1, page.url, page.body))
+        .collect::<Vec<_>>()
+        .join("\n\n---\n\n")
+}
+
+/// Find the next page URL from `Link` headers, falling back to HTML
+/// `<a rel="next">` / `<link rel="next">` elements in the body.
+fn next_page_url(link_headers: &[String], html: &str, base: &url::Url) -> Option<String> {
+    let from_header = link_headers
+        .iter()
+        .flat_map(|header| split_link_header(header))
+        .collect::<Vec<_>>();
+    let hints = EarlyHints::parse(&from_header);
+
+    let href = hints
+        .links
+        .iter()
+        .find(|link| has_next_rel(&link.rel))
+        .map(|link| link.url.clone())
+        .or_else(|| {
+            let doc = Html::parse_document(html);
+            let sel = Selector::parse("a[rel][href], link[rel][href]").ok()?;
+            doc.select(&sel)
+                .find(|el| el.value().attr("rel").is_some_and(has_next_rel))
+                .and_then(|el| el.value().attr("href"))
+                .map(String::from)
+        })?;
+
+    base.join(&href).ok().map(String::from)
+}
+
+/// Split a `Link` header carrying several comma-separated entries.
+fn split_link_header(header: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut in_url = false;
+
+    for (i, c) in header.char_indices() {
+        match c {
+            '<' => in_url = true,
+            '>' => in_url = false,
+            ',' if !in_url => {
+                parts.push(header[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(header[start..].trim());
+    parts.retain(|p| !p.is_empty());
+    parts
+}
+
+fn has_next_rel(rel: &str) -> bool {
+    rel.split_whitespace()
+        .any(|r| r.eq_ignore_ascii_case("next"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::test_support::{serve, Response};
+
+    /// Serve `/1` → `/2` → `/3` linked by `Link: rel=next`; `/3` links back to `/1`.
+    fn mock_paginated_api() -> String {
+        let addr = serve(|request| {
+            let page: u8 = request.target.trim_start_matches('/').parse().unwrap_or(1);
+            let next = if page == 3 { 1 } else { page + 1 };
+            Response::typed("text/plain", format!("page {page} content")).header(
+                "Link",
+                format!("</{next}>; rel=\"next\", </3>; rel=\"last\""),
+            )
+        });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn follows_link_header_in_order_and_stops_on_loop() {
+        let base = mock_paginated_api();
+        let client = AcceleratedClient::from_client(reqwest::Client::new()).unwrap();
+
+        let pages = fetch_pages(&client, &format!("{base}/1"), 10, &HeaderMap::new(), true)
+            .await
+            .unwrap();
+
+        let bodies: Vec<&str> = pages.iter().map(|p| p.body.as_str()).collect();
+        assert_eq!(
+            bodies,
+            ["page 1 content", "page 2 content", "page 3 content"]
+        );
+
+        let joined = join_pages(&pages);
+        let first = joined.find("page 1 content").unwrap();
+        let third = joined.find("page 3 content").unwrap();
+        assert!(first < third);
+    }
+
+    #[tokio::test]
+    async fn respects_page_cap() {
+        let base = mock_paginated_api();
+        let client = AcceleratedClient::from_client(reqwest::Client::new()).unwrap();
+
+        let pages = fetch_pages(&client, &format!("{base}/1"), 2, &HeaderMap::new(), true)
+            .await
+            .unwrap();
+
+        assert_eq!(pages.len(), 2);
+        assert!(pages[1].url.ends_with("/2"));
+    }
+
+    #[test]
+    fn next_page_from_html_rel() {
+        let base = url::Url::parse("https://example.com/list?page=1").unwrap();
+        let html = r#"<a href="/about">About</a><a rel="next" href="?page=2">Next</a>"#;
+        assert_eq!(
+            next_page_url(&[], html, &base).as_deref(),
+            Some("https://example.com/list?page=2")
+        );
+    }
+}
@@ -2,12 +2,19 @@ use std::time::Instant;
 
 use anyhow::Result;
 use scraper::{Html, Selector};
+use tracing::{field, instrument};
 
-use nab::{inject_fetch_sync, AcceleratedClient, ApiDiscovery, FetchClient, JsEngine};
+use nab::spa::{extract_json_islands, transform_json, try_extract_script_json};
+use nab::{inject_fetch_sync, AcceleratedClient, ApiDiscovery, FetchClient, JsEngine, RequestDest};
 
-use super::fetch::{resolve_browser_name, resolve_cookie_source};
+use super::fetch::{resolve_browser_name, resolve_cookie_source, with_user_agent};
 
 #[allow(clippy::too_many_arguments)]
+#[instrument(
+    name = "spa",
+    skip_all,
+    fields(request_id = %uuid::Uuid::new_v4(), url = %url, status = field::Empty)
+)]
 pub async fn cmd_spa(
     url: &str,
     cookies: &str,
@@ -22,8 +29,14 @@ pub async fn cmd_spa(
     max_array: Option<usize>,
     max_depth: Option<usize>,
     _http1: bool,
+    openapi: bool,
+    graphql_query: Option<&str>,
+    user_agent: Option<&str>,
 ) -> Result<()> {
-    let client = AcceleratedClient::new()?;
+    let client = match user_agent {
+        Some(ua) => AcceleratedClient::with_profile(with_user_agent(nab::random_profile(), ua)?)?,
+        None => AcceleratedClient::new()?,
+    };
 
     // Extract domain from URL
     let domain = url::Url::parse(url)
@@ -49,18 +62,33 @@ pub async fn cmd_spa(
     let response = if cookie_header.is_empty() {
         client.fetch(url).await?
     } else {
-        client
+        let request = client
             .inner()
             .get(url)
             .header("Cookie", &cookie_header)
             .headers(profile.to_headers())
-            .send()
-            .await?
+            .build()?;
+        client.execute(request).await?
     };
+    tracing::Span::current().record("status", response.status().as_u16());
 
     let html = response.text().await?;
     let elapsed = start.elapsed();
 
+    if openapi {
+        let endpoints = ApiDiscovery::new()?.discover_from_html(&html);
+        let mut doc = ApiDiscovery::to_openapi(&endpoints);
+        if doc.get("servers").is_none() {
+            if let Ok(page) = url::Url::parse(url) {
+                doc["servers"] =
+                    serde_json::json!([{ "url": page.origin().unicode_serialization() }]);
+            }
+        }
+        doc["info"]["title"] = serde_json::json!(format!("{domain} (discovered)"));
+        println!("{}", serde_json::to_string_pretty(&doc)?);
+        return Ok(());
+    }
+
     println!("🕸️  Extracting SPA data from: {url}");
 
     // Look for common SPA data patterns
@@ -90,8 +118,42 @@ pub async fn cmd_spa(
         }
     }
 
+    // Run the user's query against the first discovered GraphQL endpoint
+    if let Some(query) = graphql_query {
+        let graphql_url = discovered_endpoints
+            .iter()
+            .filter(|e| e.url.contains("graphql"))
+            .find_map(|e| resolve_endpoint_url(url, &e.url));
+
+        match graphql_url {
+            Some(endpoint_url) => {
+                let headers = api_headers(&profile, url, &endpoint_url, "POST", &cookie_header);
+                if show_console {
+                    println!("🌐 POST GraphQL query to: {endpoint_url}");
+                }
+                let data = post_graphql(&client, &endpoint_url, query, headers).await?;
+                println!(
+                    "\n📊 Extraction complete in {:.2}ms",
+                    elapsed.as_secs_f64() * 1000.0
+                );
+                println!("\n✅ GraphQL endpoint {endpoint_url} returned data:");
+                output_spa_data(
+                    &data,
+                    output,
+                    extract_path,
+                    summary,
+                    minify,
+                    max_array,
+                    max_depth,
+                )?;
+                found_data = true;
+            }
+            None => println!("⚠️  --graphql-query given but no GraphQL endpoint was discovered"),
+        }
+    }
+
     // Try fetching discovered endpoints (only GET requests for now)
-    if !discovered_endpoints.is_empty() {
+    if !found_data && !discovered_endpoints.is_empty() {
         let mut sorted_endpoints = discovered_endpoints.clone();
         sorted_endpoints.sort_by_key(|e| -ApiDiscovery::score_endpoint(e));
 
@@ -100,45 +162,16 @@ pub async fn cmd_spa(
                 continue;
             }
 
-            let endpoint_url =
-                if endpoint.url.starts_with("http://") || endpoint.url.starts_with("https://") {
-                    endpoint.url.clone()
-                } else if endpoint.url.starts_with('/') {
-                    url::Url::parse(url).ok().map_or_else(
-                        || endpoint.url.clone(),
-                        |u| format!("{}{}", u.origin().unicode_serialization(), endpoint.url),
-                    )
-                } else {
-                    continue;
-                };
+            let Some(endpoint_url) = resolve_endpoint_url(url, &endpoint.url) else {
+                continue;
+            };
 
             if show_console {
                 println!("🌐 Trying endpoint: {endpoint_url}");
             }
 
-            let fetch_result = async {
-                let resp = if cookie_header.is_empty() {
-                    client.fetch(&endpoint_url).await?
-                } else {
-                    client
-                        .inner()
-                        .get(&endpoint_url)
-                        .header("Cookie", &cookie_header)
-                        .headers(profile.to_headers())
-                        .send()
-                        .await?
-                };
-
-                let text = resp.text().await?;
-                let data = serde_json::from_str::<serde_json::Value>(&text)?;
-
-                if data.is_object() || data.is_array() {
-                    Ok(data)
-                } else {
-                    Err(anyhow::anyhow!("Not an object or array"))
-                }
-            }
-            .await;
+            let headers = api_headers(&profile, url, &endpoint_url, "GET", &cookie_header);
+            let fetch_result = fetch_endpoint_json(&client, &endpoint_url, headers).await;
 
             if let Ok(data) = fetch_result {
                 println!(
@@ -163,7 +196,7 @@ pub async fn cmd_spa(
 
     // STEP 1: Try embedded JSON extraction (fast path ~100ms)
     if !found_data {
-        if let Some(data) = extract_script_json(&html, "__NEXT_DATA__") {
+        if let Some(data) = script_json(&html, "__NEXT_DATA__") {
             println!(
                 "\n📊 Extraction complete in {:.2}ms",
                 elapsed.as_secs_f64() * 1000.0
@@ -182,7 +215,7 @@ pub async fn cmd_spa(
         }
     }
 
-    if let Some(data) = extract_script_json(&html, "__INITIAL_STATE__") {
+    if let Some(data) = script_json(&html, "__INITIAL_STATE__") {
         if !found_data {
             println!(
                 "\n📊 Extraction complete in {:.2}ms",
@@ -202,7 +235,7 @@ pub async fn cmd_spa(
         found_data = true;
     }
 
-    if let Some(data) = extract_script_json(&html, "__NUXT__") {
+    if let Some(data) = script_json(&html, "__NUXT__") {
         if !found_data {
             println!(
                 "\n📊 Extraction complete in {:.2}ms",
@@ -222,7 +255,7 @@ pub async fn cmd_spa(
         found_data = true;
     }
 
-    if let Some(data) = extract_script_json(&html, "__PRELOADED_STATE__") {
+    if let Some(data) = script_json(&html, "__PRELOADED_STATE__") {
         if !found_data {
             println!(
                 "\n📊 Extraction complete in {:.2}ms",
@@ -242,6 +275,30 @@ pub async fn cmd_spa(
         found_data = true;
     }
 
+    // Generic <script type="application/json"> islands (Remix, SvelteKit, JSON-LD, ...)
+    for island in extract_json_islands(&html) {
+        if NAMED_GLOBALS.contains(&island.label.as_str()) {
+            continue;
+        }
+        if !found_data {
+            println!(
+                "\n📊 Extraction complete in {:.2}ms",
+                elapsed.as_secs_f64() * 1000.0
+            );
+        }
+        println!("\n✅ {} data island `{}` found:", island.kind, island.label);
+        output_spa_data(
+            &island.data,
+            output,
+            extract_path,
+            summary,
+            minify,
+            max_array,
+            max_depth,
+        )?;
+        found_data = true;
+    }
+
     if !found_data {
         println!("\n⚙️  No embedded JSON found, trying JavaScript execution...");
 
@@ -264,7 +321,12 @@ pub async fn cmd_spa(
             } else {
                 Some(base_url.clone())
             },
-        );
+        )
+        .with_page_url(url);
+        let fetch_client = match user_agent {
+            Some(ua) => fetch_client.with_user_agent(ua),
+            None => fetch_client,
+        };
 
         let fetch_client_clone = fetch_client.clone();
         inject_fetch_sync(js_engine.context(), fetch_client_clone)?;
@@ -299,6 +361,13 @@ pub async fn cmd_spa(
             } else {
                 scripts_executed += 1;
             }
+
+            let console_output = js_engine.take_console();
+            if show_console {
+                for entry in console_output {
+                    println!("   [console.{}] {}", entry.level, entry.message);
+                }
+            }
         }
 
         println!("✅ Executed {scripts_executed} inline scripts");
@@ -374,11 +443,11 @@ pub async fn cmd_spa(
             }
         }
 
-        let fetched_urls = fetch_client.get_fetch_log();
-        if !fetched_urls.is_empty() {
-            println!("\n📡 JavaScript made {} fetch() calls:", fetched_urls.len());
-            for (i, url) in fetched_urls.iter().enumerate() {
-                println!("   {}. {}", i + 1, url);
+        let fetch_calls = fetch_client.get_fetch_log();
+        if !fetch_calls.is_empty() {
+            println!("\n📡 JavaScript made {} fetch() calls:", fetch_calls.len());
+            for (i, call) in fetch_calls.iter().enumerate() {
+                println!("   {}. {}", i + 1, call);
             }
         }
 
@@ -396,123 +465,112 @@ pub async fn cmd_spa(
     Ok(())
 }
 
-fn extract_script_json(html: &str, var_name: &str) -> Option<serde_json::Value> {
-    let document = Html::parse_document(html);
-
-    // Try script tag with id
-    let id_selector = Selector::parse(&format!("script#{var_name}")).ok()?;
-    if let Some(script) = document.select(&id_selector).next() {
-        let content = script.text().collect::<String>();
-        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
-            return Some(json);
-        }
-    }
-
-    // Try window.__VAR__ = pattern
-    let pattern = format!("window.{var_name}");
-    if let Some(start_idx) = html.find(&pattern) {
-        let after_eq = html[start_idx..].find('=')? + start_idx + 1;
-        let json_start = html[after_eq..]
-            .chars()
-            .position(|c| c == '{' || c == '[')?
-            + after_eq;
-
-        let json_str = extract_json_object(&html[json_start..])?;
-        if let Ok(json) = serde_json::from_str::<serde_json::Value>(json_str) {
-            return Some(json);
-        }
-    }
-
-    // Try self.__VAR__ pattern (some frameworks)
-    let self_pattern = format!("self.{var_name}");
-    if let Some(start_idx) = html.find(&self_pattern) {
-        let after_eq = html[start_idx..].find('=')? + start_idx + 1;
-        let json_start = html[after_eq..]
-            .chars()
-            .position(|c| c == '{' || c == '[')?
-            + after_eq;
-        let json_str = extract_json_object(&html[json_start..])?;
-        if let Ok(json) = serde_json::from_str::<serde_json::Value>(json_str) {
-            return Some(json);
-        }
+/// Absolute URL for a discovered endpoint; relative paths resolve against the page origin
+fn resolve_endpoint_url(page_url: &str, endpoint: &str) -> Option<String> {
+    if endpoint.starts_with("http://") || endpoint.starts_with("https://") {
+        Some(endpoint.to_string())
+    } else if endpoint.starts_with('/') {
+        Some(url::Url::parse(page_url).ok().map_or_else(
+            || endpoint.to_string(),
+            |u| format!("{}{endpoint}", u.origin().unicode_serialization()),
+        ))
+    } else {
+        None
     }
-
-    None
 }
 
-/// Lightweight SPA data extraction for auto-detection during `nab fetch`.
-#[allow(dead_code)]
-pub fn extract_spa_data_inline(html: &str) -> Option<String> {
-    const SPA_PATTERNS: &[(&str, &str)] = &[
-        ("__NEXT_DATA__", "Next.js"),
-        ("__NUXT__", "Nuxt.js"),
-        ("__INITIAL_STATE__", "Redux/Vuex"),
-        ("__APOLLO_STATE__", "Apollo GraphQL"),
-        ("__RELAY_STORE__", "Relay"),
-        ("__PRELOADED_STATE__", "Redux"),
-    ];
-
-    let mut sections = Vec::new();
-
-    for (var_name, framework) in SPA_PATTERNS {
-        if let Some(data) = extract_script_json(html, var_name) {
-            let json_str = serde_json::to_string_pretty(&data).unwrap_or_default();
-            let truncated = if json_str.len() > 8192 {
-                format!(
-                    "```json\n{}\n... [{} more bytes]\n```",
-                    &json_str[..8192],
-                    json_str.len() - 8192
-                )
-            } else {
-                format!("```json\n{json_str}\n```")
-            };
-            sections.push(format!("### {framework} (`{var_name}`)\n\n{truncated}"));
+/// Headers for an API call the page at `page_url` makes to `endpoint_url`
+///
+/// Looks like the page's own `fetch()`: CORS-mode `Sec-Fetch-*`, plus the
+/// `Referer` and `Origin` a browser would send, so Referer-checking APIs accept it.
+fn api_headers(
+    profile: &nab::BrowserProfile,
+    page_url: &str,
+    endpoint_url: &str,
+    method: &str,
+    cookie_header: &str,
+) -> reqwest::header::HeaderMap {
+    let mut headers = profile.to_subresource_headers(page_url, endpoint_url, RequestDest::Fetch);
+    // Adds `Origin` to same-origin POSTs
+    headers.extend(nab::fingerprint::referrer_headers(
+        page_url,
+        endpoint_url,
+        method,
+    ));
+    if !cookie_header.is_empty() {
+        if let Ok(value) = reqwest::header::HeaderValue::from_str(cookie_header) {
+            headers.insert(reqwest::header::COOKIE, value);
         }
     }
+    headers
+}
 
-    if sections.is_empty() {
-        None
+/// GET a discovered endpoint, accepting only a JSON object or array
+async fn fetch_endpoint_json(
+    client: &AcceleratedClient,
+    endpoint_url: &str,
+    headers: reqwest::header::HeaderMap,
+) -> Result<serde_json::Value> {
+    let request = client.inner().get(endpoint_url).headers(headers).build()?;
+    let text = client.execute(request).await?.text().await?;
+    let data = serde_json::from_str::<serde_json::Value>(&text)?;
+
+    if data.is_object() || data.is_array() {
+        Ok(data)
     } else {
-        Some(sections.join("\n\n"))
+        Err(anyhow::anyhow!("Not an object or array"))
     }
 }
 
-fn extract_json_object(s: &str) -> Option<&str> {
-    let first_char = s.chars().next()?;
-    let (open, close) = match first_char {
-        '{' => ('{', '}'),
-        '[' => ('[', ']'),
-        _ => return None,
-    };
-
-    let mut depth = 0;
-    let mut in_string = false;
-    let mut escape_next = false;
-
-    for (i, c) in s.char_indices() {
-        if escape_next {
-            escape_next = false;
-            continue;
-        }
-
-        match c {
-            '\\' if in_string => escape_next = true,
-            '"' => in_string = !in_string,
-            _ if in_string => {}
-            c if c == open => depth += 1,
-            c if c == close => {
-                depth -= 1;
-                if depth == 0 {
-                    return Some(&s[..=i]);
-                }
-            }
-            _ => {}
+/// POST `{"query": ...}` to a GraphQL endpoint and return the JSON response.
+/// Fails when the response carries `errors` and no `data`.
+async fn post_graphql(
+    client: &AcceleratedClient,
+    endpoint_url: &str,
+    query: &str,
+    mut headers: reqwest::header::HeaderMap,
+) -> Result<serde_json::Value> {
+    // Replaces the `fetch()` Accept rather than sending both
+    headers.insert(
+        reqwest::header::ACCEPT,
+        reqwest::header::HeaderValue::from_static("application/json"),
+    );
+    let request = client
+        .inner()
+        .post(endpoint_url)
+        .headers(headers)
+        .json(&serde_json::json!({ "query": query }))
+        .build()?;
+    let response = client.execute(request).await?;
+    let status = response.status();
+    let data: serde_json::Value = response.json().await.map_err(|e| {
+        anyhow::anyhow!("GraphQL endpoint returned non-JSON response (HTTP {status}): {e}")
+    })?;
+
+    if data.get("data").is_none_or(serde_json::Value::is_null) {
+        if let Some(errors) = data.get("errors") {
+            anyhow::bail!("GraphQL query failed (HTTP {status}): {errors}");
         }
     }
+    Ok(data)
+}
 
-    None
+/// Named SPA global; a payload that can't be parsed is reported, not skipped
+fn script_json(html: &str, var_name: &str) -> Option<serde_json::Value> {
+    try_extract_script_json(html, var_name)
+        .inspect_err(|e| eprintln!("⚠️  {e}"))
+        .ok()
+        .flatten()
 }
 
+/// Globals handled by name above; islands with these ids are not repeated
+const NAMED_GLOBALS: &[&str] = &[
+    "__NEXT_DATA__",
+    "__INITIAL_STATE__",
+    "__NUXT__",
+    "__PRELOADED_STATE__",
+];
+
 fn output_spa_data(
     data: &serde_json::Value,
     output: &str,
@@ -523,12 +581,9 @@ fn output_spa_data(
     max_depth: Option<usize>,
 ) -> Result<()> {
     let target = if let Some(path) = extract_path {
-        let parts: Vec<&str> = path.split('.').collect();
-        let mut current = data;
-        for part in parts {
-            current = current.get(part).unwrap_or(&serde_json::Value::Null);
-        }
-        current.clone()
+        nab::json_path::select(data, path)
+            .cloned()
+            .unwrap_or(serde_json::Value::Null)
     } else {
         data.clone()
     };
@@ -560,50 +615,6 @@ fn output_spa_data(
     Ok(())
 }
 
-fn transform_json(
-    value: &serde_json::Value,
-    max_array: usize,
-    max_depth: usize,
-    depth: usize,
-) -> serde_json::Value {
-    if depth >= max_depth {
-        return serde_json::Value::String("[depth limit]".to_string());
-    }
-
-    match value {
-        serde_json::Value::Array(arr) => {
-            let limited: Vec<serde_json::Value> = arr
-                .iter()
-                .take(max_array)
-                .map(|v| transform_json(v, max_array, max_depth, depth + 1))
-                .collect();
-            if arr.len() > max_array {
-                let mut result = limited;
-                result.push(serde_json::Value::String(format!(
-                    "... +{} more",
-                    arr.len() - max_array
-                )));
-                serde_json::Value::Array(result)
-            } else {
-                serde_json::Value::Array(limited)
-            }
-        }
-        serde_json::Value::Object(obj) => {
-            let transformed: serde_json::Map<String, serde_json::Value> = obj
-                .iter()
-                .map(|(k, v)| {
-                    (
-                        k.clone(),
-                        transform_json(v, max_array, max_depth, depth + 1),
-                    )
-                })
-                .collect();
-            serde_json::Value::Object(transformed)
-        }
-        _ => value.clone(),
-    }
-}
-
 fn print_structure(value: &serde_json::Value, max_depth: usize, depth: usize) {
     let indent = "  ".repeat(depth);
 
@@ -643,3 +654,112 @@ fn print_structure(value: &serde_json::Value, max_depth: usize, depth: usize) {
         _ => {}
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::cmd::test_support::{serve_once, Request, Response};
+    use std::thread::JoinHandle;
+
+    /// Answer one request with `body` as JSON; the handle yields the request
+    fn mock_graphql(body: &'static str) -> (String, JoinHandle<Request>) {
+        let (addr, server) = serve_once(move |_| Response::typed("application/json", body));
+        (format!("http://{addr}/graphql"), server)
+    }
+
+    #[tokio::test]
+    async fn discovered_endpoint_request_carries_page_referer() {
+        let (endpoint, server) = mock_graphql(r#"{"orders":[1,2]}"#);
+        let page = endpoint.replace("/graphql", "/account/orders?tab=open");
+        let profile = tokio::task::spawn_blocking(nab::chrome_profile)
+            .await
+            .unwrap();
+
+        let headers = api_headers(&profile, &page, &endpoint, "GET", "session=abc");
+        let client =
+            AcceleratedClient::from_client_with_profile(reqwest::Client::new(), profile.clone())
+                .unwrap();
+        let data = fetch_endpoint_json(&client, &endpoint, headers)
+            .await
+            .unwrap();
+        assert_eq!(data["orders"][1], 2);
+
+        let request = server.join().unwrap();
+        assert_eq!(request.header("referer"), Some(page.as_str()));
+        assert_eq!(request.header("sec-fetch-mode"), Some("cors"));
+        assert_eq!(
+            request.header("accept"),
+            Some(profile.accept_for(RequestDest::Fetch))
+        );
+        assert_eq!(request.header("cookie"), Some("session=abc"));
+        // Same-origin GET: browsers send no Origin
+        assert_eq!(request.header("origin"), None);
+    }
+
+    #[tokio::test]
+    async fn posts_graphql_query_and_outputs_response() {
+        let (endpoint, server) = mock_graphql(r#"{"data":{"viewer":{"login":"ada"}}}"#);
+        let query = "query { viewer { login } }";
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            reqwest::header::COOKIE,
+            reqwest::header::HeaderValue::from_static("session=abc"),
+        );
+
+        let client = AcceleratedClient::from_client(reqwest::Client::new()).unwrap();
+        let data = post_graphql(&client, &endpoint, query, headers)
+            .await
+            .unwrap();
+
+        let request = server.join().unwrap();
+        assert_eq!(
+            (request.method.as_str(), request.target.as_str()),
+            ("POST", "/graphql")
+        );
+        assert_eq!(request.header("cookie"), Some("session=abc"));
+        let sent: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(sent["query"], query);
+
+        assert_eq!(data["data"]["viewer"]["login"], "ada");
+        output_spa_data(
+            &data,
+            "json",
+            Some("data.viewer.login"),
+            false,
+            true,
+            None,
+            None,
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn graphql_errors_without_data_fail() {
+        let (endpoint, _requests) =
+            mock_graphql(r#"{"errors":[{"message":"Cannot query field \"nope\""}]}"#);
+
+        let err = post_graphql(
+            &AcceleratedClient::from_client(reqwest::Client::new()).unwrap(),
+            &endpoint,
+            "{ nope }",
+            reqwest::header::HeaderMap::new(),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("Cannot query field"));
+    }
+
+    #[test]
+    fn resolves_relative_endpoints_against_page_origin() {
+        assert_eq!(
+            resolve_endpoint_url("https://shop.test/a/b?c=1", "/graphql").as_deref(),
+            Some("https://shop.test/graphql")
+        );
+        assert_eq!(
+            resolve_endpoint_url("https://shop.test/", "https://api.shop.test/graphql").as_deref(),
+            Some("https://api.shop.test/graphql")
+        );
+        assert_eq!(resolve_endpoint_url("https://shop.test/", "graphql"), None);
+    }
+}
@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::time::Duration;
+
+use anyhow::Result;
+use futures::StreamExt;
+
+use nab::{AcceleratedClient, SseEvent};
+
+use super::fetch::{resolve_browser_name, resolve_cookie_source};
+use crate::OutputFormat;
+
+/// Reconnection delay until the server sends a `retry:` hint
+const DEFAULT_RETRY: Duration = Duration::from_secs(3);
+/// Consecutive failed reconnects before giving up
+const MAX_RECONNECT_FAILURES: u32 = 8;
+/// Longest wait between reconnect attempts
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+pub async fn cmd_sse(
+    url: &str,
+    format: OutputFormat,
+    cookies: &str,
+    last_event_id: Option<String>,
+    max_events: Option<usize>,
+) -> Result<()> {
+    let client = AcceleratedClient::new()?;
+
+    let mut headers = HashMap::new();
+    if let Some(browser) = resolve_browser_name(cookies) {
+        let domain = url::Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(std::string::ToString::to_string))
+            .unwrap_or_default();
+        let cookie_header = resolve_cookie_source(&browser)
+            .get_cookie_header(&domain)
+            .unwrap_or_default();
+        if !cookie_header.is_empty() {
+            headers.insert("Cookie".to_string(), cookie_header);
+        }
+    }
+
+    if matches!(format, OutputFormat::Full) {
+        println!("📡 Listening: {url}");
+    }
+    let mut stdout = std::io::stdout();
+    let consumed = consume_events(&client, url, headers, last_event_id, max_events, |event| {
+        print_event(&mut stdout, event, format)
+    });
+
+    tokio::select! {
+        result = consumed => {
+            let count = result?;
+            if matches!(format, OutputFormat::Full) {
+                println!("\n✅ Received {count} events");
+            }
+        }
+        _ = tokio::signal::ctrl_c() => {
+            eprintln!("\n⏹️  Interrupted");
+        }
+    }
+    Ok(())
+}
+
+/// Read events until `max_events` have been handled
+///
+/// When the server closes the stream, reconnects after its `retry:` delay and
+/// resumes with `Last-Event-ID`. A reconnect that fails on the network or with
+/// a server error is retried with a doubling delay; a client error such as
+/// `404` ends the stream, as does the first connection failing. Without
+/// `max_events` this only returns on error.
+async fn consume_events(
+    client: &AcceleratedClient,
+    url: &str,
+    mut headers: HashMap<String, String>,
+    mut last_event_id: Option<String>,
+    max_events: Option<usize>,
+    mut on_event: impl FnMut(&SseEvent) -> Result<()>,
+) -> Result<usize> {
+    let mut retry = DEFAULT_RETRY;
+    let mut count = 0;
+    let mut connected = false;
+    let mut failures = 0;
+
+    loop {
+        if let Some(id) = &last_event_id {
+            headers.insert("Last-Event-ID".to_string(), id.clone());
+        }
+        let events = match client.stream_events_with(url, &headers).await {
+            Ok(events) => events,
+            Err(e) if connected && failures < MAX_RECONNECT_FAILURES && reconnectable(&e) => {
+                failures += 1;
+                let delay = reconnect_delay(retry, failures);
+                tracing::warn!(error = %format!("{e:#}"), failures, ?delay, "Reconnect failed, retrying");
+                tokio::time::sleep(delay).await;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        connected = true;
+        failures = 0;
+        let mut events = std::pin::pin!(events);
+
+        while let Some(event) = events.next().await {
+            if let Some(ms) = event.retry {
+                retry = Duration::from_millis(ms);
+            }
+            if event.id.is_some() {
+                last_event_id.clone_from(&event.id);
+            }
+            on_event(&event)?;
+            count += 1;
+            if max_events.is_some_and(|max| count >= max) {
+                return Ok(count);
+            }
+        }
+
+        tracing::info!(?retry, ?last_event_id, "Event stream closed, reconnecting");
+        tokio::time::sleep(retry).await;
+    }
+}
+
+/// Whether a failed reconnect is worth retrying: network errors and server
+/// overload, not client errors, which the SSE spec treats as final
+fn reconnectable(err: &anyhow::Error) -> bool {
+    nab::retry::is_transient(err)
+        || err.chain().any(|cause| {
+            cause
+                .downcast_ref::<reqwest::Error>()
+                .and_then(reqwest::Error::status)
+                .is_some_and(|s| s.is_server_error() || s == reqwest::StatusCode::TOO_MANY_REQUESTS)
+        })
+}
+
+/// Wait before the reconnect following `failures` failed ones: the server's
+/// retry delay, doubled per failure, capped at [`MAX_RECONNECT_DELAY`]
+fn reconnect_delay(retry: Duration, failures: u32) -> Duration {
+    retry
+        .saturating_mul(2_u32.saturating_pow(failures))
+        .min(MAX_RECONNECT_DELAY)
+}
+
+fn print_event(out: &mut impl Write, event: &SseEvent, format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Json => writeln!(out, "{}", serde_json::to_string(event)?)?,
+        OutputFormat::Compact => writeln!(out, "{}", event.data)?,
+        OutputFormat::Full => {
+            match &event.id {
+                Some(id) => writeln!(out, "\n📨 {} (id {id})", event.event)?,
+                None => writeln!(out, "\n📨 {}", event.event)?,
+            }
+            writeln!(out, "{}", event.data)?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::test_support::{serve, Response};
+    use std::sync::{Arc, Mutex};
+
+    /// Serve two connections: the first closes after two events, the second
+    /// continues from the `Last-Event-ID` it was sent.
+    fn resumable_stream(seen_ids: Arc<Mutex<Vec<Option<String>>>>) -> String {
+        let addr = serve(move |request| {
+            let last_id = request.header("last-event-id").map(String::from);
+            let body = match last_id {
+                None => "retry: 20\nid: 1\ndata: first\n\nid: 2\ndata: second\ndata: part\n\n",
+                Some(_) => "id: 3\nevent: done\ndata: third\n\n",
+            };
+            seen_ids.lock().unwrap().push(last_id);
+            Response::typed("text/event-stream", body)
+        });
+        format!("http://{addr}/events")
+    }
+
+    #[tokio::test]
+    async fn prints_events_in_order_and_resumes_with_last_event_id() {
+        let seen_ids = Arc::new(Mutex::new(Vec::new()));
+        let url = resumable_stream(Arc::clone(&seen_ids));
+        let client =
+            tokio::task::spawn_blocking(|| AcceleratedClient::from_client(reqwest::Client::new()))
+                .await
+                .unwrap()
+                .unwrap();
+
+        let mut out = Vec::new();
+        let count = consume_events(&client, &url, HashMap::new(), None, Some(3), |event| {
+            print_event(&mut out, event, OutputFormat::Json)
+        })
+        .await
+        .unwrap();
+        assert_eq!(count, 3);
+
+        let events: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let data: Vec<_> = events.iter().map(|e| e["data"].as_str().unwrap()).collect();
+        assert_eq!(data, ["first", "second\npart", "third"]);
+        assert_eq!(events[2]["event"], "done");
+        assert_eq!(events[2]["id"], "3");
+
+        assert_eq!(*seen_ids.lock().unwrap(), [None, Some("2".to_string())]);
+    }
+
+    /// Client that leaves retrying to `consume_events`
+    async fn single_attempt_client() -> AcceleratedClient {
+        tokio::task::spawn_blocking(|| AcceleratedClient::from_client(reqwest::Client::new()))
+            .await
+            .unwrap()
+            .unwrap()
+            .with_retry(nab::RetryPolicy::none())
+    }
+
+    #[tokio::test]
+    async fn failed_reconnects_back_off_until_the_server_recovers() {
+        let mut connections = 0;
+        let addr = serve(move |_| {
+            connections += 1;
+            match connections {
+                1 => Response::typed("text/event-stream", "retry: 10\nid: 1\ndata: first\n\n"),
+                2 | 3 => Response::status(503),
+                _ => Response::typed("text/event-stream", "id: 2\ndata: second\n\n"),
+            }
+        });
+        let client = single_attempt_client().await;
+
+        let start = std::time::Instant::now();
+        let mut data = Vec::new();
+        let count = consume_events(
+            &client,
+            &format!("http://{addr}/events"),
+            HashMap::new(),
+            None,
+            Some(2),
+            |event| {
+                data.push(event.data.clone());
+                Ok(())
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(data, ["first", "second"]);
+        // The 10ms retry delay, then 20ms and 40ms after the two failures
+        assert!(start.elapsed() >= Duration::from_millis(70));
+    }
+
+    #[tokio::test]
+    async fn client_error_on_reconnect_ends_the_stream() {
+        let mut connections = 0;
+        let addr = serve(move |_| {
+            connections += 1;
+            match connections {
+                1 => Response::typed("text/event-stream", "retry: 10\ndata: first\n\n"),
+                _ => Response::status(404),
+            }
+        });
+        let client = single_attempt_client().await;
+
+        let err = consume_events(
+            &client,
+            &format!("http://{addr}/events"),
+            HashMap::new(),
+            None,
+            None,
+            |_| Ok(()),
+        )
+        .await
+        .unwrap_err();
+        assert!(format!("{err:#}").contains("404"), "{err:#}");
+    }
+
+    #[test]
+    fn reconnect_delay_doubles_up_to_the_cap() {
+        let retry = Duration::from_secs(3);
+        assert_eq!(reconnect_delay(retry, 1), Duration::from_secs(6));
+        assert_eq!(reconnect_delay(retry, 3), Duration::from_secs(24));
+        assert_eq!(reconnect_delay(retry, 4), MAX_RECONNECT_DELAY);
+        assert_eq!(reconnect_delay(retry, u32::MAX), MAX_RECONNECT_DELAY);
+    }
+}
@@ -1,8 +1,16 @@
 use anyhow::Result;
 
-use nab::CookieSource;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
 
-use super::fetch::resolve_browser_name;
+use nab::stream::backends::StreamTechInfo;
+use nab::stream::provider::forwarded_for;
+use nab::stream::{SeriesInfo, StopSignal, StreamInfo};
+
+use super::fetch::{resolve_browser_name, resolve_cookie_source};
+use crate::OutputFormat;
 
 #[allow(clippy::too_many_arguments)]
 pub async fn cmd_stream(
@@ -18,15 +26,21 @@ pub async fn cmd_stream(
     duration: Option<&str>,
     ffmpeg_opts: Option<&str>,
     player: Option<&str>,
+    player_args: Option<&str>,
+    format: OutputFormat,
+    audio_lang: Option<&str>,
+    audio_only: bool,
+    audio_format: Option<&str>,
+    custom_headers: &[String],
+    referer: Option<&str>,
+    xff: Option<&str>,
 ) -> Result<()> {
     use nab::stream::{
         backend::StreamConfig,
-        backends::{FfmpegBackend, NativeHlsBackend},
+        backends::{FfmpegBackend, NativeDashBackend, NativeHlsBackend},
         providers::{GenericHlsProvider, YleProvider},
         StreamBackend, StreamProvider, StreamQuality,
     };
-    use std::collections::HashMap;
-    use std::process::Stdio;
     use tokio::io::{stdout, AsyncWriteExt};
 
     // Parse quality
@@ -39,6 +53,12 @@ pub async fn cmd_stream(
             .unwrap_or(StreamQuality::Best),
     };
 
+    // Fail before fetching anything if the player can't be started
+    let player = match player {
+        Some(cmd) if !info_only && !list_episodes => Some(Player::resolve(cmd, player_args)?),
+        _ => None,
+    };
+
     // Select provider based on source
     let provider: Box<dyn StreamProvider> = match source.to_lowercase().as_str() {
         "yle" => Box::new(YleProvider::new()?),
@@ -67,6 +87,10 @@ pub async fn cmd_stream(
     if list_episodes {
         eprintln!("📋 Listing episodes for: {id}");
         let series = provider.list_series(id).await?;
+        if matches!(format, OutputFormat::Json) {
+            println!("{}", serde_json::to_string_pretty(&series_json(&series))?);
+            return Ok(());
+        }
         println!("Series: {}", series.title);
         println!("Episodes: {}", series.episodes.len());
         for ep in &series.episodes {
@@ -91,47 +115,10 @@ pub async fn cmd_stream(
     eprintln!("📡 Fetching stream info for: {id}");
     let stream_info = provider.get_stream_info(id).await?;
 
-    // Info only mode
-    if info_only {
-        println!("Title: {}", stream_info.title);
-        if let Some(ref desc) = stream_info.description {
-            println!("Description: {desc}");
-        }
-        if let Some(dur) = stream_info.duration_seconds {
-            println!("Duration: {}:{:02}", dur / 60, dur % 60);
-        }
-        println!("Live: {}", stream_info.is_live);
-        println!("Manifest: {}", stream_info.manifest_url);
-        if let Some(ref thumb) = stream_info.thumbnail_url {
-            println!("Thumbnail: {thumb}");
-        }
-        return Ok(());
-    }
-
-    eprintln!("📺 {}", stream_info.title);
-    if stream_info.is_live {
-        eprintln!("   🔴 LIVE");
-    }
-    if let Some(dur) = stream_info.duration_seconds {
-        eprintln!("   Duration: {}:{:02}", dur / 60, dur % 60);
-    }
-
-    // Build stream config
-    let mut headers = HashMap::new();
-    headers.insert("Referer".to_string(), "https://areena.yle.fi".to_string());
-    headers.insert("Origin".to_string(), "https://areena.yle.fi".to_string());
+    // Build stream config: provider defaults, then user overrides
+    let mut headers = stream_headers(provider.default_headers(), custom_headers, referer, xff)?;
 
     if provider.name() == "yle" {
-        use rand::Rng;
-        let mut rng = rand::thread_rng();
-        let ip = format!(
-            "91.{}.{}.{}",
-            rng.gen_range(152..160),
-            rng.gen_range(0..256),
-            rng.gen_range(1..255)
-        );
-        headers.insert("X-Forwarded-For".to_string(), ip);
-
         if cookies.to_lowercase() == "none" {
             eprintln!("🌍 Using Finnish IP for geo access. Add --cookies to enable authenticated content.");
         } else {
@@ -144,14 +131,7 @@ pub async fn cmd_stream(
 
     if let Some(browser) = browser_name {
         eprintln!("🍪 Extracting cookies from {browser}...");
-        let cookie_source = match browser.to_lowercase().as_str() {
-            "brave" => CookieSource::Brave,
-            "chrome" => CookieSource::Chrome,
-            "firefox" => CookieSource::Firefox,
-            "safari" => CookieSource::Safari,
-            "edge" => CookieSource::Chrome,
-            _ => CookieSource::Chrome,
-        };
+        let cookie_source = resolve_cookie_source(&browser);
 
         match cookie_source.get_cookies("yle.fi") {
             Ok(cookie_map) if !cookie_map.is_empty() => {
@@ -182,6 +162,51 @@ pub async fn cmd_stream(
         },
     };
 
+    // Info only mode
+    if info_only {
+        // Technical details come from ffprobe; a missing ffprobe only skips them
+        let tech = match FfmpegBackend::new()?
+            .probe(&stream_info.manifest_url, &config)
+            .await
+        {
+            Ok(tech) => Some(tech),
+            Err(e) => {
+                eprintln!("   ⚠️  Probe skipped: {e}");
+                None
+            }
+        };
+
+        if matches!(format, OutputFormat::Json) {
+            let json = info_json(&stream_info, tech.as_ref())?;
+            println!("{}", serde_json::to_string_pretty(&json)?);
+            return Ok(());
+        }
+        println!("Title: {}", stream_info.title);
+        if let Some(ref desc) = stream_info.description {
+            println!("Description: {desc}");
+        }
+        if let Some(dur) = stream_info.duration_seconds {
+            println!("Duration: {}:{:02}", dur / 60, dur % 60);
+        }
+        println!("Live: {}", stream_info.is_live);
+        println!("Manifest: {}", stream_info.manifest_url);
+        if let Some(ref thumb) = stream_info.thumbnail_url {
+            println!("Thumbnail: {thumb}");
+        }
+        if let Some(ref tech) = tech {
+            print_tech_info(tech, stream_info.duration_seconds.is_none());
+        }
+        return Ok(());
+    }
+
+    eprintln!("📺 {}", stream_info.title);
+    if stream_info.is_live {
+        eprintln!("   🔴 LIVE");
+    }
+    if let Some(dur) = stream_info.duration_seconds {
+        eprintln!("   Duration: {}:{:02}", dur / 60, dur % 60);
+    }
+
     // For Yle, get fresh manifest URL via yle-dl
     let manifest_url = if provider.name() == "yle" {
         eprintln!("🔄 Getting fresh manifest URL via yle-dl...");
@@ -204,16 +229,30 @@ pub async fn cmd_stream(
     let is_dash = manifest_url.contains(".mpd");
     let is_encrypted = false;
 
-    let use_ffmpeg = force_ffmpeg || is_dash || is_encrypted || ffmpeg_opts.is_some();
-    let use_native = force_native && !is_dash && !is_encrypted;
+    if force_native && audio_format.is_some() {
+        anyhow::bail!("--audio-format re-encodes with ffmpeg and cannot be used with --native");
+    }
+
+    let use_ffmpeg =
+        force_ffmpeg || is_dash || is_encrypted || ffmpeg_opts.is_some() || audio_format.is_some();
+    // --audio-lang needs the native DASH backend's track selection
+    let wants_native_dash =
+        is_dash && audio_lang.is_some() && !force_ffmpeg && audio_format.is_none();
+    let use_native = (force_native || wants_native_dash) && !is_encrypted;
+
+    let stop = StopSignal::new();
+    stop_on_ctrl_c(stop.clone());
 
     if use_ffmpeg && !use_native {
         eprintln!("🔧 Backend: ffmpeg");
-        let mut backend = FfmpegBackend::new()?;
+        let mut backend = FfmpegBackend::new()?.with_stop_signal(stop.clone());
 
         if let Some(opts) = ffmpeg_opts {
             backend = backend.with_transcode_opts(opts);
         }
+        if audio_only {
+            backend = backend.with_audio_only(audio_format);
+        }
 
         if !backend.check_available().await {
             anyhow::bail!("ffmpeg not found in PATH. Install ffmpeg or use --native.");
@@ -227,21 +266,8 @@ pub async fn cmd_stream(
             );
         };
 
-        if let Some(player_cmd) = player {
-            eprintln!("🎬 Piping to: {player_cmd}");
-            let player_args = get_player_stdin_args(player_cmd);
-            let mut child = tokio::process::Command::new(player_cmd)
-                .args(&player_args)
-                .stdin(Stdio::piped())
-                .stdout(Stdio::inherit())
-                .stderr(Stdio::inherit())
-                .spawn()
-                .map_err(|e| anyhow::anyhow!("Failed to spawn {player_cmd}: {e}"))?;
-
-            let mut stdin = child
-                .stdin
-                .take()
-                .ok_or_else(|| anyhow::anyhow!("Failed to get stdin for {player_cmd}"))?;
+        if let Some(player) = &player {
+            let (mut child, mut stdin) = player.spawn()?;
 
             if let Some(dur_str) = duration {
                 let secs = parse_duration(dur_str)?;
@@ -304,94 +330,218 @@ pub async fn cmd_stream(
                 )
                 .await?;
         }
+    } else if is_dash {
+        eprintln!("🔧 Backend: native DASH");
+        let mut backend = NativeDashBackend::new()?.with_stop_signal(stop.clone());
+        if let Some(lang) = audio_lang {
+            backend = backend.with_audio_lang(lang);
+        }
+        backend = backend.with_audio_only(audio_only);
+        run_native(
+            &backend,
+            manifest_url,
+            &config,
+            output,
+            player.as_ref(),
+            duration,
+        )
+        .await?;
     } else {
         eprintln!("🔧 Backend: native");
-        let backend = NativeHlsBackend::new()?;
+        let backend = NativeHlsBackend::new()?
+            .with_audio_only(audio_only)
+            .with_stop_signal(stop.clone());
 
         if !backend.can_handle(manifest_url, is_encrypted) {
             anyhow::bail!("Native backend cannot handle this stream. Try --ffmpeg.");
         }
+        run_native(
+            &backend,
+            manifest_url,
+            &config,
+            output,
+            player.as_ref(),
+            duration,
+        )
+        .await?;
+    }
 
-        let progress_cb = |p: nab::stream::backend::StreamProgress| {
-            let total = p
-                .segments_total
-                .map(|t| format!("/{t}"))
-                .unwrap_or_default();
-            eprint!(
-                "\r   📥 {:.1} MB, {}{} segments, {:.1}s    ",
-                p.bytes_downloaded as f64 / 1_000_000.0,
-                p.segments_completed,
-                total,
-                p.elapsed_seconds
-            );
-        };
+    if stop.is_stopped() {
+        eprintln!("\n✅ Stream stopped, partial output finalized");
+    } else {
+        eprintln!("\n✅ Stream complete");
+    }
+    Ok(())
+}
 
-        if let Some(player_cmd) = player {
-            eprintln!("🎬 Piping to: {player_cmd}");
-            let player_args = get_player_stdin_args(player_cmd);
-            let mut child = tokio::process::Command::new(player_cmd)
-                .args(&player_args)
-                .stdin(Stdio::piped())
-                .stdout(Stdio::inherit())
-                .stderr(Stdio::inherit())
-                .spawn()
-                .map_err(|e| anyhow::anyhow!("Failed to spawn {player_cmd}: {e}"))?;
-
-            let mut stdin = child
-                .stdin
-                .take()
-                .ok_or_else(|| anyhow::anyhow!("Failed to get stdin for {player_cmd}"))?;
+/// First Ctrl-C stops the download cleanly so the output stays playable;
+/// a second one aborts immediately
+fn stop_on_ctrl_c(stop: StopSignal) {
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_err() {
+            return;
+        }
+        eprintln!("\n⏹️  Stopping, finalizing output (Ctrl-C again to abort)");
+        stop.stop();
+        if tokio::signal::ctrl_c().await.is_ok() {
+            eprintln!("\n⏹️  Aborted");
+            std::process::exit(130);
+        }
+    });
+}
 
-            backend
-                .stream_to(
-                    manifest_url,
-                    &config,
-                    &mut stdin,
-                    Some(Box::new(progress_cb)),
-                )
-                .await?;
+/// Run a native backend to a player, stdout or a file
+async fn run_native<B: nab::stream::StreamBackend>(
+    backend: &B,
+    manifest_url: &str,
+    config: &nab::stream::backend::StreamConfig,
+    output: &str,
+    player: Option<&Player>,
+    duration: Option<&str>,
+) -> Result<()> {
+    use tokio::io::{stdout, AsyncWriteExt};
 
-            drop(stdin);
-            child.wait().await?;
-        } else if output == "-" {
-            let mut stdout = stdout();
-            backend
-                .stream_to(
-                    manifest_url,
-                    &config,
-                    &mut stdout,
-                    Some(Box::new(progress_cb)),
-                )
-                .await?;
-            stdout.flush().await?;
-        } else {
-            let path = std::path::Path::new(output);
-            let duration_parsed = duration.map(parse_duration).transpose()?;
-            backend
-                .stream_to_file(
-                    manifest_url,
-                    &config,
-                    path,
-                    Some(Box::new(progress_cb)),
-                    duration_parsed,
-                )
-                .await?;
-        }
+    let progress_cb = |p: nab::stream::backend::StreamProgress| {
+        let total = p
+            .segments_total
+            .map(|t| format!("/{t}"))
+            .unwrap_or_default();
+        eprint!(
+            "\r   📥 {:.1} MB, {}{} segments, {:.1}s    ",
+            p.bytes_downloaded as f64 / 1_000_000.0,
+            p.segments_completed,
+            total,
+            p.elapsed_seconds
+        );
+    };
+
+    if let Some(player) = player {
+        let (mut child, mut stdin) = player.spawn()?;
+
+        backend
+            .stream_to(
+                manifest_url,
+                config,
+                &mut stdin,
+                Some(Box::new(progress_cb)),
+            )
+            .await?;
+
+        drop(stdin);
+        child.wait().await?;
+    } else if output == "-" {
+        let mut stdout = stdout();
+        backend
+            .stream_to(
+                manifest_url,
+                config,
+                &mut stdout,
+                Some(Box::new(progress_cb)),
+            )
+            .await?;
+        stdout.flush().await?;
+    } else {
+        let path = std::path::Path::new(output);
+        let duration_parsed = duration.map(parse_duration).transpose()?;
+        backend
+            .stream_to_file(
+                manifest_url,
+                config,
+                path,
+                Some(Box::new(progress_cb)),
+                duration_parsed,
+            )
+            .await?;
     }
 
-    eprintln!("\n✅ Stream complete");
     Ok(())
 }
 
+/// Players with known stdin arguments, listed when `--player` isn't found
+const SUPPORTED_PLAYERS: &[&str] = &[
+    "vlc",
+    "cvlc",
+    "mpv",
+    "ffplay",
+    "mplayer",
+    "iina",
+    "mpg123",
+    "celluloid",
+];
+
+/// Media player the stream is piped into, located before any download starts
+#[derive(Debug)]
+struct Player {
+    name: String,
+    path: PathBuf,
+    args: Vec<String>,
+}
+
+impl Player {
+    /// Find `cmd` in PATH (or at the given path) and build its stdin arguments
+    ///
+    /// `extra_args` (`--player-args`) are split like a shell would split
+    /// them, quotes and escapes included, and appended.
+    fn resolve(cmd: &str, extra_args: Option<&str>) -> Result<Self> {
+        let path = which::which(cmd).map_err(|_| {
+            anyhow::anyhow!(
+                "Media player '{cmd}' not found in PATH. Supported players: {}. \
+                 Any player that reads from stdin works; pass its arguments with --player-args.",
+                SUPPORTED_PLAYERS.join(", ")
+            )
+        })?;
+        let name = Path::new(cmd)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or(cmd)
+            .to_lowercase();
+        let mut args: Vec<String> = get_player_stdin_args(&name)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        if let Some(extra) = extra_args {
+            args.extend(shlex::split(extra).ok_or_else(|| {
+                anyhow::anyhow!("Invalid --player-args '{extra}': unbalanced quotes")
+            })?);
+        }
+        Ok(Self { name, path, args })
+    }
+
+    fn command(&self) -> tokio::process::Command {
+        let mut command = tokio::process::Command::new(&self.path);
+        command
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit());
+        command
+    }
+
+    fn spawn(&self) -> Result<(tokio::process::Child, tokio::process::ChildStdin)> {
+        eprintln!("🎬 Piping to: {} ({})", self.name, self.path.display());
+        let mut child = self
+            .command()
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("Failed to spawn {}: {e}", self.name))?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow::anyhow!("Failed to get stdin for {}", self.name))?;
+        Ok((child, stdin))
+    }
+}
+
 /// Get arguments for media players to read from stdin
-fn get_player_stdin_args(player: &str) -> Vec<&'static str> {
+fn get_player_stdin_args(player: &str) -> &'static [&'static str] {
     match player {
-        "vlc" => vec!["-", "--intf", "dummy", "--play-and-exit"],
-        "mpv" => vec!["-"],
-        "ffplay" => vec!["-i", "-"],
-        "mplayer" => vec!["-"],
-        "iina" => vec!["--stdin"],
-        _ => vec!["-"],
+        "vlc" => &["-", "--intf", "dummy", "--play-and-exit"],
+        "cvlc" => &["-", "--play-and-exit"],
+        "mpv" | "celluloid" => &["-"],
+        "ffplay" => &["-i", "-"],
+        "mplayer" => &["-"],
+        "iina" | "iina-cli" => &["--stdin"],
+        "mpg123" => &["-q", "-"],
+        _ => &["-"],
     }
 }
 
@@ -432,3 +582,229 @@ fn parse_duration(s: &str) -> Result<u64> {
 
     Ok(total_secs)
 }
+
+/// Merge `--header`, `--referer` and `--xff` over the provider's default headers.
+///
+/// Header names match case-insensitively, so `referer: x` replaces a default `Referer`.
+fn stream_headers(
+    defaults: HashMap<String, String>,
+    custom_headers: &[String],
+    referer: Option<&str>,
+    xff: Option<&str>,
+) -> Result<HashMap<String, String>> {
+    let mut headers = defaults;
+    let mut set = |name: &str, value: String| {
+        headers.retain(|k, _| !k.eq_ignore_ascii_case(name));
+        headers.insert(name.to_string(), value);
+    };
+
+    for header_str in custom_headers {
+        let (name, value) = header_str.split_once(':').ok_or_else(|| {
+            anyhow::anyhow!("Invalid header (expected \"Name: value\"): {header_str}")
+        })?;
+        set(name.trim(), value.trim().to_string());
+    }
+    if let Some(referer) = referer {
+        set("Referer", referer.to_string());
+    }
+    if let Some(spec) = xff {
+        set("X-Forwarded-For", forwarded_for(spec)?);
+    }
+
+    Ok(headers)
+}
+
+/// `--info` JSON: the provider's stream info plus ffprobe details under `tech`
+fn info_json(info: &StreamInfo, tech: Option<&StreamTechInfo>) -> Result<serde_json::Value> {
+    let mut json = serde_json::to_value(info)?;
+    json["tech"] = serde_json::to_value(tech)?;
+    Ok(json)
+}
+
+/// Print ffprobe details for `--info`, including the duration when the provider had none
+fn print_tech_info(tech: &StreamTechInfo, show_duration: bool) {
+    if let Some(ref codec) = tech.video_codec {
+        let mut line = format!("Video: {codec}");
+        if let (Some(w), Some(h)) = (tech.width, tech.height) {
+            let _ = write!(line, " {w}x{h}");
+        }
+        if let Some(fps) = tech.fps {
+            let _ = write!(line, " @ {fps:.2} fps");
+        }
+        println!("{line}");
+    }
+    if let Some(ref codec) = tech.audio_codec {
+        let mut line = format!("Audio: {codec}");
+        if let Some(ch) = tech.audio_channels {
+            let _ = write!(line, ", {ch} ch");
+        }
+        if let Some(rate) = tech.audio_sample_rate {
+            let _ = write!(line, ", {rate} Hz");
+        }
+        println!("{line}");
+    }
+    if let Some(bps) = tech.bitrate_bps {
+        println!("Bitrate: {} kbps", bps / 1000);
+    }
+    if let Some(ref container) = tech.container {
+        println!("Container: {container}");
+    }
+    if show_duration {
+        if let Some(secs) = tech.duration_seconds {
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let secs = secs.round() as u64;
+            println!("Duration: {}:{:02}", secs / 60, secs % 60);
+        }
+    }
+}
+
+/// JSON listing for `stream --list --format json`
+fn series_json(series: &SeriesInfo) -> serde_json::Value {
+    serde_json::json!({
+        "id": series.id,
+        "title": series.title,
+        "episode_count": series.episodes.len(),
+        "episodes": series.episodes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nab::stream::{EpisodeInfo, QualityInfo, StreamProvider};
+
+    #[test]
+    fn stream_info_json_has_expected_keys() {
+        let info = StreamInfo {
+            id: "1-2345678".to_string(),
+            title: "Uutiset".to_string(),
+            description: None,
+            duration_seconds: Some(1800),
+            manifest_url: "https://example.com/master.m3u8".to_string(),
+            is_live: false,
+            qualities: vec![QualityInfo {
+                height: 720,
+                bandwidth: 2_500_000,
+                codecs: Some("avc1.4d401f,mp4a.40.2".to_string()),
+            }],
+            thumbnail_url: Some("https://example.com/thumb.jpg".to_string()),
+        };
+
+        let tech = StreamTechInfo {
+            video_codec: Some("h264".to_string()),
+            height: Some(720),
+            ..StreamTechInfo::default()
+        };
+        let json = info_json(&info, Some(&tech)).unwrap();
+
+        assert_eq!(json["title"], "Uutiset");
+        assert_eq!(json["tech"]["video_codec"], "h264");
+        assert_eq!(json["tech"]["height"], 720);
+        assert!(info_json(&info, None).unwrap()["tech"].is_null());
+        assert_eq!(json["duration_seconds"], 1800);
+        assert_eq!(json["is_live"], false);
+        assert_eq!(json["manifest_url"], "https://example.com/master.m3u8");
+        assert_eq!(json["thumbnail_url"], "https://example.com/thumb.jpg");
+        assert!(json["description"].is_null());
+        assert_eq!(json["qualities"][0]["height"], 720);
+    }
+
+    #[test]
+    fn explicit_headers_override_provider_defaults() {
+        let defaults = nab::stream::providers::YleProvider::new()
+            .unwrap()
+            .default_headers();
+        assert!(defaults["X-Forwarded-For"].starts_with("91.15"));
+
+        let headers = stream_headers(
+            defaults,
+            &[
+                "origin: https://example.com".to_string(),
+                "X-Api-Key: k1".to_string(),
+            ],
+            Some("https://example.com/watch"),
+            Some("random:91.152.0.0/13"),
+        )
+        .unwrap();
+
+        assert_eq!(headers["Referer"], "https://example.com/watch");
+        assert_eq!(headers["origin"], "https://example.com");
+        assert!(!headers.contains_key("Origin"));
+        assert_eq!(headers["X-Api-Key"], "k1");
+        let ip: std::net::Ipv4Addr = headers["X-Forwarded-For"].parse().unwrap();
+        assert_eq!(ip.octets()[0], 91);
+        assert!((152..160).contains(&ip.octets()[1]));
+
+        let headers = stream_headers(HashMap::new(), &[], None, Some("203.0.113.9")).unwrap();
+        assert_eq!(headers["X-Forwarded-For"], "203.0.113.9");
+        assert!(stream_headers(HashMap::new(), &["no-colon".to_string()], None, None).is_err());
+    }
+
+    #[test]
+    fn missing_player_fails_preflight() {
+        let err = Player::resolve("nab-no-such-player", None)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("'nab-no-such-player' not found in PATH"));
+        assert!(err.contains("vlc, cvlc, mpv"));
+    }
+
+    #[test]
+    fn player_args_are_appended_to_spawn_command() {
+        let extra = r#"--volume 50  --title "Live news" --sub-file=a\ b.srt"#;
+        let player = Player::resolve("sh", Some(extra)).unwrap();
+        assert_eq!(player.name, "sh");
+        let command = player.command();
+        let args: Vec<_> = command.as_std().get_args().collect();
+        assert_eq!(
+            args,
+            [
+                "-",
+                "--volume",
+                "50",
+                "--title",
+                "Live news",
+                "--sub-file=a b.srt"
+            ]
+        );
+        assert!(Player::resolve("sh", Some("--title \"open")).is_err());
+        assert_eq!(command.as_std().get_program(), player.path);
+
+        assert_eq!(
+            get_player_stdin_args("vlc"),
+            ["-", "--intf", "dummy", "--play-and-exit"]
+        );
+    }
+
+    #[test]
+    fn series_listing_serializes_episode_numbers_and_durations() {
+        let episode = |id: &str, season, number, duration| EpisodeInfo {
+            id: id.to_string(),
+            title: format!("Episode {id}"),
+            episode_number: number,
+            season_number: season,
+            duration_seconds: duration,
+            publish_date: None,
+        };
+        let series = SeriesInfo {
+            id: "1-100".to_string(),
+            title: "Docs".to_string(),
+            episodes: vec![
+                episode("1-101", Some(1), Some(1), Some(2640)),
+                episode("1-102", Some(1), Some(2), None),
+            ],
+        };
+
+        let json = series_json(&series);
+
+        assert_eq!(json["title"], "Docs");
+        assert_eq!(json["episode_count"], 2);
+        let first = &json["episodes"][0];
+        assert_eq!(first["id"], "1-101");
+        assert_eq!(first["season_number"], 1);
+        assert_eq!(first["episode_number"], 1);
+        assert_eq!(first["duration_seconds"], 2640);
+        assert_eq!(json["episodes"][1]["episode_number"], 2);
+        assert!(json["episodes"][1]["duration_seconds"].is_null());
+    }
+}
@@ -15,12 +15,14 @@ pub async fn cmd_submit(
     show_headers: bool,
     format: OutputFormat,
 ) -> Result<()> {
-    use nab::{parse_field_args, Form};
+    use nab::{parse_field_args, Form, SubmitOptions};
 
     let client = create_client_with_cookies(cookies, use_1password, url).await?;
 
     println!("Fetching form page: {url}");
-    let page_html = client.fetch_text(url).await?;
+    let page = client.fetch(url).await?;
+    let page_headers = page.headers().clone();
+    let page_html = page.text().await?;
 
     let mut forms = Form::parse_all(&page_html)?;
 
@@ -32,9 +34,10 @@ pub async fn cmd_submit(
     println!("Found form: {} {}", form.method, form.action);
     println!("  Hidden fields: {}", form.hidden_fields.len());
 
+    let mut options = SubmitOptions::default();
     if let Some(selector) = csrf_from {
         if let Some(token) = Form::extract_csrf_token(&page_html, selector)? {
-            println!("  CSRF token extracted: {}", &token[..token.len().min(20)]);
+            println!("  CSRF token extracted: {}", token_preview(&token));
             let field_name = if selector.contains("name=") {
                 selector
                     .split("name=")
@@ -48,6 +51,11 @@ pub async fn cmd_submit(
         } else {
             anyhow::bail!("CSRF token not found with selector: {}", selector);
         }
+    } else if let Some(token) =
+        Form::detect_csrf_token(&page_html).or_else(|| Form::csrf_cookie(&page_headers))
+    {
+        println!("  CSRF token detected: {}", token_preview(token.value()));
+        options = options.with_csrf_token(token);
     }
 
     let user_fields = parse_field_args(field_args)?;
@@ -57,20 +65,18 @@ pub async fn cmd_submit(
     let action_url = form.resolve_action(url)?;
     println!("Submitting to: {action_url}");
 
-    let form_data = form.encode_urlencoded();
-    let response = client
-        .inner()
-        .post(&action_url)
-        .header("Content-Type", form.content_type())
-        .body(form_data)
-        .send()
-        .await?;
+    let response = form.submit(&client, url, &options).await?;
 
     output_response(response, show_headers, true, format, None, false, false, 0).await?;
 
     Ok(())
 }
 
+/// First 20 characters of a token, for logging
+fn token_preview(token: &str) -> String {
+    token.chars().take(20).collect()
+}
+
 /// Create HTTP client with cookie support
 async fn create_client_with_cookies(
     _cookies: &str,
@@ -79,3 +85,14 @@ async fn create_client_with_cookies(
 ) -> Result<AcceleratedClient> {
     AcceleratedClient::new()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_preview_keeps_whole_characters() {
+        assert_eq!(token_preview("short"), "short");
+        assert_eq!(token_preview(&"é".repeat(30)), "é".repeat(20));
+    }
+}
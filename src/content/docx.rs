@@ -0,0 +1,511 @@
+//! DOCX (Office Open XML) to Markdown conversion handler.
+//!
+//! Unzips the container and walks `word/document.xml`, rendering paragraphs,
+//! headings, lists and tables. Heading levels come from the paragraph style
+//! (`Heading1`..`Heading6`, `Title`), resolved through `word/styles.xml` so
+//! localized style IDs still map by their `heading N` name. List markers are
+//! looked up in `word/numbering.xml`.
+//!
+//! # Pipeline
+//!
+//! ```text
+//! DOCX bytes → unzip → document.xml (+ styles, numbering) → blocks → markdown
+//! ```
+
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+
+use anyhow::{Context, Result};
+use roxmltree::{Document, Node};
+
+use super::{ContentHandler, ConversionResult};
+
+/// Maximum DOCX input size (50 MB), matching the PDF handler.
+const MAX_DOCX_SIZE: usize = 50 * 1024 * 1024;
+
+/// Maximum decompressed size of a single XML part, guarding against zip bombs.
+const MAX_PART_SIZE: u64 = 200 * 1024 * 1024;
+
+/// `WordprocessingML` main namespace.
+const W: &str = "http://schemas.openxmlformats.org/wordprocessingml/2006/main";
+
+/// Converts Word documents to markdown.
+pub struct DocxHandler;
+
+impl ContentHandler for DocxHandler {
+    fn supported_types(&self) -> &[&str] {
+        &["application/vnd.openxmlformats-officedocument.wordprocessingml.document"]
+    }
+
+    fn to_markdown(&self, bytes: &[u8], content_type: &str) -> Result<ConversionResult> {
+        let start = std::time::Instant::now();
+        anyhow::ensure!(
+            bytes.len() <= MAX_DOCX_SIZE,
+            "DOCX too large: {} bytes (max {MAX_DOCX_SIZE})",
+            bytes.len()
+        );
+
+        let mut archive =
+            zip::ZipArchive::new(Cursor::new(bytes)).context("Not a valid DOCX (ZIP) container")?;
+        let document = read_part(&mut archive, "word/document.xml")?
+            .context("DOCX has no word/document.xml")?;
+        let styles = read_part(&mut archive, "word/styles.xml")?;
+        let numbering = read_part(&mut archive, "word/numbering.xml")?;
+
+        let converter = Converter {
+            headings: styles
+                .as_deref()
+                .map(heading_styles)
+                .transpose()?
+                .unwrap_or_default(),
+            ordered: numbering
+                .as_deref()
+                .map(ordered_levels)
+                .transpose()?
+                .unwrap_or_default(),
+        };
+
+        let doc = Document::parse(&document).context("Invalid word/document.xml")?;
+        let body = children(doc.root_element(), "body")
+            .next()
+            .context("word/document.xml has no body")?;
+        let mut blocks = Vec::new();
+        converter.render_blocks(body, &mut blocks);
+
+        Ok(ConversionResult {
+            markdown: join_blocks(&blocks),
+            page_count: None,
+            content_type: content_type.to_string(),
+            elapsed_ms: start.elapsed().as_secs_f64() * 1000.0,
+        })
+    }
+}
+
+/// A rendered top-level element; consecutive list items share one block gap.
+struct Block {
+    text: String,
+    list_item: bool,
+}
+
+struct Converter {
+    /// Paragraph style ID → heading level
+    headings: HashMap<String, usize>,
+    /// Numbering `(numId, ilvl)` pairs with a non-bullet format
+    ordered: HashMap<(String, u32), bool>,
+}
+
+impl Converter {
+    fn render_blocks(&self, container: Node, blocks: &mut Vec<Block>) {
+        for node in container.children().filter(Node::is_element) {
+            match local_name(node) {
+                Some("p") => blocks.extend(self.paragraph(node)),
+                Some("tbl") => blocks.extend(self.table(node).map(|text| Block {
+                    text,
+                    list_item: false,
+                })),
+                // Content controls wrap ordinary paragraphs and tables
+                Some("sdt") => {
+                    if let Some(content) = children(node, "sdtContent").next() {
+                        self.render_blocks(content, blocks);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn paragraph(&self, p: Node) -> Option<Block> {
+        let props = children(p, "pPr").next();
+
+        let style = props
+            .and_then(|pr| children(pr, "pStyle").next())
+            .and_then(val);
+        if let Some(level) =
+            style.and_then(|s| self.headings.get(s).copied().or_else(|| heading_level(s)))
+        {
+            let text = inline_text(p, false);
+            let text = text.trim();
+            return (!text.is_empty()).then(|| Block {
+                text: format!("{} {text}", "#".repeat(level)),
+                list_item: false,
+            });
+        }
+
+        let text = inline_text(p, true);
+        let text = text.trim();
+        if text.is_empty() {
+            return None;
+        }
+
+        if let Some(num) = props.and_then(|pr| children(pr, "numPr").next()) {
+            let num_id = children(num, "numId").next().and_then(val).unwrap_or("0");
+            let level = children(num, "ilvl")
+                .next()
+                .and_then(val)
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0);
+            // numId 0 explicitly removes numbering
+            if num_id != "0" {
+                let ordered = self
+                    .ordered
+                    .get(&(num_id.to_string(), level))
+                    .copied()
+                    .unwrap_or(false);
+                let marker = if ordered { "1." } else { "-" };
+                return Some(Block {
+                    text: format!("{}{marker} {text}", "  ".repeat(level as usize)),
+                    list_item: true,
+                });
+            }
+        }
+
+        Some(Block {
+            text: text.to_string(),
+            list_item: false,
+        })
+    }
+
+    fn table(&self, tbl: Node) -> Option<String> {
+        let rows: Vec<Vec<String>> = children(tbl, "tr")
+            .map(|tr| {
+                let mut cells = Vec::new();
+                for tc in children(tr, "tc") {
+                    cells.push(self.cell_text(tc));
+                    // Horizontally merged cells keep later columns aligned
+                    let span = children(tc, "tcPr")
+                        .next()
+                        .and_then(|pr| children(pr, "gridSpan").next())
+                        .and_then(val)
+                        .and_then(|v| v.parse::<usize>().ok())
+                        .unwrap_or(1);
+                    cells.extend(std::iter::repeat_n(String::new(), span.saturating_sub(1)));
+                }
+                cells
+            })
+            .filter(|cells| !cells.is_empty())
+            .collect();
+        render_table(&rows)
+    }
+
+    fn cell_text(&self, tc: Node) -> String {
+        let mut blocks = Vec::new();
+        self.render_blocks(tc, &mut blocks);
+        blocks
+            .iter()
+            .map(|b| b.text.trim().replace('\n', " ").replace('|', "\\|"))
+            .collect::<Vec<_>>()
+            .join("<br>")
+    }
+}
+
+/// Render rows as a GitHub-flavored markdown table, first row as header.
+fn render_table(rows: &[Vec<String>]) -> Option<String> {
+    let columns = rows.iter().map(Vec::len).max()?;
+    let line = |cells: &[String]| {
+        let mut line = String::from("|");
+        for col in 0..columns {
+            let cell = cells.get(col).map_or("", String::as_str);
+            line.push(' ');
+            line.push_str(cell);
+            line.push_str(" |");
+        }
+        line
+    };
+
+    let mut lines = vec![line(&rows[0]), format!("|{}", " --- |".repeat(columns))];
+    lines.extend(rows[1..].iter().map(|row| line(row)));
+    Some(lines.join("\n"))
+}
+
+fn join_blocks(blocks: &[Block]) -> String {
+    let mut markdown = String::new();
+    let mut previous_list_item = false;
+    for (i, block) in blocks.iter().enumerate() {
+        if i > 0 {
+            markdown.push_str(if previous_list_item && block.list_item {
+                "\n"
+            } else {
+                "\n\n"
+            });
+        }
+        markdown.push_str(&block.text);
+        previous_list_item = block.list_item;
+    }
+    markdown
+}
+
+/// Text of the runs belonging to paragraph `p`, optionally with bold/italic.
+fn inline_text(p: Node, formatted: bool) -> String {
+    // (text, bold, italic), adjacent runs with the same formatting merged
+    let mut segments: Vec<(String, bool, bool)> = Vec::new();
+    let runs = p
+        .descendants()
+        .filter(|n| local_name(*n) == Some("r"))
+        // Skip runs of paragraphs nested in text boxes
+        .filter(|r| r.ancestors().find(|a| local_name(*a) == Some("p")) == Some(p));
+
+    for run in runs {
+        let props = children(run, "rPr").next().filter(|_| formatted);
+        let bold = props.is_some_and(|pr| toggle(pr, "b"));
+        let italic = props.is_some_and(|pr| toggle(pr, "i"));
+        let segment = match segments.last_mut() {
+            Some(last) if last.1 == bold && last.2 == italic => &mut last.0,
+            _ => {
+                segments.push((String::new(), bold, italic));
+                &mut segments.last_mut().expect("just pushed").0
+            }
+        };
+        for node in run.children() {
+            match local_name(node) {
+                Some("t") => segment.push_str(node.text().unwrap_or("")),
+                Some("tab") => segment.push(' '),
+                Some("br" | "cr") => segment.push('\n'),
+                _ => {}
+            }
+        }
+    }
+
+    segments
+        .iter()
+        .map(|(text, bold, italic)| emphasize(text, *bold, *italic))
+        .collect()
+}
+
+/// Wrap `text` in emphasis markers, keeping surrounding whitespace outside.
+fn emphasize(text: &str, bold: bool, italic: bool) -> String {
+    let marker = match (bold, italic) {
+        (true, true) => "***",
+        (true, false) => "**",
+        (false, true) => "*",
+        (false, false) => return text.to_string(),
+    };
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return text.to_string();
+    }
+    let lead = &text[..text.len() - text.trim_start().len()];
+    let trail = &text[text.trim_end().len()..];
+    format!("{lead}{marker}{trimmed}{marker}{trail}")
+}
+
+/// Whether an on/off run property (`<w:b/>`, `<w:i w:val="0"/>`) is on.
+fn toggle(props: Node, name: &str) -> bool {
+    children(props, name)
+        .next()
+        .is_some_and(|n| !matches!(val(n), Some("0" | "false" | "off")))
+}
+
+/// Heading level for a style ID or name such as `Heading2`, `heading 2` or `Title`.
+fn heading_level(style: &str) -> Option<usize> {
+    let normalized: String = style
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    if normalized == "title" {
+        return Some(1);
+    }
+    normalized
+        .strip_prefix("heading")?
+        .parse()
+        .ok()
+        .filter(|level| (1..=6).contains(level))
+}
+
+/// Map paragraph style IDs to heading levels using their names and outline levels.
+fn heading_styles(xml: &str) -> Result<HashMap<String, usize>> {
+    let doc = Document::parse(xml).context("Invalid word/styles.xml")?;
+    let mut headings = HashMap::new();
+    for style in children(doc.root_element(), "style") {
+        if style.attribute((W, "type")) != Some("paragraph") {
+            continue;
+        }
+        let Some(id) = style.attribute((W, "styleId")) else {
+            continue;
+        };
+        let outline = children(style, "pPr")
+            .next()
+            .and_then(|pr| children(pr, "outlineLvl").next())
+            .and_then(val)
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|level| *level < 6)
+            .map(|level| level + 1);
+        let level = children(style, "name")
+            .next()
+            .and_then(val)
+            .and_then(heading_level)
+            .or(outline);
+        if let Some(level) = level {
+            headings.insert(id.to_string(), level);
+        }
+    }
+    Ok(headings)
+}
+
+/// Which numbering instance levels are ordered (decimal, letters, roman).
+fn ordered_levels(xml: &str) -> Result<HashMap<(String, u32), bool>> {
+    let doc = Document::parse(xml).context("Invalid word/numbering.xml")?;
+    let root = doc.root_element();
+
+    let mut abstract_formats: HashMap<&str, Vec<(u32, bool)>> = HashMap::new();
+    for abstract_num in children(root, "abstractNum") {
+        let Some(id) = abstract_num.attribute((W, "abstractNumId")) else {
+            continue;
+        };
+        let levels = children(abstract_num, "lvl")
+            .filter_map(|lvl| {
+                let level = lvl.attribute((W, "ilvl"))?.parse().ok()?;
+                let format = children(lvl, "numFmt").next().and_then(val)?;
+                Some((level, !matches!(format, "bullet" | "none")))
+            })
+            .collect();
+        abstract_formats.insert(id, levels);
+    }
+
+    let mut ordered = HashMap::new();
+    for num in children(root, "num") {
+        let Some(num_id) = num.attribute((W, "numId")) else {
+            continue;
+        };
+        let levels = children(num, "abstractNumId")
+            .next()
+            .and_then(val)
+            .and_then(|id| abstract_formats.get(id));
+        for &(level, is_ordered) in levels.into_iter().flatten() {
+            ordered.insert((num_id.to_string(), level), is_ordered);
+        }
+    }
+    Ok(ordered)
+}
+
+fn read_part(archive: &mut zip::ZipArchive<Cursor<&[u8]>>, name: &str) -> Result<Option<String>> {
+    let file = match archive.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Failed to open {name}")),
+    };
+    let mut xml = String::new();
+    file.take(MAX_PART_SIZE)
+        .read_to_string(&mut xml)
+        .with_context(|| format!("Failed to read {name}"))?;
+    Ok(Some(xml))
+}
+
+fn local_name<'a>(node: Node<'a, '_>) -> Option<&'a str> {
+    (node.is_element() && node.tag_name().namespace() == Some(W)).then(|| node.tag_name().name())
+}
+
+fn children<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    name: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.children()
+        .filter(move |child| local_name(*child) == Some(name))
+}
+
+fn val<'a>(node: Node<'a, '_>) -> Option<&'a str> {
+    node.attribute((W, "val"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const DOCUMENT: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:body>
+    <w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Project Plan</w:t></w:r></w:p>
+    <w:p><w:r><w:t xml:space="preserve">Ship the </w:t></w:r><w:r><w:rPr><w:b/></w:rPr><w:t>be</w:t></w:r><w:r><w:rPr><w:b/></w:rPr><w:t>ta</w:t></w:r><w:r><w:t xml:space="preserve"> in March.</w:t></w:r></w:p>
+    <w:p><w:pPr><w:pStyle w:val="Kop2"/></w:pPr><w:r><w:t>Budget</w:t></w:r></w:p>
+    <w:tbl>
+      <w:tr>
+        <w:tc><w:p><w:r><w:t>Item</w:t></w:r></w:p></w:tc>
+        <w:tc><w:p><w:r><w:t>Cost</w:t></w:r></w:p></w:tc>
+      </w:tr>
+      <w:tr>
+        <w:tc><w:p><w:r><w:t>Servers</w:t></w:r></w:p></w:tc>
+        <w:tc><w:p><w:r><w:t>1200</w:t></w:r></w:p></w:tc>
+      </w:tr>
+    </w:tbl>
+    <w:p><w:pPr><w:numPr><w:ilvl w:val="0"/><w:numId w:val="1"/></w:numPr></w:pPr><w:r><w:t>Hire</w:t></w:r></w:p>
+    <w:p><w:pPr><w:numPr><w:ilvl w:val="1"/><w:numId w:val="1"/></w:numPr></w:pPr><w:r><w:t>Contract</w:t></w:r></w:p>
+    <w:p><w:pPr><w:numPr><w:ilvl w:val="0"/><w:numId w:val="2"/></w:numPr></w:pPr><w:r><w:t>Review</w:t></w:r></w:p>
+  </w:body>
+</w:document>"#;
+
+    const STYLES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:style w:type="paragraph" w:styleId="Kop2"><w:name w:val="heading 2"/></w:style>
+</w:styles>"#;
+
+    const NUMBERING: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:numbering xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+  <w:abstractNum w:abstractNumId="0">
+    <w:lvl w:ilvl="0"><w:numFmt w:val="bullet"/></w:lvl>
+    <w:lvl w:ilvl="1"><w:numFmt w:val="bullet"/></w:lvl>
+  </w:abstractNum>
+  <w:abstractNum w:abstractNumId="1">
+    <w:lvl w:ilvl="0"><w:numFmt w:val="decimal"/></w:lvl>
+  </w:abstractNum>
+  <w:num w:numId="1"><w:abstractNumId w:val="0"/></w:num>
+  <w:num w:numId="2"><w:abstractNumId w:val="1"/></w:num>
+</w:numbering>"#;
+
+    fn docx(parts: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in parts {
+            writer
+                .start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn converts_headings_tables_and_lists() {
+        let bytes = docx(&[
+            ("word/document.xml", DOCUMENT),
+            ("word/styles.xml", STYLES),
+            ("word/numbering.xml", NUMBERING),
+        ]);
+        let result = crate::content::ContentRouter::new()
+            .convert(
+                &bytes,
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            )
+            .unwrap();
+
+        assert_eq!(
+            result.markdown,
+            "# Project Plan\n\n\
+             Ship the **beta** in March.\n\n\
+             ## Budget\n\n\
+             | Item | Cost |\n\
+             | --- | --- |\n\
+             | Servers | 1200 |\n\n\
+             - Hire\n  \
+             - Contract\n\
+             1. Review"
+        );
+    }
+
+    #[test]
+    fn heading_styles_without_styles_part() {
+        let document = DOCUMENT.replace("Kop2", "Heading3");
+        let result = DocxHandler
+            .to_markdown(&docx(&[("word/document.xml", &document)]), "")
+            .unwrap();
+        assert!(result.markdown.contains("\n### Budget\n"));
+        // Without numbering.xml every list is bulleted
+        assert!(result.markdown.ends_with("- Review"));
+    }
+
+    #[test]
+    fn rejects_non_docx() {
+        assert!(DocxHandler.to_markdown(b"not a zip", "").is_err());
+        let empty = docx(&[("other.xml", "<x/>")]);
+        assert!(DocxHandler.to_markdown(&empty, "").is_err());
+    }
+}
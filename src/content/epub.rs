@@ -0,0 +1,282 @@
+//! EPUB to Markdown conversion handler.
+//!
+//! Unzips the container, follows `META-INF/container.xml` to the OPF package
+//! document and converts the XHTML chapters in spine (reading) order with the
+//! HTML handler. The book title from the package metadata becomes the
+//! top-level heading.
+//!
+//! # Pipeline
+//!
+//! ```text
+//! EPUB bytes → unzip → container.xml → OPF (manifest + spine) → XHTML chapters → markdown
+//! ```
+
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+
+use anyhow::{Context, Result};
+use roxmltree::{Document, Node};
+
+use super::html::html_to_markdown;
+use super::{ContentHandler, ConversionResult};
+
+/// Maximum EPUB input size (50 MB), matching the PDF and DOCX handlers.
+const MAX_EPUB_SIZE: usize = 50 * 1024 * 1024;
+
+/// Maximum decompressed size of a single part, guarding against zip bombs.
+const MAX_PART_SIZE: u64 = 200 * 1024 * 1024;
+
+/// Converts EPUB e-books to markdown.
+pub struct EpubHandler;
+
+impl ContentHandler for EpubHandler {
+    fn supported_types(&self) -> &[&str] {
+        &["application/epub+zip"]
+    }
+
+    fn to_markdown(&self, bytes: &[u8], content_type: &str) -> Result<ConversionResult> {
+        let start = std::time::Instant::now();
+        anyhow::ensure!(
+            bytes.len() <= MAX_EPUB_SIZE,
+            "EPUB too large: {} bytes (max {MAX_EPUB_SIZE})",
+            bytes.len()
+        );
+
+        let mut archive =
+            zip::ZipArchive::new(Cursor::new(bytes)).context("Not a valid EPUB (ZIP) container")?;
+        let container = read_part(&mut archive, "META-INF/container.xml")?
+            .context("EPUB has no META-INF/container.xml")?;
+        let opf_path = rootfile(&container)?;
+        let opf = read_part(&mut archive, &opf_path)?
+            .with_context(|| format!("EPUB package {opf_path} is missing"))?;
+        let package = Package::parse(&opf, &opf_path)?;
+
+        let mut sections = Vec::new();
+        if let Some(title) = &package.title {
+            sections.push(format!("# {title}"));
+        }
+        for chapter in &package.chapters {
+            // A spine entry missing from the archive is skipped, not fatal
+            let Some(xhtml) = read_part(&mut archive, chapter)? else {
+                tracing::debug!(chapter, "EPUB spine item not in archive");
+                continue;
+            };
+            let markdown = html_to_markdown(&xhtml);
+            if !markdown.is_empty() {
+                sections.push(markdown);
+            }
+        }
+
+        Ok(ConversionResult {
+            markdown: sections.join("\n\n"),
+            page_count: None,
+            content_type: content_type.to_string(),
+            elapsed_ms: start.elapsed().as_secs_f64() * 1000.0,
+        })
+    }
+}
+
+/// The parts of the OPF package document the conversion needs
+struct Package {
+    /// `dc:title`, if present
+    title: Option<String>,
+    /// Archive paths of the XHTML spine items, in reading order
+    chapters: Vec<String>,
+}
+
+impl Package {
+    fn parse(opf: &str, opf_path: &str) -> Result<Self> {
+        let doc = Document::parse(opf).with_context(|| format!("Invalid {opf_path}"))?;
+        let root = doc.root_element();
+        let base = opf_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+
+        let title = descendants(root, "title")
+            .find_map(|node| node.text())
+            .map(str::trim)
+            .filter(|title| !title.is_empty())
+            .map(str::to_string);
+
+        let manifest: HashMap<&str, (&str, &str)> = descendants(root, "item")
+            .filter_map(|item| {
+                Some((
+                    item.attribute("id")?,
+                    (item.attribute("href")?, item.attribute("media-type")?),
+                ))
+            })
+            .collect();
+
+        let chapters = descendants(root, "itemref")
+            .filter_map(|itemref| manifest.get(itemref.attribute("idref")?))
+            .filter(|(_, media_type)| matches!(*media_type, "application/xhtml+xml" | "text/html"))
+            .map(|(href, _)| resolve(base, href))
+            .collect();
+
+        Ok(Self { title, chapters })
+    }
+}
+
+/// Path of the OPF package document named by `META-INF/container.xml`
+fn rootfile(container: &str) -> Result<String> {
+    let doc = Document::parse(container).context("Invalid META-INF/container.xml")?;
+    let path = descendants(doc.root_element(), "rootfile")
+        .find_map(|node| node.attribute("full-path"))
+        .map(str::to_string);
+    path.context("META-INF/container.xml names no rootfile")
+}
+
+/// Resolve a manifest `href` against the OPF directory into an archive path
+///
+/// Fragments are dropped, `%`-escapes decoded and `.`/`..` segments collapsed.
+fn resolve(base: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or(href);
+    let href =
+        urlencoding::decode(href).map_or_else(|_| href.to_string(), std::borrow::Cow::into_owned);
+    let mut segments: Vec<&str> = base.split('/').filter(|s| !s.is_empty()).collect();
+    for segment in href.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+fn read_part(archive: &mut zip::ZipArchive<Cursor<&[u8]>>, name: &str) -> Result<Option<String>> {
+    let file = match archive.by_name(name) {
+        Ok(file) => file,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Failed to open {name}")),
+    };
+    let mut bytes = Vec::new();
+    file.take(MAX_PART_SIZE)
+        .read_to_end(&mut bytes)
+        .with_context(|| format!("Failed to read {name}"))?;
+    Ok(Some(String::from_utf8_lossy(&bytes).into_owned()))
+}
+
+/// Descendant elements with the given local name, in any namespace
+fn descendants<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    name: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    node.descendants()
+        .filter(move |child| child.is_element() && child.tag_name().name() == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const CONTAINER: &str = r#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>"#;
+
+    const PACKAGE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="id">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:title>Field Notes</dc:title>
+  </metadata>
+  <manifest>
+    <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+    <item id="c2" href="text/chapter%202.xhtml" media-type="application/xhtml+xml"/>
+    <item id="c1" href="text/ch1.xhtml" media-type="application/xhtml+xml"/>
+    <item id="css" href="style.css" media-type="text/css"/>
+    <item id="gone" href="text/missing.xhtml" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine>
+    <itemref idref="c1"/>
+    <itemref idref="gone"/>
+    <itemref idref="c2"/>
+  </spine>
+</package>"#;
+
+    fn epub(parts: &[(&str, &str)]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in parts {
+            // `mimetype` is stored first and uncompressed, as the spec requires
+            let method = if *name == "mimetype" {
+                zip::CompressionMethod::Stored
+            } else {
+                zip::CompressionMethod::Deflated
+            };
+            let options = zip::write::SimpleFileOptions::default().compression_method(method);
+            writer.start_file(*name, options).unwrap();
+            writer.write_all(content.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn converts_chapters_in_spine_order() {
+        let bytes = epub(&[
+            ("mimetype", "application/epub+zip"),
+            ("META-INF/container.xml", CONTAINER),
+            ("OEBPS/content.opf", PACKAGE),
+            (
+                "OEBPS/nav.xhtml",
+                "<html><body><nav>Contents</nav></body></html>",
+            ),
+            (
+                "OEBPS/text/chapter 2.xhtml",
+                "<html><body><h1>Two</h1><p>Second chapter.</p></body></html>",
+            ),
+            (
+                "OEBPS/text/ch1.xhtml",
+                "<html><body><h1>One</h1><p>First chapter.</p></body></html>",
+            ),
+        ]);
+        let result = crate::content::ContentRouter::new()
+            .convert(&bytes, "application/epub+zip")
+            .unwrap();
+
+        let md = &result.markdown;
+        assert!(md.starts_with("# Field Notes\n\n"), "{md}");
+        let one = md.find("First chapter.").unwrap();
+        let two = md.find("Second chapter.").unwrap();
+        assert!(one < two);
+        // Not in the spine
+        assert!(!md.contains("Contents"));
+    }
+
+    #[test]
+    fn sniffed_epub_is_converted() {
+        let bytes = epub(&[
+            ("mimetype", "application/epub+zip"),
+            ("META-INF/container.xml", CONTAINER),
+            ("OEBPS/content.opf", PACKAGE),
+            (
+                "OEBPS/text/ch1.xhtml",
+                "<html><body><p>Only</p></body></html>",
+            ),
+        ]);
+        let result = crate::content::ContentRouter::new()
+            .convert(&bytes, "application/octet-stream")
+            .unwrap();
+        assert_eq!(result.content_type, "application/epub+zip");
+        assert!(result.markdown.contains("Only"));
+    }
+
+    #[test]
+    fn resolves_hrefs_against_the_package() {
+        assert_eq!(resolve("OEBPS", "text/a.xhtml#s1"), "OEBPS/text/a.xhtml");
+        assert_eq!(
+            resolve("OEBPS/pkg", "../text/a.xhtml"),
+            "OEBPS/text/a.xhtml"
+        );
+        assert_eq!(resolve("", "./a%20b.xhtml"), "a b.xhtml");
+    }
+
+    #[test]
+    fn rejects_non_epub() {
+        assert!(EpubHandler.to_markdown(b"not a zip", "").is_err());
+        let empty = epub(&[("mimetype", "application/epub+zip")]);
+        assert!(EpubHandler.to_markdown(&empty, "").is_err());
+    }
+}
@@ -0,0 +1,208 @@
+//! Reproducible content hashes for change monitoring.
+//!
+//! Pages carry values that change on every request (timestamps, CSRF
+//! tokens, nonces, request IDs). [`ContentHasher`] masks those before
+//! hashing, so the SHA-256 only changes when the content itself does.
+//!
+//! ```rust
+//! use nab::content::hash::ContentHasher;
+//!
+//! let hasher = ContentHasher::new();
+//! assert_eq!(
+//!     hasher.hash("Price: 10 EUR\nGenerated 2026-01-25T10:00:00Z"),
+//!     hasher.hash("Price: 10 EUR\nGenerated 2026-01-26T08:30:12Z"),
+//! );
+//! ```
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use sha2::{Digest, Sha256};
+
+/// Replacement for masked volatile values
+const PLACEHOLDER: &str = "<volatile>";
+
+/// Patterns masked by default.
+///
+/// Only values that are volatile by construction: keyed tokens, timestamps to
+/// the second, UUIDs and cache-busting query values. Bare hex strings and
+/// numbers are left alone, since commit hashes, checksums and IDs are content.
+pub const DEFAULT_VOLATILE_PATTERNS: &[&str] = &[
+    // Token assignments: nonce="...", csrf_token: ..., authenticity_token=...
+    r#"(?i)\b(?:nonce|csrf[_-]?token|csrf|xsrf[_-]?token|authenticity_token|_token|request[_-]?id|timestamp)\b["']?\s*[:=]\s*["']?[A-Za-z0-9+/=_.\-]+"#,
+    // ISO 8601 timestamps with seconds; a date or `HH:MM` alone is usually content
+    r"\b\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(?:\.\d+)?(?:Z|[+-]\d{2}:?\d{2})?",
+    // UUIDs
+    r"\b[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}\b",
+    // Cache-busting query values: app.js?v=3f2a9c..., style.css?_=1706...
+    r"(?i)[?&](?:v|ver|t|ts|_|cb|cachebust|rev)=[A-Za-z0-9._\-]+",
+];
+
+/// Normalizes text and hashes it with SHA-256
+#[derive(Debug, Clone)]
+pub struct ContentHasher {
+    patterns: Vec<Regex>,
+}
+
+impl Default for ContentHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ContentHasher {
+    /// Hasher masking [`DEFAULT_VOLATILE_PATTERNS`]
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            patterns: DEFAULT_VOLATILE_PATTERNS
+                .iter()
+                .map(|p| Regex::new(p).expect("default volatile pattern is valid"))
+                .collect(),
+        }
+    }
+
+    /// Hasher that masks nothing but whitespace differences
+    #[must_use]
+    pub fn without_defaults() -> Self {
+        Self {
+            patterns: Vec::new(),
+        }
+    }
+
+    /// Also mask matches of `pattern` (a regex)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `pattern` is not a valid regex.
+    pub fn with_pattern(mut self, pattern: &str) -> Result<Self> {
+        let regex =
+            Regex::new(pattern).with_context(|| format!("Invalid volatile pattern '{pattern}'"))?;
+        self.patterns.push(regex);
+        Ok(self)
+    }
+
+    /// Mask volatile values, collapse whitespace and drop blank lines
+    #[must_use]
+    pub fn normalize(&self, text: &str) -> String {
+        let mut masked = text.to_string();
+        for pattern in &self.patterns {
+            if let std::borrow::Cow::Owned(replaced) = pattern.replace_all(&masked, PLACEHOLDER) {
+                masked = replaced;
+            }
+        }
+        masked
+            .lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Lowercase hex SHA-256 of the normalized text
+    #[must_use]
+    pub fn hash(&self, text: &str) -> String {
+        format!("{:x}", Sha256::digest(self.normalize(text).as_bytes()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{serve, Response};
+
+    use crate::content::ContentRouter;
+    use crate::AcceleratedClient;
+
+    /// Serve a page whose nonce, CSRF token and timestamp change per request
+    fn volatile_page() -> String {
+        let mut n = 0_u64;
+        let addr = serve(move |_| {
+            let page = format!(
+                "<html><body><h1>Release notes</h1>\
+                 <p>Version 2.4 fixes the login bug.</p>\
+                 <p>nonce={n:04}abcXYZ</p>\
+                 <p>csrf_token: tok{n}</p>\
+                 <footer>Generated 2026-01-{:02}T10:{:02}:00Z, request-id: {:x}</footer>\
+                 </body></html>",
+                10 + n,
+                n * 7 % 60,
+                0x1234_5678_9abc_def0_u64 + n
+            );
+            n += 1;
+            Response::typed("text/html", page)
+        });
+        format!("http://{addr}/notes")
+    }
+
+    async fn fetch_markdown(client: &AcceleratedClient, url: &str) -> String {
+        let bytes = client.fetch(url).await.unwrap().bytes().await.unwrap();
+        ContentRouter::new()
+            .convert(&bytes, "text/html")
+            .unwrap()
+            .markdown
+    }
+
+    #[tokio::test]
+    async fn hashes_match_when_only_volatile_values_change() {
+        let url = volatile_page();
+        let client =
+            tokio::task::spawn_blocking(|| AcceleratedClient::from_client(reqwest::Client::new()))
+                .await
+                .unwrap()
+                .unwrap();
+
+        let first = fetch_markdown(&client, &url).await;
+        let second = fetch_markdown(&client, &url).await;
+        assert_ne!(first, second);
+
+        let hasher = ContentHasher::new();
+        assert_eq!(hasher.hash(&first), hasher.hash(&second));
+        assert_eq!(hasher.hash(&first).len(), 64);
+        // Without masking the nonce changes the hash
+        let plain = ContentHasher::without_defaults();
+        assert_ne!(plain.hash(&first), plain.hash(&second));
+    }
+
+    #[test]
+    fn real_changes_and_custom_patterns() {
+        let hasher = ContentHasher::new();
+        assert_ne!(
+            hasher.hash("Version 2.4 fixes the login bug."),
+            hasher.hash("Version 2.5 fixes the login bug.")
+        );
+        assert_eq!(hasher.hash("a  b\n\n  c "), hasher.hash("a b\nc"));
+
+        let hasher = ContentHasher::new()
+            .with_pattern(r"\d+ people viewing")
+            .unwrap();
+        assert_eq!(
+            hasher.hash("Tickets\n12 people viewing"),
+            hasher.hash("Tickets\n40 people viewing")
+        );
+        assert!(ContentHasher::new().with_pattern("(").is_err());
+    }
+
+    #[test]
+    fn hashes_numbers_and_times_that_are_content() {
+        let hasher = ContentHasher::new();
+        for (a, b) in [
+            (
+                "Fixed in 3f2a9c1b7d4e5f60a1b2",
+                "Fixed in 9e8d7c6b5a4f3e2d1c0b",
+            ),
+            (
+                "SHA-256: 9f86d081884c7d659a2feaa0c55ad015",
+                "SHA-256: 60303ae22b998861bce3b28f33eec1be",
+            ),
+            ("Call 1234567890", "Call 1987654321"),
+            ("Meetup 2026-03-01 18:00", "Meetup 2026-03-01 19:00"),
+            ("Due 2026-03-01", "Due 2026-03-08"),
+        ] {
+            assert_ne!(hasher.hash(a), hasher.hash(b), "{a} vs {b}");
+        }
+        assert_eq!(
+            hasher.hash("[logo](/img/logo.png?v=3f2a9c1b) Updated 2026-01-25 10:00:12"),
+            hasher.hash("[logo](/img/logo.png?v=77aa01ce) Updated 2026-01-25 10:04:59")
+        );
+    }
+}
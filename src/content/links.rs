@@ -0,0 +1,186 @@
+//! Link extraction from HTML.
+//!
+//! Collects `<a href>` targets with their anchor text, either as written
+//! in the page ([`extract_links`]) or resolved to absolute URLs against the
+//! page URL and any `<base href>` ([`extract_resolved_links`]).
+
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use scraper::{Html, Selector};
+use serde::Serialize;
+use url::Url;
+
+use crate::AcceleratedClient;
+
+/// A link with its anchor text and absolute target.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Link {
+    /// Anchor text (whitespace-collapsed, may be empty)
+    pub text: String,
+    /// Absolute URL, without fragment
+    pub href: String,
+}
+
+/// Extract `(text, href)` pairs as written in the page.
+///
+/// Skips fragment-only and `javascript:` links and duplicate hrefs.
+pub fn extract_links(html: &str) -> Vec<(String, String)> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("a[href]").unwrap();
+
+    let mut links = Vec::new();
+    let mut seen = HashSet::new();
+
+    for element in document.select(&selector) {
+        if let Some(href) = element.value().attr("href") {
+            // Skip anchors, javascript, and duplicates
+            if href.starts_with('#') || href.starts_with("javascript:") || seen.contains(href) {
+                continue;
+            }
+            seen.insert(href.to_string());
+
+            let text = element
+                .text()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .trim()
+                .to_string();
+
+            links.push((text, href.to_string()));
+        }
+    }
+
+    links
+}
+
+/// Extract links resolved against `base_url`, keeping only `http(s)` targets.
+///
+/// A `<base href>` in the page takes precedence over `base_url`. Fragments
+/// are dropped and duplicates removed after resolution, so `/a` and
+/// `https://host/a#top` count once. With `same_host`, links to other hosts
+/// are left out.
+pub fn extract_resolved_links(html: &str, base_url: &Url, same_host: bool) -> Vec<Link> {
+    let base = page_base(html, base_url);
+    let mut seen = HashSet::new();
+
+    extract_links(html)
+        .into_iter()
+        .filter_map(|(text, href)| {
+            let mut url = base.join(href.trim()).ok()?;
+            if !matches!(url.scheme(), "http" | "https") {
+                return None;
+            }
+            if same_host && url.host_str() != base_url.host_str() {
+                return None;
+            }
+            url.set_fragment(None);
+            let href = String::from(url);
+            seen.insert(href.clone()).then(|| Link {
+                text: text.split_whitespace().collect::<Vec<_>>().join(" "),
+                href,
+            })
+        })
+        .collect()
+}
+
+/// Fetch a page and extract its resolved links.
+///
+/// Relative links resolve against the final URL after redirects.
+pub async fn fetch_links(
+    client: &AcceleratedClient,
+    url: &str,
+    same_host: bool,
+) -> Result<Vec<Link>> {
+    let response = client.fetch(url).await?;
+    let status = response.status();
+    if !status.is_success() {
+        anyhow::bail!("HTTP {} from {url}", status.as_u16());
+    }
+    let final_url = response.url().clone();
+    let html = response
+        .text()
+        .await
+        .with_context(|| format!("Failed to read body of {url}"))?;
+    Ok(extract_resolved_links(&html, &final_url, same_host))
+}
+
+/// Resolve the document's `<base href>`, if any, against the page URL.
+fn page_base(html: &str, page_url: &Url) -> Url {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("base[href]").unwrap();
+    document
+        .select(&selector)
+        .next()
+        .and_then(|base| base.value().attr("href"))
+        .and_then(|href| page_url.join(href.trim()).ok())
+        .unwrap_or_else(|| page_url.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{http1_client, serve, Response};
+
+    const PAGE: &str = r##"<html><body>
+        <a href="/docs/">Docs</a>
+        <a href="guide.html#intro">Getting
+            started</a>
+        <a href="https://other.example/x">Elsewhere</a>
+        <a href="/docs/#top">Docs again</a>
+        <a href="#main">Skip</a>
+        <a href="javascript:void(0)">Menu</a>
+        <a href="mailto:team@example.com">Mail</a>
+    </body></html>"##;
+
+    #[test]
+    fn test_extract_links_keeps_hrefs_as_written() {
+        let links = extract_links(PAGE);
+        assert_eq!(links.len(), 5);
+        assert_eq!(links[0], ("Docs".to_string(), "/docs/".to_string()));
+    }
+
+    #[test]
+    fn test_resolved_links_honor_base_element() {
+        let html = r#"<head><base href="https://cdn.example/v2/"></head>
+            <a href="page">Page</a>"#;
+        let links =
+            extract_resolved_links(html, &Url::parse("https://example.com/a/b").unwrap(), false);
+        assert_eq!(links[0].href, "https://cdn.example/v2/page");
+    }
+
+    fn mock_page() -> String {
+        let addr = serve(|_| Response::typed("text/html", PAGE));
+        format!("http://{addr}/blog/post")
+    }
+
+    #[tokio::test]
+    async fn test_fetch_links_resolves_and_filters_hosts() {
+        let url = mock_page();
+        let origin = url.trim_end_matches("/blog/post");
+        let client = http1_client().await;
+
+        let links = fetch_links(&client, &url, false).await.unwrap();
+        assert_eq!(
+            links,
+            [
+                Link {
+                    text: "Docs".into(),
+                    href: format!("{origin}/docs/"),
+                },
+                Link {
+                    text: "Getting started".into(),
+                    href: format!("{origin}/blog/guide.html"),
+                },
+                Link {
+                    text: "Elsewhere".into(),
+                    href: "https://other.example/x".into(),
+                },
+            ]
+        );
+
+        let same_host = fetch_links(&client, &url, true).await.unwrap();
+        assert_eq!(same_host.len(), 2);
+        assert!(same_host.iter().all(|link| link.href.starts_with(origin)));
+    }
+}
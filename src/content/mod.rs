@@ -10,8 +10,13 @@
 //! |------|---------|-------------|
 //! | `text/html`, `application/xhtml+xml` | [`HtmlHandler`] | always |
 //! | `application/pdf` | [`PdfHandler`] | `pdf` |
+//! | `application/vnd.openxmlformats-officedocument.wordprocessingml.document` | [`DocxHandler`] | `docx` |
+//! | `application/epub+zip` | [`EpubHandler`] | `epub` |
+//! | `application/x-ndjson`, `application/jsonl` | [`NdjsonHandler`] | always |
 //! | `text/plain`, `application/json`, etc. | [`PlainHandler`] | always |
 //!
+//! Bodies with a missing or generic type are sniffed by [`sniff::detect_content_type`].
+//!
 //! # Example
 //!
 //! ```rust
@@ -23,16 +28,29 @@
 //! assert!(result.markdown.contains("Hello"));
 //! ```
 
+#[cfg(feature = "docx")]
+pub mod docx;
+#[cfg(feature = "epub")]
+pub mod epub;
+pub mod hash;
 pub mod html;
+pub mod links;
+pub mod ndjson;
 #[cfg(feature = "pdf")]
 pub mod pdf;
 pub mod plain;
+pub mod sniff;
 #[cfg(feature = "pdf")]
 pub mod table;
 #[cfg(feature = "pdf")]
 pub mod types;
 
-use anyhow::Result;
+use std::io::Read;
+
+use anyhow::{bail, Result};
+
+/// Maximum size a gzip body is decompressed to, guarding against gzip bombs.
+const MAX_GUNZIP_SIZE: u64 = 200 * 1024 * 1024;
 
 /// Metadata about a content conversion result.
 #[derive(Debug, Clone)]
@@ -41,7 +59,8 @@ pub struct ConversionResult {
     pub markdown: String,
     /// Number of pages (for paginated formats like PDF).
     pub page_count: Option<usize>,
-    /// Original content type.
+    /// Content type the body was converted as (sniffed when the declared
+    /// one was missing or generic).
     pub content_type: String,
     /// Conversion time in milliseconds.
     pub elapsed_ms: f64,
@@ -75,47 +94,68 @@ pub struct ContentRouter {
 impl ContentRouter {
     /// Create a router with all available handlers.
     ///
-    /// PDF handler is included only when the `pdf` feature flag is enabled.
+    /// PDF, DOCX and EPUB handlers are included only when the `pdf`, `docx`
+    /// and `epub` feature flags are enabled.
     pub fn new() -> Self {
-        #[cfg(feature = "pdf")]
         let handlers: Vec<Box<dyn ContentHandler>> = vec![
+            #[cfg(feature = "pdf")]
             Box::new(pdf::PdfHandler::new()),
+            #[cfg(feature = "docx")]
+            Box::new(docx::DocxHandler),
+            #[cfg(feature = "epub")]
+            Box::new(epub::EpubHandler),
             Box::new(html::HtmlHandler),
+            Box::new(ndjson::NdjsonHandler),
             Box::new(plain::PlainHandler),
         ];
 
-        #[cfg(not(feature = "pdf"))]
-        let handlers: Vec<Box<dyn ContentHandler>> =
-            vec![Box::new(html::HtmlHandler), Box::new(plain::PlainHandler)];
-
         Self { handlers }
     }
 
     /// Find a handler for the given content type and convert the bytes.
     ///
-    /// Falls back to HTML if the bytes look like HTML (common for responses
-    /// with missing or incorrect `Content-Type`). Ultimate fallback is
+    /// Missing or generic types are sniffed from the body (see [`sniff`]),
+    /// gzip bodies are decompressed first, and bytes that look like HTML go
+    /// to the HTML handler whatever their type. Ultimate fallback is
     /// [`PlainHandler`].
+    #[tracing::instrument(name = "content_convert", skip(self, bytes), fields(bytes = bytes.len()))]
     pub fn convert(&self, bytes: &[u8], content_type: &str) -> Result<ConversionResult> {
-        let mime = content_type
-            .split(';')
-            .next()
-            .unwrap_or(content_type)
-            .trim()
-            .to_lowercase();
+        let (mime, content_type) = Self::dispatch_type(bytes, content_type);
 
-        for handler in &self.handlers {
-            if handler.supported_types().iter().any(|t| *t == mime) {
-                return handler.to_markdown(bytes, content_type);
+        // A gzip body the client didn't decode: unpack and route the contents,
+        // once; a gzip inside it is not unpacked again
+        if mime == "application/gzip" {
+            let mut decoded = Vec::new();
+            if flate2::read::GzDecoder::new(bytes)
+                .take(MAX_GUNZIP_SIZE + 1)
+                .read_to_end(&mut decoded)
+                .is_ok()
+            {
+                if decoded.len() as u64 > MAX_GUNZIP_SIZE {
+                    bail!(
+                        "Decompressed body exceeds {} MB",
+                        MAX_GUNZIP_SIZE / 1024 / 1024
+                    );
+                }
+                let (mime, content_type) = Self::dispatch_type(&decoded, "");
+                return self.convert_as(&decoded, &mime, &content_type);
             }
         }
 
-        // Fallback: if bytes look like HTML (common for missing Content-Type)
-        if bytes.starts_with(b"<!") || bytes.starts_with(b"<html") || bytes.starts_with(b"<HTML") {
+        self.convert_as(bytes, &mime, &content_type)
+    }
+
+    /// Convert `bytes` with the handler for `mime`, else as HTML if they look
+    /// like it, else as plain text
+    fn convert_as(&self, bytes: &[u8], mime: &str, content_type: &str) -> Result<ConversionResult> {
+        if let Some(handler) = self.handler_for(mime) {
+            return handler.to_markdown(bytes, content_type);
+        }
+
+        // Fallback: unknown declared type, but the bytes look like HTML
+        if sniff::detect_content_type(bytes) == Some("text/html") {
             return self
-                .handlers
-                .iter()
-                .find(|h| h.supported_types().contains(&"text/html"))
+                .handler_for("text/html")
                 .expect("HtmlHandler always registered")
                 .to_markdown(bytes, "text/html");
         }
@@ -123,6 +163,41 @@ impl ContentRouter {
         // Ultimate fallback: plain text passthrough
         plain::PlainHandler.to_markdown(bytes, content_type)
     }
+
+    /// MIME type (without parameters) and content type to convert `bytes` as
+    ///
+    /// A missing or generic declared type (`application/octet-stream`,
+    /// `text/plain`) is replaced by a sniffed one when the body is recognised.
+    fn dispatch_type(bytes: &[u8], content_type: &str) -> (String, String) {
+        let mime = content_type
+            .split(';')
+            .next()
+            .unwrap_or(content_type)
+            .trim()
+            .to_lowercase();
+
+        match sniff::detect_content_type(bytes) {
+            Some(sniffed) if sniff::is_generic_content_type(&mime) => {
+                tracing::debug!(declared = %mime, sniffed, "Sniffed content type");
+                (sniffed.to_string(), sniffed.to_string())
+            }
+            _ => (mime, content_type.to_string()),
+        }
+    }
+
+    /// Whether a registered handler converts `content_type` itself, rather
+    /// than it falling back to HTML sniffing or plain text
+    pub fn supports(&self, content_type: &str) -> bool {
+        let mime = content_type.split(';').next().unwrap_or_default().trim();
+        self.handler_for(&mime.to_lowercase()).is_some()
+    }
+
+    fn handler_for(&self, mime: &str) -> Option<&dyn ContentHandler> {
+        self.handlers
+            .iter()
+            .find(|h| h.supported_types().contains(&mime))
+            .map(AsRef::as_ref)
+    }
 }
 
 impl Default for ContentRouter {
@@ -186,6 +261,70 @@ mod tests {
         assert!(result.markdown.contains("Fallback"));
     }
 
+    #[test]
+    fn router_sniffs_pdf_served_as_octet_stream() {
+        let pdf = b"%PDF-1.4\n1 0 obj\n<< /Type /Catalog >>\nendobj\n%%EOF";
+        let (mime, _) = ContentRouter::dispatch_type(pdf, "application/octet-stream");
+        assert_eq!(mime, "application/pdf");
+
+        #[cfg(feature = "pdf")]
+        assert_eq!(
+            ContentRouter::new()
+                .handler_for(&mime)
+                .unwrap()
+                .supported_types(),
+            &["application/pdf"]
+        );
+    }
+
+    #[test]
+    fn router_sniffs_json_served_as_text_plain() {
+        let router = ContentRouter::new();
+        let result = router
+            .convert(br#"{"user": "ada", "id": 7}"#, "text/plain; charset=utf-8")
+            .unwrap();
+        assert_eq!(result.content_type, "application/json");
+        assert!(result.markdown.contains(r#""user""#));
+
+        // Real plain text keeps its declared type
+        let result = router.convert(b"Hello", "text/plain").unwrap();
+        assert_eq!(result.content_type, "text/plain");
+    }
+
+    #[test]
+    fn router_decompresses_gzip_bodies() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder
+            .write_all(b"<html><body><h1>Packed</h1></body></html>")
+            .unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        let result = ContentRouter::new()
+            .convert(&gzipped, "application/octet-stream")
+            .unwrap();
+        assert_eq!(result.content_type, "text/html");
+        assert!(result.markdown.contains("Packed"));
+    }
+
+    #[test]
+    fn router_unpacks_gzip_only_once() {
+        use std::io::Write;
+
+        let gzip = |bytes: &[u8]| {
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(bytes).unwrap();
+            encoder.finish().unwrap()
+        };
+        let inner = gzip(b"<html><body><h1>Packed</h1></body></html>");
+        let result = ContentRouter::new()
+            .convert(&gzip(&inner), "application/gzip")
+            .unwrap();
+        assert!(!result.markdown.contains("Packed"));
+    }
+
     #[test]
     fn router_falls_back_to_plain_for_unknown() {
         let router = ContentRouter::new();
@@ -0,0 +1,161 @@
+//! Newline-delimited JSON (NDJSON / JSON Lines) handler.
+//!
+//! Streaming APIs send one JSON value per line. [`NdjsonDecoder`] splits a
+//! body, possibly arriving in chunks, into those values; [`NdjsonHandler`]
+//! renders a complete body as a single JSON array.
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+
+use super::{ContentHandler, ConversionResult};
+
+/// Incrementally decodes newline-delimited JSON values.
+///
+/// A line split across chunks is buffered until its newline arrives.
+#[derive(Debug, Default)]
+pub struct NdjsonDecoder {
+    buffer: Vec<u8>,
+    line: usize,
+}
+
+impl NdjsonDecoder {
+    /// Create an empty decoder.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Consume a chunk, returning one result per complete non-blank line.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<Result<Value>> {
+        self.buffer.extend_from_slice(chunk);
+        let Some(last_newline) = self.buffer.iter().rposition(|&b| b == b'\n') else {
+            return Vec::new();
+        };
+        let complete: Vec<u8> = self.buffer.drain(..=last_newline).collect();
+        complete[..last_newline]
+            .split(|&b| b == b'\n')
+            .filter_map(|line| self.parse_line(line))
+            .collect()
+    }
+
+    /// Parse whatever follows the last newline.
+    ///
+    /// A final line without a trailing newline is returned if it is complete
+    /// JSON; a truncated one (e.g. from an interrupted stream) is an error.
+    pub fn finish(mut self) -> Option<Result<Value>> {
+        let rest = std::mem::take(&mut self.buffer);
+        let line_no = self.line + 1;
+        self.parse_line(&rest)
+            .map(|result| result.with_context(|| format!("Truncated NDJSON line {line_no}")))
+    }
+
+    fn parse_line(&mut self, line: &[u8]) -> Option<Result<Value>> {
+        self.line += 1;
+        let line = line.trim_ascii();
+        if line.is_empty() {
+            return None;
+        }
+        let line_no = self.line;
+        Some(
+            serde_json::from_slice(line)
+                .with_context(|| format!("Invalid JSON on NDJSON line {line_no}")),
+        )
+    }
+}
+
+/// Renders NDJSON bodies as a pretty-printed JSON array.
+///
+/// Lines that fail to parse, including a truncated trailing line, are
+/// skipped with a warning rather than failing the whole conversion.
+pub struct NdjsonHandler;
+
+impl ContentHandler for NdjsonHandler {
+    fn supported_types(&self) -> &[&str] {
+        &[
+            "application/x-ndjson",
+            "application/ndjson",
+            "application/jsonl",
+            "application/x-jsonlines",
+        ]
+    }
+
+    fn to_markdown(&self, bytes: &[u8], content_type: &str) -> Result<ConversionResult> {
+        let start = std::time::Instant::now();
+
+        let mut decoder = NdjsonDecoder::new();
+        let mut results = decoder.feed(bytes);
+        results.extend(decoder.finish());
+
+        let mut values = Vec::with_capacity(results.len());
+        for result in results {
+            match result {
+                Ok(value) => values.push(value),
+                Err(e) => tracing::warn!("Skipping NDJSON line: {e:#}"),
+            }
+        }
+
+        Ok(ConversionResult {
+            markdown: serde_json::to_string_pretty(&values)?,
+            page_count: None,
+            content_type: content_type.to_string(),
+            elapsed_ms: start.elapsed().as_secs_f64() * 1000.0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const BODY: &[u8] =
+        b"{\"id\":1,\"msg\":\"a\"}\n{\"id\":2,\"msg\":\"b\"}\r\n\n{\"id\":3,\"msg\":\"c\"}\n{\"id\":4,\"ms";
+
+    #[test]
+    fn handler_renders_complete_lines_and_drops_partial_tail() {
+        let result = NdjsonHandler
+            .to_markdown(BODY, "application/x-ndjson")
+            .unwrap();
+        let values: Value = serde_json::from_str(&result.markdown).unwrap();
+        assert_eq!(
+            values,
+            json!([
+                {"id": 1, "msg": "a"},
+                {"id": 2, "msg": "b"},
+                {"id": 3, "msg": "c"},
+            ])
+        );
+    }
+
+    #[test]
+    fn decoder_buffers_lines_split_across_chunks() {
+        let mut decoder = NdjsonDecoder::new();
+        let mut ids = Vec::new();
+        for chunk in BODY.chunks(7) {
+            for value in decoder.feed(chunk) {
+                ids.push(value.unwrap()["id"].clone());
+            }
+        }
+        assert_eq!(ids, [json!(1), json!(2), json!(3)]);
+
+        let tail = decoder.finish().unwrap().unwrap_err();
+        assert!(tail.to_string().contains("Truncated NDJSON line 5"));
+    }
+
+    #[test]
+    fn decoder_accepts_final_line_without_newline() {
+        let mut decoder = NdjsonDecoder::new();
+        assert_eq!(decoder.feed(b"[1,2]\n\"x\"").len(), 1);
+        assert_eq!(decoder.finish().unwrap().unwrap(), json!("x"));
+        assert!(NdjsonDecoder::new().finish().is_none());
+    }
+
+    #[test]
+    fn router_dispatches_ndjson() {
+        let result = crate::content::ContentRouter::new()
+            .convert(BODY, "application/x-ndjson; charset=utf-8")
+            .unwrap();
+        assert!(result.markdown.starts_with('['));
+        assert!(result.markdown.contains("\"msg\": \"c\""));
+    }
+}
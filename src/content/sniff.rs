@@ -0,0 +1,191 @@
+//! Content-type sniffing for responses with a missing or generic type.
+//!
+//! Servers often label PDFs `application/octet-stream` or JSON `text/plain`.
+//! [`detect_content_type`] inspects magic bytes and the first KB of the body
+//! so the router can pick the right handler anyway.
+
+/// How much of the body the text heuristics look at
+const SNIFF_LEN: usize = 1024;
+
+/// Declared types that say nothing reliable about the body
+const GENERIC_TYPES: &[&str] = &[
+    "",
+    "application/octet-stream",
+    "binary/octet-stream",
+    "application/unknown",
+    "application/x-unknown",
+    "unknown/unknown",
+    "text/plain",
+];
+
+/// Whether a declared MIME type (without parameters) should be sniffed
+#[must_use]
+pub fn is_generic_content_type(mime: &str) -> bool {
+    GENERIC_TYPES.contains(&mime.trim().to_ascii_lowercase().as_str())
+}
+
+/// Guess the MIME type of `bytes` from its magic bytes and leading text
+///
+/// Returns `None` when nothing recognisable is found (plain text or unknown
+/// binary data).
+#[must_use]
+pub fn detect_content_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"%PDF-") {
+        return Some("application/pdf");
+    }
+    if bytes.starts_with(b"\x1f\x8b") {
+        return Some("application/gzip");
+    }
+    if bytes.starts_with(b"PK\x03\x04") {
+        return Some(detect_zip_type(bytes));
+    }
+
+    let text = strip_bom(bytes);
+    let head = &text[..text.len().min(SNIFF_LEN)];
+    let start = head.trim_ascii_start();
+
+    if looks_like_json(start) {
+        return Some("application/json");
+    }
+    if looks_like_html(start) {
+        return Some("text/html");
+    }
+    if starts_with_ignore_case(start, b"<?xml") {
+        return Some(if contains_ignore_case(head, b"<html") {
+            "text/html"
+        } else {
+            "application/xml"
+        });
+    }
+    None
+}
+
+/// Tell EPUB and Office documents apart from plain ZIP archives
+fn detect_zip_type(bytes: &[u8]) -> &'static str {
+    let head = &bytes[..bytes.len().min(SNIFF_LEN * 4)];
+    // EPUB requires an uncompressed `mimetype` entry first
+    if head
+        .get(30..)
+        .is_some_and(|rest| rest.starts_with(b"mimetypeapplication/epub+zip"))
+    {
+        "application/epub+zip"
+    } else if contains_ignore_case(head, b"word/") {
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+    } else if contains_ignore_case(head, b"xl/") {
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+    } else if contains_ignore_case(head, b"ppt/") {
+        "application/vnd.openxmlformats-officedocument.presentationml.presentation"
+    } else {
+        "application/zip"
+    }
+}
+
+fn strip_bom(bytes: &[u8]) -> &[u8] {
+    [&b"\xEF\xBB\xBF"[..], b"\xFF\xFE", b"\xFE\xFF"]
+        .iter()
+        .find_map(|bom| bytes.strip_prefix(*bom))
+        .unwrap_or(bytes)
+}
+
+/// An object opening with a key or closing, or an array opening with a value
+fn looks_like_json(start: &[u8]) -> bool {
+    let next = |rest: &[u8]| rest.trim_ascii_start().first().copied();
+    match start.split_first() {
+        Some((b'{', rest)) => matches!(next(rest), Some(b'"' | b'}')),
+        Some((b'[', rest)) => matches!(
+            next(rest),
+            Some(b'{' | b'[' | b']' | b'"' | b'-' | b'0'..=b'9' | b't' | b'f' | b'n')
+        ),
+        _ => false,
+    }
+}
+
+fn looks_like_html(start: &[u8]) -> bool {
+    [
+        &b"<!doctype html"[..],
+        b"<html",
+        b"<head",
+        b"<body",
+        b"<!--",
+        b"<script",
+        b"<title",
+        b"<div",
+        b"<p>",
+    ]
+    .iter()
+    .any(|tag| starts_with_ignore_case(start, tag))
+}
+
+fn starts_with_ignore_case(haystack: &[u8], prefix: &[u8]) -> bool {
+    haystack
+        .get(..prefix.len())
+        .is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+}
+
+fn contains_ignore_case(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window.eq_ignore_ascii_case(needle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_magic_bytes() {
+        assert_eq!(
+            detect_content_type(b"%PDF-1.7\n%\xE2\xE3\xCF\xD3\n1 0 obj"),
+            Some("application/pdf")
+        );
+        assert_eq!(
+            detect_content_type(b"\x1f\x8b\x08\x00\x00\x00"),
+            Some("application/gzip")
+        );
+
+        let mut epub = b"PK\x03\x04".to_vec();
+        epub.resize(30, 0);
+        epub.extend_from_slice(b"mimetypeapplication/epub+zip");
+        assert_eq!(detect_content_type(&epub), Some("application/epub+zip"));
+
+        let mut docx = b"PK\x03\x04".to_vec();
+        docx.resize(30, 0);
+        docx.extend_from_slice(b"word/document.xml");
+        assert!(detect_content_type(&docx)
+            .unwrap()
+            .contains("wordprocessingml"));
+        assert_eq!(
+            detect_content_type(b"PK\x03\x04\x14\x00data.csv"),
+            Some("application/zip")
+        );
+    }
+
+    #[test]
+    fn detects_text_formats() {
+        assert_eq!(
+            detect_content_type(b"\xEF\xBB\xBF  {\"items\": []}"),
+            Some("application/json")
+        );
+        assert_eq!(detect_content_type(b"[\n  1, 2]"), Some("application/json"));
+        assert_eq!(
+            detect_content_type(b"\n<!DOCTYPE HTML><html>"),
+            Some("text/html")
+        );
+        assert_eq!(
+            detect_content_type(b"<?xml version=\"1.0\"?><rss></rss>"),
+            Some("application/xml")
+        );
+        assert_eq!(detect_content_type(b"{not json"), None);
+        assert_eq!(detect_content_type(b"[citation needed]"), None);
+        assert_eq!(detect_content_type(b"Just some words"), None);
+    }
+
+    #[test]
+    fn generic_types() {
+        assert!(is_generic_content_type("application/octet-stream"));
+        assert!(is_generic_content_type(""));
+        assert!(is_generic_content_type("Text/Plain"));
+        assert!(!is_generic_content_type("text/html"));
+        assert!(!is_generic_content_type("application/json"));
+    }
+}
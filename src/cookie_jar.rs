@@ -0,0 +1,427 @@
+//! In-memory cookie jar shared by every request of a client
+//!
+//! [`CookieJar`] plugs into reqwest as its cookie store, so `Set-Cookie`
+//! values from a warmup request, a login form or any hop of a redirect chain
+//! are sent on later requests to matching hosts. Domain, path, `Secure` and
+//! expiry are honoured as in RFC 6265, and a `Domain` that is a public suffix
+//! (`com`, `co.uk`, `github.io`) is refused as browsers do. The jar can be
+//! exported in the Netscape `cookies.txt` format read by curl and wget.
+
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+
+use chrono::{DateTime, Utc};
+use publicsuffix::Psl;
+use reqwest::header::HeaderValue;
+use url::Url;
+
+use crate::http_client::SetCookie;
+
+/// Mozilla's Public Suffix List, bundled from <https://publicsuffix.org/list/>
+///
+/// The first line of `data/public_suffix_list.dat` says when the snapshot was
+/// taken; `scripts/update-public-suffix-list.sh` refreshes it.
+static PUBLIC_SUFFIXES: LazyLock<publicsuffix::List> = LazyLock::new(|| {
+    include_str!("../data/public_suffix_list.dat")
+        .parse()
+        .expect("bundled public suffix list parses")
+});
+
+/// Cookies received from servers, keyed by name, domain and path
+#[derive(Debug)]
+pub struct CookieJar {
+    cookies: Mutex<Vec<StoredCookie>>,
+    enabled: AtomicBool,
+}
+
+#[derive(Debug, Clone)]
+struct StoredCookie {
+    name: String,
+    value: String,
+    /// Lowercase, without a leading dot
+    domain: String,
+    /// No `Domain` attribute: only the exact host gets the cookie
+    host_only: bool,
+    path: String,
+    expires: Option<DateTime<Utc>>,
+    secure: bool,
+    http_only: bool,
+}
+
+impl Default for CookieJar {
+    fn default() -> Self {
+        Self {
+            cookies: Mutex::default(),
+            enabled: AtomicBool::new(true),
+        }
+    }
+}
+
+impl CookieJar {
+    /// Create an empty jar
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start or stop keeping cookies; stopping also empties the jar
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.lock().clear();
+        }
+    }
+
+    /// Whether responses' cookies are being kept
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Number of cookies held, including expired ones not yet evicted
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether the jar holds no cookies
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Store a `Set-Cookie` value received from `url`
+    ///
+    /// Cookies whose `Domain` doesn't cover `url`'s host or is a public suffix
+    /// are rejected (a public suffix naming the host itself makes a host-only
+    /// cookie), and an already expired cookie deletes the stored one of the
+    /// same name.
+    pub fn store(&self, set_cookie: &str, url: &Url) {
+        self.store_at(set_cookie, url, Utc::now());
+    }
+
+    fn store_at(&self, set_cookie: &str, url: &Url, now: DateTime<Utc>) {
+        if !self.is_enabled() {
+            return;
+        }
+        let Some(cookie) = SetCookie::parse_at(set_cookie, now) else {
+            return;
+        };
+        let Some(host) = url.host_str().map(str::to_ascii_lowercase) else {
+            return;
+        };
+        let attribute = cookie
+            .domain
+            .as_deref()
+            .map(|domain| domain.trim_start_matches('.').to_ascii_lowercase())
+            .filter(|domain| !domain.is_empty());
+        let (domain, host_only) = match attribute {
+            Some(domain) if is_public_suffix(&domain) && domain == host => (host, true),
+            Some(domain) if is_public_suffix(&domain) => {
+                tracing::debug!(host, domain, "Ignoring cookie for a public suffix");
+                return;
+            }
+            Some(domain) if domain_matches(&host, &domain) => (domain, false),
+            Some(domain) => {
+                tracing::debug!(host, domain, "Ignoring cookie for a foreign domain");
+                return;
+            }
+            None => (host, true),
+        };
+        let path = cookie
+            .path
+            .filter(|path| path.starts_with('/'))
+            .unwrap_or_else(|| default_path(url));
+
+        let mut cookies = self.lock();
+        cookies.retain(|c| !(c.name == cookie.name && c.domain == domain && c.path == path));
+        if cookie.expires.is_some_and(|expires| expires <= now) {
+            return;
+        }
+        cookies.push(StoredCookie {
+            name: cookie.name,
+            value: cookie.value,
+            domain,
+            host_only,
+            path,
+            expires: cookie.expires,
+            secure: cookie.secure,
+            http_only: cookie.http_only,
+        });
+    }
+
+    /// `Cookie` header value for a request to `url`, empty when no cookie
+    /// applies
+    ///
+    /// Longer paths come first, as browsers send them.
+    pub fn header_for(&self, url: &Url) -> String {
+        self.pairs_for(url, Utc::now())
+            .into_iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    /// Combine an explicit `Cookie` header (e.g. browser cookies) with the
+    /// jar's cookies for `url`; the jar's value wins for a name in both
+    pub fn merge_header(&self, url: &Url, explicit: &str) -> String {
+        let mut jar = self.pairs_for(url, Utc::now());
+        let mut merged = Vec::new();
+        for pair in explicit.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+            let name = pair.split_once('=').map_or(pair, |(name, _)| name.trim());
+            match jar.iter().position(|(jar_name, _)| jar_name == name) {
+                Some(i) => {
+                    let (name, value) = jar.remove(i);
+                    merged.push(format!("{name}={value}"));
+                }
+                None => merged.push(pair.to_string()),
+            }
+        }
+        merged.extend(
+            jar.into_iter()
+                .map(|(name, value)| format!("{name}={value}")),
+        );
+        merged.join("; ")
+    }
+
+    fn pairs_for(&self, url: &Url, now: DateTime<Utc>) -> Vec<(String, String)> {
+        let Some(host) = url.host_str().map(str::to_ascii_lowercase) else {
+            return Vec::new();
+        };
+        let secure = url.scheme() == "https" || url.scheme() == "wss";
+        let mut cookies = self.lock();
+        cookies.retain(|c| c.expires.is_none_or(|expires| expires > now));
+        let mut matching: Vec<&StoredCookie> = cookies
+            .iter()
+            .filter(|c| {
+                let domain_ok = if c.host_only {
+                    host == c.domain
+                } else {
+                    domain_matches(&host, &c.domain)
+                };
+                domain_ok && path_matches(url.path(), &c.path) && (secure || !c.secure)
+            })
+            .collect();
+        matching.sort_by_key(|c| std::cmp::Reverse(c.path.len()));
+        matching
+            .into_iter()
+            .map(|c| (c.name.clone(), c.value.clone()))
+            .collect()
+    }
+
+    /// Every unexpired cookie in Netscape `cookies.txt` format
+    ///
+    /// `HttpOnly` cookies get curl's `#HttpOnly_` domain prefix; session
+    /// cookies have expiry `0`.
+    pub fn to_netscape(&self) -> String {
+        let now = Utc::now();
+        let mut out = String::from("# Netscape HTTP Cookie File\n# Exported by nab\n\n");
+        for cookie in self
+            .lock()
+            .iter()
+            .filter(|c| c.expires.is_none_or(|expires| expires > now))
+        {
+            let flag = |set: bool| if set { "TRUE" } else { "FALSE" };
+            let _ = writeln!(
+                out,
+                "{}{}{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                if cookie.http_only { "#HttpOnly_" } else { "" },
+                if cookie.host_only { "" } else { "." },
+                cookie.domain,
+                flag(!cookie.host_only),
+                cookie.path,
+                flag(cookie.secure),
+                cookie.expires.map_or(0, |expires| expires.timestamp()),
+                cookie.name,
+                cookie.value,
+            );
+        }
+        out
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<StoredCookie>> {
+        self.cookies
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl reqwest::cookie::CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        // Preconnect probes leave the jar as it was
+        if crate::http_client::preconnecting() {
+            return;
+        }
+        let now = Utc::now();
+        for header in cookie_headers {
+            if let Ok(value) = header.to_str() {
+                self.store_at(value, url, now);
+            }
+        }
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        if crate::http_client::preconnecting() {
+            return None;
+        }
+        let header = self.header_for(url);
+        if header.is_empty() {
+            return None;
+        }
+        HeaderValue::from_str(&header).ok()
+    }
+}
+
+/// `host` is `domain` or one of its subdomains, ignoring ASCII case
+/// (RFC 6265 domain-match)
+///
+/// `www.example.com` and `example.com` match `example.com`;
+/// `notexample.com` doesn't.
+pub fn domain_matches(host: &str, domain: &str) -> bool {
+    let Some(at) = host.len().checked_sub(domain.len()) else {
+        return false;
+    };
+    !domain.is_empty()
+        && host.as_bytes()[at..].eq_ignore_ascii_case(domain.as_bytes())
+        && (at == 0 || host.as_bytes()[at - 1] == b'.')
+}
+
+/// `domain` is a public suffix, so a cookie for it would reach unrelated sites
+fn is_public_suffix(domain: &str) -> bool {
+    PUBLIC_SUFFIXES
+        .suffix(domain.as_bytes())
+        .is_some_and(|suffix| suffix.as_bytes() == domain.as_bytes())
+}
+
+/// RFC 6265 path match: `/docs` covers `/docs` and `/docs/a`, not `/docsx`
+fn path_matches(request_path: &str, cookie_path: &str) -> bool {
+    request_path == cookie_path
+        || request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || request_path[cookie_path.len()..].starts_with('/'))
+}
+
+/// Directory of the request path, used when `Set-Cookie` has no `Path`
+fn default_path(url: &Url) -> String {
+    match url.path().rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(end) => url.path()[..end].to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(s: &str) -> Url {
+        Url::parse(s).unwrap()
+    }
+
+    #[test]
+    fn matches_domain_and_path() {
+        let jar = CookieJar::new();
+        let login = url("https://login.example.com/account/signin");
+        jar.store("sid=1; Domain=.example.com; Path=/", &login);
+        jar.store("host=2", &login);
+        jar.store("scoped=3; Path=/account/settings", &login);
+        jar.store("foreign=4; Domain=other.com", &login);
+
+        assert_eq!(jar.len(), 3);
+        assert_eq!(jar.header_for(&url("https://www.example.com/")), "sid=1");
+        assert_eq!(
+            jar.header_for(&url("https://login.example.com/account/x")),
+            "host=2; sid=1"
+        );
+        assert_eq!(
+            jar.header_for(&url("https://login.example.com/account/settings/a")),
+            "scoped=3; host=2; sid=1"
+        );
+        assert_eq!(jar.header_for(&url("https://notexample.com/")), "");
+        assert!(!path_matches("/account/settingsx", "/account/settings"));
+    }
+
+    #[test]
+    fn domain_match_is_suffix_on_a_label_boundary() {
+        assert!(domain_matches("www.Example.com", "example.COM"));
+        assert!(domain_matches("example.com", "example.com"));
+        assert!(!domain_matches("notexample.com", "example.com"));
+        assert!(!domain_matches("com", "example.com"));
+        assert!(!domain_matches("example.com.", ""));
+    }
+
+    #[test]
+    fn rejects_public_suffix_domains() {
+        let jar = CookieJar::new();
+        jar.store("a=1; Domain=com", &url("https://example.com/"));
+        jar.store("b=2; Domain=.co.uk", &url("https://shop.example.co.uk/"));
+        jar.store("c=3; Domain=github.io", &url("https://user.github.io/"));
+        assert!(jar.is_empty());
+        assert_eq!(jar.header_for(&url("https://other.co.uk/")), "");
+
+        // A public suffix naming the host itself only reaches that host
+        jar.store("d=4; Domain=github.io", &url("https://github.io/"));
+        assert_eq!(jar.header_for(&url("https://github.io/")), "d=4");
+        assert_eq!(jar.header_for(&url("https://user.github.io/")), "");
+
+        jar.store(
+            "e=5; Domain=example.co.uk",
+            &url("https://shop.example.co.uk/"),
+        );
+        assert_eq!(jar.header_for(&url("https://www.example.co.uk/")), "e=5");
+    }
+
+    #[test]
+    fn secure_cookies_need_https() {
+        let jar = CookieJar::new();
+        jar.store("token=a; Secure", &url("https://example.com/"));
+        assert_eq!(jar.header_for(&url("http://example.com/")), "");
+        assert_eq!(jar.header_for(&url("https://example.com/")), "token=a");
+    }
+
+    #[test]
+    fn replaces_and_expires_cookies() {
+        let jar = CookieJar::new();
+        let site = url("https://example.com/");
+        jar.store("sid=old", &site);
+        jar.store("sid=new", &site);
+        assert_eq!(jar.header_for(&site), "sid=new");
+
+        jar.store("sid=gone; Max-Age=0", &site);
+        assert!(jar.is_empty());
+
+        jar.set_enabled(false);
+        jar.store("sid=ignored", &site);
+        assert!(jar.is_empty());
+    }
+
+    #[test]
+    fn jar_values_override_explicit_cookies() {
+        let jar = CookieJar::new();
+        let site = url("https://example.com/");
+        jar.store("sid=fresh", &site);
+        jar.store("csrf=x", &site);
+        assert_eq!(
+            jar.merge_header(&site, "theme=dark; sid=stale"),
+            "theme=dark; sid=fresh; csrf=x"
+        );
+        assert_eq!(jar.merge_header(&url("https://other.com/"), ""), "");
+    }
+
+    #[test]
+    fn exports_netscape_format() {
+        let jar = CookieJar::new();
+        let now = Utc::now();
+        let site = url("https://example.com/app/login");
+        jar.store_at(
+            "sid=abc; Domain=example.com; Path=/; Secure; HttpOnly; Max-Age=60",
+            &site,
+            now,
+        );
+        jar.store_at("pref=1", &site, now);
+
+        let text = jar.to_netscape();
+        assert!(text.starts_with("# Netscape HTTP Cookie File\n"));
+        let expiry = (now + chrono::Duration::seconds(60)).timestamp();
+        assert!(text.contains(&format!(
+            "#HttpOnly_.example.com\tTRUE\t/\tTRUE\t{expiry}\tsid\tabc\n"
+        )));
+        assert!(text.contains("example.com\tFALSE\t/app\tFALSE\t0\tpref\t1\n"));
+    }
+}
@@ -0,0 +1,231 @@
+//! Crawl planning from `robots.txt` and sitemaps
+//!
+//! Discovers a site's structure before fetching: which URLs its sitemaps
+//! list, which of those the robots rules allow, and the requested crawl delay.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use nab::crawl::discover_site_map;
+//! use nab::AcceleratedClient;
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let client = AcceleratedClient::new()?;
+//! let map = discover_site_map(&client, "https://example.com", "nab").await?;
+//! println!("{} URLs, crawl-delay {:?}", map.allowed.len(), map.crawl_delay);
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod output;
+pub mod robots;
+pub mod sitemap;
+
+pub use output::{extension_for, OutputDir, DEFAULT_FILENAME_TEMPLATE};
+pub use robots::{RobotsGroup, RobotsRule, RobotsTxt};
+pub use sitemap::{Sitemap, SitemapUrl};
+
+use std::collections::{HashSet, VecDeque};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use tracing::{debug, warn};
+use url::Url;
+
+use crate::AcceleratedClient;
+
+/// Maximum sitemap documents read (index nesting included)
+const MAX_SITEMAP_DOCUMENTS: usize = 50;
+
+/// Maximum URLs collected across all sitemaps (the per-file protocol limit)
+const MAX_URLS: usize = 50_000;
+
+/// Crawl plan for one host
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SiteMap {
+    /// Scheme, host and port the plan applies to
+    pub origin: String,
+    /// Whether a `robots.txt` was found
+    pub robots_found: bool,
+    /// `Crawl-delay` in seconds for the user agent
+    pub crawl_delay: Option<f64>,
+    /// Sitemap documents that were read
+    pub sitemaps: Vec<String>,
+    /// Sitemap URLs the robots rules allow
+    pub allowed: Vec<SitemapUrl>,
+    /// Number of sitemap URLs the robots rules disallow
+    pub disallowed: usize,
+}
+
+/// Fetch `robots.txt` and the sitemaps of the host of `url`, returning the
+/// same-host sitemap URLs that `user_agent` may crawl
+///
+/// Sitemaps come from the `Sitemap:` lines in `robots.txt`, or
+/// `/sitemap.xml` when there are none. A missing `robots.txt` allows everything.
+pub async fn discover_site_map(
+    client: &AcceleratedClient,
+    url: &str,
+    user_agent: &str,
+) -> Result<SiteMap> {
+    let base = Url::parse(url).with_context(|| format!("Invalid URL '{url}'"))?;
+    let origin = base.origin().ascii_serialization();
+
+    let robots_url = base.join("/robots.txt")?;
+    let robots = fetch_body(client, robots_url.as_str())
+        .await?
+        .map(|body| RobotsTxt::parse(&String::from_utf8_lossy(&body)));
+    let robots_found = robots.is_some();
+    let robots = robots.unwrap_or_default();
+
+    let mut queue: VecDeque<String> = robots
+        .sitemaps
+        .iter()
+        .filter_map(|s| base.join(s).ok())
+        .map(String::from)
+        .collect();
+    if queue.is_empty() {
+        queue.push_back(base.join("/sitemap.xml")?.to_string());
+    }
+
+    let mut seen = HashSet::new();
+    let mut sitemaps = Vec::new();
+    let mut urls = Vec::new();
+    while let Some(sitemap_url) = queue.pop_front() {
+        if sitemaps.len() >= MAX_SITEMAP_DOCUMENTS || urls.len() >= MAX_URLS {
+            break;
+        }
+        if !seen.insert(sitemap_url.clone()) {
+            continue;
+        }
+        let body = match fetch_body(client, &sitemap_url).await {
+            Ok(Some(body)) => body,
+            Ok(None) => {
+                debug!("Sitemap not found: {sitemap_url}");
+                continue;
+            }
+            // One unreachable sitemap shouldn't hide the others
+            Err(e) => {
+                warn!("Failed to fetch sitemap {sitemap_url}: {e:#}");
+                continue;
+            }
+        };
+        match Sitemap::parse(&body) {
+            Ok(Sitemap::UrlSet(entries)) => urls.extend(entries),
+            Ok(Sitemap::Index(children)) => queue.extend(children),
+            Err(e) => {
+                debug!("Skipping unparseable sitemap {sitemap_url}: {e}");
+                continue;
+            }
+        }
+        sitemaps.push(sitemap_url);
+    }
+    urls.truncate(MAX_URLS);
+
+    let mut allowed = Vec::new();
+    let mut disallowed = 0;
+    let mut seen_urls = HashSet::new();
+    for entry in urls {
+        // The sitemap protocol only covers URLs on the sitemap's own host
+        let Ok(parsed) = Url::parse(&entry.loc) else {
+            continue;
+        };
+        if parsed.origin().ascii_serialization() != origin || !seen_urls.insert(entry.loc.clone()) {
+            continue;
+        }
+        let path = match parsed.query() {
+            Some(query) => format!("{}?{query}", parsed.path()),
+            None => parsed.path().to_string(),
+        };
+        if robots.is_allowed(user_agent, &path) {
+            allowed.push(entry);
+        } else {
+            disallowed += 1;
+        }
+    }
+
+    Ok(SiteMap {
+        origin,
+        robots_found,
+        crawl_delay: robots.crawl_delay(user_agent),
+        sitemaps,
+        allowed,
+        disallowed,
+    })
+}
+
+/// Body of a successful response, or `None` for an error status
+async fn fetch_body(client: &AcceleratedClient, url: &str) -> Result<Option<Vec<u8>>> {
+    let response = client.fetch(url).await?;
+    if !response.status().is_success() {
+        return Ok(None);
+    }
+    Ok(Some(response.bytes().await?.to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{http1_client, serve, Response};
+
+    /// Serve `robots.txt` and sitemaps from a local mock host
+    fn mock_host() -> String {
+        let addr = serve(|request| {
+            let base = format!("http://{}", request.header("host").unwrap_or_default());
+            match request.target.as_str() {
+                "/robots.txt" => Response::ok(format!(
+                    "User-agent: *\nDisallow: /admin/\nDisallow: /*.pdf$\nAllow: /admin/public\n\
+                     Crawl-delay: 1.5\n\nSitemap: {base}/sitemap_index.xml\n"
+                )),
+                "/sitemap_index.xml" => Response::ok(format!(
+                    "<sitemapindex><sitemap><loc>http://127.0.0.1:1/down.xml</loc></sitemap>\
+                     <sitemap><loc>{base}/pages.xml</loc></sitemap>\
+                     <sitemap><loc>{base}/missing.xml</loc></sitemap></sitemapindex>"
+                )),
+                "/pages.xml" => Response::ok(format!(
+                    "<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\
+                     <url><loc>{base}/</loc></url>\
+                     <url><loc>{base}/blog/post</loc><lastmod>2024-05-01</lastmod></url>\
+                     <url><loc>{base}/admin/settings</loc></url>\
+                     <url><loc>{base}/admin/public</loc></url>\
+                     <url><loc>{base}/report.pdf</loc></url>\
+                     <url><loc>https://elsewhere.example/page</loc></url>\
+                     </urlset>"
+                )),
+                _ => Response::status(404),
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn test_site_map_excludes_disallowed_paths() {
+        let base = mock_host();
+        let client = http1_client().await;
+
+        let map = discover_site_map(&client, &format!("{base}/blog/"), "nab")
+            .await
+            .unwrap();
+
+        assert_eq!(map.origin, base);
+        assert!(map.robots_found);
+        assert_eq!(map.crawl_delay, Some(1.5));
+        assert_eq!(
+            map.sitemaps,
+            [
+                format!("{base}/sitemap_index.xml"),
+                format!("{base}/pages.xml")
+            ]
+        );
+        let allowed: Vec<&str> = map.allowed.iter().map(|u| u.loc.as_str()).collect();
+        assert_eq!(
+            allowed,
+            [
+                format!("{base}/"),
+                format!("{base}/blog/post"),
+                format!("{base}/admin/public")
+            ]
+        );
+        assert_eq!(map.allowed[1].lastmod.as_deref(), Some("2024-05-01"));
+        assert_eq!(map.disallowed, 2);
+    }
+}
@@ -0,0 +1,276 @@
+//! Per-URL output files for batch fetches and crawls
+//!
+//! Each fetched page is written to its own file under an output directory,
+//! named by a template such as `{host}/{path_slug}.{ext}`:
+//!
+//! | Placeholder | Value |
+//! |-------------|-------|
+//! | `{host}` | Host name, e.g. `docs.example.com` |
+//! | `{path_slug}` | Path and query as a slug, `index` for `/` |
+//! | `{index}` | 1-based position of the URL in the input |
+//! | `{ext}` | File extension, see [`extension_for`] |
+//!
+//! Placeholder values are sanitized so they can never escape the directory,
+//! and an existing file is never overwritten: `page.md` becomes `page-2.md`.
+
+use std::fs::OpenOptions;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use url::Url;
+
+/// Template used when none is given
+pub const DEFAULT_FILENAME_TEMPLATE: &str = "{host}-{path_slug}.{ext}";
+
+/// Longest slug kept from a URL path, in characters
+const MAX_SLUG_CHARS: usize = 100;
+
+/// Give up on suffixes after this many collisions
+const MAX_SUFFIX: usize = 10_000;
+
+const PLACEHOLDERS: [&str; 4] = ["host", "path_slug", "index", "ext"];
+
+/// Directory receiving one file per fetched URL
+#[derive(Debug, Clone)]
+pub struct OutputDir {
+    dir: PathBuf,
+    template: String,
+}
+
+impl OutputDir {
+    /// Write into `dir` using [`DEFAULT_FILENAME_TEMPLATE`]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            template: DEFAULT_FILENAME_TEMPLATE.to_string(),
+        }
+    }
+
+    /// Name files with `template`, rejecting unknown placeholders
+    pub fn with_template(mut self, template: &str) -> Result<Self> {
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let Some(len) = rest[start..].find('}') else {
+                bail!("Unclosed '{{' in filename template '{template}'");
+            };
+            let name = &rest[start + 1..start + len];
+            if !PLACEHOLDERS.contains(&name) {
+                bail!(
+                    "Unknown placeholder '{{{name}}}' in filename template \
+                     (expected {{host}}, {{path_slug}}, {{index}} or {{ext}})"
+                );
+            }
+            rest = &rest[start + len + 1..];
+        }
+        if !template.contains("{path_slug}") && !template.contains("{index}") {
+            tracing::warn!(
+                "Filename template has no {{path_slug}} or {{index}}; names will collide"
+            );
+        }
+        self.template = template.to_string();
+        Ok(self)
+    }
+
+    /// Output directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Relative file name for the `index`-th (1-based) URL, before collision
+    /// handling
+    pub fn file_name(&self, url: &str, index: usize, ext: &str) -> PathBuf {
+        let ext = sanitize(ext, MAX_SLUG_CHARS);
+        let parsed = Url::parse(url).ok();
+        let host = parsed
+            .as_ref()
+            .and_then(Url::host_str)
+            .map_or_else(|| "unknown".to_string(), |h| sanitize(h, MAX_SLUG_CHARS));
+        let path_slug = parsed
+            .as_ref()
+            .map_or_else(|| sanitize(url, MAX_SLUG_CHARS), path_slug);
+
+        let rendered = self
+            .template
+            .replace("{host}", &host)
+            .replace("{path_slug}", &path_slug)
+            .replace("{index}", &index.to_string())
+            .replace("{ext}", &ext);
+
+        // Literal `/` in the template creates subdirectories; nothing may climb out
+        let components: PathBuf = rendered
+            .split(['/', '\\'])
+            .filter(|part| !part.is_empty() && *part != "." && *part != "..")
+            .collect();
+        if components.as_os_str().is_empty() {
+            PathBuf::from(format!("{index}.{ext}"))
+        } else {
+            components
+        }
+    }
+
+    /// Write `body` for the `index`-th (1-based) URL, adding a numeric suffix
+    /// when the name is taken, and return the path written
+    pub fn write(&self, url: &str, index: usize, ext: &str, body: &[u8]) -> Result<PathBuf> {
+        let path = self.dir.join(self.file_name(url, index, ext));
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
+        for attempt in 1..=MAX_SUFFIX {
+            let candidate = if attempt == 1 {
+                path.clone()
+            } else {
+                with_suffix(&path, attempt)
+            };
+            // `create_new` reserves the name atomically, so concurrent writers never clash
+            match OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&candidate)
+            {
+                Ok(mut file) => {
+                    file.write_all(body)
+                        .with_context(|| format!("Failed to write {}", candidate.display()))?;
+                    return Ok(candidate);
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("Failed to create {}", candidate.display()));
+                }
+            }
+        }
+        bail!("Too many files named like {}", path.display())
+    }
+}
+
+/// File extension for a response content type
+///
+/// JSON and PDF bodies are saved as-is, so they keep their own extension;
+/// everything else is saved as converted markdown.
+pub fn extension_for(content_type: &str) -> &'static str {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    if mime == "application/json" || mime.ends_with("+json") {
+        "json"
+    } else if mime == "application/pdf" {
+        "pdf"
+    } else {
+        "md"
+    }
+}
+
+fn path_slug(url: &Url) -> String {
+    let mut raw =
+        urlencoding::decode(url.path()).map_or_else(|_| url.path().to_string(), String::from);
+    if let Some(query) = url.query() {
+        raw.push('-');
+        raw.push_str(query);
+    }
+    // The extension comes from the content type, not the URL
+    if let Some((stem, ext)) = raw.rsplit_once('.') {
+        if !ext.contains('/') && ext.len() <= 5 && url.query().is_none() {
+            raw = stem.to_string();
+        }
+    }
+    let slug = sanitize(&raw, MAX_SLUG_CHARS);
+    if slug.is_empty() {
+        "index".to_string()
+    } else {
+        slug
+    }
+}
+
+/// Lowercase ASCII letters, digits, `.` and `_`, with every other run
+/// collapsed to a single `-`
+fn sanitize(raw: &str, max_chars: usize) -> String {
+    let mut out = String::new();
+    for c in raw.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' || c == '.' {
+            out.push(c.to_ascii_lowercase());
+        } else if !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    let trimmed: String = out
+        .trim_matches(|c| c == '-' || c == '.')
+        .chars()
+        .take(max_chars)
+        .collect();
+    trimmed.trim_end_matches(['-', '.']).to_string()
+}
+
+/// `dir/page.md` → `dir/page-2.md`
+fn with_suffix(path: &Path, n: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(ext) => format!("{stem}-{n}.{}", ext.to_string_lossy()),
+        None => format!("{stem}-{n}"),
+    };
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extension_follows_content_type() {
+        assert_eq!(extension_for("text/html; charset=utf-8"), "md");
+        assert_eq!(extension_for("application/json"), "json");
+        assert_eq!(extension_for("application/ld+json"), "json");
+        assert_eq!(extension_for("Application/PDF"), "pdf");
+        assert_eq!(extension_for(""), "md");
+    }
+
+    #[test]
+    fn file_names_are_sanitized() {
+        let out = OutputDir::new("/tmp/out");
+        assert_eq!(
+            out.file_name("https://Example.com/", 1, "md"),
+            PathBuf::from("example.com-index.md")
+        );
+        assert_eq!(
+            out.file_name("https://example.com/Blog/My Post.html?id=3&x=../y", 2, "md"),
+            PathBuf::from("example.com-blog-my-post.html-id-3-x-..-y.md")
+        );
+
+        let nested = OutputDir::new("/tmp/out")
+            .with_template("../{host}/{index}-{path_slug}.{ext}")
+            .unwrap();
+        assert_eq!(
+            nested.file_name("https://example.com/a/b.pdf", 7, "pdf"),
+            PathBuf::from("example.com/7-a-b.pdf")
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_placeholders() {
+        assert!(OutputDir::new("out")
+            .with_template("{host}/{slug}")
+            .is_err());
+        assert!(OutputDir::new("out").with_template("{host").is_err());
+    }
+
+    #[test]
+    fn collisions_get_numeric_suffixes() {
+        let dir = std::env::temp_dir().join(format!("nab-output-{}", uuid::Uuid::new_v4()));
+        let out = OutputDir::new(&dir).with_template("{host}.{ext}").unwrap();
+
+        let first = out.write("https://example.com/a", 1, "md", b"a").unwrap();
+        let second = out.write("https://example.com/b", 2, "md", b"b").unwrap();
+        let third = out.write("https://example.com/c", 3, "md", b"c").unwrap();
+
+        assert_eq!(first, dir.join("example.com.md"));
+        assert_eq!(second, dir.join("example.com-2.md"));
+        assert_eq!(third, dir.join("example.com-3.md"));
+        assert_eq!(std::fs::read(&second).unwrap(), b"b");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,229 @@
+//! `robots.txt` parsing and matching (RFC 9309)
+//!
+//! Rules are grouped by `User-agent`; the most specific group matching the
+//! crawler's product token applies, falling back to `*`. Within a group the
+//! longest matching path pattern wins, and `Allow` wins ties. Patterns
+//! support `*` wildcards and a trailing `$` end anchor.
+
+use serde::Serialize;
+
+/// A single `Allow`/`Disallow` rule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RobotsRule {
+    /// `true` for `Allow`, `false` for `Disallow`
+    pub allow: bool,
+    /// Path pattern (may contain `*` and a trailing `$`)
+    pub pattern: String,
+}
+
+/// Rules for one or more user agents
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RobotsGroup {
+    /// Lowercased user-agent tokens this group applies to
+    pub user_agents: Vec<String>,
+    pub rules: Vec<RobotsRule>,
+    /// `Crawl-delay` in seconds (non-standard but widely used)
+    pub crawl_delay: Option<f64>,
+}
+
+/// Parsed `robots.txt`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RobotsTxt {
+    pub groups: Vec<RobotsGroup>,
+    /// `Sitemap:` URLs (apply to all user agents)
+    pub sitemaps: Vec<String>,
+}
+
+impl RobotsTxt {
+    /// Parse `robots.txt` content, ignoring unknown and malformed lines
+    #[must_use]
+    pub fn parse(content: &str) -> Self {
+        let mut robots = Self::default();
+        let mut current: Option<RobotsGroup> = None;
+        // Consecutive User-agent lines share one group
+        let mut collecting_agents = false;
+
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim().to_ascii_lowercase();
+            let value = value.trim();
+
+            match key.as_str() {
+                "user-agent" => {
+                    if !collecting_agents {
+                        robots.groups.extend(current.take());
+                        current = Some(RobotsGroup::default());
+                    }
+                    collecting_agents = true;
+                    if let Some(group) = current.as_mut() {
+                        group.user_agents.push(value.to_ascii_lowercase());
+                    }
+                }
+                "allow" | "disallow" => {
+                    collecting_agents = false;
+                    // An empty Disallow allows everything; it adds no rule
+                    if let (Some(group), false) = (current.as_mut(), value.is_empty()) {
+                        group.rules.push(RobotsRule {
+                            allow: key == "allow",
+                            pattern: value.to_string(),
+                        });
+                    }
+                }
+                "crawl-delay" => {
+                    collecting_agents = false;
+                    if let Some(group) = current.as_mut() {
+                        group.crawl_delay = value.parse::<f64>().ok().filter(|d| *d >= 0.0);
+                    }
+                }
+                "sitemap" if !value.is_empty() => robots.sitemaps.push(value.to_string()),
+                _ => {}
+            }
+        }
+        robots.groups.extend(current);
+        robots
+    }
+
+    /// Groups that apply to `user_agent`: those naming the longest token
+    /// contained in its product name, or the `*` groups otherwise
+    #[must_use]
+    pub fn groups_for(&self, user_agent: &str) -> Vec<&RobotsGroup> {
+        let product = product_token(user_agent);
+        let best = self
+            .groups
+            .iter()
+            .flat_map(|g| g.user_agents.iter())
+            .filter(|agent| *agent != "*" && !agent.is_empty() && product.contains(agent.as_str()))
+            .max_by_key(|agent| agent.len())
+            .map_or("*", String::as_str);
+
+        self.groups
+            .iter()
+            .filter(|g| g.user_agents.iter().any(|agent| agent == best))
+            .collect()
+    }
+
+    /// Whether `user_agent` may fetch `path` (path plus optional query)
+    #[must_use]
+    pub fn is_allowed(&self, user_agent: &str, path: &str) -> bool {
+        let path = if path.is_empty() { "/" } else { path };
+        self.groups_for(user_agent)
+            .into_iter()
+            .flat_map(|g| g.rules.iter())
+            .filter(|rule| pattern_matches(&rule.pattern, path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+            .is_none_or(|rule| rule.allow)
+    }
+
+    /// `Crawl-delay` for `user_agent` in seconds, if any applicable group sets one
+    #[must_use]
+    pub fn crawl_delay(&self, user_agent: &str) -> Option<f64> {
+        self.groups_for(user_agent)
+            .into_iter()
+            .find_map(|g| g.crawl_delay)
+    }
+}
+
+/// Lowercased product token of a user agent (`nab/0.4 (...)` → `nab`)
+fn product_token(user_agent: &str) -> String {
+    user_agent
+        .split(['/', ' '])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// Match a robots path pattern against a path
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(stripped) => (stripped, true),
+        None => (pattern, false),
+    };
+
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    for (i, part) in parts.iter().enumerate() {
+        let is_last = i + 1 == parts.len();
+        if is_last && anchored {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROBOTS: &str = "\
+# Example robots.txt
+User-agent: *
+Disallow: /private/
+Disallow: /*.pdf$
+Allow: /private/press/
+Crawl-delay: 2
+
+User-agent: nab
+User-agent: otherbot
+Disallow: /drafts
+Crawl-delay: 0.5
+
+Sitemap: https://example.com/sitemap.xml
+";
+
+    #[test]
+    fn test_parse_groups_and_sitemaps() {
+        let robots = RobotsTxt::parse(ROBOTS);
+        assert_eq!(robots.groups.len(), 2);
+        assert_eq!(robots.groups[1].user_agents, ["nab", "otherbot"]);
+        assert_eq!(robots.sitemaps, ["https://example.com/sitemap.xml"]);
+    }
+
+    #[test]
+    fn test_longest_match_wins() {
+        let robots = RobotsTxt::parse(ROBOTS);
+        assert!(!robots.is_allowed("somebot", "/private/report"));
+        assert!(robots.is_allowed("somebot", "/private/press/2024"));
+        assert!(!robots.is_allowed("somebot", "/files/a.pdf"));
+        assert!(robots.is_allowed("somebot", "/files/a.pdf?download=1"));
+        assert!(robots.is_allowed("somebot", "/"));
+    }
+
+    #[test]
+    fn test_specific_agent_group_replaces_wildcard() {
+        let robots = RobotsTxt::parse(ROBOTS);
+        assert!(!robots.is_allowed("nab/0.4", "/drafts/post"));
+        // The `*` rules don't apply to a crawler with its own group
+        assert!(robots.is_allowed("nab/0.4", "/private/report"));
+        assert_eq!(robots.crawl_delay("nab/0.4"), Some(0.5));
+        assert_eq!(robots.crawl_delay("somebot"), Some(2.0));
+    }
+
+    #[test]
+    fn test_empty_disallow_allows_everything() {
+        let robots = RobotsTxt::parse("User-agent: *\nDisallow:\n");
+        assert!(robots.is_allowed("nab", "/anything"));
+        assert_eq!(robots.crawl_delay("nab"), None);
+    }
+
+    #[test]
+    fn test_pattern_matches() {
+        assert!(pattern_matches("/", "/a"));
+        assert!(pattern_matches("/a*c", "/abc"));
+        assert!(pattern_matches("/a$", "/a"));
+        assert!(!pattern_matches("/a$", "/ab"));
+        assert!(pattern_matches("/*/b$", "/x/y/b"));
+        assert!(!pattern_matches("/b", "/a/b"));
+    }
+}
@@ -0,0 +1,120 @@
+//! `sitemap.xml` parsing (sitemaps.org protocol)
+//!
+//! Handles both `<urlset>` documents and `<sitemapindex>` documents that
+//! point at further sitemaps, plain or gzip-compressed.
+
+use std::io::Read;
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+
+/// A page listed in a `<urlset>`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SitemapUrl {
+    pub loc: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lastmod: Option<String>,
+}
+
+/// A parsed sitemap document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sitemap {
+    /// `<urlset>`: the pages themselves
+    UrlSet(Vec<SitemapUrl>),
+    /// `<sitemapindex>`: locations of child sitemaps
+    Index(Vec<String>),
+}
+
+impl Sitemap {
+    /// Parse a sitemap body, transparently inflating `.xml.gz` content
+    pub fn parse(body: &[u8]) -> Result<Self> {
+        let mut inflated = String::new();
+        let xml = if body.starts_with(&[0x1f, 0x8b]) {
+            flate2::read::GzDecoder::new(body)
+                .read_to_string(&mut inflated)
+                .context("Failed to decompress gzip sitemap")?;
+            inflated.as_str()
+        } else {
+            std::str::from_utf8(body).context("Sitemap is not valid UTF-8")?
+        };
+        Self::parse_xml(xml)
+    }
+
+    /// Parse sitemap XML
+    pub fn parse_xml(xml: &str) -> Result<Self> {
+        let doc = roxmltree::Document::parse(xml).context("Invalid sitemap XML")?;
+        let root = doc.root_element();
+        let child_text = |node: roxmltree::Node, name: &str| {
+            node.children()
+                .find(|c| c.has_tag_name(name))
+                .and_then(|c| c.text())
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+        };
+
+        match root.tag_name().name() {
+            "urlset" => Ok(Self::UrlSet(
+                root.children()
+                    .filter(|n| n.has_tag_name("url"))
+                    .filter_map(|n| {
+                        Some(SitemapUrl {
+                            loc: child_text(n, "loc")?,
+                            lastmod: child_text(n, "lastmod"),
+                        })
+                    })
+                    .collect(),
+            )),
+            "sitemapindex" => Ok(Self::Index(
+                root.children()
+                    .filter(|n| n.has_tag_name("sitemap"))
+                    .filter_map(|n| child_text(n, "loc"))
+                    .collect(),
+            )),
+            other => bail!("Not a sitemap: unexpected root element <{other}>"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_parse_urlset() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url><loc>https://example.com/</loc><lastmod>2024-01-01</lastmod></url>
+  <url><loc> https://example.com/about </loc></url>
+  <url><lastmod>2024-01-01</lastmod></url>
+</urlset>"#;
+
+        let Sitemap::UrlSet(urls) = Sitemap::parse_xml(xml).unwrap() else {
+            panic!("expected a urlset");
+        };
+        assert_eq!(urls.len(), 2);
+        assert_eq!(urls[0].lastmod.as_deref(), Some("2024-01-01"));
+        assert_eq!(urls[1].loc, "https://example.com/about");
+    }
+
+    #[test]
+    fn test_parse_index_gzip() {
+        let xml = r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <sitemap><loc>https://example.com/posts.xml</loc></sitemap>
+</sitemapindex>"#;
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+        encoder.write_all(xml.as_bytes()).unwrap();
+        let gz = encoder.finish().unwrap();
+
+        assert_eq!(
+            Sitemap::parse(&gz).unwrap(),
+            Sitemap::Index(vec!["https://example.com/posts.xml".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_rejects_non_sitemap() {
+        assert!(Sitemap::parse_xml("<html><body/></html>").is_err());
+        assert!(Sitemap::parse(b"not xml").is_err());
+    }
+}
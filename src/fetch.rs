@@ -0,0 +1,365 @@
+//! One-call fetch-and-convert pipeline
+//!
+//! [`fetch_to_markdown`] runs the core steps of `nab fetch` for library use:
+//!
+//! 1. A matching [site provider](crate::site) answers first (Twitter, Reddit,
+//!    GitHub, …), skipping the generic fetch entirely.
+//! 2. Otherwise the page is fetched and routed by content type through
+//!    [`ContentRouter`] (HTML, PDF, JSON, …).
+//! 3. HTML pages that embed SPA state get it appended as JSON sections.
+//!
+//! The CLI and the MCP server have their own implementations with more
+//! request options (custom headers, methods and bodies, proxies, retries,
+//! rendering fallbacks), so their output can differ for the same URL.
+//!
+//! ```rust,no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! let result = nab::fetch_to_markdown("https://example.com", &nab::FetchOptions::default()).await?;
+//! println!("{}", result.markdown);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+
+use crate::auth::CookieSource;
+use crate::content::ContentRouter;
+use crate::http_client::AcceleratedClient;
+use crate::site::{SiteMetadata, SiteRouter};
+use crate::spa::{extract_spa_data_inline, SpaLimits};
+
+/// Options for [`fetch_to_markdown`]
+#[derive(Debug, Clone)]
+pub struct FetchOptions {
+    /// Browser whose cookies are sent for the URL's domain
+    pub cookies: Option<CookieSource>,
+    /// Convert the body to markdown; `false` returns the raw body text
+    pub markdown: bool,
+    /// Stop reading the body after this many bytes
+    pub max_bytes: Option<usize>,
+    /// Append embedded SPA data (`__NEXT_DATA__`, JSON islands) to HTML pages
+    pub spa: bool,
+    /// Size caps for the appended SPA data
+    pub spa_limits: SpaLimits,
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        Self {
+            cookies: None,
+            markdown: true,
+            max_bytes: None,
+            spa: true,
+            spa_limits: SpaLimits::default(),
+        }
+    }
+}
+
+impl FetchOptions {
+    /// Send cookies from this browser's store
+    #[must_use]
+    pub fn with_cookies(mut self, source: CookieSource) -> Self {
+        self.cookies = Some(source);
+        self
+    }
+
+    /// Return the raw body instead of markdown
+    #[must_use]
+    pub fn with_markdown(mut self, markdown: bool) -> Self {
+        self.markdown = markdown;
+        self
+    }
+
+    /// Read at most `max_bytes` of the body
+    #[must_use]
+    pub fn with_max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Toggle SPA data augmentation
+    #[must_use]
+    pub fn with_spa(mut self, spa: bool) -> Self {
+        self.spa = spa;
+        self
+    }
+
+    /// Size caps for the appended SPA data
+    #[must_use]
+    pub fn with_spa_limits(mut self, limits: SpaLimits) -> Self {
+        self.spa_limits = limits;
+        self
+    }
+}
+
+/// Output of [`fetch_to_markdown`]
+#[derive(Debug, Clone)]
+pub struct FetchResult {
+    /// Final URL after redirects
+    pub url: String,
+    /// Converted markdown (or raw body text with `markdown: false`)
+    pub markdown: String,
+    /// HTTP status; `None` when a site provider answered
+    pub status: Option<u16>,
+    /// Content type the body was handled as (sniffed when the declared one
+    /// was missing or generic)
+    pub content_type: Option<String>,
+    /// Page `<title>`, or the site provider's title
+    pub title: Option<String>,
+    /// Name of the site provider that answered, if any
+    pub provider: Option<String>,
+    /// Provider metadata (author, engagement, …), if a provider answered
+    pub site: Option<SiteMetadata>,
+    /// Body bytes read
+    pub bytes: usize,
+    /// The body was cut off at [`FetchOptions::max_bytes`]
+    pub truncated: bool,
+    /// Page count for paginated formats such as PDF
+    pub page_count: Option<usize>,
+    /// Embedded SPA data was appended to the markdown
+    pub spa_data: bool,
+    /// Total time in milliseconds
+    pub elapsed_ms: f64,
+}
+
+/// Fetch `url` and convert it to markdown with a fresh client and the
+/// built-in site providers
+pub async fn fetch_to_markdown(url: &str, options: &FetchOptions) -> Result<FetchResult> {
+    let client = tokio::task::spawn_blocking(AcceleratedClient::new).await??;
+    fetch_to_markdown_with(&client, &SiteRouter::new(), url, options).await
+}
+
+/// [`fetch_to_markdown`] with a caller-supplied client and site router
+pub async fn fetch_to_markdown_with(
+    client: &AcceleratedClient,
+    router: &SiteRouter,
+    url: &str,
+    options: &FetchOptions,
+) -> Result<FetchResult> {
+    let start = Instant::now();
+
+    if let Some(content) = router.try_extract(url, client).await {
+        return Ok(FetchResult {
+            url: content.metadata.canonical_url.clone(),
+            bytes: content.markdown.len(),
+            markdown: content.markdown,
+            status: None,
+            content_type: None,
+            title: content.metadata.title.clone(),
+            provider: router.provider_for(url).map(|p| p.name().to_string()),
+            site: Some(content.metadata),
+            truncated: false,
+            page_count: None,
+            spa_data: false,
+            elapsed_ms: start.elapsed().as_secs_f64() * 1000.0,
+        });
+    }
+
+    let mut headers = HashMap::new();
+    if let Some(source) = options.cookies {
+        let domain = url::Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+            .unwrap_or_default();
+        // Reading a browser's cookie store shells out and may block
+        let cookie_header = tokio::task::spawn_blocking(move || source.get_cookie_header(&domain))
+            .await
+            .context("Cookie lookup panicked")?
+            .unwrap_or_default();
+        if !cookie_header.is_empty() {
+            headers.insert("Cookie".to_string(), cookie_header);
+        }
+    }
+
+    let mut response = client.request("GET", url, &headers, None).await?;
+    let status = response.status().as_u16();
+    let final_url = response.url().to_string();
+    let declared_type = response
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("text/html")
+        .to_string();
+
+    let mut body = Vec::new();
+    let mut truncated = false;
+    while let Some(chunk) = response.chunk().await? {
+        body.extend_from_slice(&chunk);
+        if let Some(max) = options.max_bytes.filter(|max| body.len() > *max) {
+            body.truncate(max);
+            truncated = true;
+            break;
+        }
+    }
+    let bytes = body.len();
+
+    let markdown = options.markdown;
+    let spa = options.spa.then_some(options.spa_limits);
+    let converted = tokio::task::spawn_blocking(move || -> Result<_> {
+        let raw = String::from_utf8_lossy(&body);
+        let (text, page_count, content_type) = if markdown {
+            let result = ContentRouter::new().convert(&body, &declared_type)?;
+            (result.markdown, result.page_count, result.content_type)
+        } else {
+            (raw.to_string(), None, declared_type)
+        };
+        let is_html = content_type.contains("html");
+        let title = if is_html { extract_title(&raw) } else { None };
+        let spa_data = spa
+            .filter(|_| markdown && is_html)
+            .and_then(|limits| extract_spa_data_inline(&raw, &limits));
+        Ok((text, page_count, content_type, title, spa_data))
+    })
+    .await
+    .context("Content conversion panicked")??;
+    let (mut text, page_count, content_type, title, spa_data) = converted;
+
+    if let Some(data) = &spa_data {
+        text.push_str("\n\n## Embedded Data\n\n");
+        text.push_str(data);
+    }
+
+    Ok(FetchResult {
+        url: final_url,
+        markdown: text,
+        status: Some(status),
+        content_type: Some(content_type),
+        title,
+        provider: None,
+        site: None,
+        bytes,
+        truncated,
+        page_count,
+        spa_data: spa_data.is_some(),
+        elapsed_ms: start.elapsed().as_secs_f64() * 1000.0,
+    })
+}
+
+fn extract_title(html: &str) -> Option<String> {
+    let doc = scraper::Html::parse_document(html);
+    let sel = scraper::Selector::parse("title").ok()?;
+    doc.select(&sel)
+        .next()
+        .map(|el| el.text().collect::<String>().trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::site::{SiteContent, SiteProvider};
+    use crate::test_support::{http1_client, serve, Response};
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    const PAGE: &str = r#"<!DOCTYPE html><html><head><title>Mock Article</title></head>
+<body><article><h1>Hello from the mock</h1>
+<p>This paragraph is the article body and should survive conversion.</p></article>
+<script id="__NEXT_DATA__" type="application/json">{"props":{"pageProps":{"slug":"mock"}}}</script>
+</body></html>"#;
+
+    /// Serve `PAGE` as HTML to every request, counting them
+    fn mock_site() -> (String, Arc<AtomicUsize>) {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        let addr = serve(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Response::typed("text/html; charset=utf-8", PAGE)
+        });
+        (format!("http://{addr}"), hits)
+    }
+
+    /// Provider claiming `/provided/` URLs on any host
+    struct MockProvider;
+
+    #[async_trait]
+    impl SiteProvider for MockProvider {
+        fn name(&self) -> &'static str {
+            "mock"
+        }
+
+        fn matches(&self, url: &str) -> bool {
+            url.contains("/provided/")
+        }
+
+        async fn extract(&self, url: &str, _client: &AcceleratedClient) -> Result<SiteContent> {
+            Ok(SiteContent {
+                markdown:
+                    "## From the provider\n\nStructured content that beats scraping the page."
+                        .to_string(),
+                metadata: SiteMetadata {
+                    author: Some("ada".to_string()),
+                    title: Some("Provider title".to_string()),
+                    published: None,
+                    platform: "Mock".to_string(),
+                    canonical_url: url.to_string(),
+                    media_urls: Vec::new(),
+                    engagement: None,
+                    paywalled: false,
+                },
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn converts_html_with_metadata_and_spa_data() {
+        let (base, hits) = mock_site();
+        let url = format!("{base}/article");
+        let client = http1_client().await;
+
+        let result =
+            fetch_to_markdown_with(&client, &SiteRouter::new(), &url, &FetchOptions::default())
+                .await
+                .unwrap();
+
+        assert!(result.markdown.contains("Hello from the mock"));
+        assert!(result.markdown.contains("article body"));
+        assert!(!result.markdown.contains("<p>"));
+        assert!(result.markdown.contains("`__NEXT_DATA__`"));
+        assert!(result.spa_data);
+        assert_eq!(result.status, Some(200));
+        assert_eq!(result.url, url);
+        assert!(result.content_type.unwrap().starts_with("text/html"));
+        assert_eq!(result.title.as_deref(), Some("Mock Article"));
+        assert_eq!(result.bytes, PAGE.len());
+        assert!(!result.truncated);
+        assert!(result.provider.is_none());
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        let raw = fetch_to_markdown_with(
+            &client,
+            &SiteRouter::new(),
+            &url,
+            &FetchOptions::default()
+                .with_markdown(false)
+                .with_max_bytes(64),
+        )
+        .await
+        .unwrap();
+        assert_eq!(raw.markdown, PAGE[..64]);
+        assert!(raw.truncated);
+        assert!(!raw.spa_data);
+    }
+
+    #[tokio::test]
+    async fn matched_site_provider_takes_precedence() {
+        let (base, hits) = mock_site();
+        let url = format!("{base}/provided/1");
+        let client = http1_client().await;
+        let router = SiteRouter::with_providers(vec![Box::new(MockProvider)]);
+
+        let result = fetch_to_markdown_with(&client, &router, &url, &FetchOptions::default())
+            .await
+            .unwrap();
+
+        assert!(result.markdown.starts_with("## From the provider"));
+        assert_eq!(result.provider.as_deref(), Some("mock"));
+        assert_eq!(result.title.as_deref(), Some("Provider title"));
+        assert_eq!(result.site.unwrap().author.as_deref(), Some("ada"));
+        assert_eq!(result.status, None);
+        assert_eq!(hits.load(Ordering::SeqCst), 0);
+    }
+}
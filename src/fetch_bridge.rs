@@ -5,19 +5,67 @@
 //!
 //! Architecture:
 //! ```text
-//! JavaScript:  fetch("/api/data")
-//!      ↓       Native function call
-//! Rust:        reqwest::blocking::get(url).text()
+//! JavaScript:  fetch("/api/data", { method: "POST", headers, body })
+//!      ↓       Native function call (URL + JSON-encoded init)
+//! Rust:        reqwest::blocking request with method, headers and body
 //!      ↓       HTTP/2 client with cookies
-//! HTTP:        GET /api/data Cookie: ...
+//! HTTP:        POST /api/data Cookie: ...
 //!      ↓
-//! JavaScript:  Returns response text
+//! JavaScript:  Resolves to a Response (status, headers, text(), json())
 //! ```
 
-use anyhow::Result;
+use anyhow::{Context as _, Result};
 use reqwest::blocking::Client;
 use rquickjs::{Context, Function};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::sync::{Arc, Mutex};
+use url::Url;
+
+use crate::fingerprint::referrer_headers;
+
+/// Options from a JavaScript `fetch(url, init)` call
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct FetchInit {
+    /// HTTP method (defaults to `GET`)
+    pub method: Option<String>,
+    /// Request headers as name/value pairs, in call order
+    pub headers: Vec<(String, String)>,
+    /// Request body
+    pub body: Option<String>,
+}
+
+/// Response handed back to the JavaScript `Response` object
+#[derive(Debug, Clone, Serialize)]
+pub struct FetchResponse {
+    pub status: u16,
+    #[serde(rename = "statusText")]
+    pub status_text: String,
+    /// Lowercased header names; repeated headers are joined with `, `
+    pub headers: BTreeMap<String, String>,
+    pub body: String,
+}
+
+/// One `fetch()` call made by page JavaScript
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FetchLogEntry {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+impl fmt::Display for FetchLogEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.method, self.url)?;
+        if let Some(body) = &self.body {
+            write!(f, " ({} byte body)", body.len())?;
+        }
+        Ok(())
+    }
+}
 
 /// HTTP client wrapper for fetch bridge
 #[derive(Clone)]
@@ -25,8 +73,12 @@ pub struct FetchClient {
     client: Client,
     cookie_header: String,
     base_url: String,
-    /// Log of all fetched URLs (for debugging/discovery)
-    fetch_log: Arc<Mutex<Vec<String>>>,
+    /// Page the scripts run on; sent as `Referer`/`Origin` like a browser would
+    page_url: Option<String>,
+    /// Replaces the default `nab/1.0` User-Agent
+    user_agent: Option<String>,
+    /// Log of all fetch calls (for debugging/discovery)
+    fetch_log: Arc<Mutex<Vec<FetchLogEntry>>>,
 }
 
 impl FetchClient {
@@ -40,48 +92,125 @@ impl FetchClient {
                 .expect("HTTP client builder should succeed with default config"),
             cookie_header: cookies.unwrap_or_default(),
             base_url: base_url.unwrap_or_default(),
+            page_url: None,
+            user_agent: None,
             fetch_log: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
-    /// Get the list of all fetched URLs
+    /// Send `Referer` and `Origin` for `page_url` with every fetch
     #[must_use]
-    pub fn get_fetch_log(&self) -> Vec<String> {
+    pub fn with_page_url(mut self, page_url: impl Into<String>) -> Self {
+        self.page_url = Some(page_url.into());
+        self
+    }
+
+    /// Send `user_agent` instead of `nab/1.0` unless a script sets its own
+    #[must_use]
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Get the list of all fetch calls
+    #[must_use]
+    pub fn get_fetch_log(&self) -> Vec<FetchLogEntry> {
         self.fetch_log
             .lock()
             .expect("Fetch log mutex should not be poisoned")
             .clone()
     }
 
+    /// Resolve `url` as the page would: against the page URL, else the base
+    /// URL; unresolvable URLs are used as given
+    fn resolve(&self, url: &str) -> String {
+        [self.page_url.as_deref(), Some(self.base_url.as_str())]
+            .into_iter()
+            .flatten()
+            .find_map(|base| Url::parse(base).ok())
+            .and_then(|base| base.join(url).ok())
+            .map_or_else(|| url.to_string(), String::from)
+    }
+
     /// Fetch a URL and return the response body as text
     /// This is a blocking call that executes the HTTP request synchronously
+    #[allow(clippy::needless_pass_by_value)]
     pub fn fetch_sync(&self, url: String) -> Result<String> {
-        // Resolve relative URLs against base_url
-        let full_url = if url.starts_with("http://") || url.starts_with("https://") {
-            url
-        } else if url.starts_with('/') && !self.base_url.is_empty() {
-            format!("{}{}", self.base_url, url)
-        } else {
-            url
-        };
+        Ok(self.fetch_with(&url, &FetchInit::default())?.body)
+    }
+
+    /// Execute a `fetch(url, init)` call with its method, headers and body
+    ///
+    /// Blocking. Browser cookies are sent unless `init` sets its own `Cookie`.
+    pub fn fetch_with(&self, url: &str, init: &FetchInit) -> Result<FetchResponse> {
+        let full_url = self.resolve(url);
+        let method = init.method.as_deref().unwrap_or("GET").to_ascii_uppercase();
 
         // Log the fetch for discovery
         if let Ok(mut log) = self.fetch_log.lock() {
-            log.push(full_url.clone());
+            log.push(FetchLogEntry {
+                method: method.clone(),
+                url: full_url.clone(),
+                headers: init.headers.clone(),
+                body: init.body.clone(),
+            });
         }
 
-        let mut request = self.client.get(&full_url);
+        let method = reqwest::Method::from_bytes(method.as_bytes())
+            .with_context(|| format!("Invalid HTTP method '{method}'"))?;
+        let mut request = self.client.request(method.clone(), &full_url);
+
+        let sets = |header: &str| {
+            init.headers
+                .iter()
+                .any(|(name, _)| name.trim().eq_ignore_ascii_case(header))
+        };
 
         // Add cookies
-        if !self.cookie_header.is_empty() {
+        if !self.cookie_header.is_empty() && !sets("cookie") {
             request = request.header("Cookie", &self.cookie_header);
         }
 
+        if let Some(user_agent) = self.user_agent.as_deref().filter(|_| !sets("user-agent")) {
+            request = request.header(reqwest::header::USER_AGENT, user_agent);
+        }
+
+        // Add the Referer/Origin the page would send
+        if let Some(page_url) = &self.page_url {
+            for (name, value) in &referrer_headers(page_url, &full_url, method.as_str()) {
+                if !sets(name.as_str()) {
+                    request = request.header(name, value);
+                }
+            }
+        }
+        for (name, value) in &init.headers {
+            request = request.header(name.trim(), value.trim());
+        }
+        if let Some(body) = &init.body {
+            request = request.body(body.clone());
+        }
+
         // Execute request (blocking)
         let response = request.send()?;
-        let body = response.text()?;
+        let status = response.status();
+        let mut headers = BTreeMap::new();
+        for (name, value) in response.headers() {
+            let value = String::from_utf8_lossy(value.as_bytes());
+            headers
+                .entry(name.as_str().to_string())
+                .and_modify(|existing: &mut String| {
+                    existing.push_str(", ");
+                    existing.push_str(&value);
+                })
+                .or_insert_with(|| value.into_owned());
+        }
 
-        Ok(body)
+        Ok(FetchResponse {
+            status: status.as_u16(),
+            status_text: status.canonical_reason().unwrap_or_default().to_string(),
+            headers,
+            body: response.text()?,
+        })
     }
 }
 
@@ -90,11 +219,15 @@ impl FetchClient {
 pub fn inject_fetch_sync(ctx: &Context, client: FetchClient) -> Result<()> {
     ctx.with(|ctx| {
         // Create fetch function
+        // Takes the URL and JSON-encoded init options, returns a JSON envelope
+        // with either the response or an `error` message
         let fetch_fn = Function::new(ctx.clone(), {
-            move |url: String| {
-                client
-                    .fetch_sync(url)
-                    .unwrap_or_else(|e| format!("{{\"error\": \"{e}\"}}"))
+            move |url: String, init: String| {
+                let result = serde_json::from_str::<FetchInit>(&init)
+                    .context("Invalid fetch() options")
+                    .and_then(|init| client.fetch_with(&url, &init))
+                    .and_then(|response| Ok(serde_json::to_string(&response)?));
+                result.unwrap_or_else(|e| serde_json::json!({ "error": format!("{e:#}") }).to_string())
             }
         })?;
 
@@ -103,7 +236,7 @@ pub fn inject_fetch_sync(ctx: &Context, client: FetchClient) -> Result<()> {
 
         // Create a minimal Response + Promise polyfill for fetch() compatibility
         // QuickJS has no event loop, so we use synchronous "fake" Promises
-        let response_code = r#"
+        let response_code = r"
             // Minimal Promise polyfill that resolves immediately (no event loop)
             class SyncPromise {
                 constructor(executor) {
@@ -113,6 +246,11 @@ pub fn inject_fetch_sync(ctx: &Context, client: FetchClient) -> Result<()> {
 
                     const resolve = (value) => {
                         if (this._state !== 'pending') return;
+                        // Adopt returned promises, e.g. `.then(r => r.json())`
+                        if (value && typeof value.then === 'function') {
+                            value.then(resolve, reject);
+                            return;
+                        }
                         this._state = 'fulfilled';
                         this._value = value;
                         this._handlers.forEach(h => h.onFulfilled && h.onFulfilled(value));
@@ -185,13 +323,39 @@ pub fn inject_fetch_sync(ctx: &Context, client: FetchClient) -> Result<()> {
                 globalThis.Promise = SyncPromise;
             }
 
+            class Headers {
+                constructor(init = {}) {
+                    this._map = {};
+                    const pairs = Array.isArray(init) ? init
+                        : (init instanceof Headers) ? init.entries()
+                        : Object.entries(init || {});
+                    pairs.forEach(([name, value]) => this.append(name, value));
+                }
+
+                append(name, value) {
+                    const key = String(name).toLowerCase();
+                    this._map[key] = key in this._map ? this._map[key] + ', ' + value : String(value);
+                }
+
+                set(name, value) { this._map[String(name).toLowerCase()] = String(value); }
+                get(name) { const key = String(name).toLowerCase(); return key in this._map ? this._map[key] : null; }
+                has(name) { return String(name).toLowerCase() in this._map; }
+                delete(name) { delete this._map[String(name).toLowerCase()]; }
+                entries() { return Object.entries(this._map); }
+                forEach(callback) { this.entries().forEach(([name, value]) => callback(value, name, this)); }
+            }
+
+            if (typeof globalThis.Headers === 'undefined') {
+                globalThis.Headers = Headers;
+            }
+
             class Response {
                 constructor(body, init = {}) {
                     this.body = body;
-                    this.ok = init.ok !== false;
                     this.status = init.status || 200;
-                    this.statusText = init.statusText || 'OK';
-                    this.headers = init.headers || {};
+                    this.ok = this.status >= 200 && this.status < 300;
+                    this.statusText = init.statusText || '';
+                    this.headers = new Headers(init.headers || {});
                     this._bodyUsed = false;
                 }
 
@@ -207,10 +371,9 @@ pub fn inject_fetch_sync(ctx: &Context, client: FetchClient) -> Result<()> {
 
                 clone() {
                     return new Response(this.body, {
-                        ok: this.ok,
                         status: this.status,
                         statusText: this.statusText,
-                        headers: this.headers
+                        headers: this.headers.entries()
                     });
                 }
             }
@@ -220,20 +383,27 @@ pub fn inject_fetch_sync(ctx: &Context, client: FetchClient) -> Result<()> {
             globalThis.fetch = function(url, options = {}) {
                 return new SyncPromise((resolve, reject) => {
                     try {
-                        const body = _nativeFetch(url);
-                        // Check for error response
-                        if (body && body.startsWith('{"error":')) {
-                            const err = JSON.parse(body);
-                            reject(new Error(err.error));
+                        const init = {
+                            method: options.method || 'GET',
+                            headers: new Headers(options.headers || {}).entries(),
+                        };
+                        if (options.body !== undefined && options.body !== null) {
+                            init.body = typeof options.body === 'string'
+                                ? options.body
+                                : String(options.body);
+                        }
+                        const result = JSON.parse(_nativeFetch(String(url), JSON.stringify(init)));
+                        if (result.error !== undefined) {
+                            reject(new Error(result.error));
                         } else {
-                            resolve(new Response(body, { ok: true, status: 200 }));
+                            resolve(new Response(result.body, result));
                         }
                     } catch (e) {
                         reject(e);
                     }
                 });
             };
-        "#;
+        ";
 
         ctx.eval::<(), _>(response_code)?;
 
@@ -244,6 +414,7 @@ pub fn inject_fetch_sync(ctx: &Context, client: FetchClient) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::{serve_once, Request, Response};
 
     #[test]
     fn test_fetch_client_new() {
@@ -264,6 +435,105 @@ mod tests {
         assert_eq!(client.base_url, "https://example.com");
     }
 
+    /// Answer one request with a JSON body; the handle yields the request
+    fn mock_api() -> (String, std::thread::JoinHandle<Request>) {
+        let (addr, server) = serve_once(|_| {
+            Response::status(201)
+                .header("Content-Type", "application/json")
+                .body(r#"{"id":7}"#)
+        });
+        (format!("http://{addr}"), server)
+    }
+
+    #[test]
+    fn test_js_fetch_sends_method_headers_and_body() {
+        let (base, server) = mock_api();
+        let engine = crate::JsEngine::new().unwrap();
+        let client = FetchClient::new(Some("session=abc".to_string()), Some(base.clone()))
+            .with_page_url(format!("{base}/shop/cart"));
+        inject_fetch_sync(engine.context(), client.clone()).unwrap();
+
+        let result = engine
+            .eval(
+                r#"
+            var out = 'pending';
+            fetch('/api', {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json', 'X-Token': 't1' },
+                body: '{"x":1}'
+            })
+                .then(r => {
+                    out = r.status + ' ' + r.ok + ' ' + r.headers.get('Content-Type');
+                    return r.json();
+                })
+                .then(data => { out += ' id=' + data.id; });
+            out;
+        "#,
+            )
+            .unwrap();
+        assert_eq!(result, "201 true application/json id=7");
+
+        let request = server.join().unwrap();
+        assert_eq!(
+            (request.method.as_str(), request.target.as_str()),
+            ("POST", "/api")
+        );
+        assert_eq!(request.header("content-type"), Some("application/json"));
+        assert_eq!(request.header("x-token"), Some("t1"));
+        assert_eq!(request.header("cookie"), Some("session=abc"));
+        assert_eq!(
+            request.header("referer"),
+            Some(format!("{base}/shop/cart").as_str())
+        );
+        assert_eq!(request.header("origin"), Some(base.as_str()));
+        assert_eq!(request.body, br#"{"x":1}"#);
+
+        let log = client.get_fetch_log();
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].method, "POST");
+        assert_eq!(log[0].url, format!("{base}/api"));
+        assert_eq!(log[0].body.as_deref(), Some(r#"{"x":1}"#));
+        assert_eq!(log[0].to_string(), format!("POST {base}/api (7 byte body)"));
+    }
+
+    #[test]
+    fn test_js_fetch_rejects_on_network_error() {
+        let engine = crate::JsEngine::new().unwrap();
+        inject_fetch_sync(engine.context(), FetchClient::new(None, None)).unwrap();
+        let result = engine
+            .eval(
+                r"
+            var out = 'pending';
+            fetch('http://127.0.0.1:1/none').catch(e => { out = 'rejected'; });
+            out;
+        ",
+            )
+            .unwrap();
+        assert_eq!(result, "rejected");
+    }
+
+    #[test]
+    fn test_resolves_relative_urls_like_the_page() {
+        let client = FetchClient::new(None, Some("https://shop.example".to_string()));
+        assert_eq!(client.resolve("/api"), "https://shop.example/api");
+        assert_eq!(client.resolve("api"), "https://shop.example/api");
+
+        let client = client.with_page_url("https://shop.example/shop/cart?step=1");
+        for (url, resolved) in [
+            ("/api", "https://shop.example/api"),
+            ("api/items", "https://shop.example/shop/api/items"),
+            ("../v2/items", "https://shop.example/v2/items"),
+            ("?step=2", "https://shop.example/shop/cart?step=2"),
+            ("//cdn.example/x.json", "https://cdn.example/x.json"),
+            ("http://other.example/y", "http://other.example/y"),
+        ] {
+            assert_eq!(client.resolve(url), resolved, "{url}");
+        }
+
+        // Nothing to resolve against
+        assert_eq!(FetchClient::new(None, None).resolve("/api"), "/api");
+    }
+
     #[test]
     fn test_fetch_log_empty_initially() {
         let client = FetchClient::new(None, None);
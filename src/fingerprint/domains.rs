@@ -0,0 +1,174 @@
+//! Per-domain fingerprint overrides loaded from `~/.config/nab/fingerprints.toml`.
+//!
+//! Some sites only serve full content to one browser, or block another.
+//! Pin the browser per domain:
+//!
+//! ```toml
+//! [domains]
+//! "apple.com" = "safari"
+//! "*.microsoft.com" = "edge"
+//! ```
+//!
+//! A bare domain matches itself and its subdomains, `*.domain` only its
+//! subdomains and `*` every host. When several patterns match, the longest
+//! wins.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::browser_detect::BrowserType;
+use crate::cookie_jar::domain_matches;
+
+/// Domain pattern → browser map
+#[derive(Debug, Clone, Default)]
+pub struct DomainProfiles {
+    rules: Vec<(String, BrowserType)>,
+}
+
+/// Top-level fingerprints configuration file.
+#[derive(Debug, Default, Deserialize)]
+struct FingerprintsFile {
+    #[serde(default)]
+    domains: BTreeMap<String, String>,
+}
+
+impl DomainProfiles {
+    /// An empty map (no overrides)
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load overrides from `~/.config/nab/fingerprints.toml`.
+    ///
+    /// Returns an empty map if the file doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn load() -> Result<Self> {
+        let path = config_path();
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Self::from_toml(&content).with_context(|| format!("invalid {}", path.display()))
+    }
+
+    /// Parse the `[domains]` table of a fingerprints config
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for invalid TOML or an unknown browser name.
+    pub fn from_toml(content: &str) -> Result<Self> {
+        let file: FingerprintsFile = toml::from_str(content)?;
+        file.domains
+            .into_iter()
+            .try_fold(Self::new(), |profiles, (pattern, browser)| {
+                let browser = browser
+                    .parse()
+                    .with_context(|| format!("domain '{pattern}'"))?;
+                Ok(profiles.with_rule(pattern, browser))
+            })
+    }
+
+    /// Use `browser` for hosts matching `pattern`
+    #[must_use]
+    pub fn with_rule(mut self, pattern: impl Into<String>, browser: BrowserType) -> Self {
+        let pattern = pattern.into().trim().trim_end_matches('.').to_lowercase();
+        self.rules.push((pattern, browser));
+        self
+    }
+
+    /// Whether no overrides are configured
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Browsers named by any rule, without duplicates
+    pub fn browsers(&self) -> Vec<BrowserType> {
+        let mut browsers = Vec::new();
+        for (_, browser) in &self.rules {
+            if !browsers.contains(browser) {
+                browsers.push(*browser);
+            }
+        }
+        browsers
+    }
+
+    /// Browser pinned for `host`, if any
+    pub fn browser_for(&self, host: &str) -> Option<BrowserType> {
+        let host = host.trim_end_matches('.').to_lowercase();
+        self.rules
+            .iter()
+            .filter(|(pattern, _)| pattern_matches(pattern, &host))
+            .max_by_key(|(pattern, _)| pattern.len())
+            .map(|(_, browser)| *browser)
+    }
+}
+
+fn pattern_matches(pattern: &str, host: &str) -> bool {
+    if pattern == "*" {
+        return true;
+    }
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host.len() > domain.len() && domain_matches(host, domain),
+        None => domain_matches(host, pattern),
+    }
+}
+
+/// Return the path to the fingerprints config file.
+fn config_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("nab")
+        .join("fingerprints.toml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_config_and_matches_hosts() {
+        let profiles = DomainProfiles::from_toml(
+            r#"
+[domains]
+"apple.com" = "safari"
+"*.microsoft.com" = "Edge"
+"support.apple.com" = "firefox"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(profiles.browser_for("apple.com"), Some(BrowserType::Safari));
+        assert_eq!(
+            profiles.browser_for("www.Apple.com"),
+            Some(BrowserType::Safari)
+        );
+        // The longer pattern wins
+        assert_eq!(
+            profiles.browser_for("support.apple.com"),
+            Some(BrowserType::Firefox)
+        );
+        assert_eq!(
+            profiles.browser_for("learn.microsoft.com"),
+            Some(BrowserType::Edge)
+        );
+        assert_eq!(profiles.browser_for("microsoft.com"), None);
+        assert_eq!(profiles.browser_for("notapple.com"), None);
+        assert_eq!(profiles.browsers().len(), 3);
+    }
+
+    #[test]
+    fn rejects_unknown_browsers() {
+        let err = DomainProfiles::from_toml("[domains]\n\"a.com\" = \"netscape\"").unwrap_err();
+        assert!(format!("{err:#}").contains("Unknown browser 'netscape'"));
+        assert!(DomainProfiles::from_toml("").unwrap().is_empty());
+    }
+}
@@ -4,11 +4,15 @@
 //! Based on real browser statistics and anti-fingerprinting research.
 
 pub mod autoupdate;
+pub mod domains;
 
+use crate::browser_detect::BrowserType;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::Rng;
+use rand::{Rng, SeedableRng};
 use reqwest::header::{
-    HeaderMap, HeaderValue, ACCEPT, ACCEPT_ENCODING, ACCEPT_LANGUAGE, USER_AGENT,
+    HeaderMap, HeaderName, HeaderValue, ACCEPT, ACCEPT_ENCODING, ACCEPT_LANGUAGE, ORIGIN, REFERER,
+    USER_AGENT,
 };
 
 // Load versions once on first use (auto-updates if stale)
@@ -16,7 +20,7 @@ static BROWSER_VERSIONS: std::sync::LazyLock<autoupdate::BrowserVersions> =
     std::sync::LazyLock::new(autoupdate::BrowserVersions::load_or_update);
 
 /// Browser profile with realistic fingerprint
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct BrowserProfile {
     pub user_agent: String,
     pub accept: String,
@@ -25,6 +29,10 @@ pub struct BrowserProfile {
     pub sec_ch_ua: String,
     pub sec_ch_ua_mobile: String,
     pub sec_ch_ua_platform: String,
+    /// OS version of a mobile device (`"14.0.0"`), empty on desktop; only
+    /// sent to origins that ask for it (see
+    /// [`platform_version_header`](Self::platform_version_header))
+    pub sec_ch_ua_platform_version: String,
     pub sec_fetch_dest: String,
     pub sec_fetch_mode: String,
     pub sec_fetch_site: String,
@@ -40,11 +48,12 @@ pub enum Platform {
     MacOS,
     Windows,
     Linux,
+    Android,
 }
 
 impl Platform {
-    fn random() -> Self {
-        let mut rng = rand::thread_rng();
+    /// Random desktop platform
+    fn random(rng: &mut impl Rng) -> Self {
         // Realistic distribution: Windows 65%, macOS 20%, Linux 15%
         let roll: f32 = rng.gen();
         if roll < 0.65 {
@@ -61,6 +70,24 @@ impl Platform {
             Platform::MacOS => "Macintosh; Intel Mac OS X 10_15_7",
             Platform::Windows => "Windows NT 10.0; Win64; x64",
             Platform::Linux => "X11; Linux x86_64",
+            // Chrome's reduced UA hides the real version and model
+            Platform::Android => "Linux; Android 10; K",
+        }
+    }
+
+    /// Platform named by a user agent's OS token
+    fn from_user_agent(user_agent: &str) -> Option<Self> {
+        // Android agents also say "Linux"
+        if user_agent.contains("Android") {
+            Some(Platform::Android)
+        } else if user_agent.contains("Windows") {
+            Some(Platform::Windows)
+        } else if user_agent.contains("Macintosh") {
+            Some(Platform::MacOS)
+        } else if user_agent.contains("Linux") {
+            Some(Platform::Linux)
+        } else {
+            None
         }
     }
 
@@ -69,18 +96,32 @@ impl Platform {
             Platform::MacOS => "\"macOS\"",
             Platform::Windows => "\"Windows\"",
             Platform::Linux => "\"Linux\"",
+            Platform::Android => "\"Android\"",
         }
     }
 }
 
+/// Major version after `token` in a user agent (`Chrome/` → 131)
+fn version_major(user_agent: &str, token: &str) -> Option<u32> {
+    let version = user_agent.split(token).nth(1)?;
+    version
+        .split(|c: char| !c.is_ascii_digit())
+        .next()?
+        .parse()
+        .ok()
+}
+
 /// Generate a realistic Chrome browser profile
 #[must_use]
 pub fn chrome_profile() -> BrowserProfile {
-    let mut rng = rand::thread_rng();
-    let platform = Platform::random();
+    chrome_profile_with(&mut rand::thread_rng())
+}
+
+fn chrome_profile_with(rng: &mut impl Rng) -> BrowserProfile {
+    let platform = Platform::random(rng);
     let (major, full) = BROWSER_VERSIONS
         .chrome
-        .choose(&mut rng)
+        .choose(rng)
         .expect("Chrome versions list should not be empty");
 
     let user_agent = format!(
@@ -99,11 +140,12 @@ pub fn chrome_profile() -> BrowserProfile {
     BrowserProfile {
         user_agent,
         accept: "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,image/apng,*/*;q=0.8,application/signed-exchange;v=b3;q=0.7".to_string(),
-        accept_language: random_accept_language(),
+        accept_language: random_accept_language(rng),
         accept_encoding: "gzip, deflate, br, zstd".to_string(),
         sec_ch_ua: brands.join(", "),
         sec_ch_ua_mobile: "?0".to_string(),
         sec_ch_ua_platform: platform.sec_ch_platform().to_string(),
+        sec_ch_ua_platform_version: String::new(),
         sec_fetch_dest: "document".to_string(),
         sec_fetch_mode: "navigate".to_string(),
         sec_fetch_site: "none".to_string(),
@@ -114,11 +156,14 @@ pub fn chrome_profile() -> BrowserProfile {
 /// Generate a realistic Firefox browser profile
 #[must_use]
 pub fn firefox_profile() -> BrowserProfile {
-    let mut rng = rand::thread_rng();
-    let platform = Platform::random();
+    firefox_profile_with(&mut rand::thread_rng())
+}
+
+fn firefox_profile_with(rng: &mut impl Rng) -> BrowserProfile {
+    let platform = Platform::random(rng);
     let version = BROWSER_VERSIONS
         .firefox
-        .choose(&mut rng)
+        .choose(rng)
         .expect("Firefox versions list should not be empty");
 
     let user_agent = format!(
@@ -133,12 +178,13 @@ pub fn firefox_profile() -> BrowserProfile {
         accept:
             "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,*/*;q=0.8"
                 .to_string(),
-        accept_language: random_accept_language(),
+        accept_language: random_accept_language(rng),
         accept_encoding: "gzip, deflate, br, zstd".to_string(),
         // Firefox doesn't send Sec-CH-UA headers
         sec_ch_ua: String::new(),
         sec_ch_ua_mobile: String::new(),
         sec_ch_ua_platform: String::new(),
+        sec_ch_ua_platform_version: String::new(),
         sec_fetch_dest: "document".to_string(),
         sec_fetch_mode: "navigate".to_string(),
         sec_fetch_site: "none".to_string(),
@@ -149,10 +195,13 @@ pub fn firefox_profile() -> BrowserProfile {
 /// Generate a realistic Safari browser profile
 #[must_use]
 pub fn safari_profile() -> BrowserProfile {
-    let mut rng = rand::thread_rng();
+    safari_profile_with(&mut rand::thread_rng())
+}
+
+fn safari_profile_with(rng: &mut impl Rng) -> BrowserProfile {
     let (version, webkit) = BROWSER_VERSIONS
         .safari
-        .choose(&mut rng)
+        .choose(rng)
         .expect("Safari versions list should not be empty");
 
     // Safari only runs on macOS/iOS - always use macOS for desktop
@@ -163,12 +212,13 @@ pub fn safari_profile() -> BrowserProfile {
     BrowserProfile {
         user_agent,
         accept: "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8".to_string(),
-        accept_language: random_accept_language(),
+        accept_language: random_accept_language(rng),
         accept_encoding: "gzip, deflate, br".to_string(), // Safari doesn't support zstd yet
         // Safari doesn't send Sec-CH-UA headers
         sec_ch_ua: String::new(),
         sec_ch_ua_mobile: String::new(),
         sec_ch_ua_platform: String::new(),
+        sec_ch_ua_platform_version: String::new(),
         sec_fetch_dest: "document".to_string(),
         sec_fetch_mode: "navigate".to_string(),
         sec_fetch_site: "none".to_string(),
@@ -176,24 +226,172 @@ pub fn safari_profile() -> BrowserProfile {
     }
 }
 
+/// Generate a realistic Microsoft Edge browser profile
+#[must_use]
+pub fn edge_profile() -> BrowserProfile {
+    edge_profile_with(&mut rand::thread_rng())
+}
+
+fn edge_profile_with(rng: &mut impl Rng) -> BrowserProfile {
+    let platform = Platform::random(rng);
+    // Edge follows Chromium's release train
+    let (major, full) = BROWSER_VERSIONS
+        .chrome
+        .choose(rng)
+        .expect("Chrome versions list should not be empty");
+
+    let user_agent = format!(
+        "Mozilla/5.0 ({}) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/{full} Safari/537.36 Edg/{full}",
+        platform.os_string()
+    );
+    let brands = [
+        format!("\"Microsoft Edge\";v=\"{major}\""),
+        format!("\"Chromium\";v=\"{major}\""),
+        "\"Not_A Brand\";v=\"24\"".to_string(),
+    ];
+
+    BrowserProfile {
+        user_agent,
+        sec_ch_ua: brands.join(", "),
+        sec_ch_ua_platform: platform.sec_ch_platform().to_string(),
+        ..chrome_profile_with(rng)
+    }
+}
+
+/// Android releases: version in `Sec-CH-UA-Platform-Version`, with rough weight
+const ANDROID_VERSIONS: [(&str, u32); 4] = [
+    ("15.0.0", 30),
+    ("14.0.0", 35),
+    ("13.0.0", 20),
+    ("12.0.0", 15),
+];
+
+/// iOS releases: UA version (`18_3`) and Safari version (`18.3`)
+const IOS_VERSIONS: [(&str, &str); 4] = [
+    ("18_3", "18.3"),
+    ("18_1", "18.1"),
+    ("17_6", "17.6"),
+    ("17_5", "17.5"),
+];
+
+/// Generate a realistic Chrome on Android profile
+///
+/// The user agent is Chrome's reduced one (`Android 10; K`, minor versions
+/// zeroed), so the device's real Android version is only in
+/// `Sec-CH-UA-Platform-Version`.
+#[must_use]
+pub fn android_chrome_profile() -> BrowserProfile {
+    android_chrome_profile_with(&mut rand::thread_rng())
+}
+
+fn android_chrome_profile_with(rng: &mut impl Rng) -> BrowserProfile {
+    let (major, _) = BROWSER_VERSIONS
+        .chrome
+        .choose(rng)
+        .expect("Chrome versions list should not be empty");
+    let (android, _) = ANDROID_VERSIONS
+        .choose_weighted(rng, |(_, weight)| *weight)
+        .expect("Android versions list should not be empty");
+    let brands = [
+        format!("\"Google Chrome\";v=\"{major}\""),
+        format!("\"Chromium\";v=\"{major}\""),
+        "\"Not_A Brand\";v=\"24\"".to_string(),
+    ];
+
+    BrowserProfile {
+        user_agent: format!(
+            "Mozilla/5.0 ({}) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/{major}.0.0.0 Mobile Safari/537.36",
+            Platform::Android.os_string()
+        ),
+        sec_ch_ua: brands.join(", "),
+        sec_ch_ua_mobile: "?1".to_string(),
+        sec_ch_ua_platform: Platform::Android.sec_ch_platform().to_string(),
+        sec_ch_ua_platform_version: format!("\"{android}\""),
+        ..chrome_profile_with(rng)
+    }
+}
+
+/// Generate a realistic Safari on iPhone profile
+///
+/// Like desktop Safari it sends no `Sec-CH-UA` headers; the platform and
+/// version are only in the user agent.
+#[must_use]
+pub fn ios_safari_profile() -> BrowserProfile {
+    ios_safari_profile_with(&mut rand::thread_rng())
+}
+
+fn ios_safari_profile_with(rng: &mut impl Rng) -> BrowserProfile {
+    let (os, version) = IOS_VERSIONS
+        .choose(rng)
+        .expect("iOS versions list should not be empty");
+
+    BrowserProfile {
+        user_agent: format!(
+            "Mozilla/5.0 (iPhone; CPU iPhone OS {os} like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/{version} Mobile/15E148 Safari/604.1"
+        ),
+        ..safari_profile_with(rng)
+    }
+}
+
+/// Generate a random mobile profile (weighted by market share)
+///
+/// Many sites serve lighter HTML to phones.
+#[must_use]
+pub fn mobile_profile() -> BrowserProfile {
+    // Realistic distribution: Android 70%, iOS 30%
+    let mut rng = rand::thread_rng();
+    if rng.gen_bool(0.7) {
+        android_chrome_profile_with(&mut rng)
+    } else {
+        ios_safari_profile_with(&mut rng)
+    }
+}
+
+/// Generate a profile for a specific browser (Chromium-based browsers without
+/// a profile of their own use Chrome's)
+#[must_use]
+pub fn profile_for(browser: BrowserType) -> BrowserProfile {
+    match browser {
+        BrowserType::Chrome | BrowserType::Brave | BrowserType::Dia => chrome_profile(),
+        BrowserType::Firefox => firefox_profile(),
+        BrowserType::Safari => safari_profile(),
+        BrowserType::Edge => edge_profile(),
+    }
+}
+
 /// Generate a random browser profile (weighted by market share)
 #[must_use]
 pub fn random_profile() -> BrowserProfile {
-    let mut rng = rand::thread_rng();
+    random_profile_with(&mut rand::thread_rng())
+}
+
+/// Generate the browser profile [`random_profile`] would pick for `seed`
+///
+/// The same seed always yields the same platform, browser version and
+/// `Accept-Language`, so a blocked request can be replayed exactly or a
+/// whole session can keep one fingerprint. Versions come from the cached
+/// version list, so a seed's profile changes when that list is refreshed.
+#[must_use]
+pub fn profile_from_seed(seed: u64) -> BrowserProfile {
+    random_profile_with(&mut StdRng::seed_from_u64(seed))
+}
+
+fn random_profile_with(rng: &mut impl Rng) -> BrowserProfile {
     // Realistic distribution: Chrome 65%, Safari 20%, Firefox 10%, Edge 5%
     let roll: f32 = rng.gen();
     if roll < 0.65 {
-        chrome_profile()
+        chrome_profile_with(rng)
     } else if roll < 0.85 {
-        safari_profile()
+        safari_profile_with(rng)
+    } else if roll < 0.95 {
+        firefox_profile_with(rng)
     } else {
-        firefox_profile()
+        edge_profile_with(rng)
     }
 }
 
 /// Generate random Accept-Language header
-fn random_accept_language() -> String {
-    let mut rng = rand::thread_rng();
+fn random_accept_language(rng: &mut impl Rng) -> String {
     let languages = [
         "en-US,en;q=0.9",
         "en-GB,en;q=0.9",
@@ -204,14 +402,430 @@ fn random_accept_language() -> String {
         "fi-FI,fi;q=0.9,en;q=0.8",
     ];
     languages
-        .choose(&mut rng)
+        .choose(rng)
         .expect("Languages list should not be empty")
         .to_string()
 }
 
+/// Kind of resource a request loads, as in its `Sec-Fetch-Dest`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RequestDest {
+    /// A page loaded by navigation
+    #[default]
+    Document,
+    /// `<img>` and CSS images
+    Image,
+    /// `<script src>`
+    Script,
+    /// `fetch()`/XHR, e.g. a discovered API endpoint
+    Fetch,
+}
+
+/// Browser engine behind a profile, which decides its subresource `Accept`
+/// and header order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Engine {
+    Chromium,
+    Gecko,
+    WebKit,
+}
+
+impl Engine {
+    /// Lowercase header names in the order the browser sends them;
+    /// anti-bot systems compare it against the claimed user agent
+    fn header_order(self) -> &'static [&'static str] {
+        match self {
+            Engine::Chromium => &[
+                "cache-control",
+                "sec-ch-ua",
+                "sec-ch-ua-mobile",
+                "sec-ch-ua-platform",
+                "sec-ch-ua-platform-version",
+                "upgrade-insecure-requests",
+                "user-agent",
+                "accept",
+                "origin",
+                "sec-fetch-site",
+                "sec-fetch-mode",
+                "sec-fetch-user",
+                "sec-fetch-dest",
+                "referer",
+                "accept-encoding",
+                "accept-language",
+                "cookie",
+            ],
+            Engine::Gecko => &[
+                "user-agent",
+                "accept",
+                "accept-language",
+                "accept-encoding",
+                "origin",
+                "referer",
+                "cookie",
+                "upgrade-insecure-requests",
+                "sec-fetch-dest",
+                "sec-fetch-mode",
+                "sec-fetch-site",
+                "sec-fetch-user",
+                "cache-control",
+            ],
+            Engine::WebKit => &[
+                "sec-fetch-dest",
+                "user-agent",
+                "accept",
+                "sec-fetch-site",
+                "sec-fetch-mode",
+                "sec-fetch-user",
+                "origin",
+                "referer",
+                "accept-language",
+                "upgrade-insecure-requests",
+                "accept-encoding",
+                "cookie",
+                "cache-control",
+            ],
+        }
+    }
+}
+
+/// Headers a browser sends on navigations and never on `fetch()`/XHR
+const NAVIGATION_ONLY_HEADERS: [&str; 3] =
+    ["Sec-Fetch-User", "Upgrade-Insecure-Requests", "Cache-Control"];
+
+/// Low-entropy `Sec-CH-UA` client hints, which only Chromium sends, on
+/// every request
+const CLIENT_HINT_HEADERS: [&str; 3] = ["Sec-CH-UA", "Sec-CH-UA-Mobile", "Sec-CH-UA-Platform"];
+
+/// How a request was initiated, which decides its `Sec-Fetch-*` headers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RequestContext {
+    /// User navigation from the address bar (`navigate`, `document`, `none`)
+    #[default]
+    TopLevel,
+    /// `fetch()`/XHR from a page to its own origin
+    SameOriginApi,
+    /// `fetch()`/XHR from a page to another origin
+    CrossOriginApi,
+}
+
+impl RequestContext {
+    /// API context for a request to `target` made by the page at `page`
+    #[must_use]
+    pub fn api(page: &str, target: &str) -> Self {
+        let origin = |u: &str| url::Url::parse(u).ok().map(|u| u.origin());
+        match (origin(page), origin(target)) {
+            (Some(page), Some(target)) if page == target => Self::SameOriginApi,
+            _ => Self::CrossOriginApi,
+        }
+    }
+
+    /// `Sec-Fetch-Site`, `Sec-Fetch-Mode` and `Sec-Fetch-Dest` values
+    fn sec_fetch(self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            Self::TopLevel => ("none", "navigate", "document"),
+            Self::SameOriginApi => ("same-origin", "cors", "empty"),
+            Self::CrossOriginApi => ("cross-site", "cors", "empty"),
+        }
+    }
+}
+
+/// `Referer` and `Origin` a browser sends when the page at `page` requests `target`
+///
+/// Follows the default `strict-origin-when-cross-origin` policy: the page URL
+/// without its fragment for same-origin requests, only its origin for
+/// cross-origin ones, and no `Referer` on an HTTPS to HTTP downgrade. `Origin`
+/// accompanies cross-origin and non-GET/HEAD requests.
+#[must_use]
+pub fn referrer_headers(page: &str, target: &str, method: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    let (Ok(mut page), Ok(target)) = (url::Url::parse(page), url::Url::parse(target)) else {
+        return headers;
+    };
+    page.set_fragment(None);
+    let origin = page.origin().ascii_serialization();
+    let same_origin = page.origin() == target.origin();
+    let downgrade = page.scheme() == "https" && target.scheme() == "http";
+
+    let referer = if same_origin {
+        Some(page.to_string())
+    } else if downgrade {
+        None
+    } else {
+        Some(format!("{origin}/"))
+    };
+    if let Some(value) = referer.and_then(|r| HeaderValue::from_str(&r).ok()) {
+        headers.insert(REFERER, value);
+    }
+
+    let safe_method = method.eq_ignore_ascii_case("GET") || method.eq_ignore_ascii_case("HEAD");
+    if !same_origin || !safe_method {
+        if let Ok(value) = HeaderValue::from_str(&origin) {
+            headers.insert(ORIGIN, value);
+        }
+    }
+    headers
+}
+
 impl BrowserProfile {
-    /// Convert profile to reqwest `HeaderMap`
+    /// `Accept` this profile's browser sends when loading `dest`
+    ///
+    /// Documents use the profile's own [`accept`](Self::accept); scripts and
+    /// `fetch()` send `*/*` in every engine, images the engine's image list.
+    #[must_use]
+    pub fn accept_for(&self, dest: RequestDest) -> &str {
+        match dest {
+            RequestDest::Document => &self.accept,
+            RequestDest::Script | RequestDest::Fetch => "*/*",
+            RequestDest::Image => match self.engine() {
+                Engine::Chromium => "image/avif,image/webp,image/apng,image/svg+xml,image/*,*/*;q=0.8",
+                Engine::Gecko => "image/avif,image/webp,image/png,image/svg+xml,image/*;q=0.8,*/*;q=0.5",
+                Engine::WebKit => {
+                    "image/webp,image/avif,image/jxl,image/heic,image/heic-sequence,video/*;q=0.8,image/png,image/svg+xml,image/*;q=0.8,*/*;q=0.5"
+                }
+            },
+        }
+    }
+
+    /// Send exactly `user_agent`, keeping the rest of the profile
+    ///
+    /// For a Chromium agent the `Sec-CH-UA` hints are rebuilt to agree with
+    /// it: brand versions from its `Chrome/`, `Edg/` or `OPR/` token, platform
+    /// and mobile flag from its OS. Other agents get no hints, since only
+    /// Chromium sends them.
+    #[must_use]
+    pub fn with_user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        let chrome = version_major(&self.user_agent, "Chrome/")
+            .or_else(|| version_major(&self.user_agent, "Chromium/"));
+        if let Some(chrome) = chrome {
+            self.match_client_hints(chrome);
+        } else {
+            self.sec_ch_ua.clear();
+            self.sec_ch_ua_mobile.clear();
+            self.sec_ch_ua_platform.clear();
+            self.sec_ch_ua_platform_version.clear();
+        }
+        self
+    }
+
+    /// Rebuild the client hints for a Chromium user agent at major `chrome`
+    fn match_client_hints(&mut self, chrome: u32) {
+        let ua = &self.user_agent;
+        let (brand, version) = if let Some(edge) = version_major(ua, "Edg/") {
+            ("Microsoft Edge", edge)
+        } else if let Some(opera) = version_major(ua, "OPR/") {
+            ("Opera", opera)
+        } else if self.sec_ch_ua.contains("\"Brave\"") {
+            // Brave's user agent is Chrome's; only the hints name it
+            ("Brave", chrome)
+        } else {
+            ("Google Chrome", chrome)
+        };
+        let grease = self
+            .sec_ch_ua
+            .split(", ")
+            .find(|entry| entry.starts_with("\"Not"))
+            .unwrap_or("\"Not_A Brand\";v=\"24\"");
+        self.sec_ch_ua =
+            format!("\"{brand}\";v=\"{version}\", \"Chromium\";v=\"{chrome}\", {grease}");
+
+        let platform = Platform::from_user_agent(&self.user_agent)
+            .map_or("\"Unknown\"", |platform| platform.sec_ch_platform());
+        if self.sec_ch_ua_platform != platform {
+            self.sec_ch_ua_platform = platform.to_string();
+            // The OS version described the old platform
+            self.sec_ch_ua_platform_version.clear();
+        }
+        let mobile = if self.user_agent.contains(" Mobile") {
+            "?1"
+        } else {
+            "?0"
+        };
+        self.sec_ch_ua_mobile = mobile.to_string();
+    }
+
+    fn engine(&self) -> Engine {
+        let ua = &self.user_agent;
+        if ua.contains("Firefox/") {
+            Engine::Gecko
+        } else if ua.contains("Safari/") && !ua.contains("Chrome/") {
+            Engine::WebKit
+        } else {
+            Engine::Chromium
+        }
+    }
+
+    /// Convert profile to reqwest `HeaderMap` for a top-level navigation
     pub fn to_headers(&self) -> HeaderMap {
+        self.to_headers_for(RequestContext::TopLevel)
+    }
+
+    /// Convert profile to reqwest `HeaderMap` for a request in `context`
+    ///
+    /// API contexts send `Accept: */*` and drop the navigation-only
+    /// `Sec-Fetch-User`, `Upgrade-Insecure-Requests` and `Cache-Control`.
+    /// reqwest fills in a client's default headers without ever removing
+    /// one, so build clients with [`to_client_headers`](Self::to_client_headers).
+    pub fn to_headers_for(&self, context: RequestContext) -> HeaderMap {
+        let mut headers = self.to_navigation_headers();
+        if context == RequestContext::TopLevel {
+            return headers;
+        }
+
+        let (site, mode, dest) = context.sec_fetch();
+        headers.insert(
+            ACCEPT,
+            HeaderValue::from_str(self.accept_for(RequestDest::Fetch))
+                .expect("Accept should be valid header value"),
+        );
+        headers.insert("Sec-Fetch-Site", HeaderValue::from_static(site));
+        headers.insert("Sec-Fetch-Mode", HeaderValue::from_static(mode));
+        headers.insert("Sec-Fetch-Dest", HeaderValue::from_static(dest));
+        for name in NAVIGATION_ONLY_HEADERS {
+            headers.remove(name);
+        }
+        // Removal reorders the map
+        self.order_headers(headers).into_iter().collect()
+    }
+
+    /// Headers for a subresource of kind `dest` that the page at `page`
+    /// loads from `target`
+    ///
+    /// `Accept` follows [`accept_for`](Self::accept_for). `fetch()` is a CORS
+    /// request; images and scripts are `no-cors` with their own
+    /// `Sec-Fetch-Dest`. `Referer` and, where a browser would send it,
+    /// `Origin` come from [`referrer_headers`]. A `Document` destination is
+    /// a navigation and gets [`to_headers`](Self::to_headers).
+    pub fn to_subresource_headers(
+        &self,
+        page: &str,
+        target: &str,
+        dest: RequestDest,
+    ) -> HeaderMap {
+        if dest == RequestDest::Document {
+            return self.to_headers();
+        }
+        let mut headers = self.to_headers_for(RequestContext::api(page, target));
+        headers.insert(
+            ACCEPT,
+            HeaderValue::from_str(self.accept_for(dest))
+                .expect("Accept should be valid header value"),
+        );
+        let mut referrer = referrer_headers(page, target, "GET");
+        let fetch_dest = match dest {
+            RequestDest::Image => Some("image"),
+            RequestDest::Script => Some("script"),
+            RequestDest::Fetch | RequestDest::Document => None,
+        };
+        if let Some(fetch_dest) = fetch_dest {
+            headers.insert("Sec-Fetch-Mode", HeaderValue::from_static("no-cors"));
+            headers.insert("Sec-Fetch-Dest", HeaderValue::from_static(fetch_dest));
+            // `no-cors` GETs carry no `Origin`
+            referrer.remove(ORIGIN);
+        }
+        headers.extend(referrer);
+        self.order_headers(headers).into_iter().collect()
+    }
+
+    /// Default headers for a client using this profile: the navigation
+    /// headers without those only a navigation sends and without client hints
+    ///
+    /// Navigations add [`navigation_only_headers`](Self::navigation_only_headers)
+    /// per request, and API calls made with the same client don't inherit
+    /// them. [`client_hint_headers`](Self::client_hint_headers) are added per
+    /// request too, so a host pinned to a browser without them gets none.
+    pub fn to_client_headers(&self) -> HeaderMap {
+        let mut headers = self.to_navigation_headers();
+        for name in NAVIGATION_ONLY_HEADERS.iter().chain(&CLIENT_HINT_HEADERS) {
+            headers.remove(*name);
+        }
+        self.order_headers(headers).into_iter().collect()
+    }
+
+    /// The navigation-only headers [`to_headers`](Self::to_headers) sends
+    /// and [`to_client_headers`](Self::to_client_headers) leaves out
+    pub fn navigation_only_headers(&self) -> HeaderMap {
+        self.headers_named(&NAVIGATION_ONLY_HEADERS)
+    }
+
+    /// The `Sec-CH-UA` client hints [`to_client_headers`](Self::to_client_headers)
+    /// leaves out; empty unless the profile is Chromium based
+    pub fn client_hint_headers(&self) -> HeaderMap {
+        self.headers_named(&CLIENT_HINT_HEADERS)
+    }
+
+    /// `Sec-CH-UA-Platform-Version`, if this Chromium profile has one
+    ///
+    /// It is a high-entropy hint: Chrome only sends it to an origin whose
+    /// `Accept-CH` response header asked for it, so it is never part of
+    /// [`to_headers`](Self::to_headers).
+    pub fn platform_version_header(&self) -> Option<HeaderValue> {
+        if self.sec_ch_ua.is_empty() || self.sec_ch_ua_platform_version.is_empty() {
+            return None;
+        }
+        HeaderValue::from_str(&self.sec_ch_ua_platform_version).ok()
+    }
+
+    fn headers_named(&self, names: &[&str]) -> HeaderMap {
+        self.to_navigation_headers()
+            .into_iter()
+            .filter_map(|(name, value)| Some((name?, value)))
+            .filter(|(name, _)| {
+                names
+                    .iter()
+                    .any(|only| name.as_str().eq_ignore_ascii_case(only))
+            })
+            .collect()
+    }
+
+    /// Headers for a top-level navigation in the order this profile's
+    /// browser sends them
+    ///
+    /// Chrome, Firefox and Safari each have their own order, e.g. Chrome
+    /// sends `Sec-CH-UA` before `User-Agent` and Firefox starts with
+    /// `User-Agent`. [`to_headers`](Self::to_headers) iterates in the same
+    /// order, which is what reqwest writes on the wire.
+    pub fn to_ordered_headers(&self) -> Vec<(HeaderName, HeaderValue)> {
+        self.to_ordered_headers_for(RequestContext::TopLevel)
+    }
+
+    /// [`to_ordered_headers`](Self::to_ordered_headers) for a request in `context`
+    pub fn to_ordered_headers_for(
+        &self,
+        context: RequestContext,
+    ) -> Vec<(HeaderName, HeaderValue)> {
+        self.order_headers(self.to_headers_for(context))
+    }
+
+    /// Sort `headers` (e.g. a profile's headers plus `Cookie` and `Referer`)
+    /// into this profile's browser order; headers the browser wouldn't send
+    /// keep their relative order at the end
+    pub fn order_headers(&self, headers: HeaderMap) -> Vec<(HeaderName, HeaderValue)> {
+        let order = self.engine().header_order();
+        let mut ordered: Vec<(HeaderName, HeaderValue)> = Vec::with_capacity(headers.len());
+        let mut name = None;
+        for (key, value) in headers {
+            // Repeated values of one header come with `None`
+            if key.is_some() {
+                name = key;
+            }
+            if let Some(ref name) = name {
+                ordered.push((name.clone(), value));
+            }
+        }
+        // Stable, so repeated headers and unknown ones keep their order
+        ordered.sort_by_key(|(name, _)| {
+            order
+                .iter()
+                .position(|known| *known == name.as_str())
+                .unwrap_or(order.len())
+        });
+        ordered
+    }
+
+    /// Navigation headers in browser order (see
+    /// [`to_ordered_headers`](Self::to_ordered_headers))
+    fn to_navigation_headers(&self) -> HeaderMap {
         let mut headers = HeaderMap::new();
 
         // These header values are generated from known-good static strings or controlled values,
@@ -281,7 +895,7 @@ impl BrowserProfile {
         headers.insert("Upgrade-Insecure-Requests", HeaderValue::from_static("1"));
         headers.insert("Cache-Control", HeaderValue::from_static("max-age=0"));
 
-        headers
+        self.order_headers(headers).into_iter().collect()
     }
 }
 
@@ -310,6 +924,197 @@ mod tests {
         assert!(profile.user_agent.contains("Macintosh"));
     }
 
+    #[test]
+    fn test_edge_profile() {
+        let profile = edge_profile();
+        assert!(profile.user_agent.contains(" Edg/"));
+        assert!(profile.sec_ch_ua.contains("Microsoft Edge"));
+        let major = profile.user_agent.split(" Edg/").nth(1).unwrap();
+        let major = major.split('.').next().unwrap();
+        assert!(profile
+            .sec_ch_ua
+            .contains(&format!("\"Microsoft Edge\";v=\"{major}\"")));
+        let platform = if profile.user_agent.contains("Windows") {
+            "\"Windows\""
+        } else if profile.user_agent.contains("Macintosh") {
+            "\"macOS\""
+        } else {
+            "\"Linux\""
+        };
+        assert_eq!(profile.sec_ch_ua_platform, platform);
+        assert_eq!(profile.sec_ch_ua_mobile, "?0");
+        assert!(profile_for(BrowserType::Edge).user_agent.contains(" Edg/"));
+        assert!(profile_for(BrowserType::Brave)
+            .user_agent
+            .contains("Chrome/"));
+    }
+
+    #[test]
+    fn test_random_profile_includes_edge() {
+        let edge = (0..400)
+            .map(profile_from_seed)
+            .filter(|p| p.user_agent.contains(" Edg/"))
+            .count();
+        // About 5%
+        assert!((5..=40).contains(&edge), "{edge} Edge profiles in 400");
+    }
+
+    #[test]
+    fn test_android_chrome_profile() {
+        let profile = android_chrome_profile();
+        assert!(profile
+            .user_agent
+            .starts_with("Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36"));
+        assert!(profile.user_agent.ends_with(".0.0.0 Mobile Safari/537.36"));
+        let major = profile.user_agent.split("Chrome/").nth(1).unwrap();
+        let major = major.split('.').next().unwrap();
+        assert!(profile
+            .sec_ch_ua
+            .contains(&format!("\"Google Chrome\";v=\"{major}\"")));
+
+        let headers = profile.to_headers();
+        assert_eq!(headers["Sec-CH-UA-Mobile"], "?1");
+        assert_eq!(headers["Sec-CH-UA-Platform"], "\"Android\"");
+        // High entropy, so only sent after an `Accept-CH` opt-in
+        assert!(!headers.contains_key("Sec-CH-UA-Platform-Version"));
+        let version = profile.platform_version_header().unwrap();
+        let version = version.to_str().unwrap();
+        assert!(ANDROID_VERSIONS
+            .iter()
+            .any(|(v, _)| version == format!("\"{v}\"")));
+
+        // A desktop agent drops the device's version along with the hints
+        let desktop = profile.with_user_agent("Mozilla/5.0 (X11; Linux x86_64) Firefox/130.0");
+        assert!(desktop.sec_ch_ua_platform_version.is_empty());
+    }
+
+    #[test]
+    fn test_ios_safari_profile() {
+        let profile = ios_safari_profile();
+        let (os, version) = IOS_VERSIONS
+            .iter()
+            .find(|(os, _)| profile.user_agent.contains(&format!("iPhone OS {os} ")))
+            .unwrap();
+        assert!(os.starts_with(&version.replace('.', "_")));
+        assert!(profile
+            .user_agent
+            .contains(&format!("Version/{version} Mobile/15E148 Safari/604.1")));
+        assert!(profile.accept.starts_with("text/html"));
+        assert!(!profile.to_headers().contains_key("Sec-CH-UA-Mobile"));
+        assert_eq!(
+            profile.accept_for(RequestDest::Image),
+            safari_profile().accept_for(RequestDest::Image)
+        );
+
+        for _ in 0..10 {
+            let ua = mobile_profile().user_agent;
+            assert!(ua.contains("Mobile"), "{ua}");
+        }
+    }
+
+    #[test]
+    fn test_profile_from_seed_is_deterministic() {
+        let profiles: Vec<BrowserProfile> = (0..20).map(profile_from_seed).collect();
+        for (seed, profile) in (0..20).zip(&profiles) {
+            assert_eq!(&profile_from_seed(seed), profile);
+        }
+
+        // Seeds still spread over browsers and languages
+        let distinct = |field: fn(&BrowserProfile) -> &str| {
+            let mut values: Vec<&str> = profiles.iter().map(field).collect();
+            values.sort_unstable();
+            values.dedup();
+            values.len()
+        };
+        assert!(distinct(|p| &p.user_agent) > 1);
+        assert!(distinct(|p| &p.accept_language) > 1);
+    }
+
+    #[test]
+    fn test_header_order_follows_browser() {
+        let names = |profile: &BrowserProfile| {
+            profile
+                .to_ordered_headers()
+                .into_iter()
+                .map(|(name, _)| name.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(&chrome_profile()),
+            [
+                "cache-control",
+                "sec-ch-ua",
+                "sec-ch-ua-mobile",
+                "sec-ch-ua-platform",
+                "upgrade-insecure-requests",
+                "user-agent",
+                "accept",
+                "sec-fetch-site",
+                "sec-fetch-mode",
+                "sec-fetch-user",
+                "sec-fetch-dest",
+                "accept-encoding",
+                "accept-language",
+            ]
+        );
+        assert_eq!(
+            names(&firefox_profile())[..4],
+            ["user-agent", "accept", "accept-language", "accept-encoding"]
+        );
+        assert_eq!(
+            names(&safari_profile())[..3],
+            ["sec-fetch-dest", "user-agent", "accept"]
+        );
+        let android = android_chrome_profile();
+        let mut headers = android.to_headers();
+        headers.insert(
+            "Sec-CH-UA-Platform-Version",
+            android.platform_version_header().unwrap(),
+        );
+        assert!(android
+            .order_headers(headers)
+            .windows(2)
+            .any(|pair| pair[0].0 == "sec-ch-ua-platform"
+                && pair[1].0 == "sec-ch-ua-platform-version"));
+
+        // The map iterates in the same order
+        let chrome = chrome_profile();
+        let map_names: Vec<String> = chrome
+            .to_headers()
+            .keys()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(map_names, names(&chrome));
+
+        // Added headers slot into place; unknown ones go last
+        let mut headers = chrome.to_headers_for(RequestContext::CrossOriginApi);
+        headers.insert(
+            "x-requested-with",
+            HeaderValue::from_static("XMLHttpRequest"),
+        );
+        headers.insert(REFERER, HeaderValue::from_static("https://example.com/"));
+        headers.insert(ORIGIN, HeaderValue::from_static("https://example.com"));
+        let ordered: Vec<String> = chrome
+            .order_headers(headers)
+            .into_iter()
+            .map(|(name, _)| name.to_string())
+            .collect();
+        assert_eq!(
+            ordered[ordered.len() - 8..],
+            [
+                "origin",
+                "sec-fetch-site",
+                "sec-fetch-mode",
+                "sec-fetch-dest",
+                "referer",
+                "accept-encoding",
+                "accept-language",
+                "x-requested-with",
+            ]
+        );
+    }
+
     #[test]
     fn test_headers_conversion() {
         let profile = random_profile();
@@ -318,6 +1123,119 @@ mod tests {
         assert!(headers.contains_key(ACCEPT));
     }
 
+    #[test]
+    fn test_sec_fetch_headers_follow_request_context() {
+        let profile = chrome_profile();
+        let page = "https://shop.example.com/products";
+
+        let top = profile.to_headers();
+        assert_eq!(top["Sec-Fetch-Mode"], "navigate");
+        assert_eq!(top["Sec-Fetch-Dest"], "document");
+        assert_eq!(top["Sec-Fetch-Site"], "none");
+        assert_eq!(top["Sec-Fetch-User"], "?1");
+
+        let context = RequestContext::api(page, "https://shop.example.com/api/items?page=2");
+        assert_eq!(context, RequestContext::SameOriginApi);
+        let api = profile.to_headers_for(context);
+        assert_eq!(api["Sec-Fetch-Mode"], "cors");
+        assert_eq!(api["Sec-Fetch-Dest"], "empty");
+        assert_eq!(api["Sec-Fetch-Site"], "same-origin");
+        assert_eq!(api[ACCEPT], "*/*");
+        assert!(!api.contains_key("Sec-Fetch-User"));
+        assert!(!api.contains_key("Upgrade-Insecure-Requests"));
+        assert!(!api.contains_key("Cache-Control"));
+        assert_eq!(api[USER_AGENT], top[USER_AGENT]);
+
+        let context = RequestContext::api(page, "https://api.other.com/v1/items");
+        assert_eq!(context, RequestContext::CrossOriginApi);
+        assert_eq!(
+            profile.to_headers_for(context)["Sec-Fetch-Site"],
+            "cross-site"
+        );
+    }
+
+    #[test]
+    fn test_client_headers_leave_out_navigation_only_headers() {
+        let profile = chrome_profile();
+        let client = profile.to_client_headers();
+        let navigation = profile.navigation_only_headers();
+        let hints = profile.client_hint_headers();
+
+        assert_eq!(navigation.len(), 3);
+        assert_eq!(navigation["Upgrade-Insecure-Requests"], "1");
+        assert!(hints.contains_key("Sec-CH-UA"));
+        for name in navigation.keys().chain(hints.keys()) {
+            assert!(!client.contains_key(name), "{name}");
+        }
+        let mut merged = client;
+        merged.extend(navigation);
+        merged.extend(hints);
+        assert_eq!(merged, profile.to_headers());
+        assert!(firefox_profile().client_hint_headers().is_empty());
+    }
+
+    #[test]
+    fn test_accept_follows_request_destination() {
+        let chrome = chrome_profile();
+        assert_eq!(chrome.accept_for(RequestDest::Fetch), "*/*");
+        assert_eq!(
+            chrome.accept_for(RequestDest::Document),
+            "text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,\
+             image/apng,*/*;q=0.8,application/signed-exchange;v=b3;q=0.7"
+        );
+        assert_eq!(chrome.accept_for(RequestDest::Script), "*/*");
+        assert!(chrome
+            .accept_for(RequestDest::Image)
+            .starts_with("image/avif,image/webp,image/apng"));
+
+        assert!(firefox_profile()
+            .accept_for(RequestDest::Image)
+            .starts_with("image/avif,image/webp,image/png"));
+        assert!(safari_profile()
+            .accept_for(RequestDest::Image)
+            .starts_with("image/webp,image/avif,image/jxl"));
+        assert!(edge_profile()
+            .accept_for(RequestDest::Image)
+            .starts_with("image/avif,image/webp,image/apng"));
+
+        let page = "https://shop.example.com/products";
+        let cdn = "https://cdn.example.net/lamp.webp";
+        let image = chrome.to_subresource_headers(page, cdn, RequestDest::Image);
+        assert_eq!(image[ACCEPT], chrome.accept_for(RequestDest::Image));
+        assert_eq!(image["Sec-Fetch-Dest"], "image");
+        assert_eq!(image["Sec-Fetch-Mode"], "no-cors");
+        assert_eq!(image["Sec-Fetch-Site"], "cross-site");
+        assert_eq!(image[REFERER], "https://shop.example.com/");
+        assert!(!image.contains_key(ORIGIN));
+        let api = chrome.to_subresource_headers(page, cdn, RequestDest::Fetch);
+        assert_eq!(api[ACCEPT], "*/*");
+        assert_eq!(api[ORIGIN], "https://shop.example.com");
+        assert_eq!(
+            chrome.to_subresource_headers(page, cdn, RequestDest::Document),
+            chrome.to_headers()
+        );
+    }
+
+    #[test]
+    fn test_referrer_headers_follow_default_policy() {
+        let page = "https://shop.example.com/products?id=3#reviews";
+
+        let same = referrer_headers(page, "https://shop.example.com/api/items", "GET");
+        assert_eq!(same[REFERER], "https://shop.example.com/products?id=3");
+        assert!(!same.contains_key(ORIGIN));
+
+        let post = referrer_headers(page, "https://shop.example.com/api/cart", "POST");
+        assert_eq!(post[ORIGIN], "https://shop.example.com");
+
+        let cross = referrer_headers(page, "https://api.example.net/v1", "GET");
+        assert_eq!(cross[REFERER], "https://shop.example.com/");
+        assert_eq!(cross[ORIGIN], "https://shop.example.com");
+
+        let downgrade = referrer_headers(page, "http://cdn.example.com/data.json", "GET");
+        assert!(!downgrade.contains_key(REFERER));
+        assert!(referrer_headers("not a url", page, "GET").is_empty());
+    }
+
     #[test]
     fn test_browser_versions_not_empty() {
         let versions = &*BROWSER_VERSIONS;
@@ -364,6 +1282,52 @@ mod tests {
         assert!(profile.sec_ch_ua_platform.is_empty());
     }
 
+    #[test]
+    fn test_custom_user_agent_drops_client_hints() {
+        let custom = chrome_profile().with_user_agent("nab-test/1.0");
+        let headers = custom.to_headers();
+        assert_eq!(headers["user-agent"], "nab-test/1.0");
+        assert!(!headers.contains_key("sec-ch-ua"));
+        assert!(!headers.contains_key("sec-ch-ua-platform"));
+        assert!(headers.contains_key("accept-language"));
+    }
+
+    #[test]
+    fn test_custom_chromium_user_agent_rewrites_client_hints() {
+        let chromium = chrome_profile().with_user_agent(
+            "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/99.0.0.0 Safari/537.36",
+        );
+        let headers = chromium.to_headers();
+        assert_eq!(
+            headers["sec-ch-ua"],
+            "\"Google Chrome\";v=\"99\", \"Chromium\";v=\"99\", \"Not_A Brand\";v=\"24\""
+        );
+        assert_eq!(headers["sec-ch-ua-platform"], "\"Linux\"");
+        assert_eq!(headers["sec-ch-ua-mobile"], "?0");
+
+        let edge = firefox_profile().with_user_agent(
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Edg/121.0.0.0",
+        );
+        assert_eq!(
+            edge.sec_ch_ua,
+            "\"Microsoft Edge\";v=\"121\", \"Chromium\";v=\"120\", \"Not_A Brand\";v=\"24\""
+        );
+        assert_eq!(edge.sec_ch_ua_platform, "\"Windows\"");
+
+        // Moving the Android profile to desktop drops its OS version
+        let desktop = android_chrome_profile().with_user_agent(
+            "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/130.0.0.0 Safari/537.36",
+        );
+        assert_eq!(desktop.sec_ch_ua_platform, "\"macOS\"");
+        assert_eq!(desktop.sec_ch_ua_mobile, "?0");
+        assert!(desktop.sec_ch_ua_platform_version.is_empty());
+        let mobile = android_chrome_profile().with_user_agent(
+            "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/130.0.0.0 Mobile Safari/537.36",
+        );
+        assert_eq!(mobile.sec_ch_ua_mobile, "?1");
+        assert!(!mobile.sec_ch_ua_platform_version.is_empty());
+    }
+
     #[test]
     fn test_safari_only_macos() {
         let profile = safari_profile();
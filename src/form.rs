@@ -2,20 +2,108 @@
 //!
 //! Smart form POST handling:
 //! - Extracts hidden fields (CSRF tokens, session tokens)
+//! - Finds CSRF tokens in form inputs, meta tags and double-submit cookies
 //! - Merges user-provided fields with hidden fields
 //! - Detects and uses correct encoding (urlencoded or multipart)
 //! - Supports form discovery with heuristics
+//! - Submits with the form's method, retrying only when repeating is safe
 
 use anyhow::{Context, Result};
+use reqwest::header::{HeaderMap, SET_COOKIE};
+use reqwest::{Method, Response};
 use scraper::{Html, Selector};
+use std::borrow::Cow;
 use std::collections::HashMap;
 
+use crate::http_client::AcceleratedClient;
+use crate::retry::RetryPolicy;
+
+/// Field, meta and cookie names commonly holding a CSRF token (lowercase)
+const CSRF_NAMES: &[&str] = &[
+    "csrf-token",
+    "csrf_token",
+    "csrftoken",
+    "_csrf",
+    "_token",
+    "authenticity_token",
+    "csrfmiddlewaretoken",
+    "xsrf-token",
+    "__requestverificationtoken",
+];
+
+/// Header carrying a token copied from a meta tag or cookie
+const CSRF_HEADER: &str = "X-CSRF-Token";
+
+/// A CSRF token and where it has to be sent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsrfToken {
+    /// Form field, as `<input name="authenticity_token">` carries it
+    Field { name: String, value: String },
+    /// Request header, for meta-tag tokens and double-submit cookies
+    Header { name: String, value: String },
+}
+
+impl CsrfToken {
+    /// The token itself
+    pub fn value(&self) -> &str {
+        match self {
+            Self::Field { value, .. } | Self::Header { value, .. } => value,
+        }
+    }
+}
+
+/// How [`Form::submit`] sends a form
+#[derive(Debug, Clone, Default)]
+pub struct SubmitOptions {
+    /// Method to use instead of the form's `method` attribute
+    pub method: Option<String>,
+    /// Sent as `Idempotency-Key` on every attempt
+    pub idempotency_key: Option<String>,
+    /// Extra attempts after failures the client's [`RetryPolicy`] retries.
+    /// Only used when repeating is safe: an idempotent method or an
+    /// idempotency key.
+    pub retries: u32,
+    /// CSRF token added as a field or header
+    pub csrf_token: Option<CsrfToken>,
+}
+
+impl SubmitOptions {
+    /// Override the form's method (e.g. `PUT`, `PATCH`)
+    #[must_use]
+    pub fn with_method(mut self, method: impl Into<String>) -> Self {
+        self.method = Some(method.into());
+        self
+    }
+
+    /// Send an `Idempotency-Key` header
+    #[must_use]
+    pub fn with_idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    /// Retry failed submissions up to `retries` times
+    #[must_use]
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Send a CSRF token, e.g. from [`Form::detect_csrf_token`]
+    #[must_use]
+    pub fn with_csrf_token(mut self, token: CsrfToken) -> Self {
+        self.csrf_token = Some(token);
+        self
+    }
+}
+
 /// A parsed HTML form
 #[derive(Debug, Clone)]
 pub struct Form {
     /// Form action URL (may be relative)
     pub action: String,
-    /// HTTP method (GET or POST)
+    /// HTTP method from the `method` attribute, upper-cased; `POST` when the
+    /// form has none, as nab has always submitted such forms
     pub method: String,
     /// Encoding type (application/x-www-form-urlencoded or multipart/form-data)
     pub enctype: String,
@@ -45,7 +133,7 @@ impl Form {
             let method = form_elem
                 .value()
                 .attr("method")
-                .unwrap_or("get")
+                .unwrap_or("post")
                 .to_uppercase();
             let enctype = form_elem
                 .value()
@@ -142,8 +230,13 @@ impl Form {
         let css_selector = Selector::parse(selector).map_err(|e| anyhow::anyhow!("{:?}", e))?;
 
         if let Some(element) = document.select(&css_selector).next() {
-            // Try to get value attribute (for input elements)
-            if let Some(value) = element.value().attr("value") {
+            // Try to get value attribute (for input elements), then content
+            // (for meta tags)
+            if let Some(value) = element
+                .value()
+                .attr("value")
+                .or_else(|| element.value().attr("content"))
+            {
                 return Ok(Some(value.to_string()));
             }
             // Try to get text content (for other elements)
@@ -156,6 +249,74 @@ impl Form {
         Ok(None)
     }
 
+    /// Find a CSRF token in HTML without a selector
+    ///
+    /// Inputs with a common token name (`authenticity_token`, `_token`,
+    /// `csrf_token`, ...) are sent back as that field; a
+    /// `<meta name="csrf-token">` tag as an `X-CSRF-Token` header, or the
+    /// header named by Spring's `<meta name="_csrf_header">`.
+    pub fn detect_csrf_token(html: &str) -> Option<CsrfToken> {
+        let document = Html::parse_document(html);
+        let named = |element: &scraper::ElementRef<'_>, value_attr: &str| {
+            let name = element.value().attr("name")?;
+            let value = element.value().attr(value_attr)?;
+            (CSRF_NAMES.contains(&name.to_ascii_lowercase().as_str()) && !value.is_empty())
+                .then(|| (name.to_string(), value.to_string()))
+        };
+
+        let inputs = Selector::parse("input[name][value]").ok()?;
+        if let Some((name, value)) = document
+            .select(&inputs)
+            .find_map(|input| named(&input, "value"))
+        {
+            return Some(CsrfToken::Field { name, value });
+        }
+
+        let metas = Selector::parse("meta[name][content]").ok()?;
+        let (_, value) = document
+            .select(&metas)
+            .find_map(|meta| named(&meta, "content"))?;
+        let header = document
+            .select(&metas)
+            .find(|meta| meta.value().attr("name") == Some("_csrf_header"))
+            .and_then(|meta| meta.value().attr("content"))
+            .unwrap_or(CSRF_HEADER);
+        Some(CsrfToken::Header {
+            name: header.to_string(),
+            value,
+        })
+    }
+
+    /// Find a double-submit CSRF cookie among a response's `Set-Cookie`
+    /// headers
+    ///
+    /// The value is copied into the header its framework expects:
+    /// `X-XSRF-TOKEN` for `XSRF-TOKEN`, `X-CSRFToken` for Django's
+    /// `csrftoken`, otherwise `X-CSRF-Token`.
+    pub fn csrf_cookie(headers: &HeaderMap) -> Option<CsrfToken> {
+        headers
+            .get_all(SET_COOKIE)
+            .iter()
+            .filter_map(|header| header.to_str().ok())
+            .filter_map(|cookie| cookie.split(';').next()?.split_once('='))
+            .map(|(name, value)| (name.trim(), value.trim().trim_matches('"')))
+            .find(|(name, value)| {
+                CSRF_NAMES.contains(&name.to_ascii_lowercase().as_str()) && !value.is_empty()
+            })
+            .map(|(name, value)| {
+                let header = match name.to_ascii_lowercase().as_str() {
+                    "xsrf-token" => "X-XSRF-TOKEN",
+                    "csrftoken" => "X-CSRFToken",
+                    _ => CSRF_HEADER,
+                };
+                CsrfToken::Header {
+                    name: header.to_string(),
+                    value: urlencoding::decode(value)
+                        .map_or_else(|_| value.to_string(), Cow::into_owned),
+                }
+            })
+    }
+
     /// Encode form data as application/x-www-form-urlencoded
     pub fn encode_urlencoded(&self) -> String {
         let mut pairs: Vec<String> = self
@@ -172,6 +333,55 @@ impl Form {
         &self.enctype
     }
 
+    /// Submit the form found on `page_url`
+    ///
+    /// `GET` forms send their fields as the query string, every other method
+    /// as a body encoded per [`content_type`](Self::content_type). Retries
+    /// go through the client's retry handling and reuse the same idempotency
+    /// key.
+    pub async fn submit(
+        &self,
+        client: &AcceleratedClient,
+        page_url: &str,
+        options: &SubmitOptions,
+    ) -> Result<Response> {
+        let form = match &options.csrf_token {
+            Some(CsrfToken::Field { name, value }) => {
+                let mut form = self.clone();
+                form.fields.insert(name.clone(), value.clone());
+                Cow::Owned(form)
+            }
+            _ => Cow::Borrowed(self),
+        };
+        let method = options.method.as_deref().unwrap_or(&self.method);
+        let method = Method::from_bytes(method.trim().to_ascii_uppercase().as_bytes())
+            .with_context(|| format!("Invalid HTTP method '{method}'"))?;
+        let mut action =
+            url::Url::parse(&form.resolve_action(page_url)?).context("Invalid form action URL")?;
+        if matches!(method, Method::GET | Method::HEAD) {
+            action.set_query(Some(&form.encode_urlencoded()));
+        }
+
+        let mut request = client.inner().request(method.clone(), action);
+        if !matches!(method, Method::GET | Method::HEAD) {
+            request = request
+                .header(reqwest::header::CONTENT_TYPE, form.content_type())
+                .body(form.encode_urlencoded());
+        }
+        if let Some(key) = &options.idempotency_key {
+            request = request.header("Idempotency-Key", key);
+        }
+        if let Some(CsrfToken::Header { name, value }) = &options.csrf_token {
+            request = request.header(name.as_str(), value);
+        }
+
+        let policy = RetryPolicy {
+            max_attempts: options.retries + 1,
+            ..client.retry_policy().clone()
+        };
+        client.execute_with_retry(request.build()?, &policy).await
+    }
+
     /// Resolve action URL against a base URL
     pub fn resolve_action(&self, base_url: &str) -> Result<String> {
         if self.action.is_empty() {
@@ -204,6 +414,8 @@ pub fn parse_field_args(field_args: &[String]) -> Result<HashMap<String, String>
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_support::{http1_client, serve, Request, Response};
+    use std::sync::{Arc, Mutex};
 
     #[test]
     fn test_parse_simple_form() {
@@ -285,6 +497,150 @@ mod tests {
         assert_eq!(fields.get("password"), Some(&"secret".to_string()));
     }
 
+    /// Serve `page` on `/form`; answer other requests with `statuses` in
+    /// turn (the last one repeats), logging them
+    fn mock_form_server(
+        page: &'static str,
+        statuses: &'static [u16],
+    ) -> (String, Arc<Mutex<Vec<Request>>>) {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        let mut submissions = 0;
+        let addr = serve(move |request| {
+            if request.method == "GET" && request.target == "/form" {
+                return Response::typed("text/html", page);
+            }
+            let status = statuses[submissions.min(statuses.len() - 1)];
+            submissions += 1;
+            log.lock().unwrap().push(request.clone());
+            Response::status(status).body("ok")
+        });
+        (format!("http://{addr}/form"), seen)
+    }
+
+    async fn first_form(client: &AcceleratedClient, url: &str) -> Form {
+        let html = client.fetch_text(url).await.unwrap();
+        Form::parse_all(&html).unwrap().remove(0)
+    }
+
+    #[tokio::test]
+    async fn submits_with_the_form_method() {
+        let (url, seen) = mock_form_server(
+            r#"<form action="/items/7" method="put"><input name="title" value="Lamp"></form>"#,
+            &[200],
+        );
+        let client = http1_client().await;
+        let form = first_form(&client, &url).await;
+
+        let response = form
+            .submit(&client, &url, &SubmitOptions::default())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), 200);
+        let seen = seen.lock().unwrap();
+        assert_eq!(
+            (seen[0].method.as_str(), seen[0].target.as_str()),
+            ("PUT", "/items/7")
+        );
+        assert_eq!(seen[0].body, b"title=Lamp");
+        assert_eq!(seen[0].header("idempotency-key"), None);
+    }
+
+    #[tokio::test]
+    async fn method_override_and_get_query() {
+        let (url, seen) = mock_form_server(
+            r#"<form action="/search" method="get"><input name="q" value="rust lang"></form>"#,
+            &[200],
+        );
+        let client = http1_client().await;
+        let form = first_form(&client, &url).await;
+
+        form.submit(&client, &url, &SubmitOptions::default())
+            .await
+            .unwrap();
+        form.submit(
+            &client,
+            &url,
+            &SubmitOptions::default().with_method("patch"),
+        )
+        .await
+        .unwrap();
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(
+            (seen[0].method.as_str(), seen[0].target.as_str()),
+            ("GET", "/search?q=rust%20lang")
+        );
+        assert_eq!(
+            (seen[1].method.as_str(), seen[1].target.as_str()),
+            ("PATCH", "/search")
+        );
+        assert_eq!(seen[1].body, b"q=rust%20lang");
+    }
+
+    #[tokio::test]
+    async fn forms_without_a_method_are_posted() {
+        let (url, seen) = mock_form_server(
+            r#"<form action="/login"><input name="user" value="ada"></form>"#,
+            &[200],
+        );
+        let client = http1_client().await;
+        let form = first_form(&client, &url).await;
+        assert_eq!(form.method, "POST");
+
+        form.submit(&client, &url, &SubmitOptions::default())
+            .await
+            .unwrap();
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(
+            (seen[0].method.as_str(), seen[0].target.as_str()),
+            ("POST", "/login")
+        );
+        assert_eq!(seen[0].body, b"user=ada");
+    }
+
+    #[tokio::test]
+    async fn retries_reuse_the_idempotency_key() {
+        let (url, seen) = mock_form_server(
+            r#"<form action="/orders" method="post"><input name="sku" value="A1"></form>"#,
+            &[503, 200],
+        );
+        let client = http1_client().await;
+        let form = first_form(&client, &url).await;
+        let options = SubmitOptions::default()
+            .with_idempotency_key("order-42")
+            .with_retries(2);
+
+        let response = form.submit(&client, &url, &options).await.unwrap();
+
+        assert_eq!(response.status(), 200);
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 2);
+        assert!(seen
+            .iter()
+            .all(|r| r.header("idempotency-key") == Some("order-42")));
+    }
+
+    #[tokio::test]
+    async fn post_without_key_is_not_retried() {
+        let (url, seen) = mock_form_server(
+            r#"<form action="/orders" method="post"><input name="sku" value="A1"></form>"#,
+            &[503, 200],
+        );
+        let client = http1_client().await;
+        let form = first_form(&client, &url).await;
+
+        let response = form
+            .submit(&client, &url, &SubmitOptions::default().with_retries(2))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), 503);
+        assert_eq!(seen.lock().unwrap().len(), 1);
+    }
+
     #[test]
     fn test_merge_fields() {
         let mut form = Form {
@@ -310,4 +666,104 @@ mod tests {
         assert_eq!(form.fields.get("password"), Some(&"secret".to_string()));
         assert_eq!(form.fields.get("csrf"), Some(&"token123".to_string()));
     }
+
+    #[tokio::test]
+    async fn meta_tag_csrf_token_goes_in_a_header() {
+        let (url, seen) = mock_form_server(
+            r#"<html><head><meta name="csrf-token" content="meta-tok"></head><body>
+               <form action="/comments" method="post"><input name="text" value="hi"></form>
+               </body></html>"#,
+            &[200],
+        );
+        let client = http1_client().await;
+        let html = client.fetch_text(&url).await.unwrap();
+        let form = Form::parse_all(&html).unwrap().remove(0);
+
+        let token = Form::detect_csrf_token(&html).unwrap();
+        assert_eq!(
+            token,
+            CsrfToken::Header {
+                name: "X-CSRF-Token".to_string(),
+                value: "meta-tok".to_string(),
+            }
+        );
+        assert_eq!(
+            Form::extract_csrf_token(&html, "meta[name=csrf-token]").unwrap(),
+            Some("meta-tok".to_string())
+        );
+
+        form.submit(
+            &client,
+            &url,
+            &SubmitOptions::default().with_csrf_token(token),
+        )
+        .await
+        .unwrap();
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen[0].header("x-csrf-token"), Some("meta-tok"));
+        assert_eq!(seen[0].body, b"text=hi");
+    }
+
+    #[test]
+    fn detects_csrf_input_by_name() {
+        let html = r#"<form><input type="hidden" name="authenticity_token" value="rails-tok">
+            <input name="q" value=""></form>"#;
+        assert_eq!(
+            Form::detect_csrf_token(html),
+            Some(CsrfToken::Field {
+                name: "authenticity_token".to_string(),
+                value: "rails-tok".to_string(),
+            })
+        );
+        assert_eq!(Form::detect_csrf_token("<form><input name=q></form>"), None);
+    }
+
+    #[tokio::test]
+    async fn csrf_cookie_is_copied_into_a_header() {
+        let response = reqwest::Response::from(
+            http::Response::builder()
+                .header(SET_COOKIE, "session=s1; Path=/; HttpOnly")
+                .header(SET_COOKIE, "csrf_token=abc%3D%3D; Path=/")
+                .body("")
+                .unwrap(),
+        );
+
+        let token = Form::csrf_cookie(response.headers()).unwrap();
+        assert_eq!(
+            token,
+            CsrfToken::Header {
+                name: "X-CSRF-Token".to_string(),
+                value: "abc==".to_string(),
+            }
+        );
+
+        let (url, seen) = mock_form_server(
+            r#"<form action="/save" method="post"><input name="a" value="1"></form>"#,
+            &[200],
+        );
+        let client = http1_client().await;
+        let form = first_form(&client, &url).await;
+        form.submit(
+            &client,
+            &url,
+            &SubmitOptions::default().with_csrf_token(token),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            seen.lock().unwrap()[0].header("x-csrf-token"),
+            Some("abc==")
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert(SET_COOKIE, "XSRF-TOKEN=ng; Path=/".parse().unwrap());
+        assert_eq!(
+            Form::csrf_cookie(&headers),
+            Some(CsrfToken::Header {
+                name: "X-XSRF-TOKEN".to_string(),
+                value: "ng".to_string(),
+            })
+        );
+    }
 }
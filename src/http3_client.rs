@@ -51,6 +51,11 @@ impl Http3Client {
             .and_then(|v| v.to_str().ok())
             .is_some_and(|v| v.contains("h3"))
     }
+
+    /// HTTP/3 disabled -- always fails.
+    pub async fn preconnect(&self, _url: &str) -> anyhow::Result<()> {
+        Err(anyhow::anyhow!("HTTP/3 disabled in this build"))
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -58,7 +63,9 @@ impl Http3Client {
 // ═══════════════════════════════════════════════════════════════════════════════
 
 #[cfg(feature = "http3")]
-use std::sync::Arc;
+use std::collections::HashMap;
+#[cfg(feature = "http3")]
+use std::sync::{Arc, Mutex};
 #[cfg(feature = "http3")]
 use std::time::Duration;
 
@@ -75,10 +82,21 @@ use tracing::{debug, info};
 use crate::fingerprint::BrowserProfile;
 
 /// HTTP/3 client with QUIC transport
+///
+/// Connections are kept per `host:port` and reused by later requests until
+/// the server or the idle timeout closes them.
 #[cfg(feature = "http3")]
 pub struct Http3Client {
     endpoint: quinn::Endpoint,
     profile: BrowserProfile,
+    connections: Mutex<HashMap<String, H3Connection>>,
+}
+
+/// An open HTTP/3 connection; its driver task runs until it closes
+#[cfg(feature = "http3")]
+struct H3Connection {
+    quic: quinn::Connection,
+    send_request: h3::client::SendRequest<h3_quinn::OpenStreams, Bytes>,
 }
 
 #[cfg(feature = "http3")]
@@ -95,9 +113,11 @@ impl Http3Client {
             let _ = roots.add(cert);
         }
 
-        let tls_config = rustls::ClientConfig::builder()
+        let mut tls_config = rustls::ClientConfig::builder()
             .with_root_certificates(roots)
             .with_no_client_auth();
+        // Servers refuse a QUIC handshake that doesn't offer h3
+        tls_config.alpn_protocols = vec![b"h3".to_vec()];
 
         // Configure QUIC
         let mut transport = quinn::TransportConfig::default();
@@ -113,42 +133,63 @@ impl Http3Client {
         let mut endpoint = quinn::Endpoint::client("0.0.0.0:0".parse()?)?;
         endpoint.set_default_client_config(client_config);
 
-        Ok(Self { endpoint, profile })
+        Ok(Self {
+            endpoint,
+            profile,
+            connections: Mutex::default(),
+        })
     }
 
-    /// Fetch a URL using HTTP/3 over QUIC.
-    ///
-    /// Performs DNS resolution, establishes a QUIC connection, upgrades to
-    /// HTTP/3, sends a GET request, and returns the complete response.
-    pub async fn fetch(&self, url: &str) -> Result<Http3Response> {
+    /// Open a connection to `url`'s host (DNS, QUIC and TLS handshakes, HTTP/3
+    /// setup) for later requests to reuse
+    pub async fn preconnect(&self, url: &str) -> Result<()> {
         let uri: http::Uri = url.parse().context("Invalid URL")?;
         let host = uri.host().context("No host in URL")?;
-        let port = uri.port_u16().unwrap_or(443);
+        self.connection(host, uri.port_u16().unwrap_or(443))
+            .await
+            .map(|_| ())
+    }
+
+    /// Request handle for an open connection to `host:port`, connecting first
+    /// when there is none or it has closed
+    async fn connection(
+        &self,
+        host: &str,
+        port: u16,
+    ) -> Result<h3::client::SendRequest<h3_quinn::OpenStreams, Bytes>> {
+        let key = format!("{host}:{port}");
+        if let Some(open) = self
+            .connections
+            .lock()
+            .expect("connection pool lock poisoned")
+            .get(&key)
+            .filter(|open| open.quic.close_reason().is_none())
+        {
+            debug!("HTTP/3 reusing connection to {key}");
+            return Ok(open.send_request.clone());
+        }
 
         info!("HTTP/3 connecting to {}:{}", host, port);
 
         // DNS resolution
-        let addr = tokio::net::lookup_host(format!("{host}:{port}"))
+        let addr = tokio::net::lookup_host(&key)
             .await
             .context("DNS lookup failed for host")?
             .next()
             .context("DNS resolution returned no addresses")?;
 
         // QUIC connection
-        let connection = self
+        let quic = self
             .endpoint
             .connect(addr, host)
             .context("Failed to initiate QUIC connection")?
             .await
             .context("QUIC handshake failed")?;
 
-        debug!(
-            "QUIC connected, protocol: {:?}",
-            connection.handshake_data()
-        );
+        debug!("QUIC connected, protocol: {:?}", quic.handshake_data());
 
         // HTTP/3 layer
-        let (mut driver, mut send_request) = h3::client::new(h3_quinn::Connection::new(connection))
+        let (mut driver, send_request) = h3::client::new(h3_quinn::Connection::new(quic.clone()))
             .await
             .context("H3 connection setup failed")?;
 
@@ -158,6 +199,30 @@ impl Http3Client {
             debug!("H3 driver closed: {:?}", err);
         });
 
+        self.connections
+            .lock()
+            .expect("connection pool lock poisoned")
+            .insert(
+                key,
+                H3Connection {
+                    quic,
+                    send_request: send_request.clone(),
+                },
+            );
+        Ok(send_request)
+    }
+
+    /// Fetch a URL using HTTP/3 over QUIC.
+    ///
+    /// Reuses an open connection to the host (see [`preconnect`](Self::preconnect)),
+    /// otherwise resolves DNS, establishes a QUIC connection and upgrades to
+    /// HTTP/3; then sends a GET request and returns the complete response.
+    pub async fn fetch(&self, url: &str) -> Result<Http3Response> {
+        let uri: http::Uri = url.parse().context("Invalid URL")?;
+        let host = uri.host().context("No host in URL")?;
+        let port = uri.port_u16().unwrap_or(443);
+        let mut send_request = self.connection(host, port).await?;
+
         // Build request with browser-like headers
         let request = http::Request::builder()
             .method("GET")
@@ -8,20 +8,329 @@
 //! - Connection pooling with keep-alive
 //! - Realistic browser fingerprinting
 
-use std::sync::Arc;
-use std::time::Duration;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-use anyhow::Result;
-use reqwest::{Client, Response};
+use anyhow::{bail, Context, Result};
+use bytes::Bytes;
+use futures::stream::{self, Stream, StreamExt};
+use hyper_util::client::legacy::connect::HttpInfo;
+use reqwest::tls::Version;
+use reqwest::{header, Client, ClientBuilder, Request, Response};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use tokio::sync::RwLock;
-use tracing::{debug, info, instrument};
+use tracing::{debug, info, instrument, warn};
 
-use crate::fingerprint::{random_profile, BrowserProfile};
+use crate::browser_detect::BrowserType;
+#[cfg(feature = "cassette")]
+use crate::cassette::{BodyCodec, Cassette, CassetteMode};
+use crate::cookie_jar::CookieJar;
+use crate::fingerprint::domains::DomainProfiles;
+use crate::fingerprint::{profile_for, random_profile, BrowserProfile, RequestDest};
+use crate::http3_client::Http3Client;
+use crate::retry::{self, RetryPolicy};
+use crate::sse::{SseEvent, SseParser};
+#[cfg(feature = "metrics")]
+use crate::telemetry;
+
+/// Redirects followed when no limit is given
+pub const DEFAULT_MAX_REDIRECTS: usize = 10;
+
+/// Give up warming a host after this long
+const PRECONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+tokio::task_local! {
+    /// Set while a [`AcceleratedClient::preconnect`] probe is in flight
+    static PRECONNECTING: ();
+}
+
+/// Whether the request being polled is a preconnect probe, which follows no
+/// redirects and neither sends nor stores cookies
+pub(crate) fn preconnecting() -> bool {
+    PRECONNECTING.try_with(|()| ()).is_ok()
+}
+
+/// How long an idle pooled connection is kept open
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Connections [`AcceleratedClient::connection_info`] remembers for reuse
+/// detection
+const MAX_LOGGED_CONNECTIONS: usize = 1024;
 
 /// HTTP client with all acceleration features
 pub struct AcceleratedClient {
     client: Client,
     profile: Arc<RwLock<BrowserProfile>>,
+    tls: TlsOptions,
+    domain_profiles: Option<Arc<DomainOverrides>>,
+    connections: Arc<Mutex<ConnectionLog>>,
+    /// Origins whose `Accept-CH` asked for `Sec-CH-UA-Platform-Version`
+    platform_version_origins: Arc<Mutex<HashSet<String>>>,
+    /// Default headers of a wrapped client this type built, spelled out on
+    /// each request so they can be sent in browser order
+    client_headers: Arc<header::HeaderMap>,
+    retry: RetryPolicy,
+    cookie_jar: Option<Arc<CookieJar>>,
+    /// `cookie_jar` is the wrapped client's cookie provider; otherwise
+    /// [`execute`](AcceleratedClient::execute) applies it to each request
+    jar_in_client: bool,
+    http3: Option<Arc<Http3Client>>,
+    #[cfg(feature = "cassette")]
+    cassette: Option<Arc<Cassette>>,
+}
+
+/// What the connection behind a response actually negotiated
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConnectionInfo {
+    /// HTTP version of the response: `HTTP/1.0`, `HTTP/1.1`, `HTTP/2` or `HTTP/3`
+    pub http_version: &'static str,
+    /// ALPN protocol id (`h2`, `http/1.1`, `h3`); `None` for cleartext
+    /// connections, which negotiate nothing (HTTP/2 there is h2c), and for
+    /// responses that never touched the network
+    pub protocol: Option<&'static str>,
+    /// Whether the request went over TLS
+    pub tls: bool,
+    /// Negotiated TLS version, known only when the client's [`TlsOptions`]
+    /// allow just one (reqwest doesn't expose it otherwise)
+    pub tls_version: Option<&'static str>,
+    /// Server address the connection went to
+    pub remote_addr: Option<SocketAddr>,
+    /// Client side of the connection
+    pub local_addr: Option<SocketAddr>,
+    /// Served on a pooled connection an earlier response already used: same
+    /// local and remote address, seen within the pool's idle timeout
+    pub reused: bool,
+}
+
+/// One host warmed by [`AcceleratedClient::preconnect`]
+#[derive(Debug, Clone, Serialize)]
+pub struct Preconnected {
+    pub host: String,
+    pub elapsed_ms: f64,
+    /// The pooled connection, or `None` when warming failed
+    pub connection: Option<ConnectionInfo>,
+    /// HTTP/3 endpoint the host advertises in `Alt-Svc` (e.g. `:443`)
+    pub h3_endpoint: Option<String>,
+    /// An HTTP/3 connection to `h3_endpoint` is open in the client's
+    /// [`Http3Client`] (see [`AcceleratedClient::with_http3`])
+    pub h3_warmed: bool,
+    pub error: Option<String>,
+}
+
+/// Connections seen recently, to spot pool reuse
+#[derive(Debug, Default)]
+struct ConnectionLog {
+    /// When each (local, remote) address pair last carried a response
+    seen: HashMap<(SocketAddr, SocketAddr), Instant>,
+    last: Option<ConnectionInfo>,
+}
+
+impl ConnectionLog {
+    /// Record a response on `addrs`, returning whether its connection was
+    /// seen before
+    ///
+    /// A pair idle for longer than the pool keeps connections must belong to
+    /// a new connection that happens to get the same ephemeral port, so it
+    /// doesn't count and is forgotten; at most [`MAX_LOGGED_CONNECTIONS`]
+    /// pairs are kept.
+    fn reused(&mut self, addrs: (SocketAddr, SocketAddr)) -> bool {
+        let now = Instant::now();
+        self.seen
+            .retain(|_, seen| now.duration_since(*seen) < POOL_IDLE_TIMEOUT);
+        if self.seen.len() >= MAX_LOGGED_CONNECTIONS && !self.seen.contains_key(&addrs) {
+            if let Some(oldest) = self
+                .seen
+                .iter()
+                .min_by_key(|(_, seen)| **seen)
+                .map(|(addrs, _)| *addrs)
+            {
+                self.seen.remove(&oldest);
+            }
+        }
+        self.seen.insert(addrs, now).is_some()
+    }
+}
+
+/// Fingerprints pinned per domain, with a profile generated once per browser
+struct DomainOverrides {
+    profiles: DomainProfiles,
+    browsers: Vec<(BrowserType, BrowserProfile)>,
+}
+
+/// TLS protocol version constraints and certificate trust settings
+///
+/// rustls implements only TLS 1.2 and 1.3, with its secure default cipher
+/// suites, so pinning the version range is the available policy knob.
+/// Certificate verification stays on unless explicitly disabled.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TlsOptions {
+    pub min_tls_version: Option<Version>,
+    pub max_tls_version: Option<Version>,
+    /// Extra trusted root certificates (PEM), added to the built-in roots
+    pub root_certificates_pem: Vec<Vec<u8>>,
+    /// Skip certificate verification entirely
+    pub insecure: bool,
+}
+
+impl TlsOptions {
+    /// Refuse anything below TLS 1.3
+    #[must_use]
+    pub fn tls13_only() -> Self {
+        Self::default().with_min_tls_version(Version::TLS_1_3)
+    }
+
+    /// Set the minimum accepted TLS version
+    #[must_use]
+    pub fn with_min_tls_version(mut self, version: Version) -> Self {
+        self.min_tls_version = Some(version);
+        self
+    }
+
+    /// Set the maximum offered TLS version
+    #[must_use]
+    pub fn with_max_tls_version(mut self, version: Version) -> Self {
+        self.max_tls_version = Some(version);
+        self
+    }
+
+    /// Trust the root certificates in a PEM bundle (one or more certificates)
+    pub fn with_root_certificate_pem(mut self, pem: &[u8]) -> Result<Self> {
+        let certs =
+            reqwest::Certificate::from_pem_bundle(pem).context("Invalid PEM certificate bundle")?;
+        if certs.is_empty() {
+            bail!("PEM bundle contains no certificates");
+        }
+        self.root_certificates_pem.push(pem.to_vec());
+        Ok(self)
+    }
+
+    /// Trust the root certificates in a PEM file (`--cacert`)
+    pub fn with_root_certificate_file(self, path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let pem = std::fs::read(path)
+            .with_context(|| format!("Failed to read CA bundle '{}'", path.display()))?;
+        self.with_root_certificate_pem(&pem)
+            .with_context(|| format!("Failed to load CA bundle '{}'", path.display()))
+    }
+
+    /// Disable certificate verification (`--insecure`)
+    #[must_use]
+    pub fn with_insecure(mut self, insecure: bool) -> Self {
+        self.insecure = insecure;
+        self
+    }
+
+    /// The one TLS version these constraints allow, if they leave only one
+    pub fn only_version(&self) -> Option<&'static str> {
+        let min = self.min_tls_version.map(version_name);
+        let max = self.max_tls_version.map(version_name);
+        match (min, max) {
+            (Some("1.3"), _) => Some("1.3"),
+            (_, Some("1.2")) => Some("1.2"),
+            (Some(min), Some(max)) if min == max => Some(min),
+            _ => None,
+        }
+    }
+
+    /// Parse a version as given on the command line (`1.2`, `1.3`)
+    pub fn parse_version(version: &str) -> Result<Version> {
+        match version.trim().trim_start_matches(['v', 'V']) {
+            "1.2" => Ok(Version::TLS_1_2),
+            "1.3" => Ok(Version::TLS_1_3),
+            "1.0" | "1.1" => {
+                bail!("TLS {version} is not supported (rustls implements TLS 1.2 and 1.3)")
+            }
+            _ => bail!("Unknown TLS version '{version}' (expected 1.2 or 1.3)"),
+        }
+    }
+
+    /// Whether any version constraint is set
+    #[must_use]
+    pub fn is_constrained(&self) -> bool {
+        self.min_tls_version.is_some() || self.max_tls_version.is_some()
+    }
+
+    /// Whether the default certificate trust is changed
+    #[must_use]
+    pub fn customizes_trust(&self) -> bool {
+        self.insecure || !self.root_certificates_pem.is_empty()
+    }
+
+    /// Apply the constraints to a reqwest client builder
+    pub fn apply(&self, mut builder: ClientBuilder) -> ClientBuilder {
+        if let Some(min) = self.min_tls_version {
+            builder = builder.min_tls_version(min);
+        }
+        if let Some(max) = self.max_tls_version {
+            builder = builder.max_tls_version(max);
+        }
+        for pem in &self.root_certificates_pem {
+            // Validated when added
+            for cert in reqwest::Certificate::from_pem_bundle(pem).unwrap_or_default() {
+                builder = builder.add_root_certificate(cert);
+            }
+        }
+        if self.insecure {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+        builder
+    }
+
+    /// Attach the active constraint to TLS handshake failures, so a server that
+    /// can't meet the pinned version isn't reported as a bare connect error.
+    pub fn explain_error(&self, err: reqwest::Error) -> anyhow::Error {
+        if !self.is_constrained() || !is_tls_failure(&err) {
+            return err.into();
+        }
+        let policy = match (self.min_tls_version, self.max_tls_version) {
+            (Some(min), Some(max)) if min == max => format!("TLS {} only", version_name(min)),
+            (Some(min), Some(max)) => {
+                format!("TLS {} to {}", version_name(min), version_name(max))
+            }
+            (Some(min), None) => format!("TLS {} or newer", version_name(min)),
+            (None, Some(max)) => format!("TLS {} or older", version_name(max)),
+            (None, None) => unreachable!("checked is_constrained"),
+        };
+        anyhow::Error::new(err).context(format!(
+            "TLS handshake failed: client is restricted to {policy} and the server could not negotiate it"
+        ))
+    }
+}
+
+fn version_name(version: Version) -> &'static str {
+    if version == Version::TLS_1_3 {
+        "1.3"
+    } else if version == Version::TLS_1_2 {
+        "1.2"
+    } else if version == Version::TLS_1_1 {
+        "1.1"
+    } else {
+        "1.0"
+    }
+}
+
+fn is_tls_failure(err: &reqwest::Error) -> bool {
+    let mut source: Option<&dyn std::error::Error> = Some(err);
+    while let Some(e) = source {
+        let text = e.to_string().to_lowercase();
+        if [
+            "handshake",
+            "protocol version",
+            "protocolversion",
+            "incompatible",
+            "alert",
+        ]
+        .iter()
+        .any(|needle| text.contains(needle))
+        {
+            return true;
+        }
+        source = e.source();
+    }
+    false
 }
 
 impl AcceleratedClient {
@@ -32,17 +341,52 @@ impl AcceleratedClient {
 
     /// Create client with specific browser profile
     pub fn with_profile(profile: BrowserProfile) -> Result<Self> {
-        let headers = profile.to_headers();
+        Self::with_tls(profile, TlsOptions::default())
+    }
 
-        let client = Client::builder()
+    /// Create client with specific browser profile and TLS version constraints
+    pub fn with_tls(profile: BrowserProfile, tls: TlsOptions) -> Result<Self> {
+        Self::with_redirects(profile, tls, DEFAULT_MAX_REDIRECTS)
+    }
+
+    /// Create client like [`with_tls`](Self::with_tls) that follows at most
+    /// `max_redirects` redirects (see [`redirect_policy`])
+    pub fn with_redirects(
+        profile: BrowserProfile,
+        tls: TlsOptions,
+        max_redirects: usize,
+    ) -> Result<Self> {
+        // HTTP/2: Multiplexing - 100 streams per connection
+        Self::with_builder(
+            profile,
+            tls,
+            max_redirects,
+            ClientBuilder::http2_prior_knowledge,
+        )
+    }
+
+    /// Create client like [`with_redirects`](Self::with_redirects), letting
+    /// `customize` adjust the reqwest builder last, e.g. to add a proxy, a DNS
+    /// resolver or connector layer, or to pick the HTTP version
+    ///
+    /// Unlike [`with_redirects`](Self::with_redirects), the HTTP version is
+    /// negotiated via ALPN unless `customize` sets one.
+    pub fn with_builder(
+        profile: BrowserProfile,
+        tls: TlsOptions,
+        max_redirects: usize,
+        customize: impl FnOnce(ClientBuilder) -> ClientBuilder,
+    ) -> Result<Self> {
+        let headers = profile.to_client_headers();
+        let jar = Arc::new(CookieJar::new());
+
+        let builder = Client::builder()
             // ═══════════════════════════════════════════════════════════════
             // CONNECTION ACCELERATION
             // ═══════════════════════════════════════════════════════════════
-            // HTTP/2: Multiplexing - 100 streams per connection
-            .http2_prior_knowledge()
             // Keep connections alive for reuse
             .pool_max_idle_per_host(10)
-            .pool_idle_timeout(Duration::from_secs(90))
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
             // TCP keepalive
             .tcp_keepalive(Duration::from_secs(60))
             // Enable TCP_NODELAY for lower latency
@@ -73,7 +417,7 @@ impl AcceleratedClient {
             // ═══════════════════════════════════════════════════════════════
             // BROWSER FINGERPRINTING
             // ═══════════════════════════════════════════════════════════════
-            .default_headers(headers)
+            .default_headers(headers.clone())
             // ═══════════════════════════════════════════════════════════════
             // TIMEOUTS
             // ═══════════════════════════════════════════════════════════════
@@ -82,29 +426,30 @@ impl AcceleratedClient {
             // ═══════════════════════════════════════════════════════════════
             // REDIRECTS
             // ═══════════════════════════════════════════════════════════════
-            .redirect(reqwest::redirect::Policy::limited(10))
+            .redirect(redirect_policy(max_redirects))
             // ═══════════════════════════════════════════════════════════════
             // COOKIES
             // ═══════════════════════════════════════════════════════════════
-            .cookie_store(true)
-            .build()?;
+            .cookie_provider(Arc::clone(&jar));
+        let client = customize(tls.apply(builder)).build()?;
 
-        Ok(Self {
-            client,
-            profile: Arc::new(RwLock::new(profile)),
-        })
+        let mut this = Self::from_parts(client, profile).with_cookie_jar(jar);
+        this.tls = tls;
+        this.client_headers = Arc::new(headers);
+        Ok(this)
     }
 
     /// Create client that tries HTTP/2 with fallback to HTTP/1.1
     pub fn new_adaptive() -> Result<Self> {
         let profile = random_profile();
-        let headers = profile.to_headers();
+        let headers = profile.to_client_headers();
+        let jar = Arc::new(CookieJar::new());
 
         let client = Client::builder()
             // Don't assume HTTP/2 - let server negotiate
             .http2_adaptive_window(true)
             .pool_max_idle_per_host(10)
-            .pool_idle_timeout(Duration::from_secs(90))
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
             .tcp_keepalive(Duration::from_secs(60))
             .tcp_nodelay(true)
             .use_rustls_tls()
@@ -115,33 +460,277 @@ impl AcceleratedClient {
             .default_headers(headers)
             .connect_timeout(Duration::from_secs(10))
             .timeout(Duration::from_secs(30))
-            .redirect(reqwest::redirect::Policy::limited(10))
-            .cookie_store(true)
+            .redirect(redirect_policy(DEFAULT_MAX_REDIRECTS))
+            .cookie_provider(Arc::clone(&jar))
             .build()?;
 
-        Ok(Self {
-            client,
-            profile: Arc::new(RwLock::new(profile)),
-        })
+        Ok(Self::from_parts(client, profile).with_cookie_jar(jar))
     }
 
     /// Create client from an existing reqwest::Client (for custom configurations like proxies)
     pub fn from_client(client: Client) -> Result<Self> {
-        Ok(Self {
+        Self::from_client_with_profile(client, random_profile())
+    }
+
+    /// Create client from an existing `reqwest::Client`, reporting `profile`
+    /// as its fingerprint
+    ///
+    /// `client`'s own default headers are left as they are.
+    pub fn from_client_with_profile(client: Client, profile: BrowserProfile) -> Result<Self> {
+        Ok(Self::from_parts(client, profile))
+    }
+
+    fn from_parts(client: Client, profile: BrowserProfile) -> Self {
+        Self {
             client,
-            profile: Arc::new(RwLock::new(random_profile())),
-        })
+            profile: Arc::new(RwLock::new(profile)),
+            tls: TlsOptions::default(),
+            domain_profiles: None,
+            connections: Arc::default(),
+            platform_version_origins: Arc::default(),
+            client_headers: Arc::default(),
+            retry: RetryPolicy::default(),
+            cookie_jar: None,
+            jar_in_client: false,
+            http3: None,
+            #[cfg(feature = "cassette")]
+            cassette: None,
+        }
+    }
+
+    /// Retry transient failures of idempotent requests according to `policy`
+    /// instead of [`RetryPolicy::default`]
+    #[must_use]
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    /// Retry policy [`execute`](Self::execute) applies
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    /// Keep cookies set by responses (including every hop of a redirect
+    /// chain) and send them on later requests, or stop doing so and forget
+    /// them
+    ///
+    /// Clients built by this type keep cookies by default. One wrapping a
+    /// caller-built `reqwest::Client` gets a jar that [`execute`](Self::execute)
+    /// applies around each request; it sees the `Set-Cookie` headers of the
+    /// final response only, not those of redirects the wrapped client followed.
+    #[must_use]
+    pub fn with_cookie_store(mut self, enabled: bool) -> Self {
+        match &self.cookie_jar {
+            Some(jar) => jar.set_enabled(enabled),
+            None if enabled => self.cookie_jar = Some(Arc::new(CookieJar::new())),
+            None => {}
+        }
+        self
+    }
+
+    /// Use `jar`, which must also be the wrapped client's cookie provider
+    /// (`ClientBuilder::cookie_provider`), for [`export_cookies`](Self::export_cookies)
+    #[must_use]
+    pub fn with_cookie_jar(mut self, jar: Arc<CookieJar>) -> Self {
+        self.cookie_jar = Some(jar);
+        self.jar_in_client = true;
+        self
+    }
+
+    /// Warm HTTP/3 connections in `client` when [`preconnect`](Self::preconnect)
+    /// finds a host advertising h3 in `Alt-Svc`; requests sent with
+    /// [`http3`](Self::http3) then reuse them
+    #[must_use]
+    pub fn with_http3(mut self, client: Http3Client) -> Self {
+        self.http3 = Some(Arc::new(client));
+        self
+    }
+
+    /// The HTTP/3 client set with [`with_http3`](Self::with_http3)
+    pub fn http3(&self) -> Option<&Http3Client> {
+        self.http3.as_deref()
+    }
+
+    /// The cookie jar requests go through, if this client has one
+    pub fn cookie_jar(&self) -> Option<&Arc<CookieJar>> {
+        self.cookie_jar.as_ref()
+    }
+
+    /// Cookies collected so far, in Netscape `cookies.txt` format
+    pub fn export_cookies(&self) -> String {
+        self.cookie_jar
+            .as_deref()
+            .map_or_else(|| CookieJar::new().to_netscape(), CookieJar::to_netscape)
+    }
+
+    /// Route all requests through a record/replay cassette
+    ///
+    /// In [`CassetteMode::Record`] requests hit the network and are saved to
+    /// `path`; in [`CassetteMode::Replay`] they are served from it offline.
+    #[cfg(feature = "cassette")]
+    pub fn with_cassette(
+        self,
+        path: impl AsRef<std::path::Path>,
+        mode: CassetteMode,
+    ) -> Result<Self> {
+        self.with_cassette_codec(path, mode, BodyCodec::None)
+    }
+
+    /// Like [`with_cassette`](Self::with_cassette), compressing recorded
+    /// response bodies with `codec`
+    #[cfg(feature = "cassette")]
+    pub fn with_cassette_codec(
+        mut self,
+        path: impl AsRef<std::path::Path>,
+        mode: CassetteMode,
+        codec: BodyCodec,
+    ) -> Result<Self> {
+        self.cassette = Some(Arc::new(Cassette::open(path, mode)?.with_codec(codec)));
+        Ok(self)
+    }
+
+    /// Send a browser's fingerprint to hosts matching its domain pattern
+    ///
+    /// The pinned browser's headers replace this client's profile defaults
+    /// for those hosts, and hosts pinned to Firefox or Safari get no
+    /// `Sec-CH-UA` client hints; headers set on the request itself still win.
+    /// Pins apply to requests sent through [`execute`](Self::execute); use
+    /// [`profile_for_url`](Self::profile_for_url) for requests sent otherwise.
+    #[must_use]
+    pub fn with_domain_profiles(mut self, profiles: DomainProfiles) -> Self {
+        self.domain_profiles = (!profiles.is_empty()).then(|| {
+            let browsers = profiles
+                .browsers()
+                .into_iter()
+                .map(|browser| (browser, profile_for(browser)))
+                .collect();
+            Arc::new(DomainOverrides { profiles, browsers })
+        });
+        self
+    }
+
+    /// Profile whose headers `url`'s host gets: its pinned browser's, else
+    /// this client's
+    pub async fn profile_for_url(&self, url: &str) -> BrowserProfile {
+        match url::Url::parse(url).ok().and_then(|url| self.pinned(&url)) {
+            Some((_, profile)) => profile.clone(),
+            None => self.profile().await,
+        }
+    }
+
+    /// Browser pinned for `url`'s host, with its profile
+    fn pinned(&self, url: &url::Url) -> Option<&(BrowserType, BrowserProfile)> {
+        let overrides = self.domain_profiles.as_deref()?;
+        let browser = overrides.profiles.browser_for(url.host_str()?)?;
+        overrides.browsers.iter().find(|(b, _)| *b == browser)
+    }
+
+    /// Fill in the headers the client's defaults leave out: the host's pinned
+    /// fingerprint, client hints and, unless the request's `Sec-Fetch-Mode`
+    /// says it isn't a navigation, the navigation-only headers
+    async fn with_request_headers(&self, mut request: Request) -> Request {
+        let base = self.profile.read().await;
+        let pinned = self.pinned(request.url());
+        let profile = pinned.map_or(&*base, |(_, profile)| profile);
+        let navigation = request
+            .headers()
+            .get("sec-fetch-mode")
+            .is_none_or(|mode| mode == "navigate");
+
+        let mut headers = profile.client_hint_headers();
+        if self.wants_platform_version(request.url()) {
+            if let Some(version) = profile.platform_version_header() {
+                headers.insert("sec-ch-ua-platform-version", version);
+            }
+        }
+        if let Some((browser, pinned)) = pinned {
+            debug!(browser = browser.as_str(), "Using pinned fingerprint");
+            headers.extend(pinned.to_client_headers());
+        }
+        if navigation {
+            headers.extend(profile.navigation_only_headers());
+        }
+        for (name, value) in &headers {
+            request
+                .headers_mut()
+                .entry(name)
+                .or_insert_with(|| value.clone());
+        }
+        request
+    }
+
+    /// Spell out the wrapped client's default headers on `request` and put
+    /// all its headers in the order the host's browser profile sends them
+    ///
+    /// reqwest appends its default headers after the request's own, so
+    /// without this a browser's order would only hold for the headers set on
+    /// the request.
+    async fn order_headers(&self, request: &mut Request) {
+        for (name, value) in self.client_headers.iter() {
+            request
+                .headers_mut()
+                .entry(name)
+                .or_insert_with(|| value.clone());
+        }
+        let base = self.profile.read().await;
+        let profile = self
+            .pinned(request.url())
+            .map_or(&*base, |(_, profile)| profile);
+        let headers = std::mem::take(request.headers_mut());
+        *request.headers_mut() = profile.order_headers(headers).into_iter().collect();
+    }
+
+    /// Whether `url`'s origin opted in to `Sec-CH-UA-Platform-Version`
+    fn wants_platform_version(&self, url: &url::Url) -> bool {
+        self.platform_version_origins
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .contains(&url.origin().ascii_serialization())
+    }
+
+    /// Remember whether `response`'s `Accept-CH` asks for
+    /// `Sec-CH-UA-Platform-Version`, as Chrome does per origin; a later
+    /// `Accept-CH` without it withdraws the opt-in
+    fn note_accept_ch(&self, response: &Response) {
+        let Some(accept_ch) = response
+            .headers()
+            .get("accept-ch")
+            .and_then(|value| value.to_str().ok())
+        else {
+            return;
+        };
+        let wanted = accept_ch.split(',').any(|hint| {
+            hint.trim()
+                .eq_ignore_ascii_case("sec-ch-ua-platform-version")
+        });
+        let origin = response.url().origin().ascii_serialization();
+        let mut origins = self
+            .platform_version_origins
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if wanted {
+            origins.insert(origin);
+        } else {
+            origins.remove(&origin);
+        }
     }
 
     /// Create client that doesn't follow redirects (for auth flows)
     pub fn new_no_redirect() -> Result<Self> {
-        let profile = random_profile();
-        let headers = profile.to_headers();
+        Self::new_no_redirect_with_profile(random_profile())
+    }
+
+    /// Create client like [`new_no_redirect`](Self::new_no_redirect) with a
+    /// specific browser profile
+    pub fn new_no_redirect_with_profile(profile: BrowserProfile) -> Result<Self> {
+        let headers = profile.to_client_headers();
+        let jar = Arc::new(CookieJar::new());
 
         let client = Client::builder()
             .http2_adaptive_window(true)
             .pool_max_idle_per_host(10)
-            .pool_idle_timeout(Duration::from_secs(90))
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
             .tcp_keepalive(Duration::from_secs(60))
             .tcp_nodelay(true)
             .use_rustls_tls()
@@ -149,25 +738,24 @@ impl AcceleratedClient {
             .zstd(true)
             .gzip(true)
             .deflate(true)
-            .default_headers(headers)
+            .default_headers(headers.clone())
             .connect_timeout(Duration::from_secs(10))
             .timeout(Duration::from_secs(30))
             // No redirects - capture 302 responses directly
             .redirect(reqwest::redirect::Policy::none())
-            .cookie_store(true)
+            .cookie_provider(Arc::clone(&jar))
             .build()?;
 
-        Ok(Self {
-            client,
-            profile: Arc::new(RwLock::new(profile)),
-        })
+        let mut this = Self::from_parts(client, profile).with_cookie_jar(jar);
+        this.client_headers = Arc::new(headers);
+        Ok(this)
     }
 
     /// Fetch a URL with all accelerations
     #[instrument(skip(self), fields(url = %url))]
     pub async fn fetch(&self, url: &str) -> Result<Response> {
         debug!("Fetching with acceleration");
-        let response = self.client.get(url).send().await?;
+        let response = self.execute(self.client.get(url).build()?).await?;
 
         info!(
             status = %response.status(),
@@ -179,6 +767,344 @@ impl AcceleratedClient {
         Ok(response)
     }
 
+    /// Fetch a URL like [`fetch`](Self::fetch), also reporting what its
+    /// connection negotiated
+    pub async fn fetch_with_info(&self, url: &str) -> Result<(Response, ConnectionInfo)> {
+        let response = self.fetch(url).await?;
+        let info = self.connection_info(&response);
+        Ok((response, info))
+    }
+
+    /// Describe the connection a response arrived on, remembering it so later
+    /// responses on the same connection are reported as reused
+    ///
+    /// Addresses are `None` for responses that never touched the network,
+    /// such as cassette replays.
+    pub fn connection_info(&self, response: &Response) -> ConnectionInfo {
+        let http_info = response.extensions().get::<HttpInfo>();
+        let local_addr = http_info.map(HttpInfo::local_addr);
+        let remote_addr = http_info.map(HttpInfo::remote_addr);
+        let tls = response.url().scheme() == "https";
+        let (http_version, alpn) = match response.version() {
+            reqwest::Version::HTTP_2 => ("HTTP/2", "h2"),
+            reqwest::Version::HTTP_3 => ("HTTP/3", "h3"),
+            reqwest::Version::HTTP_10 => ("HTTP/1.0", "http/1.0"),
+            reqwest::Version::HTTP_09 => ("HTTP/0.9", "http/0.9"),
+            _ => ("HTTP/1.1", "http/1.1"),
+        };
+        // Only a TLS handshake negotiates anything
+        let negotiated = tls && http_info.is_some();
+
+        let mut log = self
+            .connections
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let info = ConnectionInfo {
+            http_version,
+            protocol: negotiated.then_some(alpn),
+            tls,
+            tls_version: negotiated.then(|| self.tls.only_version()).flatten(),
+            remote_addr,
+            local_addr,
+            reused: local_addr
+                .zip(remote_addr)
+                .is_some_and(|addrs| log.reused(addrs)),
+        };
+        log.last = Some(info.clone());
+        info
+    }
+
+    /// Open pooled connections to `hosts` (DNS, TCP and TLS) ahead of the
+    /// real requests, in parallel
+    ///
+    /// Each host gets a `HEAD /` (a bare host means `https://`); the
+    /// connection stays in the pool, so the next request to that host skips
+    /// the handshakes. The probe follows no redirects and, on clients built
+    /// by this type, neither sends nor stores cookies. A host advertising
+    /// HTTP/3 via `Alt-Svc` is reported in [`Preconnected::h3_endpoint`] and,
+    /// when an [`Http3Client`] is attached with [`with_http3`](Self::with_http3),
+    /// warmed there too.
+    pub async fn preconnect(&self, hosts: &[&str]) -> Vec<Preconnected> {
+        let warm = |host: &str| {
+            let host = host.trim().to_string();
+            async move {
+                let start = std::time::Instant::now();
+                let url = if host.contains("://") {
+                    host.clone()
+                } else {
+                    format!("https://{host}/")
+                };
+                let headers = self.profile_for_url(&url).await.to_headers();
+                let result = match self
+                    .client
+                    .head(&url)
+                    .headers(headers)
+                    .timeout(PRECONNECT_TIMEOUT)
+                    .build()
+                {
+                    Ok(mut probe) => {
+                        self.order_headers(&mut probe).await;
+                        // Sent inside the scope: reqwest reads the cookie jar
+                        // when the request is sent, not when it is polled
+                        PRECONNECTING
+                            .scope((), async { self.client.execute(probe).await })
+                            .await
+                    }
+                    Err(e) => Err(e),
+                };
+                let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
+                match result {
+                    Ok(response) => {
+                        let h3_endpoint = response
+                            .headers()
+                            .get_all(header::ALT_SVC)
+                            .iter()
+                            .filter_map(|v| v.to_str().ok())
+                            .find_map(h3_alt_svc);
+                        let connection = self.connection_info(&response);
+                        let h3_warmed = match (&self.http3, &h3_endpoint) {
+                            (Some(http3), Some(endpoint)) => {
+                                self.warm_http3(http3, response.url(), endpoint).await
+                            }
+                            _ => false,
+                        };
+                        debug!(
+                            host,
+                            elapsed_ms,
+                            protocol = connection.protocol.unwrap_or(connection.http_version),
+                            h3_warmed,
+                            "Preconnected"
+                        );
+                        Preconnected {
+                            host,
+                            elapsed_ms,
+                            connection: Some(connection),
+                            h3_endpoint,
+                            h3_warmed,
+                            error: None,
+                        }
+                    }
+                    Err(e) => Preconnected {
+                        host,
+                        elapsed_ms,
+                        connection: None,
+                        h3_endpoint: None,
+                        h3_warmed: false,
+                        error: Some(e.to_string()),
+                    },
+                }
+            }
+        };
+        futures::future::join_all(hosts.iter().map(|host| warm(host))).await
+    }
+
+    /// Open an HTTP/3 connection to the `Alt-Svc` `endpoint` (`:443` or
+    /// `host:443`) of `origin`, returning whether it succeeded
+    async fn warm_http3(&self, http3: &Http3Client, origin: &url::Url, endpoint: &str) -> bool {
+        let Some(origin_host) = origin.host_str() else {
+            return false;
+        };
+        let (host, port) = endpoint.rsplit_once(':').unwrap_or((endpoint, "443"));
+        let host = if host.is_empty() { origin_host } else { host };
+        let url = format!("https://{host}:{port}/");
+        match tokio::time::timeout(PRECONNECT_TIMEOUT, http3.preconnect(&url)).await {
+            Ok(Ok(())) => true,
+            Ok(Err(e)) => {
+                debug!(url, "HTTP/3 preconnect failed: {e:#}");
+                false
+            }
+            Err(_) => {
+                debug!(url, "HTTP/3 preconnect timed out");
+                false
+            }
+        }
+    }
+
+    /// Browser profile in use, with the connection of the most recent
+    /// response passed through [`connection_info`](Self::connection_info)
+    pub async fn fetch_profile(&self) -> (BrowserProfile, Option<ConnectionInfo>) {
+        let last = self
+            .connections
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .last
+            .clone();
+        (self.profile().await, last)
+    }
+
+    /// Send a request with an explicit method, extra headers and an optional body
+    ///
+    /// Headers override the browser profile's defaults. A body without a
+    /// `Content-Type` header is sent as `application/json`, as with `fetch --data`.
+    #[instrument(skip(self, headers, body), fields(url = %url, method = %method))]
+    pub async fn request(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &HashMap<String, String>,
+        body: Option<&str>,
+    ) -> Result<Response> {
+        let method = reqwest::Method::from_bytes(method.trim().to_ascii_uppercase().as_bytes())
+            .with_context(|| format!("Invalid HTTP method '{method}'"))?;
+        let mut request = self.client.request(method, url);
+
+        if let Some(body) = body {
+            request = request.body(body.to_owned());
+            if !headers
+                .keys()
+                .any(|name| name.eq_ignore_ascii_case("content-type"))
+            {
+                request = request.header(header::CONTENT_TYPE, "application/json");
+            }
+        }
+        for (name, value) in headers {
+            request = request.header(name.trim(), value.trim());
+        }
+
+        self.execute(request.build()?).await
+    }
+
+    /// Execute a prepared request, honouring the cassette if one is attached
+    pub async fn execute(&self, request: Request) -> Result<Response> {
+        self.execute_with_retry(request, &self.retry).await
+    }
+
+    /// [`execute`](Self::execute) with `policy` instead of the client's
+    /// [`retry_policy`](Self::retry_policy)
+    pub async fn execute_with_retry(
+        &self,
+        request: Request,
+        policy: &RetryPolicy,
+    ) -> Result<Response> {
+        #[cfg(feature = "metrics")]
+        let (host, start) = (
+            request.url().host_str().unwrap_or_default().to_string(),
+            std::time::Instant::now(),
+        );
+
+        let request = self.with_request_headers(request).await;
+        let result = self.dispatch_with_retry(request, policy).await;
+
+        #[cfg(feature = "metrics")]
+        {
+            let (status, bytes) = match &result {
+                Ok(response) => (Some(response.status().as_u16()), response.content_length()),
+                Err(_) => (None, None),
+            };
+            telemetry::record_request(&host, status, start.elapsed(), bytes);
+
+            #[cfg(feature = "cassette")]
+            if result.is_ok()
+                && self
+                    .cassette
+                    .as_ref()
+                    .is_some_and(|c| c.mode() == CassetteMode::Replay)
+            {
+                telemetry::record_cache_hit(&host, "cassette");
+            }
+        }
+
+        result
+    }
+
+    /// Send a request that is safe to repeat (an idempotent method or an
+    /// `Idempotency-Key` header) again while it fails transiently and
+    /// `policy` allows; other requests are sent once
+    async fn dispatch_with_retry(
+        &self,
+        request: Request,
+        policy: &RetryPolicy,
+    ) -> Result<Response> {
+        let repeatable =
+            request.method().is_idempotent() || request.headers().contains_key("idempotency-key");
+        if !repeatable {
+            return self.dispatch(request).await;
+        }
+        let mut attempt = 1;
+        loop {
+            // A streaming body can't be cloned and so is never retried
+            let Some(copy) = (attempt < policy.max_attempts)
+                .then(|| request.try_clone())
+                .flatten()
+            else {
+                return self.dispatch(request).await;
+            };
+            let delay = match self.dispatch(copy).await {
+                Ok(response) if policy.retries_status(response.status()) => {
+                    let status = response.status();
+                    let Some(delay) =
+                        policy.delay_for_response(attempt, status, response.headers())
+                    else {
+                        debug!(%status, "Retry-After exceeds the retry policy, giving up");
+                        return Ok(response);
+                    };
+                    debug!(
+                        attempt,
+                        %status,
+                        delay_ms = delay.as_millis(),
+                        url = %request.url(),
+                        "Retrying request"
+                    );
+                    delay
+                }
+                Err(e) if retry::is_transient(&e) => {
+                    let delay = policy.backoff(attempt);
+                    debug!(
+                        attempt,
+                        error = %e,
+                        delay_ms = delay.as_millis(),
+                        url = %request.url(),
+                        "Retrying request"
+                    );
+                    delay
+                }
+                result => return result,
+            };
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    async fn dispatch(&self, mut request: Request) -> Result<Response> {
+        let jar = self.cookie_jar.as_deref().filter(|_| !self.jar_in_client);
+        if let Some(jar) = jar {
+            let explicit = request
+                .headers()
+                .get(header::COOKIE)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default();
+            let cookie = jar.merge_header(request.url(), explicit);
+            if !cookie.is_empty() {
+                if let Ok(value) = header::HeaderValue::from_str(&cookie) {
+                    request.headers_mut().insert(header::COOKIE, value);
+                }
+            }
+        }
+
+        self.order_headers(&mut request).await;
+        let response = self.send(request).await?;
+        self.note_accept_ch(&response);
+        if let Some(jar) = jar {
+            for value in response.headers().get_all(header::SET_COOKIE) {
+                if let Ok(value) = value.to_str() {
+                    jar.store(value, response.url());
+                }
+            }
+        }
+        Ok(response)
+    }
+
+    async fn send(&self, request: Request) -> Result<Response> {
+        #[cfg(feature = "cassette")]
+        if let Some(cassette) = &self.cassette {
+            return cassette.execute(&self.client, request).await;
+        }
+        self.client
+            .execute(request)
+            .await
+            .map_err(|e| self.tls.explain_error(e))
+    }
+
     /// Fetch and return body as string
     pub async fn fetch_text(&self, url: &str) -> Result<String> {
         let response = self.fetch(url).await?;
@@ -186,6 +1112,150 @@ impl AcceleratedClient {
         Ok(text)
     }
 
+    /// Fetch a URL and stream its body chunk by chunk
+    ///
+    /// Unlike [`fetch_text`](Self::fetch_text) nothing is buffered, so large
+    /// downloads and endless chunked responses can be processed as they arrive.
+    pub async fn stream_body(
+        &self,
+        url: &str,
+    ) -> Result<impl Stream<Item = Result<Bytes>> + Send + 'static> {
+        let response = self.fetch(url).await?;
+        Ok(response
+            .bytes_stream()
+            .map(|chunk| chunk.map_err(Into::into)))
+    }
+
+    /// Subscribe to a Server-Sent Events endpoint
+    ///
+    /// Sends `Accept: text/event-stream` and yields each event as its frame
+    /// completes. The stream ends when the server closes the connection or
+    /// the body fails mid-stream.
+    pub async fn stream_events(
+        &self,
+        url: &str,
+    ) -> Result<impl Stream<Item = SseEvent> + Send + 'static> {
+        self.stream_events_with(url, &HashMap::new()).await
+    }
+
+    /// [`stream_events`](Self::stream_events) with extra request headers
+    ///
+    /// Used to resume a stream with `Last-Event-ID` or to send cookies.
+    pub async fn stream_events_with(
+        &self,
+        url: &str,
+        headers: &HashMap<String, String>,
+    ) -> Result<impl Stream<Item = SseEvent> + Send + 'static> {
+        let mut request = self
+            .client
+            .get(url)
+            .header(header::ACCEPT, "text/event-stream")
+            .header(header::CACHE_CONTROL, "no-cache");
+        for (name, value) in headers {
+            request = request.header(name.trim(), value.trim());
+        }
+        let request = request.build()?;
+        let response = self
+            .execute(request)
+            .await?
+            .error_for_status()
+            .with_context(|| format!("subscribing to {url}"))?;
+
+        let state = (
+            Box::pin(response.bytes_stream()),
+            SseParser::new(),
+            VecDeque::new(),
+        );
+        Ok(stream::unfold(
+            state,
+            |(mut body, mut parser, mut pending)| async move {
+                loop {
+                    if let Some(event) = pending.pop_front() {
+                        return Some((event, (body, parser, pending)));
+                    }
+                    match body.next().await? {
+                        Ok(chunk) => {
+                            let dropped = parser.dropped();
+                            pending.extend(parser.feed(&chunk));
+                            if parser.dropped() > dropped {
+                                warn!(
+                                    limit = crate::sse::DEFAULT_MAX_EVENT_SIZE,
+                                    "Dropped a server-sent event over the size limit"
+                                );
+                            }
+                        }
+                        Err(e) => {
+                            warn!(error = %e, "Event stream interrupted");
+                            return None;
+                        }
+                    }
+                }
+            },
+        ))
+    }
+
+    /// Fetch bytes `start..=end` of a resource (`end: None` reads to the end)
+    ///
+    /// Sends a `Range` header; if the server ignores it and answers `200`, the
+    /// body is read only as far as needed and sliced client-side.
+    pub async fn fetch_range(
+        &self,
+        url: &str,
+        start: u64,
+        end: Option<u64>,
+    ) -> Result<RangeResponse> {
+        let request = self
+            .client
+            .get(url)
+            .header(header::RANGE, range_header(start, end))
+            .header(header::ACCEPT_ENCODING, "identity")
+            .build()?;
+        let response = self.execute(request).await?;
+        RangeResponse::from_response(response, start, end).await
+    }
+
+    /// Fetch `url` as a subresource of kind `dest` loaded by the page at `page`
+    ///
+    /// Sends the host's profile's
+    /// [`to_subresource_headers`](BrowserProfile::to_subresource_headers), so
+    /// an image gets the browser's image `Accept` and `Sec-Fetch-Dest: image`
+    /// and a discovered endpoint looks like the page's own `fetch()`.
+    pub async fn fetch_subresource(
+        &self,
+        url: &str,
+        page: &str,
+        dest: RequestDest,
+    ) -> Result<Response> {
+        let headers = self
+            .profile_for_url(url)
+            .await
+            .to_subresource_headers(page, url, dest);
+        let request = self.client.get(url).headers(headers).build()?;
+        self.execute(request).await
+    }
+
+    /// Fetch a JSON API endpoint and deserialize the body into `T`
+    ///
+    /// Sends `Accept: application/json` and fails with a clear error when the
+    /// server answers with a non-JSON `Content-Type` (e.g. an HTML login page).
+    pub async fn fetch_json<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
+        let request = self
+            .client
+            .get(url)
+            .header(header::ACCEPT, "application/json")
+            .build()?;
+        let response = self.execute(request).await?;
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("")
+            .to_string();
+        let bytes = response.bytes().await?;
+
+        parse_json_body(&bytes, &content_type).with_context(|| format!("fetching JSON from {url}"))
+    }
+
     /// Get current browser profile
     pub async fn profile(&self) -> BrowserProfile {
         self.profile.read().await.clone()
@@ -207,6 +1277,499 @@ impl AcceleratedClient {
     }
 }
 
+/// Whether a `Content-Type` value denotes JSON (`application/json`, `*/*+json`).
+#[must_use]
+pub fn is_json_content_type(content_type: &str) -> bool {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase();
+    mime == "application/json" || mime == "text/json" || mime.ends_with("+json")
+}
+
+/// `Range` header value for bytes `start..=end` (open-ended when `end` is `None`)
+#[must_use]
+pub fn range_header(start: u64, end: Option<u64>) -> String {
+    match end {
+        Some(end) => format!("bytes={start}-{end}"),
+        None => format!("bytes={start}-"),
+    }
+}
+
+/// A redirect chain came back to a URL it had already visited
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("Redirect loop back to {url} ({} -> {url})", .chain.join(" -> "))]
+pub struct RedirectLoop {
+    /// The repeated URL
+    pub url: String,
+    /// URLs requested before the loop closed, in order
+    pub chain: Vec<String>,
+}
+
+impl RedirectLoop {
+    /// The loop behind a failed request, if that is why it failed
+    #[must_use]
+    pub fn from_error(err: &reqwest::Error) -> Option<Self> {
+        let mut source = std::error::Error::source(err);
+        while let Some(e) = source {
+            if let Some(redirect_loop) = e.downcast_ref::<Self>() {
+                return Some(redirect_loop.clone());
+            }
+            source = e.source();
+        }
+        None
+    }
+}
+
+/// Follow up to `max_redirects` redirects, failing with [`RedirectLoop`] as
+/// soon as the chain revisits a URL
+///
+/// `0` returns redirect responses as-is, like [`reqwest::redirect::Policy::none`],
+/// and so does a [`AcceleratedClient::preconnect`] probe.
+#[must_use]
+pub fn redirect_policy(max_redirects: usize) -> reqwest::redirect::Policy {
+    if max_redirects == 0 {
+        return reqwest::redirect::Policy::none();
+    }
+    reqwest::redirect::Policy::custom(move |attempt| {
+        if preconnecting() {
+            attempt.stop()
+        } else if attempt.previous().contains(attempt.url()) {
+            let redirect_loop = RedirectLoop {
+                url: attempt.url().to_string(),
+                chain: attempt.previous().iter().map(ToString::to_string).collect(),
+            };
+            attempt.error(redirect_loop)
+        } else if attempt.previous().len() > max_redirects {
+            attempt.error(format!("too many redirects (limit {max_redirects})"))
+        } else {
+            attempt.follow()
+        }
+    })
+}
+
+/// Body of a ranged request, with whether the server honoured the range
+#[derive(Debug)]
+pub struct RangeResponse {
+    pub status: reqwest::StatusCode,
+    /// `true` for `206 Partial Content`; `false` means the slice was cut client-side
+    pub honored: bool,
+    /// Full resource size, from `Content-Range` or `Content-Length` when known
+    pub total_length: Option<u64>,
+    pub body: Vec<u8>,
+}
+
+impl RangeResponse {
+    /// Read the requested byte slice from a response to a ranged request
+    ///
+    /// A `206` body is returned as-is. A `200` means the range was ignored, so
+    /// the body is streamed only up to `end` and sliced to `start..=end`.
+    pub async fn from_response(
+        mut response: Response,
+        start: u64,
+        end: Option<u64>,
+    ) -> Result<Self> {
+        let status = response.status();
+        let honored = status == reqwest::StatusCode::PARTIAL_CONTENT;
+
+        if honored {
+            let total_length = response
+                .headers()
+                .get(header::CONTENT_RANGE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|range| range.rsplit('/').next())
+                .and_then(|total| total.parse().ok());
+            let body = response.bytes().await?.into();
+            return Ok(Self {
+                status,
+                honored,
+                total_length,
+                body,
+            });
+        }
+
+        let total_length = response.content_length();
+        if !status.is_success() {
+            let body = response.bytes().await?.into();
+            return Ok(Self {
+                status,
+                honored,
+                total_length,
+                body,
+            });
+        }
+
+        // Stop downloading once the requested slice is in hand
+        let needed = end.map(|end| usize::try_from(end.saturating_add(1)).unwrap_or(usize::MAX));
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            body.extend_from_slice(&chunk);
+            if needed.is_some_and(|needed| body.len() >= needed) {
+                break;
+            }
+        }
+
+        let slice_end = needed.map_or(body.len(), |needed| needed.min(body.len()));
+        let slice_start = usize::try_from(start).unwrap_or(usize::MAX).min(slice_end);
+        body.truncate(slice_end);
+        body.drain(..slice_start);
+
+        Ok(Self {
+            status,
+            honored,
+            total_length,
+            body,
+        })
+    }
+}
+
+/// Deserialize a response body, rejecting responses not labelled as JSON.
+pub fn parse_json_body<T: DeserializeOwned>(body: &[u8], content_type: &str) -> Result<T> {
+    if !is_json_content_type(content_type) {
+        let shown = if content_type.is_empty() {
+            "none"
+        } else {
+            content_type
+        };
+        bail!("Expected a JSON response but got Content-Type: {shown}");
+    }
+    serde_json::from_slice(body).context("Response body is not valid JSON for the expected type")
+}
+
+/// File name suggested by a `Content-Disposition` header, safe to join onto
+/// a directory
+///
+/// The RFC 5987 `filename*=charset''value` form (UTF-8 or ISO-8859-1) wins
+/// over plain `filename=`. Any directory part is dropped, and names that
+/// would still escape or be hidden (`..`, `.env`) are rejected or trimmed.
+#[must_use]
+pub fn content_disposition_filename(value: &str) -> Option<String> {
+    let mut plain = None;
+    let mut extended = None;
+    for param in split_header_params(value).into_iter().skip(1) {
+        let Some((name, raw)) = param.split_once('=') else {
+            continue;
+        };
+        match name.trim().to_ascii_lowercase().as_str() {
+            "filename*" => extended = decode_ext_value(raw.trim()),
+            "filename" => plain = Some(unquote(raw.trim())),
+            _ => {}
+        }
+    }
+    extended.or(plain).and_then(|name| sanitize_filename(&name))
+}
+
+/// Split a header value on `;` outside quoted strings
+fn split_header_params(value: &str) -> Vec<String> {
+    let mut params = Vec::new();
+    let mut current = String::new();
+    let (mut quoted, mut escaped) = (false, false);
+    for c in value.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            ';' if !quoted => {
+                params.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    params.push(current);
+    params
+}
+
+fn unquote(raw: &str) -> String {
+    let Some(inner) = raw.strip_prefix('"').and_then(|r| r.strip_suffix('"')) else {
+        return raw.to_string();
+    };
+    let mut out = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        out.push(if c == '\\' {
+            chars.next().unwrap_or(c)
+        } else {
+            c
+        });
+    }
+    out
+}
+
+/// Decode an RFC 5987 `charset'language'percent-encoded` value
+fn decode_ext_value(raw: &str) -> Option<String> {
+    let mut parts = unquote(raw)
+        .splitn(3, '\'')
+        .map(String::from)
+        .collect::<Vec<_>>();
+    if parts.len() != 3 {
+        return None;
+    }
+    let encoded = parts.pop()?;
+    let bytes = urlencoding::decode_binary(encoded.as_bytes());
+    match parts[0].to_ascii_lowercase().as_str() {
+        "utf-8" => String::from_utf8(bytes.into_owned()).ok(),
+        "iso-8859-1" => Some(bytes.iter().map(|&b| char::from(b)).collect()),
+        _ => None,
+    }
+}
+
+/// Keep only the final path component of `name`, without control or reserved
+/// characters or leading dots; `None` when nothing usable is left
+#[must_use]
+pub fn sanitize_filename(name: &str) -> Option<String> {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let cleaned: String = base
+        .chars()
+        .filter(|c| !c.is_control())
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '|' | '?' | '*' => '_',
+            c => c,
+        })
+        .collect();
+    let cleaned = cleaned.trim().trim_start_matches('.').trim();
+    if cleaned.is_empty() {
+        return None;
+    }
+    // Stay within common file name limits without splitting a character
+    let mut end = cleaned.len().min(255);
+    while !cleaned.is_char_boundary(end) {
+        end -= 1;
+    }
+    Some(cleaned[..end].to_string())
+}
+
+/// Authority of the `h3` alternative in an `Alt-Svc` value, e.g. `:443` for
+/// `h3=":443"; ma=86400, h3-29=":443"`
+#[must_use]
+pub fn h3_alt_svc(value: &str) -> Option<String> {
+    value.split(',').find_map(|alternative| {
+        let (protocol, rest) = alternative.trim().split_once('=')?;
+        (protocol.trim() == "h3").then(|| {
+            let authority = rest.split(';').next().unwrap_or_default();
+            unquote(authority.trim())
+        })
+    })
+}
+
+/// A `Set-Cookie` header with its attributes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SetCookie {
+    /// Cookie name, trimmed
+    pub name: String,
+    /// Cookie value as sent, trimmed but not unquoted or decoded
+    pub value: String,
+    /// `Domain` attribute as sent (a leading dot is kept); `None` makes it a
+    /// host-only cookie
+    pub domain: Option<String>,
+    /// `Path` attribute; `None` means the default path of the request URL
+    pub path: Option<String>,
+    /// From `Max-Age` when present (it wins over `Expires`), else `Expires`;
+    /// `None` for a session cookie. A `Max-Age` too large to represent
+    /// saturates at the latest representable date.
+    pub expires: Option<chrono::DateTime<chrono::Utc>>,
+    /// `Max-Age` in seconds as sent; zero or negative expires the cookie
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_age: Option<i64>,
+    /// `Secure`: only sent over HTTPS
+    pub secure: bool,
+    /// `HttpOnly`: hidden from page scripts
+    #[serde(rename = "httponly")]
+    pub http_only: bool,
+    /// `SameSite` value as sent (`Strict`, `Lax`, `None`)
+    #[serde(rename = "samesite")]
+    pub same_site: Option<String>,
+}
+
+impl SetCookie {
+    /// Parse a `Set-Cookie` value received now
+    #[must_use]
+    pub fn parse(header: &str) -> Option<Self> {
+        Self::parse_at(header, chrono::Utc::now())
+    }
+
+    /// Parse a `Set-Cookie` value received at `now`, which `Max-Age` counts from
+    ///
+    /// Unknown attributes and unparseable dates are ignored, as browsers do.
+    #[must_use]
+    pub fn parse_at(header: &str, now: chrono::DateTime<chrono::Utc>) -> Option<Self> {
+        let mut parts = header.split(';');
+        let (name, value) = parts.next()?.split_once('=')?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        let mut cookie = Self {
+            name: name.to_string(),
+            value: value.trim().to_string(),
+            domain: None,
+            path: None,
+            expires: None,
+            max_age: None,
+            secure: false,
+            http_only: false,
+            same_site: None,
+        };
+
+        let mut expires = None;
+        for attribute in parts {
+            let (key, value) = attribute
+                .split_once('=')
+                .map_or((attribute, ""), |(k, v)| (k, v));
+            let value = value.trim();
+            match key.trim().to_ascii_lowercase().as_str() {
+                "domain" if !value.is_empty() => cookie.domain = Some(value.to_string()),
+                "path" if !value.is_empty() => cookie.path = Some(value.to_string()),
+                "expires" => expires = parse_cookie_date(value),
+                "max-age" => cookie.max_age = value.parse().ok(),
+                "secure" => cookie.secure = true,
+                "httponly" => cookie.http_only = true,
+                "samesite" if !value.is_empty() => cookie.same_site = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        cookie.expires = match cookie.max_age {
+            // Out of range is far in the future, not a panic
+            Some(secs) => Some(
+                chrono::TimeDelta::try_seconds(secs.max(0))
+                    .and_then(|max_age| now.checked_add_signed(max_age))
+                    .unwrap_or(chrono::DateTime::<chrono::Utc>::MAX_UTC),
+            ),
+            None => expires,
+        };
+        Some(cookie)
+    }
+}
+
+/// `Expires` dates: `Wed, 21 Oct 2015 07:28:00 GMT`, also with the legacy
+/// dashes (`21-Oct-2015`)
+fn parse_cookie_date(value: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc2822(&value.replace('-', " "))
+        .ok()
+        .map(|date| date.with_timezone(&chrono::Utc))
+}
+
+/// Turn off reqwest's transparent decompression so a response body can be
+/// read as it came over the wire with [`WireBody::read`]
+///
+/// `Accept-Encoding` still has to be sent, e.g. from a browser profile.
+pub fn without_decompression(builder: ClientBuilder) -> ClientBuilder {
+    builder.no_gzip().no_brotli().no_zstd().no_deflate()
+}
+
+/// Largest body [`WireBody::decode`] inflates a response to
+pub const MAX_DECODED_BYTES: usize = 256 * 1024 * 1024;
+
+/// Response body decoded by nab rather than reqwest, keeping its on-wire size
+#[derive(Debug, Clone)]
+pub struct WireBody {
+    /// Decoded body
+    pub body: Vec<u8>,
+    /// `Content-Encoding` as sent, `None` for an unencoded body
+    pub content_encoding: Option<String>,
+    /// Body size before decoding
+    pub compressed_bytes: usize,
+}
+
+impl WireBody {
+    /// Read and decode the body of a response from a client built with
+    /// [`without_decompression`]
+    pub async fn read(response: Response) -> Result<Self> {
+        let encoding = response
+            .headers()
+            .get(header::CONTENT_ENCODING)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from);
+        let raw = response.bytes().await?;
+        Self::decode(encoding.as_deref(), &raw)
+    }
+
+    /// Decode `raw` according to a `Content-Encoding` value
+    ///
+    /// Stacked codings (`gzip, br`) are undone last to first. `gzip`,
+    /// `deflate`, `br`, `zstd` and `identity` are understood. A body that
+    /// decodes to more than [`MAX_DECODED_BYTES`] is rejected, so a small
+    /// compression bomb can't exhaust memory.
+    pub fn decode(content_encoding: Option<&str>, raw: &[u8]) -> Result<Self> {
+        Self::decode_capped(content_encoding, raw, MAX_DECODED_BYTES)
+    }
+
+    fn decode_capped(content_encoding: Option<&str>, raw: &[u8], limit: usize) -> Result<Self> {
+        let codings: Vec<String> = content_encoding
+            .unwrap_or_default()
+            .split(',')
+            .map(|c| c.trim().to_ascii_lowercase())
+            .filter(|c| !c.is_empty() && c != "identity")
+            .collect();
+
+        let mut body = raw.to_vec();
+        for coding in codings.iter().rev() {
+            body = decode_coding(coding, &body, limit)
+                .with_context(|| format!("Failed to decode {coding} response body"))?;
+        }
+
+        Ok(Self {
+            body,
+            content_encoding: (!codings.is_empty()).then(|| codings.join(", ")),
+            compressed_bytes: raw.len(),
+        })
+    }
+
+    /// Body size after decoding
+    #[must_use]
+    pub fn decompressed_bytes(&self) -> usize {
+        self.body.len()
+    }
+
+    /// Decoded size over on-wire size, e.g. `4.0` when compression made the
+    /// body four times smaller; `None` for an empty body
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn ratio(&self) -> Option<f64> {
+        (self.compressed_bytes > 0)
+            .then(|| self.decompressed_bytes() as f64 / self.compressed_bytes as f64)
+    }
+}
+
+fn decode_coding(coding: &str, data: &[u8], limit: usize) -> std::io::Result<Vec<u8>> {
+    match coding {
+        "gzip" | "x-gzip" => read_capped(flate2::read::MultiGzDecoder::new(data), limit),
+        // Meant to be zlib-wrapped, but some servers send raw deflate
+        "deflate" => match read_capped(flate2::read::ZlibDecoder::new(data), limit) {
+            Err(e) if e.kind() != std::io::ErrorKind::FileTooLarge => {
+                read_capped(flate2::read::DeflateDecoder::new(data), limit)
+            }
+            result => result,
+        },
+        "br" => read_capped(brotli_decompressor::Decompressor::new(data, 4096), limit),
+        "zstd" => read_capped(zstd::stream::read::Decoder::new(data)?, limit),
+        other => Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            format!("unsupported content coding '{other}'"),
+        )),
+    }
+}
+
+/// Read `reader` to the end, failing once it yields more than `limit` bytes
+fn read_capped(reader: impl std::io::Read, limit: usize) -> std::io::Result<Vec<u8>> {
+    use std::io::Read;
+
+    let mut out = Vec::new();
+    reader
+        .take(u64::try_from(limit).unwrap_or(u64::MAX).saturating_add(1))
+        .read_to_end(&mut out)?;
+    if out.len() > limit {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::FileTooLarge,
+            format!("decodes to more than {limit} bytes"),
+        ));
+    }
+    Ok(out)
+}
+
 impl Default for AcceleratedClient {
     fn default() -> Self {
         Self::new().expect("Failed to create default client")
@@ -217,6 +1780,1061 @@ impl Default for AcceleratedClient {
 mod tests {
     use super::*;
 
+    use crate::test_support::{http1_client, serve, Response};
+    use serde::Deserialize;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// Serve a single canned response per connection on a local port.
+    fn mock_endpoint(content_type: &'static str, body: &'static str) -> String {
+        let addr = serve(move |_| Response::typed(content_type, body));
+        format!("http://{addr}/")
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Repo {
+        name: String,
+        stars: u32,
+    }
+
+    #[tokio::test]
+    async fn test_fetch_json_deserializes_struct() {
+        let url = mock_endpoint(
+            "application/json; charset=utf-8",
+            r#"{"name":"nab","stars":42,"owner":{"logins":["a","b"]}}"#,
+        );
+        let client = http1_client().await;
+
+        let repo: Repo = client.fetch_json(&url).await.unwrap();
+        assert_eq!(
+            repo,
+            Repo {
+                name: "nab".into(),
+                stars: 42
+            }
+        );
+
+        let value: serde_json::Value = client.fetch_json(&url).await.unwrap();
+        assert_eq!(
+            crate::json_path::select(&value, "owner.logins[1]"),
+            Some(&serde_json::json!("b"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_json_rejects_html() {
+        let url = mock_endpoint("text/html", "<html>login</html>");
+        let client = http1_client().await;
+
+        let err = client
+            .fetch_json::<serde_json::Value>(&url)
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("Content-Type: text/html"));
+    }
+
+    #[tokio::test]
+    async fn subresources_send_their_destination_accept() {
+        let addr = serve(|request| Response::ok(request.head()));
+        let page = format!("http://{addr}/gallery");
+        let image = format!("http://{addr}/photo.png");
+        let profile = crate::fingerprint::firefox_profile();
+        let image_accept = profile.accept_for(RequestDest::Image).to_lowercase();
+        let client = tokio::task::spawn_blocking(move || {
+            AcceleratedClient::with_builder(
+                profile,
+                TlsOptions::default(),
+                DEFAULT_MAX_REDIRECTS,
+                ClientBuilder::http1_only,
+            )
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+        let head = client
+            .fetch_subresource(&image, &page, RequestDest::Image)
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap()
+            .to_lowercase();
+        assert!(
+            head.contains(&format!("accept: {image_accept}\r\n")),
+            "{head}"
+        );
+        assert!(head.contains("sec-fetch-dest: image"), "{head}");
+        assert!(head.contains("sec-fetch-mode: no-cors"), "{head}");
+        assert!(head.contains(&format!("referer: {page}")), "{head}");
+        assert!(!head.contains("sec-fetch-user"), "{head}");
+        assert!(!head.contains("origin:"), "{head}");
+
+        let head = client
+            .fetch_subresource(&image, &page, RequestDest::Fetch)
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap()
+            .to_lowercase();
+        assert!(head.contains("accept: */*\r\n"), "{head}");
+        assert!(head.contains("sec-fetch-mode: cors"), "{head}");
+        assert!(head.contains("sec-fetch-dest: empty"), "{head}");
+    }
+
+    #[tokio::test]
+    async fn api_calls_do_not_inherit_navigation_headers() {
+        let addr = serve(|request| Response::ok(request.head()));
+        let url = format!("http://{addr}/");
+        let profile = crate::fingerprint::chrome_profile();
+        let api_headers = profile.to_headers_for(crate::RequestContext::SameOriginApi);
+        let client = tokio::task::spawn_blocking(move || {
+            AcceleratedClient::with_builder(
+                profile,
+                TlsOptions::default(),
+                DEFAULT_MAX_REDIRECTS,
+                ClientBuilder::http1_only,
+            )
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+        let navigation = client.fetch_text(&url).await.unwrap().to_lowercase();
+        assert!(navigation.contains("sec-fetch-user: ?1"), "{navigation}");
+        assert!(navigation.contains("upgrade-insecure-requests: 1"));
+        assert!(navigation.contains("cache-control: max-age=0"));
+
+        for method in [reqwest::Method::GET, reqwest::Method::POST] {
+            let request = client.inner().request(method, &url);
+            let request = request.headers(api_headers.clone()).build().unwrap();
+            let head = client.execute(request).await.unwrap().text().await;
+            let head = head.unwrap().to_lowercase();
+            assert!(head.contains("sec-fetch-mode: cors"), "{head}");
+            assert!(!head.contains("sec-fetch-user"), "{head}");
+            assert!(!head.contains("upgrade-insecure-requests"), "{head}");
+            assert!(!head.contains("cache-control"), "{head}");
+        }
+        let sent = client
+            .inner()
+            .get(&url)
+            .headers(api_headers)
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert!(!sent.to_lowercase().contains("sec-fetch-user"), "{sent}");
+    }
+
+    #[tokio::test]
+    async fn domain_profiles_pick_fingerprint_by_host() {
+        let addr = serve(|request| Response::ok(request.head()));
+        let profiles =
+            DomainProfiles::from_toml("[domains]\n\"example.com\" = \"firefox\"").unwrap();
+        let client = tokio::task::spawn_blocking(move || {
+            let chrome = crate::fingerprint::chrome_profile();
+            let inner = Client::builder()
+                .default_headers(chrome.to_client_headers())
+                .resolve("example.com", addr)
+                .resolve("other.test", addr)
+                .build()
+                .unwrap();
+            AcceleratedClient::from_client_with_profile(inner, chrome)
+        })
+        .await
+        .unwrap()
+        .unwrap()
+        .with_domain_profiles(profiles);
+        let port = addr.port();
+
+        let pinned = client
+            .fetch_text(&format!("http://example.com:{port}/"))
+            .await
+            .unwrap();
+        let default = client
+            .fetch_text(&format!("http://other.test:{port}/"))
+            .await
+            .unwrap();
+
+        assert!(pinned.contains("Firefox/"), "{pinned}");
+        assert!(!pinned.contains("sec-ch-ua"), "{pinned}");
+        assert!(default.contains("Chrome/"), "{default}");
+        assert!(default.contains("sec-ch-ua: "), "{default}");
+        assert!(!default.contains("Firefox/"));
+        assert!(client
+            .profile_for_url(&format!("http://www.example.com:{port}/"))
+            .await
+            .user_agent
+            .contains("Firefox/"));
+    }
+
+    /// Serve a 26-byte alphabet; honour `Range: bytes=a-b` unless `ignore_range`.
+    fn range_server(ignore_range: bool) -> String {
+        const DATA: &str = "abcdefghijklmnopqrstuvwxyz";
+        let addr = serve(move |request| {
+            let range = request
+                .header("range")
+                .and_then(|r| r.strip_prefix("bytes="))
+                .and_then(|r| r.split_once('-'))
+                .and_then(|(a, b)| Some((a.parse::<usize>().ok()?, b.parse::<usize>().ok()?)));
+
+            match range {
+                Some((start, end)) if !ignore_range => Response::status(206)
+                    .header(
+                        "Content-Range",
+                        format!("bytes {start}-{end}/{}", DATA.len()),
+                    )
+                    .body(&DATA[start..=end]),
+                _ => Response::ok(DATA),
+            }
+        });
+        format!("http://{addr}/alphabet")
+    }
+
+    #[tokio::test]
+    async fn test_fetch_range_partial_content() {
+        let url = range_server(false);
+        let client = http1_client().await;
+
+        let range = client.fetch_range(&url, 2, Some(5)).await.unwrap();
+        assert_eq!(range.status, reqwest::StatusCode::PARTIAL_CONTENT);
+        assert!(range.honored);
+        assert_eq!(range.body, b"cdef");
+        assert_eq!(range.total_length, Some(26));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_range_truncates_when_ignored() {
+        let url = range_server(true);
+        let client = http1_client().await;
+
+        let range = client.fetch_range(&url, 2, Some(5)).await.unwrap();
+        assert_eq!(range.status, reqwest::StatusCode::OK);
+        assert!(!range.honored);
+        assert_eq!(range.body, b"cdef");
+
+        let tail = client.fetch_range(&url, 24, None).await.unwrap();
+        assert_eq!(tail.body, b"yz");
+    }
+
+    #[test]
+    fn test_range_header() {
+        assert_eq!(range_header(0, Some(99)), "bytes=0-99");
+        assert_eq!(range_header(100, None), "bytes=100-");
+    }
+
+    #[test]
+    fn test_wire_body_decodes_stacked_codings() {
+        let text = "compressible ".repeat(200);
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+        gzip.write_all(text.as_bytes()).unwrap();
+        let gzipped = gzip.finish().unwrap();
+        let stacked = zstd::stream::encode_all(&gzipped[..], 0).unwrap();
+
+        let wire = WireBody::decode(Some("gzip, ZSTD"), &stacked).unwrap();
+        assert_eq!(wire.body, text.as_bytes());
+        assert_eq!(wire.content_encoding.as_deref(), Some("gzip, zstd"));
+        assert_eq!(wire.compressed_bytes, stacked.len());
+        assert!(wire.ratio().unwrap() > 10.0);
+
+        let plain = WireBody::decode(Some("identity"), b"abc").unwrap();
+        assert_eq!(plain.content_encoding, None);
+        assert_eq!(plain.ratio(), Some(1.0));
+        assert_eq!(WireBody::decode(None, b"").unwrap().ratio(), None);
+
+        assert!(WireBody::decode(Some("compress"), b"abc").is_err());
+        assert!(WireBody::decode(Some("gzip"), b"not gzip").is_err());
+        // Decoding stops at the cap instead of inflating the whole body
+        let mut zlib = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::best());
+        zlib.write_all(text.as_bytes()).unwrap();
+        let deflated = zlib.finish().unwrap();
+        let zstd = zstd::stream::encode_all(text.as_bytes(), 0).unwrap();
+        for (coding, body) in [("gzip", &gzipped), ("deflate", &deflated), ("zstd", &zstd)] {
+            let err = WireBody::decode_capped(Some(coding), body, 100).unwrap_err();
+            assert!(
+                format!("{err:#}").contains("more than 100 bytes"),
+                "{err:#}"
+            );
+            let wire = WireBody::decode_capped(Some(coding), body, text.len()).unwrap();
+            assert_eq!(wire.body, text.as_bytes());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_redirect_policy_reports_loops_and_limits() {
+        // `/a` and `/b` bounce between each other; `/n/<i>` goes one deeper
+        let addr = serve(|request| {
+            let location = match request.target.as_str() {
+                "/a" => "/b".to_string(),
+                "/b" => "/a".to_string(),
+                path => format!("{path}/n"),
+            };
+            Response::status(302).header("Location", location)
+        });
+        let base = format!("http://{addr}");
+        let client = Client::builder()
+            .redirect(redirect_policy(10))
+            .build()
+            .unwrap();
+
+        let err = client.get(format!("{base}/a")).send().await.unwrap_err();
+        let redirect_loop = RedirectLoop::from_error(&err).unwrap();
+        assert_eq!(redirect_loop.url, format!("{base}/a"));
+        assert_eq!(
+            redirect_loop.to_string(),
+            format!("Redirect loop back to {base}/a ({base}/a -> {base}/b -> {base}/a)")
+        );
+
+        let err = client.get(format!("{base}/n")).send().await.unwrap_err();
+        assert!(err.is_redirect());
+        assert!(RedirectLoop::from_error(&err).is_none());
+        assert!(format!("{err:?}").contains("too many redirects (limit 10)"));
+    }
+
+    #[test]
+    fn test_set_cookie_attributes() {
+        let now = chrono::DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let cookie = SetCookie::parse_at(
+            "sid=abc123; Domain=.example.com; Path=/app; \
+             Expires=Wed, 21 Oct 2015 07:28:00 GMT; Max-Age=3600; Secure; HttpOnly; SameSite=Lax",
+            now,
+        )
+        .unwrap();
+
+        assert_eq!(cookie.name, "sid");
+        assert_eq!(cookie.value, "abc123");
+        assert_eq!(cookie.domain.as_deref(), Some(".example.com"));
+        assert_eq!(cookie.path.as_deref(), Some("/app"));
+        assert_eq!(cookie.max_age, Some(3600));
+        // Max-Age wins over Expires
+        assert_eq!(
+            cookie.expires.unwrap().to_rfc3339(),
+            "2026-01-01T01:00:00+00:00"
+        );
+        assert!(cookie.secure);
+        assert!(cookie.http_only);
+        assert_eq!(cookie.same_site.as_deref(), Some("Lax"));
+
+        let json = serde_json::to_value(&cookie).unwrap();
+        assert_eq!(json["httponly"], true);
+        assert_eq!(json["samesite"], "Lax");
+        assert_eq!(json["expires"], "2026-01-01T01:00:00Z");
+
+        let legacy =
+            SetCookie::parse_at("a=1; expires=Wed, 21-Oct-2015 07:28:00 GMT", now).unwrap();
+        assert_eq!(
+            legacy.expires.unwrap().to_rfc3339(),
+            "2015-10-21T07:28:00+00:00"
+        );
+        assert!(!legacy.secure && legacy.domain.is_none() && legacy.same_site.is_none());
+        assert!(SetCookie::parse_at("no-equals-sign", now).is_none());
+    }
+
+    #[test]
+    fn test_set_cookie_huge_max_age_saturates() {
+        let now = chrono::Utc::now();
+        for max_age in [i64::MAX.to_string(), "9999999999999".to_string()] {
+            let cookie = SetCookie::parse_at(&format!("a=1; Max-Age={max_age}"), now).unwrap();
+            assert_eq!(
+                cookie.expires,
+                Some(chrono::DateTime::<chrono::Utc>::MAX_UTC)
+            );
+        }
+        let expired = SetCookie::parse_at(&format!("a=1; Max-Age={}", i64::MIN), now).unwrap();
+        assert_eq!(expired.expires, Some(now));
+    }
+
+    #[test]
+    fn test_content_disposition_plain_filename() {
+        assert_eq!(
+            content_disposition_filename(r#"attachment; filename="report 2026.pdf""#).as_deref(),
+            Some("report 2026.pdf")
+        );
+        assert_eq!(
+            content_disposition_filename("attachment; filename=data.csv; size=10").as_deref(),
+            Some("data.csv")
+        );
+        assert_eq!(
+            content_disposition_filename(r#"attachment; filename="a \"q\"; b.txt""#).as_deref(),
+            Some("a _q_; b.txt")
+        );
+        // Path components and hidden-file dots never survive
+        assert_eq!(
+            content_disposition_filename(r#"attachment; filename="../../etc/passwd""#).as_deref(),
+            Some("passwd")
+        );
+        assert_eq!(
+            content_disposition_filename(r#"attachment; filename="C:\\temp\\..\\.bashrc""#)
+                .as_deref(),
+            Some("bashrc")
+        );
+        assert_eq!(
+            content_disposition_filename(r#"attachment; filename="..""#),
+            None
+        );
+        assert_eq!(content_disposition_filename("inline"), None);
+    }
+
+    #[test]
+    fn test_content_disposition_encoded_filename() {
+        // filename* takes precedence over the ASCII fallback
+        assert_eq!(
+            content_disposition_filename(
+                "attachment; filename=\"fallback.txt\"; filename*=UTF-8''na%C3%AFve%20r%C3%A9sum%C3%A9.txt"
+            )
+            .as_deref(),
+            Some("naïve résumé.txt")
+        );
+        assert_eq!(
+            content_disposition_filename("attachment; filename*=iso-8859-1'fi'h%E4t%E4.txt")
+                .as_deref(),
+            Some("hätä.txt")
+        );
+        assert_eq!(
+            content_disposition_filename("attachment; filename*=UTF-8''%2E%2E%2Fsecret%2Fkey.pem")
+                .as_deref(),
+            Some("key.pem")
+        );
+        // Unknown charset falls back to the plain name
+        assert_eq!(
+            content_disposition_filename(
+                "attachment; filename*=koi8-r''%C6%C1%CA%CC; filename=plain.txt"
+            )
+            .as_deref(),
+            Some("plain.txt")
+        );
+    }
+
+    /// Echo each request (head and body) back as the response body.
+    fn echo_endpoint() -> String {
+        let addr = serve(|request| {
+            let mut echo = format!("{}\r\n", request.head()).into_bytes();
+            echo.extend_from_slice(&request.body);
+            Response::typed("text/plain", echo)
+        });
+        format!("http://{addr}/api/items")
+    }
+
+    #[tokio::test]
+    async fn test_request_sends_method_headers_and_body() {
+        let url = echo_endpoint();
+        let client = http1_client().await;
+        let headers = HashMap::from([(
+            "Authorization".to_string(),
+            "Bearer secret-token".to_string(),
+        )]);
+
+        let response = client
+            .request("post", &url, &headers, Some(r#"{"name":"nab"}"#))
+            .await
+            .unwrap();
+        let echoed = response.text().await.unwrap();
+        let lower = echoed.to_ascii_lowercase();
+
+        assert!(echoed.starts_with("POST /api/items HTTP/1.1"), "{echoed}");
+        assert!(lower.contains("authorization: bearer secret-token"));
+        assert!(lower.contains("content-type: application/json"));
+        assert!(echoed.ends_with(r#"{"name":"nab"}"#));
+
+        assert!(client
+            .request("NOT A METHOD", &url, &HashMap::new(), None)
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_tls13_only_client() {
+        let tls = TlsOptions::tls13_only();
+        assert_eq!(tls.min_tls_version, Some(Version::TLS_1_3));
+        assert!(tls.is_constrained());
+
+        let client = AcceleratedClient::with_tls(random_profile(), tls.clone()).unwrap();
+        assert_eq!(client.tls, tls);
+    }
+
+    /// Self-signed certificate for `localhost`
+    const TEST_CA_PEM: &str = "-----BEGIN CERTIFICATE-----\n\
+MIIDJzCCAg+gAwIBAgIUElGOLsfHDqinah+qRASRaMB7KQQwDQYJKoZIhvcNAQEL\n\
+BQAwFDESMBAGA1UEAwwJbG9jYWxob3N0MCAXDTI2MTAxNjA5Mzk1N1oYDzIxMjYw\n\
+OTIyMDkzOTU3WjAUMRIwEAYDVQQDDAlsb2NhbGhvc3QwggEiMA0GCSqGSIb3DQEB\n\
+AQUAA4IBDwAwggEKAoIBAQDiHZADq4e8QhkJzBsNjI5FkFsTAt3JhXUYi5XPfN+N\n\
+AbuPEh6pvtZIE7AOb+oX7S16GF0ac0T8gOQqM/LALCMtHYFox22bhe0y3tOh0wCf\n\
+D9WxgqUX9OC9qXX9+n28MxULv/uoPzi/gRN9WiiXvNOLZp9yqsd4KxbhbMt/prvV\n\
+yEBFHu1jdLR98sigS5Mwg755LI694LaNsZ/m2DqTTQ0GEeAs2RoeZ+g2IgmKBquT\n\
+XzsXQFWu4rYNglaR/ayAwA8v6Q2MFiNBDMGxUK7Vd5rWkIVoYg0a+Ux/BV2MQ+EY\n\
+68MjqVf7ahpIY4ooZ02D39amfkrRi9IrykYdZFBYmYvFAgMBAAGjbzBtMB0GA1Ud\n\
+DgQWBBToC6w3PM/0qwDBED03N9xyYY0LFjAfBgNVHSMEGDAWgBToC6w3PM/0qwDB\n\
+ED03N9xyYY0LFjAPBgNVHRMBAf8EBTADAQH/MBoGA1UdEQQTMBGCCWxvY2FsaG9z\n\
+dIcEfwAAATANBgkqhkiG9w0BAQsFAAOCAQEAULJvi/zArDmHJyWBAhCAL/lOIiaN\n\
+M7SFx1NnayxQTGsAoHI6/+7mliVH0Vd/z8nW9Ly/LqQR+5/qKTK+ch1wexDDvzwL\n\
+9/iH5F3Pdwh644pCFNgvUGy9WIPpXYqe5o1I3NrS0gcPhwIDzcueJVJYn94QQ+A4\n\
+F8ZyoGkPZvP43ZHXJpdRgIfQZcdHwj9nPE75Afqa+MBkpUxTg+fGzPBhQw7cvQJC\n\
+3GfbYMtQpHzqxx9RE5Rv2uTc/vnOAPP3mv5bOEPyZtRKtrhWw1cifvJeC8s8vtyu\n\
+RZsTbe/39DIHi64WABLVNmWwFdPFJf289MjN+CYatilE4nw0J9Uu0ZkvgQ==\n\
+-----END CERTIFICATE-----\n\
+";
+
+    #[test]
+    fn test_custom_ca_bundle_is_loaded() {
+        let tls = TlsOptions::default()
+            .with_root_certificate_pem(TEST_CA_PEM.as_bytes())
+            .unwrap();
+        assert_eq!(tls.root_certificates_pem.len(), 1);
+        assert!(tls.customizes_trust());
+        assert!(!tls.insecure);
+
+        let client = AcceleratedClient::with_tls(random_profile(), tls.clone()).unwrap();
+        assert_eq!(client.tls, tls);
+
+        assert!(TlsOptions::default()
+            .with_root_certificate_pem(b"not a certificate")
+            .is_err());
+        assert!(TlsOptions::default()
+            .with_root_certificate_file("/nonexistent/ca.pem")
+            .is_err());
+        assert!(!TlsOptions::default().customizes_trust());
+        assert!(TlsOptions::default().with_insecure(true).customizes_trust());
+    }
+
+    /// Keep-alive HTTP/1.1 server answering every request on a connection
+    fn keep_alive_endpoint() -> std::net::SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                std::thread::spawn(move || {
+                    let mut buf = [0u8; 4096];
+                    while let Ok(n @ 1..) = stream.read(&mut buf) {
+                        let body = if buf[..n].starts_with(b"HEAD") {
+                            ""
+                        } else {
+                            "ok"
+                        };
+                        let response =
+                            format!("HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{body}");
+                        if stream.write_all(response.as_bytes()).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_fetch_with_info_reports_protocol_and_reuse() {
+        let addr = keep_alive_endpoint();
+        let url = format!("http://{addr}/");
+        let client = http1_client().await;
+        assert_eq!(client.fetch_profile().await.1, None);
+
+        let (response, first) = client.fetch_with_info(&url).await.unwrap();
+        response.bytes().await.unwrap();
+        assert_eq!(first.http_version, "HTTP/1.1");
+        // Cleartext negotiates no ALPN protocol
+        assert_eq!(first.protocol, None);
+        assert!(!first.tls);
+        assert_eq!(first.tls_version, None);
+        assert_eq!(first.remote_addr, Some(addr));
+        assert!(first.local_addr.is_some());
+        assert!(!first.reused);
+
+        let (response, second) = client.fetch_with_info(&url).await.unwrap();
+        response.bytes().await.unwrap();
+        assert!(second.reused);
+        assert_eq!(second.local_addr, first.local_addr);
+
+        let (_, last) = client.fetch_profile().await;
+        assert_eq!(last, Some(second));
+    }
+
+    #[test]
+    fn test_connection_log_ignores_port_reuse_and_stays_bounded() {
+        let mut log = ConnectionLog::default();
+        let remote: SocketAddr = "10.0.0.1:443".parse().unwrap();
+        let local = |port| SocketAddr::from(([10, 0, 0, 2], port));
+
+        assert!(!log.reused((local(50000), remote)));
+        assert!(log.reused((local(50000), remote)));
+        // The same port towards another server is another connection
+        assert!(!log.reused((local(50000), "10.0.0.3:443".parse().unwrap())));
+        // A port idle past the pool timeout now belongs to a new connection
+        let stale = Instant::now().checked_sub(POOL_IDLE_TIMEOUT).unwrap();
+        log.seen.insert((local(50001), remote), stale);
+        assert!(!log.reused((local(50001), remote)));
+
+        for port in 0..2000 {
+            log.reused((local(port), remote));
+        }
+        assert_eq!(log.seen.len(), MAX_LOGGED_CONNECTIONS);
+        assert!(log.reused((local(1999), remote)));
+    }
+
+    #[tokio::test]
+    async fn test_preconnect_pools_connection_for_fetch() {
+        let addr = keep_alive_endpoint();
+        let url = format!("http://{addr}/");
+        let client = http1_client().await;
+
+        let warmed = client.preconnect(&[&url, "http://127.0.0.1:1/"]).await;
+        assert_eq!(warmed.len(), 2);
+        let connection = warmed[0].connection.as_ref().unwrap();
+        assert_eq!(connection.remote_addr, Some(addr));
+        assert!(!connection.reused);
+        assert!(warmed[0].error.is_none());
+        assert!(warmed[1].connection.is_none() && warmed[1].error.is_some());
+
+        let (response, info) = client.fetch_with_info(&url).await.unwrap();
+        response.bytes().await.unwrap();
+        assert!(info.reused);
+        assert_eq!(info.local_addr, connection.local_addr);
+    }
+
+    #[tokio::test]
+    async fn test_preconnect_probe_skips_redirects_and_cookies() {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&requests);
+        let addr = serve(move |request| {
+            seen.lock().unwrap().push(format!(
+                "{} {} {}",
+                request.method,
+                request.target,
+                request.header("cookie").unwrap_or("-")
+            ));
+            match request.target.as_str() {
+                "/" => Response::status(302)
+                    .header("Location", "/next")
+                    .header("Set-Cookie", "probe=1; Path=/"),
+                _ => Response::ok(request.header("cookie").unwrap_or("no cookie")),
+            }
+        });
+        let url = format!("http://{addr}/");
+        let client = tokio::task::spawn_blocking(|| {
+            AcceleratedClient::with_builder(
+                crate::fingerprint::chrome_profile(),
+                TlsOptions::default(),
+                DEFAULT_MAX_REDIRECTS,
+                ClientBuilder::http1_only,
+            )
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        let jar = Arc::clone(client.cookie_jar().unwrap());
+        jar.store("sid=secret; Path=/", &url::Url::parse(&url).unwrap());
+
+        let warmed = client.preconnect(&[&url]).await;
+        assert!(warmed[0].error.is_none(), "{:?}", warmed[0].error);
+        assert!(!warmed[0].h3_warmed);
+        assert!(!client.export_cookies().contains("probe"));
+        assert_eq!(*requests.lock().unwrap(), ["HEAD / -"]);
+
+        // Ordinary requests still follow the redirect and keep the cookie
+        let body = client.fetch_text(&url).await.unwrap();
+        assert!(
+            body.contains("sid=secret") && body.contains("probe=1"),
+            "{body}"
+        );
+    }
+
+    /// Server failing the first `failures` requests with `status`, then
+    /// answering 200; returns the number of requests seen
+    fn flaky_endpoint(
+        failures: usize,
+        status: u16,
+    ) -> (std::net::SocketAddr, Arc<std::sync::atomic::AtomicUsize>) {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let seen = Arc::new(AtomicUsize::new(0));
+        let count = Arc::clone(&seen);
+        let addr = serve(move |_| {
+            if count.fetch_add(1, Ordering::SeqCst) < failures {
+                Response::status(status).header("Retry-After", "0")
+            } else {
+                Response::ok("ok")
+            }
+        });
+        (addr, seen)
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_status() {
+        use std::sync::atomic::Ordering;
+
+        let (addr, seen) = flaky_endpoint(2, 503);
+        let client = http1_client().await;
+        let response = client.fetch(&format!("http://{addr}/")).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(seen.load(Ordering::SeqCst), 3);
+
+        // Out of attempts: the last failure is returned as-is
+        let (addr, seen) = flaky_endpoint(5, 502);
+        let client = http1_client().await.with_retry(RetryPolicy {
+            max_attempts: 2,
+            base_delay: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        });
+        let response = client.fetch(&format!("http://{addr}/")).await.unwrap();
+        assert_eq!(response.status(), 502);
+        assert_eq!(seen.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_no_retry_for_client_errors_or_post() {
+        use std::sync::atomic::Ordering;
+
+        let (addr, seen) = flaky_endpoint(1, 404);
+        let client = http1_client().await.with_retry(RetryPolicy {
+            retryable_statuses: vec![404, 503],
+            ..RetryPolicy::default()
+        });
+        let response = client.fetch(&format!("http://{addr}/")).await.unwrap();
+        assert_eq!(response.status(), 404);
+        assert_eq!(seen.load(Ordering::SeqCst), 1);
+
+        let (addr, seen) = flaky_endpoint(1, 503);
+        let response = client
+            .request(
+                "POST",
+                &format!("http://{addr}/"),
+                &HashMap::new(),
+                Some("{}"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 503);
+        assert_eq!(seen.load(Ordering::SeqCst), 1);
+    }
+
+    /// Login endpoint: `/login` sets a session cookie and redirects to
+    /// `/home`, which echoes the `Cookie` header it received
+    fn login_endpoint() -> std::net::SocketAddr {
+        serve(|request| {
+            if request.target == "/login" {
+                Response::status(302)
+                    .header("Location", "/home")
+                    .header("Set-Cookie", "session=abc; Path=/; HttpOnly")
+            } else {
+                Response::ok(request.header("cookie").unwrap_or("none"))
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_cookie_store_follows_redirect_chain() {
+        let addr = login_endpoint();
+        let client = tokio::task::spawn_blocking(AcceleratedClient::new_adaptive)
+            .await
+            .unwrap()
+            .unwrap()
+            .with_cookie_store(true);
+
+        let body = client
+            .fetch_text(&format!("http://{addr}/login"))
+            .await
+            .unwrap();
+        assert_eq!(body, "session=abc");
+        let body = client
+            .fetch_text(&format!("http://{addr}/account"))
+            .await
+            .unwrap();
+        assert_eq!(body, "session=abc");
+        assert!(client
+            .export_cookies()
+            .contains("#HttpOnly_127.0.0.1\tFALSE\t/\tFALSE\t0\tsession\tabc\n"));
+
+        let client = client.with_cookie_store(false);
+        let body = client
+            .fetch_text(&format!("http://{addr}/account"))
+            .await
+            .unwrap();
+        assert_eq!(body, "none");
+    }
+
+    #[tokio::test]
+    async fn test_cookie_store_on_wrapped_client() {
+        let addr = serve(|request| {
+            let echo = Response::ok(request.header("cookie").unwrap_or("none"));
+            if request.target == "/set" {
+                echo.header("Set-Cookie", "session=abc; Path=/")
+            } else {
+                echo
+            }
+        });
+        let client = http1_client().await.with_cookie_store(true);
+
+        let body = client
+            .fetch_text(&format!("http://{addr}/set"))
+            .await
+            .unwrap();
+        assert_eq!(body, "none");
+        let body = client
+            .fetch_text(&format!("http://{addr}/account"))
+            .await
+            .unwrap();
+        assert_eq!(body, "session=abc");
+        assert!(client.export_cookies().contains("\tsession\tabc\n"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_sends_headers_in_browser_order() {
+        use crate::test_support::serve_once;
+
+        let (addr, server) = serve_once(|_| Response::ok("ok"));
+        let chrome = tokio::task::spawn_blocking(crate::fingerprint::chrome_profile)
+            .await
+            .unwrap();
+        let client = AcceleratedClient::with_builder(
+            chrome.clone(),
+            TlsOptions::default(),
+            DEFAULT_MAX_REDIRECTS,
+            ClientBuilder::http1_only,
+        )
+        .unwrap();
+
+        // Set before the client's defaults are merged in, as fetch does
+        let request = client
+            .inner()
+            .get(format!("http://{addr}/"))
+            .header(header::COOKIE, "a=1")
+            .header(header::REFERER, "https://example.com/")
+            .build()
+            .unwrap();
+        client.execute(request).await.unwrap();
+
+        let mut sent = chrome.to_headers();
+        sent.insert(header::COOKIE, header::HeaderValue::from_static("a=1"));
+        sent.insert(
+            header::REFERER,
+            header::HeaderValue::from_static("https://example.com/"),
+        );
+        let expected: Vec<String> = chrome
+            .order_headers(sent)
+            .into_iter()
+            .map(|(name, _)| name.to_string())
+            .collect();
+        let received: Vec<String> = server
+            .join()
+            .unwrap()
+            .headers
+            .into_iter()
+            .map(|(name, _)| name)
+            .filter(|name| name != "host")
+            .collect();
+        assert_eq!(received, expected);
+    }
+
+    #[tokio::test]
+    async fn test_platform_version_needs_accept_ch() {
+        let addr = serve(|request| {
+            let sent = request
+                .header("sec-ch-ua-platform-version")
+                .unwrap_or("none");
+            let response = Response::ok(sent);
+            match request.target.as_str() {
+                "/opt-in" => {
+                    response.header("Accept-CH", "Sec-CH-UA-Model, Sec-CH-UA-Platform-Version")
+                }
+                "/opt-out" => response.header("Accept-CH", "Sec-CH-UA-Model"),
+                _ => response,
+            }
+        });
+        let client = tokio::task::spawn_blocking(|| {
+            AcceleratedClient::from_client_with_profile(
+                reqwest::Client::new(),
+                crate::fingerprint::android_chrome_profile(),
+            )
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        let url = |path: &str| format!("http://{addr}{path}");
+
+        assert_eq!(client.fetch_text(&url("/")).await.unwrap(), "none");
+        assert_eq!(client.fetch_text(&url("/opt-in")).await.unwrap(), "none");
+        let version = client.fetch_text(&url("/")).await.unwrap();
+        assert!(version.starts_with('"'), "{version}");
+        assert_eq!(client.fetch_text(&url("/opt-out")).await.unwrap(), version);
+        assert_eq!(client.fetch_text(&url("/")).await.unwrap(), "none");
+    }
+
+    #[test]
+    fn test_h3_alt_svc() {
+        assert_eq!(
+            h3_alt_svc(r#"h3-29=":443"; ma=86400, h3=":443"; ma=86400"#).as_deref(),
+            Some(":443")
+        );
+        assert_eq!(
+            h3_alt_svc(r#"h3="alt.example.com:8443""#).as_deref(),
+            Some("alt.example.com:8443")
+        );
+        assert_eq!(h3_alt_svc(r#"h2=":443""#), None);
+        assert_eq!(h3_alt_svc("clear"), None);
+    }
+
+    #[test]
+    fn test_only_tls_version() {
+        assert_eq!(TlsOptions::tls13_only().only_version(), Some("1.3"));
+        assert_eq!(
+            TlsOptions::default()
+                .with_max_tls_version(Version::TLS_1_2)
+                .only_version(),
+            Some("1.2")
+        );
+        assert_eq!(TlsOptions::default().only_version(), None);
+    }
+
+    #[test]
+    fn test_parse_tls_version() {
+        assert_eq!(TlsOptions::parse_version("1.2").unwrap(), Version::TLS_1_2);
+        assert_eq!(TlsOptions::parse_version("1.3").unwrap(), Version::TLS_1_3);
+        assert!(TlsOptions::parse_version("1.0").is_err());
+        assert!(TlsOptions::parse_version("tls").is_err());
+    }
+
+    #[test]
+    fn test_is_json_content_type() {
+        assert!(is_json_content_type("application/json"));
+        assert!(is_json_content_type(
+            "application/vnd.api+json; charset=utf-8"
+        ));
+        assert!(!is_json_content_type("text/html"));
+        assert!(!is_json_content_type(""));
+    }
+
+    #[cfg(feature = "cassette")]
+    #[tokio::test]
+    async fn test_cassette_record_then_replay_offline() {
+        use crate::test_support::serve_once;
+
+        // Server answers exactly one request, then closes its port
+        let (addr, server) = serve_once(|_| {
+            Response::typed("text/plain", "recorded \u{1F4FC} payload").header("X-Test", "yes")
+        });
+        let url = format!("http://{addr}/data");
+        let path = std::env::temp_dir().join(format!("nab-cassette-{}.json", uuid::Uuid::new_v4()));
+
+        let recorder = http1_client()
+            .await
+            .with_cassette(&path, CassetteMode::Record)
+            .unwrap();
+        let live = recorder.fetch(&url).await.unwrap();
+        assert_eq!(live.headers()["x-test"], "yes");
+        let live_bytes = live.bytes().await.unwrap();
+        server.join().unwrap();
+
+        let player = http1_client()
+            .await
+            .with_cassette(&path, CassetteMode::Replay)
+            .unwrap();
+        let replayed = player.fetch(&url).await.unwrap();
+        assert_eq!(replayed.status(), 200);
+        assert_eq!(replayed.headers()["x-test"], "yes");
+        assert_eq!(replayed.bytes().await.unwrap(), live_bytes);
+
+        // Unrecorded requests fail instead of reaching the network
+        assert!(player.fetch(&format!("{url}?other")).await.is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_fetch_records_metrics() {
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+        let url = mock_endpoint("text/plain", "metered");
+        let client = AcceleratedClient::from_client(Client::new()).unwrap();
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+
+        // Local recorders are thread-scoped, so drive the fetches on this thread
+        metrics::with_local_recorder(&recorder, || {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap()
+                .block_on(async {
+                    client.fetch(&url).await.unwrap();
+                    client.fetch(&url).await.unwrap();
+                });
+        });
+
+        let mut requests = 0;
+        let mut latency_samples = 0;
+        for (key, _, _, value) in snapshotter.snapshot().into_vec() {
+            match (key.key().name(), value) {
+                (telemetry::REQUESTS_TOTAL, DebugValue::Counter(n)) => requests += n,
+                (telemetry::REQUEST_DURATION_SECONDS, DebugValue::Histogram(samples)) => {
+                    latency_samples += samples.len();
+                }
+                _ => {}
+            }
+        }
+        assert_eq!(requests, 2);
+        assert_eq!(latency_samples, 2);
+    }
+
+    /// Serve an event stream in chunks that split frames mid-line
+    fn mock_sse_endpoint(chunks: &'static [&'static str]) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf);
+                let _ = stream.write_all(
+                    b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\
+                      Transfer-Encoding: chunked\r\nConnection: close\r\n\r\n",
+                );
+                for chunk in chunks {
+                    let _ = write!(stream, "{:x}\r\n{chunk}\r\n", chunk.len());
+                    let _ = stream.flush();
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                let _ = stream.write_all(b"0\r\n\r\n");
+            }
+        });
+        format!("http://{addr}/events")
+    }
+
+    #[tokio::test]
+    async fn test_stream_events_parses_sse() {
+        let url = mock_sse_endpoint(&[
+            ": connected\n\nevent: price\nid: 1\ndata: {\"eur\":",
+            "10}\n\ndata: first line\ndata: second",
+            " line\n\nretry: 5000\ndata: bye\n\n",
+        ]);
+        let client = http1_client().await;
+
+        let events: Vec<SseEvent> = client.stream_events(&url).await.unwrap().collect().await;
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].event, "price");
+        assert_eq!(events[0].data, r#"{"eur":10}"#);
+        assert_eq!(events[0].id.as_deref(), Some("1"));
+        assert_eq!(events[1].event, "message");
+        assert_eq!(events[1].data, "first line\nsecond line");
+        assert_eq!(events[2].data, "bye");
+        assert_eq!(events[2].retry, Some(5000));
+
+        let body: Vec<Bytes> = client
+            .stream_body(&url)
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert!(body.len() > 1, "body arrives in several chunks");
+        assert!(String::from_utf8(body.concat())
+            .unwrap()
+            .ends_with("data: bye\n\n"));
+    }
+
     #[tokio::test]
     async fn test_fetch_example() {
         let client = AcceleratedClient::new().unwrap();
@@ -3,16 +3,68 @@
 //! Provides minimal JavaScript execution for SPA support.
 //! Uses `QuickJS` via rquickjs bindings (ES2020, ~1MB).
 
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
 use anyhow::Result;
 use rquickjs::{Context, Function, Runtime, Type};
 use tracing::debug;
 
+/// Severity of a captured `console.*` call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleLevel {
+    Log,
+    Info,
+    Warn,
+    Error,
+    Debug,
+}
+
+impl ConsoleLevel {
+    /// Lowercase name, matching the `console` method
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Log => "log",
+            Self::Info => "info",
+            Self::Warn => "warn",
+            Self::Error => "error",
+            Self::Debug => "debug",
+        }
+    }
+
+    fn from_method(method: &str) -> Self {
+        match method {
+            "info" => Self::Info,
+            "warn" => Self::Warn,
+            "error" => Self::Error,
+            "debug" => Self::Debug,
+            _ => Self::Log,
+        }
+    }
+}
+
+impl fmt::Display for ConsoleLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One captured `console.log/info/warn/error/debug` call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsoleMessage {
+    pub level: ConsoleLevel,
+    /// Arguments joined with spaces (non-strings JSON-encoded)
+    pub message: String,
+}
+
 /// Minimal JavaScript engine for executing scripts
 pub struct JsEngine {
-    /// Runtime must be kept alive for Context lifetime - not directly used after initialization
-    #[allow(dead_code)]
+    /// Runtime must be kept alive for Context lifetime; also used for GC on reset
     runtime: Runtime,
     context: Context,
+    /// `console.*` output not yet taken
+    console: Arc<Mutex<Vec<ConsoleMessage>>>,
 }
 
 impl JsEngine {
@@ -27,7 +79,60 @@ impl JsEngine {
         // Set max stack size
         runtime.set_max_stack_size(1024 * 1024);
 
-        Ok(Self { runtime, context })
+        let engine = Self {
+            runtime,
+            context,
+            console: Arc::new(Mutex::new(Vec::new())),
+        };
+        engine.install_console()?;
+        Ok(engine)
+    }
+
+    /// Route `console.*` calls into the capture buffer
+    fn install_console(&self) -> Result<()> {
+        let buffer = Arc::clone(&self.console);
+        self.context.with(|ctx| -> Result<()> {
+            let sink = Function::new(ctx.clone(), move |method: String, message: String| {
+                if let Ok(mut buffer) = buffer.lock() {
+                    buffer.push(ConsoleMessage {
+                        level: ConsoleLevel::from_method(&method),
+                        message,
+                    });
+                }
+            })?;
+            ctx.globals().set("__nabConsole", sink)?;
+            ctx.eval::<(), _>(
+                r"
+                globalThis.console = {};
+                ['log', 'info', 'warn', 'error', 'debug'].forEach(function (method) {
+                    console[method] = function () {
+                        var parts = [];
+                        for (var i = 0; i < arguments.length; i++) {
+                            var arg = arguments[i];
+                            if (typeof arg === 'string') {
+                                parts.push(arg);
+                                continue;
+                            }
+                            var json;
+                            try { json = JSON.stringify(arg); } catch (e) {}
+                            parts.push(json === undefined ? String(arg) : json);
+                        }
+                        __nabConsole(method, parts.join(' '));
+                    };
+                });
+            ",
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Drain the `console.*` messages captured since the last call
+    #[must_use]
+    pub fn take_console(&self) -> Vec<ConsoleMessage> {
+        self.console
+            .lock()
+            .map(|mut buffer| std::mem::take(&mut *buffer))
+            .unwrap_or_default()
     }
 
     /// Execute JavaScript code and return the result as a string
@@ -206,12 +311,86 @@ impl JsEngine {
             }
         ";
 
-        self.context.with(|ctx| {
+        self.context.with(|ctx| -> Result<()> {
             ctx.eval::<(), _>(dom_shim)?;
             Ok(())
+        })?;
+        self.mark_baseline()
+    }
+
+    /// Record the current globals (and `window`/`document` keys) as the
+    /// baseline that [`reset`](Self::reset) returns to.
+    ///
+    /// [`inject_minimal_dom`](Self::inject_minimal_dom) calls this; call it
+    /// again after injecting further natives such as `fetch`.
+    pub fn mark_baseline(&self) -> Result<()> {
+        let baseline_script = r"
+            globalThis.__nabBaseline = {
+                globals: Object.getOwnPropertyNames(globalThis).concat(['__nabBaseline']),
+                window: typeof window === 'object' ? Object.keys(window) : [],
+                document: typeof document === 'object' ? Object.keys(document) : []
+            };
+        ";
+
+        self.context.with(|ctx| -> Result<()> {
+            ctx.eval::<(), _>(baseline_script)?;
+            Ok(())
         })
     }
 
+    /// Clear page-specific state so the engine can run another page.
+    ///
+    /// Globals added since [`mark_baseline`](Self::mark_baseline) are removed
+    /// (non-configurable `var`s are set to `undefined`), `window.location`,
+    /// storage, document elements and captured console output are emptied,
+    /// and the DOM shims and
+    /// injected natives are kept. Much cheaper than a fresh
+    /// `new()` + `inject_minimal_dom()` (see `benches/js_engine_bench.rs`).
+    pub fn reset(&self) -> Result<()> {
+        let reset_script = r"
+            (function () {
+                var base = globalThis.__nabBaseline;
+                if (!base) throw new Error('reset() requires mark_baseline()');
+                function prune(obj, keep) {
+                    Object.keys(obj).forEach(function (k) {
+                        if (keep.indexOf(k) < 0) delete obj[k];
+                    });
+                }
+                Object.getOwnPropertyNames(globalThis).forEach(function (k) {
+                    if (base.globals.indexOf(k) >= 0) return;
+                    // Scripts run in strict mode, where deleting a `var` throws
+                    try {
+                        delete globalThis[k];
+                    } catch (e) {
+                        globalThis[k] = undefined;
+                    }
+                });
+                if (typeof window === 'object') {
+                    prune(window, base.window);
+                    window.location = { href: '', hostname: '', pathname: '/', search: '', hash: '' };
+                    if (window.localStorage) window.localStorage._data = {};
+                    if (window.sessionStorage) window.sessionStorage._data = {};
+                }
+                if (typeof document === 'object') {
+                    prune(document, base.document);
+                    document._elements = {};
+                    if (document.body) {
+                        document.body.children = [];
+                        document.body.innerHTML = '';
+                    }
+                }
+            })();
+        ";
+
+        self.context.with(|ctx| -> Result<()> {
+            ctx.eval::<(), _>(reset_script)?;
+            Ok(())
+        })?;
+        drop(self.take_console());
+        self.runtime.run_gc();
+        Ok(())
+    }
+
     /// Parse JSON from a JavaScript object
     pub fn parse_json(&self, json_str: &str) -> Result<String> {
         let code = format!("JSON.parse('{}')", json_str.replace('\'', "\\'"));
@@ -370,4 +549,89 @@ mod tests {
             .unwrap();
         assert_eq!(result, "function");
     }
+
+    #[test]
+    fn test_reset_clears_page_state_but_keeps_shims() {
+        let engine = JsEngine::new().unwrap();
+        engine.inject_minimal_dom().unwrap();
+        crate::inject_fetch_sync(engine.context(), crate::FetchClient::new(None, None)).unwrap();
+        engine.mark_baseline().unwrap();
+
+        engine
+            .eval(
+                r"
+            window.location.href = 'https://shop.test/cart';
+            window.__INITIAL_STATE__ = { cart: [1, 2] };
+            var pageConfig = { id: 7 };
+            globalThis.appCache = 'warm';
+            window.localStorage.setItem('token', 'abc');
+            document._elements['root'] = document.createElement('div');
+            'loaded';
+        ",
+            )
+            .unwrap();
+
+        engine.reset().unwrap();
+
+        assert_eq!(engine.eval("window.location.href").unwrap(), "");
+        assert_eq!(
+            engine.eval("typeof window.__INITIAL_STATE__").unwrap(),
+            "undefined"
+        );
+        assert_eq!(engine.eval("typeof pageConfig").unwrap(), "undefined");
+        assert_eq!(engine.eval("typeof appCache").unwrap(), "undefined");
+        assert_eq!(
+            engine.eval("window.localStorage.getItem('token')").unwrap(),
+            "null"
+        );
+        assert_eq!(
+            engine.eval("document.getElementById('root')").unwrap(),
+            "null"
+        );
+
+        assert_eq!(engine.eval("typeof fetch").unwrap(), "function");
+        assert_eq!(
+            engine.eval("typeof document.createElement").unwrap(),
+            "function"
+        );
+        assert_eq!(
+            engine.eval("typeof window.localStorage.setItem").unwrap(),
+            "function"
+        );
+        assert_eq!(
+            engine.eval("document.createElement('p').tagName").unwrap(),
+            "P"
+        );
+    }
+
+    #[test]
+    fn test_console_capture() {
+        let engine = JsEngine::new().unwrap();
+        engine.inject_minimal_dom().unwrap();
+
+        engine
+            .eval(
+                r"
+            console.log('hi');
+            console.warn('careful', 42, { a: 1 });
+            window.console.error('boom');
+        ",
+            )
+            .unwrap();
+
+        let messages = engine.take_console();
+        assert_eq!(
+            messages[0],
+            ConsoleMessage {
+                level: ConsoleLevel::Log,
+                message: "hi".to_string()
+            }
+        );
+        assert_eq!(messages[0].level.as_str(), "log");
+        assert_eq!(messages[1].level, ConsoleLevel::Warn);
+        assert_eq!(messages[1].message, r#"careful 42 {"a":1}"#);
+        assert_eq!(messages[2].level, ConsoleLevel::Error);
+        assert_eq!(messages[2].message, "boom");
+        assert!(engine.take_console().is_empty());
+    }
 }
@@ -0,0 +1,137 @@
+//! Lightweight JSON path selection
+//!
+//! Supports the subset agents actually use to drill into API responses and
+//! SPA state: dotted keys, bracketed array indices and quoted keys.
+//!
+//! ```rust
+//! use nab::json_path::select;
+//!
+//! let data = serde_json::json!({"a": {"b": [{"c": 1}, {"c": 2}]}});
+//! assert_eq!(select(&data, "a.b[1].c"), Some(&serde_json::json!(2)));
+//! assert_eq!(select(&data, "$.a.b.0.c"), Some(&serde_json::json!(1)));
+//! ```
+
+use serde_json::Value;
+
+/// A single step in a parsed path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Select the value at `path` (e.g. `a.b[0].c`, `items.0.id`, `["odd.key"]`).
+///
+/// A leading `$` is ignored. Numeric dotted segments index into arrays but are
+/// treated as keys on objects. Returns `None` when any step is missing.
+#[must_use]
+pub fn select<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    parse(path)
+        .iter()
+        .try_fold(value, |current, segment| match (segment, current) {
+            (Segment::Index(i), Value::Array(items)) => items.get(*i),
+            (Segment::Index(i), Value::Object(map)) => map.get(&i.to_string()),
+            (Segment::Key(key), Value::Object(map)) => map.get(key),
+            (Segment::Key(key), Value::Array(items)) => {
+                key.parse::<usize>().ok().and_then(|i| items.get(i))
+            }
+            _ => None,
+        })
+}
+
+fn parse(path: &str) -> Vec<Segment> {
+    let path = path.trim();
+    let path = path.strip_prefix('$').unwrap_or(path);
+
+    let mut segments = Vec::new();
+    let mut key = String::new();
+    let mut chars = path.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => flush_key(&mut key, &mut segments),
+            '[' => {
+                flush_key(&mut key, &mut segments);
+                let mut inner = String::new();
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                    inner.push(c);
+                }
+                let inner = inner.trim();
+                let quoted = inner
+                    .strip_prefix('"')
+                    .and_then(|s| s.strip_suffix('"'))
+                    .or_else(|| inner.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')));
+                match (quoted, inner.parse::<usize>()) {
+                    (Some(quoted), _) => segments.push(Segment::Key(quoted.to_string())),
+                    (None, Ok(index)) => segments.push(Segment::Index(index)),
+                    (None, Err(_)) if !inner.is_empty() => {
+                        segments.push(Segment::Key(inner.to_string()));
+                    }
+                    _ => {}
+                }
+            }
+            _ => key.push(c),
+        }
+    }
+    flush_key(&mut key, &mut segments);
+    segments
+}
+
+fn flush_key(key: &mut String, segments: &mut Vec<Segment>) {
+    if !key.is_empty() {
+        segments.push(Segment::Key(std::mem::take(key)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_mixed_path() {
+        assert_eq!(
+            parse("$.data.items[2].name"),
+            vec![
+                Segment::Key("data".into()),
+                Segment::Key("items".into()),
+                Segment::Index(2),
+                Segment::Key("name".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_select_nested_array_value() {
+        let data = json!({"props": {"pageProps": {"items": [{"id": 7}, {"id": 8}]}}});
+        assert_eq!(
+            select(&data, "props.pageProps.items[1].id"),
+            Some(&json!(8))
+        );
+        assert_eq!(select(&data, "props.pageProps.items.0.id"), Some(&json!(7)));
+    }
+
+    #[test]
+    fn test_select_quoted_key_with_dot() {
+        let data = json!({"a.b": {"c": true}});
+        assert_eq!(select(&data, r#"["a.b"].c"#), Some(&json!(true)));
+    }
+
+    #[test]
+    fn test_select_missing_returns_none() {
+        let data = json!({"a": [1, 2]});
+        assert_eq!(select(&data, "a[5]"), None);
+        assert_eq!(select(&data, "b.c"), None);
+        assert_eq!(select(&data, "a.x"), None);
+    }
+
+    #[test]
+    fn test_select_empty_path_is_root() {
+        let data = json!({"a": 1});
+        assert_eq!(select(&data, ""), Some(&data));
+        assert_eq!(select(&data, "$"), Some(&data));
+    }
+}
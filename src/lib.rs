@@ -26,20 +26,37 @@ pub mod annotate;
 pub mod api_discovery;
 pub mod arena;
 pub mod auth;
+pub mod binarycookies;
 pub mod browser_detect;
+#[cfg(feature = "cassette")]
+pub mod cassette;
+pub mod challenge;
 pub mod content;
+pub mod cookie_jar;
+pub mod crawl;
+pub mod fetch;
 pub mod fetch_bridge;
 pub mod fingerprint;
 pub mod form;
 pub mod http3_client;
 pub mod http_client;
 pub mod js_engine;
+pub mod json_path;
 pub mod login;
 pub mod mfa;
+pub mod paywall;
 pub mod plugin;
 pub mod prefetch;
+pub mod render;
+pub mod retry;
 pub mod site;
+pub mod spa;
+pub mod sse;
 pub mod stream;
+#[cfg(feature = "metrics")]
+pub mod telemetry;
+#[cfg(test)]
+mod test_support;
 pub mod websocket;
 
 pub use analyze::{
@@ -54,25 +71,34 @@ pub use annotate::{
 pub use api_discovery::{ApiDiscovery, ApiEndpoint};
 pub use arena::{ArenaResponse, ResponseArena, ResponseBuffer, StringInterner};
 pub use auth::{
-    CookieSource, Credential, CredentialRetriever, CredentialSource, OnePasswordAuth, OtpCode,
-    OtpRetriever, OtpSource,
+    get_cookies_merged, get_cookies_merged_with, BitwardenAuth, CookieReport, CookieSource,
+    Credential, CredentialRetriever, CredentialSource, MergedCookies, OnePasswordAuth, OtpCode,
+    OtpKeywords, OtpRetriever, OtpSource, Passkey,
 };
-pub use browser_detect::{detect_default_browser, BrowserType};
-pub use fetch_bridge::{inject_fetch_sync, FetchClient};
+pub use browser_detect::{detect_default_browser, BrowserCache, BrowserType};
+#[cfg(feature = "cassette")]
+pub use cassette::{BodyCodec, Cassette, CassetteMode};
+pub use cookie_jar::CookieJar;
+pub use fetch::{fetch_to_markdown, fetch_to_markdown_with, FetchOptions, FetchResult};
+pub use fetch_bridge::{inject_fetch_sync, FetchClient, FetchInit, FetchLogEntry, FetchResponse};
 pub use fingerprint::{
-    chrome_profile, firefox_profile, random_profile, safari_profile, BrowserProfile,
+    android_chrome_profile, chrome_profile, firefox_profile, ios_safari_profile, mobile_profile,
+    profile_from_seed, random_profile, safari_profile, BrowserProfile, RequestContext, RequestDest,
 };
-pub use form::{parse_field_args, Form};
+pub use form::{parse_field_args, CsrfToken, Form, SubmitOptions};
 pub use http3_client::Http3Client;
 #[cfg(feature = "http3")]
 pub use http3_client::Http3Response;
-pub use http_client::AcceleratedClient;
-pub use js_engine::JsEngine;
-pub use login::{get_session_dir, LoginFlow, LoginResult};
+pub use http_client::{AcceleratedClient, ConnectionInfo, Preconnected, TlsOptions};
+pub use js_engine::{ConsoleLevel, ConsoleMessage, JsEngine};
+pub use login::{get_session_dir, LoginFlow, LoginOutcome, LoginResult};
 pub use mfa::{detect_mfa_type, MfaHandler, MfaResult, MfaType, NotificationConfig};
 pub use prefetch::{extract_link_hints, EarlyHintLink, EarlyHints, PrefetchManager};
+pub use retry::RetryPolicy;
+pub use spa::SpaLimits;
+pub use sse::{SseEvent, SseParser};
 pub use stream::{StreamBackend, StreamInfo, StreamProvider};
-pub use websocket::{JsonRpcWebSocket, WebSocket, WebSocketMessage};
+pub use websocket::{JsonRpcWebSocket, WebSocket, WebSocketMessage, WebSocketOptions};
 
 /// Version of nab
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -4,15 +4,44 @@
 //! to automate login flows.
 
 use anyhow::{Context, Result};
-use tracing::{debug, info, warn};
+use scraper::{Html, Selector};
+use tracing::{debug, info};
 
-use crate::auth::{Credential, OnePasswordAuth, OtpRetriever};
+use crate::auth::{Credential, OnePasswordAuth, OtpRetriever, OTP_POLL_INTERVAL, OTP_WAIT_TIMEOUT};
 use crate::form::Form;
 use crate::http_client::AcceleratedClient;
 
 /// Session storage directory
 const SESSION_DIR: &str = ".nab/sessions";
 
+/// Phrases of a rejected login (matched case-insensitively)
+const FAILURE_PHRASES: &[&str] = &[
+    "invalid password",
+    "incorrect password",
+    "wrong password",
+    "invalid username",
+    "incorrect username",
+    "invalid email or password",
+    "invalid credentials",
+    "invalid login",
+    "login failed",
+    "authentication failed",
+    "sign-in failed",
+    "couldn't sign you in",
+    "could not sign you in",
+    "account is locked",
+    "too many failed",
+];
+
+/// Elements that carry error banners
+const ERROR_SELECTOR: &str = "[role=alert], .error, .errors, .alert, .alert-danger, .alert-error, \
+     .flash-error, .form-error, .invalid-feedback, .notice-error, #error, #errors";
+
+/// Links and forms only a signed-in user sees
+const LOGGED_IN_SELECTOR: &str = "a[href*=logout], a[href*=log-out], a[href*=log_out], \
+     a[href*=signout], a[href*=sign-out], a[href*=sign_out], form[action*=logout], \
+     form[action*=signout]";
+
 /// Login flow orchestrator
 pub struct LoginFlow {
     client: AcceleratedClient,
@@ -21,6 +50,9 @@ pub struct LoginFlow {
 
 impl LoginFlow {
     /// Create a new login flow
+    ///
+    /// The client keeps the cookies every step sets, so the session cookie
+    /// issued by the form submission reaches the pages after it.
     pub fn new(client: AcceleratedClient, use_1password: bool) -> Self {
         let one_password = if use_1password {
             Some(OnePasswordAuth::new(None))
@@ -29,7 +61,7 @@ impl LoginFlow {
         };
 
         Self {
-            client,
+            client: client.with_cookie_store(true),
             one_password,
         }
     }
@@ -76,29 +108,37 @@ impl LoginFlow {
         debug!("Submitting form to: {}", action_url);
 
         let form_data = form.encode_urlencoded();
-        let response = self
+        let request = self
             .client
             .inner()
             .post(&action_url)
             .header("Content-Type", form.content_type())
             .body(form_data)
-            .send()
-            .await?;
+            .build()?;
+        // Any SMS or email code is sent in response to this submission
+        let submitted_at = chrono::Utc::now();
+        let response = self.client.execute(request).await?;
 
-        let final_url = response.url().to_string();
+        let mut final_url = response.url().to_string();
         let mut body = response.text().await?;
 
         // Step 6: Check for MFA/2FA requirement
-        if self.detect_mfa_required(&body) {
+        let mut outcome = LoginOutcome::detect(url, &final_url, &body);
+        if outcome == LoginOutcome::MfaRequired {
             info!("MFA required, attempting to get OTP...");
-            body = self.handle_mfa(url, &body, &credential).await?;
+            (final_url, body) = self
+                .handle_mfa(url, &body, &credential, submitted_at)
+                .await?;
+            outcome = LoginOutcome::detect(url, &final_url, &body);
         }
 
+        info!(?outcome, "Login finished");
         Ok(LoginResult {
-            success: true,
+            success: outcome == LoginOutcome::Success,
+            message: outcome.to_string(),
+            outcome,
             final_url,
             body,
-            message: "Login successful".to_string(),
         })
     }
 
@@ -135,7 +175,7 @@ impl LoginFlow {
     }
 
     /// Detect if MFA is required from the response
-    fn detect_mfa_required(&self, html: &str) -> bool {
+    fn detect_mfa_required(html: &str) -> bool {
         let mfa_indicators = [
             "two-factor",
             "2fa",
@@ -159,7 +199,8 @@ impl LoginFlow {
         base_url: &str,
         html: &str,
         credential: &Credential,
-    ) -> Result<String> {
+        requested_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(String, String)> {
         // Try to get OTP code
         let otp_code = if credential.has_totp {
             // Try 1Password TOTP first
@@ -170,16 +211,20 @@ impl LoginFlow {
                         info!("Got TOTP from 1Password");
                         totp
                     } else {
-                        self.get_otp_from_other_sources(base_url).await?
+                        self.get_otp_from_other_sources(base_url, requested_at)
+                            .await?
                     }
                 } else {
-                    self.get_otp_from_other_sources(base_url).await?
+                    self.get_otp_from_other_sources(base_url, requested_at)
+                        .await?
                 }
             } else {
-                self.get_otp_from_other_sources(base_url).await?
+                self.get_otp_from_other_sources(base_url, requested_at)
+                    .await?
             }
         } else {
-            self.get_otp_from_other_sources(base_url).await?
+            self.get_otp_from_other_sources(base_url, requested_at)
+                .await?
         };
 
         // Find MFA form
@@ -212,37 +257,64 @@ impl LoginFlow {
         let form_data = mfa_form.encode_urlencoded();
 
         debug!("Submitting MFA form to: {}", action_url);
-        let response = self
+        let request = self
             .client
             .inner()
             .post(&action_url)
             .header("Content-Type", mfa_form.content_type())
             .body(form_data)
-            .send()
-            .await?;
+            .build()?;
+        let response = self.client.execute(request).await?;
 
-        Ok(response.text().await?)
+        let final_url = response.url().to_string();
+        Ok((final_url, response.text().await?))
     }
 
-    /// Get OTP from SMS or email sources
-    async fn get_otp_from_other_sources(&self, domain: &str) -> Result<String> {
-        if let Some(otp_code) = OtpRetriever::get_otp_for_domain(domain)? {
+    /// Wait for an SMS or email code sent at or after `requested_at`
+    async fn get_otp_from_other_sources(
+        &self,
+        url: &str,
+        requested_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<String> {
+        let domain = url::Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+            .with_context(|| format!("No host in URL '{url}'"))?;
+        // Polling sleeps and shells out
+        let otp = tokio::task::spawn_blocking(move || {
+            OtpRetriever::get_otp_for_domain_wait(
+                &domain,
+                requested_at,
+                OTP_WAIT_TIMEOUT,
+                OTP_POLL_INTERVAL,
+            )
+        })
+        .await??;
+        if let Some(otp_code) = otp {
             info!("Got OTP from {}", otp_code.source);
             return Ok(otp_code.code);
         }
-        anyhow::bail!("No OTP code available")
+        anyhow::bail!("No OTP code arrived within {OTP_WAIT_TIMEOUT:?}")
     }
 
-    /// Save session cookies to disk
-    pub fn save_session(&self, _url: &str, _save: bool) -> Result<()> {
-        // Session cookie saving will use the cookie jar from AcceleratedClient
-        // The reqwest client already handles cookies automatically
-        // For now, we can skip explicit session saving since cookies are
-        // maintained in memory during the client lifetime
-
-        // Future: implement persistent session storage in ~/.nab/sessions/
-        warn!("Session saving not yet implemented (cookies maintained in memory)");
-        Ok(())
+    /// Save the cookies collected during the flow to
+    /// `<session dir>/<host>.cookies.txt` (Netscape format), returning the path
+    ///
+    /// The file holds live session tokens, so on Unix only its owner can read
+    /// it (mode `0600`).
+    pub fn save_session(&self, url: &str) -> Result<std::path::PathBuf> {
+        let host = url::Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+            .with_context(|| format!("No host in URL '{url}'"))?;
+        let dir = get_session_dir()?;
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        let path = dir.join(format!("{host}.cookies.txt"));
+        write_private(&path, &self.client.export_cookies())
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        info!(path = %path.display(), "Saved session cookies");
+        Ok(path)
     }
 }
 
@@ -250,26 +322,167 @@ impl LoginFlow {
 #[derive(Debug, Clone)]
 pub struct LoginResult {
     pub success: bool,
+    pub outcome: LoginOutcome,
     pub final_url: String,
     pub body: String,
     pub message: String,
 }
 
+/// How a login attempt ended, judged from the page it landed on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoginOutcome {
+    Success,
+    /// A second factor is still being asked for
+    MfaRequired,
+    /// The site rejected the login; the reason is its error text if any
+    Failed(String),
+}
+
+impl LoginOutcome {
+    /// Judge a login from the page it landed on
+    ///
+    /// In order: an invalid-credentials banner means failure, an MFA prompt
+    /// means a second factor is due, a password field means the login form
+    /// is still showing, logout links mean success, and finally whether the
+    /// URL moved away from `login_url`. Login and MFA pages often carry
+    /// sign-out links of their own, so the forms are checked first.
+    #[must_use]
+    pub fn detect(login_url: &str, final_url: &str, html: &str) -> Self {
+        let document = Html::parse_document(html);
+
+        if let Some(reason) = failure_banner(&document) {
+            return Self::Failed(reason);
+        }
+        if LoginFlow::detect_mfa_required(html) {
+            return Self::MfaRequired;
+        }
+        if matches_selector(&document, "input[type=password]") {
+            return Self::Failed("Still on the login form".to_string());
+        }
+        if matches_selector(&document, LOGGED_IN_SELECTOR)
+            || page_text(&document).contains("log out")
+            || page_text(&document).contains("sign out")
+        {
+            return Self::Success;
+        }
+        let moved = url::Url::parse(login_url)
+            .ok()
+            .zip(url::Url::parse(final_url).ok())
+            .is_some_and(|(before, after)| before.path() != after.path());
+        if moved {
+            Self::Success
+        } else {
+            Self::Failed("Landed back on the login page".to_string())
+        }
+    }
+}
+
+impl std::fmt::Display for LoginOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Success => f.write_str("Login successful"),
+            Self::MfaRequired => f.write_str("MFA code required"),
+            Self::Failed(reason) => write!(f, "Login failed: {reason}"),
+        }
+    }
+}
+
+/// Text of the first error banner naming a rejected login, else the failure
+/// phrase found anywhere on the page
+fn failure_banner(document: &Html) -> Option<String> {
+    let is_failure = |text: &str| {
+        let lower = text.to_lowercase();
+        FAILURE_PHRASES.iter().any(|phrase| lower.contains(phrase))
+    };
+
+    if let Ok(selector) = Selector::parse(ERROR_SELECTOR) {
+        let banner = document
+            .select(&selector)
+            .map(|element| {
+                element
+                    .text()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .find(|text| is_failure(text));
+        if banner.is_some() {
+            return banner;
+        }
+    }
+
+    let text = page_text(document);
+    FAILURE_PHRASES
+        .iter()
+        .find(|phrase| text.contains(*phrase))
+        .map(ToString::to_string)
+}
+
+fn matches_selector(document: &Html, selector: &str) -> bool {
+    Selector::parse(selector).is_ok_and(|selector| document.select(&selector).next().is_some())
+}
+
+/// Lowercased visible body text
+fn page_text(document: &Html) -> String {
+    let Ok(body) = Selector::parse("body") else {
+        return String::new();
+    };
+    document
+        .select(&body)
+        .flat_map(|body| body.text())
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
 /// Get session directory path
 pub fn get_session_dir() -> Result<std::path::PathBuf> {
     let home = dirs::home_dir().context("Could not find home directory")?;
     Ok(home.join(SESSION_DIR))
 }
 
+/// Write `contents` to `path`, readable and writable by the owner only
+fn write_private(path: &std::path::Path, contents: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    #[cfg(unix)]
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(path)?;
+    // `mode` only applies to new files; tighten one an older version left
+    #[cfg(unix)]
+    file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    file.write_all(contents.as_bytes())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[cfg(unix)]
     #[test]
-    fn test_detect_mfa_required() {
-        let client = AcceleratedClient::new().unwrap();
-        let flow = LoginFlow::new(client, false);
+    fn session_file_is_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("nab-session-{}.txt", uuid::Uuid::new_v4()));
+        std::fs::write(&path, "old contents that are longer").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_private(&path, "sid=1").unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(contents, "sid=1");
+    }
 
+    #[test]
+    fn test_detect_mfa_required() {
         let html_with_mfa = r#"
             <html>
                 <body>
@@ -280,7 +493,7 @@ mod tests {
             </html>
         "#;
 
-        assert!(flow.detect_mfa_required(html_with_mfa));
+        assert!(LoginFlow::detect_mfa_required(html_with_mfa));
 
         let html_without_mfa = r#"
             <html>
@@ -290,7 +503,7 @@ mod tests {
             </html>
         "#;
 
-        assert!(!flow.detect_mfa_required(html_without_mfa));
+        assert!(!LoginFlow::detect_mfa_required(html_without_mfa));
     }
 
     #[test]
@@ -328,4 +541,88 @@ mod tests {
         assert_eq!(form.fields.get("username"), Some(&"testuser".to_string()));
         assert_eq!(form.fields.get("password"), Some(&"testpass".to_string()));
     }
+
+    #[test]
+    fn invalid_password_banner_is_a_failure() {
+        let html = r#"<html><body>
+            <div class="alert alert-danger" role="alert">
+                Invalid password.   Please try again.
+            </div>
+            <form action="/login" method="post">
+                <input name="username" value="ada">
+                <input type="password" name="password">
+            </form>
+        </body></html>"#;
+
+        let outcome = LoginOutcome::detect(
+            "https://example.com/login",
+            "https://example.com/login",
+            html,
+        );
+
+        assert_eq!(
+            outcome,
+            LoginOutcome::Failed("Invalid password. Please try again.".to_string())
+        );
+        assert_eq!(
+            outcome.to_string(),
+            "Login failed: Invalid password. Please try again."
+        );
+    }
+
+    #[test]
+    fn logout_link_is_a_success() {
+        let html = r#"<html><body>
+            <nav><a href="/settings">Settings</a> <a href="/users/logout">Log out</a></nav>
+            <h1>Your orders</h1>
+        </body></html>"#;
+
+        assert_eq!(
+            LoginOutcome::detect(
+                "https://example.com/login",
+                "https://example.com/login",
+                html
+            ),
+            LoginOutcome::Success
+        );
+    }
+
+    #[test]
+    fn login_form_and_redirects_decide_the_rest() {
+        let form = r#"<form><input name="user"><input type="password" name="pw"></form>"#;
+        assert!(matches!(
+            LoginOutcome::detect("https://a.test/login", "https://a.test/login", form),
+            LoginOutcome::Failed(_)
+        ));
+
+        let mfa =
+            r#"<form><input name="otp_code"></form><p>Enter the code from your authenticator</p>"#;
+        assert_eq!(
+            LoginOutcome::detect("https://a.test/login", "https://a.test/2fa", mfa),
+            LoginOutcome::MfaRequired
+        );
+
+        // Header links a login page shows anyone don't count
+        let with_links = r#"<nav><a href="/account">Account</a> <a href="/logout">Sign out</a></nav>
+            <form><input name="user"><input type="password" name="pw"></form>"#;
+        assert!(matches!(
+            LoginOutcome::detect("https://a.test/login", "https://a.test/login", with_links),
+            LoginOutcome::Failed(_)
+        ));
+        let account = r#"<a href="/account">Account</a> <a href="/dashboard">Dashboard</a>"#;
+        assert!(matches!(
+            LoginOutcome::detect("https://a.test/login", "https://a.test/login", account),
+            LoginOutcome::Failed(_)
+        ));
+
+        let home = "<html><body><h1>Welcome</h1></body></html>";
+        assert_eq!(
+            LoginOutcome::detect("https://a.test/login", "https://a.test/home", home),
+            LoginOutcome::Success
+        );
+        assert!(matches!(
+            LoginOutcome::detect("https://a.test/login", "https://a.test/login?x=1", home),
+            LoginOutcome::Failed(_)
+        ));
+    }
 }
@@ -20,6 +20,14 @@ struct Cli {
     #[arg(short, long, global = true)]
     verbose: bool,
 
+    /// Emit structured JSON logs (with span fields such as `request_id`) to stderr
+    #[arg(long, global = true)]
+    log_json: bool,
+
+    /// Detect the default browser again instead of using the cached result
+    #[arg(long, global = true)]
+    refresh_browser_detection: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -35,6 +43,17 @@ enum OutputFormat {
     Json,
 }
 
+#[derive(Clone, Copy, Default, ValueEnum)]
+enum BenchOutputFormat {
+    #[default]
+    /// Human-readable summary
+    Text,
+    /// JSON report with per-iteration timings
+    Json,
+    /// CSV rows per iteration plus a summary row per URL
+    Csv,
+}
+
 #[derive(Clone, Copy, Default, ValueEnum)]
 enum AnalyzeOutputFormat {
     #[default]
@@ -76,11 +95,12 @@ enum Commands {
         #[arg(short, long, default_value = "full")]
         format: OutputFormat,
 
-        /// Save body to file (bypasses truncation)
+        /// Save body to file, or download the raw response into a directory under the server's suggested name (bypasses truncation)
         #[arg(short, long)]
         output: Option<PathBuf>,
 
         /// Use cookies from browser (auto, brave, chrome, firefox, safari, edge). Use 'none' to disable.
+        /// A named browser also pins its fingerprint for the URL's host, overriding fingerprints.toml.
         #[arg(short, long, default_value = "auto")]
         cookies: String,
 
@@ -112,6 +132,10 @@ enum Commands {
         #[arg(long)]
         warmup_url: Option<String>,
 
+        /// Open connections to these hosts before the request (comma-separated)
+        #[arg(long, value_delimiter = ',', value_name = "HOSTS")]
+        preconnect: Vec<String>,
+
         /// HTTP method (GET, POST, PUT, DELETE, PATCH)
         #[arg(short = 'X', long, default_value = "GET")]
         method: String,
@@ -120,7 +144,7 @@ enum Commands {
         #[arg(short = 'd', long)]
         data: Option<String>,
 
-        /// Output Set-Cookie headers from response (for auth flows)
+        /// Show Set-Cookie headers with their attributes (a `cookies` array with --format json)
         #[arg(long)]
         capture_cookies: bool,
 
@@ -128,6 +152,10 @@ enum Commands {
         #[arg(long)]
         no_redirect: bool,
 
+        /// Follow at most N redirects (0 behaves like --no-redirect); loops are reported
+        #[arg(long, value_name = "N", default_value_t = nab::http_client::DEFAULT_MAX_REDIRECTS, conflicts_with = "no_redirect")]
+        max_redirects: usize,
+
         /// Disable automatic SPA data extraction (Next.js, Nuxt, Redux, etc.)
         #[arg(long)]
         no_spa: bool,
@@ -140,9 +168,127 @@ enum Commands {
         #[arg(long, default_value = "5")]
         parallel: usize,
 
+        /// Stream batch results as JSON Lines in completion order
+        #[arg(long)]
+        jsonl: bool,
+
+        /// Save each batch result to its own file in this directory
+        #[arg(long, value_name = "DIR", requires = "batch")]
+        output_dir: Option<PathBuf>,
+
+        /// File names for --output-dir: {host}, {path_slug}, {index}, {ext}
+        #[arg(long, value_name = "TEMPLATE", requires = "output_dir")]
+        filename_template: Option<String>,
+
         /// Proxy URL (SOCKS5 or HTTP). Also checks HTTP_PROXY/HTTPS_PROXY/ALL_PROXY env vars.
         #[arg(long)]
         proxy: Option<String>,
+
+        /// Send exactly this User-Agent, keeping the rest of the browser fingerprint
+        #[arg(long, value_name = "STRING")]
+        user_agent: Option<String>,
+
+        /// Follow `rel=next` links (Link header or HTML) up to N pages (default: 10)
+        #[arg(long, value_name = "MAX", num_args = 0..=1, default_missing_value = "10")]
+        paginate: Option<usize>,
+
+        /// Parse the response as JSON and print the value at this path (e.g., 'data.items[0].id')
+        #[arg(long, value_name = "PATH")]
+        json_path: Option<String>,
+
+        /// Fetch only bytes START-END (inclusive) or START- via a Range header
+        #[arg(long, value_name = "START-END")]
+        range: Option<String>,
+
+        /// Require TLS 1.3 (refuse servers that only offer TLS 1.2)
+        #[arg(long, conflicts_with = "min_tls")]
+        tls13_only: bool,
+
+        /// Minimum TLS version to accept (1.2 or 1.3)
+        #[arg(long, value_name = "VERSION")]
+        min_tls: Option<String>,
+
+        /// Maximum TLS version to offer (1.2 or 1.3), for legacy servers
+        #[arg(long, value_name = "VERSION")]
+        max_tls: Option<String>,
+
+        /// Trust the root certificate(s) in this PEM file, in addition to the system roots
+        #[arg(long, value_name = "PEM")]
+        cacert: Option<String>,
+
+        /// Disable TLS certificate verification (dangerous; for testing only)
+        #[arg(short = 'k', long)]
+        insecure: bool,
+
+        /// Render client-side rendered pages in headless Chromium when the content looks empty
+        #[arg(long)]
+        render: bool,
+
+        /// Print a SHA-256 of the page converted to text, volatile values masked, for change monitoring
+        #[arg(long)]
+        hash: bool,
+
+        /// Extra regex to mask before hashing (repeatable; implies --hash)
+        #[arg(long, value_name = "REGEX")]
+        hash_ignore: Vec<String>,
+
+        /// Abort the whole command (fetch, conversion, rendering) after this long: 500ms, 30s, 2m
+        #[arg(long, value_name = "DURATION")]
+        deadline: Option<String>,
+
+        /// Print the assembled request (method, URL, every header, body) and exit without sending it
+        #[arg(long, conflicts_with_all = ["batch", "paginate"])]
+        dry_run: bool,
+    },
+
+    /// Show status, size, type and final URL without downloading the body
+    Head {
+        /// URL to check
+        url: String,
+
+        /// Output format: full, compact, json
+        #[arg(short, long, default_value = "full")]
+        format: OutputFormat,
+
+        /// Use cookies from browser (auto, brave, chrome, firefox, safari, edge). Use 'none' to disable.
+        #[arg(short, long, default_value = "auto")]
+        cookies: String,
+    },
+
+    /// Convert a local HTML, PDF, DOCX or other file to markdown
+    Convert {
+        /// File to convert (type taken from the extension, sniffed when unknown)
+        path: PathBuf,
+
+        /// Output format: full, compact (markdown only), json
+        #[arg(short, long, default_value = "full")]
+        format: OutputFormat,
+
+        /// Save the markdown to a file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Consume a Server-Sent Events (text/event-stream) endpoint
+    Sse {
+        /// Event stream URL
+        url: String,
+
+        /// Output format: full, compact (data only), json (one object per event)
+        #[arg(short, long, default_value = "full")]
+        format: OutputFormat,
+
+        /// Use cookies from browser (auto, brave, chrome, firefox, safari, edge). Use 'none' to disable.
+        #[arg(short, long, default_value = "auto")]
+        cookies: String,
+
+        /// Resume after this event ID (sent as Last-Event-ID)
+        #[arg(long, value_name = "ID")]
+        last_event_id: Option<String>,
+
+        /// Stop after N events (default: run until Ctrl-C)
+        #[arg(short = 'n', long, value_name = "N")]
+        max_events: Option<usize>,
     },
 
     /// Extract data from JavaScript-heavy SPA pages
@@ -174,7 +320,7 @@ enum Commands {
         #[arg(short, long, default_value = "text")]
         output: String,
 
-        /// Extract specific JSON path (e.g., 'props.pageProps.session')
+        /// Extract specific JSON path (e.g., 'props.pageProps.items[0].id')
         #[arg(long)]
         extract: Option<String>,
 
@@ -197,6 +343,18 @@ enum Commands {
         /// Force HTTP/1.1 (for servers with HTTP/2 issues)
         #[arg(long)]
         http1: bool,
+
+        /// Print discovered API endpoints as an OpenAPI 3.1 stub instead of extracting data
+        #[arg(long)]
+        openapi: bool,
+
+        /// GraphQL query to POST to the discovered GraphQL endpoint (with cookies)
+        #[arg(long)]
+        graphql_query: Option<String>,
+
+        /// Send exactly this User-Agent, keeping the rest of the browser fingerprint
+        #[arg(long, value_name = "STRING")]
+        user_agent: Option<String>,
     },
 
     /// Benchmark fetching multiple URLs
@@ -207,6 +365,27 @@ enum Commands {
         /// Number of iterations per URL
         #[arg(short, long, default_value = "5")]
         iterations: usize,
+
+        /// Unmeasured warm-up requests per URL (primes DNS and connections)
+        #[arg(long, default_value = "1")]
+        warmup: usize,
+
+        /// Compare protocols side by side (comma-separated: h1,h2,h3)
+        #[arg(long, value_name = "PROTOCOLS")]
+        compare: Option<String>,
+
+        /// Revalidate with If-None-Match/If-Modified-Since after the first fetch
+        /// and report the 304 rate and bytes saved
+        #[arg(long, conflicts_with = "compare")]
+        conditional: bool,
+
+        /// Output format
+        #[arg(long, short, default_value = "text")]
+        format: BenchOutputFormat,
+
+        /// Write json/csv results to a file (default: stdout)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
     },
 
     /// Test browser fingerprint spoofing
@@ -214,9 +393,13 @@ enum Commands {
         /// Number of profiles to generate
         #[arg(short, long, default_value = "3")]
         count: usize,
+
+        /// Generate mobile profiles (Chrome on Android, Safari on iPhone)
+        #[arg(long)]
+        mobile: bool,
     },
 
-    /// Test 1Password integration
+    /// Test password manager integration (1Password, Bitwarden)
     Auth {
         /// URL to find credentials for
         url: String,
@@ -229,6 +412,14 @@ enum Commands {
     Otp {
         /// Domain or URL to get OTP for
         domain: String,
+
+        /// Wait up to SECONDS for an SMS/email code sent after this command starts
+        #[arg(long, value_name = "SECONDS")]
+        wait: Option<u64>,
+
+        /// Seconds between checks while waiting
+        #[arg(long, default_value = "3", requires = "wait")]
+        poll_interval: u64,
     },
 
     /// Stream media from various providers
@@ -278,6 +469,38 @@ enum Commands {
         /// Pipe output to media player (vlc, mpv, etc.)
         #[arg(long)]
         player: Option<String>,
+
+        /// Extra arguments for --player, appended after its stdin arguments and split like a shell would (e.g. "--volume 50 --title 'Live news'")
+        #[arg(long, requires = "player", allow_hyphen_values = true)]
+        player_args: Option<String>,
+
+        /// Output format for --info and --list: full (text) or json
+        #[arg(short = 'f', long, default_value = "full")]
+        format: OutputFormat,
+
+        /// Audio language for DASH streams with several audio tracks (e.g. fi, sv); uses the native DASH backend
+        #[arg(long)]
+        audio_lang: Option<String>,
+
+        /// Download only the audio track (podcasts, lectures)
+        #[arg(long)]
+        audio_only: bool,
+
+        /// Re-encode extracted audio to this format (mp3, aac, opus, flac); uses ffmpeg
+        #[arg(long, requires = "audio_only", value_parser = ["mp3", "aac", "opus", "flac"])]
+        audio_format: Option<String>,
+
+        /// Add a request header for manifests and segments (repeatable: --header "X-Api-Key: abc")
+        #[arg(long = "header", action = clap::ArgAction::Append)]
+        headers: Vec<String>,
+
+        /// Referer header (overrides the provider default)
+        #[arg(long)]
+        referer: Option<String>,
+
+        /// X-Forwarded-For IP, or random:CIDR for a random address in a range (e.g. random:91.152.0.0/13)
+        #[arg(long)]
+        xff: Option<String>,
     },
 
     /// Analyze video with multimodal pipeline (transcription + vision)
@@ -301,8 +524,38 @@ enum Commands {
         #[arg(long, short)]
         output: Option<PathBuf>,
 
-        /// Offload processing to DGX Spark
+        /// Transcription language code (e.g. en, fi); default auto-detects
+        #[arg(long, value_name = "CODE")]
+        lang: Option<String>,
+
+        /// Recompute every stage instead of reusing cached results
         #[arg(long)]
+        no_cache: bool,
+
+        /// Whisper model cache directory (or `NAB_MODEL_DIR` env; default: Whisper's own)
+        #[arg(long, value_name = "DIR")]
+        model_dir: Option<PathBuf>,
+
+        /// Offload GPU stages to a remote worker (host:port or URL)
+        #[arg(long, value_name = "HOST:PORT")]
+        remote: Option<String>,
+
+        /// Bearer token for the remote worker (or `NAB_REMOTE_TOKEN` env)
+        #[arg(long, requires = "remote")]
+        remote_token: Option<String>,
+
+        /// Stages to run remotely (default: all)
+        #[arg(
+            long,
+            requires = "remote",
+            value_delimiter = ',',
+            value_parser = ["transcribe", "diarize", "vision"]
+        )]
+        remote_stages: Vec<String>,
+
+        /// Deprecated: `--remote spark:8765`, failing instead of falling back
+        /// to local processing when the worker fails
+        #[arg(long, conflicts_with = "remote")]
         dgx: bool,
 
         /// Claude API key for vision analysis (or `ANTHROPIC_API_KEY` env)
@@ -315,8 +568,13 @@ enum Commands {
         /// Input video file
         video: String,
 
-        /// Output video file
-        output: String,
+        /// Output video file (not needed with --subs-only)
+        #[arg(required_unless_present = "subs_only")]
+        output: Option<String>,
+
+        /// Only write a subtitle file (format from extension: .srt, .ass, .vtt); skips video compositing
+        #[arg(long, value_name = "FILE", conflicts_with = "output")]
+        subs_only: Option<String>,
 
         /// Generate and burn subtitles
         #[arg(long)]
@@ -330,6 +588,10 @@ enum Commands {
         #[arg(long)]
         analysis: bool,
 
+        /// Highlight each word as it is spoken (karaoke-style ASS subtitles)
+        #[arg(long)]
+        karaoke: bool,
+
         /// Overlay style
         #[arg(long, default_value = "minimal")]
         style: OverlayStyleArg,
@@ -337,6 +599,10 @@ enum Commands {
         /// Use hardware acceleration (`VideoToolbox` on macOS)
         #[arg(long)]
         hwaccel: bool,
+
+        /// Transcription language code (e.g. en, fi); default auto-detects
+        #[arg(long, value_name = "CODE")]
+        lang: Option<String>,
     },
 
     /// Submit a form with smart field extraction (hidden fields, CSRF tokens)
@@ -348,7 +614,8 @@ enum Commands {
         #[arg(short, long = "field", action = clap::ArgAction::Append)]
         fields: Vec<String>,
 
-        /// Extract CSRF token from specific selector (e.g., "input[name=_token]")
+        /// Extract CSRF token from specific selector (e.g., "input[name=_token]").
+        /// Without it, token inputs, csrf-token meta tags and CSRF cookies are detected
         #[arg(long)]
         csrf_from: Option<String>,
 
@@ -409,7 +676,7 @@ enum CookiesAction {
         /// Domain to export cookies for (e.g., "github.com")
         domain: String,
 
-        /// Browser to export from (auto, brave, chrome, firefox, safari, edge)
+        /// Browser to export from (auto, brave, chrome, firefox, safari, edge), or 'all' to merge every browser
         #[arg(short, long, default_value = "auto")]
         cookies: String,
     },
@@ -426,11 +693,28 @@ async fn main() -> Result<()> {
         Level::INFO
     };
 
-    FmtSubscriber::builder()
-        .with_max_level(log_level)
-        .with_target(false)
-        .compact()
-        .init();
+    if cli.log_json {
+        FmtSubscriber::builder()
+            .with_max_level(log_level)
+            .with_writer(std::io::stderr)
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .init();
+    } else {
+        FmtSubscriber::builder()
+            .with_max_level(log_level)
+            .with_target(false)
+            .compact()
+            .init();
+    }
+
+    if cli.refresh_browser_detection {
+        match nab::BrowserCache::default().refresh() {
+            Ok(browser) => tracing::debug!("Default browser: {}", browser.as_str()),
+            Err(e) => tracing::warn!("Browser detection failed: {e}"),
+        }
+    }
 
     match cli.command {
         Commands::Fetch {
@@ -447,39 +731,117 @@ async fn main() -> Result<()> {
             add_headers,
             auto_referer,
             warmup_url,
+            preconnect,
             method,
             data,
             capture_cookies,
             no_redirect,
+            max_redirects,
             no_spa,
             batch,
             parallel,
+            jsonl,
+            output_dir,
+            filename_template,
             proxy,
+            user_agent,
+            paginate,
+            json_path,
+            range,
+            tls13_only,
+            min_tls,
+            max_tls,
+            cacert,
+            insecure,
+            render,
+            hash,
+            hash_ignore,
+            deadline,
+            dry_run,
         } => {
-            cmd::cmd_fetch(
+            let hasher = cmd::fetch::content_hasher(hash, &hash_ignore)?;
+            let deadline = deadline
+                .as_deref()
+                .map(cmd::fetch::parse_deadline)
+                .transpose()?;
+            let tls = cmd::fetch::tls_options(
+                tls13_only,
+                min_tls.as_deref(),
+                max_tls.as_deref(),
+                cacert.as_deref(),
+                insecure,
+            )?;
+            let output_dir = output_dir
+                .map(|dir| {
+                    let out = nab::crawl::OutputDir::new(dir);
+                    match filename_template.as_deref() {
+                        Some(template) => out.with_template(template),
+                        None => Ok(out),
+                    }
+                })
+                .transpose()?;
+            let fetch = cmd::cmd_fetch(
                 &url,
-                headers,
-                body,
-                format,
-                output,
-                &cookies,
-                use_1password,
-                raw_html,
-                links,
-                max_body,
-                &add_headers,
-                auto_referer,
-                warmup_url.as_deref(),
-                &method,
-                data.as_deref(),
-                capture_cookies,
-                no_redirect,
-                no_spa,
-                batch.as_deref(),
-                parallel,
-                proxy.as_deref(),
-            )
-            .await?;
+                cmd::FetchOptions {
+                    show_headers: headers,
+                    show_body: body,
+                    format,
+                    output_file: output,
+                    cookies: &cookies,
+                    use_1password,
+                    raw_html,
+                    links,
+                    max_body,
+                    custom_headers: &add_headers,
+                    auto_referer,
+                    warmup_url: warmup_url.as_deref(),
+                    preconnect: &preconnect,
+                    method: &method,
+                    data: data.as_deref(),
+                    capture_cookies,
+                    max_redirects: if no_redirect { 0 } else { max_redirects },
+                    no_spa,
+                    batch_file: batch.as_deref(),
+                    parallel,
+                    jsonl,
+                    output_dir: output_dir.as_ref(),
+                    proxy: proxy.as_deref(),
+                    user_agent: user_agent.as_deref(),
+                    paginate,
+                    json_path: json_path.as_deref(),
+                    range: range.as_deref(),
+                    tls,
+                    render,
+                    hasher: hasher.as_ref(),
+                    dry_run,
+                },
+            );
+            cmd::fetch::with_deadline(deadline, fetch)
+                .await
+                .map_err(cmd::fetch::exit_on_deadline)?;
+        }
+        Commands::Head {
+            url,
+            format,
+            cookies,
+        } => {
+            cmd::cmd_head(&url, format, &cookies).await?;
+        }
+        Commands::Convert {
+            path,
+            format,
+            output,
+        } => {
+            cmd::cmd_convert(&path, format, output).await?;
+        }
+        Commands::Sse {
+            url,
+            format,
+            cookies,
+            last_event_id,
+            max_events,
+        } => {
+            cmd::cmd_sse(&url, format, &cookies, last_event_id, max_events).await?;
         }
         Commands::Spa {
             url,
@@ -495,6 +857,9 @@ async fn main() -> Result<()> {
             max_array,
             max_depth,
             http1,
+            openapi,
+            graphql_query,
+            user_agent,
         } => {
             cmd::cmd_spa(
                 &url,
@@ -510,14 +875,34 @@ async fn main() -> Result<()> {
                 max_array,
                 max_depth,
                 http1,
+                openapi,
+                graphql_query.as_deref(),
+                user_agent.as_deref(),
             )
             .await?;
         }
-        Commands::Bench { urls, iterations } => {
-            cmd::cmd_bench(&urls, iterations).await?;
+        Commands::Bench {
+            urls,
+            iterations,
+            warmup,
+            compare,
+            conditional,
+            format,
+            output,
+        } => {
+            cmd::cmd_bench(
+                &urls,
+                iterations,
+                warmup,
+                compare.as_deref(),
+                conditional,
+                format,
+                output,
+            )
+            .await?;
         }
-        Commands::Fingerprint { count } => {
-            cmd::cmd_fingerprint(count);
+        Commands::Fingerprint { count, mobile } => {
+            cmd::cmd_fingerprint(count, mobile);
         }
         Commands::Auth { url } => {
             cmd::cmd_auth(&url)?;
@@ -525,8 +910,18 @@ async fn main() -> Result<()> {
         Commands::Validate => {
             cmd::cmd_validate().await?;
         }
-        Commands::Otp { domain } => {
-            cmd::cmd_otp(&domain)?;
+        Commands::Otp {
+            domain,
+            wait,
+            poll_interval,
+        } => {
+            let wait = wait.map(|secs| {
+                (
+                    std::time::Duration::from_secs(secs),
+                    std::time::Duration::from_secs(poll_interval.max(1)),
+                )
+            });
+            cmd::cmd_otp(&domain, wait)?;
         }
         Commands::Stream {
             source,
@@ -541,6 +936,14 @@ async fn main() -> Result<()> {
             duration,
             ffmpeg_opts,
             player,
+            player_args,
+            format,
+            audio_lang,
+            audio_only,
+            audio_format,
+            headers,
+            referer,
+            xff,
         } => {
             cmd::cmd_stream(
                 &source,
@@ -555,6 +958,14 @@ async fn main() -> Result<()> {
                 duration.as_deref(),
                 ffmpeg_opts.as_deref(),
                 player.as_deref(),
+                player_args.as_deref(),
+                format,
+                audio_lang.as_deref(),
+                audio_only,
+                audio_format.as_deref(),
+                &headers,
+                referer.as_deref(),
+                xff.as_deref(),
             )
             .await?;
         }
@@ -564,37 +975,61 @@ async fn main() -> Result<()> {
             diarize,
             format,
             output,
+            lang,
+            no_cache,
+            model_dir,
+            remote,
+            remote_token,
+            remote_stages,
             dgx,
             api_key,
         } => {
+            if dgx {
+                eprintln!("⚠️  --dgx is deprecated, use --remote spark:8765");
+            }
+            let remote = remote.or_else(|| dgx.then(|| "spark:8765".to_string()));
             cmd::cmd_analyze(
                 &video,
-                audio_only,
-                diarize,
-                format,
-                output,
-                dgx,
-                api_key.as_deref(),
+                cmd::AnalyzeOptions {
+                    audio_only,
+                    diarize,
+                    format,
+                    output,
+                    lang: lang.as_deref(),
+                    no_cache,
+                    model_dir,
+                    remote: remote.as_deref(),
+                    remote_token: remote_token.as_deref(),
+                    remote_stages: &remote_stages,
+                    remote_required: dgx,
+                    api_key: api_key.as_deref(),
+                },
             )
             .await?;
         }
         Commands::Annotate {
             video,
             output,
+            subs_only,
             subtitles,
             speaker_labels,
             analysis,
+            karaoke,
             style,
             hwaccel,
+            lang,
         } => {
             cmd::cmd_annotate(
                 &video,
-                &output,
+                output.as_deref(),
+                subs_only.as_deref(),
                 subtitles,
                 speaker_labels,
                 analysis,
+                karaoke,
                 style,
                 hwaccel,
+                lang.as_deref(),
             )
             .await?;
         }
@@ -10,10 +10,11 @@ use std::process::Command;
 use std::time::{Duration, Instant};
 
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
 
-use crate::auth::{OnePasswordAuth, OtpRetriever, OtpSource};
+use crate::auth::{OnePasswordAuth, OtpRetriever, OtpSource, OTP_POLL_INTERVAL, OTP_WAIT_TIMEOUT};
 
 /// Type of MFA challenge detected
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -132,15 +133,28 @@ impl MfaHandler {
         }
     }
 
-    /// Handle an MFA challenge
+    /// Handle an MFA challenge whose code is being sent now
     pub fn handle(&self, mfa_type: &MfaType, domain: &str) -> Result<MfaResult> {
+        self.handle_since(mfa_type, domain, Utc::now())
+    }
+
+    /// Handle an MFA challenge whose code was requested at `requested_at`
+    ///
+    /// SMS and email codes are waited for until one sent at or after
+    /// `requested_at` arrives, so an older code is never submitted.
+    pub fn handle_since(
+        &self,
+        mfa_type: &MfaType,
+        domain: &str,
+        requested_at: DateTime<Utc>,
+    ) -> Result<MfaResult> {
         let start = Instant::now();
         info!("🔐 Handling MFA challenge: {}", mfa_type.description());
 
         let result = match mfa_type {
             MfaType::Totp => self.handle_totp(domain),
-            MfaType::SmsOtp => self.handle_sms_otp(domain),
-            MfaType::EmailOtp => self.handle_email_otp(domain),
+            MfaType::SmsOtp => self.handle_sms_otp(domain, requested_at),
+            MfaType::EmailOtp => self.handle_email_otp(domain, requested_at),
             MfaType::Passkey => self.handle_passkey(domain),
             MfaType::MobileAppPush { app_name } => {
                 self.handle_human_in_loop(domain, &format!("Open {app_name} and approve"))
@@ -195,12 +209,16 @@ impl MfaHandler {
     }
 
     /// Handle SMS OTP via Beeper
-    fn handle_sms_otp(&self, domain: &str) -> Result<Option<String>> {
-        if let Some(otp) = OtpRetriever::get_otp_for_domain(domain)? {
-            if otp.source == OtpSource::SmsBeeper {
-                info!("   ✅ Got SMS OTP via Beeper");
-                return Ok(Some(otp.code));
-            }
+    fn handle_sms_otp(&self, domain: &str, requested_at: DateTime<Utc>) -> Result<Option<String>> {
+        if let Some(otp) = OtpRetriever::wait_for_message_otp(
+            domain,
+            &OtpSource::SmsBeeper,
+            requested_at,
+            OTP_WAIT_TIMEOUT,
+            OTP_POLL_INTERVAL,
+        )? {
+            info!("   ✅ Got SMS OTP via Beeper");
+            return Ok(Some(otp.code));
         }
         // Fall back to human-in-loop if SMS not synced
         warn!("   ⚠️ SMS not available via Beeper, requesting manual input");
@@ -208,12 +226,20 @@ impl MfaHandler {
     }
 
     /// Handle Email OTP via Gmail
-    fn handle_email_otp(&self, domain: &str) -> Result<Option<String>> {
-        if let Some(otp) = OtpRetriever::get_otp_for_domain(domain)? {
-            if otp.source == OtpSource::EmailGmail {
-                info!("   ✅ Got Email OTP via Gmail");
-                return Ok(Some(otp.code));
-            }
+    fn handle_email_otp(
+        &self,
+        domain: &str,
+        requested_at: DateTime<Utc>,
+    ) -> Result<Option<String>> {
+        if let Some(otp) = OtpRetriever::wait_for_message_otp(
+            domain,
+            &OtpSource::EmailGmail,
+            requested_at,
+            OTP_WAIT_TIMEOUT,
+            OTP_POLL_INTERVAL,
+        )? {
+            info!("   ✅ Got Email OTP via Gmail");
+            return Ok(Some(otp.code));
         }
         // Fall back to human-in-loop
         warn!("   ⚠️ Email OTP not found, requesting manual input");
@@ -223,18 +249,16 @@ impl MfaHandler {
     /// Handle Passkey via 1Password
     fn handle_passkey(&self, domain: &str) -> Result<Option<String>> {
         // Check if 1Password has a passkey for this domain
-        let passkeys = self.op_auth.list_passkeys()?;
-        for passkey in passkeys {
-            if let Some(ref url) = passkey.url {
-                if url.contains(domain) {
-                    info!("   ✅ Found passkey in 1Password: {}", passkey.title);
-                    // Note: Actual passkey signing requires 1Password browser extension
-                    // or their SDK. For now, we notify the user to use 1Password.
-                    if let Some(id) = passkey.passkey_credential_id {
-                        return Ok(Some(id));
-                    }
-                }
-            }
+        if let Some(passkey) = self
+            .op_auth
+            .list_passkeys_for_domain(domain)?
+            .into_iter()
+            .next()
+        {
+            info!("   ✅ Found passkey in 1Password: {}", passkey.title);
+            // Note: Actual passkey signing requires 1Password browser extension
+            // or their SDK. For now, we notify the user to use 1Password.
+            return Ok(Some(passkey.credential_id));
         }
 
         // Try using op CLI to sign (if supported)
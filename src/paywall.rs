@@ -0,0 +1,236 @@
+//! Paywall detection
+//!
+//! Flags pages that are a subscription teaser rather than the full article,
+//! so callers don't mistake the visible excerpt for the real content.
+//!
+//! ```rust
+//! use nab::paywall::{detect_paywall, PaywallSignal};
+//!
+//! let html = r#"<script type="application/ld+json">
+//!     {"@type": "NewsArticle", "isAccessibleForFree": false}
+//! </script>"#;
+//! assert_eq!(detect_paywall(html), Some(PaywallSignal::NotAccessibleForFree));
+//! ```
+
+use std::fmt;
+
+use scraper::{Html, Selector};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Class/id fragments used by common paywall vendors and CMS themes
+const PAYWALL_MARKERS: &[&str] = &[
+    "paywall",
+    "regwall",
+    "meteredcontent",
+    "subscriber-only",
+    "subscribers-only",
+    "premium-content-locked",
+    "article-locked",
+    "tp-modal",
+    "piano-offer",
+];
+
+/// Calls to action shown in place of the rest of the article
+const SUBSCRIBE_PHRASES: &[&str] = &[
+    "subscribe to continue",
+    "subscribe to read",
+    "subscribe now to read",
+    "to continue reading",
+    "continue reading with a subscription",
+    "this article is for subscribers",
+    "this content is for subscribers",
+    "exclusive to subscribers",
+    "become a member to read",
+    "sign up to read",
+    "already a subscriber",
+];
+
+/// An article body with fewer words than this counts as a teaser
+const TEASER_MAX_WORDS: usize = 150;
+
+/// Why a page was classified as paywalled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaywallSignal {
+    /// JSON-LD declares `isAccessibleForFree: false`
+    NotAccessibleForFree,
+    /// A known paywall container class or id is present
+    PaywallContainer,
+    /// A short article body followed by a subscribe call to action
+    SubscribeTeaser,
+}
+
+impl fmt::Display for PaywallSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::NotAccessibleForFree => "JSON-LD isAccessibleForFree: false",
+            Self::PaywallContainer => "paywall container in page",
+            Self::SubscribeTeaser => "short teaser with subscribe prompt",
+        })
+    }
+}
+
+/// Detect whether an HTML page is a paywall teaser.
+#[must_use]
+pub fn detect_paywall(html: &str) -> Option<PaywallSignal> {
+    detect_paywall_in(&Html::parse_document(html))
+}
+
+/// Like [`detect_paywall`], for a page the caller has already parsed
+#[must_use]
+pub fn detect_paywall_in(document: &Html) -> Option<PaywallSignal> {
+    if json_ld_not_free(document) {
+        return Some(PaywallSignal::NotAccessibleForFree);
+    }
+    if has_paywall_container(document) {
+        return Some(PaywallSignal::PaywallContainer);
+    }
+    if is_subscribe_teaser(document) {
+        return Some(PaywallSignal::SubscribeTeaser);
+    }
+    None
+}
+
+/// Whether an HTML page is a paywall teaser.
+#[must_use]
+pub fn is_paywalled(html: &str) -> bool {
+    detect_paywall(html).is_some()
+}
+
+fn json_ld_not_free(document: &Html) -> bool {
+    let selector = Selector::parse(r#"script[type="application/ld+json"]"#).unwrap();
+    document.select(&selector).any(|script| {
+        let text = script.text().collect::<String>();
+        serde_json::from_str::<Value>(text.trim()).is_ok_and(|value| declares_not_free(&value))
+    })
+}
+
+/// Search a JSON-LD tree (including `@graph` and `hasPart`) for
+/// `isAccessibleForFree` set to false
+fn declares_not_free(value: &Value) -> bool {
+    match value {
+        Value::Object(map) => map.iter().any(|(key, value)| {
+            if key == "isAccessibleForFree" {
+                match value {
+                    Value::Bool(free) => !free,
+                    Value::String(free) => free.eq_ignore_ascii_case("false"),
+                    _ => false,
+                }
+            } else {
+                declares_not_free(value)
+            }
+        }),
+        Value::Array(items) => items.iter().any(declares_not_free),
+        _ => false,
+    }
+}
+
+fn has_paywall_container(document: &Html) -> bool {
+    let selector = Selector::parse("[class], [id]").unwrap();
+    document.select(&selector).any(|element| {
+        let element = element.value();
+        element.classes().chain(element.id()).any(|name| {
+            let name = name.to_ascii_lowercase();
+            PAYWALL_MARKERS
+                .iter()
+                .any(|marker| contains_word(&name, marker))
+        })
+    })
+}
+
+/// Whether `marker` occurs in `name` as a whole word, delimited by the ends
+/// or by non-alphanumerics (`c-paywall__overlay` has `paywall`,
+/// `regwallet` has no `regwall`)
+fn contains_word(name: &str, marker: &str) -> bool {
+    name.match_indices(marker).any(|(at, _)| {
+        let before = name[..at].chars().next_back();
+        let after = name[at + marker.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+fn is_subscribe_teaser(document: &Html) -> bool {
+    let article = Selector::parse("article").unwrap();
+    let body = Selector::parse("body").unwrap();
+    let Some(root) = document
+        .select(&article)
+        .next()
+        .or_else(|| document.select(&body).next())
+    else {
+        return false;
+    };
+
+    let text = root.text().collect::<Vec<_>>().join(" ");
+    if text.split_whitespace().count() >= TEASER_MAX_WORDS {
+        return false;
+    }
+    let page_text = document
+        .root_element()
+        .text()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    SUBSCRIBE_PHRASES
+        .iter()
+        .any(|phrase| page_text.contains(phrase))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAYWALLED_ARTICLE: &str = r#"<!DOCTYPE html><html><head><title>Markets rally</title>
+<script type="application/ld+json">
+{"@context": "https://schema.org", "@type": "NewsArticle", "headline": "Markets rally",
+ "isAccessibleForFree": "False",
+ "hasPart": {"@type": "WebPageElement", "isAccessibleForFree": false, "cssSelector": ".body"}}
+</script></head>
+<body><article><h1>Markets rally</h1><p>Stocks rose sharply on Tuesday as investors...</p></article></body></html>"#;
+
+    #[test]
+    fn detects_json_ld_not_accessible_for_free() {
+        assert_eq!(
+            detect_paywall(PAYWALLED_ARTICLE),
+            Some(PaywallSignal::NotAccessibleForFree)
+        );
+        assert!(is_paywalled(PAYWALLED_ARTICLE));
+    }
+
+    #[test]
+    fn normal_article_is_not_paywalled() {
+        let paragraph = "<p>The committee met on Tuesday to review the proposal in detail, \
+            discussing costs, timelines and the expected impact on local residents.</p>";
+        let html = format!(
+            r#"<html><head><script type="application/ld+json">
+{{"@type": "NewsArticle", "isAccessibleForFree": true}}</script></head>
+<body><article><h1>Council approves plan</h1>{}</article>
+<footer>Already a subscriber? Sign in</footer></body></html>"#,
+            paragraph.repeat(10)
+        );
+        assert_eq!(detect_paywall(&html), None);
+    }
+
+    #[test]
+    fn detects_paywall_container() {
+        let html = r#"<body><article><p>Intro</p><div class="c-paywall__overlay">Locked</div></article></body>"#;
+        assert_eq!(detect_paywall(html), Some(PaywallSignal::PaywallContainer));
+        let html = r#"<body><p id="tp-modal">Offer</p></body>"#;
+        assert_eq!(detect_paywall(html), Some(PaywallSignal::PaywallContainer));
+    }
+
+    #[test]
+    fn marker_inside_a_longer_word_is_not_a_container() {
+        let html = r#"<body><div class="regwallet unmeteredcontent"><p>Pay by card</p></div>
+<nav id="paywalls-explained">Guide</nav></body>"#;
+        assert_eq!(detect_paywall(html), None);
+    }
+
+    #[test]
+    fn detects_short_teaser_with_subscribe_prompt() {
+        let html = r"<body><article><h1>Inside the deal</h1><p>The talks began in March.</p>
+<p>Subscribe to continue reading.</p></article></body>";
+        assert_eq!(detect_paywall(html), Some(PaywallSignal::SubscribeTeaser));
+        assert!(!is_paywalled("<body><p>Short note, no prompt.</p></body>"));
+    }
+}
@@ -33,6 +33,9 @@ struct PluginMetadata {
     title: Option<String>,
     author: Option<String>,
     published: Option<String>,
+    /// Set by plugins that detect a paywall teaser
+    #[serde(default)]
+    paywalled: bool,
 }
 
 /// Runs an external plugin binary as a [`SiteProvider`].
@@ -41,7 +44,16 @@ struct PluginMetadata {
 /// `{"markdown": "...", "metadata": {...}}` on stdout within 30 seconds.
 pub struct PluginRunner {
     config: PluginConfig,
+    /// Compiled once here; `matches` never recompiles
     patterns: Vec<Regex>,
+    name: &'static str,
+}
+
+#[cfg(test)]
+thread_local! {
+    /// Patterns compiled on this thread, so tests can assert routing reuses them
+    pub(crate) static PATTERN_COMPILATIONS: std::cell::Cell<usize> =
+        const { std::cell::Cell::new(0) };
 }
 
 impl PluginRunner {
@@ -61,17 +73,24 @@ impl PluginRunner {
                     .with_context(|| format!("invalid pattern '{p}' in plugin '{}'", config.name))
             })
             .collect::<Result<Vec<_>>>()?;
-
-        Ok(Self { config, patterns })
+        #[cfg(test)]
+        PATTERN_COMPILATIONS.with(|count| count.set(count.get() + patterns.len()));
+
+        // Leak the name once so `name()` can return &'static str. Plugins
+        // are loaded once at startup, so this is fine.
+        let name = Box::leak(config.name.clone().into_boxed_str());
+        Ok(Self {
+            config,
+            patterns,
+            name,
+        })
     }
 }
 
 #[async_trait]
 impl SiteProvider for PluginRunner {
     fn name(&self) -> &'static str {
-        // Leak the name so we can return &'static str.
-        // Plugins are loaded once at startup, so this is fine.
-        Box::leak(self.config.name.clone().into_boxed_str())
+        self.name
     }
 
     fn matches(&self, url: &str) -> bool {
@@ -151,6 +170,7 @@ impl SiteProvider for PluginRunner {
                 canonical_url: url_owned,
                 media_urls: Vec::new(),
                 engagement: None,
+                paywalled: output.metadata.paywalled,
             },
         })
     }
@@ -0,0 +1,157 @@
+//! Headless rendering fallback for client-side rendered pages
+//!
+//! Single-page apps often ship only a JavaScript bootstrap, so converting the
+//! raw HTML yields almost no content. [`looks_client_rendered`] spots that
+//! case, and a [`PageRenderer`] produces the post-JavaScript DOM instead.
+//! With the `headless` feature, [`ChromeRenderer`] drives a local
+//! Chrome/Chromium via `--dump-dom`.
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+/// Smallest HTML document considered for the heuristic
+const MIN_HTML_BYTES: usize = 1024;
+/// Markdown with fewer words than this counts as "empty"
+const MAX_EMPTY_WORDS: usize = 40;
+
+/// Produces the rendered (post-JavaScript) HTML of a page
+#[async_trait]
+pub trait PageRenderer: Send + Sync {
+    /// Renderer name for reporting (e.g. "headless Chromium")
+    fn name(&self) -> &'static str;
+
+    /// Load `url` and return the serialized DOM after scripts have run
+    async fn render(&self, url: &str) -> Result<String>;
+}
+
+/// Whether converted `markdown` is suspiciously empty for the size of `html`,
+/// which indicates content that only appears after client-side rendering.
+#[must_use]
+pub fn looks_client_rendered(html: &str, markdown: &str) -> bool {
+    if html.len() < MIN_HTML_BYTES || !html.to_ascii_lowercase().contains("<script") {
+        return false;
+    }
+    let words = markdown.split_whitespace().count();
+    // Under 40 words and under 5% of the HTML size
+    words < MAX_EMPTY_WORDS && markdown.trim().len() * 20 < html.len()
+}
+
+/// Local Chrome/Chromium run as `--headless --dump-dom`
+#[cfg(feature = "headless")]
+#[derive(Debug, Clone)]
+pub struct ChromeRenderer {
+    binary: std::path::PathBuf,
+    timeout: std::time::Duration,
+}
+
+#[cfg(feature = "headless")]
+impl ChromeRenderer {
+    /// Browser binaries tried in order (override with `NAB_CHROME`)
+    const CANDIDATES: &'static [&'static str] = &[
+        "chromium",
+        "chromium-browser",
+        "google-chrome",
+        "google-chrome-stable",
+        "chrome",
+        "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+        "/Applications/Chromium.app/Contents/MacOS/Chromium",
+        "/Applications/Brave Browser.app/Contents/MacOS/Brave Browser",
+    ];
+
+    /// Find a Chrome/Chromium binary via `NAB_CHROME` or `PATH`
+    pub fn discover() -> Result<Self> {
+        if let Some(path) = std::env::var_os("NAB_CHROME") {
+            return Ok(Self::with_binary(path));
+        }
+        Self::CANDIDATES
+            .iter()
+            .find_map(|candidate| which::which(candidate).ok())
+            .map(Self::with_binary)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "No Chrome/Chromium found for --render (install one or set NAB_CHROME)"
+                )
+            })
+    }
+
+    /// Use a specific browser binary
+    pub fn with_binary(binary: impl Into<std::path::PathBuf>) -> Self {
+        Self {
+            binary: binary.into(),
+            timeout: std::time::Duration::from_secs(30),
+        }
+    }
+
+    /// Overall time limit for a render
+    #[must_use]
+    pub fn with_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+#[cfg(feature = "headless")]
+#[async_trait]
+impl PageRenderer for ChromeRenderer {
+    fn name(&self) -> &'static str {
+        "headless Chromium"
+    }
+
+    async fn render(&self, url: &str) -> Result<String> {
+        let output = tokio::time::timeout(
+            self.timeout,
+            tokio::process::Command::new(&self.binary)
+                .args([
+                    "--headless=new",
+                    "--disable-gpu",
+                    "--hide-scrollbars",
+                    "--mute-audio",
+                    "--virtual-time-budget=5000",
+                    "--dump-dom",
+                    url,
+                ])
+                .kill_on_drop(true)
+                .output(),
+        )
+        .await
+        .map_err(|_| anyhow::anyhow!("Headless render timed out after {:?}", self.timeout))??;
+
+        if !output.status.success() {
+            anyhow::bail!(
+                "Headless render failed ({}): {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn csr_shell() -> String {
+        format!(
+            r#"<!DOCTYPE html><html><head><title>App</title></head><body><div id="root"></div>
+<script>window.__BOOT__={{"chunks":"{}"}}</script><script src="/static/js/main.js"></script></body></html>"#,
+            "a1b2c3".repeat(300)
+        )
+    }
+
+    #[test]
+    fn flags_empty_spa_shell() {
+        assert!(looks_client_rendered(&csr_shell(), "App"));
+    }
+
+    #[test]
+    fn ignores_content_pages_and_small_documents() {
+        let article = format!(
+            "<html><body><script></script><article>{}</article></body></html>",
+            "<p>Plenty of readable server-rendered text here.</p>".repeat(40)
+        );
+        let markdown = "Plenty of readable server-rendered text here.\n\n".repeat(40);
+        assert!(!looks_client_rendered(&article, &markdown));
+        assert!(!looks_client_rendered("<html><script></script></html>", ""));
+    }
+}
@@ -0,0 +1,213 @@
+//! Retry policy for transient HTTP failures
+//!
+//! [`AcceleratedClient`](crate::AcceleratedClient) retries idempotent
+//! requests (`GET`, `HEAD`, `PUT`, `DELETE`, `OPTIONS`, `TRACE`, or any
+//! method with an `Idempotency-Key` header) that fail
+//! with a connection error, a timeout or one of the policy's status codes.
+//! Waits grow exponentially from [`RetryPolicy::base_delay`]; a `Retry-After`
+//! header on `429` or `503` replaces the computed wait. Client errors other
+//! than `429` are never retried.
+
+use std::time::Duration;
+
+use rand::Rng;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::StatusCode;
+
+/// When and how often to retry a failed request
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Total attempts including the first; `1` disables retries
+    pub max_attempts: u32,
+    /// Wait before the first retry
+    pub base_delay: Duration,
+    /// Factor applied to the wait after each retry
+    pub multiplier: f64,
+    /// Fraction of each wait randomized in both directions, `0.0` to `1.0`
+    pub jitter: f64,
+    /// Longest wait between attempts; a longer `Retry-After` ends retrying
+    pub max_delay: Duration,
+    /// Response statuses worth retrying (4xx other than 429 are ignored)
+    pub retryable_statuses: Vec<u16>,
+}
+
+impl Default for RetryPolicy {
+    /// Three attempts, waiting about 250ms then 500ms, on 429, 502, 503 and 504
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(250),
+            multiplier: 2.0,
+            jitter: 0.2,
+            max_delay: Duration::from_secs(30),
+            retryable_statuses: vec![429, 502, 503, 504],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Never retry
+    #[must_use]
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Whether a response with `status` should be retried
+    pub fn retries_status(&self, status: StatusCode) -> bool {
+        let client_error = status.is_client_error() && status != StatusCode::TOO_MANY_REQUESTS;
+        !client_error && self.retryable_statuses.contains(&status.as_u16())
+    }
+
+    /// Backoff before retry number `retry` (1-based), jittered and capped at
+    /// [`max_delay`](Self::max_delay)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponent = i32::try_from(retry.saturating_sub(1)).unwrap_or(i32::MAX);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let factor = if jitter > 0.0 {
+            rand::thread_rng().gen_range(1.0 - jitter..=1.0 + jitter)
+        } else {
+            1.0
+        };
+        let secs = self.base_delay.as_secs_f64() * self.multiplier.powi(exponent) * factor;
+        Duration::try_from_secs_f64(secs)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+
+    /// Wait before retry number `retry` after a response with `status`, or
+    /// `None` when the server asks for a longer pause than
+    /// [`max_delay`](Self::max_delay)
+    pub fn delay_for_response(
+        &self,
+        retry: u32,
+        status: StatusCode,
+        headers: &HeaderMap,
+    ) -> Option<Duration> {
+        let honours_retry_after = matches!(
+            status,
+            StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+        );
+        match retry_after(headers, chrono::Utc::now()).filter(|_| honours_retry_after) {
+            Some(wait) if wait > self.max_delay => None,
+            Some(wait) => Some(wait),
+            None => Some(self.backoff(retry)),
+        }
+    }
+}
+
+/// Wait requested by a `Retry-After` header, in seconds or as an HTTP date
+pub fn retry_after(headers: &HeaderMap, now: chrono::DateTime<chrono::Utc>) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&chrono::Utc) - now)
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+/// Whether a failed request may succeed if sent again: connection failures,
+/// resets and timeouts, but not e.g. invalid URLs or TLS policy errors
+pub fn is_transient(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        if let Some(err) = cause.downcast_ref::<reqwest::Error>() {
+            return err.is_connect() || err.is_timeout() || err.is_request();
+        }
+        cause.downcast_ref::<std::io::Error>().is_some_and(|err| {
+            matches!(
+                err.kind(),
+                std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::BrokenPipe
+                    | std::io::ErrorKind::UnexpectedEof
+            )
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exact() -> RetryPolicy {
+        RetryPolicy {
+            jitter: 0.0,
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_up_to_the_cap() {
+        let policy = RetryPolicy {
+            max_delay: Duration::from_millis(800),
+            ..exact()
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(250));
+        assert_eq!(policy.backoff(2), Duration::from_millis(500));
+        assert_eq!(policy.backoff(3), Duration::from_millis(800));
+
+        let jittered = RetryPolicy::default().backoff(1);
+        assert!(jittered >= Duration::from_millis(200) && jittered <= Duration::from_millis(300));
+    }
+
+    #[test]
+    fn client_errors_other_than_429_are_not_retried() {
+        let policy = RetryPolicy {
+            retryable_statuses: vec![404, 429, 503],
+            ..exact()
+        };
+        assert!(policy.retries_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(policy.retries_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(!policy.retries_status(StatusCode::NOT_FOUND));
+        assert!(!policy.retries_status(StatusCode::INTERNAL_SERVER_ERROR));
+    }
+
+    #[test]
+    fn parses_retry_after() {
+        let now = chrono::DateTime::parse_from_rfc2822("Wed, 21 Oct 2026 07:28:00 GMT")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let headers = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(RETRY_AFTER, value.parse().unwrap());
+            headers
+        };
+
+        assert_eq!(
+            retry_after(&headers("120"), now),
+            Some(Duration::from_mins(2))
+        );
+        assert_eq!(
+            retry_after(&headers("Wed, 21 Oct 2026 07:28:30 GMT"), now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            retry_after(&headers("Wed, 21 Oct 2026 07:00:00 GMT"), now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(retry_after(&headers("soon"), now), None);
+    }
+
+    #[test]
+    fn long_retry_after_stops_retrying() {
+        let policy = exact();
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, "3600".parse().unwrap());
+
+        assert_eq!(
+            policy.delay_for_response(1, StatusCode::SERVICE_UNAVAILABLE, &headers),
+            None
+        );
+        // Retry-After only counts on 429 and 503
+        assert_eq!(
+            policy.delay_for_response(1, StatusCode::BAD_GATEWAY, &headers),
+            Some(Duration::from_millis(250))
+        );
+    }
+}
@@ -39,6 +39,10 @@ impl SiteProvider for GitHubProvider {
         "github"
     }
 
+    fn hosts(&self) -> &'static [&'static str] {
+        &["github.com"]
+    }
+
     fn matches(&self, url: &str) -> bool {
         let normalized = url.to_lowercase();
         let normalized = normalized.split('?').next().unwrap_or(&normalized);
@@ -88,6 +92,7 @@ impl SiteProvider for GitHubProvider {
             canonical_url: issue.html_url.clone(),
             media_urls: vec![],
             engagement: Some(engagement),
+            paywalled: false,
         };
 
         Ok(SiteContent { markdown, metadata })
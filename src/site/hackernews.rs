@@ -39,6 +39,10 @@ impl SiteProvider for HackerNewsProvider {
         "hackernews"
     }
 
+    fn hosts(&self) -> &'static [&'static str] {
+        &["news.ycombinator.com"]
+    }
+
     fn matches(&self, url: &str) -> bool {
         let normalized = url.to_lowercase();
         let normalized = normalized.split('?').next().unwrap_or(&normalized);
@@ -79,6 +83,7 @@ impl SiteProvider for HackerNewsProvider {
             canonical_url,
             media_urls: vec![],
             engagement: Some(engagement),
+            paywalled: false,
         };
 
         Ok(SiteContent { markdown, metadata })
@@ -40,6 +40,10 @@ impl SiteProvider for InstagramProvider {
         "instagram"
     }
 
+    fn hosts(&self) -> &'static [&'static str] {
+        &["instagram.com"]
+    }
+
     fn matches(&self, url: &str) -> bool {
         let normalized = url.to_lowercase();
         let normalized = normalized.split('?').next().unwrap_or(&normalized);
@@ -89,6 +93,7 @@ impl InstagramProvider {
             canonical_url: url.to_string(),
             media_urls: vec![oembed.thumbnail_url.clone()],
             engagement: None,
+            paywalled: false,
         };
 
         Ok(SiteContent { markdown, metadata })
@@ -127,6 +132,7 @@ impl InstagramProvider {
             canonical_url: url.to_string(),
             media_urls,
             engagement: None,
+            paywalled: false,
         };
 
         Ok(SiteContent { markdown, metadata })
@@ -39,6 +39,10 @@ impl SiteProvider for LinkedInProvider {
         "linkedin"
     }
 
+    fn hosts(&self) -> &'static [&'static str] {
+        &["linkedin.com"]
+    }
+
     fn matches(&self, url: &str) -> bool {
         let normalized = url.to_lowercase();
         let normalized = normalized.split('?').next().unwrap_or(&normalized);
@@ -77,6 +81,7 @@ impl SiteProvider for LinkedInProvider {
                 .map(|t| vec![t.clone()])
                 .unwrap_or_default(),
             engagement: None,
+            paywalled: false,
         };
 
         Ok(SiteContent { markdown, metadata })
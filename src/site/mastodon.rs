@@ -59,6 +59,10 @@ impl SiteProvider for MastodonProvider {
         "mastodon"
     }
 
+    fn hosts(&self) -> &'static [&'static str] {
+        KNOWN_INSTANCES
+    }
+
     fn matches(&self, url: &str) -> bool {
         let normalized = url.to_lowercase();
         let normalized = normalized.split('?').next().unwrap_or(&normalized);
@@ -116,6 +120,7 @@ impl SiteProvider for MastodonProvider {
                 .filter_map(|m| m.url.clone())
                 .collect(),
             engagement: Some(engagement),
+            paywalled: false,
         };
 
         Ok(SiteContent { markdown, metadata })
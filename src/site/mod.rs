@@ -40,6 +40,7 @@ pub mod youtube;
 use anyhow::Result;
 use async_trait::async_trait;
 
+use crate::cookie_jar::domain_matches;
 use crate::http_client::AcceleratedClient;
 
 /// Engagement metrics for social media content.
@@ -61,6 +62,8 @@ pub struct SiteMetadata {
     pub canonical_url: String,
     pub media_urls: Vec<String>,
     pub engagement: Option<Engagement>,
+    /// The content is a paywall teaser rather than the full text
+    pub paywalled: bool,
 }
 
 /// Extracted and formatted site content.
@@ -72,12 +75,36 @@ pub struct SiteContent {
     pub metadata: SiteMetadata,
 }
 
+impl SiteContent {
+    /// Whether the markdown carries enough text to beat a generic fetch.
+    ///
+    /// Counts non-whitespace characters, so a provider that only produced
+    /// headings or blank lines doesn't pass.
+    #[must_use]
+    pub fn is_substantial(&self, min_chars: usize) -> bool {
+        self.markdown.chars().filter(|c| !c.is_whitespace()).count() >= min_chars
+    }
+}
+
+/// Default minimum provider output (non-whitespace characters) before the
+/// router prefers it over a generic fetch.
+pub const DEFAULT_MIN_CONTENT_CHARS: usize = 50;
+
 /// Provider for extracting content from a specific platform.
 #[async_trait]
 pub trait SiteProvider: Send + Sync {
     /// Provider name (e.g., "twitter", "youtube").
     fn name(&self) -> &'static str;
 
+    /// Domains this provider can match, subdomains included (e.g.
+    /// `"reddit.com"` also covers `old.reddit.com`, but not `notreddit.com`).
+    ///
+    /// The router only calls [`matches`](Self::matches) for URLs whose host
+    /// is one of these or a subdomain. Empty (the default) means any host.
+    fn hosts(&self) -> &'static [&'static str] {
+        &[]
+    }
+
     /// Check if this provider handles the given URL.
     fn matches(&self, url: &str) -> bool;
 
@@ -87,10 +114,20 @@ pub trait SiteProvider: Send + Sync {
 
 /// Routes URLs to specialized site providers.
 ///
-/// Providers are checked in registration order. First match wins.
-/// Returns `None` if no provider matches or extraction fails.
+/// Providers are checked in order and the first match wins: custom
+/// providers (from [`with_providers`](Self::with_providers),
+/// [`with_provider`](Self::with_provider) or [`register`](Self::register))
+/// come before the built-in ones, so they can override them. The URL host
+/// is extracted once per lookup and providers whose
+/// [`hosts`](SiteProvider::hosts) can't match it are skipped without
+/// running their full `matches`.
+/// Returns `None` if no provider matches, extraction fails, or the result
+/// is too thin to be worth more than a generic fetch.
 pub struct SiteRouter {
     providers: Vec<Box<dyn SiteProvider>>,
+    /// Number of custom providers at the front of `providers`
+    custom_count: usize,
+    min_content_chars: usize,
 }
 
 impl SiteRouter {
@@ -110,7 +147,58 @@ impl SiteRouter {
             Box::new(linkedin::LinkedInProvider),
         ];
 
-        Self { providers }
+        Self {
+            providers,
+            custom_count: 0,
+            min_content_chars: DEFAULT_MIN_CONTENT_CHARS,
+        }
+    }
+
+    /// Create a router with custom providers ahead of all built-in ones.
+    ///
+    /// Custom providers are checked in the given order.
+    #[must_use]
+    pub fn with_providers(providers: Vec<Box<dyn SiteProvider>>) -> Self {
+        let mut router = Self::new();
+        for provider in providers {
+            router.register(provider);
+        }
+        router
+    }
+
+    /// Register a custom provider (e.g. a [`PluginRunner`]).
+    ///
+    /// It is checked after previously registered custom providers but
+    /// before all built-in ones.
+    ///
+    /// [`PluginRunner`]: crate::plugin::PluginRunner
+    pub fn register(&mut self, provider: Box<dyn SiteProvider>) {
+        self.providers.insert(self.custom_count, provider);
+        self.custom_count += 1;
+    }
+
+    /// Builder form of [`register`](Self::register).
+    #[must_use]
+    pub fn with_provider(mut self, provider: Box<dyn SiteProvider>) -> Self {
+        self.register(provider);
+        self
+    }
+
+    /// Set the minimum provider output (non-whitespace characters) below
+    /// which the router falls back to generic fetch. `0` accepts any result.
+    #[must_use]
+    pub fn with_min_content_chars(mut self, min_chars: usize) -> Self {
+        self.min_content_chars = min_chars;
+        self
+    }
+
+    /// The first provider that handles `url`, if any.
+    pub fn provider_for(&self, url: &str) -> Option<&dyn SiteProvider> {
+        let host = url_host(url);
+        self.providers
+            .iter()
+            .map(AsRef::as_ref)
+            .find(|provider| host_matches(host, provider.hosts()) && provider.matches(url))
     }
 
     /// Try to extract content using a specialized provider.
@@ -118,25 +206,37 @@ impl SiteRouter {
     /// Returns `None` if:
     /// - No provider matches the URL
     /// - Provider extraction fails (logged as warning)
+    /// - The extracted markdown is below the minimum length (logged at debug)
+    #[tracing::instrument(
+        name = "site_router",
+        skip(self, client),
+        fields(provider = tracing::field::Empty)
+    )]
     pub async fn try_extract(&self, url: &str, client: &AcceleratedClient) -> Option<SiteContent> {
-        for provider in &self.providers {
-            if provider.matches(url) {
-                tracing::debug!("Matched site provider: {}", provider.name());
-                match provider.extract(url, client).await {
-                    Ok(content) => return Some(content),
-                    Err(e) => {
-                        tracing::warn!(
-                            "Site provider {} failed for {}: {}",
-                            provider.name(),
-                            url,
-                            e
-                        );
-                        return None;
-                    }
-                }
+        let provider = self.provider_for(url)?;
+        tracing::Span::current().record("provider", provider.name());
+        tracing::debug!("Matched site provider: {}", provider.name());
+        match provider.extract(url, client).await {
+            Ok(content) if content.is_substantial(self.min_content_chars) => Some(content),
+            Ok(_) => {
+                tracing::debug!(
+                    "Site provider {} returned under {} characters for {}; falling back to generic fetch",
+                    provider.name(),
+                    self.min_content_chars,
+                    url
+                );
+                None
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "Site provider {} failed for {}: {}",
+                    provider.name(),
+                    url,
+                    e
+                );
+                None
             }
         }
-        None
     }
 }
 
@@ -146,9 +246,29 @@ impl Default for SiteRouter {
     }
 }
 
+/// Host part of a URL without allocating (scheme optional; userinfo and
+/// port stripped)
+fn url_host(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    host.split(':').next().unwrap_or_default()
+}
+
+/// Whether `host` is one of `domains` or a subdomain of one (ASCII
+/// case-insensitive), so `old.reddit.com` matches `reddit.com` but
+/// `notreddit.com` doesn't. An empty list matches every host.
+fn host_matches(host: &str, domains: &[&str]) -> bool {
+    domains.is_empty() || domains.iter().any(|domain| domain_matches(host, domain))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
 
     #[test]
     fn router_registers_all_providers() {
@@ -166,6 +286,242 @@ mod tests {
         assert_eq!(router.providers[9].name(), "linkedin");
     }
 
+    /// Provider that matches everything and returns fixed markdown
+    struct StubProvider(&'static str);
+
+    #[async_trait]
+    impl SiteProvider for StubProvider {
+        fn name(&self) -> &'static str {
+            "stub"
+        }
+
+        fn matches(&self, _url: &str) -> bool {
+            true
+        }
+
+        async fn extract(&self, url: &str, _client: &AcceleratedClient) -> Result<SiteContent> {
+            Ok(SiteContent {
+                markdown: self.0.to_string(),
+                metadata: SiteMetadata {
+                    author: None,
+                    title: Some("Stub".to_string()),
+                    published: None,
+                    platform: "stub".to_string(),
+                    canonical_url: url.to_string(),
+                    media_urls: Vec::new(),
+                    engagement: None,
+                    paywalled: false,
+                },
+            })
+        }
+    }
+
+    fn stub_router(markdown: &'static str) -> SiteRouter {
+        SiteRouter {
+            providers: vec![Box::new(StubProvider(markdown))],
+            custom_count: 0,
+            min_content_chars: DEFAULT_MIN_CONTENT_CHARS,
+        }
+    }
+
+    #[tokio::test]
+    async fn router_falls_back_on_near_empty_content() {
+        let client = tokio::task::spawn_blocking(AcceleratedClient::new)
+            .await
+            .unwrap()
+            .unwrap();
+        let url = "https://example.com/post/1";
+
+        let thin = stub_router("## Stub\n\n   \n");
+        assert!(thin.try_extract(url, &client).await.is_none());
+
+        // A lower threshold accepts the same result
+        let lenient = stub_router("## Stub\n\n   \n").with_min_content_chars(4);
+        assert!(lenient.try_extract(url, &client).await.is_some());
+
+        let full = stub_router(
+            "## Stub\n\nA complete post body with enough text to be worth keeping over a generic fetch.",
+        );
+        let content = full.try_extract(url, &client).await.unwrap();
+        assert!(content.markdown.contains("complete post body"));
+    }
+
+    /// Custom provider for an internal wiki that also claims one x.com account
+    struct WikiProvider;
+
+    #[async_trait]
+    impl SiteProvider for WikiProvider {
+        fn name(&self) -> &'static str {
+            "wiki"
+        }
+
+        fn matches(&self, url: &str) -> bool {
+            url.starts_with("https://wiki.internal.example/") || url.contains("x.com/ourteam/")
+        }
+
+        async fn extract(&self, url: &str, _client: &AcceleratedClient) -> Result<SiteContent> {
+            Ok(SiteContent {
+                markdown: "## Runbook\n\nRestart the ingest workers, then verify the queue drains."
+                    .to_string(),
+                metadata: SiteMetadata {
+                    author: None,
+                    title: Some("Runbook".to_string()),
+                    published: None,
+                    platform: "wiki".to_string(),
+                    canonical_url: url.to_string(),
+                    media_urls: Vec::new(),
+                    engagement: None,
+                    paywalled: false,
+                },
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn custom_providers_take_precedence_over_built_ins() {
+        let mut router = SiteRouter::with_providers(vec![Box::new(WikiProvider)]);
+        router.register(Box::new(StubProvider("unused")));
+        let router = router.with_provider(Box::new(StubProvider("also unused")));
+        assert_eq!(router.providers[0].name(), "wiki");
+        assert_eq!(router.providers[1].name(), "stub");
+        assert_eq!(router.providers[2].name(), "stub");
+        assert_eq!(router.providers[3].name(), "twitter");
+
+        let client = tokio::task::spawn_blocking(AcceleratedClient::new)
+            .await
+            .unwrap()
+            .unwrap();
+        let content = router
+            .try_extract("https://wiki.internal.example/ops/runbook", &client)
+            .await
+            .unwrap();
+        assert_eq!(content.metadata.platform, "wiki");
+
+        // Overrides a built-in for the URLs it claims
+        let custom = SiteRouter::with_providers(vec![Box::new(WikiProvider)]);
+        let name = |url| custom.provider_for(url).map(SiteProvider::name);
+        assert_eq!(name("https://x.com/ourteam/status/1"), Some("wiki"));
+        // Built-ins still handle everything else
+        assert_eq!(name("https://x.com/naval/status/1"), Some("twitter"));
+        assert_eq!(
+            name("https://reddit.com/r/rust/comments/abc123"),
+            Some("reddit")
+        );
+        assert_eq!(name("https://example.com/page"), None);
+    }
+
+    #[test]
+    fn url_host_strips_scheme_userinfo_and_port() {
+        assert_eq!(
+            url_host("https://user:pw@Old.Reddit.com:443/r/x"),
+            "Old.Reddit.com"
+        );
+        assert_eq!(url_host("x.com/user/status/1"), "x.com");
+        assert_eq!(url_host("https://youtu.be?t=1"), "youtu.be");
+        assert!(host_matches("OLD.REDDIT.COM", &["reddit.com"]));
+        assert!(host_matches("reddit.com", &["reddit.com"]));
+        assert!(!host_matches("notreddit.com", &["reddit.com"]));
+        assert!(!host_matches("com", &["reddit.com"]));
+        assert!(!host_matches("example.com", &["reddit.com"]));
+        assert!(host_matches("example.com", &[]));
+    }
+
+    /// Provider for `docs.rs` that counts its `matches` calls and never matches
+    struct CountingProvider(Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl SiteProvider for CountingProvider {
+        fn name(&self) -> &'static str {
+            "counting"
+        }
+
+        fn hosts(&self) -> &'static [&'static str] {
+            &["docs.rs"]
+        }
+
+        fn matches(&self, _url: &str) -> bool {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            false
+        }
+
+        async fn extract(&self, _url: &str, _client: &AcceleratedClient) -> Result<SiteContent> {
+            unreachable!("never matches")
+        }
+    }
+
+    #[test]
+    fn prefiltered_routing_matches_full_scan_without_recompiling() {
+        use crate::plugin::runner::PATTERN_COMPILATIONS;
+        use crate::plugin::{PluginConfig, PluginRunner};
+
+        let compilations = || PATTERN_COMPILATIONS.with(std::cell::Cell::get);
+        let before = compilations();
+        let plugin = PluginRunner::new(PluginConfig {
+            name: "docs".to_string(),
+            binary: std::path::PathBuf::from("/nonexistent"),
+            patterns: vec![r"docs\.rs/[^/]+/latest".to_string()],
+        })
+        .unwrap();
+        // The counter sees the plugin's one pattern
+        assert_eq!(compilations(), before + 1);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let router = SiteRouter::with_providers(vec![
+            Box::new(CountingProvider(Arc::clone(&calls))),
+            Box::new(plugin),
+        ]);
+
+        let urls = [
+            "https://x.com/naval/status/1234567890",
+            "https://mobile.twitter.com/user/status/42",
+            "https://www.reddit.com/r/rust/comments/abc123/title",
+            "https://news.ycombinator.com/item?id=38471822",
+            "https://GITHUB.COM/owner/repo/ISSUES/999",
+            "https://not-github.com/owner/repo/issues/1",
+            "https://www.instagram.com/reel/XYZ789abc",
+            "https://youtu.be/dQw4w9WgXcQ",
+            "https://de.wikipedia.org/wiki/Rust",
+            "https://stackoverflow.com/questions/26946646/how",
+            "https://hachyderm.io/@user/109876543210",
+            "https://www.linkedin.com/pulse/some-article",
+            "https://docs.rs/tokio/latest/tokio/",
+            "https://example.com/status/fake",
+            "https://x.com/naval",
+            "https://arxiv.org/abs/2301.12345",
+        ];
+        // Routing agrees with a full scan without the host prefilter...
+        let mut expected: Vec<_> = urls
+            .iter()
+            .map(|url| {
+                router
+                    .providers
+                    .iter()
+                    .find(|provider| provider.matches(url))
+                    .map(|provider| provider.name())
+            })
+            .collect();
+        calls.store(0, Ordering::Relaxed);
+        // ...except that the prefilter also turns away look-alike hosts the
+        // GitHub provider's substring match would take
+        assert_eq!(expected[5], Some("github"));
+        expected[5] = None;
+
+        let compiled = compilations();
+        for i in 0..1000 {
+            let url = urls[i % urls.len()];
+            assert_eq!(
+                router.provider_for(url).map(SiteProvider::name),
+                expected[i % urls.len()],
+                "{url}"
+            );
+        }
+
+        assert_eq!(expected[12], Some("docs"));
+        assert_eq!(compilations(), compiled);
+        // Only the docs.rs URLs got past the prefilter to the counting provider
+        let docs_urls = (0..1000).filter(|i| i % urls.len() == 12).count();
+        assert_eq!(calls.load(Ordering::Relaxed), docs_urls);
+    }
+
     #[test]
     fn router_matches_twitter_urls() {
         let router = SiteRouter::new();
@@ -39,6 +39,10 @@ impl SiteProvider for RedditProvider {
         "reddit"
     }
 
+    fn hosts(&self) -> &'static [&'static str] {
+        &["reddit.com"]
+    }
+
     fn matches(&self, url: &str) -> bool {
         let normalized = url.to_lowercase();
         let normalized = normalized.split('?').next().unwrap_or(&normalized);
@@ -109,6 +113,7 @@ impl SiteProvider for RedditProvider {
             canonical_url: post_data.data.url.clone(),
             media_urls: vec![],
             engagement: Some(engagement),
+            paywalled: false,
         };
 
         Ok(SiteContent { markdown, metadata })
@@ -39,6 +39,10 @@ impl SiteProvider for StackOverflowProvider {
         "stackoverflow"
     }
 
+    fn hosts(&self) -> &'static [&'static str] {
+        &["stackoverflow.com"]
+    }
+
     fn matches(&self, url: &str) -> bool {
         let normalized = url.to_lowercase();
         let normalized = normalized.split('?').next().unwrap_or(&normalized);
@@ -106,6 +110,7 @@ impl SiteProvider for StackOverflowProvider {
             canonical_url: question.link.clone(),
             media_urls: vec![],
             engagement: Some(engagement),
+            paywalled: false,
         };
 
         Ok(SiteContent { markdown, metadata })
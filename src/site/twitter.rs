@@ -42,6 +42,10 @@ impl SiteProvider for TwitterProvider {
         "twitter"
     }
 
+    fn hosts(&self) -> &'static [&'static str] {
+        &["x.com", "twitter.com"]
+    }
+
     fn matches(&self, url: &str) -> bool {
         let normalized = url.to_lowercase();
         let normalized = normalized.split('?').next().unwrap_or(&normalized);
@@ -92,6 +96,7 @@ impl SiteProvider for TwitterProvider {
                 .map(|m| m.all.iter().filter_map(|item| item.url.clone()).collect())
                 .unwrap_or_default(),
             engagement: Some(engagement),
+            paywalled: false,
         };
 
         Ok(SiteContent { markdown, metadata })
@@ -39,6 +39,10 @@ impl SiteProvider for WikipediaProvider {
         "wikipedia"
     }
 
+    fn hosts(&self) -> &'static [&'static str] {
+        &["wikipedia.org"]
+    }
+
     fn matches(&self, url: &str) -> bool {
         let normalized = url.to_lowercase();
         let normalized = normalized.split('?').next().unwrap_or(&normalized);
@@ -91,6 +95,7 @@ impl SiteProvider for WikipediaProvider {
                 .map(|t| vec![t.source.clone()])
                 .unwrap_or_default(),
             engagement: None,
+            paywalled: false,
         };
 
         Ok(SiteContent { markdown, metadata })
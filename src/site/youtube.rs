@@ -39,6 +39,10 @@ impl SiteProvider for YouTubeProvider {
         "youtube"
     }
 
+    fn hosts(&self) -> &'static [&'static str] {
+        &["youtube.com", "youtu.be"]
+    }
+
     fn matches(&self, url: &str) -> bool {
         let normalized = url.to_lowercase();
         let normalized = normalized.split('?').next().unwrap_or(&normalized);
@@ -72,6 +76,7 @@ impl SiteProvider for YouTubeProvider {
             canonical_url: url.to_string(),
             media_urls: vec![oembed.thumbnail_url.clone()],
             engagement: None,
+            paywalled: false,
         };
 
         Ok(SiteContent { markdown, metadata })
@@ -0,0 +1,596 @@
+//! Embedded SPA data extraction
+//!
+//! Client-rendered pages ship their state as JSON: framework globals such as
+//! `window.__NEXT_DATA__` and `<script type="application/json">` islands.
+//! These helpers pull that data out of the raw HTML without running any
+//! JavaScript.
+
+use scraper::{Html, Selector};
+use thiserror::Error;
+use tracing::instrument;
+
+/// JSON embedded in a `<script type="application/json">` or
+/// `<script type="application/ld+json">` block
+#[derive(Debug, Clone)]
+pub struct JsonIsland {
+    /// Element `id`, else `data-url` (SvelteKit), else the script type
+    pub label: String,
+    /// Script type (`application/json` or `application/ld+json`)
+    pub kind: &'static str,
+    pub data: serde_json::Value,
+}
+
+/// Collect every parseable JSON data island in document order.
+#[must_use]
+pub fn extract_json_islands(html: &str) -> Vec<JsonIsland> {
+    let document = Html::parse_document(html);
+    let Ok(selector) = Selector::parse("script[type]") else {
+        return Vec::new();
+    };
+
+    document
+        .select(&selector)
+        .filter_map(|script| {
+            let element = script.value();
+            let script_type = element.attr("type")?;
+            let mime = script_type.split(';').next()?.trim().to_ascii_lowercase();
+            let kind = match mime.as_str() {
+                "application/json" => "application/json",
+                "application/ld+json" => "application/ld+json",
+                _ => return None,
+            };
+            let content = script.text().collect::<String>();
+            let data = serde_json::from_str(content.trim()).ok()?;
+            let label = element
+                .attr("id")
+                .or_else(|| element.attr("data-url"))
+                .unwrap_or(kind)
+                .to_string();
+            Some(JsonIsland { label, kind, data })
+        })
+        .collect()
+}
+
+/// A framework global was found but its JSON couldn't be parsed, even after
+/// repair
+#[derive(Debug, Clone, Error)]
+#[error(
+    "`{var_name}` is not valid JSON: {message} at line {line}, column {column}, near `{snippet}`"
+)]
+pub struct SpaJsonError {
+    pub var_name: String,
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+    /// Text around the error position
+    pub snippet: String,
+}
+
+/// Extract the JSON assigned to a framework global such as `__NEXT_DATA__`
+///
+/// Like [`try_extract_script_json`], but a payload that can't be parsed is
+/// logged and treated as absent.
+#[must_use]
+pub fn extract_script_json(html: &str, var_name: &str) -> Option<serde_json::Value> {
+    try_extract_script_json(html, var_name)
+        .inspect_err(|e| tracing::warn!("{e}"))
+        .ok()
+        .flatten()
+}
+
+/// Extract the JSON assigned to a framework global such as `__NEXT_DATA__`
+///
+/// Looks at every `<script id="…">` block and every `window.… =` and
+/// `self.… =` assignment. Pages sometimes assign an empty placeholder before
+/// the real state, so when several parse, the largest one wins.
+///
+/// JavaScript-isms that aren't JSON (a trailing `;`, `undefined`,
+/// `new Date(…)`, trailing commas) are repaired before giving up. Returns
+/// `Ok(None)` when the global isn't on the page and an error when it is but
+/// nothing parses.
+#[instrument(level = "debug", name = "spa_extract", skip(html), fields(bytes = html.len()))]
+pub fn try_extract_script_json(
+    html: &str,
+    var_name: &str,
+) -> Result<Option<serde_json::Value>, SpaJsonError> {
+    let document = Html::parse_document(html);
+    let mut sources = Vec::new();
+
+    if let Ok(id_selector) = Selector::parse(&format!("script#{var_name}")) {
+        sources.extend(
+            document
+                .select(&id_selector)
+                .map(|script| script.text().collect::<String>()),
+        );
+    }
+
+    // window.__VAR__ = … and self.__VAR__ = … (some frameworks)
+    for object in ["window", "self"] {
+        let pattern = format!("{object}.{var_name}");
+        sources.extend(
+            html.match_indices(&pattern)
+                .filter_map(|(idx, _)| assigned_json(&html[idx + pattern.len()..]))
+                .map(str::to_string),
+        );
+    }
+
+    let mut first_error = None;
+    let mut candidates = Vec::new();
+    for source in &sources {
+        match parse_lenient(source) {
+            Ok(value) => candidates.push(value),
+            Err(e) => {
+                first_error.get_or_insert_with(|| json_error(var_name, source, &e));
+            }
+        }
+    }
+
+    // `rev` so the earliest of equally large candidates wins
+    let richest = candidates
+        .into_iter()
+        .rev()
+        .max_by_key(|value| serde_json::to_string(value).map_or(0, |json| json.len()));
+    match (richest, first_error) {
+        (Some(value), _) => Ok(Some(value)),
+        (None, Some(error)) => Err(error),
+        (None, None) => Ok(None),
+    }
+}
+
+/// JSON literal assigned right after a global's name (`= {…}`)
+fn assigned_json(rest: &str) -> Option<&str> {
+    let rest = rest.trim_start().strip_prefix('=')?;
+    if rest.starts_with('=') {
+        return None;
+    }
+    let json_start = rest.find(['{', '['])?;
+    extract_json_object(&rest[json_start..])
+}
+
+/// Parse `source` as JSON, retrying once with [`repair_json`]
+///
+/// The error is from the original text so its position matches the page.
+fn parse_lenient(source: &str) -> serde_json::Result<serde_json::Value> {
+    serde_json::from_str(source)
+        .or_else(|e| serde_json::from_str(&repair_json(source)).map_err(|_| e))
+}
+
+/// Rewrite common JavaScript object-literal syntax into JSON
+fn repair_json(source: &str) -> String {
+    let source = source.trim().trim_end_matches(';').trim_end();
+    let mut out = String::with_capacity(source.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut i = 0;
+
+    while let Some(c) = source[i..].chars().next() {
+        let rest = &source[i..];
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if let Some(after) = rest
+            .strip_prefix("undefined")
+            .filter(|after| !after.starts_with(|c: char| c.is_alphanumeric() || c == '_'))
+        {
+            out.push_str("null");
+            i = source.len() - after.len();
+            continue;
+        } else if let Some((arg, after)) = rest
+            .strip_prefix("new Date(")
+            .and_then(|after| after.split_once(')'))
+        {
+            // Keep the date's string or number argument
+            let arg = arg.trim();
+            out.push_str(if arg.is_empty() { "null" } else { arg });
+            i = source.len() - after.len();
+            continue;
+        } else if c == ',' && rest[1..].trim_start().starts_with(['}', ']']) {
+            i += 1;
+            continue;
+        }
+        out.push(c);
+        i += c.len_utf8();
+    }
+    out
+}
+
+fn json_error(var_name: &str, source: &str, error: &serde_json::Error) -> SpaJsonError {
+    const CONTEXT: usize = 30;
+
+    let (line, column) = (error.line(), error.column());
+    let line_text = source
+        .lines()
+        .nth(line.saturating_sub(1))
+        .unwrap_or_default();
+    let chars: Vec<char> = line_text.chars().collect();
+    let at = column.saturating_sub(1).min(chars.len());
+    let snippet: String = chars[at.saturating_sub(CONTEXT)..(at + CONTEXT).min(chars.len())]
+        .iter()
+        .collect();
+    let message = error.to_string();
+    let message = message
+        .split(" at line ")
+        .next()
+        .unwrap_or(&message)
+        .to_string();
+
+    SpaJsonError {
+        var_name: var_name.to_string(),
+        message,
+        line,
+        column,
+        snippet: snippet.trim().to_string(),
+    }
+}
+
+/// Framework globals looked up by name, with the framework they belong to
+const SPA_PATTERNS: &[(&str, &str)] = &[
+    ("__NEXT_DATA__", "Next.js"),
+    ("__NUXT__", "Nuxt.js"),
+    ("__INITIAL_STATE__", "Redux/Vuex"),
+    ("__APOLLO_STATE__", "Apollo GraphQL"),
+    ("__RELAY_STORE__", "Relay"),
+    ("__PRELOADED_STATE__", "Redux"),
+];
+
+/// Array lengths and depths tried, in order, until a section fits its cap
+const SHRINK_STEPS: &[(usize, usize)] = &[
+    (usize::MAX, usize::MAX),
+    (100, usize::MAX),
+    (50, usize::MAX),
+    (20, usize::MAX),
+    (10, 12),
+    (5, 8),
+    (3, 6),
+    (2, 4),
+    (1, 3),
+    (1, 2),
+    (1, 1),
+];
+
+/// Size caps for [`extract_spa_data_inline`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpaLimits {
+    /// Maximum pretty-printed JSON bytes per section
+    pub max_section_bytes: usize,
+    /// Maximum JSON bytes across all sections
+    pub max_total_bytes: usize,
+}
+
+impl Default for SpaLimits {
+    fn default() -> Self {
+        Self {
+            max_section_bytes: 8192,
+            max_total_bytes: 32768,
+        }
+    }
+}
+
+impl SpaLimits {
+    /// Cap each section at `bytes`
+    #[must_use]
+    pub fn with_max_section_bytes(mut self, bytes: usize) -> Self {
+        self.max_section_bytes = bytes;
+        self
+    }
+
+    /// Cap all sections together at `bytes`
+    #[must_use]
+    pub fn with_max_total_bytes(mut self, bytes: usize) -> Self {
+        self.max_total_bytes = bytes;
+        self
+    }
+}
+
+/// Render every known SPA global and JSON island as markdown sections
+///
+/// Returns `None` when the page embeds no data. Payloads over
+/// [`SpaLimits::max_section_bytes`] are shrunk with [`transform_json`]
+/// (shorter arrays, then shallower nesting) so the output stays valid JSON;
+/// sections that still don't fit, or that would exceed
+/// [`SpaLimits::max_total_bytes`], are listed but omitted.
+#[must_use]
+pub fn extract_spa_data_inline(html: &str, limits: &SpaLimits) -> Option<String> {
+    let globals = SPA_PATTERNS.iter().filter_map(|(var_name, framework)| {
+        extract_script_json(html, var_name)
+            .map(|data| (format!("### {framework} (`{var_name}`)"), data))
+    });
+    let islands = extract_json_islands(html)
+        .into_iter()
+        .filter(|island| !SPA_PATTERNS.iter().any(|(name, _)| *name == island.label))
+        .map(|island| {
+            (
+                format!("### {} (`{}`)", island.kind, island.label),
+                island.data,
+            )
+        });
+
+    let mut sections = Vec::new();
+    let mut total = 0;
+    for (heading, data) in globals.chain(islands) {
+        let Some((json, trimmed)) = fit_json(&data, limits.max_section_bytes) else {
+            sections.push(format!(
+                "{heading}\n\n_Omitted: over the {} byte section limit_",
+                limits.max_section_bytes
+            ));
+            continue;
+        };
+        if total + json.len() > limits.max_total_bytes {
+            sections.push(format!(
+                "{heading}\n\n_Omitted: over the {} byte total limit_",
+                limits.max_total_bytes
+            ));
+            continue;
+        }
+        total += json.len();
+        let note = if trimmed {
+            "\n\n_Trimmed to fit the size limit_"
+        } else {
+            ""
+        };
+        sections.push(format!("{heading}\n\n```json\n{json}\n```{note}"));
+    }
+
+    if sections.is_empty() {
+        None
+    } else {
+        Some(sections.join("\n\n"))
+    }
+}
+
+/// Pretty-print `data`, shrinking it until it fits in `max_bytes`
+///
+/// Returns the JSON and whether it had to be shrunk.
+fn fit_json(data: &serde_json::Value, max_bytes: usize) -> Option<(String, bool)> {
+    SHRINK_STEPS
+        .iter()
+        .enumerate()
+        .find_map(|(step, &(max_array, max_depth))| {
+            let value = if step == 0 {
+                data.clone()
+            } else {
+                transform_json(data, max_array, max_depth, 0)
+            };
+            let json = serde_json::to_string_pretty(&value).ok()?;
+            (json.len() <= max_bytes).then_some((json, step > 0))
+        })
+}
+
+/// Limit arrays to `max_array` items and nesting to `max_depth` levels
+///
+/// Cut arrays end with a `"... +N more"` marker and values below the depth
+/// limit become `"[depth limit]"`, so the result is always valid JSON.
+#[must_use]
+pub fn transform_json(
+    value: &serde_json::Value,
+    max_array: usize,
+    max_depth: usize,
+    depth: usize,
+) -> serde_json::Value {
+    if depth >= max_depth {
+        return serde_json::Value::String("[depth limit]".to_string());
+    }
+
+    match value {
+        serde_json::Value::Array(arr) => {
+            let limited: Vec<serde_json::Value> = arr
+                .iter()
+                .take(max_array)
+                .map(|v| transform_json(v, max_array, max_depth, depth + 1))
+                .collect();
+            if arr.len() > max_array {
+                let mut result = limited;
+                result.push(serde_json::Value::String(format!(
+                    "... +{} more",
+                    arr.len() - max_array
+                )));
+                serde_json::Value::Array(result)
+            } else {
+                serde_json::Value::Array(limited)
+            }
+        }
+        serde_json::Value::Object(obj) => {
+            let transformed: serde_json::Map<String, serde_json::Value> = obj
+                .iter()
+                .map(|(k, v)| {
+                    (
+                        k.clone(),
+                        transform_json(v, max_array, max_depth, depth + 1),
+                    )
+                })
+                .collect();
+            serde_json::Value::Object(transformed)
+        }
+        _ => value.clone(),
+    }
+}
+
+fn extract_json_object(s: &str) -> Option<&str> {
+    let first_char = s.chars().next()?;
+    let (open, close) = match first_char {
+        '{' => ('{', '}'),
+        '[' => ('[', ']'),
+        _ => return None,
+    };
+
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escape_next = false;
+
+    for (i, c) in s.char_indices() {
+        if escape_next {
+            escape_next = false;
+            continue;
+        }
+
+        match c {
+            '\\' if in_string => escape_next = true,
+            '"' => in_string = !in_string,
+            _ if in_string => {}
+            c if c == open => depth += 1,
+            c if c == close => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&s[..=i]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_json_data_islands_with_ids() {
+        let html = r#"<html><head>
+<script type="application/json" id="remix-context">{"routeData":{"root":{"user":"ada"}}}</script>
+<script type="application/ld+json" id="product-schema">{"@type":"Product","name":"Lamp"}</script>
+<script type="text/javascript">var x = {"not": "an island"};</script>
+<script type="application/json" id="broken">{not json</script>
+</head><body></body></html>"#;
+
+        let islands = extract_json_islands(html);
+
+        assert_eq!(islands.len(), 2);
+        assert_eq!(islands[0].label, "remix-context");
+        assert_eq!(islands[0].kind, "application/json");
+        assert_eq!(islands[0].data["routeData"]["root"]["user"], "ada");
+        assert_eq!(islands[1].label, "product-schema");
+        assert_eq!(islands[1].kind, "application/ld+json");
+        assert_eq!(islands[1].data["name"], "Lamp");
+
+        let inline = extract_spa_data_inline(html, &SpaLimits::default()).unwrap();
+        assert!(inline.contains("### application/json (`remix-context`)"));
+        assert!(inline.contains("### application/ld+json (`product-schema`)"));
+    }
+
+    #[test]
+    fn unlabelled_islands_fall_back_to_data_url_or_type() {
+        let html = r#"<script type="application/json" data-sveltekit-fetched data-url="/api/items">[1,2]</script>
+<script type="application/ld+json">{"@type":"Organization"}</script>"#;
+
+        let labels: Vec<_> = extract_json_islands(html)
+            .into_iter()
+            .map(|island| island.label)
+            .collect();
+        assert_eq!(labels, ["/api/items", "application/ld+json"]);
+    }
+
+    /// Pull the JSON out of the `heading` section's code block
+    fn section_json(inline: &str, heading: &str) -> serde_json::Value {
+        let start = inline.find(heading).unwrap();
+        let block = &inline[start..];
+        let open = block.find("```json\n").unwrap() + "```json\n".len();
+        let close = open + block[open..].find("\n```").unwrap();
+        serde_json::from_str(&block[open..close]).unwrap()
+    }
+
+    #[test]
+    fn large_next_data_is_trimmed_to_valid_json() {
+        let items: Vec<_> = (0..500)
+            .map(|i| serde_json::json!({"id": i, "title": format!("Item number {i}"), "tags": ["a", "b"]}))
+            .collect();
+        let payload = serde_json::json!({"props": {"pageProps": {"items": items, "page": 1}}});
+        let html =
+            format!(r#"<script id="__NEXT_DATA__" type="application/json">{payload}</script>"#);
+        let limits = SpaLimits::default().with_max_section_bytes(4096);
+
+        let inline = extract_spa_data_inline(&html, &limits).unwrap();
+
+        assert!(inline.contains("_Trimmed to fit the size limit_"));
+        let json = section_json(&inline, "### Next.js (`__NEXT_DATA__`)");
+        assert!(serde_json::to_string_pretty(&json).unwrap().len() <= 4096);
+        let kept = json["props"]["pageProps"]["items"].as_array().unwrap();
+        assert_eq!(kept[0]["title"], "Item number 0");
+        assert!(kept.last().unwrap().as_str().unwrap().starts_with("... +"));
+        assert_eq!(json["props"]["pageProps"]["page"], 1);
+
+        // The default cap keeps more of the same payload
+        let roomy = extract_spa_data_inline(&html, &SpaLimits::default()).unwrap();
+        let more = section_json(&roomy, "### Next.js (`__NEXT_DATA__`)");
+        assert!(
+            more["props"]["pageProps"]["items"]
+                .as_array()
+                .unwrap()
+                .len()
+                > kept.len()
+        );
+    }
+
+    #[test]
+    fn total_limit_omits_later_sections() {
+        let html = r#"<script id="first" type="application/json">{"a": "xxxxxxxxxxxxxxxxxxxxxxxx"}</script>
+<script id="second" type="application/json">{"b": "yyyyyyyyyyyyyyyyyyyyyyyy"}</script>"#;
+        let limits = SpaLimits::default().with_max_total_bytes(50);
+
+        let inline = extract_spa_data_inline(html, &limits).unwrap();
+
+        assert_eq!(
+            section_json(&inline, "(`first`)")["a"],
+            "xxxxxxxxxxxxxxxxxxxxxxxx"
+        );
+        assert!(inline.contains("(`second`)\n\n_Omitted: over the 50 byte total limit_"));
+        assert!(!inline.contains("yyyy"));
+    }
+
+    #[test]
+    fn picks_the_richest_of_repeated_globals() {
+        let html = r#"<script>window.__INITIAL_STATE__ = {};</script>
+<script>if (window.__INITIAL_STATE__ === undefined) {}</script>
+<script>window.__INITIAL_STATE__ = {"user": {"name": "ada"}, "cart": [1, 2]};</script>"#;
+
+        let state = extract_script_json(html, "__INITIAL_STATE__").unwrap();
+
+        assert_eq!(state["user"]["name"], "ada");
+        assert_eq!(state["cart"], serde_json::json!([1, 2]));
+        assert_eq!(extract_script_json(html, "__NUXT__"), None);
+    }
+
+    #[test]
+    fn repairs_javascript_literals() {
+        let html = r#"<script id="__NEXT_DATA__" type="application/json">{"page": "/", "build": undefined, "at": new Date("2024-05-01"), "ids": [1, 2,],};</script>"#;
+
+        let data = try_extract_script_json(html, "__NEXT_DATA__")
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(data["page"], "/");
+        assert!(data["build"].is_null());
+        assert_eq!(data["at"], "2024-05-01");
+        assert_eq!(data["ids"], serde_json::json!([1, 2]));
+
+        // Strings are left alone
+        let html = r#"<script>window.__NUXT__ = {"note": "undefined, new Date(1),]"};</script>"#;
+        let data = try_extract_script_json(html, "__NUXT__").unwrap().unwrap();
+        assert_eq!(data["note"], "undefined, new Date(1),]");
+    }
+
+    #[test]
+    fn unrepairable_payload_is_reported() {
+        let html = r#"<script>window.__INITIAL_STATE__ = {"user": {"name": getName()}};</script>"#;
+
+        let error = try_extract_script_json(html, "__INITIAL_STATE__").unwrap_err();
+
+        assert_eq!(error.var_name, "__INITIAL_STATE__");
+        assert_eq!((error.line, error.column), (1, 19));
+        assert!(error.snippet.contains("getName()"));
+        let message = error.to_string();
+        assert!(message.starts_with("`__INITIAL_STATE__` is not valid JSON: expected value"));
+        assert!(message.contains("line 1, column 19"));
+
+        assert_eq!(extract_script_json(html, "__INITIAL_STATE__"), None);
+        assert_eq!(try_extract_script_json(html, "__NUXT__").unwrap(), None);
+    }
+}
@@ -0,0 +1,250 @@
+//! Server-Sent Events (`text/event-stream`) parsing
+//!
+//! [`SseParser`] is fed raw body chunks as they arrive and yields complete
+//! [`SseEvent`]s, following the WHATWG event-stream interpretation rules:
+//! `data:` lines are joined with `\n`, `:` lines are comments, and an event
+//! is dispatched on a blank line.
+//!
+//! Memory is bounded: a line or event larger than the parser's maximum size
+//! is dropped rather than buffered.
+
+use serde::Serialize;
+
+/// Default for [`SseParser::with_max_event_size`]
+pub const DEFAULT_MAX_EVENT_SIZE: usize = 1024 * 1024;
+
+/// One dispatched event from a `text/event-stream` body
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SseEvent {
+    /// Event type (`event:` field), `"message"` when unset
+    pub event: String,
+    /// Event payload; multiple `data:` lines are joined with `\n`
+    pub data: String,
+    /// Last event ID seen on the stream (`id:` field)
+    pub id: Option<String>,
+    /// Reconnection time in milliseconds last set by the server (`retry:` field)
+    pub retry: Option<u64>,
+}
+
+/// Incremental `text/event-stream` parser
+#[derive(Debug)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+    event: Option<String>,
+    data: Option<String>,
+    last_id: Option<String>,
+    retry: Option<u64>,
+    started: bool,
+    max_size: usize,
+    /// The rest of the current line belongs to one that was too long
+    skip_line: bool,
+    /// The current event outgrew `max_size` and won't be dispatched
+    oversized: bool,
+    dropped: usize,
+}
+
+impl Default for SseParser {
+    fn default() -> Self {
+        Self {
+            buffer: Vec::new(),
+            event: None,
+            data: None,
+            last_id: None,
+            retry: None,
+            started: false,
+            max_size: DEFAULT_MAX_EVENT_SIZE,
+            skip_line: false,
+            oversized: false,
+            dropped: 0,
+        }
+    }
+}
+
+impl SseParser {
+    /// Create an empty parser
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop lines and events larger than `bytes` instead of buffering them
+    /// (default [`DEFAULT_MAX_EVENT_SIZE`])
+    #[must_use]
+    pub fn with_max_event_size(mut self, bytes: usize) -> Self {
+        self.max_size = bytes;
+        self
+    }
+
+    /// Events dropped so far for exceeding the maximum size
+    #[must_use]
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Consume a body chunk, returning the events it completed
+    ///
+    /// Lines may end in `\n`, `\r\n` or `\r`; a partial line is kept until
+    /// the next chunk arrives, unless it outgrows the maximum size.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+        if !self.started && self.buffer.len() >= 3 {
+            if self.buffer.starts_with(b"\xEF\xBB\xBF") {
+                self.buffer.drain(..3);
+            }
+            self.started = true;
+        }
+
+        let mut events = Vec::new();
+        let mut start = 0;
+        while let Some(offset) = self.buffer[start..]
+            .iter()
+            .position(|&b| b == b'\n' || b == b'\r')
+        {
+            let end = start + offset;
+            let next = match self.buffer[end] {
+                b'\r' if end + 1 == self.buffer.len() => break, // may be half of "\r\n"
+                b'\r' if self.buffer[end + 1] == b'\n' => end + 2,
+                _ => end + 1,
+            };
+            if !std::mem::take(&mut self.skip_line) {
+                let line = String::from_utf8_lossy(&self.buffer[start..end]).into_owned();
+                if let Some(event) = self.process_line(&line) {
+                    events.push(event);
+                }
+            }
+            start = next;
+        }
+        self.buffer.drain(..start);
+
+        // A line too long to keep is dropped up to its end, with its event
+        if self.buffer.len() > self.max_size {
+            self.buffer.clear();
+            self.skip_line = true;
+            self.oversized = true;
+        }
+        events
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            return self.dispatch();
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event = Some(value.to_string()),
+            "data" if self.oversized => {}
+            "data" => {
+                let size = self.data.as_ref().map_or(0, |data| data.len() + 1) + value.len();
+                if size > self.max_size {
+                    self.oversized = true;
+                    self.data = None;
+                } else if let Some(data) = &mut self.data {
+                    data.push('\n');
+                    data.push_str(value);
+                } else {
+                    self.data = Some(value.to_string());
+                }
+            }
+            "id" if !value.contains('\0') => self.last_id = Some(value.to_string()),
+            "retry" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+                self.retry = value.parse().ok();
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn dispatch(&mut self) -> Option<SseEvent> {
+        let event = self.event.take();
+        let data = self.data.take();
+        if std::mem::take(&mut self.oversized) {
+            self.dropped += 1;
+            return None;
+        }
+        let data = data?;
+        Some(SseEvent {
+            event: event
+                .filter(|e| !e.is_empty())
+                .unwrap_or_else(|| "message".to_string()),
+            data,
+            id: self.last_id.clone(),
+            retry: self.retry,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(chunks: &[&str]) -> Vec<SseEvent> {
+        let mut parser = SseParser::new();
+        chunks
+            .iter()
+            .flat_map(|chunk| parser.feed(chunk.as_bytes()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_fields_and_joins_multiline_data() {
+        let events = parse(&[
+            ": keep-alive\n\nevent: update\nid: 7\nretry: 3000\ndata: line one\ndata:line two\n\n",
+            "data: plain\n\n",
+        ]);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].event, "update");
+        assert_eq!(events[0].data, "line one\nline two");
+        assert_eq!(events[0].id.as_deref(), Some("7"));
+        assert_eq!(events[0].retry, Some(3000));
+        // The type resets per event; the last ID and retry persist
+        assert_eq!(events[1].event, "message");
+        assert_eq!(events[1].id.as_deref(), Some("7"));
+        assert_eq!(events[1].retry, Some(3000));
+    }
+
+    #[test]
+    fn handles_split_chunks_and_line_endings() {
+        let events = parse(&[
+            "\u{feff}da",
+            "ta: a\r",
+            "\n\r",
+            "\ndata: b\r\rdata",
+            ": c\n",
+        ]);
+        let data: Vec<_> = events.iter().map(|e| e.data.as_str()).collect();
+        assert_eq!(data, ["a", "b"]);
+    }
+
+    #[test]
+    fn ignores_events_without_data() {
+        assert!(parse(&["event: ping\n\nretry: x\n\n"]).is_empty());
+        // An unterminated event is never dispatched
+        assert!(parse(&["data: partial\n"]).is_empty());
+    }
+
+    #[test]
+    fn drops_oversized_lines_and_events() {
+        let mut parser = SseParser::new().with_max_event_size(16);
+        let mut events = parser.feed(b"data: ok\n\nid: 1\ndata: ");
+        // A line that never ends is not buffered past the limit
+        for _ in 0..100 {
+            events.extend(parser.feed(&[b'x'; 64]));
+            assert!(parser.buffer.len() <= 16);
+        }
+        events.extend(parser.feed(b"xx\n\ndata: after\n\n"));
+        // Short lines adding up to an oversized event
+        events.extend(parser.feed(b"data: 0123456789\ndata: 0123456789\n\ndata: end\n\n"));
+
+        let data: Vec<_> = events.iter().map(|e| e.data.as_str()).collect();
+        assert_eq!(data, ["ok", "after", "end"]);
+        assert_eq!(events[1].id.as_deref(), Some("1"));
+        assert_eq!(parser.dropped(), 2);
+    }
+}
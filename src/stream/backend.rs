@@ -9,7 +9,9 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use std::collections::HashMap;
+use std::sync::Arc;
 use tokio::io::AsyncWrite;
+use tokio::sync::watch;
 
 /// Identifies which backend implementation is in use.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -59,6 +61,44 @@ pub struct StreamProgress {
     pub elapsed_seconds: f64,
 }
 
+/// Request to stop a download early, keeping what was written playable.
+///
+/// Backends given a signal (via their `with_stop_signal` builders) stop at
+/// the next segment boundary, or ask ffmpeg to quit so it writes the
+/// container trailer. Clones share the same state.
+#[derive(Debug, Clone)]
+pub struct StopSignal(Arc<watch::Sender<bool>>);
+
+impl Default for StopSignal {
+    fn default() -> Self {
+        Self(Arc::new(watch::Sender::new(false)))
+    }
+}
+
+impl StopSignal {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask every holder of this signal to stop.
+    pub fn stop(&self) {
+        self.0.send_replace(true);
+    }
+
+    /// Whether [`stop`](Self::stop) has been called.
+    pub fn is_stopped(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Resolves once [`stop`](Self::stop) has been called.
+    pub async fn stopped(&self) {
+        let mut rx = self.0.subscribe();
+        // Cannot fail: the sender lives as long as `self`
+        let _ = rx.wait_for(|stopped| *stopped).await;
+    }
+}
+
 /// Trait for media streaming backends.
 ///
 /// Backends are responsible for fetching segments (or invoking an external
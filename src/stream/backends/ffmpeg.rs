@@ -5,27 +5,68 @@
 //! - Encrypted HLS (Widevine/AES)
 //! - Transcoding
 //! - Complex format handling
+//! - Technical metadata probing via ffprobe ([`FfmpegBackend::probe`])
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::Value;
 use std::path::Path;
 use std::process::Stdio;
 use tokio::io::{AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
-use tokio::process::Command;
+use tokio::process::{Child, ChildStdin, Command};
 use tracing::{debug, info, warn};
 
 use crate::stream::backend::{
-    BackendType, ProgressCallback, StreamBackend, StreamConfig, StreamProgress,
+    BackendType, ProgressCallback, StopSignal, StreamBackend, StreamConfig, StreamProgress,
 };
 
+/// How long `probe` waits for ffprobe before giving up
+const PROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Technical stream details reported by ffprobe
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StreamTechInfo {
+    /// Container/demuxer name (e.g. "hls", "dash", "mov,mp4,m4a,3gp,3g2,mj2")
+    pub container: Option<String>,
+    pub video_codec: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// Frames per second
+    pub fps: Option<f64>,
+    pub audio_codec: Option<String>,
+    pub audio_channels: Option<u32>,
+    /// Audio sample rate in Hz
+    pub audio_sample_rate: Option<u32>,
+    /// Overall bitrate in bits per second
+    pub bitrate_bps: Option<u64>,
+    pub duration_seconds: Option<f64>,
+}
+
+/// Parse an ffprobe rational frame rate ("30000/1001", "25/1")
+fn parse_frame_rate(value: &Value) -> Option<f64> {
+    let (num, den) = value.as_str()?.split_once('/')?;
+    let num: f64 = num.parse().ok()?;
+    let den: f64 = den.parse().ok()?;
+    (num > 0.0 && den > 0.0).then(|| num / den)
+}
+
 /// ffmpeg-based streaming backend
 pub struct FfmpegBackend {
     /// Path to ffmpeg binary
     ffmpeg_path: String,
+    /// Path to ffprobe binary (`None` when not installed)
+    ffprobe_path: Option<String>,
     /// Additional ffmpeg arguments
     extra_args: Vec<String>,
     /// Transcoding options (e.g., "-c:v libx265 -crf 28")
     transcode_opts: Option<String>,
+    /// Drop the video track and keep only audio
+    audio_only: bool,
+    /// Audio format to re-encode to in audio-only mode (`None` copies the source codec)
+    audio_format: Option<String>,
+    /// Asks ffmpeg to quit cleanly, writing the container trailer
+    stop: StopSignal,
 }
 
 impl FfmpegBackend {
@@ -36,10 +77,18 @@ impl FfmpegBackend {
             |p| p.to_string_lossy().to_string(),
         );
 
+        let ffprobe_path = which::which("ffprobe")
+            .ok()
+            .map(|p| p.to_string_lossy().to_string());
+
         Ok(Self {
             ffmpeg_path,
+            ffprobe_path,
             extra_args: Vec::new(),
             transcode_opts: None,
+            audio_only: false,
+            audio_format: None,
+            stop: StopSignal::new(),
         })
     }
 
@@ -50,6 +99,13 @@ impl FfmpegBackend {
         self
     }
 
+    /// Specify custom ffprobe binary path
+    #[must_use]
+    pub fn with_ffprobe_path(mut self, path: &str) -> Self {
+        self.ffprobe_path = Some(path.to_string());
+        self
+    }
+
     /// Enable transcoding (e.g., "-c:v libx265 -crf 28")
     #[must_use]
     pub fn with_transcode_opts(mut self, opts: &str) -> Self {
@@ -64,6 +120,50 @@ impl FfmpegBackend {
         self
     }
 
+    /// Extract only the audio track (`-vn`), optionally re-encoding it
+    /// (e.g. "mp3", "aac", "opus", "flac"). Without a format the source codec is copied.
+    #[must_use]
+    pub fn with_audio_only(mut self, format: Option<&str>) -> Self {
+        self.audio_only = true;
+        self.audio_format = format.map(str::to_ascii_lowercase);
+        self
+    }
+
+    /// Send ffmpeg `q` when `stop` fires, so it finalizes the container
+    /// (writes the trailer) and the partial output stays playable
+    #[must_use]
+    pub fn with_stop_signal(mut self, stop: StopSignal) -> Self {
+        self.stop = stop;
+        self
+    }
+
+    /// Map an audio format name to its ffmpeg encoder and output muxer
+    fn audio_codec(format: &str) -> (&str, &str) {
+        match format {
+            "mp3" => ("libmp3lame", "mp3"),
+            "aac" | "m4a" => ("aac", "adts"),
+            "opus" => ("libopus", "ogg"),
+            "vorbis" | "ogg" => ("libvorbis", "ogg"),
+            "flac" => ("flac", "flac"),
+            "wav" => ("pcm_s16le", "wav"),
+            other => (other, other),
+        }
+    }
+
+    /// `-headers` arguments for the configured HTTP headers (empty when none)
+    fn header_args(config: &StreamConfig) -> Vec<String> {
+        if config.headers.is_empty() {
+            return Vec::new();
+        }
+        let header_str = config
+            .headers
+            .iter()
+            .map(|(k, v)| format!("{k}: {v}"))
+            .collect::<Vec<_>>()
+            .join("\r\n");
+        vec!["-headers".to_string(), format!("{header_str}\r\n")]
+    }
+
     /// Build ffmpeg command arguments
     fn build_args(
         &self,
@@ -82,16 +182,7 @@ impl FfmpegBackend {
         );
 
         // Headers
-        if !config.headers.is_empty() {
-            let header_str = config
-                .headers
-                .iter()
-                .map(|(k, v)| format!("{k}: {v}"))
-                .collect::<Vec<_>>()
-                .join("\r\n");
-            args.push("-headers".to_string());
-            args.push(format!("{header_str}\r\n"));
-        }
+        args.extend(Self::header_args(config));
 
         // Duration limit for live streams
         if let Some(dur) = duration_secs {
@@ -128,10 +219,22 @@ impl FfmpegBackend {
         args.push("-i".to_string());
         args.push(manifest_url.to_string());
 
+        // Drop video (and subtitle/data) streams for audio extraction
+        if self.audio_only {
+            args.extend(
+                ["-vn", "-sn", "-dn"]
+                    .iter()
+                    .map(std::string::ToString::to_string),
+            );
+        }
+
         // Transcoding or copy
         if let Some(ref opts) = self.transcode_opts {
             // Parse transcode options
             args.extend(opts.split_whitespace().map(String::from));
+        } else if let Some(format) = self.audio_format.as_deref().filter(|_| self.audio_only) {
+            args.push("-c:a".to_string());
+            args.push(Self::audio_codec(format).0.to_string());
         } else {
             // Copy streams without re-encoding
             args.extend(["-c", "copy"].iter().map(std::string::ToString::to_string));
@@ -145,9 +248,15 @@ impl FfmpegBackend {
             args.push("-y".to_string()); // Overwrite
             args.push(path.to_string());
         } else {
-            // Output to stdout as MPEG-TS (streamable)
+            // Output to stdout as MPEG-TS (streamable), or the raw audio
+            // container when re-encoding audio
+            let muxer = self
+                .audio_format
+                .as_deref()
+                .filter(|_| self.audio_only)
+                .map_or("mpegts", |f| Self::audio_codec(f).1);
             args.extend(
-                ["-f", "mpegts", "pipe:1"]
+                ["-f", muxer, "pipe:1"]
                     .iter()
                     .map(std::string::ToString::to_string),
             );
@@ -168,6 +277,91 @@ impl FfmpegBackend {
             .unwrap_or(false)
     }
 
+    /// Probe codecs, resolution, bitrate and duration with ffprobe without downloading
+    pub async fn probe(&self, manifest_url: &str, config: &StreamConfig) -> Result<StreamTechInfo> {
+        let Some(ref ffprobe) = self.ffprobe_path else {
+            bail!("ffprobe not found in PATH");
+        };
+
+        let mut args: Vec<String> = [
+            "-v",
+            "error",
+            "-print_format",
+            "json",
+            "-show_format",
+            "-show_streams",
+        ]
+        .iter()
+        .map(std::string::ToString::to_string)
+        .collect();
+        args.extend(Self::header_args(config));
+        args.push(manifest_url.to_string());
+        debug!("ffprobe args: {:?}", args);
+
+        let output = tokio::time::timeout(
+            PROBE_TIMEOUT,
+            Command::new(ffprobe)
+                .args(&args)
+                .stdin(Stdio::null())
+                .kill_on_drop(true)
+                .output(),
+        )
+        .await
+        .map_err(|_| anyhow!("ffprobe timed out after {}s", PROBE_TIMEOUT.as_secs()))?
+        .context("Failed to run ffprobe")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            bail!("ffprobe failed: {}", stderr.trim());
+        }
+
+        Self::parse_probe_output(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Parse `ffprobe -print_format json -show_format -show_streams` output.
+    ///
+    /// HLS master playlists list every variant as its own stream; the
+    /// highest-resolution video stream and the first audio stream are reported.
+    fn parse_probe_output(json: &str) -> Result<StreamTechInfo> {
+        let probe: Value = serde_json::from_str(json).context("Invalid ffprobe JSON")?;
+        let streams = probe["streams"].as_array().map_or(&[][..], Vec::as_slice);
+        let format = &probe["format"];
+
+        let video = streams
+            .iter()
+            .filter(|s| s["codec_type"] == "video")
+            .max_by_key(|s| s["height"].as_u64().unwrap_or(0));
+        let audio = streams.iter().find(|s| s["codec_type"] == "audio");
+
+        let num = |v: &Value| -> Option<u64> {
+            v.as_u64()
+                .or_else(|| v.as_str().and_then(|s| s.parse().ok()))
+        };
+        let string = |v: &Value| v.as_str().map(String::from);
+        let small = |v: &Value| num(v).and_then(|n| u32::try_from(n).ok());
+
+        Ok(StreamTechInfo {
+            container: string(&format["format_name"]),
+            video_codec: video.and_then(|v| string(&v["codec_name"])),
+            width: video.and_then(|v| small(&v["width"])),
+            height: video.and_then(|v| small(&v["height"])),
+            fps: video.and_then(|v| {
+                parse_frame_rate(&v["avg_frame_rate"])
+                    .or_else(|| parse_frame_rate(&v["r_frame_rate"]))
+            }),
+            audio_codec: audio.and_then(|a| string(&a["codec_name"])),
+            audio_channels: audio.and_then(|a| small(&a["channels"])),
+            audio_sample_rate: audio.and_then(|a| small(&a["sample_rate"])),
+            bitrate_bps: num(&format["bit_rate"])
+                .or_else(|| video.and_then(|v| num(&v["tags"]["variant_bitrate"])))
+                .or_else(|| video.and_then(|v| num(&v["bit_rate"]))),
+            duration_seconds: format["duration"]
+                .as_str()
+                .and_then(|d| d.parse::<f64>().ok())
+                .filter(|d| *d > 0.0),
+        })
+    }
+
     /// Parse progress from ffmpeg stderr
     fn parse_progress(line: &str) -> Option<FfmpegProgress> {
         // ffmpeg progress format: "frame=  123 fps= 30 ... time=00:01:23.45 bitrate=1234.5kbits/s speed=1.5x"
@@ -220,23 +414,58 @@ impl FfmpegBackend {
 
         let mut child = Command::new(&self.ffmpeg_path)
             .args(&args)
+            .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()
             .context("Failed to spawn ffmpeg process")?;
 
+        self.copy_stdout(&mut child, output, progress.as_ref())
+            .await?;
+
+        let status = child.wait().await.context("Failed to wait for ffmpeg")?;
+
+        if !status.success() && !self.stop.is_stopped() {
+            // Duration limit often causes ffmpeg to exit with signal, which is ok
+            let code = status.code();
+            if code != Some(255) && code.is_some() {
+                return Err(anyhow!("ffmpeg exited with status: {status}"));
+            }
+        }
+
+        output.flush().await?;
+        Ok(())
+    }
+
+    /// Copy ffmpeg's stdout into `output` until it exits, asking it to quit
+    /// when the stop signal fires. Returns the number of bytes copied.
+    async fn copy_stdout<W: AsyncWrite + Unpin + Send>(
+        &self,
+        child: &mut Child,
+        output: &mut W,
+        progress: Option<&ProgressCallback>,
+    ) -> Result<u64> {
         let stdout = child
             .stdout
             .take()
             .ok_or_else(|| anyhow!("Failed to capture ffmpeg stdout"))?;
+        let mut stdin = child.stdin.take();
 
         let start_time = std::time::Instant::now();
         let mut stdout_reader = BufReader::new(stdout);
-        let mut buffer = [0u8; 64 * 1024];
+        let mut buffer = vec![0u8; 64 * 1024]; // 64KB buffer
         let mut total_bytes = 0u64;
+        let stopped = self.stop.stopped();
+        tokio::pin!(stopped);
 
         loop {
-            let n = stdout_reader.read(&mut buffer).await?;
+            let n = tokio::select! {
+                n = stdout_reader.read(&mut buffer) => n?,
+                () = &mut stopped, if stdin.is_some() => {
+                    Self::request_quit(&mut stdin).await;
+                    continue;
+                }
+            };
             if n == 0 {
                 break;
             }
@@ -244,7 +473,7 @@ impl FfmpegBackend {
             output.write_all(&buffer[..n]).await?;
             total_bytes += n as u64;
 
-            if let Some(ref cb) = progress {
+            if let Some(cb) = progress {
                 cb(StreamProgress {
                     bytes_downloaded: total_bytes,
                     segments_completed: 0,
@@ -253,19 +482,17 @@ impl FfmpegBackend {
                 });
             }
         }
+        Ok(total_bytes)
+    }
 
-        let status = child.wait().await.context("Failed to wait for ffmpeg")?;
-
-        if !status.success() {
-            // Duration limit often causes ffmpeg to exit with signal, which is ok
-            let code = status.code();
-            if code != Some(255) && code.is_some() {
-                return Err(anyhow!("ffmpeg exited with status: {status}"));
+    /// Ask ffmpeg to finish (`q` on stdin) so it writes the container trailer
+    async fn request_quit(stdin: &mut Option<ChildStdin>) {
+        if let Some(mut stdin) = stdin.take() {
+            info!("Stop requested, asking ffmpeg to finalize the output");
+            if let Err(e) = stdin.write_all(b"q").await {
+                debug!("ffmpeg stdin closed before quit: {e}");
             }
         }
-
-        output.flush().await?;
-        Ok(())
     }
 }
 
@@ -292,22 +519,17 @@ impl StreamBackend for FfmpegBackend {
 
         let mut child = Command::new(&self.ffmpeg_path)
             .args(&args)
+            .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()
             .context("Failed to spawn ffmpeg process")?;
 
-        let stdout = child
-            .stdout
-            .take()
-            .ok_or_else(|| anyhow!("Failed to capture ffmpeg stdout"))?;
         let stderr = child
             .stderr
             .take()
             .ok_or_else(|| anyhow!("Failed to capture ffmpeg stderr"))?;
 
-        let start_time = std::time::Instant::now();
-
         // Spawn stderr reader for progress
         let progress_active = progress.is_some();
         let stderr_handle = tokio::spawn(async move {
@@ -330,28 +552,9 @@ impl StreamBackend for FfmpegBackend {
         });
 
         // Copy stdout to output
-        let mut stdout_reader = BufReader::new(stdout);
-        let mut buffer = [0u8; 64 * 1024]; // 64KB buffer
-        let mut total_bytes = 0u64;
-
-        loop {
-            let n = stdout_reader.read(&mut buffer).await?;
-            if n == 0 {
-                break;
-            }
-
-            output.write_all(&buffer[..n]).await?;
-            total_bytes += n as u64;
-
-            if let Some(ref cb) = progress {
-                cb(StreamProgress {
-                    bytes_downloaded: total_bytes,
-                    segments_completed: 0,
-                    segments_total: None,
-                    elapsed_seconds: start_time.elapsed().as_secs_f64(),
-                });
-            }
-        }
+        let total_bytes = self
+            .copy_stdout(&mut child, output, progress.as_ref())
+            .await?;
 
         // Wait for process to complete
         let status = child
@@ -360,7 +563,7 @@ impl StreamBackend for FfmpegBackend {
             .context("Failed to wait for ffmpeg process")?;
         stderr_handle.abort(); // Stop stderr reader
 
-        if !status.success() {
+        if !status.success() && !self.stop.is_stopped() {
             return Err(anyhow!("ffmpeg exited with status: {status}"));
         }
 
@@ -384,6 +587,7 @@ impl StreamBackend for FfmpegBackend {
 
         let mut child = Command::new(&self.ffmpeg_path)
             .args(&args)
+            .stdin(Stdio::piped())
             .stdout(Stdio::null())
             .stderr(Stdio::piped())
             .spawn()
@@ -393,14 +597,27 @@ impl StreamBackend for FfmpegBackend {
             .stderr
             .take()
             .ok_or_else(|| anyhow!("Failed to capture ffmpeg stderr"))?;
+        let mut stdin = child.stdin.take();
 
         let start_time = std::time::Instant::now();
 
         // Read stderr for progress
         let reader = BufReader::new(stderr);
         let mut lines = tokio::io::AsyncBufReadExt::lines(reader);
+        let stopped = self.stop.stopped();
+        tokio::pin!(stopped);
 
-        while let Ok(Some(line)) = lines.next_line().await {
+        loop {
+            let line = tokio::select! {
+                line = lines.next_line() => line,
+                () = &mut stopped, if stdin.is_some() => {
+                    Self::request_quit(&mut stdin).await;
+                    continue;
+                }
+            };
+            let Ok(Some(line)) = line else {
+                break;
+            };
             if let Some(prog) = Self::parse_progress(&line) {
                 if let Some(ref cb) = progress {
                     cb(StreamProgress {
@@ -422,7 +639,7 @@ impl StreamBackend for FfmpegBackend {
             .await
             .context("Failed to wait for ffmpeg process")?;
 
-        if !status.success() {
+        if !status.success() && !self.stop.is_stopped() {
             return Err(anyhow!("ffmpeg exited with status: {status}"));
         }
 
@@ -459,8 +676,12 @@ mod tests {
     fn test_build_args_basic() {
         let backend = FfmpegBackend {
             ffmpeg_path: "ffmpeg".to_string(),
+            ffprobe_path: None,
             extra_args: vec![],
             transcode_opts: None,
+            audio_only: false,
+            audio_format: None,
+            stop: StopSignal::new(),
         };
 
         let config = StreamConfig {
@@ -482,8 +703,12 @@ mod tests {
     fn test_build_args_with_transcode() {
         let backend = FfmpegBackend {
             ffmpeg_path: "ffmpeg".to_string(),
+            ffprobe_path: None,
             extra_args: vec![],
             transcode_opts: Some("-c:v libx265 -crf 28".to_string()),
+            audio_only: false,
+            audio_format: None,
+            stop: StopSignal::new(),
         };
 
         let config = StreamConfig {
@@ -503,8 +728,12 @@ mod tests {
     fn test_build_args_with_headers() {
         let backend = FfmpegBackend {
             ffmpeg_path: "ffmpeg".to_string(),
+            ffprobe_path: None,
             extra_args: vec![],
             transcode_opts: None,
+            audio_only: false,
+            audio_format: None,
+            stop: StopSignal::new(),
         };
 
         let mut headers = HashMap::new();
@@ -531,8 +760,12 @@ mod tests {
     fn test_build_args_with_duration() {
         let backend = FfmpegBackend {
             ffmpeg_path: "ffmpeg".to_string(),
+            ffprobe_path: None,
             extra_args: vec![],
             transcode_opts: None,
+            audio_only: false,
+            audio_format: None,
+            stop: StopSignal::new(),
         };
 
         let config = StreamConfig::default();
@@ -543,6 +776,148 @@ mod tests {
         assert!(args.contains(&"3600".to_string()));
     }
 
+    #[test]
+    fn test_build_args_audio_only_mp3() {
+        let backend = FfmpegBackend::new().unwrap().with_audio_only(Some("mp3"));
+
+        let args = backend.build_args(
+            "https://example.com/master.m3u8",
+            &StreamConfig::default(),
+            None,
+            None,
+        );
+
+        assert!(args.contains(&"-vn".to_string()));
+        let codec_idx = args.iter().position(|a| a == "-c:a").unwrap();
+        assert_eq!(args[codec_idx + 1], "libmp3lame");
+        assert!(!args.contains(&"copy".to_string()));
+        let fmt_idx = args.iter().position(|a| a == "-f").unwrap();
+        assert_eq!(args[fmt_idx + 1], "mp3");
+    }
+
+    #[test]
+    fn test_build_args_audio_only_copy() {
+        let backend = FfmpegBackend::new().unwrap().with_audio_only(None);
+
+        let args = backend.build_args(
+            "https://example.com/master.m3u8",
+            &StreamConfig::default(),
+            Some("episode.m4a"),
+            None,
+        );
+
+        assert!(args.contains(&"-vn".to_string()));
+        assert!(args.contains(&"copy".to_string()));
+        assert!(!args.contains(&"-c:a".to_string()));
+    }
+
+    #[test]
+    fn test_parse_probe_output() {
+        let json = r#"{
+            "streams": [
+                {"index": 0, "codec_name": "h264", "codec_type": "video", "width": 640, "height": 360,
+                 "avg_frame_rate": "0/0", "r_frame_rate": "25/1", "tags": {"variant_bitrate": "800000"}},
+                {"index": 1, "codec_name": "h264", "codec_type": "video", "width": 1920, "height": 1080,
+                 "avg_frame_rate": "30000/1001", "r_frame_rate": "30000/1001", "tags": {"variant_bitrate": "5000000"}},
+                {"index": 2, "codec_name": "aac", "codec_type": "audio", "sample_rate": "48000", "channels": 2}
+            ],
+            "format": {"format_name": "hls", "duration": "1834.560000", "bit_rate": "5128000"}
+        }"#;
+
+        let info = FfmpegBackend::parse_probe_output(json).unwrap();
+
+        assert_eq!(info.container.as_deref(), Some("hls"));
+        assert_eq!(info.video_codec.as_deref(), Some("h264"));
+        assert_eq!(info.width, Some(1920));
+        assert_eq!(info.height, Some(1080));
+        assert!((info.fps.unwrap() - 29.97).abs() < 0.01);
+        assert_eq!(info.audio_codec.as_deref(), Some("aac"));
+        assert_eq!(info.audio_channels, Some(2));
+        assert_eq!(info.audio_sample_rate, Some(48000));
+        assert_eq!(info.bitrate_bps, Some(5_128_000));
+        assert!((info.duration_seconds.unwrap() - 1834.56).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_parse_probe_output_audio_only_live() {
+        let json = r#"{
+            "streams": [{"codec_name": "mp3", "codec_type": "audio", "sample_rate": "44100", "channels": 1, "bit_rate": "128000"}],
+            "format": {"format_name": "hls", "duration": "N/A"}
+        }"#;
+
+        let info = FfmpegBackend::parse_probe_output(json).unwrap();
+
+        assert_eq!(info.video_codec, None);
+        assert_eq!(info.height, None);
+        assert_eq!(info.fps, None);
+        assert_eq!(info.audio_codec.as_deref(), Some("mp3"));
+        assert_eq!(info.audio_channels, Some(1));
+        assert_eq!(info.bitrate_bps, None);
+        assert_eq!(info.duration_seconds, None);
+        assert!(FfmpegBackend::parse_probe_output("not json").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_probe_without_ffprobe() {
+        let mut backend = FfmpegBackend::new().unwrap();
+        backend.ffprobe_path = None;
+
+        let err = backend
+            .probe("https://example.com/master.m3u8", &StreamConfig::default())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("ffprobe not found"));
+    }
+
+    /// A stand-in ffmpeg writes a header, then appends its trailer only when
+    /// asked to quit on stdin, like a real muxer finalizing its container
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stop_signal_finalizes_file() {
+        let dir = std::env::temp_dir().join(format!("nab-ffmpeg-stop-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = dir.join("ffmpeg");
+        crate::test_support::write_stub(
+            &script,
+            "for arg; do out=\"$arg\"; done\n\
+             printf 'HEADER\\n' > \"$out\"\nread -r cmd\n\
+             printf 'TRAILER %s\\n' \"$cmd\" >> \"$out\"\n",
+        );
+        let output = dir.join("out.ts");
+
+        let stop = StopSignal::new();
+        let backend = FfmpegBackend::new()
+            .unwrap()
+            .with_ffmpeg_path(&script.to_string_lossy())
+            .with_stop_signal(stop.clone());
+
+        let watched = output.clone();
+        let stopper = tokio::spawn(async move {
+            while !std::fs::read_to_string(&watched).is_ok_and(|s| s.starts_with("HEADER")) {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+            stop.stop();
+        });
+        tokio::time::timeout(
+            std::time::Duration::from_secs(10),
+            backend.stream_to_file(
+                "https://example.com/live.m3u8",
+                &StreamConfig::default(),
+                &output,
+                None,
+                None,
+            ),
+        )
+        .await
+        .expect("ffmpeg should quit when stopped")
+        .unwrap();
+        stopper.await.unwrap();
+
+        let written = std::fs::read_to_string(&output).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(written, "HEADER\nTRAILER q\n");
+    }
+
     #[test]
     fn test_can_handle() {
         let backend = FfmpegBackend::new().unwrap();
@@ -1,7 +1,9 @@
 pub mod ffmpeg;
+pub mod native_dash;
 pub mod native_hls;
 pub mod streamlink;
 
-pub use ffmpeg::FfmpegBackend;
+pub use ffmpeg::{FfmpegBackend, StreamTechInfo};
+pub use native_dash::NativeDashBackend;
 pub use native_hls::NativeHlsBackend;
 pub use streamlink::StreamlinkBackend;
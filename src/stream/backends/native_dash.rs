@@ -0,0 +1,1304 @@
+//! Native DASH streaming backend
+//!
+//! Parses static (VOD) MPEG-DASH manifests and fetches segments directly.
+//! Supports:
+//! - `SegmentTemplate` with `$Number$` or `SegmentTimeline` (`$Time$`)
+//! - `SegmentList` and single-file `BaseURL` representations
+//! - Multiple `Period`s, concatenated into one track per kind
+//! - Separate audio/video adaptation sets, muxed with ffmpeg (`-c copy`)
+//! - Audio language selection ([`NativeDashBackend::with_audio_lang`])
+//! - Audio-only downloads ([`NativeDashBackend::with_audio_only`])
+
+use anyhow::{anyhow, bail, Context, Result};
+use async_trait::async_trait;
+use reqwest::Client;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::process::Command;
+use tracing::{debug, info};
+use url::Url;
+
+use super::super::backend::{
+    BackendType, ProgressCallback, StopSignal, StreamBackend, StreamConfig, StreamProgress,
+};
+use super::super::StreamQuality;
+
+/// Native DASH streaming backend
+pub struct NativeDashBackend {
+    client: Client,
+    /// Path to ffmpeg binary (used only to mux separate audio/video)
+    ffmpeg_path: String,
+    /// Maximum concurrent segment downloads
+    max_concurrent: usize,
+    /// Retry count for failed segments
+    max_retries: u32,
+    /// Preferred audio language (`lang` attribute, e.g. "fi", "en")
+    audio_lang: Option<String>,
+    /// Download only the audio track
+    audio_only: bool,
+    /// Stops the download at the next segment boundary
+    stop: StopSignal,
+}
+
+/// What a representation carries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackKind {
+    Video,
+    Audio,
+    /// Audio and video in one representation (no mux needed)
+    Muxed,
+}
+
+/// A downloadable DASH representation with its segment URLs resolved
+#[derive(Debug, Clone)]
+pub struct DashRepresentation {
+    pub id: String,
+    pub kind: TrackKind,
+    pub bandwidth: u64,
+    pub height: u32,
+    pub codecs: Option<String>,
+    /// Language of the adaptation set (`lang` attribute)
+    pub lang: Option<String>,
+    /// Initialization segment URL, if any
+    pub init_url: Option<String>,
+    /// Media segment URLs in playback order
+    pub segment_urls: Vec<String>,
+    /// Seconds per segment, when known (used for duration limits)
+    pub segment_duration: Option<f64>,
+}
+
+/// Parsed static MPD
+#[derive(Debug)]
+pub struct DashManifest {
+    /// `type="dynamic"` (live) manifests are not supported natively
+    pub is_live: bool,
+    /// `mediaPresentationDuration` in seconds
+    pub duration_seconds: Option<f64>,
+    /// Whether any adaptation set carries `ContentProtection` (DRM)
+    pub encrypted: bool,
+    pub representations: Vec<DashRepresentation>,
+}
+
+/// Tracks chosen for download
+#[derive(Debug)]
+pub struct DashPlan {
+    pub video: Option<DashRepresentation>,
+    pub audio: Option<DashRepresentation>,
+}
+
+impl DashPlan {
+    /// Separate video and audio tracks have to be muxed after download
+    #[must_use]
+    pub fn needs_mux(&self) -> bool {
+        self.video.is_some() && self.audio.is_some()
+    }
+
+    /// Tracks in download order (video first)
+    #[must_use]
+    pub fn tracks(&self) -> Vec<&DashRepresentation> {
+        self.video.iter().chain(self.audio.iter()).collect()
+    }
+}
+
+/// Inherited `SegmentTemplate` attributes
+#[derive(Debug, Clone, Default)]
+struct SegmentTemplate {
+    media: Option<String>,
+    initialization: Option<String>,
+    start_number: Option<u64>,
+    timescale: Option<u64>,
+    duration: Option<u64>,
+    /// `(t, d, r)` entries from `SegmentTimeline`
+    timeline: Option<Vec<(Option<u64>, u64, i64)>>,
+}
+
+impl SegmentTemplate {
+    /// Overlay attributes from a `SegmentTemplate` element at a deeper level
+    fn merged(&self, node: Option<roxmltree::Node<'_, '_>>) -> Self {
+        let Some(node) = node else {
+            return self.clone();
+        };
+        let num = |name| node.attribute(name).and_then(|v| v.parse().ok());
+        let timeline = child(node, "SegmentTimeline").map(|tl| {
+            tl.children()
+                .filter(|n| n.has_tag_name("S"))
+                .map(|s| {
+                    (
+                        s.attribute("t").and_then(|v| v.parse().ok()),
+                        s.attribute("d").and_then(|v| v.parse().ok()).unwrap_or(0),
+                        s.attribute("r").and_then(|v| v.parse().ok()).unwrap_or(0),
+                    )
+                })
+                .collect()
+        });
+
+        Self {
+            media: node
+                .attribute("media")
+                .map(String::from)
+                .or_else(|| self.media.clone()),
+            initialization: node
+                .attribute("initialization")
+                .map(String::from)
+                .or_else(|| self.initialization.clone()),
+            start_number: num("startNumber").or(self.start_number),
+            timescale: num("timescale").or(self.timescale),
+            duration: num("duration").or(self.duration),
+            timeline: timeline.or_else(|| self.timeline.clone()),
+        }
+    }
+}
+
+impl DashManifest {
+    /// Parse an MPD document, resolving segment URLs against `manifest_url`
+    pub fn parse(xml: &str, manifest_url: &str) -> Result<Self> {
+        let doc = roxmltree::Document::parse(xml).context("Invalid MPD XML")?;
+        let mpd = doc.root_element();
+        if !mpd.has_tag_name("MPD") {
+            bail!(
+                "Not a DASH manifest (root element is <{}>)",
+                mpd.tag_name().name()
+            );
+        }
+
+        let is_live = mpd.attribute("type") == Some("dynamic");
+        let duration_seconds = mpd
+            .attribute("mediaPresentationDuration")
+            .and_then(parse_mpd_duration);
+        let mpd_base = with_base_url(Url::parse(manifest_url)?, mpd);
+
+        let periods: Vec<_> = mpd
+            .children()
+            .filter(|n| n.has_tag_name("Period"))
+            .collect();
+        if periods.is_empty() {
+            bail!("MPD has no Period");
+        }
+
+        let mut encrypted = false;
+        let mut representations: Vec<DashRepresentation> = Vec::new();
+        let mut period_start = 0.0;
+        for (i, period) in periods.iter().enumerate() {
+            period_start = period
+                .attribute("start")
+                .and_then(parse_mpd_duration)
+                .unwrap_or(period_start);
+            // A period ends where the next one starts, the last one with the
+            // presentation
+            let period_end = match periods.get(i + 1) {
+                Some(next) => next.attribute("start").and_then(parse_mpd_duration),
+                None => duration_seconds,
+            };
+            let period_duration = period
+                .attribute("duration")
+                .and_then(parse_mpd_duration)
+                .or_else(|| period_end.map(|end| end - period_start));
+
+            let (reps, period_encrypted) =
+                period_representations(*period, mpd_base.clone(), period_duration)?;
+            encrypted |= period_encrypted;
+            representations = if i == 0 {
+                reps
+            } else {
+                append_period(representations, &reps)
+            };
+            period_start += period_duration.unwrap_or(0.0);
+        }
+
+        Ok(Self {
+            is_live,
+            duration_seconds,
+            encrypted,
+            representations,
+        })
+    }
+
+    /// Pick the video representation for `quality` and the best audio
+    /// representation (in `audio_lang`, when given).
+    pub fn plan(&self, quality: &StreamQuality, audio_lang: Option<&str>) -> Result<DashPlan> {
+        let mut videos: Vec<_> = self
+            .representations
+            .iter()
+            .filter(|r| matches!(r.kind, TrackKind::Video | TrackKind::Muxed))
+            .collect();
+        videos.sort_by_key(|r| std::cmp::Reverse(r.bandwidth));
+
+        let video = match quality {
+            StreamQuality::Best => videos.first(),
+            StreamQuality::Worst => videos.last(),
+            StreamQuality::Specific(height) => videos
+                .iter()
+                .min_by_key(|r| (i64::from(r.height) - i64::from(*height)).abs()),
+        }
+        .map(|r| (*r).clone());
+
+        let audios: Vec<_> = self
+            .representations
+            .iter()
+            .filter(|r| r.kind == TrackKind::Audio)
+            .collect();
+        let candidates: Vec<_> = match audio_lang {
+            Some(wanted) => {
+                let matching: Vec<_> = audios
+                    .iter()
+                    .copied()
+                    .filter(|r| {
+                        r.lang
+                            .as_deref()
+                            .is_some_and(|lang| lang_matches(lang, wanted))
+                    })
+                    .collect();
+                if matching.is_empty() && !audios.is_empty() {
+                    let mut available: Vec<_> =
+                        audios.iter().filter_map(|r| r.lang.as_deref()).collect();
+                    available.sort_unstable();
+                    available.dedup();
+                    bail!(
+                        "No audio track in language '{wanted}' (available: {})",
+                        if available.is_empty() {
+                            "untagged".to_string()
+                        } else {
+                            available.join(", ")
+                        }
+                    );
+                }
+                matching
+            }
+            None => audios,
+        };
+        let audio = if video.as_ref().is_some_and(|v| v.kind == TrackKind::Muxed) {
+            None
+        } else {
+            candidates.into_iter().max_by_key(|r| r.bandwidth).cloned()
+        };
+
+        if video.is_none() && audio.is_none() {
+            bail!("MPD has no playable audio or video representation");
+        }
+        Ok(DashPlan { video, audio })
+    }
+}
+
+impl NativeDashBackend {
+    pub fn new() -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(30))
+            .pool_max_idle_per_host(16)
+            .pool_idle_timeout(Duration::from_secs(60))
+            .tcp_nodelay(true)
+            .build()?;
+        let ffmpeg_path = which::which("ffmpeg").map_or_else(
+            |_| "ffmpeg".to_string(),
+            |p| p.to_string_lossy().to_string(),
+        );
+
+        Ok(Self {
+            client,
+            ffmpeg_path,
+            max_concurrent: 8,
+            max_retries: 3,
+            audio_lang: None,
+            audio_only: false,
+            stop: StopSignal::new(),
+        })
+    }
+
+    /// Prefer the audio track in this language (matches "fi" against "fi-FI")
+    #[must_use]
+    pub fn with_audio_lang(mut self, lang: &str) -> Self {
+        self.audio_lang = Some(lang.to_string());
+        self
+    }
+
+    /// Skip the video track and download only the audio representation
+    #[must_use]
+    pub fn with_audio_only(mut self, audio_only: bool) -> Self {
+        self.audio_only = audio_only;
+        self
+    }
+
+    #[must_use]
+    pub fn with_concurrency(mut self, max: usize) -> Self {
+        self.max_concurrent = max;
+        self
+    }
+
+    /// Stop at the next segment boundary when `stop` fires; tracks already
+    /// downloaded are still muxed
+    #[must_use]
+    pub fn with_stop_signal(mut self, stop: StopSignal) -> Self {
+        self.stop = stop;
+        self
+    }
+
+    /// Fetch the manifest and choose tracks for `config`
+    pub async fn plan(&self, manifest_url: &str, config: &StreamConfig) -> Result<DashPlan> {
+        let xml = String::from_utf8(self.fetch(manifest_url, &config.headers).await?)
+            .context("MPD is not valid UTF-8")?;
+        let manifest = DashManifest::parse(&xml, manifest_url)?;
+        if manifest.is_live {
+            bail!("Live DASH is not supported by the native backend. Try --ffmpeg.");
+        }
+        if manifest.encrypted {
+            bail!("DRM-protected DASH cannot be downloaded");
+        }
+        let mut plan = manifest.plan(&config.quality, self.audio_lang.as_deref())?;
+        if self.audio_only {
+            if plan.audio.is_none() {
+                bail!("No separate audio track in MPD (use --ffmpeg to strip video)");
+            }
+            plan.video = None;
+        }
+        Ok(plan)
+    }
+
+    /// ffmpeg arguments that mux separate video and audio files without re-encoding
+    #[must_use]
+    pub fn mux_args(video: &Path, audio: &Path, output: Option<&Path>) -> Vec<String> {
+        let mut args: Vec<String> = ["-hide_banner", "-loglevel", "warning", "-i"]
+            .iter()
+            .map(std::string::ToString::to_string)
+            .collect();
+        args.push(video.to_string_lossy().to_string());
+        args.push("-i".to_string());
+        args.push(audio.to_string_lossy().to_string());
+        args.extend(
+            ["-map", "0:v:0", "-map", "1:a:0", "-c", "copy"]
+                .iter()
+                .map(std::string::ToString::to_string),
+        );
+        match output {
+            Some(path) => {
+                args.push("-y".to_string());
+                args.push(path.to_string_lossy().to_string());
+            }
+            None => args.extend(
+                ["-f", "mpegts", "pipe:1"]
+                    .iter()
+                    .map(std::string::ToString::to_string),
+            ),
+        }
+        args
+    }
+
+    async fn fetch(&self, url: &str, headers: &HashMap<String, String>) -> Result<Vec<u8>> {
+        let mut last_error = None;
+
+        for attempt in 0..self.max_retries {
+            let mut req = self.client.get(url);
+            for (k, v) in headers {
+                req = req.header(k.as_str(), v.as_str());
+            }
+
+            match req.send().await {
+                Ok(resp) if resp.status().is_success() => {
+                    return resp
+                        .bytes()
+                        .await
+                        .map(|b| b.to_vec())
+                        .with_context(|| format!("Failed to read body from {url}"));
+                }
+                Ok(resp) => {
+                    last_error = Some(anyhow!("Fetch returned HTTP {}: {}", resp.status(), url));
+                }
+                Err(e) => {
+                    last_error =
+                        Some(anyhow::Error::new(e).context(format!("Request failed: {url}")));
+                }
+            }
+
+            if attempt < self.max_retries - 1 {
+                tokio::time::sleep(Duration::from_millis(500 * (u64::from(attempt) + 1))).await;
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| anyhow!("Fetch exhausted all retries: {url}")))
+    }
+
+    /// Download one track (init + media segments) into `output`
+    async fn download_track<W: AsyncWrite + Unpin + Send>(
+        &self,
+        track: &DashRepresentation,
+        headers: &HashMap<String, String>,
+        output: &mut W,
+        max_segments: Option<usize>,
+        progress: &mut ProgressState,
+    ) -> Result<()> {
+        if let Some(init) = &track.init_url {
+            let data = self.fetch(init, headers).await?;
+            progress.add_bytes(data.len());
+            output.write_all(&data).await?;
+        }
+
+        let segments = &track.segment_urls[..max_segments
+            .unwrap_or(usize::MAX)
+            .min(track.segment_urls.len())];
+        for chunk in segments.chunks(self.max_concurrent) {
+            let fetches =
+                futures::future::join_all(chunk.iter().map(|url| self.fetch(url, headers)));
+            let results = tokio::select! {
+                results = fetches => results,
+                () = self.stop.stopped() => {
+                    info!("Stop requested, ending {:?} track", track.kind);
+                    break;
+                }
+            };
+            for result in results {
+                let data = result?;
+                output.write_all(&data).await?;
+                progress.segment_done(data.len());
+            }
+        }
+
+        output.flush().await?;
+        Ok(())
+    }
+
+    /// Download planned tracks; separate audio/video are muxed into `destination`
+    async fn run<W: AsyncWrite + Unpin + Send>(
+        &self,
+        manifest_url: &str,
+        config: &StreamConfig,
+        destination: Destination<'_, W>,
+        progress: Option<ProgressCallback>,
+        duration_secs: Option<u64>,
+    ) -> Result<()> {
+        let plan = self.plan(manifest_url, config).await?;
+        for track in plan.tracks() {
+            info!(
+                "Selected {:?} representation {} ({} bps, {} segments{})",
+                track.kind,
+                track.id,
+                track.bandwidth,
+                track.segment_urls.len(),
+                track
+                    .lang
+                    .as_deref()
+                    .map(|l| format!(", lang {l}"))
+                    .unwrap_or_default()
+            );
+        }
+
+        let limit = |track: &DashRepresentation| {
+            let (secs, seg) = (duration_secs?, track.segment_duration?);
+            #[allow(
+                clippy::cast_possible_truncation,
+                clippy::cast_precision_loss,
+                clippy::cast_sign_loss
+            )]
+            Some((secs as f64 / seg).ceil() as usize)
+        };
+        let total: usize = plan
+            .tracks()
+            .iter()
+            .map(|t| limit(t).unwrap_or(usize::MAX).min(t.segment_urls.len()))
+            .sum();
+        let mut state = ProgressState::new(progress, u32::try_from(total).ok());
+
+        if !plan.needs_mux() {
+            let track = plan.tracks()[0];
+            return match destination {
+                Destination::Writer(output) => {
+                    self.download_track(track, &config.headers, output, limit(track), &mut state)
+                        .await
+                }
+                Destination::File(path) => {
+                    let file = tokio::fs::File::create(path).await?;
+                    let mut writer = tokio::io::BufWriter::new(file);
+                    self.download_track(
+                        track,
+                        &config.headers,
+                        &mut writer,
+                        limit(track),
+                        &mut state,
+                    )
+                    .await
+                }
+            };
+        }
+
+        let scratch = ScratchFiles::new();
+        let mut downloaded = Vec::new();
+        for (track, path) in plan
+            .tracks()
+            .into_iter()
+            .zip([&scratch.video, &scratch.audio])
+        {
+            if self.stop.is_stopped() {
+                break;
+            }
+            let file = tokio::fs::File::create(path).await?;
+            let mut writer = tokio::io::BufWriter::new(file);
+            self.download_track(
+                track,
+                &config.headers,
+                &mut writer,
+                limit(track),
+                &mut state,
+            )
+            .await?;
+            downloaded.push(path);
+        }
+
+        // Stopped before the second track: there is nothing to mux
+        if downloaded.len() < 2 {
+            let Some(only) = downloaded.first() else {
+                return Ok(());
+            };
+            info!("Stopped early, keeping the first track only");
+            return destination.copy_from(only).await;
+        }
+
+        debug!(
+            "Muxing {} + {}",
+            scratch.video.display(),
+            scratch.audio.display()
+        );
+        match destination {
+            Destination::File(path) => {
+                let status = Command::new(&self.ffmpeg_path)
+                    .args(Self::mux_args(&scratch.video, &scratch.audio, Some(path)))
+                    .stdin(Stdio::null())
+                    .status()
+                    .await
+                    .context("Failed to run ffmpeg for audio/video mux")?;
+                if !status.success() {
+                    bail!("ffmpeg mux exited with status: {status}");
+                }
+            }
+            Destination::Writer(output) => {
+                let mut child = Command::new(&self.ffmpeg_path)
+                    .args(Self::mux_args(&scratch.video, &scratch.audio, None))
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::piped())
+                    .spawn()
+                    .context("Failed to run ffmpeg for audio/video mux")?;
+                let mut stdout = child
+                    .stdout
+                    .take()
+                    .ok_or_else(|| anyhow!("Failed to capture ffmpeg stdout"))?;
+                tokio::io::copy(&mut stdout, output).await?;
+                let status = child.wait().await?;
+                if !status.success() {
+                    bail!("ffmpeg mux exited with status: {status}");
+                }
+                output.flush().await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl StreamBackend for NativeDashBackend {
+    fn backend_type(&self) -> BackendType {
+        BackendType::Native
+    }
+
+    fn can_handle(&self, manifest_url: &str, encrypted: bool) -> bool {
+        // Native DASH handles unencrypted static MPDs
+        !encrypted && manifest_url.contains(".mpd")
+    }
+
+    async fn stream_to<W: AsyncWrite + Unpin + Send>(
+        &self,
+        manifest_url: &str,
+        config: &StreamConfig,
+        output: &mut W,
+        progress: Option<ProgressCallback>,
+    ) -> Result<()> {
+        self.run(
+            manifest_url,
+            config,
+            Destination::Writer(output),
+            progress,
+            None,
+        )
+        .await
+    }
+
+    async fn stream_to_file(
+        &self,
+        manifest_url: &str,
+        config: &StreamConfig,
+        path: &Path,
+        progress: Option<ProgressCallback>,
+        duration_secs: Option<u64>,
+    ) -> Result<()> {
+        self.run::<tokio::io::Sink>(
+            manifest_url,
+            config,
+            Destination::File(path),
+            progress,
+            duration_secs,
+        )
+        .await
+    }
+}
+
+enum Destination<'a, W> {
+    Writer(&'a mut W),
+    File(&'a Path),
+}
+
+impl<W: AsyncWrite + Unpin + Send> Destination<'_, W> {
+    /// Deliver a finished single-track file unchanged
+    async fn copy_from(self, track: &Path) -> Result<()> {
+        match self {
+            Destination::File(path) => {
+                tokio::fs::copy(track, path).await?;
+            }
+            Destination::Writer(output) => {
+                let mut file = tokio::fs::File::open(track).await?;
+                tokio::io::copy(&mut file, output).await?;
+                output.flush().await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Progress counters shared across tracks
+struct ProgressState {
+    callback: Option<ProgressCallback>,
+    total: Option<u32>,
+    bytes: u64,
+    segments: u32,
+    start: std::time::Instant,
+}
+
+impl ProgressState {
+    fn new(callback: Option<ProgressCallback>, total: Option<u32>) -> Self {
+        Self {
+            callback,
+            total,
+            bytes: 0,
+            segments: 0,
+            start: std::time::Instant::now(),
+        }
+    }
+
+    fn add_bytes(&mut self, len: usize) {
+        self.bytes += len as u64;
+    }
+
+    fn segment_done(&mut self, len: usize) {
+        self.add_bytes(len);
+        self.segments += 1;
+        if let Some(cb) = &self.callback {
+            cb(StreamProgress {
+                bytes_downloaded: self.bytes,
+                segments_completed: self.segments,
+                segments_total: self.total,
+                elapsed_seconds: self.start.elapsed().as_secs_f64(),
+            });
+        }
+    }
+}
+
+/// Temporary per-track files, removed on drop
+struct ScratchFiles {
+    video: PathBuf,
+    audio: PathBuf,
+}
+
+impl ScratchFiles {
+    fn new() -> Self {
+        let id = uuid::Uuid::new_v4();
+        let dir = std::env::temp_dir();
+        Self {
+            video: dir.join(format!("nab-dash-{id}-video.mp4")),
+            audio: dir.join(format!("nab-dash-{id}-audio.mp4")),
+        }
+    }
+}
+
+impl Drop for ScratchFiles {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.video);
+        let _ = std::fs::remove_file(&self.audio);
+    }
+}
+
+/// Representations of one `Period`, and whether any of them is encrypted
+fn period_representations(
+    period: roxmltree::Node<'_, '_>,
+    mpd_base: Url,
+    period_duration: Option<f64>,
+) -> Result<(Vec<DashRepresentation>, bool)> {
+    let period_base = with_base_url(mpd_base, period);
+    let period_template = SegmentTemplate::default().merged(child(period, "SegmentTemplate"));
+
+    let mut encrypted = false;
+    let mut representations = Vec::new();
+
+    for set in period
+        .children()
+        .filter(|n| n.has_tag_name("AdaptationSet"))
+    {
+        encrypted |= child(set, "ContentProtection").is_some();
+        let set_base = with_base_url(period_base.clone(), set);
+        let set_template = period_template.merged(child(set, "SegmentTemplate"));
+        let lang = set.attribute("lang").map(String::from);
+
+        for rep in set.children().filter(|n| n.has_tag_name("Representation")) {
+            encrypted |= child(rep, "ContentProtection").is_some();
+            let attr = |name| rep.attribute(name).or_else(|| set.attribute(name));
+            let codecs = attr("codecs").map(String::from);
+            let Some(kind) = track_kind(
+                set.attribute("contentType"),
+                attr("mimeType"),
+                codecs.as_deref(),
+            ) else {
+                continue; // subtitles, thumbnails, ...
+            };
+
+            let id = rep.attribute("id").unwrap_or_default().to_string();
+            let bandwidth = rep
+                .attribute("bandwidth")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0);
+            let height = attr("height").and_then(|v| v.parse().ok()).unwrap_or(0);
+            let base = with_base_url(set_base.clone(), rep);
+            let template = set_template.merged(child(rep, "SegmentTemplate"));
+            let segment_list = child(rep, "SegmentList").or_else(|| child(set, "SegmentList"));
+
+            let (init_url, segment_urls, segment_duration) = if template.media.is_some() {
+                expand_segment_template(&template, &base, &id, bandwidth, period_duration)?
+            } else if let Some(list) = segment_list {
+                segment_list_urls(list, &base)
+            } else {
+                // Single file addressed by BaseURL
+                (None, vec![base.to_string()], None)
+            };
+
+            representations.push(DashRepresentation {
+                id,
+                kind,
+                bandwidth,
+                height,
+                codecs,
+                lang: lang.clone(),
+                init_url,
+                segment_urls,
+                segment_duration,
+            });
+        }
+    }
+    Ok((representations, encrypted))
+}
+
+/// Continue each track with its counterpart in the next period: the same
+/// representation id, else the same kind and language at the closest
+/// bandwidth. Tracks without a counterpart are dropped, since they would
+/// stop short of the end.
+///
+/// A period with its own initialization segment gets it inserted before
+/// its media segments.
+fn append_period(
+    tracks: Vec<DashRepresentation>,
+    period: &[DashRepresentation],
+) -> Vec<DashRepresentation> {
+    tracks
+        .into_iter()
+        .filter_map(|mut track| {
+            let next = period
+                .iter()
+                .find(|r| r.kind == track.kind && r.id == track.id)
+                .or_else(|| {
+                    period
+                        .iter()
+                        .filter(|r| r.kind == track.kind && r.lang == track.lang)
+                        .min_by_key(|r| r.bandwidth.abs_diff(track.bandwidth))
+                })?;
+            if next.init_url.is_some() && next.init_url != track.init_url {
+                track.segment_urls.extend(next.init_url.clone());
+            }
+            track.segment_urls.extend(next.segment_urls.iter().cloned());
+            Some(track)
+        })
+        .collect()
+}
+
+fn child<'a, 'input>(
+    node: roxmltree::Node<'a, 'input>,
+    name: &str,
+) -> Option<roxmltree::Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(name))
+}
+
+/// Apply a `BaseURL` child of `node`, if any, on top of `base`
+fn with_base_url(base: Url, node: roxmltree::Node<'_, '_>) -> Url {
+    child(node, "BaseURL")
+        .and_then(|b| b.text())
+        .and_then(|text| base.join(text.trim()).ok())
+        .unwrap_or(base)
+}
+
+fn track_kind(
+    content_type: Option<&str>,
+    mime_type: Option<&str>,
+    codecs: Option<&str>,
+) -> Option<TrackKind> {
+    let codecs = codecs.unwrap_or_default().to_ascii_lowercase();
+    let has_audio_codec = ["mp4a", "ac-3", "ec-3", "opus", "flac"]
+        .iter()
+        .any(|c| codecs.contains(c));
+    let has_video_codec = ["avc", "hvc", "hev", "vp09", "vp9", "av01"]
+        .iter()
+        .any(|c| codecs.contains(c));
+    let kind = content_type.or_else(|| mime_type.and_then(|m| m.split('/').next()));
+
+    match kind {
+        Some("video") if has_audio_codec && has_video_codec => Some(TrackKind::Muxed),
+        Some("video") => Some(TrackKind::Video),
+        Some("audio") => Some(TrackKind::Audio),
+        Some(_) => None,
+        None if has_audio_codec && has_video_codec => Some(TrackKind::Muxed),
+        None if has_video_codec => Some(TrackKind::Video),
+        None if has_audio_codec => Some(TrackKind::Audio),
+        None => None,
+    }
+}
+
+fn lang_matches(lang: &str, wanted: &str) -> bool {
+    lang.eq_ignore_ascii_case(wanted)
+        || lang
+            .split(['-', '_'])
+            .next()
+            .is_some_and(|primary| primary.eq_ignore_ascii_case(wanted))
+}
+
+type SegmentUrls = (Option<String>, Vec<String>, Option<f64>);
+
+#[allow(clippy::cast_precision_loss)]
+fn expand_segment_template(
+    template: &SegmentTemplate,
+    base: &Url,
+    rep_id: &str,
+    bandwidth: u64,
+    period_duration: Option<f64>,
+) -> Result<SegmentUrls> {
+    let media = template.media.as_deref().unwrap_or_default();
+    let timescale = template.timescale.unwrap_or(1).max(1);
+    let start_number = template.start_number.unwrap_or(1);
+    let resolve = |path: String| base.join(&path).map(String::from);
+
+    let init_url = template
+        .initialization
+        .as_deref()
+        .map(|init| resolve(fill_template(init, rep_id, bandwidth, None, None)))
+        .transpose()?;
+
+    let mut urls = Vec::new();
+    let segment_duration;
+
+    if let Some(timeline) = &template.timeline {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let end = period_duration.map(|d| (d * timescale as f64) as u64);
+        let mut time = 0u64;
+        let mut number = start_number;
+        let mut longest = 0u64;
+        for (i, &(t, d, r)) in timeline.iter().enumerate() {
+            if let Some(t) = t {
+                time = t;
+            }
+            longest = longest.max(d);
+            // r = -1 repeats until the next S@t or the end of the period
+            let repeats = if r >= 0 {
+                u64::try_from(r).unwrap_or(0) + 1
+            } else {
+                let until = timeline
+                    .get(i + 1)
+                    .and_then(|next| next.0)
+                    .or(end)
+                    .ok_or_else(|| anyhow!("Open-ended SegmentTimeline without a duration"))?;
+                until.saturating_sub(time).div_ceil(d.max(1))
+            };
+            for _ in 0..repeats {
+                urls.push(resolve(fill_template(
+                    media,
+                    rep_id,
+                    bandwidth,
+                    Some(number),
+                    Some(time),
+                ))?);
+                time += d;
+                number += 1;
+            }
+        }
+        segment_duration = (longest > 0).then(|| longest as f64 / timescale as f64);
+    } else {
+        let duration = template
+            .duration
+            .filter(|d| *d > 0)
+            .ok_or_else(|| anyhow!("SegmentTemplate without duration or SegmentTimeline"))?;
+        let seconds = duration as f64 / timescale as f64;
+        let total =
+            period_duration.ok_or_else(|| anyhow!("MPD has no mediaPresentationDuration"))?;
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let count = (total / seconds).ceil() as u64;
+        for number in start_number..start_number + count {
+            urls.push(resolve(fill_template(
+                media,
+                rep_id,
+                bandwidth,
+                Some(number),
+                None,
+            ))?);
+        }
+        segment_duration = Some(seconds);
+    }
+
+    Ok((init_url, urls, segment_duration))
+}
+
+fn segment_list_urls(list: roxmltree::Node<'_, '_>, base: &Url) -> SegmentUrls {
+    let resolve = |path: &str| base.join(path).map(String::from).ok();
+    let init_url = child(list, "Initialization")
+        .and_then(|i| i.attribute("sourceURL"))
+        .and_then(resolve);
+    let urls = list
+        .children()
+        .filter(|n| n.has_tag_name("SegmentURL"))
+        .filter_map(|s| s.attribute("media").and_then(resolve))
+        .collect();
+    #[allow(clippy::cast_precision_loss)]
+    let segment_duration = list
+        .attribute("duration")
+        .and_then(|d| d.parse::<u64>().ok())
+        .map(|d| {
+            let timescale = list
+                .attribute("timescale")
+                .and_then(|t| t.parse::<u64>().ok())
+                .unwrap_or(1)
+                .max(1);
+            d as f64 / timescale as f64
+        });
+    (init_url, urls, segment_duration)
+}
+
+/// Substitute `$RepresentationID$`, `$Bandwidth$`, `$Number$` and `$Time$`
+/// (with optional `%0Nd` width) in a segment template
+fn fill_template(
+    template: &str,
+    rep_id: &str,
+    bandwidth: u64,
+    number: Option<u64>,
+    time: Option<u64>,
+) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('$') else {
+            out.push_str(&rest[start..]);
+            return out;
+        };
+        let token = &after[..end];
+        rest = &after[end + 1..];
+
+        if token.is_empty() {
+            out.push('$'); // "$$" escape
+            continue;
+        }
+        let (name, width) = match token.split_once("%0") {
+            Some((name, fmt)) => (name, fmt.trim_end_matches('d').parse::<usize>().ok()),
+            None => (token, None),
+        };
+        let value = match name {
+            "RepresentationID" => Some(rep_id.to_string()),
+            "Bandwidth" => Some(bandwidth.to_string()),
+            "Number" => number.map(|n| n.to_string()),
+            "Time" => time.map(|t| t.to_string()),
+            _ => None,
+        };
+        match (value, width) {
+            (Some(v), Some(w)) => {
+                let _ = write!(out, "{v:0>w$}");
+            }
+            (Some(v), None) => out.push_str(&v),
+            (None, _) => {
+                out.push('$');
+                out.push_str(token);
+                out.push('$');
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Parse an `xs:duration` like `PT1H2M3.5S` or `P1DT2H` into seconds
+fn parse_mpd_duration(value: &str) -> Option<f64> {
+    let rest = value.trim().strip_prefix('P')?;
+    let (date, time) = rest.split_once('T').unwrap_or((rest, ""));
+    let mut total = 0.0;
+
+    for (part, units) in [
+        (date, &[('D', 86_400.0)][..]),
+        (time, &[('H', 3600.0), ('M', 60.0), ('S', 1.0)][..]),
+    ] {
+        let mut number = String::new();
+        for c in part.chars() {
+            if c.is_ascii_digit() || c == '.' {
+                number.push(c);
+                continue;
+            }
+            let factor = units.iter().find(|(unit, _)| *unit == c)?.1;
+            total += number.parse::<f64>().ok()? * factor;
+            number.clear();
+        }
+        if !number.is_empty() {
+            return None;
+        }
+    }
+    Some(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEPARATE_AV_MPD: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<MPD xmlns="urn:mpeg:dash:schema:mpd:2011" type="static" mediaPresentationDuration="PT20S" minBufferTime="PT2S">
+  <BaseURL>media/</BaseURL>
+  <Period id="0">
+    <AdaptationSet contentType="video" mimeType="video/mp4" segmentAlignment="true">
+      <SegmentTemplate timescale="1000" duration="4000" startNumber="1"
+        initialization="$RepresentationID$/init.mp4" media="$RepresentationID$/seg-$Number%05d$.m4s"/>
+      <Representation id="v1080" bandwidth="5000000" width="1920" height="1080" codecs="avc1.640028"/>
+      <Representation id="v720" bandwidth="2500000" width="1280" height="720" codecs="avc1.4d401f"/>
+    </AdaptationSet>
+    <AdaptationSet contentType="audio" mimeType="audio/mp4" lang="fi">
+      <SegmentTemplate timescale="48000" initialization="$RepresentationID$/init.mp4" media="$RepresentationID$/$Time$.m4s">
+        <SegmentTimeline><S t="0" d="192000" r="4"/></SegmentTimeline>
+      </SegmentTemplate>
+      <Representation id="a-fi" bandwidth="128000" codecs="mp4a.40.2"/>
+    </AdaptationSet>
+    <AdaptationSet contentType="audio" mimeType="audio/mp4" lang="sv">
+      <SegmentTemplate timescale="48000" initialization="$RepresentationID$/init.mp4" media="$RepresentationID$/$Time$.m4s">
+        <SegmentTimeline><S t="0" d="192000" r="4"/></SegmentTimeline>
+      </SegmentTemplate>
+      <Representation id="a-sv-hi" bandwidth="192000" codecs="mp4a.40.2"/>
+      <Representation id="a-sv" bandwidth="96000" codecs="mp4a.40.2"/>
+    </AdaptationSet>
+    <AdaptationSet contentType="text" mimeType="application/ttml+xml" lang="fi">
+      <Representation id="sub-fi" bandwidth="1000"><BaseURL>subs/fi.ttml</BaseURL></Representation>
+    </AdaptationSet>
+  </Period>
+</MPD>"#;
+
+    const MANIFEST_URL: &str = "https://cdn.example.com/show/manifest.mpd";
+
+    #[test]
+    fn test_parse_separate_adaptation_sets() {
+        let manifest = DashManifest::parse(SEPARATE_AV_MPD, MANIFEST_URL).unwrap();
+
+        assert!(!manifest.is_live);
+        assert!(!manifest.encrypted);
+        assert_eq!(manifest.duration_seconds, Some(20.0));
+        // Subtitles are skipped
+        assert_eq!(manifest.representations.len(), 5);
+
+        let v720 = manifest
+            .representations
+            .iter()
+            .find(|r| r.id == "v720")
+            .unwrap();
+        assert_eq!(v720.kind, TrackKind::Video);
+        assert_eq!(
+            v720.init_url.as_deref(),
+            Some("https://cdn.example.com/show/media/v720/init.mp4")
+        );
+        assert_eq!(v720.segment_urls.len(), 5);
+        assert_eq!(
+            v720.segment_urls[0],
+            "https://cdn.example.com/show/media/v720/seg-00001.m4s"
+        );
+        assert_eq!(v720.segment_duration, Some(4.0));
+
+        let audio = manifest
+            .representations
+            .iter()
+            .find(|r| r.id == "a-fi")
+            .unwrap();
+        assert_eq!(audio.kind, TrackKind::Audio);
+        assert_eq!(audio.lang.as_deref(), Some("fi"));
+        assert_eq!(audio.segment_urls.len(), 5);
+        assert_eq!(
+            audio.segment_urls[1],
+            "https://cdn.example.com/show/media/a-fi/192000.m4s"
+        );
+    }
+
+    #[test]
+    fn test_plan_selects_video_and_audio_and_schedules_mux() {
+        let manifest = DashManifest::parse(SEPARATE_AV_MPD, MANIFEST_URL).unwrap();
+
+        let plan = manifest
+            .plan(&StreamQuality::Specific(720), Some("fi"))
+            .unwrap();
+
+        assert_eq!(plan.video.as_ref().unwrap().id, "v720");
+        assert_eq!(plan.audio.as_ref().unwrap().id, "a-fi");
+        assert!(plan.needs_mux());
+        let ids: Vec<_> = plan.tracks().iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, ["v720", "a-fi"]);
+
+        let args = NativeDashBackend::mux_args(
+            Path::new("/tmp/v.mp4"),
+            Path::new("/tmp/a.mp4"),
+            Some(Path::new("out.mp4")),
+        );
+        assert_eq!(
+            args[3..].join(" "),
+            "-i /tmp/v.mp4 -i /tmp/a.mp4 -map 0:v:0 -map 1:a:0 -c copy -y out.mp4"
+        );
+    }
+
+    #[test]
+    fn test_plan_audio_language() {
+        let manifest = DashManifest::parse(SEPARATE_AV_MPD, MANIFEST_URL).unwrap();
+
+        // Best bandwidth wins without a language preference
+        let plan = manifest.plan(&StreamQuality::Best, None).unwrap();
+        assert_eq!(plan.video.as_ref().unwrap().id, "v1080");
+        assert_eq!(plan.audio.as_ref().unwrap().id, "a-sv-hi");
+
+        let plan = manifest.plan(&StreamQuality::Worst, Some("SV")).unwrap();
+        assert_eq!(plan.video.as_ref().unwrap().id, "v720");
+        assert_eq!(plan.audio.as_ref().unwrap().id, "a-sv-hi");
+
+        let err = manifest.plan(&StreamQuality::Best, Some("en")).unwrap_err();
+        assert!(err.to_string().contains("available: fi, sv"));
+    }
+
+    #[test]
+    fn test_multiple_periods_are_concatenated() {
+        // Pre-roll with its own representation ids, then the programme
+        let mpd = r#"<MPD type="static" mediaPresentationDuration="PT18S">
+  <Period id="ad" duration="PT6S">
+    <AdaptationSet contentType="video" mimeType="video/mp4">
+      <SegmentTemplate timescale="1" duration="2" startNumber="1"
+        initialization="ad/$RepresentationID$/init.mp4" media="ad/$RepresentationID$/$Number$.m4s"/>
+      <Representation id="ad-v" bandwidth="2000000" height="720"/>
+    </AdaptationSet>
+    <AdaptationSet contentType="audio" mimeType="audio/mp4" lang="sv">
+      <SegmentTemplate timescale="1" duration="2" startNumber="1"
+        initialization="ad/$RepresentationID$/init.mp4" media="ad/$RepresentationID$/$Number$.m4s"/>
+      <Representation id="ad-a" bandwidth="96000"/>
+    </AdaptationSet>
+  </Period>
+  <Period id="main">
+    <AdaptationSet contentType="video" mimeType="video/mp4">
+      <SegmentTemplate timescale="1" duration="4" startNumber="1"
+        initialization="main/$RepresentationID$/init.mp4" media="main/$RepresentationID$/$Number$.m4s"/>
+      <Representation id="v720" bandwidth="2500000" height="720"/>
+      <Representation id="v360" bandwidth="800000" height="360"/>
+    </AdaptationSet>
+    <AdaptationSet contentType="audio" mimeType="audio/mp4" lang="sv">
+      <SegmentTemplate timescale="1" duration="4" startNumber="1"
+        initialization="main/$RepresentationID$/init.mp4" media="main/$RepresentationID$/$Number$.m4s"/>
+      <Representation id="a-sv" bandwidth="128000"/>
+    </AdaptationSet>
+  </Period>
+</MPD>"#;
+        let manifest = DashManifest::parse(mpd, MANIFEST_URL).unwrap();
+
+        // Tracks follow the first period; each continues at the closest bandwidth
+        assert_eq!(manifest.representations.len(), 2);
+        let video = &manifest.representations[0];
+        assert_eq!(video.id, "ad-v");
+        assert_eq!(
+            video.init_url.as_deref(),
+            Some("https://cdn.example.com/show/ad/ad-v/init.mp4")
+        );
+        // 3 pre-roll segments, the programme's init segment, 3 programme segments
+        assert_eq!(video.segment_urls.len(), 7);
+        assert_eq!(
+            video.segment_urls[3],
+            "https://cdn.example.com/show/main/v720/init.mp4"
+        );
+        assert_eq!(
+            video.segment_urls[6],
+            "https://cdn.example.com/show/main/v720/3.m4s"
+        );
+        assert_eq!(
+            manifest.representations[1].segment_urls.last().unwrap(),
+            "https://cdn.example.com/show/main/a-sv/3.m4s"
+        );
+    }
+
+    #[test]
+    fn test_missing_language_lists_each_once() {
+        let mpd = r#"<MPD type="static" mediaPresentationDuration="PT4S"><Period>
+<AdaptationSet contentType="audio" mimeType="audio/mp4" lang="sv"><Representation id="a1" bandwidth="1"/></AdaptationSet>
+<AdaptationSet contentType="audio" mimeType="audio/mp4" lang="fi"><Representation id="a2" bandwidth="1"/></AdaptationSet>
+<AdaptationSet contentType="audio" mimeType="audio/mp4" lang="sv"><Representation id="a3" bandwidth="1"/></AdaptationSet>
+</Period></MPD>"#;
+        let manifest = DashManifest::parse(mpd, MANIFEST_URL).unwrap();
+
+        // The second Swedish set comes after Finnish, so only sorting dedups it
+        let err = manifest.plan(&StreamQuality::Best, Some("en")).unwrap_err();
+        assert!(err.to_string().ends_with("(available: fi, sv)"), "{err}");
+    }
+
+    #[test]
+    fn test_muxed_representation_needs_no_mux() {
+        let mpd = r#"<MPD type="static" mediaPresentationDuration="PT10S"><Period>
+<AdaptationSet mimeType="video/mp4" codecs="avc1.4d401f,mp4a.40.2">
+<Representation id="av" bandwidth="1000"><BaseURL>https://cdn.example.com/full.mp4</BaseURL></Representation>
+</AdaptationSet></Period></MPD>"#;
+        let manifest = DashManifest::parse(mpd, MANIFEST_URL).unwrap();
+        let plan = manifest.plan(&StreamQuality::Best, None).unwrap();
+
+        assert_eq!(plan.video.as_ref().unwrap().kind, TrackKind::Muxed);
+        assert!(plan.audio.is_none());
+        assert!(!plan.needs_mux());
+        assert_eq!(
+            plan.video.unwrap().segment_urls,
+            ["https://cdn.example.com/full.mp4"]
+        );
+    }
+
+    #[test]
+    fn test_fill_template() {
+        assert_eq!(
+            fill_template(
+                "$RepresentationID$/$Number%03d$-$Bandwidth$.m4s",
+                "v1",
+                800,
+                Some(7),
+                None
+            ),
+            "v1/007-800.m4s"
+        );
+        assert_eq!(
+            fill_template("a$$b/$Time$", "v", 0, None, Some(42)),
+            "a$b/42"
+        );
+    }
+
+    #[test]
+    fn test_parse_mpd_duration() {
+        assert_eq!(parse_mpd_duration("PT1H2M3.5S"), Some(3723.5));
+        assert_eq!(parse_mpd_duration("P1DT1S"), Some(86_401.0));
+        assert_eq!(parse_mpd_duration("PT"), Some(0.0));
+        assert_eq!(parse_mpd_duration("1H"), None);
+    }
+
+    #[test]
+    fn test_can_handle() {
+        let backend = NativeDashBackend::new().unwrap();
+        assert!(backend.can_handle("https://example.com/stream.mpd", false));
+        assert!(!backend.can_handle("https://example.com/stream.mpd", true));
+        assert!(!backend.can_handle("https://example.com/stream.m3u8", false));
+    }
+}
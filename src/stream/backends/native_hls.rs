@@ -7,6 +7,7 @@
 //! - Live playlists (continuous refresh)
 //! - Parallel segment fetching
 //! - Retry on segment failure
+//! - Clean early stop at a segment boundary ([`NativeHlsBackend::with_stop_signal`])
 
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
@@ -17,7 +18,7 @@ use tokio::io::{AsyncWrite, AsyncWriteExt};
 use tracing::{debug, info};
 
 use super::super::backend::{
-    BackendType, ProgressCallback, StreamBackend, StreamConfig, StreamProgress,
+    BackendType, ProgressCallback, StopSignal, StreamBackend, StreamConfig, StreamProgress,
 };
 use super::super::StreamQuality;
 
@@ -28,6 +29,10 @@ pub struct NativeHlsBackend {
     max_concurrent: usize,
     /// Retry count for failed segments
     max_retries: u32,
+    /// Prefer an audio-only rendition from master playlists
+    audio_only: bool,
+    /// Stops the download at the next segment boundary
+    stop: StopSignal,
 }
 
 impl NativeHlsBackend {
@@ -43,6 +48,8 @@ impl NativeHlsBackend {
             client,
             max_concurrent: 8, // Higher concurrency for faster VOD downloads
             max_retries: 3,
+            audio_only: false,
+            stop: StopSignal::new(),
         })
     }
 
@@ -52,6 +59,75 @@ impl NativeHlsBackend {
         self
     }
 
+    /// Fetch an audio-only rendition instead of a video variant
+    #[must_use]
+    pub fn with_audio_only(mut self, audio_only: bool) -> Self {
+        self.audio_only = audio_only;
+        self
+    }
+
+    /// Stop at the next segment boundary when `stop` fires, so the output
+    /// only ever holds whole segments
+    #[must_use]
+    pub fn with_stop_signal(mut self, stop: StopSignal) -> Self {
+        self.stop = stop;
+        self
+    }
+
+    /// Pick an audio-only media playlist from master playlist content.
+    ///
+    /// Prefers `#EXT-X-MEDIA:TYPE=AUDIO` renditions (the `DEFAULT=YES` one
+    /// first), then falls back to the highest-bandwidth `#EXT-X-STREAM-INF`
+    /// variant whose codecs are all audio.
+    fn select_audio_rendition(content: &str, base_url: &str) -> Option<String> {
+        const VIDEO_CODECS: [&str; 6] = ["avc1", "avc3", "hvc1", "hev1", "vp09", "av01"];
+
+        let mut renditions = Vec::new();
+        let mut audio_variants = Vec::new();
+        let mut lines = content.lines();
+
+        while let Some(line) = lines.next() {
+            if let Some(rest) = line.strip_prefix("#EXT-X-MEDIA:") {
+                let attrs = Self::parse_attributes(rest);
+                if attrs.get("TYPE").map(String::as_str) != Some("AUDIO") {
+                    continue;
+                }
+                if let Some(uri) = attrs.get("URI") {
+                    let is_default = attrs.get("DEFAULT").map(String::as_str) == Some("YES");
+                    renditions.push((is_default, Self::resolve_url(base_url, uri)));
+                }
+            } else if let Some(rest) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+                let attrs = Self::parse_attributes(rest);
+                let audio_only = attrs.get("CODECS").is_some_and(|codecs| {
+                    !codecs
+                        .split(',')
+                        .any(|c| VIDEO_CODECS.iter().any(|v| c.trim().starts_with(v)))
+                }) && !attrs.contains_key("RESOLUTION");
+                let bandwidth: u64 = attrs
+                    .get("BANDWIDTH")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(0);
+                if let Some(uri_line) = lines.next() {
+                    if audio_only && !uri_line.starts_with('#') {
+                        audio_variants.push((bandwidth, Self::resolve_url(base_url, uri_line)));
+                    }
+                }
+            }
+        }
+
+        renditions
+            .iter()
+            .find(|(is_default, _)| *is_default)
+            .or_else(|| renditions.first())
+            .map(|(_, uri)| uri.clone())
+            .or_else(|| {
+                audio_variants
+                    .into_iter()
+                    .max_by_key(|(bandwidth, _)| *bandwidth)
+                    .map(|(_, uri)| uri)
+            })
+    }
+
     /// Parse master playlist and return quality variants
     async fn parse_master_playlist(
         &self,
@@ -298,6 +374,11 @@ impl NativeHlsBackend {
                 }
             }
 
+            if self.stop.is_stopped() {
+                info!("Stop requested, ending live stream");
+                break;
+            }
+
             let playlist = self.parse_media_playlist(playlist_url, headers).await?;
 
             // Find new segments
@@ -311,7 +392,10 @@ impl NativeHlsBackend {
                 debug!("Found {} new segments", new_segments.len());
 
                 for seg in new_segments {
-                    let data = self.fetch_segment(&seg.uri, headers).await?;
+                    let data = tokio::select! {
+                        data = self.fetch_segment(&seg.uri, headers) => data?,
+                        () = self.stop.stopped() => return Ok(()),
+                    };
                     bytes_downloaded += data.len() as u64;
                     segments_completed += 1;
                     last_sequence = seg.sequence;
@@ -343,7 +427,10 @@ impl NativeHlsBackend {
             }
 
             // Wait before next poll (half of target duration is typical)
-            tokio::time::sleep(Duration::from_secs_f64(playlist.target_duration / 2.0)).await;
+            tokio::select! {
+                () = tokio::time::sleep(Duration::from_secs_f64(playlist.target_duration / 2.0)) => {}
+                () = self.stop.stopped() => break,
+            }
         }
 
         Ok(())
@@ -365,7 +452,14 @@ impl NativeHlsBackend {
         let content = self.fetch_playlist(manifest_url, headers).await?;
         let is_master = content.contains("#EXT-X-STREAM-INF:");
 
-        let media_url = if is_master {
+        let media_url = if is_master && self.audio_only {
+            let base_url = manifest_url.rsplit_once('/').map_or("", |(base, _)| base);
+            let uri = Self::select_audio_rendition(&content, base_url).ok_or_else(|| {
+                anyhow!("No audio-only rendition in master playlist (use --ffmpeg to strip video)")
+            })?;
+            info!("Selected audio rendition: {}", uri);
+            uri
+        } else if is_master {
             let variants = self.parse_master_playlist(manifest_url, headers).await?;
             debug!("Found {} quality variants", variants.len());
 
@@ -434,7 +528,13 @@ impl NativeHlsBackend {
                     .map(|seg| self.fetch_segment(&seg.uri, headers))
                     .collect();
 
-                let results = futures::future::join_all(futures).await;
+                let results = tokio::select! {
+                    results = futures::future::join_all(futures) => results,
+                    () = self.stop.stopped() => {
+                        info!("Stop requested after {segments_completed} segments");
+                        break;
+                    }
+                };
 
                 for result in results {
                     let data = result?;
@@ -691,6 +791,127 @@ mod tests {
             .is_none());
     }
 
+    #[test]
+    fn test_select_audio_rendition_media_group() {
+        let master = r#"#EXTM3U
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID="aud",LANGUAGE="fi",NAME="Suomi",URI="audio/fi.m3u8"
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID="aud",LANGUAGE="en",NAME="English",DEFAULT=YES,URI="audio/en.m3u8"
+#EXT-X-MEDIA:TYPE=SUBTITLES,GROUP-ID="subs",NAME="Suomi",URI="subs/fi.m3u8"
+#EXT-X-STREAM-INF:BANDWIDTH=5000000,RESOLUTION=1920x1080,CODECS="avc1.640028,mp4a.40.2",AUDIO="aud"
+video/1080p.m3u8
+"#;
+        let uri = NativeHlsBackend::select_audio_rendition(master, "https://cdn.example.com/show");
+        assert_eq!(
+            uri.as_deref(),
+            Some("https://cdn.example.com/show/audio/en.m3u8")
+        );
+    }
+
+    #[test]
+    fn test_select_audio_rendition_audio_variant() {
+        let master = "#EXTM3U
+#EXT-X-STREAM-INF:BANDWIDTH=64000,CODECS=\"mp4a.40.5\"
+audio_low.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=128000,CODECS=\"mp4a.40.2\"
+audio_high.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=2000000,RESOLUTION=1280x720,CODECS=\"avc1.4d401f,mp4a.40.2\"
+video_720.m3u8
+";
+        let uri = NativeHlsBackend::select_audio_rendition(master, "https://cdn.example.com");
+        assert_eq!(
+            uri.as_deref(),
+            Some("https://cdn.example.com/audio_high.m3u8")
+        );
+
+        let video_only = "#EXTM3U
+#EXT-X-STREAM-INF:BANDWIDTH=2000000,RESOLUTION=1280x720,CODECS=\"avc1.4d401f,mp4a.40.2\"
+video_720.m3u8
+";
+        assert!(
+            NativeHlsBackend::select_audio_rendition(video_only, "https://cdn.example.com")
+                .is_none()
+        );
+    }
+
+    /// 188-byte MPEG-TS packets, two per segment
+    const SEGMENT_LEN: usize = 2 * 188;
+
+    /// Serve a four-segment VOD playlist whose third segment never arrives;
+    /// `requested` receives each segment path as it is requested
+    fn stalling_playlist(requested: std::sync::mpsc::Sender<String>) -> String {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let requested = requested.clone();
+                std::thread::spawn(move || {
+                    let mut request_line = String::new();
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    reader.read_line(&mut request_line).unwrap();
+                    let mut line = String::new();
+                    while reader.read_line(&mut line).is_ok_and(|n| n > 2) {
+                        line.clear();
+                    }
+                    let path = request_line.split(' ').nth(1).unwrap_or("/").to_string();
+
+                    let body = if path == "/media.m3u8" {
+                        b"#EXTM3U\n#EXT-X-TARGETDURATION:4\n#EXT-X-MEDIA-SEQUENCE:0\n\
+                          #EXTINF:4.0,\nseg0.ts\n#EXTINF:4.0,\nseg1.ts\n\
+                          #EXTINF:4.0,\nseg2.ts\n#EXTINF:4.0,\nseg3.ts\n#EXT-X-ENDLIST\n"
+                            .to_vec()
+                    } else {
+                        let _ = requested.send(path.clone());
+                        if path == "/seg2.ts" {
+                            std::thread::sleep(Duration::from_secs(30));
+                        }
+                        [[0x47_u8; 1].as_slice(), &[0u8; 187]].concat().repeat(2)
+                    };
+                    let _ = write!(
+                        stream,
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        body.len()
+                    );
+                    let _ = stream.write_all(&body);
+                });
+            }
+        });
+        format!("http://{addr}/media.m3u8")
+    }
+
+    #[tokio::test]
+    async fn test_stop_signal_keeps_whole_segments() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let url = stalling_playlist(tx);
+        let path = std::env::temp_dir().join(format!("nab-hls-stop-{}.ts", uuid::Uuid::new_v4()));
+
+        let stop = StopSignal::new();
+        let backend = NativeHlsBackend::new()
+            .unwrap()
+            .with_concurrency(1)
+            .with_stop_signal(stop.clone());
+
+        // Stop (as Ctrl-C would) once the stalled segment is being fetched
+        let stopper = tokio::task::spawn_blocking(move || {
+            while rx.recv().unwrap() != "/seg2.ts" {}
+            stop.stop();
+        });
+        tokio::time::timeout(
+            Duration::from_secs(10),
+            backend.stream_to_file(&url, &StreamConfig::default(), &path, None, None),
+        )
+        .await
+        .expect("stop signal should end the download")
+        .unwrap();
+        stopper.await.unwrap();
+
+        let written = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(written.len(), 2 * SEGMENT_LEN);
+        assert!(written.chunks(188).all(|packet| packet[0] == 0x47));
+    }
+
     #[test]
     fn test_can_handle_hls() {
         let backend = NativeHlsBackend::new().unwrap();
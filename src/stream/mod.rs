@@ -15,5 +15,7 @@ pub mod backends;
 pub mod provider;
 pub mod providers;
 
-pub use backend::{BackendType, StreamBackend};
-pub use provider::{StreamInfo, StreamProvider, StreamQuality};
+pub use backend::{BackendType, StopSignal, StreamBackend};
+pub use provider::{
+    EpisodeInfo, QualityInfo, SeriesInfo, StreamInfo, StreamProvider, StreamQuality,
+};
@@ -4,8 +4,12 @@
 //! URLs, titles, durations) from a specific streaming service (Yle, SVT,
 //! NRK, DR, or generic HLS/DASH endpoints).
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use async_trait::async_trait;
+use rand::Rng;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr};
 
 /// Quality selection strategy for stream variants.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -19,7 +23,7 @@ pub enum StreamQuality {
 }
 
 /// Metadata about a single quality variant in a multi-bitrate stream.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct QualityInfo {
     /// Vertical resolution in pixels.
     pub height: u32,
@@ -30,7 +34,7 @@ pub struct QualityInfo {
 }
 
 /// Metadata and manifest URL for a single stream/program.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct StreamInfo {
     /// Provider-specific program or video ID.
     pub id: String,
@@ -51,7 +55,7 @@ pub struct StreamInfo {
 }
 
 /// Information about a series/playlist and its episodes.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct SeriesInfo {
     /// Provider-specific series identifier.
     pub id: String,
@@ -62,7 +66,7 @@ pub struct SeriesInfo {
 }
 
 /// Metadata for a single episode within a series.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct EpisodeInfo {
     /// Provider-specific episode identifier.
     pub id: String,
@@ -97,9 +101,99 @@ pub trait StreamProvider: Send + Sync {
     /// List all episodes in a series or playlist.
     async fn list_series(&self, series_id: &str) -> Result<SeriesInfo>;
 
+    /// HTTP headers the provider's manifests and segments need (e.g. a
+    /// `Referer`, or an `X-Forwarded-For` for geo-locked services).
+    /// Empty by default; user-supplied headers override these.
+    fn default_headers(&self) -> HashMap<String, String> {
+        HashMap::new()
+    }
+
     /// Search the provider's catalog. Returns an empty vec by default.
     async fn search(&self, query: &str) -> Result<Vec<EpisodeInfo>> {
         let _ = query;
         Ok(vec![])
     }
 }
+
+/// Resolve an `X-Forwarded-For` spec: a literal IP address, or
+/// `random:CIDR` for a random IPv4 host address within the range
+/// (e.g. `random:91.152.0.0/13`).
+pub fn forwarded_for(spec: &str) -> Result<String> {
+    if let Some(cidr) = spec.strip_prefix("random:") {
+        return Ok(random_ip_in_cidr(cidr)?.to_string());
+    }
+    let ip: IpAddr = spec
+        .trim()
+        .parse()
+        .map_err(|_| anyhow!("Invalid X-Forwarded-For IP: {spec}"))?;
+    Ok(ip.to_string())
+}
+
+/// Pick a random IPv4 host address inside `cidr` (e.g. `91.152.0.0/13`).
+///
+/// Network and broadcast addresses are skipped for prefixes shorter than /31.
+pub fn random_ip_in_cidr(cidr: &str) -> Result<Ipv4Addr> {
+    let (addr, prefix) = cidr
+        .split_once('/')
+        .ok_or_else(|| anyhow!("Invalid CIDR (expected ADDR/PREFIX): {cidr}"))?;
+    let addr: Ipv4Addr = addr
+        .parse()
+        .map_err(|_| anyhow!("Invalid IPv4 address in CIDR: {cidr}"))?;
+    let prefix: u32 = prefix
+        .parse()
+        .map_err(|_| anyhow!("Invalid CIDR prefix: {cidr}"))?;
+    if prefix > 32 {
+        bail!("CIDR prefix must be at most 32: {cidr}");
+    }
+
+    let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+    let network = u32::from(addr) & mask;
+    let broadcast = network | !mask;
+    let (first, last) = if prefix < 31 {
+        (network + 1, broadcast - 1)
+    } else {
+        (network, broadcast)
+    };
+
+    Ok(Ipv4Addr::from(rand::thread_rng().gen_range(first..=last)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_ip_in_cidr_stays_in_range() {
+        for _ in 0..200 {
+            let ip = random_ip_in_cidr("91.152.0.0/13").unwrap();
+            let octets = ip.octets();
+            assert_eq!(octets[0], 91);
+            assert!(
+                (152..160).contains(&octets[1]),
+                "{ip} outside 91.152.0.0/13"
+            );
+            assert_ne!(ip, Ipv4Addr::new(91, 152, 0, 0));
+            assert_ne!(ip, Ipv4Addr::new(91, 159, 255, 255));
+        }
+        assert_eq!(
+            random_ip_in_cidr("10.1.2.3/32").unwrap(),
+            Ipv4Addr::new(10, 1, 2, 3)
+        );
+    }
+
+    #[test]
+    fn test_random_ip_in_cidr_rejects_invalid() {
+        assert!(random_ip_in_cidr("91.152.0.0").is_err());
+        assert!(random_ip_in_cidr("91.152.0.0/33").is_err());
+        assert!(random_ip_in_cidr("2001:db8::/32").is_err());
+    }
+
+    #[test]
+    fn test_forwarded_for() {
+        assert_eq!(forwarded_for("203.0.113.7").unwrap(), "203.0.113.7");
+        assert!(forwarded_for("random:198.51.100.0/24")
+            .unwrap()
+            .starts_with("198.51.100."));
+        assert!(forwarded_for("not-an-ip").is_err());
+    }
+}
@@ -1,24 +1,319 @@
-//! Generic HLS/DASH provider for direct manifest URLs.
+//! Generic HLS/DASH provider for direct manifest URLs and web pages.
 //!
 //! This provider matches any URL ending in `.m3u8` or `.mpd` and
-//! returns it verbatim as the manifest URL. It does not perform any
-//! API calls -- the manifest URL is used directly by the backends.
+//! returns it verbatim as the manifest URL. Any other URL is fetched as
+//! an HTML page and searched for embedded streams: `<video>`/`<source>`
+//! elements, JSON-LD `VideoObject`s, `og:video` and inline player config.
 
-use anyhow::{anyhow, Result};
+use std::sync::LazyLock;
+
+use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
+use regex::Regex;
+use scraper::{Html, Selector};
+use url::Url;
 
 use crate::stream::provider::{SeriesInfo, StreamInfo, StreamProvider};
+use crate::AcceleratedClient;
+
+/// Quoted `.m3u8`/`.mpd` URLs inside inline scripts (player configs)
+static SCRIPT_MANIFEST_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"["']([^"'\s]+?\.(?:m3u8|mpd)(?:\?[^"'\s]*)?)["']"#).expect("valid manifest regex")
+});
+
+/// Where on the page a stream URL was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StreamOrigin {
+    /// Quoted manifest URL inside an inline `<script>`
+    PlayerConfig,
+    /// `og:video` / `og:video:url` / `og:video:secure_url` meta tag
+    OpenGraph,
+    /// JSON-LD `VideoObject.contentUrl`
+    JsonLd,
+    /// `<video src>` or `<video><source src>`
+    VideoElement,
+}
+
+/// Format of a discovered stream URL
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StreamKind {
+    /// Unknown or player page (e.g. an `og:video` embed URL)
+    Other,
+    /// Progressive file (`.mp4`, `.webm`, ...)
+    Progressive,
+    /// DASH `.mpd` manifest
+    Dash,
+    /// HLS `.m3u8` manifest
+    Hls,
+}
+
+/// A stream URL discovered on a web page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamCandidate {
+    /// Absolute stream URL
+    pub url: String,
+    pub kind: StreamKind,
+    pub origin: StreamOrigin,
+}
+
+/// Page metadata used to fill [`StreamInfo`] for discovered streams
+#[derive(Debug, Default)]
+struct PageMetadata {
+    title: Option<String>,
+    description: Option<String>,
+    thumbnail_url: Option<String>,
+    duration_seconds: Option<u64>,
+}
 
 /// Provider for direct HLS `.m3u8` and DASH `.mpd` URLs.
-pub struct GenericHlsProvider;
+pub struct GenericHlsProvider {
+    /// Client for page fetches; a default [`AcceleratedClient`] is built on
+    /// first use when unset
+    client: Option<AcceleratedClient>,
+}
 
 impl GenericHlsProvider {
     #[must_use]
     pub fn new() -> Self {
-        Self
+        Self { client: None }
+    }
+
+    /// Fetch pages with `client`, e.g. one carrying the caller's cookies
+    #[must_use]
+    pub fn with_client(mut self, client: AcceleratedClient) -> Self {
+        self.client = Some(client);
+        self
+    }
+}
+
+impl GenericHlsProvider {
+    /// Fetch `page_url` and build stream info from the best embedded stream
+    async fn stream_info_from_page(&self, page_url: &str) -> Result<StreamInfo> {
+        let html = match &self.client {
+            Some(client) => Self::fetch_page(client, page_url).await?,
+            None => Self::fetch_page(&AcceleratedClient::new()?, page_url).await?,
+        };
+
+        let best = discover_streams(&html, page_url)
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("No video stream found on {page_url}"))?;
+        let meta = page_metadata(&html, page_url);
+
+        Ok(StreamInfo {
+            id: page_url.to_string(),
+            title: meta.title.unwrap_or_else(|| "Embedded Stream".to_string()),
+            description: meta.description,
+            duration_seconds: meta.duration_seconds,
+            manifest_url: best.url,
+            is_live: false,
+            qualities: vec![],
+            thumbnail_url: meta.thumbnail_url,
+        })
+    }
+
+    /// Fetch a page with the client's browser fingerprint
+    async fn fetch_page(client: &AcceleratedClient, page_url: &str) -> Result<String> {
+        Ok(client
+            .fetch(page_url)
+            .await?
+            .error_for_status()
+            .with_context(|| format!("Failed to fetch page {page_url}"))?
+            .text()
+            .await?)
+    }
+}
+
+/// Find stream URLs embedded in an HTML page, best first.
+///
+/// Adaptive manifests (HLS, then DASH) beat progressive files, which beat
+/// anything else; ties go to `<video>`/`<source>`, then JSON-LD, then
+/// `og:video`, then inline player config.
+#[must_use]
+pub fn discover_streams(html: &str, page_url: &str) -> Vec<StreamCandidate> {
+    let document = Html::parse_document(html);
+    let base = Url::parse(page_url).ok();
+    let mut candidates: Vec<StreamCandidate> = Vec::new();
+
+    let mut push = |raw: &str, mime: Option<&str>, origin: StreamOrigin| {
+        let raw = raw.trim().replace("\\/", "/");
+        if raw.is_empty() || raw.starts_with("blob:") || raw.starts_with("data:") {
+            return;
+        }
+        let Some(url) = resolve(base.as_ref(), &raw) else {
+            return;
+        };
+        if candidates.iter().any(|c| c.url == url) {
+            return;
+        }
+        let kind = stream_kind(&url, mime);
+        candidates.push(StreamCandidate { url, kind, origin });
+    };
+
+    for element in select(&document, "video[src], video source[src], source[src]") {
+        if let Some(src) = element.value().attr("src") {
+            push(
+                src,
+                element.value().attr("type"),
+                StreamOrigin::VideoElement,
+            );
+        }
+    }
+
+    for script in select(&document, r#"script[type="application/ld+json"]"#) {
+        let text = script.text().collect::<String>();
+        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) {
+            for video in video_objects(&json) {
+                if let Some(content_url) = video.get("contentUrl").and_then(|v| v.as_str()) {
+                    push(content_url, None, StreamOrigin::JsonLd);
+                }
+            }
+        }
+    }
+
+    for property in ["og:video:secure_url", "og:video:url", "og:video"] {
+        let selector = format!(r#"meta[property="{property}"][content]"#);
+        for meta in select(&document, &selector) {
+            if let Some(content) = meta.value().attr("content") {
+                push(content, None, StreamOrigin::OpenGraph);
+            }
+        }
+    }
+
+    for script in select(&document, "script:not([src])") {
+        let text = script.text().collect::<String>();
+        for cap in SCRIPT_MANIFEST_RE.captures_iter(&text) {
+            push(&cap[1], None, StreamOrigin::PlayerConfig);
+        }
+    }
+
+    candidates.sort_by(|a, b| b.kind.cmp(&a.kind).then(b.origin.cmp(&a.origin)));
+    candidates
+}
+
+fn select<'a>(document: &'a Html, selector: &str) -> Vec<scraper::ElementRef<'a>> {
+    Selector::parse(selector)
+        .map(|sel| document.select(&sel).collect())
+        .unwrap_or_default()
+}
+
+fn resolve(base: Option<&Url>, raw: &str) -> Option<String> {
+    match Url::parse(raw) {
+        Ok(url) => Some(url.to_string()),
+        Err(_) => base?.join(raw).ok().map(String::from),
+    }
+}
+
+fn stream_kind(url: &str, mime: Option<&str>) -> StreamKind {
+    let mime = mime.unwrap_or_default().to_ascii_lowercase();
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let extension = std::path::Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "m3u8" => StreamKind::Hls,
+        "mpd" => StreamKind::Dash,
+        "mp4" | "m4v" | "webm" | "mov" | "ogv" => StreamKind::Progressive,
+        _ if mime.contains("mpegurl") => StreamKind::Hls,
+        _ if mime == "application/dash+xml" => StreamKind::Dash,
+        _ if mime.starts_with("video/") => StreamKind::Progressive,
+        _ => StreamKind::Other,
+    }
+}
+
+/// JSON-LD `VideoObject`s at the top level, in arrays, or under `@graph`
+fn video_objects(json: &serde_json::Value) -> Vec<&serde_json::Value> {
+    match json {
+        serde_json::Value::Array(items) => items.iter().flat_map(video_objects).collect(),
+        serde_json::Value::Object(obj) => {
+            let is_video = match obj.get("@type") {
+                Some(serde_json::Value::String(t)) => t == "VideoObject",
+                Some(serde_json::Value::Array(types)) => types.iter().any(|t| t == "VideoObject"),
+                _ => false,
+            };
+            let mut found = if is_video { vec![json] } else { Vec::new() };
+            if let Some(graph) = obj.get("@graph") {
+                found.extend(video_objects(graph));
+            }
+            found
+        }
+        _ => Vec::new(),
     }
 }
 
+fn page_metadata(html: &str, page_url: &str) -> PageMetadata {
+    let document = Html::parse_document(html);
+    let base = Url::parse(page_url).ok();
+    let meta = |property: &str| {
+        let selector = format!(r#"meta[property="{property}"][content]"#);
+        select(&document, &selector)
+            .first()
+            .and_then(|m| m.value().attr("content"))
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty())
+    };
+
+    let mut info = PageMetadata {
+        title: meta("og:title").or_else(|| {
+            select(&document, "title")
+                .first()
+                .map(|t| t.text().collect::<String>().trim().to_string())
+                .filter(|t| !t.is_empty())
+        }),
+        description: meta("og:description"),
+        thumbnail_url: meta("og:image").or_else(|| {
+            select(&document, "video[poster]")
+                .first()
+                .and_then(|v| v.value().attr("poster"))
+                .and_then(|p| resolve(base.as_ref(), p))
+        }),
+        duration_seconds: None,
+    };
+
+    for script in select(&document, r#"script[type="application/ld+json"]"#) {
+        let text = script.text().collect::<String>();
+        let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) else {
+            continue;
+        };
+        if let Some(video) = video_objects(&json).into_iter().next() {
+            let field = |name: &str| video.get(name).and_then(|v| v.as_str()).map(String::from);
+            info.title = info.title.or_else(|| field("name"));
+            info.description = info.description.or_else(|| field("description"));
+            info.duration_seconds = field("duration")
+                .as_deref()
+                .and_then(parse_iso8601_duration);
+            break;
+        }
+    }
+
+    info
+}
+
+/// Parse an ISO 8601 duration such as `PT1H2M3S` into seconds
+fn parse_iso8601_duration(duration: &str) -> Option<u64> {
+    let time = duration.strip_prefix("PT")?;
+    let mut total = 0u64;
+    let mut number = String::new();
+    for c in time.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let value: u64 = number.parse().ok()?;
+        number.clear();
+        total += match c {
+            'H' => value * 3600,
+            'M' => value * 60,
+            'S' => value,
+            _ => return None,
+        };
+    }
+    number.is_empty().then_some(total)
+}
+
 impl Default for GenericHlsProvider {
     fn default() -> Self {
         Self::new()
@@ -36,6 +331,10 @@ impl StreamProvider for GenericHlsProvider {
     }
 
     async fn get_stream_info(&self, url: &str) -> Result<StreamInfo> {
+        if !self.matches(url) && url.starts_with("http") {
+            return self.stream_info_from_page(url).await;
+        }
+
         Ok(StreamInfo {
             id: url.to_string(),
             title: "Direct Stream".to_string(),
@@ -115,9 +414,104 @@ mod tests {
         assert!(result.is_err());
     }
 
+    const PAGE_WITH_VIDEO: &str = r#"<html><head>
+<title>Launch event</title>
+<meta property="og:title" content="Launch event replay">
+<meta property="og:video" content="https://player.example.com/embed/42">
+<meta property="og:image" content="https://cdn.example.com/poster.jpg">
+<script type="application/ld+json">
+{"@context":"https://schema.org","@type":"VideoObject","name":"Launch","duration":"PT1H2M3S",
+ "contentUrl":"https://cdn.example.com/launch.mp4"}
+</script>
+</head><body>
+<video controls poster="/poster.jpg">
+  <source src="/media/launch/master.m3u8" type="application/x-mpegURL">
+</video>
+<script>var player = {"dash": "https:\/\/cdn.example.com\/launch\/manifest.mpd"};</script>
+</body></html>"#;
+
+    #[test]
+    fn test_discovers_and_prefers_source_manifest() {
+        let candidates = discover_streams(PAGE_WITH_VIDEO, "https://example.com/events/launch");
+
+        assert_eq!(
+            candidates[0],
+            StreamCandidate {
+                url: "https://example.com/media/launch/master.m3u8".to_string(),
+                kind: StreamKind::Hls,
+                origin: StreamOrigin::VideoElement,
+            }
+        );
+        assert_eq!(
+            candidates[1].url,
+            "https://cdn.example.com/launch/manifest.mpd"
+        );
+        assert_eq!(candidates[1].origin, StreamOrigin::PlayerConfig);
+        assert_eq!(candidates[2].url, "https://cdn.example.com/launch.mp4");
+        assert_eq!(candidates[2].origin, StreamOrigin::JsonLd);
+        let og = candidates.last().unwrap();
+        assert_eq!(og.url, "https://player.example.com/embed/42");
+        assert_eq!(og.kind, StreamKind::Other);
+        assert_eq!(og.origin, StreamOrigin::OpenGraph);
+    }
+
+    #[test]
+    fn test_og_video_manifest_beats_progressive_source() {
+        let html = r#"<meta property="og:video:secure_url" content="https://cdn.example.com/v.m3u8">
+<video src="/clip.mp4"></video>"#;
+        let candidates = discover_streams(html, "https://example.com/watch");
+        assert_eq!(candidates[0].url, "https://cdn.example.com/v.m3u8");
+        assert_eq!(candidates[0].origin, StreamOrigin::OpenGraph);
+        assert_eq!(candidates[1].url, "https://example.com/clip.mp4");
+    }
+
+    #[test]
+    fn test_page_metadata() {
+        let meta = page_metadata(PAGE_WITH_VIDEO, "https://example.com/events/launch");
+        assert_eq!(meta.title.as_deref(), Some("Launch event replay"));
+        assert_eq!(
+            meta.thumbnail_url.as_deref(),
+            Some("https://cdn.example.com/poster.jpg")
+        );
+        assert_eq!(meta.duration_seconds, Some(3723));
+        assert_eq!(parse_iso8601_duration("PT45S"), Some(45));
+        assert_eq!(parse_iso8601_duration("P1D"), None);
+    }
+
+    #[tokio::test]
+    async fn test_page_fetch_uses_browser_client() {
+        use crate::test_support::{serve_once, Response};
+
+        let (addr, server) = serve_once(|_| Response::typed("text/html", PAGE_WITH_VIDEO));
+        let client = tokio::task::spawn_blocking(|| {
+            let inner = reqwest::Client::builder()
+                .default_headers(crate::fingerprint::chrome_profile().to_headers())
+                .build()
+                .unwrap();
+            AcceleratedClient::from_client(inner).unwrap()
+        })
+        .await
+        .unwrap();
+        let page_url = format!("http://{addr}/events/launch");
+        let info = GenericHlsProvider::new()
+            .with_client(client)
+            .get_stream_info(&page_url)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            info.manifest_url,
+            format!("http://{addr}/media/launch/master.m3u8")
+        );
+        let request = server.join().unwrap();
+        let user_agent = request.header("user-agent").unwrap();
+        assert!(user_agent.starts_with("Mozilla/5.0"), "{user_agent}");
+        assert!(request.header("accept-language").is_some());
+    }
+
     #[test]
     fn test_default() {
-        let provider = GenericHlsProvider;
+        let provider = GenericHlsProvider::default();
         assert_eq!(provider.name(), "generic");
     }
 }
@@ -4,12 +4,15 @@ use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::Deserialize;
+use std::collections::HashMap;
 
-use crate::stream::provider::{EpisodeInfo, SeriesInfo, StreamInfo, StreamProvider};
+use crate::stream::provider::{forwarded_for, EpisodeInfo, SeriesInfo, StreamInfo, StreamProvider};
 
 const YLE_APP_ID: &str = "player_static_prod";
 const YLE_APP_KEY: &str = "8930d72170e48303cf5f3867780d549b";
 const YLE_API_BASE: &str = "https://player.api.yle.fi/v1/preview";
+/// Finnish consumer ISP (Elisa) range used to unlock geo-restricted content
+const YLE_XFF_RANGE: &str = "random:91.152.0.0/13";
 
 pub struct YleProvider {
     client: Client,
@@ -223,6 +226,16 @@ impl StreamProvider for YleProvider {
         url.contains("areena.yle.fi") || url.contains("arenan.yle.fi")
     }
 
+    fn default_headers(&self) -> HashMap<String, String> {
+        let mut headers = HashMap::new();
+        headers.insert("Referer".to_string(), "https://areena.yle.fi".to_string());
+        headers.insert("Origin".to_string(), "https://areena.yle.fi".to_string());
+        if let Ok(ip) = forwarded_for(YLE_XFF_RANGE) {
+            headers.insert("X-Forwarded-For".to_string(), ip);
+        }
+        headers
+    }
+
     async fn get_stream_info(&self, id: &str) -> Result<StreamInfo> {
         let program_id = Self::extract_program_id(id);
         let preview = self.fetch_preview(&program_id).await?;
@@ -0,0 +1,68 @@
+//! Request metrics via the `metrics` facade
+//!
+//! [`AcceleratedClient`](crate::AcceleratedClient) reports every request it
+//! executes to whatever `metrics` recorder is installed:
+//!
+//! | Metric | Kind | Labels |
+//! |--------|------|--------|
+//! | `nab_requests_total` | counter | `host`, `status` |
+//! | `nab_request_errors_total` | counter | `host` |
+//! | `nab_request_duration_seconds` | histogram | `host` |
+//! | `nab_response_bytes_total` | counter | `host` |
+//! | `nab_cache_hits_total` | counter | `host`, `source` |
+//!
+//! Services without their own recorder can call [`install_prometheus`] once
+//! at startup and expose [`render_prometheus`] for scraping.
+
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use anyhow::Result;
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+
+pub const REQUESTS_TOTAL: &str = "nab_requests_total";
+pub const REQUEST_ERRORS_TOTAL: &str = "nab_request_errors_total";
+pub const REQUEST_DURATION_SECONDS: &str = "nab_request_duration_seconds";
+pub const RESPONSE_BYTES_TOTAL: &str = "nab_response_bytes_total";
+pub const CACHE_HITS_TOTAL: &str = "nab_cache_hits_total";
+
+static PROMETHEUS: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Record a completed request (`status: None` for transport errors)
+pub fn record_request(host: &str, status: Option<u16>, elapsed: Duration, bytes: Option<u64>) {
+    let host = host.to_string();
+    if let Some(status) = status {
+        metrics::counter!(REQUESTS_TOTAL, "host" => host.clone(), "status" => status.to_string())
+            .increment(1);
+    } else {
+        metrics::counter!(REQUESTS_TOTAL, "host" => host.clone(), "status" => "error").increment(1);
+        metrics::counter!(REQUEST_ERRORS_TOTAL, "host" => host.clone()).increment(1);
+    }
+    metrics::histogram!(REQUEST_DURATION_SECONDS, "host" => host.clone())
+        .record(elapsed.as_secs_f64());
+    if let Some(bytes) = bytes {
+        metrics::counter!(RESPONSE_BYTES_TOTAL, "host" => host).increment(bytes);
+    }
+}
+
+/// Record a response served without touching the network
+pub fn record_cache_hit(host: &str, source: &'static str) {
+    metrics::counter!(CACHE_HITS_TOTAL, "host" => host.to_string(), "source" => source)
+        .increment(1);
+}
+
+/// Install a global Prometheus recorder; later calls return the same handle
+pub fn install_prometheus() -> Result<PrometheusHandle> {
+    if let Some(handle) = PROMETHEUS.get() {
+        return Ok(handle.clone());
+    }
+    let handle = PrometheusBuilder::new().install_recorder()?;
+    Ok(PROMETHEUS.get_or_init(|| handle).clone())
+}
+
+/// Current metrics in Prometheus text exposition format, if
+/// [`install_prometheus`] has been called
+#[must_use]
+pub fn render_prometheus() -> Option<String> {
+    PROMETHEUS.get().map(PrometheusHandle::render)
+}
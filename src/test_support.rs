@@ -0,0 +1,192 @@
+//! Test helpers: a minimal local HTTP/1.1 server and an HTTP/1.1 client
+//!
+//! Shared by the library's tests and, through `#[path]`, by the binaries'
+//! tests; the including module must have `AcceleratedClient` in scope.
+
+// Each crate that includes this file uses a different subset
+#![allow(dead_code)]
+
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::Path;
+use std::thread::JoinHandle;
+
+use super::AcceleratedClient;
+
+/// A request received by [`serve`]
+#[derive(Debug, Clone, Default)]
+pub struct Request {
+    pub method: String,
+    /// Path and query, as sent
+    pub target: String,
+    /// Headers in arrival order, names lowercased
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    /// First value of a header, by case-insensitive name
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// The head as it arrived on the wire (request line and headers)
+    pub fn head(&self) -> String {
+        let mut head = format!("{} {} HTTP/1.1\r\n", self.method, self.target);
+        for (name, value) in &self.headers {
+            let _ = write!(head, "{name}: {value}\r\n");
+        }
+        head
+    }
+}
+
+/// A response written by [`serve`]; `Connection: close` is added, and so is
+/// `Content-Length` unless set explicitly (e.g. for `HEAD`)
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// Empty response with the given status
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// `200 OK` with a body
+    pub fn ok(body: impl Into<Vec<u8>>) -> Self {
+        Self::status(200).body(body)
+    }
+
+    /// `200 OK` with a body and `Content-Type`
+    pub fn typed(content_type: &str, body: impl Into<Vec<u8>>) -> Self {
+        Self::ok(body).header("Content-Type", content_type)
+    }
+
+    #[must_use]
+    pub fn header(mut self, name: &str, value: impl Into<String>) -> Self {
+        self.headers.push((name.to_string(), value.into()));
+        self
+    }
+
+    #[must_use]
+    pub fn body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    fn write_to(&self, stream: &mut TcpStream) -> std::io::Result<()> {
+        let reason = reqwest::StatusCode::from_u16(self.status)
+            .ok()
+            .and_then(|s| s.canonical_reason())
+            .unwrap_or("Status");
+        let mut head = format!("HTTP/1.1 {} {reason}\r\n", self.status);
+        for (name, value) in &self.headers {
+            let _ = write!(head, "{name}: {value}\r\n");
+        }
+        if !self
+            .headers
+            .iter()
+            .any(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        {
+            let _ = write!(head, "Content-Length: {}\r\n", self.body.len());
+        }
+        head.push_str("Connection: close\r\n\r\n");
+        stream.write_all(head.as_bytes())?;
+        stream.write_all(&self.body)
+    }
+}
+
+/// Read one request (head and `Content-Length` body) from a connection
+pub fn read_request(stream: &TcpStream) -> Option<Request> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).ok().filter(|&n| n > 0)?;
+    let mut parts = line.split_whitespace();
+    let mut request = Request {
+        method: parts.next()?.to_string(),
+        target: parts.next()?.to_string(),
+        ..Request::default()
+    };
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).ok()? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            request
+                .headers
+                .push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+
+    let length = request
+        .header("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    request.body = vec![0; length];
+    reader.read_exact(&mut request.body).ok()?;
+    Some(request)
+}
+
+/// Answer every connection with `handler`, one request per connection, on
+/// a background thread that lives until the test process exits
+pub fn serve<F>(mut handler: F) -> SocketAddr
+where
+    F: FnMut(&Request) -> Response + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            if let Some(request) = read_request(&stream) {
+                let _ = handler(&request).write_to(&mut stream);
+            }
+        }
+    });
+    addr
+}
+
+/// Answer a single request with `handler`, then close the port
+pub fn serve_once<F>(handler: F) -> (SocketAddr, JoinHandle<Request>)
+where
+    F: FnOnce(&Request) -> Response + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let request = read_request(&stream).unwrap();
+        handler(&request).write_to(&mut stream).unwrap();
+        request
+    });
+    (addr, server)
+}
+
+/// Plain HTTP/1.1 client; `AcceleratedClient::new()` assumes HTTP/2 prior
+/// knowledge, which the mock servers don't speak
+pub async fn http1_client() -> AcceleratedClient {
+    tokio::task::spawn_blocking(|| AcceleratedClient::from_client(reqwest::Client::new()))
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+/// Write an executable `/bin/sh` script standing in for an external tool
+#[cfg(unix)]
+pub fn write_stub(path: &Path, body: &str) {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::write(path, format!("#!/bin/sh\n{body}")).unwrap();
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).unwrap();
+}
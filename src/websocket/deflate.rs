@@ -0,0 +1,470 @@
+//! `permessage-deflate` (RFC 7692) for the WebSocket client.
+//!
+//! tungstenite fails the connection on frames with the RSV1 bit set, so
+//! compressed frames from the server are inflated by [`InflateStream`], a
+//! transport adapter between TLS and tungstenite that rewrites them into
+//! plain frames. Outgoing messages are compressed with [`Deflater`] and sent
+//! as raw frames with RSV1 set.
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// Extension offer sent in `Sec-WebSocket-Extensions`.
+///
+/// `client_max_window_bits` is deliberately not offered, so the server cannot
+/// shrink the window our compressor uses. Inflation always uses the maximum
+/// 32 KiB window, which covers any `server_max_window_bits` the server picks.
+pub(crate) const OFFER: &str = "permessage-deflate";
+
+/// Trailer stripped from each compressed message (RFC 7692 §7.2.1).
+const TAIL: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
+const OP_CONTINUATION: u8 = 0x0;
+const OP_TEXT: u8 = 0x1;
+const OP_BINARY: u8 = 0x2;
+
+/// A server message (or frame) larger than the configured maximum.
+///
+/// Carried inside the `io::Error` returned by [`InflateStream`] so the
+/// connection can be closed with status 1009 (message too big).
+#[derive(Debug)]
+pub(crate) struct MessageTooLarge {
+    pub limit: usize,
+}
+
+impl std::fmt::Display for MessageTooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "message exceeds {} bytes", self.limit)
+    }
+}
+
+impl std::error::Error for MessageTooLarge {}
+
+impl MessageTooLarge {
+    fn error(limit: usize) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, Self { limit })
+    }
+
+    /// Whether `error` was caused by an oversized message.
+    pub fn is(error: &io::Error) -> bool {
+        error
+            .get_ref()
+            .is_some_and(|inner| inner.downcast_ref::<Self>().is_some())
+    }
+}
+
+/// Extension parameters accepted by the server.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct DeflateParams {
+    /// Server resets its compression context after every message.
+    pub server_no_context_takeover: bool,
+    /// We must reset our compression context after every message.
+    pub client_no_context_takeover: bool,
+}
+
+impl DeflateParams {
+    /// Parse a `Sec-WebSocket-Extensions` response header.
+    ///
+    /// Returns `None` when the server did not accept `permessage-deflate`.
+    pub fn from_response_header(header: &str) -> Option<Self> {
+        header.split(',').find_map(|extension| {
+            let mut parts = extension.split(';').map(str::trim);
+            if !parts.next()?.eq_ignore_ascii_case("permessage-deflate") {
+                return None;
+            }
+
+            let mut params = Self::default();
+            for param in parts {
+                let name = param.split('=').next().unwrap_or_default().trim();
+                if name.eq_ignore_ascii_case("server_no_context_takeover") {
+                    params.server_no_context_takeover = true;
+                } else if name.eq_ignore_ascii_case("client_no_context_takeover") {
+                    params.client_no_context_takeover = true;
+                }
+            }
+            Some(params)
+        })
+    }
+}
+
+/// Compresses outgoing message payloads.
+pub(crate) struct Deflater {
+    compress: Compress,
+    no_context_takeover: bool,
+}
+
+impl Deflater {
+    pub fn new(params: DeflateParams) -> Self {
+        Self {
+            compress: Compress::new(Compression::default(), false),
+            no_context_takeover: params.client_no_context_takeover,
+        }
+    }
+
+    /// Compress one message payload, without the trailing `00 00 ff ff`.
+    pub fn compress(&mut self, data: &[u8]) -> io::Result<Vec<u8>> {
+        let mut out = Vec::with_capacity(data.len() / 2 + 64);
+        let mut input = data;
+
+        loop {
+            if out.capacity() - out.len() < 64 {
+                out.reserve(out.capacity().max(64));
+            }
+            let before_in = self.compress.total_in();
+            self.compress
+                .compress_vec(input, &mut out, FlushCompress::Sync)
+                .map_err(io::Error::other)?;
+            let consumed =
+                usize::try_from(self.compress.total_in() - before_in).map_err(io::Error::other)?;
+            input = &input[consumed..];
+
+            // A sync flush is complete once all input is consumed and the
+            // output buffer was not filled to the brim
+            if input.is_empty() && out.len() < out.capacity() {
+                break;
+            }
+        }
+
+        if out.ends_with(&TAIL) {
+            out.truncate(out.len() - TAIL.len());
+        }
+        if self.no_context_takeover {
+            self.compress.reset();
+        }
+        Ok(out)
+    }
+}
+
+/// Decompresses incoming message payloads.
+pub(crate) struct Inflater {
+    decompress: Decompress,
+    no_context_takeover: bool,
+}
+
+impl Inflater {
+    pub fn new(params: DeflateParams) -> Self {
+        Self {
+            decompress: Decompress::new(false),
+            no_context_takeover: params.server_no_context_takeover,
+        }
+    }
+
+    /// Decompress one complete message payload of at most `limit` bytes.
+    pub fn decompress(&mut self, data: &[u8], limit: usize) -> io::Result<Vec<u8>> {
+        let mut input = Vec::with_capacity(data.len() + TAIL.len());
+        input.extend_from_slice(data);
+        input.extend_from_slice(&TAIL);
+
+        let mut out = Vec::with_capacity(data.len() * 4 + 64);
+        let mut pos = 0;
+
+        loop {
+            if out.len() == out.capacity() {
+                out.reserve(out.capacity().max(1024));
+            }
+            let before_in = self.decompress.total_in();
+            let before_out = out.len();
+            self.decompress
+                .decompress_vec(&input[pos..], &mut out, FlushDecompress::Sync)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            let consumed = usize::try_from(self.decompress.total_in() - before_in)
+                .map_err(io::Error::other)?;
+            pos += consumed;
+
+            if out.len() > limit {
+                return Err(MessageTooLarge::error(limit));
+            }
+
+            let stalled = consumed == 0 && out.len() == before_out;
+            if (pos >= input.len() && out.len() < out.capacity()) || stalled {
+                break;
+            }
+        }
+
+        if self.no_context_takeover {
+            self.decompress.reset(false);
+        }
+        Ok(out)
+    }
+}
+
+/// Transport adapter that inflates compressed server frames.
+///
+/// Passes the HTTP handshake response through untouched while reading its
+/// `Sec-WebSocket-Extensions` header. If the server accepted the extension,
+/// every following compressed message is reassembled, inflated and handed
+/// to tungstenite as a single plain frame; all other frames pass through.
+pub(crate) struct InflateStream<S> {
+    inner: S,
+    /// Buffered handshake response, `Some` until the header block is complete.
+    handshake: Option<Vec<u8>>,
+    inflater: Option<Inflater>,
+    /// Undecoded bytes read from `inner`.
+    raw: Vec<u8>,
+    /// Decoded bytes waiting to be read by tungstenite.
+    ready: Vec<u8>,
+    ready_pos: usize,
+    /// Compressed message being reassembled from fragments: (opcode, payload).
+    message: Option<(u8, Vec<u8>)>,
+    /// Largest frame or inflated message accepted from the server.
+    max_message_size: usize,
+    /// Set once a message exceeded the limit; later reads see EOF.
+    oversized: bool,
+}
+
+impl<S> InflateStream<S> {
+    /// Wrap a transport. With `enabled == false` the adapter is a pure passthrough.
+    pub fn new(inner: S, enabled: bool, max_message_size: usize) -> Self {
+        Self {
+            inner,
+            handshake: enabled.then(Vec::new),
+            inflater: None,
+            raw: Vec::new(),
+            ready: Vec::new(),
+            ready_pos: 0,
+            message: None,
+            max_message_size,
+            oversized: false,
+        }
+    }
+
+    fn process(&mut self, bytes: &[u8]) -> io::Result<()> {
+        if let Some(handshake) = &mut self.handshake {
+            handshake.extend_from_slice(bytes);
+            let Some(end) = handshake.windows(4).position(|w| w == b"\r\n\r\n") else {
+                return Ok(());
+            };
+            let rest = handshake.split_off(end + 4);
+            let head = std::mem::take(handshake);
+            self.handshake = None;
+
+            self.inflater = String::from_utf8_lossy(&head)
+                .lines()
+                .filter_map(|line| line.split_once(':'))
+                .filter(|(name, _)| name.trim().eq_ignore_ascii_case("sec-websocket-extensions"))
+                .find_map(|(_, value)| DeflateParams::from_response_header(value))
+                .map(Inflater::new);
+            self.ready.extend_from_slice(&head);
+            return self.process(&rest);
+        }
+
+        if self.inflater.is_none() {
+            self.ready.extend_from_slice(bytes);
+            return Ok(());
+        }
+
+        self.raw.extend_from_slice(bytes);
+        while let Some((header_len, payload_len)) = frame_header(&self.raw) {
+            let buffered = self.message.as_ref().map_or(0, |(_, data)| data.len());
+            if payload_len.saturating_add(buffered) > self.max_message_size {
+                return Err(MessageTooLarge::error(self.max_message_size));
+            }
+            if self.raw.len() < header_len + payload_len {
+                break;
+            }
+            let frame: Vec<u8> = self.raw.drain(..header_len + payload_len).collect();
+            let fin = frame[0] & 0x80 != 0;
+            let rsv1 = frame[0] & 0x40 != 0;
+            let opcode = frame[0] & 0x0f;
+            let payload = &frame[header_len..];
+
+            if rsv1 && (opcode == OP_TEXT || opcode == OP_BINARY) {
+                self.message = Some((opcode, payload.to_vec()));
+            } else if opcode == OP_CONTINUATION && self.message.is_some() {
+                if let Some((_, data)) = &mut self.message {
+                    data.extend_from_slice(payload);
+                }
+            } else {
+                // Control frames (possibly interleaved) and uncompressed data
+                self.ready.extend_from_slice(&frame);
+                continue;
+            }
+
+            if fin {
+                if let (Some((opcode, data)), Some(inflater)) =
+                    (self.message.take(), &mut self.inflater)
+                {
+                    let inflated = inflater.decompress(&data, self.max_message_size)?;
+                    encode_frame(&mut self.ready, opcode, &inflated);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Header and declared payload length of the first frame in `buf`, once its
+/// header is complete.
+fn frame_header(buf: &[u8]) -> Option<(usize, usize)> {
+    if buf.len() < 2 {
+        return None;
+    }
+    let masked = buf[1] & 0x80 != 0;
+    let (len_bytes, payload_len) = match buf[1] & 0x7f {
+        126 => (
+            2,
+            u64::from(u16::from_be_bytes(buf.get(2..4)?.try_into().ok()?)),
+        ),
+        127 => (8, u64::from_be_bytes(buf.get(2..10)?.try_into().ok()?)),
+        len => (0, u64::from(len)),
+    };
+    let header_len = 2 + len_bytes + if masked { 4 } else { 0 };
+    let payload_len = usize::try_from(payload_len).unwrap_or(usize::MAX);
+    (buf.len() >= header_len).then_some((header_len, payload_len))
+}
+
+/// Append a final, unmasked, uncompressed frame to `out`.
+fn encode_frame(out: &mut Vec<u8>, opcode: u8, payload: &[u8]) {
+    out.push(0x80 | opcode);
+    match payload.len() {
+        len @ 0..=125 => out.push(u8::try_from(len).unwrap_or_default()),
+        len => {
+            if let Ok(len) = u16::try_from(len) {
+                out.push(126);
+                out.extend_from_slice(&len.to_be_bytes());
+            } else {
+                out.push(127);
+                out.extend_from_slice(&(len as u64).to_be_bytes());
+            }
+        }
+    }
+    out.extend_from_slice(payload);
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for InflateStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if this.oversized {
+                return Poll::Ready(Ok(()));
+            }
+            if this.ready_pos < this.ready.len() {
+                let n = buf.remaining().min(this.ready.len() - this.ready_pos);
+                buf.put_slice(&this.ready[this.ready_pos..this.ready_pos + n]);
+                this.ready_pos += n;
+                if this.ready_pos == this.ready.len() {
+                    this.ready.clear();
+                    this.ready_pos = 0;
+                }
+                return Poll::Ready(Ok(()));
+            }
+
+            let mut chunk = [0u8; 8192];
+            let mut chunk_buf = ReadBuf::new(&mut chunk);
+            match Pin::new(&mut this.inner).poll_read(cx, &mut chunk_buf) {
+                Poll::Ready(Ok(())) => {
+                    let filled = chunk_buf.filled();
+                    if filled.is_empty() {
+                        // EOF: hand over anything still buffered, then signal EOF
+                        if let Some(handshake) = this.handshake.take() {
+                            this.ready.extend_from_slice(&handshake);
+                        }
+                        this.ready.append(&mut this.raw);
+                        if this.ready.is_empty() {
+                            return Poll::Ready(Ok(()));
+                        }
+                        continue;
+                    }
+                    let filled = filled.to_vec();
+                    if let Err(e) = this.process(&filled) {
+                        if MessageTooLarge::is(&e) {
+                            // Drop the partial message; the caller closes with 1009
+                            this.oversized = true;
+                            this.raw = Vec::new();
+                            this.message = None;
+                        }
+                        return Poll::Ready(Err(e));
+                    }
+                }
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for InflateStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_accepted_extension() {
+        let params = DeflateParams::from_response_header(
+            "permessage-deflate; server_no_context_takeover; server_max_window_bits=10",
+        )
+        .unwrap();
+        assert!(params.server_no_context_takeover);
+        assert!(!params.client_no_context_takeover);
+    }
+
+    #[test]
+    fn test_parse_declined_extension() {
+        assert_eq!(
+            DeflateParams::from_response_header("x-webkit-deflate-frame"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_deflate_inflate_roundtrip_with_context_takeover() {
+        let params = DeflateParams::default();
+        let mut deflater = Deflater::new(params);
+        let mut inflater = Inflater::new(params);
+
+        let message = br#"{"jsonrpc":"2.0","method":"subscribe","params":["newHeads"]}"#;
+        for _ in 0..3 {
+            let compressed = deflater.compress(message).unwrap();
+            assert!(!compressed.ends_with(&TAIL));
+            assert_eq!(inflater.decompress(&compressed, 1024).unwrap(), message);
+        }
+    }
+
+    #[test]
+    fn test_frame_header_extended_length() {
+        let mut frame = Vec::new();
+        encode_frame(&mut frame, OP_BINARY, &[7u8; 300]);
+        assert_eq!(frame_header(&frame), Some((4, 300)));
+        assert_eq!(frame_header(&frame[..100]), Some((4, 300)));
+        assert_eq!(frame_header(&frame[..3]), None);
+    }
+
+    #[test]
+    fn test_inflate_rejects_oversized_message() {
+        let params = DeflateParams::default();
+        let compressed = Deflater::new(params)
+            .compress(&vec![b'a'; 100_000])
+            .unwrap();
+        assert!(compressed.len() < 1024);
+
+        let err = Inflater::new(params)
+            .decompress(&compressed, 10_000)
+            .unwrap_err();
+        assert!(MessageTooLarge::is(&err));
+    }
+}
@@ -0,0 +1,1071 @@
+//! WebSocket Client with TLS
+//!
+//! Features:
+//! - Secure WebSocket (wss://) with TLS 1.3
+//! - Automatic ping/pong for keep-alive
+//! - Message fragmentation handling
+//! - Browser fingerprint matching
+//! - Opt-in `permessage-deflate` compression
+
+mod deflate;
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use futures::{SinkExt, StreamExt};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    client_async_with_config,
+    tungstenite::{
+        error::CapacityError,
+        handshake::client::generate_key,
+        http::{Request, Uri},
+        protocol::{
+            frame::{
+                coding::{CloseCode, Data as OpData, OpCode},
+                CloseFrame, Frame,
+            },
+            WebSocketConfig,
+        },
+        Error as WsError, Message,
+    },
+    MaybeTlsStream, WebSocketStream,
+};
+use tracing::{debug, info};
+
+use self::deflate::{DeflateParams, Deflater, InflateStream, MessageTooLarge};
+use crate::auth::CookieSource;
+use crate::fingerprint::BrowserProfile;
+
+/// Default limit on a single incoming message, after decompression (64 MiB).
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 64 << 20;
+
+/// Handshake options for [`WebSocket::connect_with`].
+///
+/// Lets callers offer subprotocols (`Sec-WebSocket-Protocol`), attach extra
+/// headers such as `Authorization`, and send cookies, either as a ready-made
+/// header string or extracted from a browser via [`CookieSource`].
+#[derive(Debug, Clone, Default)]
+pub struct WebSocketOptions {
+    /// Subprotocols to offer, in order of preference.
+    pub subprotocols: Vec<String>,
+    /// Extra handshake headers as `(name, value)` pairs.
+    pub headers: Vec<(String, String)>,
+    /// Raw `Cookie` header value.
+    pub cookies: Option<String>,
+    /// Browser to load cookies for the endpoint's host from.
+    pub cookie_source: Option<CookieSource>,
+    /// Offer `permessage-deflate` compression; falls back to uncompressed
+    /// messages when the server declines.
+    pub permessage_deflate: bool,
+    /// Largest incoming message accepted, after decompression; defaults to
+    /// [`DEFAULT_MAX_MESSAGE_SIZE`]. Larger messages close the connection
+    /// with status 1009.
+    pub max_message_size: Option<usize>,
+}
+
+impl WebSocketOptions {
+    /// Create empty options (equivalent to a plain [`WebSocket::connect`]).
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Offer a subprotocol during the handshake.
+    #[must_use]
+    pub fn with_subprotocol(mut self, protocol: impl Into<String>) -> Self {
+        self.subprotocols.push(protocol.into());
+        self
+    }
+
+    /// Add an extra handshake header.
+    #[must_use]
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Send a raw `Cookie` header value.
+    #[must_use]
+    pub fn with_cookies(mut self, cookies: impl Into<String>) -> Self {
+        self.cookies = Some(cookies.into());
+        self
+    }
+
+    /// Load cookies for the endpoint's host from a browser.
+    #[must_use]
+    pub fn with_cookie_source(mut self, source: CookieSource) -> Self {
+        self.cookie_source = Some(source);
+        self
+    }
+
+    /// Offer `permessage-deflate` compression.
+    #[must_use]
+    pub fn with_compression(mut self) -> Self {
+        self.permessage_deflate = true;
+        self
+    }
+
+    /// Limit incoming messages to `bytes` after decompression.
+    #[must_use]
+    pub fn with_max_message_size(mut self, bytes: usize) -> Self {
+        self.max_message_size = Some(bytes);
+        self
+    }
+}
+
+/// WebSocket connection with TLS support and automatic ping/pong.
+///
+/// Wraps a `tokio-tungstenite` stream with browser-like headers and
+/// transparent keep-alive handling. Ping frames received from the
+/// server are automatically answered with Pong.
+pub struct WebSocket {
+    stream: WebSocketStream<InflateStream<MaybeTlsStream<TcpStream>>>,
+    url: String,
+    protocol: Option<String>,
+    /// Compressor for outgoing messages when `permessage-deflate` was negotiated.
+    deflater: Option<Deflater>,
+}
+
+impl WebSocket {
+    /// Connect to a WebSocket endpoint
+    pub async fn connect(url: &str, profile: &BrowserProfile) -> Result<Self> {
+        Self::connect_with(url, profile, &WebSocketOptions::default()).await
+    }
+
+    /// Connect with custom handshake options (subprotocols, headers, cookies).
+    ///
+    /// If subprotocols are offered, the server must accept one of them or the
+    /// handshake fails; the accepted one is available via [`WebSocket::protocol`].
+    pub async fn connect_with(
+        url: &str,
+        profile: &BrowserProfile,
+        options: &WebSocketOptions,
+    ) -> Result<Self> {
+        // Ensure crypto provider is installed
+        let _ = rustls::crypto::ring::default_provider().install_default();
+
+        let uri: Uri = url.parse().context("Invalid WebSocket URL")?;
+        let host = uri.host().context("No host in URL")?;
+
+        // Build WebSocket upgrade request with browser headers
+        let ws_key = generate_key();
+
+        let mut builder = Request::builder()
+            .method("GET")
+            .uri(url)
+            .header("Host", host)
+            .header("User-Agent", &profile.user_agent)
+            .header("Accept-Language", &profile.accept_language)
+            .header("Connection", "Upgrade")
+            .header("Upgrade", "websocket")
+            .header("Sec-WebSocket-Version", "13")
+            .header("Sec-WebSocket-Key", &ws_key)
+            .header("Origin", format!("https://{host}"));
+
+        if options.permessage_deflate {
+            builder = builder.header("Sec-WebSocket-Extensions", deflate::OFFER);
+        }
+
+        if !options.subprotocols.is_empty() {
+            // tungstenite splits the offered list on bare commas when validating
+            builder = builder.header("Sec-WebSocket-Protocol", options.subprotocols.join(","));
+        }
+
+        let mut cookies: Vec<String> = options.cookies.iter().cloned().collect();
+        if let Some(source) = options.cookie_source {
+            // Reading a browser's cookie store shells out and may block
+            let domain = host.to_string();
+            match tokio::task::spawn_blocking(move || source.get_cookie_header(&domain)).await {
+                Ok(Ok(header)) if !header.is_empty() => cookies.push(header),
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => debug!("No {:?} cookies for {}: {}", source, host, e),
+                Err(e) => debug!("{:?} cookie lookup for {} panicked: {}", source, host, e),
+            }
+        }
+        if !cookies.is_empty() {
+            builder = builder.header("Cookie", cookies.join("; "));
+        }
+
+        for (name, value) in &options.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+
+        let request = builder
+            .body(())
+            .context("Failed to build WebSocket request")?;
+
+        info!("Connecting WebSocket to {}", url);
+
+        let secure = uri.scheme_str() == Some("wss");
+        let port = uri.port_u16().unwrap_or(if secure { 443 } else { 80 });
+        let transport = connect_transport(host, port, secure).await?;
+
+        // The inflate adapter sits between TLS and tungstenite so compressed
+        // frames are expanded before tungstenite validates them
+        let limit = options.max_message_size.unwrap_or(DEFAULT_MAX_MESSAGE_SIZE);
+        let (stream, response) = client_async_with_config(
+            request,
+            InflateStream::new(transport, options.permessage_deflate, limit),
+            Some(WebSocketConfig {
+                max_message_size: Some(limit),
+                max_frame_size: Some(limit),
+                ..WebSocketConfig::default()
+            }),
+        )
+        .await
+        .context("WebSocket connection failed")?;
+
+        debug!("WebSocket connected: {:?}", response.status());
+
+        let protocol = response
+            .headers()
+            .get("Sec-WebSocket-Protocol")
+            .and_then(|v| v.to_str().ok())
+            .map(String::from);
+
+        let deflater = response
+            .headers()
+            .get("Sec-WebSocket-Extensions")
+            .and_then(|v| v.to_str().ok())
+            .filter(|_| options.permessage_deflate)
+            .and_then(DeflateParams::from_response_header)
+            .map(Deflater::new);
+        if deflater.is_some() {
+            debug!("permessage-deflate negotiated");
+        }
+
+        Ok(Self {
+            stream,
+            url: url.to_string(),
+            protocol,
+            deflater,
+        })
+    }
+
+    /// Send a text message
+    pub async fn send_text(&mut self, text: &str) -> Result<()> {
+        let message = match &mut self.deflater {
+            Some(deflater) => compressed_frame(deflater, OpData::Text, text.as_bytes())?,
+            None => Message::Text(text.to_string()),
+        };
+        self.stream
+            .send(message)
+            .await
+            .context("Failed to send text message")?;
+        debug!("Sent text: {} bytes", text.len());
+        Ok(())
+    }
+
+    /// Send a binary message.
+    pub async fn send_binary(&mut self, data: Vec<u8>) -> Result<()> {
+        let len = data.len();
+        let message = match &mut self.deflater {
+            Some(deflater) => compressed_frame(deflater, OpData::Binary, &data)?,
+            None => Message::Binary(data),
+        };
+        self.stream
+            .send(message)
+            .await
+            .context("Failed to send binary message")?;
+        debug!("Sent binary: {} bytes", len);
+        Ok(())
+    }
+
+    /// Whether `permessage-deflate` was negotiated with the server.
+    #[must_use]
+    pub fn is_compressed(&self) -> bool {
+        self.deflater.is_some()
+    }
+
+    /// Send a ping
+    pub async fn ping(&mut self) -> Result<()> {
+        self.stream
+            .send(Message::Ping(vec![]))
+            .await
+            .context("Failed to send ping")?;
+        Ok(())
+    }
+
+    /// Receive the next application-level message.
+    ///
+    /// Ping frames are answered automatically with Pong. Pong and raw
+    /// Frame messages are silently consumed so the caller only sees
+    /// Text, Binary, or Close messages.
+    pub async fn recv(&mut self) -> Result<Option<WebSocketMessage>> {
+        loop {
+            match self.stream.next().await {
+                Some(Ok(msg)) => match msg {
+                    Message::Text(text) => return Ok(Some(WebSocketMessage::Text(text))),
+                    Message::Binary(data) => return Ok(Some(WebSocketMessage::Binary(data))),
+                    Message::Ping(data) => {
+                        // Auto-respond with pong
+                        let _ = self.stream.send(Message::Pong(data)).await;
+                        continue;
+                    }
+                    Message::Pong(_) => {
+                        debug!("Received pong");
+                        continue;
+                    }
+                    Message::Close(frame) => {
+                        info!("WebSocket closed: {:?}", frame);
+                        return Ok(Some(WebSocketMessage::Close));
+                    }
+                    Message::Frame(_) => continue,
+                },
+                Some(Err(e)) => {
+                    if is_too_large(&e) {
+                        // Best effort: the peer may already be gone
+                        let _ = self
+                            .stream
+                            .close(Some(CloseFrame {
+                                code: CloseCode::Size,
+                                reason: "message too big".into(),
+                            }))
+                            .await;
+                    }
+                    return Err(anyhow::Error::new(e).context("WebSocket receive failed"));
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// Receive with timeout
+    pub async fn recv_timeout(&mut self, timeout: Duration) -> Result<Option<WebSocketMessage>> {
+        match tokio::time::timeout(timeout, self.recv()).await {
+            Ok(result) => result,
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Close the connection
+    pub async fn close(&mut self) -> Result<()> {
+        self.stream
+            .close(None)
+            .await
+            .context("Failed to close WebSocket")?;
+        info!("WebSocket closed");
+        Ok(())
+    }
+
+    /// Get the URL
+    #[must_use]
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Subprotocol accepted by the server, if one was negotiated.
+    #[must_use]
+    pub fn protocol(&self) -> Option<&str> {
+        self.protocol.as_deref()
+    }
+}
+
+/// Open the TCP connection, with TLS for `wss://`.
+async fn connect_transport(
+    host: &str,
+    port: u16,
+    secure: bool,
+) -> Result<MaybeTlsStream<TcpStream>> {
+    let tcp = TcpStream::connect(format!("{host}:{port}"))
+        .await
+        .context("WebSocket TCP connection failed")?;
+    let _ = tcp.set_nodelay(true);
+
+    // Connect with TLS using rustls
+    if secure {
+        let config = rustls::ClientConfig::builder()
+            .with_root_certificates({
+                let mut roots = rustls::RootCertStore::empty();
+                let certs = rustls_native_certs::load_native_certs();
+                for cert in certs.certs {
+                    let _ = roots.add(cert);
+                }
+                roots
+            })
+            .with_no_client_auth();
+        let server_name = rustls::pki_types::ServerName::try_from(
+            host.trim_start_matches('[')
+                .trim_end_matches(']')
+                .to_string(),
+        )
+        .context("Invalid TLS server name")?;
+        let tls = tokio_rustls::TlsConnector::from(std::sync::Arc::new(config))
+            .connect(server_name, tcp)
+            .await
+            .context("WebSocket TLS handshake failed")?;
+        Ok(MaybeTlsStream::Rustls(tls))
+    } else {
+        Ok(MaybeTlsStream::Plain(tcp))
+    }
+}
+
+/// Whether a receive error means the server sent an oversized message.
+fn is_too_large(error: &WsError) -> bool {
+    match error {
+        WsError::Capacity(CapacityError::MessageTooLong { .. }) => true,
+        WsError::Io(e) => MessageTooLarge::is(e),
+        _ => false,
+    }
+}
+
+/// Compress a payload into a single final data frame with RSV1 set.
+fn compressed_frame(deflater: &mut Deflater, opcode: OpData, payload: &[u8]) -> Result<Message> {
+    let compressed = deflater
+        .compress(payload)
+        .context("Failed to compress WebSocket message")?;
+    let mut frame = Frame::message(compressed, OpCode::Data(opcode), true);
+    frame.header_mut().rsv1 = true;
+    Ok(Message::Frame(frame))
+}
+
+/// Application-level WebSocket message types.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebSocketMessage {
+    /// UTF-8 text frame.
+    Text(String),
+    /// Binary data frame.
+    Binary(Vec<u8>),
+    /// Connection close frame.
+    Close,
+}
+
+impl WebSocketMessage {
+    /// Returns `true` if this is a [`Text`](WebSocketMessage::Text) message.
+    #[must_use]
+    pub fn is_text(&self) -> bool {
+        matches!(self, Self::Text(_))
+    }
+
+    /// Returns `true` if this is a [`Binary`](WebSocketMessage::Binary) message.
+    #[must_use]
+    pub fn is_binary(&self) -> bool {
+        matches!(self, Self::Binary(_))
+    }
+
+    /// Returns `true` if this is a [`Close`](WebSocketMessage::Close) message.
+    #[must_use]
+    pub fn is_close(&self) -> bool {
+        matches!(self, Self::Close)
+    }
+
+    /// Borrow the text payload, if this is a text message.
+    #[must_use]
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            Self::Text(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Borrow the binary payload, if this is a binary message.
+    #[must_use]
+    pub fn as_binary(&self) -> Option<&[u8]> {
+        match self {
+            Self::Binary(b) => Some(b),
+            _ => None,
+        }
+    }
+}
+
+/// JSON-RPC 2.0 client layered on top of a [`WebSocket`].
+///
+/// Assigns auto-incrementing request IDs and correlates responses by ID
+/// within a caller-supplied timeout.
+pub struct JsonRpcWebSocket {
+    ws: WebSocket,
+    request_id: u64,
+}
+
+impl JsonRpcWebSocket {
+    /// Connect to a JSON-RPC WebSocket endpoint.
+    pub async fn connect(url: &str, profile: &BrowserProfile) -> Result<Self> {
+        let ws = WebSocket::connect(url, profile)
+            .await
+            .context("JSON-RPC WebSocket connection failed")?;
+        Ok(Self { ws, request_id: 0 })
+    }
+
+    /// Send a JSON-RPC 2.0 request and wait for the matching response.
+    ///
+    /// `params` and the `result` are (de)serialized with serde, so callers can
+    /// use their own types; use `serde_json::Value` for untyped access.
+    /// Returns an error if the response contains an `error` field, the result
+    /// does not match `R`, or the timeout expires.
+    pub async fn call<P: serde::Serialize, R: serde::de::DeserializeOwned>(
+        &mut self,
+        method: &str,
+        params: P,
+        timeout: Duration,
+    ) -> Result<R> {
+        self.request_id += 1;
+        let id = self.request_id;
+
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params
+        });
+
+        self.ws
+            .send_text(&request.to_string())
+            .await
+            .with_context(|| format!("Failed to send JSON-RPC request for method '{method}'"))?;
+
+        // Wait for matching response
+        let deadline = tokio::time::Instant::now() + timeout;
+        while tokio::time::Instant::now() < deadline {
+            if let Some(WebSocketMessage::Text(text)) =
+                self.ws.recv_timeout(Duration::from_millis(100)).await?
+            {
+                let response: serde_json::Value =
+                    serde_json::from_str(&text).context("Invalid JSON in JSON-RPC response")?;
+                if response.get("id") == Some(&serde_json::json!(id)) {
+                    let result = response_result(&response)?;
+                    return serde_json::from_value(result).with_context(|| {
+                        format!(
+                            "JSON-RPC result for method '{method}' does not match expected type `{}`",
+                            std::any::type_name::<R>()
+                        )
+                    });
+                }
+            }
+        }
+
+        Err(anyhow::anyhow!(
+            "Timeout ({timeout:?}) waiting for JSON-RPC response to method '{method}'"
+        ))
+    }
+
+    /// Send several calls as one JSON-RPC 2.0 batch array.
+    ///
+    /// Responses are correlated by ID, so the returned results follow the
+    /// order of `calls` even when the server answers out of order or splits
+    /// the batch into individual messages. Each entry fails independently;
+    /// entries still unanswered when the timeout expires become timeout errors.
+    pub async fn call_batch(
+        &mut self,
+        calls: Vec<(&str, serde_json::Value)>,
+        timeout: Duration,
+    ) -> Result<Vec<Result<serde_json::Value>>> {
+        if calls.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut pending = std::collections::HashMap::with_capacity(calls.len());
+        let requests: Vec<serde_json::Value> = calls
+            .iter()
+            .enumerate()
+            .map(|(index, (method, params))| {
+                self.request_id += 1;
+                pending.insert(self.request_id, index);
+                serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": self.request_id,
+                    "method": method,
+                    "params": params
+                })
+            })
+            .collect();
+
+        self.ws
+            .send_text(&serde_json::Value::Array(requests).to_string())
+            .await
+            .context("Failed to send JSON-RPC batch request")?;
+
+        let mut results: Vec<Option<Result<serde_json::Value>>> =
+            calls.iter().map(|_| None).collect();
+
+        let deadline = tokio::time::Instant::now() + timeout;
+        while !pending.is_empty() && tokio::time::Instant::now() < deadline {
+            if let Some(WebSocketMessage::Text(text)) =
+                self.ws.recv_timeout(Duration::from_millis(100)).await?
+            {
+                let response: serde_json::Value =
+                    serde_json::from_str(&text).context("Invalid JSON in JSON-RPC response")?;
+                let entries = match response {
+                    serde_json::Value::Array(entries) => entries,
+                    single => vec![single],
+                };
+                for entry in entries {
+                    let Some(index) = entry
+                        .get("id")
+                        .and_then(serde_json::Value::as_u64)
+                        .and_then(|id| pending.remove(&id))
+                    else {
+                        continue;
+                    };
+                    results[index] = Some(response_result(&entry));
+                }
+            }
+        }
+
+        Ok(results
+            .into_iter()
+            .zip(&calls)
+            .map(|(result, (method, _))| {
+                result.unwrap_or_else(|| {
+                    Err(anyhow::anyhow!(
+                        "Timeout ({timeout:?}) waiting for JSON-RPC response to method '{method}'"
+                    ))
+                })
+            })
+            .collect())
+    }
+
+    /// Close the underlying WebSocket connection.
+    pub async fn close(&mut self) -> Result<()> {
+        self.ws.close().await
+    }
+}
+
+/// Extract the `result` of a single JSON-RPC response object, or its `error`.
+fn response_result(response: &serde_json::Value) -> Result<serde_json::Value> {
+    if let Some(error) = response.get("error") {
+        return Err(anyhow::anyhow!("JSON-RPC error: {error}"));
+    }
+    response
+        .get("result")
+        .cloned()
+        .context("JSON-RPC response has neither result nor error")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fingerprint::chrome_profile;
+
+    // -- WebSocketMessage unit tests (no network) --
+
+    #[test]
+    fn test_message_text_accessors() {
+        let msg = WebSocketMessage::Text("hello".to_string());
+        assert!(msg.is_text());
+        assert!(!msg.is_binary());
+        assert!(!msg.is_close());
+        assert_eq!(msg.as_text(), Some("hello"));
+        assert_eq!(msg.as_binary(), None);
+    }
+
+    #[test]
+    fn test_message_binary_accessors() {
+        let msg = WebSocketMessage::Binary(vec![1, 2, 3]);
+        assert!(!msg.is_text());
+        assert!(msg.is_binary());
+        assert!(!msg.is_close());
+        assert_eq!(msg.as_text(), None);
+        assert_eq!(msg.as_binary(), Some(&[1u8, 2, 3][..]));
+    }
+
+    #[test]
+    fn test_message_close_accessors() {
+        let msg = WebSocketMessage::Close;
+        assert!(!msg.is_text());
+        assert!(!msg.is_binary());
+        assert!(msg.is_close());
+        assert_eq!(msg.as_text(), None);
+        assert_eq!(msg.as_binary(), None);
+    }
+
+    #[test]
+    fn test_message_equality() {
+        assert_eq!(
+            WebSocketMessage::Text("a".into()),
+            WebSocketMessage::Text("a".into())
+        );
+        assert_ne!(
+            WebSocketMessage::Text("a".into()),
+            WebSocketMessage::Text("b".into())
+        );
+        assert_ne!(
+            WebSocketMessage::Text("a".into()),
+            WebSocketMessage::Binary(b"a".to_vec())
+        );
+        assert_eq!(WebSocketMessage::Close, WebSocketMessage::Close);
+    }
+
+    #[test]
+    fn test_message_clone() {
+        let msg = WebSocketMessage::Binary(vec![42; 100]);
+        let cloned = msg.clone();
+        assert_eq!(msg, cloned);
+    }
+
+    #[test]
+    fn test_message_debug() {
+        let msg = WebSocketMessage::Text("test".to_string());
+        let debug = format!("{msg:?}");
+        assert!(debug.contains("Text"));
+        assert!(debug.contains("test"));
+    }
+
+    // -- Mock server tests (localhost, no external network) --
+
+    /// Spawn a single-connection WebSocket server on localhost that replies to
+    /// each text message with `respond(text)`. Returns the `ws://` URL.
+    async fn spawn_mock_server(respond: fn(&str) -> String) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = ws.next().await {
+                if ws.send(Message::Text(respond(&text))).await.is_err() {
+                    break;
+                }
+            }
+        });
+        format!("ws://{addr}")
+    }
+
+    /// Build a profile off the async runtime: the first call may refresh
+    /// browser versions using a blocking HTTP client.
+    async fn test_profile() -> BrowserProfile {
+        tokio::task::spawn_blocking(chrome_profile).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_call_batch_correlates_out_of_order_responses() {
+        // Answer each request with its own method name, in reverse order,
+        // failing any call to "boom".
+        let url = spawn_mock_server(|text| {
+            let batch: Vec<serde_json::Value> = serde_json::from_str(text).unwrap();
+            let responses: Vec<serde_json::Value> = batch
+                .iter()
+                .rev()
+                .map(|req| {
+                    if req["method"] == "boom" {
+                        serde_json::json!({
+                            "jsonrpc": "2.0",
+                            "id": req["id"],
+                            "error": {"code": -32000, "message": "boom"}
+                        })
+                    } else {
+                        serde_json::json!({"jsonrpc": "2.0", "id": req["id"], "result": req["method"]})
+                    }
+                })
+                .collect();
+            serde_json::Value::Array(responses).to_string()
+        })
+        .await;
+
+        let mut rpc = JsonRpcWebSocket::connect(&url, &test_profile().await)
+            .await
+            .unwrap();
+        let results = rpc
+            .call_batch(
+                vec![
+                    ("first", serde_json::json!([])),
+                    ("boom", serde_json::json!([])),
+                    ("third", serde_json::json!({"x": 1})),
+                ],
+                Duration::from_secs(5),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap(), "first");
+        assert!(results[1]
+            .as_ref()
+            .unwrap_err()
+            .to_string()
+            .contains("boom"));
+        assert_eq!(results[2].as_ref().unwrap(), "third");
+    }
+
+    #[derive(Debug, serde::Deserialize, PartialEq)]
+    struct BlockInfo {
+        number: u64,
+        hash: String,
+    }
+
+    /// Reply to any request with a fixed block object as its result.
+    fn block_responder(text: &str) -> String {
+        let request: serde_json::Value = serde_json::from_str(text).unwrap();
+        serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "result": {"number": 42, "hash": "0xabc"}
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_call_deserializes_typed_result() {
+        let url = spawn_mock_server(block_responder).await;
+        let mut rpc = JsonRpcWebSocket::connect(&url, &test_profile().await)
+            .await
+            .unwrap();
+
+        let block: BlockInfo = rpc
+            .call("getBlock", ["latest"], Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(
+            block,
+            BlockInfo {
+                number: 42,
+                hash: "0xabc".to_string()
+            }
+        );
+
+        // The untyped API still works through `serde_json::Value`
+        let raw: serde_json::Value = rpc
+            .call("getBlock", serde_json::json!([]), Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(raw["number"], 42);
+    }
+
+    #[tokio::test]
+    async fn test_call_type_mismatch_is_an_error() {
+        let url = spawn_mock_server(block_responder).await;
+        let mut rpc = JsonRpcWebSocket::connect(&url, &test_profile().await)
+            .await
+            .unwrap();
+
+        let err = rpc
+            .call::<_, Vec<String>>("getBlock", (), Duration::from_secs(5))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("does not match expected type"));
+    }
+
+    /// Spawn a server that only completes the handshake when the client offers
+    /// the `graphql-transport-ws` subprotocol and sends the expected token.
+    #[allow(clippy::result_large_err)] // tungstenite's handshake callback signature
+    async fn spawn_protocol_server() -> String {
+        use tokio_tungstenite::tungstenite::handshake::server::{
+            ErrorResponse, Request as HsRequest, Response as HsResponse,
+        };
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((tcp, _)) = listener.accept().await {
+                let callback = |req: &HsRequest, mut resp: HsResponse| {
+                    let offered = req
+                        .headers()
+                        .get("Sec-WebSocket-Protocol")
+                        .and_then(|v| v.to_str().ok())
+                        .unwrap_or_default();
+                    let authorized = req
+                        .headers()
+                        .get("Authorization")
+                        .is_some_and(|v| v == "Bearer token");
+                    if authorized && offered.split(',').any(|p| p == "graphql-transport-ws") {
+                        resp.headers_mut().insert(
+                            "Sec-WebSocket-Protocol",
+                            "graphql-transport-ws".parse().unwrap(),
+                        );
+                        Ok(resp)
+                    } else {
+                        let mut err = ErrorResponse::new(Some("subprotocol required".into()));
+                        *err.status_mut() =
+                            tokio_tungstenite::tungstenite::http::StatusCode::BAD_REQUEST;
+                        Err(err)
+                    }
+                };
+                let _ = tokio_tungstenite::accept_hdr_async(tcp, callback).await;
+            }
+        });
+        format!("ws://{addr}")
+    }
+
+    #[tokio::test]
+    async fn test_connect_with_negotiates_subprotocol() {
+        let url = spawn_protocol_server().await;
+        let profile = test_profile().await;
+
+        let options = WebSocketOptions::new()
+            .with_subprotocol("graphql-ws")
+            .with_subprotocol("graphql-transport-ws")
+            .with_header("Authorization", "Bearer token");
+        let ws = WebSocket::connect_with(&url, &profile, &options)
+            .await
+            .unwrap();
+        assert_eq!(ws.protocol(), Some("graphql-transport-ws"));
+
+        let without_protocol = WebSocketOptions::new().with_header("Authorization", "Bearer token");
+        assert!(WebSocket::connect_with(&url, &profile, &without_protocol)
+            .await
+            .is_err());
+    }
+
+    /// Raw WebSocket server that optionally accepts `permessage-deflate` and
+    /// echoes one message back (compressed when negotiated). Reports whether
+    /// the client's frame arrived with the RSV1 "compressed" bit set, then
+    /// the status code of the client's close frame, if it sends one.
+    async fn spawn_deflate_echo_server(
+        accept_deflate: bool,
+    ) -> (
+        String,
+        tokio::sync::oneshot::Receiver<bool>,
+        tokio::sync::oneshot::Receiver<Option<u16>>,
+    ) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let (close_tx, close_rx) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let (mut tcp, _) = listener.accept().await.unwrap();
+
+            let mut request = Vec::new();
+            while !request.ends_with(b"\r\n\r\n") {
+                let mut byte = [0u8; 1];
+                tcp.read_exact(&mut byte).await.unwrap();
+                request.push(byte[0]);
+            }
+            let request = String::from_utf8(request).unwrap();
+            let header = |name: &str| {
+                request
+                    .lines()
+                    .filter_map(|l| l.split_once(':'))
+                    .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                    .map(|(_, v)| v.trim().to_string())
+            };
+            let key = header("Sec-WebSocket-Key").unwrap();
+            let offered = header("Sec-WebSocket-Extensions").unwrap_or_default();
+            let negotiated = accept_deflate && offered.contains("permessage-deflate");
+
+            let mut response = format!(
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\
+                 Connection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n",
+                derive_accept_key(key.as_bytes())
+            );
+            if negotiated {
+                response.push_str("Sec-WebSocket-Extensions: permessage-deflate\r\n");
+            }
+            response.push_str("\r\n");
+            tcp.write_all(response.as_bytes()).await.unwrap();
+
+            // Read one masked client frame (payload < 126 bytes)
+            let mut head = [0u8; 2];
+            tcp.read_exact(&mut head).await.unwrap();
+            let rsv1 = head[0] & 0x40 != 0;
+            let mut mask = [0u8; 4];
+            tcp.read_exact(&mut mask).await.unwrap();
+            let mut payload = vec![0u8; usize::from(head[1] & 0x7f)];
+            tcp.read_exact(&mut payload).await.unwrap();
+            for (i, b) in payload.iter_mut().enumerate() {
+                *b ^= mask[i % 4];
+            }
+
+            let params = deflate::DeflateParams::default();
+            let text = if rsv1 {
+                deflate::Inflater::new(params)
+                    .decompress(&payload, usize::MAX)
+                    .unwrap()
+            } else {
+                payload
+            };
+            let _ = tx.send(rsv1);
+
+            // Echo back, compressed when negotiated
+            let (first_byte, body) = if negotiated {
+                (
+                    0xC1,
+                    deflate::Deflater::new(params).compress(&text).unwrap(),
+                )
+            } else {
+                (0x81, text)
+            };
+            let mut frame = vec![first_byte, u8::try_from(body.len()).unwrap()];
+            frame.extend_from_slice(&body);
+            tcp.write_all(&frame).await.unwrap();
+
+            // Keep the connection open until the client has read the echo;
+            // a masked close frame carries its status in the first two bytes
+            let mut close = [0u8; 8];
+            let code = match tcp.read_exact(&mut close).await {
+                Ok(_) if close[0] & 0x0f == 0x8 => Some(u16::from_be_bytes([
+                    close[2] ^ close[6],
+                    close[3] ^ close[7],
+                ])),
+                _ => None,
+            };
+            let _ = close_tx.send(code);
+        });
+        (format!("ws://{addr}"), rx, close_rx)
+    }
+
+    #[tokio::test]
+    async fn test_permessage_deflate_roundtrip() {
+        let (url, rsv1, _) = spawn_deflate_echo_server(true).await;
+        let options = WebSocketOptions::new().with_compression();
+        let mut ws = WebSocket::connect_with(&url, &test_profile().await, &options)
+            .await
+            .unwrap();
+        assert!(ws.is_compressed());
+
+        let message = r#"{"jsonrpc":"2.0","id":1,"method":"ping","params":["aaaaaaaaaaaaaaaa"]}"#;
+        ws.send_text(message).await.unwrap();
+        assert!(rsv1.await.unwrap(), "client frame should be compressed");
+
+        let echoed = ws.recv_timeout(Duration::from_secs(5)).await.unwrap();
+        assert_eq!(echoed, Some(WebSocketMessage::Text(message.to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_permessage_deflate_declined_falls_back() {
+        let (url, rsv1, _) = spawn_deflate_echo_server(false).await;
+        let options = WebSocketOptions::new().with_compression();
+        let mut ws = WebSocket::connect_with(&url, &test_profile().await, &options)
+            .await
+            .unwrap();
+        assert!(!ws.is_compressed());
+
+        ws.send_text("plain").await.unwrap();
+        assert!(!rsv1.await.unwrap(), "client frame should be uncompressed");
+
+        let echoed = ws.recv_timeout(Duration::from_secs(5)).await.unwrap();
+        assert_eq!(echoed, Some(WebSocketMessage::Text("plain".to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_oversized_inflated_message_closes_with_1009() {
+        let (url, _, close_code) = spawn_deflate_echo_server(true).await;
+        let options = WebSocketOptions::new()
+            .with_compression()
+            .with_max_message_size(1000);
+        let mut ws = WebSocket::connect_with(&url, &test_profile().await, &options)
+            .await
+            .unwrap();
+
+        // Compresses to a few bytes; the echo inflates past the limit
+        ws.send_text(&"a".repeat(5000)).await.unwrap();
+        let err = ws.recv_timeout(Duration::from_secs(5)).await.unwrap_err();
+        assert!(format!("{err:#}").contains("exceeds 1000 bytes"), "{err:#}");
+        assert_eq!(close_code.await.unwrap(), Some(1009));
+    }
+
+    // -- Integration test (network-dependent) --
+
+    #[tokio::test]
+    async fn test_websocket_echo() {
+        // Install crypto provider for rustls
+        let _ = rustls::crypto::ring::default_provider().install_default();
+
+        // Use a public echo server
+        let profile = chrome_profile();
+        let result = WebSocket::connect("wss://echo.websocket.org", &profile).await;
+
+        // This test may fail if the echo server is down
+        if let Ok(mut ws) = result {
+            ws.send_text("Hello, WebSocket!").await.unwrap();
+            if let Ok(Some(msg)) = ws.recv_timeout(Duration::from_secs(5)).await {
+                assert!(msg.is_text());
+                println!("Echo: {:?}", msg.as_text());
+            }
+            let _ = ws.close().await;
+        } else {
+            println!("Echo server unavailable, skipping test");
+        }
+    }
+}
@@ -392,3 +392,61 @@ fn fetch_post_with_data() {
         // httpbin echoes posted data; the json field will contain parsed key/value
         .stdout(predicate::str::contains(r#""key": "value""#));
 }
+
+// ─── Dry run (offline) ──────────────────────────────────────────────────────
+
+#[test]
+fn fetch_dry_run_prints_the_assembled_request() {
+    // Port 9 (discard) is never contacted: --dry-run exits before sending
+    nab()
+        .args([
+            "fetch",
+            "--dry-run",
+            "--cookies",
+            "none",
+            "--user-agent",
+            "TestAgent/1.0",
+            "--add-header",
+            "Cookie: sid=abc123",
+            "-X",
+            "POST",
+            "-d",
+            r#"{"q":1}"#,
+            "http://127.0.0.1:9/api",
+        ])
+        .timeout(std::time::Duration::from_secs(30))
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "POST http://127.0.0.1:9/api HTTP/1.1\nhost: 127.0.0.1:9\n",
+        ))
+        .stdout(predicate::str::contains("\nuser-agent: TestAgent/1.0\n"))
+        .stdout(predicate::str::contains("\ncookie: sid=abc123\n"))
+        .stdout(predicate::str::contains("\naccept-encoding: "))
+        .stdout(predicate::str::ends_with("\n\n{\"q\":1}\n"));
+}
+
+#[test]
+fn fetch_dry_run_range_replaces_accept_encoding() {
+    let output = nab()
+        .args([
+            "fetch",
+            "--dry-run",
+            "--cookies",
+            "none",
+            "--range",
+            "0-99",
+            "http://127.0.0.1:9/file",
+        ])
+        .timeout(std::time::Duration::from_secs(30))
+        .output()
+        .expect("nab should run");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let encodings: Vec<&str> = stdout
+        .lines()
+        .filter(|line| line.starts_with("accept-encoding:"))
+        .collect();
+    assert_eq!(encodings, ["accept-encoding: identity"], "{stdout}");
+    assert!(stdout.contains("\nrange: bytes=0-99\n"), "{stdout}");
+}
@@ -0,0 +1,43 @@
+//! TLS version pinning and certificate verification against public test endpoints
+//!
+//! Run with: `cargo test --test tls_pinning -- --ignored` (requires network)
+
+use nab::{random_profile, AcceleratedClient, TlsOptions};
+
+#[tokio::test]
+#[ignore = "requires network access to badssl.com"]
+async fn tls13_only_fails_against_tls12_only_endpoint() {
+    let profile = tokio::task::spawn_blocking(random_profile).await.unwrap();
+    let client = AcceleratedClient::with_tls(profile, TlsOptions::tls13_only()).unwrap();
+
+    let err = client
+        .fetch("https://tls-v1-2.badssl.com:1012/")
+        .await
+        .expect_err("TLS 1.2-only endpoint must not negotiate TLS 1.3");
+
+    let message = format!("{err:#}");
+    assert!(
+        message.contains("TLS handshake failed") && message.contains("TLS 1.3 or newer"),
+        "unexpected error: {message}"
+    );
+}
+
+#[tokio::test]
+#[ignore = "requires network access to badssl.com"]
+async fn self_signed_endpoint_requires_insecure() {
+    let url = "https://self-signed.badssl.com/";
+    let client = |tls: TlsOptions| tls.apply(reqwest::Client::builder()).build().unwrap();
+
+    client(TlsOptions::default())
+        .get(url)
+        .send()
+        .await
+        .expect_err("self-signed certificate must be rejected by default");
+
+    let response = client(TlsOptions::default().with_insecure(true))
+        .get(url)
+        .send()
+        .await
+        .expect("--insecure must accept a self-signed certificate");
+    assert!(response.status().is_success());
+}